@@ -0,0 +1,178 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::events;
+use crate::types::A11yPrefs;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The OS accessibility preferences last read, so windows only hear about changes
+pub struct A11yState {
+    pub prefs: Arc<Mutex<Option<A11yPrefs>>>,
+}
+
+impl A11yState {
+    pub fn new() -> Self {
+        Self {
+            prefs: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Keep `next`, returning whether it differs from what was known before
+    fn update(&self, next: A11yPrefs) -> bool {
+        match self.prefs.lock() {
+            Ok(mut prefs) => prefs.replace(next).is_some_and(|previous| previous != next),
+            Err(_) => false,
+        }
+    }
+}
+
+/// Read the preferences now, off the async runtime since some platforms ask a CLI tool
+pub async fn current(app: &AppHandle) -> A11yPrefs {
+    let prefs = tauri::async_runtime::spawn_blocking(read_prefs)
+        .await
+        .unwrap_or_default();
+    if app.state::<A11yState>().update(prefs) {
+        announce(app, prefs);
+    }
+    prefs
+}
+
+/// Poll the OS accessibility preferences and emit "a11y-prefs-changed" when they change
+pub async fn monitor(app: AppHandle) {
+    loop {
+        current(&app).await;
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+fn announce(app: &AppHandle, prefs: A11yPrefs) {
+    events::record_state(app, "a11y-prefs-changed", prefs);
+    let _ = app.emit("a11y-prefs-changed", prefs);
+}
+
+fn read_prefs() -> A11yPrefs {
+    A11yPrefs {
+        reduced_motion: reduced_motion().unwrap_or(false),
+        high_contrast: high_contrast().unwrap_or(false),
+    }
+}
+
+/// "Show animations in Windows" turned off
+#[cfg(target_os = "windows")]
+fn reduced_motion() -> Option<bool> {
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        SystemParametersInfoW, SPI_GETCLIENTAREAANIMATION,
+    };
+
+    let mut animations = 0i32;
+    // SAFETY: SPI_GETCLIENTAREAANIMATION writes a BOOL to the pointer, which is ours.
+    let ok = unsafe {
+        SystemParametersInfoW(
+            SPI_GETCLIENTAREAANIMATION,
+            0,
+            (&mut animations as *mut i32).cast(),
+            0,
+        )
+    };
+    (ok != 0).then_some(animations == 0)
+}
+
+#[cfg(target_os = "windows")]
+fn high_contrast() -> Option<bool> {
+    use windows_sys::Win32::UI::Accessibility::{HCF_HIGHCONTRASTON, HIGHCONTRASTW};
+    use windows_sys::Win32::UI::WindowsAndMessaging::{SystemParametersInfoW, SPI_GETHIGHCONTRAST};
+
+    let mut contrast = HIGHCONTRASTW {
+        cbSize: std::mem::size_of::<HIGHCONTRASTW>() as u32,
+        dwFlags: 0,
+        lpszDefaultScheme: std::ptr::null_mut(),
+    };
+    // SAFETY: `contrast` is a properly sized HIGHCONTRASTW owned by this frame.
+    let ok = unsafe {
+        SystemParametersInfoW(
+            SPI_GETHIGHCONTRAST,
+            contrast.cbSize,
+            (&mut contrast as *mut HIGHCONTRASTW).cast(),
+            0,
+        )
+    };
+    (ok != 0).then_some(contrast.dwFlags & HCF_HIGHCONTRASTON != 0)
+}
+
+/// Accessibility > Display > Reduce motion
+#[cfg(target_os = "macos")]
+fn reduced_motion() -> Option<bool> {
+    universal_access_flag("reduceMotion")
+}
+
+/// Accessibility > Display > Increase contrast
+#[cfg(target_os = "macos")]
+fn high_contrast() -> Option<bool> {
+    universal_access_flag("increaseContrast")
+}
+
+#[cfg(target_os = "macos")]
+fn universal_access_flag(key: &str) -> Option<bool> {
+    let output = std::process::Command::new("defaults")
+        .args(["read", "com.apple.universalaccess", key])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    match String::from_utf8_lossy(&output.stdout).trim() {
+        "1" => Some(true),
+        "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// GNOME's "Reduce animation" switch, which other GTK desktops honour too
+#[cfg(target_os = "linux")]
+fn reduced_motion() -> Option<bool> {
+    gsettings_bool("org.gnome.desktop.interface", "enable-animations").map(|on| !on)
+}
+
+/// GNOME's high-contrast switch, or a high-contrast GTK theme picked by hand
+#[cfg(target_os = "linux")]
+fn high_contrast() -> Option<bool> {
+    if gsettings_bool("org.gnome.desktop.a11y.interface", "high-contrast") == Some(true) {
+        return Some(true);
+    }
+    let theme = gsettings("org.gnome.desktop.interface", "gtk-theme")?;
+    Some(theme.to_ascii_lowercase().contains("highcontrast"))
+}
+
+#[cfg(target_os = "linux")]
+fn gsettings(schema: &str, key: &str) -> Option<String> {
+    let output = std::process::Command::new("gsettings")
+        .args(["get", schema, key])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(target_os = "linux")]
+fn gsettings_bool(schema: &str, key: &str) -> Option<bool> {
+    match gsettings(schema, key)?.as_str() {
+        "true" => Some(true),
+        "false" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+fn reduced_motion() -> Option<bool> {
+    None
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+fn high_contrast() -> Option<bool> {
+    None
+}
@@ -0,0 +1,153 @@
+use std::str::FromStr;
+
+use tauri::{AppHandle, Emitter, Manager, WebviewUrl, WebviewWindowBuilder};
+
+use crate::shutdown;
+use crate::types::ScrollDirection;
+
+/// Keyboard-addressable actions for the popup.
+/// Window-level actions run in Rust; actions touching popup state (session, recording)
+/// are forwarded to the popup as a "popup-action" event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PopupAction {
+    Focus,
+    Hide,
+    ToggleVisibility,
+    OpenSettings,
+    OpenHistory,
+    NewSession,
+    ToggleRecording,
+    ToggleDictation,
+    ResendLast,
+    Scroll(ScrollDirection),
+    Quit,
+}
+
+impl FromStr for PopupAction {
+    type Err = String;
+
+    fn from_str(action_id: &str) -> Result<Self, Self::Err> {
+        match action_id {
+            "focus" => Ok(Self::Focus),
+            "hide" => Ok(Self::Hide),
+            "toggle-visibility" => Ok(Self::ToggleVisibility),
+            "open-settings" => Ok(Self::OpenSettings),
+            "open-history" => Ok(Self::OpenHistory),
+            "new-session" => Ok(Self::NewSession),
+            "toggle-recording" => Ok(Self::ToggleRecording),
+            "toggle-dictation" => Ok(Self::ToggleDictation),
+            "resend-last" => Ok(Self::ResendLast),
+            "scroll-up" => Ok(Self::Scroll(ScrollDirection::Up)),
+            "scroll-down" => Ok(Self::Scroll(ScrollDirection::Down)),
+            "page-up" => Ok(Self::Scroll(ScrollDirection::PageUp)),
+            "page-down" => Ok(Self::Scroll(ScrollDirection::PageDown)),
+            "scroll-top" => Ok(Self::Scroll(ScrollDirection::Top)),
+            "scroll-bottom" => Ok(Self::Scroll(ScrollDirection::Bottom)),
+            "quit" => Ok(Self::Quit),
+            other => Err(format!("Unknown action '{other}'")),
+        }
+    }
+}
+
+/// Show the popup and give it keyboard focus
+pub fn focus_popup(app: &AppHandle) -> Result<(), String> {
+    let window = app
+        .get_webview_window("popup")
+        .ok_or_else(|| "Popup window not found".to_string())?;
+    window.show().map_err(|e| e.to_string())?;
+    window.set_focus().map_err(|e| e.to_string())
+}
+
+/// Pinned, the popup is a borderless always-on-top panel outside the taskbar;
+/// unpinned, it is an ordinary resizable window
+pub fn set_popup_pinned(app: &AppHandle, pinned: bool) -> Result<(), String> {
+    let Some(window) = app.get_webview_window("popup") else {
+        return Ok(());
+    };
+    window
+        .set_always_on_top(pinned)
+        .and_then(|_| window.set_decorations(!pinned))
+        .and_then(|_| window.set_skip_taskbar(pinned))
+        .and_then(|_| window.set_resizable(!pinned))
+        .map_err(|e| e.to_string())
+}
+
+/// Ask the popup to scroll its conversation history
+pub fn scroll_history(app: &AppHandle, direction: ScrollDirection) -> Result<(), String> {
+    app.emit_to("popup", "popup-scroll", direction)
+        .map_err(|e| format!("Failed to emit scroll event: {e}"))
+}
+
+/// Open the settings window, or focus it if it already exists
+pub fn open_settings_window(app: &AppHandle) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window("settings") {
+        window.show().map_err(|e| e.to_string())?;
+        window.set_focus().map_err(|e| e.to_string())?;
+        return Ok(());
+    }
+
+    WebviewWindowBuilder::new(app, "settings", WebviewUrl::App("settings.html".into()))
+        .title("OpenClaw Settings")
+        .inner_size(500.0, 600.0)
+        .center()
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Open the history window, or focus it if it already exists
+pub fn open_history_window(app: &AppHandle) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window("history") {
+        window.show().map_err(|e| e.to_string())?;
+        window.set_focus().map_err(|e| e.to_string())?;
+        return Ok(());
+    }
+
+    WebviewWindowBuilder::new(app, "history", WebviewUrl::App("history.html".into()))
+        .title("OpenClaw History")
+        .inner_size(720.0, 560.0)
+        .center()
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Dispatch a keyboard action by id
+pub fn invoke_action(app: &AppHandle, action_id: &str) -> Result<(), String> {
+    let action: PopupAction = action_id.parse()?;
+
+    match action {
+        PopupAction::Focus => focus_popup(app),
+        PopupAction::Hide => {
+            if let Some(window) = app.get_webview_window("popup") {
+                window.hide().map_err(|e| e.to_string())?;
+            }
+            Ok(())
+        }
+        PopupAction::ToggleVisibility => {
+            let window = app
+                .get_webview_window("popup")
+                .ok_or_else(|| "Popup window not found".to_string())?;
+            if window.is_visible().unwrap_or(false) {
+                window.hide().map_err(|e| e.to_string())
+            } else {
+                focus_popup(app)
+            }
+        }
+        PopupAction::OpenSettings => open_settings_window(app),
+        PopupAction::OpenHistory => open_history_window(app),
+        PopupAction::Scroll(direction) => scroll_history(app, direction),
+        PopupAction::NewSession
+        | PopupAction::ToggleRecording
+        | PopupAction::ToggleDictation
+        | PopupAction::ResendLast => app
+            .emit_to("popup", "popup-action", action_id)
+            .map_err(|e| format!("Failed to emit action event: {e}")),
+        PopupAction::Quit => {
+            shutdown::quit(app);
+            Ok(())
+        }
+    }
+}
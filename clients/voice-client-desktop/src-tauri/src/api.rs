@@ -1,25 +1,193 @@
-use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
-use reqwest::Client;
+use std::time::Duration;
+
 use futures_util::StreamExt;
-use tauri::{AppHandle, Emitter};
+use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+use reqwest::{Body, Client, RequestBuilder, Response};
+use tauri::{AppHandle, Manager, Runtime};
+use tokio::time::Instant;
+use url::Url;
 
+use crate::brief::{self, BriefCap, BriefState};
+use crate::context;
+use crate::error::AppError;
+use crate::events;
+use crate::http_log::HttpLog;
+use crate::routing::{Endpoint, Routes, RoutingState};
 use crate::sse::SseParser;
-use crate::types::{ConnectionResult, CreateSessionRequest, SessionResponse};
+use crate::stt::Utterance;
+use crate::types::{
+    ConnectionResult, CreateSessionRequest, FeedbackRequest, RemoteHistoryMatch,
+    RemoteHistorySearch, RemoteTranscript, SessionInfo, SessionResponse, TurnContext, VoiceEvent,
+};
+use crate::watchdog::WatchdogState;
+
+const MAX_SESSION_ID_LEN: usize = 128;
+const MAX_LANGUAGE_TAG_LEN: usize = 35;
+const MAX_WORKSPACE_LEN: usize = 64;
+
+/// Names the workspace on every request, for gateways serving several teams
+pub const WORKSPACE_HEADER: &str = "X-Workspace";
+
+/// Configuration shared by every gateway call, snapshotted from managed state
+pub struct Gateway {
+    pub routes: Routes,
+    pub http_log: HttpLog,
+    /// Sent as `X-Workspace` on every request
+    pub workspace: Option<String>,
+    /// Longest a streamed reply may take to finish once the gateway has answered;
+    /// None waits as long as the gateway keeps the stream open
+    pub response_timeout: Option<Duration>,
+    /// Sentences replies are cut after in brief mode; None while it is off
+    pub brief_sentences: Option<usize>,
+}
+
+impl Gateway {
+    pub fn from_app<R: Runtime>(app: &tauri::AppHandle<R>) -> Self {
+        let routing = app.state::<RoutingState>();
+        Self {
+            routes: routing.get(),
+            http_log: app.state::<HttpLog>().inner().clone(),
+            workspace: routing.workspace(),
+            response_timeout: app
+                .try_state::<WatchdogState>()
+                .and_then(|watchdog| watchdog.response_limit()),
+            brief_sentences: app
+                .try_state::<BriefState>()
+                .and_then(|brief| brief.limit()),
+        }
+    }
+
+    /// Send a request, tracing it to the HTTP log when enabled
+    pub async fn execute(&self, request: RequestBuilder) -> Result<Response, reqwest::Error> {
+        let request = match &self.workspace {
+            Some(workspace) => request.header(WORKSPACE_HEADER, workspace.as_str()),
+            None => request,
+        };
+        let (client, request) = request.build_split();
+        let request = request?;
+        let trace = self.http_log.request(&request);
+        let result = client.execute(request).await;
+        self.http_log.response(trace, &result);
+        result
+    }
+}
+
+/// Parse the configured gateway URL, normalising surrounding whitespace and trailing slashes
+pub fn parse_base_url(base_url: &str) -> Result<Url, AppError> {
+    let trimmed = base_url.trim();
+    if trimmed.is_empty() {
+        return Err(AppError::InvalidGatewayUrl(
+            "no gateway URL configured".to_string(),
+        ));
+    }
+
+    let mut url = Url::parse(trimmed).map_err(|e| {
+        if e == url::ParseError::RelativeUrlWithoutBase {
+            AppError::InvalidGatewayUrl(format!("'{trimmed}' is missing http:// or https://"))
+        } else {
+            AppError::InvalidGatewayUrl(format!("'{trimmed}': {e}"))
+        }
+    })?;
+
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(AppError::InvalidGatewayUrl(format!(
+            "'{trimmed}' must use http or https, not {}",
+            url.scheme()
+        )));
+    }
+    if url.query().is_some() || url.fragment().is_some() {
+        return Err(AppError::InvalidGatewayUrl(format!(
+            "'{trimmed}' must not contain a query string or fragment"
+        )));
+    }
+
+    let path = url.path().trim_end_matches('/').to_string();
+    url.set_path(&path);
+    Ok(url)
+}
+
+/// Reject session ids that can't have come from the gateway before they reach a request
+fn validate_session_id(session_id: &str) -> Result<(), AppError> {
+    if session_id.is_empty() {
+        return Err(AppError::Other("Session id is empty".to_string()));
+    }
+    if session_id.len() > MAX_SESSION_ID_LEN
+        || session_id
+            .chars()
+            .any(|c| c.is_control() || c.is_whitespace())
+    {
+        return Err(AppError::Other(format!(
+            "Invalid session id '{}'",
+            session_id.escape_debug()
+        )));
+    }
+    Ok(())
+}
+
+/// Check a reply language is a plausible BCP 47 tag (`de`, `pt-BR`, `zh-Hant`)
+pub fn validate_language_tag(tag: &str) -> Result<(), String> {
+    let mut subtags = tag.split('-');
+    let primary = subtags.next().unwrap_or_default();
+    let primary_ok =
+        (2..=3).contains(&primary.len()) && primary.chars().all(|c| c.is_ascii_alphabetic());
+    let rest_ok =
+        subtags.all(|s| (1..=8).contains(&s.len()) && s.chars().all(|c| c.is_ascii_alphanumeric()));
+    if primary_ok && rest_ok && tag.len() <= MAX_LANGUAGE_TAG_LEN {
+        Ok(())
+    } else {
+        Err(format!(
+            "'{}' is not a language tag like 'en' or 'pt-BR'",
+            tag.escape_debug()
+        ))
+    }
+}
+
+/// Check a workspace name can go in a header and looks like one (`design`, `team-2`)
+pub fn validate_workspace(workspace: &str) -> Result<(), String> {
+    let valid = !workspace.is_empty()
+        && workspace.len() <= MAX_WORKSPACE_LEN
+        && workspace
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "'{}' is not a workspace name like 'design' or 'team-2'",
+            workspace.escape_debug()
+        ))
+    }
+}
 
 /// Test connection to the gateway by hitting GET /profiles
-pub async fn test_connection(base_url: &str) -> Result<ConnectionResult, String> {
+pub async fn test_connection(
+    gateway: &Gateway,
+    base_url: &str,
+) -> Result<ConnectionResult, String> {
     let client = Client::new();
-    let url = format!("{base_url}/profiles");
+    let url = match gateway.routes.url(base_url, Endpoint::Profiles, &[]) {
+        Ok(url) => url,
+        Err(e) => {
+            return Ok(ConnectionResult {
+                success: false,
+                error: Some(e.to_string()),
+            })
+        }
+    };
 
-    match client.get(&url).send().await {
+    match gateway.execute(client.get(url)).await {
         Ok(resp) if resp.status().is_success() => Ok(ConnectionResult {
             success: true,
             error: None,
         }),
-        Ok(resp) => Ok(ConnectionResult {
-            success: false,
-            error: Some(format!("Server returned status {}", resp.status())),
-        }),
+        Ok(resp) => {
+            let status = resp.status();
+            let text = resp.text().await.unwrap_or_default();
+            Ok(ConnectionResult {
+                success: false,
+                error: Some(AppError::from_gateway_response(status, &text).to_string()),
+            })
+        }
         Err(e) => Ok(ConnectionResult {
             success: false,
             error: Some(format!("Connection failed: {e}")),
@@ -29,97 +197,444 @@ pub async fn test_connection(base_url: &str) -> Result<ConnectionResult, String>
 
 /// Create a new voice session via POST /session/new
 pub async fn create_session(
+    gateway: &Gateway,
     base_url: &str,
     profile_name: &str,
-) -> Result<SessionResponse, String> {
+) -> Result<SessionResponse, AppError> {
     let client = Client::new();
-    let url = format!("{base_url}/session/new");
+    let url = gateway.routes.url(base_url, Endpoint::SessionNew, &[])?;
 
     let body = CreateSessionRequest {
         profile_name: profile_name.to_string(),
     };
 
-    let resp = client
-        .post(&url)
-        .json(&body)
-        .send()
+    let resp = gateway
+        .execute(client.post(url).json(&body))
         .await
-        .map_err(|e| format!("Failed to create session: {e}"))?;
+        .map_err(|e| AppError::Network(format!("Failed to create session: {e}")))?;
 
     if !resp.status().is_success() {
         let status = resp.status();
         let text = resp.text().await.unwrap_or_default();
-        return Err(format!("Session creation failed ({status}): {text}"));
+        return Err(AppError::from_gateway_response(status, &text));
     }
 
     resp.json::<SessionResponse>()
         .await
-        .map_err(|e| format!("Failed to parse session response: {e}"))
+        .map_err(|e| AppError::Other(format!("Failed to parse session response: {e}")))
 }
 
-/// Send WAV audio bytes to the gateway and stream SSE events back via Tauri events.
-///
-/// POST {base_url}/audio?sessionId={session_id}
-/// Headers: X-Profile, Content-Type: audio/wav, X-Session-Key (optional)
-/// Body: raw WAV bytes
-/// Events are emitted as "voice-event" to all webview windows.
-pub async fn send_audio_streaming(
-    app: &AppHandle,
+/// Look up a session via GET /session?id= — returns None if the gateway doesn't know it
+pub async fn get_session(
+    gateway: &Gateway,
     base_url: &str,
     session_id: &str,
-    profile_name: &str,
-    session_key: Option<&str>,
-    wav_bytes: Vec<u8>,
-) -> Result<(), String> {
+) -> Result<Option<SessionInfo>, AppError> {
+    validate_session_id(session_id)?;
+    let client = Client::new();
+    let url = gateway
+        .routes
+        .url(base_url, Endpoint::Session, &[("id", session_id)])?;
+
+    let resp = gateway
+        .execute(client.get(url))
+        .await
+        .map_err(|e| AppError::Network(format!("Failed to fetch session: {e}")))?;
+
+    if resp.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let text = resp.text().await.unwrap_or_default();
+        return Err(AppError::from_gateway_response(status, &text));
+    }
+
+    resp.json::<SessionInfo>()
+        .await
+        .map(Some)
+        .map_err(|e| AppError::Other(format!("Failed to parse session info: {e}")))
+}
+
+/// Search the transcripts the gateway keeps via GET /history/search?q= — returns None if
+/// the gateway doesn't keep any
+pub async fn search_history(
+    gateway: &Gateway,
+    base_url: &str,
+    query: &str,
+) -> Result<Option<Vec<RemoteHistoryMatch>>, AppError> {
+    let client = Client::new();
+    let url = gateway
+        .routes
+        .url(base_url, Endpoint::HistorySearch, &[("q", query)])?;
+
+    let resp = gateway
+        .execute(client.get(url))
+        .await
+        .map_err(|e| AppError::Network(format!("Failed to search history: {e}")))?;
+
+    if resp.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let text = resp.text().await.unwrap_or_default();
+        return Err(AppError::from_gateway_response(status, &text));
+    }
+
+    resp.json::<RemoteHistorySearch>()
+        .await
+        .map(|search| Some(search.matches))
+        .map_err(|e| AppError::Other(format!("Failed to parse history search: {e}")))
+}
+
+/// Fetch a stored transcript via GET /history/session?id= — returns None if the gateway
+/// doesn't keep transcripts or doesn't have this one
+pub async fn get_history_session(
+    gateway: &Gateway,
+    base_url: &str,
+    session_id: &str,
+) -> Result<Option<RemoteTranscript>, AppError> {
+    validate_session_id(session_id)?;
+    let client = Client::new();
+    let url = gateway
+        .routes
+        .url(base_url, Endpoint::HistorySession, &[("id", session_id)])?;
+
+    let resp = gateway
+        .execute(client.get(url))
+        .await
+        .map_err(|e| AppError::Network(format!("Failed to fetch transcript: {e}")))?;
+
+    if resp.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let text = resp.text().await.unwrap_or_default();
+        return Err(AppError::from_gateway_response(status, &text));
+    }
+
+    resp.json::<RemoteTranscript>()
+        .await
+        .map(Some)
+        .map_err(|e| AppError::Other(format!("Failed to parse transcript: {e}")))
+}
+
+/// Report a rating of a reply via POST /feedback
+pub async fn submit_feedback(
+    gateway: &Gateway,
+    base_url: &str,
+    feedback: &FeedbackRequest,
+) -> Result<(), AppError> {
+    validate_session_id(&feedback.session_id)?;
+    let client = Client::new();
+    let url = gateway.routes.url(base_url, Endpoint::Feedback, &[])?;
+
+    let resp = gateway
+        .execute(client.post(url).json(feedback))
+        .await
+        .map_err(|e| AppError::Network(format!("Failed to send feedback: {e}")))?;
+
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let text = resp.text().await.unwrap_or_default();
+        return Err(AppError::from_gateway_response(status, &text));
+    }
+    Ok(())
+}
+
+/// Download the audio an `audio` event points to. A relative URL is resolved against
+/// the gateway URL.
+pub async fn fetch_audio(
+    gateway: &Gateway,
+    base_url: &str,
+    url: &str,
+) -> Result<Vec<u8>, AppError> {
+    let mut base = parse_base_url(base_url)?;
+    // Joining keeps the last path segment only when it ends in a slash
+    let directory = format!("{}/", base.path());
+    base.set_path(&directory);
+    let url = base
+        .join(url)
+        .map_err(|e| AppError::Other(format!("Invalid audio URL '{url}': {e}")))?;
+    let client = Client::new();
+
+    let resp = gateway
+        .execute(client.get(url))
+        .await
+        .map_err(|e| AppError::Network(format!("Failed to fetch audio: {e}")))?;
+
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let text = resp.text().await.unwrap_or_default();
+        return Err(AppError::from_gateway_response(status, &text));
+    }
+
+    resp.bytes()
+        .await
+        .map(|bytes| bytes.to_vec())
+        .map_err(|e| AppError::Network(format!("Failed to read audio: {e}")))
+}
+
+/// Where and as whom a recorded turn is sent
+pub struct Turn<'a> {
+    pub base_url: &'a str,
+    pub session_id: &'a str,
+    pub profile_name: &'a str,
+    pub session_key: Option<&'a str>,
+    /// Language the reply is requested in, whatever language was spoken
+    pub response_language: Option<&'a str>,
+    /// What the user was looking at, only when they opted in to sharing it
+    pub context: Option<&'a TurnContext>,
+}
+
+/// Send a turn (audio or local transcript) and stream SSE events back via Tauri events.
+/// Events are emitted as "voice-event" to all webview windows; error events the gateway
+/// sends mid-stream are additionally classified and emitted as "gateway-error".
+pub async fn send_audio_streaming(
+    app: &AppHandle,
+    gateway: &Gateway,
+    turn: &Turn<'_>,
+    utterance: Utterance,
+) -> Result<(), AppError> {
+    let result = stream_audio(gateway, turn, utterance, |event| {
+        forward_event(app, turn.session_id, event)
+    })
+    .await;
+    keep_timed_out_reply(app, turn.session_id, &result);
+    result
+}
+
+/// Like `send_audio_streaming`, for a WAV body still being recorded; see `live_upload`
+pub async fn send_live_audio(
+    app: &AppHandle,
+    gateway: &Gateway,
+    turn: &Turn<'_>,
+    body: Body,
+) -> Result<(), AppError> {
+    let result = stream_body(gateway, turn, "audio/wav", body, |event| {
+        forward_event(app, turn.session_id, event)
+    })
+    .await;
+    keep_timed_out_reply(app, turn.session_id, &result);
+    result
+}
+
+fn keep_timed_out_reply(app: &AppHandle, session_id: &str, result: &Result<(), AppError>) {
+    if let Err(AppError::ResponseTimeout(timeout_secs)) = result {
+        events::emit_response_timeout(app, session_id, *timeout_secs);
+    }
+}
+
+fn forward_event(app: &AppHandle, session_id: &str, event: &VoiceEvent) -> Result<(), AppError> {
+    events::emit_voice_event(app, session_id, event)?;
+    if let VoiceEvent::System {
+        status,
+        message: Some(message),
+        ..
+    } = event
+    {
+        if status == "error" {
+            if let Some(err) = AppError::classify_message(message) {
+                events::emit_gateway_error(app, &err);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Send a turn to the gateway and hand each streamed SSE event to `on_event`.
+///
+/// POST {base_url}/audio?sessionId={session_id} (path per the configured routes)
+/// Headers: X-Profile, Content-Type: audio/wav (text/plain for a local transcript),
+/// X-Session-Key, X-Response-Language, X-Turn-Context, and X-Reply-Style with
+/// X-Reply-Max-Sentences in brief mode (optional)
+/// Body: raw WAV bytes, or the transcript
+pub async fn stream_audio<F>(
+    gateway: &Gateway,
+    turn: &Turn<'_>,
+    utterance: Utterance,
+    on_event: F,
+) -> Result<(), AppError>
+where
+    F: FnMut(&VoiceEvent) -> Result<(), AppError>,
+{
+    // Both hand their buffer to the body as it is; neither is copied again
+    let (content_type, body) = match utterance {
+        Utterance::Audio(wav_bytes) => ("audio/wav", Body::from(wav_bytes)),
+        Utterance::Transcript(text) => ("text/plain; charset=utf-8", Body::from(text)),
+    };
+    stream_body(gateway, turn, content_type, body, on_event).await
+}
+
+/// POST a turn's body, which may still be streaming in, and hand back the SSE events.
+/// A reply that hasn't finished within the gateway's `response_timeout` is abandoned
+/// with `AppError::ResponseTimeout`, closing the stream.
+async fn stream_body<F>(
+    gateway: &Gateway,
+    turn: &Turn<'_>,
+    content_type: &'static str,
+    body: Body,
+    mut on_event: F,
+) -> Result<(), AppError>
+where
+    F: FnMut(&VoiceEvent) -> Result<(), AppError>,
+{
+    let Turn {
+        base_url,
+        session_id,
+        profile_name,
+        session_key,
+        response_language,
+        context,
+    } = *turn;
+    validate_session_id(session_id)?;
     let client = Client::new();
-    let url = format!("{base_url}/audio?sessionId={session_id}");
+    let url = gateway
+        .routes
+        .url(base_url, Endpoint::Audio, &[("sessionId", session_id)])?;
 
     let mut headers = HeaderMap::new();
-    headers.insert(CONTENT_TYPE, HeaderValue::from_static("audio/wav"));
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
     headers.insert(
         "X-Profile",
         HeaderValue::from_str(profile_name)
-            .map_err(|e| format!("Invalid profile name header: {e}"))?,
+            .map_err(|e| AppError::Other(format!("Invalid profile name header: {e}")))?,
     );
     if let Some(key) = session_key {
         if !key.is_empty() {
             headers.insert(
                 "X-Session-Key",
                 HeaderValue::from_str(key)
-                    .map_err(|e| format!("Invalid session key header: {e}"))?,
+                    .map_err(|e| AppError::Other(format!("Invalid session key header: {e}")))?,
             );
         }
     }
+    if let Some(language) = response_language.filter(|l| !l.is_empty()) {
+        validate_language_tag(language).map_err(AppError::Other)?;
+        headers.insert(
+            "X-Response-Language",
+            HeaderValue::from_str(language)
+                .map_err(|e| AppError::Other(format!("Invalid response language header: {e}")))?,
+        );
+    }
+    if let Some(context) = context {
+        let encoded = context::encode_header(context)?;
+        headers.insert(
+            "X-Turn-Context",
+            HeaderValue::from_str(&encoded)
+                .map_err(|e| AppError::Other(format!("Invalid turn context header: {e}")))?,
+        );
+    }
+    if let Some(max_sentences) = gateway.brief_sentences {
+        headers.insert(brief::STYLE_HEADER, HeaderValue::from_static("brief"));
+        headers.insert(
+            brief::MAX_SENTENCES_HEADER,
+            HeaderValue::from(max_sentences),
+        );
+    }
 
-    let resp = client
-        .post(&url)
-        .headers(headers)
-        .body(wav_bytes)
-        .send()
+    let resp = gateway
+        .execute(client.post(url).headers(headers).body(body))
         .await
-        .map_err(|e| format!("Failed to send audio: {e}"))?;
+        .map_err(|e| AppError::Network(format!("Failed to send audio: {e}")))?;
 
     if !resp.status().is_success() {
         let status = resp.status();
         let text = resp.text().await.unwrap_or_default();
-        return Err(format!("Audio upload failed ({status}): {text}"));
+        return Err(AppError::from_gateway_response(status, &text));
     }
 
     // Stream SSE events
     let mut parser = SseParser::new();
     let mut stream = resp.bytes_stream();
+    // Cleared by the final reply event; synthesized speech may still follow it
+    let mut deadline = gateway.response_timeout.map(|limit| Instant::now() + limit);
+    let mut cap = gateway.brief_sentences.map(BriefCap::new);
 
-    while let Some(chunk_result) = stream.next().await {
-        let chunk = chunk_result
-            .map_err(|e| format!("Stream read error: {e}"))?;
+    loop {
+        let next = match deadline {
+            Some(deadline) => tokio::time::timeout_at(deadline, stream.next())
+                .await
+                .map_err(|_| {
+                    let limit = gateway.response_timeout.unwrap_or_default();
+                    AppError::ResponseTimeout(limit.as_secs())
+                })?,
+            None => stream.next().await,
+        };
+        let Some(chunk_result) = next else {
+            break;
+        };
+        let chunk =
+            chunk_result.map_err(|e| AppError::Network(format!("Stream read error: {e}")))?;
         let text = String::from_utf8_lossy(&chunk);
         let events = parser.feed(&text);
 
         for event in events {
-            app.emit("voice-event", &event)
-                .map_err(|e| format!("Failed to emit event: {e}"))?;
+            if let Some(finish) = cap.as_mut().and_then(|cap| cap.cut(&event)) {
+                // Dropping the stream closes it, so neither the rest of the reply nor
+                // the gateway's speech for it arrives
+                for event in &finish {
+                    on_event(event)?;
+                }
+                return Ok(());
+            }
+            if let VoiceEvent::Openclaw { done: true, .. } = event {
+                deadline = None;
+            }
+            on_event(&event)?;
         }
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trailing_slashes_normalised() {
+        let url = parse_base_url(" http://127.0.0.1:18790/voice-client// ").unwrap();
+        assert_eq!(url.as_str(), "http://127.0.0.1:18790/voice-client");
+    }
+
+    #[test]
+    fn test_missing_scheme_rejected() {
+        let err = parse_base_url("127.0.0.1:18790/voice-client").unwrap_err();
+        assert!(matches!(err, AppError::InvalidGatewayUrl(_)));
+    }
+
+    #[test]
+    fn test_non_http_scheme_rejected() {
+        assert!(parse_base_url("ftp://gw/voice-client").is_err());
+        assert!(parse_base_url("http://gw/voice-client?x=1").is_err());
+    }
+
+    #[test]
+    fn test_session_id_validation() {
+        assert!(validate_session_id("3f2b9c1e-0d4a-4f7e-9a51-2c7d8e6b1a90").is_ok());
+        assert!(validate_session_id("").is_err());
+        assert!(validate_session_id("abc\ndef").is_err());
+    }
+
+    #[test]
+    fn test_language_tag_validation() {
+        assert!(validate_language_tag("de").is_ok());
+        assert!(validate_language_tag("pt-BR").is_ok());
+        assert!(validate_language_tag("zh-Hant-TW").is_ok());
+        assert!(validate_language_tag("German").is_err());
+        assert!(validate_language_tag("en-").is_err());
+        assert!(validate_language_tag("en\r\nX-Profile: bob").is_err());
+    }
+
+    #[test]
+    fn test_workspace_validation() {
+        assert!(validate_workspace("design").is_ok());
+        assert!(validate_workspace("team_2.eu-west").is_ok());
+        assert!(validate_workspace("").is_err());
+        assert!(validate_workspace("my team").is_err());
+        assert!(validate_workspace("a\r\nX-Profile: bob").is_err());
+    }
+}
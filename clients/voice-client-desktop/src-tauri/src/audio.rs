@@ -1,20 +1,346 @@
+use std::collections::{HashMap, VecDeque};
 use std::io::Cursor;
+use std::ops::Range;
 use std::sync::{
-    atomic::{AtomicBool, Ordering},
+    atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering},
     Arc, Mutex,
 };
+use std::time::Duration;
 
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use cpal::{Sample, SampleFormat, Stream, StreamConfig};
+use cpal::{
+    BufferSize, HostId, Sample, SampleFormat, Stream, StreamConfig, SupportedBufferSize,
+    SupportedStreamConfig, SupportedStreamConfigRange,
+};
 use hound::{SampleFormat as HoundSampleFormat, WavSpec, WavWriter};
 
-use crate::types::AudioDevice;
+use crate::denoise;
+use crate::mic_usage::InUse;
+use crate::playback::resample_linear;
+use crate::quality::Biquad;
+use crate::types::{
+    AudioDevice, AudioHost, CaptureFormatRange, CaptureOverflow, DeviceCapabilities, DeviceFormat,
+    DeviceLabel, Effect, HalfDuplex, MicLevel, PreprocessStage,
+};
+use crate::vad;
+
+/// Level reported for digital silence, in dBFS
+pub const SILENCE_DB: f32 = -100.0;
+
+/// Samples at or past this are taken as clipped: the input gain is too high for the
+/// converter, and the peaks are cut off
+pub const CLIP_LEVEL: f32 = 0.99;
+
+/// System audio kept waiting for the microphone, in seconds. The two devices run on
+/// separate clocks; past this the oldest is dropped so the sides stay in step.
+const MAX_SYSTEM_LAG_SECS: f32 = 0.5;
+
+/// Level the `agc` preprocessing stage aims for, and the most it will amplify by
+const AGC_TARGET_DB: f32 = -20.0;
+const AGC_MAX_GAIN_DB: f32 = 20.0;
+
+/// Where a recording is between `start_recording` and `take_recording` or
+/// `cancel_recording`. It only changes through `RecordingPhase::next`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RecordingPhase {
+    #[default]
+    Idle,
+    /// The device and streams are being opened
+    Starting,
+    /// The streams are open and stored, so stopping can close them
+    Recording,
+    /// The streams are being closed and the audio taken
+    Stopping,
+}
+
+/// What moves a recording from one phase to the next
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PhaseEvent {
+    Start,
+    /// Every stream opened and was stored
+    Opened,
+    /// Opening failed part way; whatever did open has been closed
+    Failed,
+    Stop,
+    /// The streams are closed
+    Closed,
+}
+
+impl RecordingPhase {
+    /// The phase after `event`, or why it can't happen in this one
+    pub fn next(self, event: PhaseEvent) -> Result<Self, String> {
+        match (self, event) {
+            (Self::Idle, PhaseEvent::Start) => Ok(Self::Starting),
+            (Self::Starting, PhaseEvent::Opened) => Ok(Self::Recording),
+            (Self::Starting, PhaseEvent::Failed) => Ok(Self::Idle),
+            (Self::Recording, PhaseEvent::Stop) => Ok(Self::Stopping),
+            (Self::Stopping, PhaseEvent::Closed) => Ok(Self::Idle),
+            (Self::Starting | Self::Recording, PhaseEvent::Start) => {
+                Err("Recording already in progress".to_string())
+            }
+            (Self::Stopping, PhaseEvent::Start) => {
+                Err("The last recording is still stopping".to_string())
+            }
+            (_, PhaseEvent::Stop) => Err("Recording is not active".to_string()),
+            (phase, event) => Err(format!("Recording can't go from {phase:?} on {event:?}")),
+        }
+    }
+}
 
 pub struct AudioState {
+    /// Set exactly while the phase is `Recording`, for readers that can't take a lock
+    /// (the playback callback, the level meter, auto-stop); only `transition` writes it
     pub is_recording: Arc<AtomicBool>,
+    phase: Arc<Mutex<RecordingPhase>>,
+    /// Audio is dropped instead of captured while set; the stream and the samples so
+    /// far are kept, so the recording carries on where it left off
+    pub is_paused: Arc<AtomicBool>,
     pub samples: Arc<Mutex<Vec<f32>>>,
     pub stream: Arc<Mutex<Option<Stream>>>,
+    /// The system audio stream recorded alongside the microphone in meeting mode
+    pub system_stream: Arc<Mutex<Option<Stream>>>,
     pub sample_rate: Arc<Mutex<Option<u32>>>,
+    /// Name of the device the current (or last) recording came from
+    pub device_name: Arc<Mutex<Option<String>>>,
+    /// Forced capture formats keyed by device name or id
+    pub format_overrides: Arc<Mutex<HashMap<String, DeviceFormat>>>,
+    /// Input level since the meter was last read
+    pub level: Arc<LevelMeter>,
+    /// How silence is cut from the ends of a finished recording
+    pub trim: Arc<Mutex<SilenceTrim>>,
+    /// Stages run over each finished recording, in order
+    pub preprocessing: Arc<Mutex<Vec<PreprocessStage>>>,
+    /// Whether the `denoise` stage runs, from the noise suppression setting
+    pub denoise: Arc<AtomicBool>,
+    /// Memory cap on `samples`, read when a recording starts
+    pub capture_limit: Arc<Mutex<CaptureLimit>>,
+    /// Samples of the current recording dropped from the front of `samples` at the
+    /// cap; readers keeping a position in the buffer subtract it
+    pub dropped: Arc<AtomicUsize>,
+    /// The current recording has reached the cap
+    pub limit_reached: Arc<AtomicBool>,
+    /// Whether system audio is mixed in, read when a recording starts
+    pub meeting: Arc<Mutex<MeetingCapture>>,
+    /// How a reply spoken while recording is kept out of the recording
+    pub half_duplex: Arc<Mutex<HalfDuplex>>,
+    /// A reply is audible right now; the playback callback keeps it up to date
+    pub reply_audible: Arc<AtomicBool>,
+    /// The one channel recorded, counting from 1; all channels are mixed when None
+    pub input_channel: Arc<Mutex<Option<u16>>>,
+    /// Cutoff (Hz) of the `highpass` preprocessing stage, which also takes out DC
+    /// offset; 0 turns it off. Read when a recording starts.
+    pub high_pass_hz: Arc<Mutex<u32>>,
+    /// Audio backend recorded through, by cpal's name for it; the platform default if None
+    pub host: Arc<Mutex<Option<String>>>,
+    /// Keeps the open stream in the microphone usage log; dropped along with it
+    pub in_use: Arc<Mutex<Option<InUse>>>,
+}
+
+/// The half of the half-duplex policy the playback callback applies, cloned out of
+/// `AudioState` when the output stream opens
+#[derive(Clone)]
+pub struct DuplexLink {
+    recording: Arc<AtomicBool>,
+    mode: Arc<Mutex<HalfDuplex>>,
+    reply_audible: Arc<AtomicBool>,
+}
+
+impl DuplexLink {
+    /// Whether replies should be turned down: recording with `HalfDuplex::DuckPlayback`
+    pub fn duck_playback(&self) -> bool {
+        self.recording.load(Ordering::Relaxed)
+            && self
+                .mode
+                .lock()
+                .is_ok_and(|mode| *mode == HalfDuplex::DuckPlayback)
+    }
+
+    pub fn set_reply_audible(&self, audible: bool) {
+        self.reply_audible.store(audible, Ordering::Relaxed);
+    }
+}
+
+/// Meeting mode: what the computer plays is recorded along with the microphone and
+/// mixed into it, so both sides of a call are transcribed
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MeetingCapture {
+    pub enabled: bool,
+    /// Device carrying the system audio, by id or name: a loopback input such as a
+    /// PulseAudio monitor or BlackHole, or an output device to record from where the
+    /// platform supports loopback. None records the default output device.
+    pub system_device_id: Option<String>,
+    /// Gain (dB) on the microphone in the mix, e.g. negative for a loud headset mic
+    pub mic_gain_db: f32,
+    /// Gain (dB) on the system audio in the mix, e.g. positive for a quiet call
+    pub system_gain_db: f32,
+}
+
+/// Memory cap on a recording's audio, so one that is never stopped can't use up the
+/// RAM: mono `f32` at 48 kHz takes about 11 MB a minute
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CaptureLimit {
+    /// 0 means no limit
+    pub max_bytes: usize,
+    pub overflow: CaptureOverflow,
+}
+
+impl Default for CaptureLimit {
+    fn default() -> Self {
+        Self {
+            max_bytes: 512 * 1024 * 1024,
+            overflow: CaptureOverflow::Stop,
+        }
+    }
+}
+
+impl CaptureLimit {
+    fn max_samples(&self) -> usize {
+        self.max_bytes / std::mem::size_of::<f32>()
+    }
+}
+
+/// Leading and trailing silence trimming, so fumbled push-to-talk presses and dead
+/// air before the release aren't uploaded
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SilenceTrim {
+    pub enabled: bool,
+    /// Level (dBFS) below which a 10 ms frame counts as silence
+    pub threshold_db: f32,
+    /// Audio kept on either side of the sound, so soft word edges aren't cut
+    pub padding: Duration,
+    /// Raise the threshold above the noise floor of the recording's first 300 ms
+    pub calibrate: bool,
+}
+
+impl Default for SilenceTrim {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold_db: -50.0,
+            padding: Duration::from_millis(250),
+            calibrate: true,
+        }
+    }
+}
+
+impl SilenceTrim {
+    /// The part of `samples` to keep; empty when nothing reaches the threshold
+    pub fn keep(&self, samples: &[f32], sample_rate: u32) -> Range<usize> {
+        if !self.enabled || samples.is_empty() {
+            return 0..samples.len();
+        }
+        let floor_db = self
+            .calibrate
+            .then(|| vad::noise_floor_db(samples, sample_rate))
+            .flatten();
+        let threshold_db = match floor_db {
+            Some(floor_db) => vad::adapt_threshold(self.threshold_db, floor_db),
+            None => self.threshold_db,
+        };
+        let frame = (sample_rate as usize / 100).max(1);
+        let loud = |chunk: &[f32]| {
+            let rms = (chunk.iter().map(|s| s * s).sum::<f32>() / chunk.len() as f32).sqrt();
+            to_dbfs(rms) >= threshold_db
+        };
+        let mut frames = samples.chunks(frame).enumerate().filter(|(_, c)| loud(c));
+        let Some((first, _)) = frames.next() else {
+            return 0..0;
+        };
+        let last = frames.next_back().map_or(first, |(index, _)| index);
+        let padding = (self.padding.as_secs_f64() * sample_rate as f64) as usize;
+        let start = (first * frame).saturating_sub(padding);
+        let end = ((last + 1) * frame + padding).min(samples.len());
+        start..end
+    }
+}
+
+/// The stages run over each finished recording, and what they run with
+#[derive(Debug, Clone, PartialEq)]
+pub struct Preprocessing {
+    pub stages: Vec<PreprocessStage>,
+    /// Cutoff of the `highpass` stage; 0 skips it
+    pub high_pass_hz: u32,
+    /// Whether `denoise` runs
+    pub denoise: bool,
+    pub trim: SilenceTrim,
+}
+
+impl Preprocessing {
+    /// Whether the pipeline starts with the high-pass, which then runs on the
+    /// microphone as it is captured, so the level meter and auto-stop hear it too
+    pub fn filters_capture(&self) -> bool {
+        self.stages.first() == Some(&PreprocessStage::Highpass)
+    }
+
+    /// The stages left for the finished recording
+    fn after_capture(&self) -> &[PreprocessStage] {
+        if self.filters_capture() {
+            &self.stages[1..]
+        } else {
+            &self.stages
+        }
+    }
+
+    /// Whether `stage` does anything with the current settings
+    fn is_active(&self, stage: PreprocessStage) -> bool {
+        match stage {
+            PreprocessStage::Highpass => self.high_pass_hz > 0,
+            PreprocessStage::Denoise => self.denoise,
+            PreprocessStage::Agc => true,
+            PreprocessStage::Trim => self.trim.enabled,
+        }
+    }
+
+    /// The first active stage that has to see the whole recording, so it can't be
+    /// uploaded while still being captured; trimming is undone for that by offset
+    pub fn needs_whole_recording(&self) -> Option<PreprocessStage> {
+        self.after_capture()
+            .iter()
+            .copied()
+            .find(|stage| *stage != PreprocessStage::Trim && self.is_active(*stage))
+    }
+
+    /// Run the stages over a finished recording in order. Returns what is left and
+    /// how many samples trimming cut off the start.
+    pub fn run(&self, mut samples: Vec<f32>, sample_rate: u32) -> (Vec<f32>, usize) {
+        let mut trimmed_start = 0;
+        for &stage in self.after_capture() {
+            if samples.is_empty() || !self.is_active(stage) {
+                continue;
+            }
+            samples = match stage {
+                PreprocessStage::Highpass => Effect::HighPass {
+                    cutoff_hz: self.high_pass_hz as f32,
+                }
+                .process(samples, sample_rate),
+                PreprocessStage::Denoise => denoise::suppress(&samples, sample_rate),
+                PreprocessStage::Agc => Effect::Agc {
+                    target_db: AGC_TARGET_DB,
+                    max_gain_db: AGC_MAX_GAIN_DB,
+                }
+                .process(samples, sample_rate),
+                PreprocessStage::Trim => {
+                    let keep = self.trim.keep(&samples, sample_rate);
+                    trimmed_start += keep.start;
+                    samples.truncate(keep.end);
+                    samples.drain(..keep.start);
+                    samples
+                }
+            };
+        }
+        (samples, trimmed_start)
+    }
+}
+
+/// A finished recording as returned by `take_recording`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recording {
+    /// Mono samples, after the preprocessing pipeline
+    pub samples: Vec<f32>,
+    pub sample_rate: u32,
+    /// Samples cut off the start of the capture, leading silence and whatever was
+    /// dropped at the memory cap, for whoever already used the raw capture
+    pub trimmed_start: usize,
 }
 
 // SAFETY: cpal::Stream is not Send on macOS (CoreAudio handles are thread-affine),
@@ -29,13 +355,319 @@ impl AudioState {
     pub fn new() -> Self {
         Self {
             is_recording: Arc::new(AtomicBool::new(false)),
+            phase: Arc::new(Mutex::new(RecordingPhase::Idle)),
+            is_paused: Arc::new(AtomicBool::new(false)),
             samples: Arc::new(Mutex::new(Vec::new())),
             stream: Arc::new(Mutex::new(None)),
+            system_stream: Arc::new(Mutex::new(None)),
             sample_rate: Arc::new(Mutex::new(None)),
+            device_name: Arc::new(Mutex::new(None)),
+            format_overrides: Arc::new(Mutex::new(HashMap::new())),
+            level: Arc::new(LevelMeter::default()),
+            trim: Arc::new(Mutex::new(SilenceTrim::default())),
+            preprocessing: Arc::new(Mutex::new(vec![
+                PreprocessStage::Highpass,
+                PreprocessStage::Trim,
+                PreprocessStage::Denoise,
+            ])),
+            denoise: Arc::new(AtomicBool::new(false)),
+            capture_limit: Arc::new(Mutex::new(CaptureLimit::default())),
+            dropped: Arc::new(AtomicUsize::new(0)),
+            limit_reached: Arc::new(AtomicBool::new(false)),
+            meeting: Arc::new(Mutex::new(MeetingCapture::default())),
+            half_duplex: Arc::new(Mutex::new(HalfDuplex::default())),
+            reply_audible: Arc::new(AtomicBool::new(false)),
+            input_channel: Arc::new(Mutex::new(None)),
+            high_pass_hz: Arc::new(Mutex::new(80)),
+            host: Arc::new(Mutex::new(None)),
+            in_use: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Log the stream just opened as in use until it closes
+    pub fn mark_in_use(&self, in_use: InUse) {
+        if let Ok(mut current) = self.in_use.lock() {
+            *current = Some(in_use);
         }
     }
+
+    pub fn phase(&self) -> RecordingPhase {
+        self.phase.lock().map(|phase| *phase).unwrap_or_default()
+    }
+
+    /// Move to the phase after `event`, keeping `is_recording` in step. A poisoned
+    /// lock is taken over, so a panic elsewhere can't leave the phase stuck.
+    fn transition(&self, event: PhaseEvent) -> Result<RecordingPhase, String> {
+        let mut phase = self
+            .phase
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let next = phase.next(event)?;
+        *phase = next;
+        self.is_recording
+            .store(next == RecordingPhase::Recording, Ordering::SeqCst);
+        Ok(next)
+    }
+
+    fn close_stream(&self) {
+        if let Ok(mut stream_guard) = self.stream.lock() {
+            stream_guard.take();
+        }
+        if let Ok(mut system_guard) = self.system_stream.lock() {
+            system_guard.take();
+        }
+        if let Ok(mut in_use) = self.in_use.lock() {
+            in_use.take();
+        }
+    }
+
+    pub fn set_host(&self, name: Option<String>) {
+        if let Ok(mut current) = self.host.lock() {
+            *current = name.filter(|name| !name.is_empty());
+        }
+    }
+
+    pub fn host_name(&self) -> Option<String> {
+        self.host.lock().ok().and_then(|name| name.clone())
+    }
+
+    pub fn set_input_channel(&self, channel: Option<u16>) {
+        if let Ok(mut current) = self.input_channel.lock() {
+            *current = channel.filter(|c| *c > 0);
+        }
+    }
+
+    pub fn set_high_pass(&self, cutoff_hz: u32) {
+        if let Ok(mut current) = self.high_pass_hz.lock() {
+            *current = cutoff_hz;
+        }
+    }
+
+    pub fn set_half_duplex(&self, mode: HalfDuplex) {
+        if let Ok(mut current) = self.half_duplex.lock() {
+            *current = mode;
+        }
+    }
+
+    pub fn duplex_link(&self) -> DuplexLink {
+        DuplexLink {
+            recording: self.is_recording.clone(),
+            mode: self.half_duplex.clone(),
+            reply_audible: self.reply_audible.clone(),
+        }
+    }
+
+    pub fn set_meeting_capture(&self, meeting: MeetingCapture) {
+        if let Ok(mut current) = self.meeting.lock() {
+            *current = meeting;
+        }
+    }
+
+    pub fn set_capture_limit(&self, limit: CaptureLimit) {
+        if let Ok(mut current) = self.capture_limit.lock() {
+            *current = limit;
+        }
+    }
+
+    pub fn set_silence_trim(&self, trim: SilenceTrim) {
+        if let Ok(mut current) = self.trim.lock() {
+            *current = trim;
+        }
+    }
+
+    pub fn set_preprocessing(&self, stages: Vec<PreprocessStage>, denoise: bool) {
+        if let Ok(mut current) = self.preprocessing.lock() {
+            *current = stages;
+        }
+        self.denoise.store(denoise, Ordering::SeqCst);
+    }
+
+    /// The preprocessing pipeline as configured now
+    pub fn preprocessing(&self) -> Preprocessing {
+        Preprocessing {
+            stages: self
+                .preprocessing
+                .lock()
+                .map(|stages| stages.clone())
+                .unwrap_or_default(),
+            high_pass_hz: self.high_pass_hz.lock().map(|hz| *hz).unwrap_or_default(),
+            denoise: self.denoise.load(Ordering::SeqCst),
+            trim: self.trim.lock().map(|trim| *trim).unwrap_or_default(),
+        }
+    }
+
+    pub fn set_format_overrides(&self, overrides: HashMap<String, DeviceFormat>) {
+        if let Ok(mut current) = self.format_overrides.lock() {
+            *current = overrides;
+        }
+    }
+
+    fn format_override(&self, device: &cpal::Device) -> Option<DeviceFormat> {
+        let overrides = self.format_overrides.lock().ok()?;
+        if overrides.is_empty() {
+            return None;
+        }
+        let name = device.description().ok().map(|d| d.name().to_string());
+        let id = device.id().ok().map(|id| format!("{id:?}"));
+        [name, id]
+            .into_iter()
+            .flatten()
+            .find_map(|key| overrides.get(&key).cloned())
+    }
+
+    pub fn device_name(&self) -> Option<String> {
+        self.device_name.lock().ok().and_then(|name| name.clone())
+    }
+}
+
+/// Input level accumulated by the capture callback and drained by whoever shows it.
+/// Only atomics, so the callback never waits on a reader and the sample buffer's
+/// lock stays out of it.
+#[derive(Debug, Default)]
+pub struct LevelMeter {
+    /// Sum of squared samples, as `f64` bits
+    sum_squares: AtomicU64,
+    count: AtomicU64,
+    /// Largest absolute sample, as `f32` bits; for non-negative floats the bits
+    /// order the same way as the values
+    peak: AtomicU32,
+    /// Samples at or past `CLIP_LEVEL`, and all samples, since clipping was last read
+    clipped: AtomicU64,
+    clip_window: AtomicU64,
+}
+
+impl LevelMeter {
+    fn push(&self, samples: &[f32]) {
+        if samples.is_empty() {
+            return;
+        }
+        let (sum, peak, clipped) = samples.iter().filter(|s| s.is_finite()).fold(
+            (0.0f64, 0.0f32, 0u64),
+            |(sum, peak, clipped), s| {
+                (
+                    sum + (*s as f64) * (*s as f64),
+                    peak.max(s.abs()),
+                    clipped + u64::from(s.abs() >= CLIP_LEVEL),
+                )
+            },
+        );
+        let _ = self
+            .sum_squares
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bits| {
+                Some((f64::from_bits(bits) + sum).to_bits())
+            });
+        self.count
+            .fetch_add(samples.len() as u64, Ordering::Relaxed);
+        self.peak.fetch_max(peak.to_bits(), Ordering::Relaxed);
+        self.clipped.fetch_add(clipped, Ordering::Relaxed);
+        self.clip_window
+            .fetch_add(samples.len() as u64, Ordering::Relaxed);
+    }
+
+    /// Percentage of the samples since the last call that clipped, or None if no
+    /// audio arrived in between. Read less often than the level, so kept apart.
+    pub fn take_clipping(&self) -> Option<f32> {
+        let total = self.clip_window.swap(0, Ordering::Relaxed);
+        let clipped = self.clipped.swap(0, Ordering::Relaxed);
+        (total > 0).then(|| clipped as f32 * 100.0 / total as f32)
+    }
+
+    /// RMS and peak since the last call, or None if no audio arrived in between
+    pub fn take(&self) -> Option<MicLevel> {
+        let count = self.count.swap(0, Ordering::Relaxed);
+        let sum = f64::from_bits(self.sum_squares.swap(0, Ordering::Relaxed));
+        let peak = f32::from_bits(self.peak.swap(0, Ordering::Relaxed));
+        if count == 0 {
+            return None;
+        }
+        let rms = (sum / count as f64).sqrt() as f32;
+        Some(MicLevel {
+            rms_db: to_dbfs(rms),
+            peak_db: to_dbfs(peak),
+        })
+    }
+}
+
+/// Linear amplitude factor for a gain in decibels
+pub(crate) fn db_to_gain(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+/// Amplitude relative to full scale in decibels, floored at `SILENCE_DB`
+pub fn to_dbfs(amplitude: f32) -> f32 {
+    if amplitude <= 0.0 {
+        return SILENCE_DB;
+    }
+    (20.0 * amplitude.log10()).max(SILENCE_DB)
+}
+
+/// Decimate `samples` to one `[lowest, highest]` pair per `bucket_len` samples, for
+/// drawing a waveform. A partial bucket at the end is left for the next call.
+pub fn peak_pairs(samples: &[f32], bucket_len: usize) -> Vec<[f32; 2]> {
+    if bucket_len == 0 {
+        return Vec::new();
+    }
+    samples
+        .chunks_exact(bucket_len)
+        .map(|bucket| {
+            let (low, high) = bucket
+                .iter()
+                .filter(|s| s.is_finite())
+                .fold((f32::INFINITY, f32::NEG_INFINITY), |(low, high), &s| {
+                    (low.min(s), high.max(s))
+                });
+            // A bucket of nothing but NaNs draws as silence
+            if low > high {
+                [0.0, 0.0]
+            } else {
+                [low, high]
+            }
+        })
+        .collect()
+}
+
+/// The compiled-in audio backend cpal calls `name` ("ALSA", "JACK", "WASAPI", "ASIO",
+/// ...), in any letter case
+pub fn find_host(name: &str) -> Option<HostId> {
+    cpal::available_hosts()
+        .into_iter()
+        .find(|id| id.name().eq_ignore_ascii_case(name))
 }
 
+/// Open the audio backend named `name`, or the platform default if None. A backend
+/// that isn't compiled in or won't start (JACK without a running server) falls back
+/// to the default, so a stale setting doesn't leave the app without a microphone.
+pub fn open_host(name: Option<&str>) -> cpal::Host {
+    let Some(name) = name else {
+        return cpal::default_host();
+    };
+    match find_host(name).map(cpal::host_from_id) {
+        Some(Ok(host)) => host,
+        Some(Err(err)) => {
+            eprintln!("Audio host '{name}' is unavailable, using the default: {err}");
+            cpal::default_host()
+        }
+        None => {
+            eprintln!("Audio host '{name}' isn't supported by this build, using the default");
+            cpal::default_host()
+        }
+    }
+}
+
+/// The audio backends this build supports on this platform, marking the default and
+/// the one `selected` (the `audio_host` setting) resolves to
+pub fn list_audio_hosts(selected: Option<&str>) -> Vec<AudioHost> {
+    let default = cpal::default_host().id();
+    let selected = selected.and_then(find_host).unwrap_or(default);
+    cpal::available_hosts()
+        .into_iter()
+        .map(|id| AudioHost {
+            name: id.name().to_string(),
+            is_default: id == default,
+            selected: id == selected,
+        })
+        .collect()
+}
 
 /// Briefly open a mic stream to trigger the macOS permission prompt at startup.
 /// Runs on a background thread — no-op if permission is already granted.
@@ -65,8 +697,9 @@ pub fn request_mic_permission() {
     }
 }
 
-pub fn list_audio_devices() -> Result<Vec<AudioDevice>, String> {
-    let host = cpal::default_host();
+/// The input devices of the audio backend named `host`, the platform default if None
+pub fn list_audio_devices(host: Option<&str>) -> Result<Vec<AudioDevice>, String> {
+    let host = open_host(host);
     let default_device = host.default_input_device();
     let default_id = default_device
         .as_ref()
@@ -85,6 +718,10 @@ pub fn list_audio_devices() -> Result<Vec<AudioDevice>, String> {
         let name = description.name().to_string();
         let id = device.id().map_err(|err| err.to_string())?;
         let id = format!("{id:?}");
+        let supported_formats = device
+            .supported_input_configs()
+            .map(|configs| configs.map(|range| describe_range(&range)).collect())
+            .unwrap_or_default();
         let is_default = default_id
             .as_ref()
             .is_some_and(|default_id| default_id == &id)
@@ -96,25 +733,48 @@ pub fn list_audio_devices() -> Result<Vec<AudioDevice>, String> {
             name,
             id,
             is_default,
+            supported_formats,
+            alias: None,
+            favorite: false,
         });
     }
 
     Ok(entries)
 }
 
-pub fn start_recording(state: &AudioState, device_id: Option<&str>) -> Result<(), String> {
-    let start_result = state.is_recording.compare_exchange(
-        false,
-        true,
-        Ordering::SeqCst,
-        Ordering::SeqCst,
-    );
-    if start_result.is_err() {
-        return Err("Recording already in progress".to_string());
+/// Apply the user's aliases and favorites, favorites first and otherwise in the
+/// driver's order. A label under the device's id wins over one under its name.
+pub fn label_devices(
+    mut devices: Vec<AudioDevice>,
+    labels: &HashMap<String, DeviceLabel>,
+) -> Vec<AudioDevice> {
+    for device in &mut devices {
+        let Some(label) = labels.get(&device.id).or_else(|| labels.get(&device.name)) else {
+            continue;
+        };
+        device.alias = label
+            .alias
+            .as_deref()
+            .map(str::trim)
+            .filter(|alias| !alias.is_empty())
+            .map(String::from);
+        device.favorite = label.favorite;
     }
+    devices.sort_by_key(|device| !device.favorite);
+    devices
+}
+
+/// Record from `device_id` (the default microphone if None). `channel_index`, counting
+/// from 1, records just that input for this recording instead of the configured one.
+pub fn start_recording(
+    state: &AudioState,
+    device_id: Option<&str>,
+    channel_index: Option<u16>,
+) -> Result<(), String> {
+    state.transition(PhaseEvent::Start)?;
 
     let operation = (|| -> Result<(), String> {
-        let host = cpal::default_host();
+        let host = open_host(state.host_name().as_deref());
         let device = match device_id {
             Some(id) => find_input_device(&host, id)?,
             None => host
@@ -122,49 +782,84 @@ pub fn start_recording(state: &AudioState, device_id: Option<&str>) -> Result<()
                 .ok_or_else(|| "No default input device available".to_string())?,
         };
 
-        let supported_config = device
-            .default_input_config()
-            .map_err(|err| err.to_string())?;
+        let wanted = state.format_override(&device).unwrap_or_default();
+        let supported_config = if wanted.sample_format.is_some() || wanted.sample_rate.is_some() {
+            forced_config(&device, &wanted)?
+        } else {
+            device
+                .default_input_config()
+                .map_err(|err| err.to_string())?
+        };
+        let buffer_size = choose_buffer_size(supported_config.buffer_size(), wanted.buffer_size)?;
         let sample_rate = supported_config.sample_rate();
         let channels = supported_config.channels();
         let sample_format = supported_config.sample_format();
-        let config: StreamConfig = supported_config.into();
+        let mut config: StreamConfig = supported_config.into();
+        config.buffer_size = buffer_size;
 
         {
             let mut buffer = state.samples.lock().map_err(|err| err.to_string())?;
             buffer.clear();
+            state.dropped.store(0, Ordering::SeqCst);
+            state.limit_reached.store(false, Ordering::SeqCst);
         }
         {
             let mut rate = state.sample_rate.lock().map_err(|err| err.to_string())?;
             *rate = Some(sample_rate);
         }
+        if let Ok(mut name) = state.device_name.lock() {
+            *name = device
+                .description()
+                .ok()
+                .map(|description| description.name().to_string());
+        }
 
-        let samples = state.samples.clone();
-
-        let stream = match sample_format {
-            SampleFormat::I8 => build_input_stream::<i8>(&device, &config, channels, samples)?,
-            SampleFormat::I16 => build_input_stream::<i16>(&device, &config, channels, samples)?,
-            SampleFormat::I24 => build_input_stream::<cpal::I24>(&device, &config, channels, samples)?,
-            SampleFormat::I32 => build_input_stream::<i32>(&device, &config, channels, samples)?,
-            SampleFormat::I64 => build_input_stream::<i64>(&device, &config, channels, samples)?,
-            SampleFormat::U8 => build_input_stream::<u8>(&device, &config, channels, samples)?,
-            SampleFormat::U16 => build_input_stream::<u16>(&device, &config, channels, samples)?,
-            SampleFormat::U24 => build_input_stream::<cpal::U24>(&device, &config, channels, samples)?,
-            SampleFormat::U32 => build_input_stream::<u32>(&device, &config, channels, samples)?,
-            SampleFormat::U64 => build_input_stream::<u64>(&device, &config, channels, samples)?,
-            SampleFormat::F32 => build_input_stream::<f32>(&device, &config, channels, samples)?,
-            SampleFormat::F64 => build_input_stream::<f64>(&device, &config, channels, samples)?,
-            SampleFormat::DsdU8 | SampleFormat::DsdU16 | SampleFormat::DsdU32 => {
-                return Err("DSD sample formats are not supported".to_string())
-            }
-            _ => {
-                return Err(format!(
-                    "Unsupported sample format '{sample_format}'"
-                ))
+        // Whatever was left from the last recording isn't this one's level
+        state.level.take();
+        state.level.take_clipping();
+        state.is_paused.store(false, Ordering::SeqCst);
+        let meeting = state.meeting.lock().map(|m| m.clone()).unwrap_or_default();
+        let system = if meeting.enabled {
+            let opened = open_system_audio(&host, &meeting, state.is_paused.clone())
+                .map_err(|err| format!("Meeting mode can't record system audio: {err}"))?;
+            Some(opened)
+        } else {
+            None
+        };
+        let input_channel =
+            channel_index.or_else(|| state.input_channel.lock().ok().and_then(|c| *c));
+        let channel = match input_channel {
+            Some(wanted) if wanted > channels => {
+                eprintln!("Input channel {wanted} not on a {channels}-channel device; mixing all");
+                None
             }
+            wanted => wanted.map(|c| usize::from(c - 1)),
+        };
+        let capture = Capture {
+            samples: state.samples.clone(),
+            level: state.level.clone(),
+            paused: state.is_paused.clone(),
+            limit: state.capture_limit.lock().map(|l| *l).unwrap_or_default(),
+            dropped: state.dropped.clone(),
+            limit_reached: state.limit_reached.clone(),
+            sample_rate,
+            channel,
+            system: system.as_ref().map(|(_, audio)| audio.clone()),
+            high_pass: high_pass(state, sample_rate),
+            reply_audible: state
+                .half_duplex
+                .lock()
+                .is_ok_and(|mode| *mode == HalfDuplex::MuteMicrophone)
+                .then(|| state.reply_audible.clone()),
         };
 
+        let stream = open_input_stream(&device, &config, sample_format, channels, capture)?;
         stream.play().map_err(|err| err.to_string())?;
+        if let Some((system_stream, _)) = system {
+            system_stream.play().map_err(|err| err.to_string())?;
+            let mut system_guard = state.system_stream.lock().map_err(|err| err.to_string())?;
+            *system_guard = Some(system_stream);
+        }
 
         let mut stream_guard = state.stream.lock().map_err(|err| err.to_string())?;
         *stream_guard = Some(stream);
@@ -172,32 +867,59 @@ pub fn start_recording(state: &AudioState, device_id: Option<&str>) -> Result<()
         Ok(())
     })();
 
-    if let Err(err) = operation {
-        state.is_recording.store(false, Ordering::SeqCst);
-        return Err(err);
+    match operation {
+        Ok(()) => match state.transition(PhaseEvent::Opened) {
+            Ok(_) => Ok(()),
+            // The watchdog gave up on this start while the device was opening
+            Err(err) => {
+                state.close_stream();
+                Err(err)
+            }
+        },
+        Err(err) => {
+            // A stream stored before the failure would otherwise keep the device open
+            state.close_stream();
+            state.is_paused.store(false, Ordering::SeqCst);
+            state.transition(PhaseEvent::Failed)?;
+            Err(err)
+        }
     }
-
-    Ok(())
 }
 
-pub fn stop_recording(state: &AudioState) -> Result<Vec<u8>, String> {
-    if !state.is_recording.swap(false, Ordering::SeqCst) {
+/// Pause or resume the running recording. Returns whether that changed anything.
+pub fn set_paused(state: &AudioState, paused: bool) -> Result<bool, String> {
+    if !state.is_recording.load(Ordering::SeqCst) {
         return Err("Recording is not active".to_string());
     }
+    Ok(state.is_paused.swap(paused, Ordering::SeqCst) != paused)
+}
 
-    {
-        let mut stream_guard = state.stream.lock().map_err(|err| err.to_string())?;
-        if stream_guard.is_none() {
-            return Err("Audio stream was not initialized".to_string());
-        }
-        stream_guard.take();
-    }
+/// Stop capturing and return the mono samples with their sample rate, preprocessed
+pub fn take_recording(state: &AudioState) -> Result<Recording, String> {
+    state.transition(PhaseEvent::Stop)?;
+    state.is_paused.store(false, Ordering::SeqCst);
+    state.close_stream();
+    // Back to idle even if the audio can't be had, so the next recording can start
+    let taken = take_samples(state);
+    state.transition(PhaseEvent::Closed)?;
+    let (samples, sample_rate, dropped) = taken?;
+
+    let (samples, trimmed) = state.preprocessing().run(samples, sample_rate);
+    Ok(Recording {
+        samples,
+        sample_rate,
+        trimmed_start: dropped + trimmed,
+    })
+}
 
-    let samples = {
+/// The captured audio, its rate and the samples dropped at the cap, leaving the
+/// buffer empty
+fn take_samples(state: &AudioState) -> Result<(Vec<f32>, u32, usize), String> {
+    let (samples, dropped) = {
         let mut buffer = state.samples.lock().map_err(|err| err.to_string())?;
         let captured = buffer.clone();
         buffer.clear();
-        captured
+        (captured, state.dropped.swap(0, Ordering::SeqCst))
     };
 
     let sample_rate = {
@@ -206,11 +928,92 @@ pub fn stop_recording(state: &AudioState) -> Result<Vec<u8>, String> {
         *rate = None;
         stored
     };
+    Ok((samples, sample_rate, dropped))
+}
+
+/// What `probe_input` captured
+pub struct Probe {
+    pub samples: Vec<f32>,
+    pub sample_rate: u32,
+    pub device: Option<String>,
+}
+
+impl Probe {
+    pub fn peak(&self) -> f32 {
+        self.samples
+            .iter()
+            .fold(0.0f32, |peak, s| peak.max(s.abs()))
+    }
+}
+
+/// Record for `duration` on a throwaway stream, leaving the app's own recording state
+/// alone; for the self-check and the microphone test. `opened` is called with the
+/// probe once its stream is open.
+pub fn probe_input(
+    host: Option<String>,
+    device_id: Option<&str>,
+    channel_index: Option<u16>,
+    duration: Duration,
+    opened: impl FnOnce(&AudioState),
+) -> Result<Probe, String> {
+    let probe = AudioState::new();
+    probe.set_host(host);
+    start_recording(&probe, device_id, channel_index)?;
+    opened(&probe);
+    std::thread::sleep(duration);
 
-    encode_wav(&samples, sample_rate)
+    let samples = probe.samples.lock().map(|buffer| buffer.clone());
+    let sample_rate = probe.sample_rate.lock().ok().and_then(|rate| *rate);
+    let device = probe.device_name();
+    cancel_recording(&probe);
+    Ok(Probe {
+        samples: samples.map_err(|err| err.to_string())?,
+        sample_rate: sample_rate.ok_or_else(|| "Sample rate missing".to_string())?,
+        device,
+    })
+}
+
+/// Stop capturing and throw the audio away. Returns false if nothing was recording.
+pub fn cancel_recording(state: &AudioState) -> bool {
+    if state.transition(PhaseEvent::Stop).is_err() {
+        return false;
+    }
+    state.is_paused.store(false, Ordering::SeqCst);
+
+    state.close_stream();
+    if let Ok(mut buffer) = state.samples.lock() {
+        buffer.clear();
+    }
+    if let Ok(mut rate) = state.sample_rate.lock() {
+        *rate = None;
+    }
+    let _ = state.transition(PhaseEvent::Closed);
+    true
+}
+
+/// Give up on a start that never finished opening the device, because it hung or
+/// panicked part way. Returns false if no recording was starting.
+pub fn abandon_start(state: &AudioState) -> bool {
+    if state.transition(PhaseEvent::Failed).is_err() {
+        return false;
+    }
+    state.close_stream();
+    state.is_paused.store(false, Ordering::SeqCst);
+    true
 }
 
 pub fn encode_wav(samples: &[f32], sample_rate: u32) -> Result<Vec<u8>, String> {
+    encode_wav_into(Vec::new(), samples, sample_rate)
+}
+
+/// `encode_wav` into `buffer`, which is cleared first; its allocation is reused
+pub fn encode_wav_into(
+    mut buffer: Vec<u8>,
+    samples: &[f32],
+    sample_rate: u32,
+) -> Result<Vec<u8>, String> {
+    buffer.clear();
+    buffer.reserve(44 + samples.len() * 4);
     let spec = WavSpec {
         channels: 1,
         sample_rate,
@@ -218,7 +1021,7 @@ pub fn encode_wav(samples: &[f32], sample_rate: u32) -> Result<Vec<u8>, String>
         sample_format: HoundSampleFormat::Float,
     };
 
-    let mut cursor = Cursor::new(Vec::new());
+    let mut cursor = Cursor::new(buffer);
     {
         let mut writer = WavWriter::new(&mut cursor, spec).map_err(|err| err.to_string())?;
         for &sample in samples {
@@ -229,34 +1032,399 @@ pub fn encode_wav(samples: &[f32], sample_rate: u32) -> Result<Vec<u8>, String>
     Ok(cursor.into_inner())
 }
 
+/// `i16 44100-48000 Hz 2 ch`, or a single rate when min and max agree
+fn describe_range(range: &SupportedStreamConfigRange) -> String {
+    let rate = if range.min_sample_rate() == range.max_sample_rate() {
+        range.max_sample_rate().to_string()
+    } else {
+        format!("{}-{}", range.min_sample_rate(), range.max_sample_rate())
+    };
+    format!(
+        "{} {rate} Hz {} ch",
+        range.sample_format(),
+        range.channels()
+    )
+}
+
+fn format_range(range: &SupportedStreamConfigRange) -> CaptureFormatRange {
+    let (min_buffer_size, max_buffer_size) = match *range.buffer_size() {
+        SupportedBufferSize::Range { min, max } => (Some(min), Some(max)),
+        SupportedBufferSize::Unknown => (None, None),
+    };
+    CaptureFormatRange {
+        sample_format: range.sample_format().to_string(),
+        channels: range.channels(),
+        min_sample_rate: range.min_sample_rate(),
+        max_sample_rate: range.max_sample_rate(),
+        min_buffer_size,
+        max_buffer_size,
+    }
+}
+
+/// The formats `device_id` (the default microphone if None) can capture, its default
+/// and the format pinned for it in `device_formats`
+pub fn device_capabilities(
+    state: &AudioState,
+    device_id: Option<&str>,
+) -> Result<DeviceCapabilities, String> {
+    let host = open_host(state.host_name().as_deref());
+    let device = match device_id {
+        Some(id) => find_input_device(&host, id)?,
+        None => host
+            .default_input_device()
+            .ok_or_else(|| "No default input device available".to_string())?,
+    };
+    let name = device
+        .description()
+        .map_err(|err| err.to_string())?
+        .name()
+        .to_string();
+    let id = device.id().map_err(|err| err.to_string())?;
+    let default = device.default_input_config().ok();
+    let formats = device
+        .supported_input_configs()
+        .map(|configs| configs.map(|range| format_range(&range)).collect())
+        .unwrap_or_default();
+    Ok(DeviceCapabilities {
+        id: format!("{id:?}"),
+        name,
+        default_sample_format: default.as_ref().map(|c| c.sample_format().to_string()),
+        default_sample_rate: default.as_ref().map(|c| c.sample_rate()),
+        formats,
+        pinned: state.format_override(&device),
+    })
+}
+
+pub fn parse_sample_format(name: &str) -> Result<SampleFormat, String> {
+    const FORMATS: [SampleFormat; 12] = [
+        SampleFormat::I8,
+        SampleFormat::I16,
+        SampleFormat::I24,
+        SampleFormat::I32,
+        SampleFormat::I64,
+        SampleFormat::U8,
+        SampleFormat::U16,
+        SampleFormat::U24,
+        SampleFormat::U32,
+        SampleFormat::U64,
+        SampleFormat::F32,
+        SampleFormat::F64,
+    ];
+    FORMATS
+        .into_iter()
+        .find(|format| format.to_string().eq_ignore_ascii_case(name.trim()))
+        .ok_or_else(|| format!("Unknown sample format '{name}', expected e.g. i16 or f32"))
+}
+
+/// Pick the supported config matching a forced format. Only what the device lists is
+/// accepted; without a forced rate the default rate is kept when the format allows it.
+fn choose_config(
+    mut supported: Vec<SupportedStreamConfigRange>,
+    wanted: &DeviceFormat,
+    default_rate: u32,
+) -> Result<SupportedStreamConfig, String> {
+    let format = wanted
+        .sample_format
+        .as_deref()
+        .map(parse_sample_format)
+        .transpose()?;
+    let listed: Vec<String> = supported.iter().map(describe_range).collect();
+    // Best candidates first, by cpal's own ranking
+    supported.sort_by(|a, b| b.cmp_default_heuristics(a));
+    let mut candidates = supported
+        .into_iter()
+        .filter(|range| format.is_none_or(|format| range.sample_format() == format))
+        .peekable();
+    let chosen = match wanted.sample_rate {
+        Some(rate) => candidates.find_map(|range| range.try_with_sample_rate(rate)),
+        None => {
+            let first = candidates.peek().cloned();
+            candidates
+                .find_map(|range| range.try_with_sample_rate(default_rate))
+                .or_else(|| first.map(|range| range.with_max_sample_rate()))
+        }
+    };
+    chosen.ok_or_else(|| {
+        let format = wanted.sample_format.as_deref().unwrap_or("any format");
+        let rate = wanted
+            .sample_rate
+            .map_or_else(|| "any rate".to_string(), |rate| format!("{rate} Hz"));
+        format!(
+            "The device can't record {format} at {rate}; it supports: {}",
+            listed.join(", ")
+        )
+    })
+}
+
+/// The buffer size pinned for a device, if the driver accepts it
+fn choose_buffer_size(
+    supported: &SupportedBufferSize,
+    frames: Option<u32>,
+) -> Result<BufferSize, String> {
+    match (frames, supported) {
+        (None, _) => Ok(BufferSize::Default),
+        (Some(frames), SupportedBufferSize::Range { min, max })
+            if !(*min..=*max).contains(&frames) =>
+        {
+            Err(format!(
+                "The device takes buffers of {min}-{max} frames, not {frames}"
+            ))
+        }
+        (Some(frames), _) => Ok(BufferSize::Fixed(frames)),
+    }
+}
+
+/// The config asked for by a device's format override
+fn forced_config(
+    device: &cpal::Device,
+    wanted: &DeviceFormat,
+) -> Result<SupportedStreamConfig, String> {
+    let supported = device
+        .supported_input_configs()
+        .map_err(|err| err.to_string())?
+        .collect();
+    // The default may be what's broken, but its rate is still a sensible fallback
+    let default_rate = device
+        .default_input_config()
+        .map(|config| config.sample_rate())
+        .unwrap_or(48_000);
+    choose_config(supported, wanted, default_rate)
+}
+
 fn find_input_device(host: &cpal::Host, device_id: &str) -> Result<cpal::Device, String> {
     let devices = host.input_devices().map_err(|err| err.to_string())?;
+    find_device(devices, device_id)?.ok_or_else(|| format!("Input device '{device_id}' not found"))
+}
+
+/// The device whose id or name is `device_id`
+fn find_device(
+    devices: impl Iterator<Item = cpal::Device>,
+    device_id: &str,
+) -> Result<Option<cpal::Device>, String> {
     for device in devices {
         let description = device.description().map_err(|err| err.to_string())?;
         let name = description.name().to_string();
         let id = device.id().map_err(|err| err.to_string())?;
         let id = format!("{id:?}");
         if id == device_id || name == device_id {
-            return Ok(device);
+            return Ok(Some(device));
+        }
+    }
+    Ok(None)
+}
+
+/// Open the system audio stream for meeting mode, paused along with the microphone.
+/// An input device is recorded as is; an output device through loopback, which not
+/// every platform offers.
+fn open_system_audio(
+    host: &cpal::Host,
+    meeting: &MeetingCapture,
+    paused: Arc<AtomicBool>,
+) -> Result<(Stream, Arc<SystemAudio>), String> {
+    let device_id = meeting.system_device_id.as_deref();
+    let input = match device_id {
+        Some(id) => {
+            let inputs = host.input_devices().map_err(|err| err.to_string())?;
+            find_device(inputs, id)?
+        }
+        None => None,
+    };
+    let (device, supported_config) = match input {
+        Some(device) => {
+            let config = device
+                .default_input_config()
+                .map_err(|err| err.to_string())?;
+            (device, config)
+        }
+        None => {
+            let device = match device_id {
+                Some(id) => {
+                    let outputs = host.output_devices().map_err(|err| err.to_string())?;
+                    find_device(outputs, id)?.ok_or_else(|| format!("Device '{id}' not found"))?
+                }
+                None => host
+                    .default_output_device()
+                    .ok_or_else(|| "No default output device available".to_string())?,
+            };
+            let config = device
+                .default_output_config()
+                .map_err(|err| err.to_string())?;
+            (device, config)
+        }
+    };
+    let sample_format = supported_config.sample_format();
+    let channels = supported_config.channels();
+    let system = SystemAudio::new(supported_config.sample_rate(), paused).with_gains(
+        db_to_gain(meeting.mic_gain_db),
+        db_to_gain(meeting.system_gain_db),
+    );
+    let system = Arc::new(system);
+    let config: StreamConfig = supported_config.into();
+    let stream = open_input_stream(&device, &config, sample_format, channels, system.clone())?;
+    Ok((stream, system))
+}
+
+/// The capture high-pass for a stream at `sample_rate`, unless it is off, comes later
+/// in the preprocessing pipeline, or the cutoff isn't below the stream's Nyquist frequency
+fn high_pass(state: &AudioState, sample_rate: u32) -> Option<Mutex<Biquad>> {
+    let pipeline = state.preprocessing();
+    let cutoff_hz = pipeline.high_pass_hz;
+    (pipeline.filters_capture() && cutoff_hz > 0 && cutoff_hz < sample_rate / 2)
+        .then(|| Mutex::new(Biquad::new(cutoff_hz as f32, sample_rate, true)))
+}
+
+/// Where the capture callback puts what it hears
+struct Capture {
+    samples: Arc<Mutex<Vec<f32>>>,
+    level: Arc<LevelMeter>,
+    paused: Arc<AtomicBool>,
+    limit: CaptureLimit,
+    dropped: Arc<AtomicUsize>,
+    limit_reached: Arc<AtomicBool>,
+    sample_rate: u32,
+    /// Index of the only channel kept; None mixes them all
+    channel: Option<usize>,
+    /// System audio to mix in, in meeting mode
+    system: Option<Arc<SystemAudio>>,
+    /// Run on the microphone before anything else sees it; keeps its state between
+    /// callbacks so the filter doesn't restart at every buffer
+    high_pass: Option<Mutex<Biquad>>,
+    /// With `HalfDuplex::MuteMicrophone`, set while a reply is audible; the microphone
+    /// is dropped meanwhile like when paused
+    reply_audible: Option<Arc<AtomicBool>>,
+}
+
+/// System audio recorded alongside the microphone in meeting mode, waiting for the
+/// microphone's callback to mix it in
+struct SystemAudio {
+    pending: Mutex<VecDeque<f32>>,
+    sample_rate: u32,
+    paused: Arc<AtomicBool>,
+    /// Linear gains the microphone and the system audio are mixed at
+    mic_gain: f32,
+    system_gain: f32,
+}
+
+impl SystemAudio {
+    fn new(sample_rate: u32, paused: Arc<AtomicBool>) -> Self {
+        Self {
+            pending: Mutex::new(VecDeque::new()),
+            sample_rate,
+            paused,
+            mic_gain: 1.0,
+            system_gain: 1.0,
+        }
+    }
+
+    fn with_gains(self, mic_gain: f32, system_gain: f32) -> Self {
+        Self {
+            mic_gain,
+            system_gain,
+            ..self
+        }
+    }
+
+    /// Queue a callback's worth of audio, averaged to mono
+    fn push<T: Sample>(&self, input: &[T], channels: u16) {
+        if channels == 0 || self.paused.load(Ordering::Relaxed) {
+            return;
+        }
+        let mono = input
+            .chunks(channels as usize)
+            .map(|frame| downmix(frame, None));
+        let max = (self.sample_rate as f32 * MAX_SYSTEM_LAG_SECS) as usize;
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.extend(mono);
+            let excess = pending.len().saturating_sub(max);
+            pending.drain(..excess);
+        }
+    }
+
+    /// Mix the queued audio into `mic`, captured at `mic_rate`, each at its gain.
+    /// Whatever hasn't arrived yet counts as silence.
+    fn mix_into(&self, mic: &mut [f32], mic_rate: u32) {
+        if mic.is_empty() || mic_rate == 0 {
+            return;
+        }
+        if self.mic_gain != 1.0 {
+            for sample in mic.iter_mut() {
+                *sample = (*sample * self.mic_gain).clamp(-1.0, 1.0);
+            }
+        }
+        let wanted = (mic.len() as u64 * self.sample_rate as u64).div_ceil(mic_rate as u64);
+        let taken: Vec<f32> = match self.pending.lock() {
+            Ok(mut pending) => {
+                let count = (wanted as usize).min(pending.len());
+                pending.drain(..count).collect()
+            }
+            Err(_) => return,
+        };
+        let system = resample_linear(&taken, self.sample_rate, mic_rate);
+        for (sample, other) in mic.iter_mut().zip(system) {
+            *sample = (*sample + other * self.system_gain).clamp(-1.0, 1.0);
         }
     }
+}
+
+/// What a capture stream's callback hands its audio to
+trait InputSink: Send + 'static {
+    fn accept<T: Sample>(&self, input: &[T], channels: u16);
+}
 
-    Err(format!("Input device '{device_id}' not found"))
+impl InputSink for Capture {
+    fn accept<T: Sample>(&self, input: &[T], channels: u16) {
+        capture_input_data(input, channels, self);
+    }
 }
 
-fn build_input_stream<T>(
+impl InputSink for Arc<SystemAudio> {
+    fn accept<T: Sample>(&self, input: &[T], channels: u16) {
+        self.push(input, channels);
+    }
+}
+
+/// Build a capture stream in the device's own sample format
+fn open_input_stream<S: InputSink>(
     device: &cpal::Device,
     config: &StreamConfig,
+    sample_format: SampleFormat,
     channels: u16,
-    samples: Arc<Mutex<Vec<f32>>>,
+    sink: S,
+) -> Result<Stream, String> {
+    match sample_format {
+        SampleFormat::I8 => build_input_stream::<i8, S>(device, config, channels, sink),
+        SampleFormat::I16 => build_input_stream::<i16, S>(device, config, channels, sink),
+        SampleFormat::I24 => build_input_stream::<cpal::I24, S>(device, config, channels, sink),
+        SampleFormat::I32 => build_input_stream::<i32, S>(device, config, channels, sink),
+        SampleFormat::I64 => build_input_stream::<i64, S>(device, config, channels, sink),
+        SampleFormat::U8 => build_input_stream::<u8, S>(device, config, channels, sink),
+        SampleFormat::U16 => build_input_stream::<u16, S>(device, config, channels, sink),
+        SampleFormat::U24 => build_input_stream::<cpal::U24, S>(device, config, channels, sink),
+        SampleFormat::U32 => build_input_stream::<u32, S>(device, config, channels, sink),
+        SampleFormat::U64 => build_input_stream::<u64, S>(device, config, channels, sink),
+        SampleFormat::F32 => build_input_stream::<f32, S>(device, config, channels, sink),
+        SampleFormat::F64 => build_input_stream::<f64, S>(device, config, channels, sink),
+        SampleFormat::DsdU8 | SampleFormat::DsdU16 | SampleFormat::DsdU32 => {
+            Err("DSD sample formats are not supported".to_string())
+        }
+        _ => Err(format!("Unsupported sample format '{sample_format}'")),
+    }
+}
+
+fn build_input_stream<T, S>(
+    device: &cpal::Device,
+    config: &StreamConfig,
+    channels: u16,
+    sink: S,
 ) -> Result<Stream, String>
 where
     T: cpal::SizedSample + Sample,
+    S: InputSink,
 {
     let stream = device
         .build_input_stream(
             config,
-            move |data: &[T], _| capture_input_data(data, channels, &samples),
+            move |data: &[T], _| sink.accept(data, channels),
             handle_stream_error,
             None,
         )
@@ -265,26 +1433,617 @@ where
     Ok(stream)
 }
 
-fn capture_input_data<T: Sample>(input: &[T], channels: u16, samples: &Arc<Mutex<Vec<f32>>>) {
-    if channels == 0 {
+/// One frame as a mono sample: the `channel` asked for, or the average of all of them
+/// so a microphone on any input of an interface is heard
+fn downmix<T: Sample>(frame: &[T], channel: Option<usize>) -> f32 {
+    let value = |sample: &T| sample.to_float_sample().to_sample::<f32>();
+    match channel.and_then(|c| frame.get(c)) {
+        Some(sample) => value(sample),
+        None if frame.is_empty() => 0.0,
+        None => frame.iter().map(value).sum::<f32>() / frame.len() as f32,
+    }
+}
+
+fn capture_input_data<T: Sample>(input: &[T], channels: u16, capture: &Capture) {
+    if channels == 0 || capture.paused.load(Ordering::Relaxed) {
         return;
     }
+    if let Some(audible) = &capture.reply_audible {
+        if audible.load(Ordering::Relaxed) {
+            return;
+        }
+    }
 
-    let channel_count = channels as usize;
-    let mut collected = Vec::with_capacity(input.len() / channel_count);
-    for frame in input.chunks(channel_count) {
-        if let Some(sample) = frame.first() {
-            let value = sample.to_float_sample().to_sample::<f32>();
-            collected.push(value);
+    let mut collected: Vec<f32> = input
+        .chunks(channels as usize)
+        .map(|frame| downmix(frame, capture.channel))
+        .collect();
+    // Only the microphone: system audio has no rumble from a desk or a cheap preamp
+    if let Some(filter) = &capture.high_pass {
+        if let Ok(mut filter) = filter.lock() {
+            filter.process(&mut collected);
         }
     }
+    if let Some(system) = &capture.system {
+        system.mix_into(&mut collected, capture.sample_rate);
+    }
 
-    match samples.lock() {
-        Ok(mut buffer) => buffer.extend(collected),
+    capture.level.push(&collected);
+    match capture.samples.lock() {
+        Ok(mut buffer) => {
+            let (dropped, reached) = append_capped(&mut buffer, collected, capture.limit);
+            if dropped > 0 {
+                capture.dropped.fetch_add(dropped, Ordering::SeqCst);
+            }
+            if reached {
+                capture.limit_reached.store(true, Ordering::SeqCst);
+            }
+        }
         Err(err) => eprintln!("Failed to lock audio buffer: {err}"),
     }
 }
 
+/// Append captured samples within `limit`. Returns how many samples were dropped
+/// from the front of the buffer and whether the cap was reached.
+fn append_capped(buffer: &mut Vec<f32>, fresh: Vec<f32>, limit: CaptureLimit) -> (usize, bool) {
+    let max = limit.max_samples();
+    if max == 0 || buffer.len() + fresh.len() <= max {
+        buffer.extend(fresh);
+        return (0, false);
+    }
+    match limit.overflow {
+        CaptureOverflow::Stop => {
+            let room = max.saturating_sub(buffer.len());
+            buffer.extend(fresh.into_iter().take(room));
+            (0, true)
+        }
+        CaptureOverflow::DropOldest => {
+            buffer.extend(fresh);
+            // A tenth of the cap goes at once, rather than shifting the buffer every callback
+            let excess = (buffer.len() - max + max / 10).min(buffer.len());
+            buffer.drain(..excess);
+            (excess, true)
+        }
+    }
+}
+
 fn handle_stream_error(err: cpal::StreamError) {
     eprintln!("Audio stream error: {err}");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cpal::SupportedBufferSize;
+
+    fn range(format: SampleFormat, min: u32, max: u32) -> SupportedStreamConfigRange {
+        SupportedStreamConfigRange::new(2, min, max, SupportedBufferSize::Unknown, format)
+    }
+
+    #[test]
+    fn test_forced_format_keeps_default_rate_when_supported() {
+        let supported = vec![
+            range(SampleFormat::U8, 8_000, 48_000),
+            range(SampleFormat::I16, 44_100, 48_000),
+        ];
+        let wanted = DeviceFormat {
+            sample_format: Some("I16".to_string()),
+            ..DeviceFormat::default()
+        };
+        let config = choose_config(supported.clone(), &wanted, 48_000).unwrap();
+        assert_eq!(config.sample_format(), SampleFormat::I16);
+        assert_eq!(config.sample_rate(), 48_000);
+
+        // The default rate isn't available in i16, so the highest one is used
+        let config = choose_config(supported, &wanted, 16_000).unwrap();
+        assert_eq!(config.sample_rate(), 48_000);
+    }
+
+    #[test]
+    fn test_unsupported_override_lists_what_the_device_offers() {
+        let supported = vec![range(SampleFormat::I16, 44_100, 48_000)];
+        let wanted = DeviceFormat {
+            sample_format: Some("i16".to_string()),
+            sample_rate: Some(96_000),
+            ..DeviceFormat::default()
+        };
+        let err = choose_config(supported.clone(), &wanted, 48_000).unwrap_err();
+        assert!(err.contains("i16 44100-48000 Hz 2 ch"), "{err}");
+
+        let wanted = DeviceFormat {
+            sample_format: Some("s16le".to_string()),
+            ..DeviceFormat::default()
+        };
+        assert!(choose_config(supported, &wanted, 48_000).is_err());
+    }
+
+    #[test]
+    fn test_pinned_buffer_size_must_be_in_the_drivers_range() {
+        let range = SupportedBufferSize::Range { min: 64, max: 4096 };
+        assert_eq!(choose_buffer_size(&range, None), Ok(BufferSize::Default));
+        assert_eq!(
+            choose_buffer_size(&range, Some(512)),
+            Ok(BufferSize::Fixed(512))
+        );
+        let err = choose_buffer_size(&range, Some(32)).unwrap_err();
+        assert!(err.contains("64-4096"), "{err}");
+        // A driver that doesn't say is given what was asked for
+        assert_eq!(
+            choose_buffer_size(&SupportedBufferSize::Unknown, Some(32)),
+            Ok(BufferSize::Fixed(32))
+        );
+    }
+
+    #[test]
+    fn test_level_meter_reports_rms_and_peak_once() {
+        let meter = LevelMeter::default();
+        assert!(meter.take().is_none());
+
+        meter.push(&[0.5, -0.5]);
+        meter.push(&[1.0, -1.0]);
+        let level = meter.take().unwrap();
+        // RMS of [0.5, 0.5, 1, 1] is sqrt(0.625)
+        assert!((level.rms_db - to_dbfs(0.625f32.sqrt())).abs() < 0.01);
+        assert!(level.peak_db.abs() < 0.01);
+        assert!(meter.take().is_none());
+    }
+
+    #[test]
+    fn test_level_meter_counts_clipped_samples() {
+        let meter = LevelMeter::default();
+        assert!(meter.take_clipping().is_none());
+
+        meter.push(&[0.2, 0.995, -1.0, 0.5]);
+        // The level is read more often; that leaves the clipping count alone
+        meter.take();
+        assert_eq!(meter.take_clipping(), Some(50.0));
+        meter.push(&[0.98; 10]);
+        assert_eq!(meter.take_clipping(), Some(0.0));
+    }
+
+    #[test]
+    fn test_peak_pairs_keep_each_buckets_extremes() {
+        let samples = [0.1, -0.4, 0.3, 0.2, 0.5, 0.25, f32::NAN, f32::NAN, -0.9];
+        assert_eq!(
+            peak_pairs(&samples, 2),
+            vec![[-0.4, 0.1], [0.2, 0.3], [0.25, 0.5], [0.0, 0.0]]
+        );
+        assert!(peak_pairs(&samples, 0).is_empty());
+    }
+
+    #[test]
+    fn test_trim_keeps_sound_with_padding() {
+        let rate = 16_000;
+        let mut samples = vec![0.0; 8_000];
+        samples.extend(vec![0.3; 4_000]);
+        samples.extend(vec![0.001; 8_000]);
+        let trim = SilenceTrim {
+            enabled: true,
+            threshold_db: -50.0,
+            padding: Duration::from_millis(100),
+            calibrate: false,
+        };
+        assert_eq!(trim.keep(&samples, rate), 6_400..13_600);
+
+        assert_eq!(trim.keep(&[0.0; 4_000], rate), 0..0);
+        let off = SilenceTrim {
+            enabled: false,
+            ..trim
+        };
+        assert_eq!(off.keep(&samples, rate), 0..samples.len());
+
+        // Steady noise at -40 dBFS is sound to a -50 dBFS threshold, unless calibrated
+        let mut noisy = vec![0.01; 8_000];
+        noisy.extend(vec![0.3; 4_000]);
+        noisy.extend(vec![0.01; 8_000]);
+        assert_eq!(trim.keep(&noisy, rate), 0..noisy.len());
+        let calibrated = SilenceTrim {
+            calibrate: true,
+            ..trim
+        };
+        assert_eq!(calibrated.keep(&noisy, rate), 6_400..13_600);
+    }
+
+    #[test]
+    fn test_paused_capture_drops_audio() {
+        let capture = Capture {
+            samples: Arc::new(Mutex::new(Vec::new())),
+            level: Arc::new(LevelMeter::default()),
+            paused: Arc::new(AtomicBool::new(false)),
+            limit: CaptureLimit::default(),
+            dropped: Arc::new(AtomicUsize::new(0)),
+            limit_reached: Arc::new(AtomicBool::new(false)),
+            sample_rate: 16_000,
+            channel: Some(0),
+            system: None,
+            high_pass: None,
+            reply_audible: None,
+        };
+        capture_input_data(&[0.5f32, 0.1, -0.5, 0.1], 2, &capture);
+        capture.paused.store(true, Ordering::SeqCst);
+        capture_input_data(&[0.9f32, 0.9], 2, &capture);
+        capture.paused.store(false, Ordering::SeqCst);
+        capture_input_data(&[0.25f32, 0.0], 2, &capture);
+        assert_eq!(*capture.samples.lock().unwrap(), vec![0.5, -0.5, 0.25]);
+    }
+
+    #[test]
+    fn test_capture_high_pass_removes_dc_offset() {
+        let state = AudioState::new();
+        let capture = Capture {
+            samples: Arc::new(Mutex::new(Vec::new())),
+            level: Arc::new(LevelMeter::default()),
+            paused: Arc::new(AtomicBool::new(false)),
+            limit: CaptureLimit::default(),
+            dropped: Arc::new(AtomicUsize::new(0)),
+            limit_reached: Arc::new(AtomicBool::new(false)),
+            sample_rate: 16_000,
+            channel: None,
+            system: None,
+            high_pass: high_pass(&state, 16_000),
+            reply_audible: None,
+        };
+        // Half a second of a 1 kHz tone sitting on a 0.3 offset, 10 ms per callback
+        let input: Vec<f32> = (0..8_000)
+            .map(|i| 0.3 + 0.2 * (std::f32::consts::TAU * 1_000.0 * i as f32 / 16_000.0).sin())
+            .collect();
+        for chunk in input.chunks(160) {
+            capture_input_data(chunk, 1, &capture);
+        }
+        let samples = capture.samples.lock().unwrap();
+        let tail = &samples[4_000..];
+        let mean = tail.iter().sum::<f32>() / tail.len() as f32;
+        let peak = tail.iter().fold(0.0f32, |p, s| p.max(s.abs()));
+        assert!(mean.abs() < 0.005, "mean {mean}");
+        assert!((peak - 0.2).abs() < 0.02, "peak {peak}");
+
+        state.set_high_pass(0);
+        assert!(high_pass(&state, 16_000).is_none());
+        // A cutoff the stream can't carry is skipped rather than made unstable
+        state.set_high_pass(80);
+        assert!(high_pass(&state, 120).is_none());
+        // Later in the pipeline it runs on the finished recording instead
+        state.set_preprocessing(
+            vec![PreprocessStage::Trim, PreprocessStage::Highpass],
+            false,
+        );
+        assert!(high_pass(&state, 16_000).is_none());
+    }
+
+    #[test]
+    fn test_preprocessing_runs_stages_in_order() {
+        let rate = 16_000;
+        // Speech at -55 dBFS between half-second silences: below the trim threshold
+        // until the AGC has raised it
+        let tone = |i: usize| 0.0025 * (std::f32::consts::TAU * 440.0 * i as f32 / 16_000.0).sin();
+        let mut samples = vec![0.0f32; 8_000];
+        samples.extend((0..8_000).map(tone));
+        samples.extend(vec![0.0f32; 8_000]);
+        let pipeline = |stages| Preprocessing {
+            stages,
+            high_pass_hz: 80,
+            denoise: false,
+            trim: SilenceTrim {
+                enabled: true,
+                threshold_db: -50.0,
+                padding: Duration::ZERO,
+                calibrate: false,
+            },
+        };
+
+        let (trimmed, _) =
+            pipeline(vec![PreprocessStage::Trim, PreprocessStage::Agc]).run(samples.clone(), rate);
+        assert!(trimmed.is_empty());
+
+        let (raised, trimmed_start) =
+            pipeline(vec![PreprocessStage::Agc, PreprocessStage::Trim]).run(samples.clone(), rate);
+        assert!((8_000..9_600).contains(&trimmed_start), "{trimmed_start}");
+        assert!(trimmed_start + raised.len() <= 16_000);
+        assert!(raised.iter().fold(0.0f32, |p, s| p.max(s.abs())) > 0.01);
+
+        // A leading high-pass already ran as the audio was captured
+        let captured = pipeline(vec![PreprocessStage::Highpass]);
+        assert_eq!(captured.run(samples.clone(), rate), (samples, 0));
+        assert_eq!(captured.needs_whole_recording(), None);
+        let later = pipeline(vec![PreprocessStage::Trim, PreprocessStage::Highpass]);
+        assert_eq!(
+            later.needs_whole_recording(),
+            Some(PreprocessStage::Highpass)
+        );
+    }
+
+    #[test]
+    fn test_frames_are_downmixed_or_one_channel_is_kept() {
+        // A microphone on the second input of a stereo interface
+        let frame = [0.0f32, 0.6];
+        assert!((downmix(&frame, None) - 0.3).abs() < 1e-6);
+        assert_eq!(downmix(&frame, Some(1)), 0.6);
+        assert_eq!(downmix(&[0i16, 16_384], Some(1)), 0.5);
+        // A channel the frame doesn't have mixes them all
+        assert!((downmix(&frame, Some(4)) - 0.3).abs() < 1e-6);
+        assert_eq!(downmix::<f32>(&[], None), 0.0);
+    }
+
+    #[test]
+    fn test_microphone_is_muted_while_a_reply_is_audible() {
+        let state = AudioState::new();
+        let link = state.duplex_link();
+        let capture = Capture {
+            samples: Arc::new(Mutex::new(Vec::new())),
+            level: Arc::new(LevelMeter::default()),
+            paused: Arc::new(AtomicBool::new(false)),
+            limit: CaptureLimit::default(),
+            dropped: Arc::new(AtomicUsize::new(0)),
+            limit_reached: Arc::new(AtomicBool::new(false)),
+            sample_rate: 16_000,
+            channel: None,
+            system: None,
+            high_pass: None,
+            reply_audible: Some(state.reply_audible.clone()),
+        };
+        capture_input_data(&[0.5f32], 1, &capture);
+        link.set_reply_audible(true);
+        capture_input_data(&[0.9f32], 1, &capture);
+        link.set_reply_audible(false);
+        capture_input_data(&[0.25f32], 1, &capture);
+        assert_eq!(*capture.samples.lock().unwrap(), vec![0.5, 0.25]);
+
+        // Ducking only applies while recording
+        state.set_half_duplex(HalfDuplex::DuckPlayback);
+        assert!(!link.duck_playback());
+        state.transition(PhaseEvent::Start).unwrap();
+        assert!(!link.duck_playback());
+        state.transition(PhaseEvent::Opened).unwrap();
+        assert!(link.duck_playback());
+        state.set_half_duplex(HalfDuplex::Off);
+        assert!(!link.duck_playback());
+    }
+
+    #[test]
+    fn test_system_audio_is_mixed_into_the_microphone() {
+        let system = Arc::new(SystemAudio::new(16_000, Arc::new(AtomicBool::new(false))));
+        // Stereo, averaged to mono
+        system.push(&[0.2f32, 0.4, -0.2, -0.4], 2);
+        let capture = Capture {
+            samples: Arc::new(Mutex::new(Vec::new())),
+            level: Arc::new(LevelMeter::default()),
+            paused: Arc::new(AtomicBool::new(false)),
+            limit: CaptureLimit::default(),
+            dropped: Arc::new(AtomicUsize::new(0)),
+            limit_reached: Arc::new(AtomicBool::new(false)),
+            sample_rate: 16_000,
+            channel: None,
+            system: Some(system.clone()),
+            high_pass: None,
+            reply_audible: None,
+        };
+        capture_input_data(&[0.9f32, 0.1, 0.9], 1, &capture);
+        let mixed = capture.samples.lock().unwrap().clone();
+        // The sum is clipped, and the third sample's system audio hasn't arrived yet
+        assert_eq!(mixed[0], 1.0);
+        assert!((mixed[1] + 0.2).abs() < 1e-6);
+        assert_eq!(mixed[2], 0.9);
+
+        system.paused.store(true, Ordering::SeqCst);
+        system.push(&[0.5f32, 0.5], 2);
+        assert!(system.pending.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_meeting_sources_are_mixed_at_their_gains() {
+        let system = SystemAudio::new(16_000, Arc::new(AtomicBool::new(false)))
+            .with_gains(db_to_gain(-6.0), db_to_gain(6.0));
+        system.push(&[0.1f32], 1);
+        let mut mic = [0.4, 0.4];
+        system.mix_into(&mut mic, 16_000);
+        // Half the microphone plus twice the system audio, then the microphone alone
+        assert!((mic[0] - (0.2 + 0.2)).abs() < 0.01, "{mic:?}");
+        assert!((mic[1] - 0.2).abs() < 0.01, "{mic:?}");
+    }
+
+    #[test]
+    fn test_system_audio_lagging_behind_is_dropped() {
+        let system = SystemAudio::new(10, Arc::new(AtomicBool::new(false)));
+        let second: Vec<f32> = (0..10).map(|i| i as f32 / 10.0).collect();
+        system.push(&second, 1);
+        // Half a second at 10 Hz, the latest half
+        let pending: Vec<f32> = system.pending.lock().unwrap().iter().copied().collect();
+        assert_eq!(pending, second[5..].to_vec());
+    }
+
+    #[test]
+    fn test_capture_stops_growing_at_the_cap() {
+        // 10 samples
+        let limit = CaptureLimit {
+            max_bytes: 40,
+            overflow: CaptureOverflow::Stop,
+        };
+        let mut buffer: Vec<f32> = (0..8).map(|i| i as f32).collect();
+        assert_eq!(
+            append_capped(&mut buffer, vec![8.0, 9.0, 10.0], limit),
+            (0, true)
+        );
+        assert_eq!(buffer.len(), 10);
+        assert_eq!(append_capped(&mut buffer, vec![11.0], limit), (0, true));
+        assert_eq!(buffer.last(), Some(&9.0));
+
+        let unlimited = CaptureLimit {
+            max_bytes: 0,
+            ..limit
+        };
+        assert_eq!(
+            append_capped(&mut buffer, vec![11.0], unlimited),
+            (0, false)
+        );
+        assert_eq!(buffer.len(), 11);
+    }
+
+    #[test]
+    fn test_capture_drops_the_oldest_audio_past_the_cap() {
+        let limit = CaptureLimit {
+            max_bytes: 40,
+            overflow: CaptureOverflow::DropOldest,
+        };
+        let mut buffer: Vec<f32> = (0..8).map(|i| i as f32).collect();
+        assert_eq!(
+            append_capped(&mut buffer, vec![8.0, 9.0], limit),
+            (0, false)
+        );
+        // Two over the cap, plus a tenth of it
+        assert_eq!(
+            append_capped(&mut buffer, vec![10.0, 11.0], limit),
+            (3, true)
+        );
+        assert_eq!(buffer, (3..12).map(|i| i as f32).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_recording_phases_only_move_in_order() {
+        use PhaseEvent::{Closed, Failed, Opened, Start, Stop};
+        use RecordingPhase as Phase;
+        assert_eq!(Phase::Idle.next(Start), Ok(Phase::Starting));
+        assert_eq!(Phase::Starting.next(Opened), Ok(Phase::Recording));
+        assert_eq!(Phase::Starting.next(Failed), Ok(Phase::Idle));
+        assert_eq!(Phase::Recording.next(Stop), Ok(Phase::Stopping));
+        assert_eq!(Phase::Stopping.next(Closed), Ok(Phase::Idle));
+
+        let in_progress = Err("Recording already in progress".to_string());
+        assert_eq!(Phase::Starting.next(Start), in_progress);
+        assert_eq!(Phase::Recording.next(Start), in_progress);
+        assert!(Phase::Stopping.next(Start).is_err());
+        // Stopping a recording still opening would find no stream to close
+        assert_eq!(
+            Phase::Starting.next(Stop),
+            Err("Recording is not active".to_string())
+        );
+        assert!(Phase::Idle.next(Stop).is_err());
+        assert!(Phase::Idle.next(Opened).is_err());
+        assert!(Phase::Recording.next(Failed).is_err());
+        assert!(Phase::Idle.next(Closed).is_err());
+    }
+
+    #[test]
+    fn test_a_failed_start_or_stop_leaves_nothing_recording() {
+        let state = AudioState::new();
+        state.transition(PhaseEvent::Start).unwrap();
+        assert!(!state.is_recording.load(Ordering::SeqCst));
+        assert!(take_recording(&state).is_err());
+        state.transition(PhaseEvent::Failed).unwrap();
+        assert_eq!(state.phase(), RecordingPhase::Idle);
+
+        // The sample rate went missing: the stop fails, and a new recording can start
+        state.transition(PhaseEvent::Start).unwrap();
+        state.transition(PhaseEvent::Opened).unwrap();
+        assert!(state.is_recording.load(Ordering::SeqCst));
+        assert!(take_recording(&state).is_err());
+        assert_eq!(state.phase(), RecordingPhase::Idle);
+        assert!(!state.is_recording.load(Ordering::SeqCst));
+
+        state.transition(PhaseEvent::Start).unwrap();
+        state.transition(PhaseEvent::Opened).unwrap();
+        state.samples.lock().unwrap().extend([0.1, 0.2]);
+        *state.sample_rate.lock().unwrap() = Some(16_000);
+        state.set_preprocessing(Vec::new(), false);
+        let recording = take_recording(&state).unwrap();
+        assert_eq!(recording.samples, vec![0.1, 0.2]);
+        assert_eq!(state.phase(), RecordingPhase::Idle);
+        assert!(!cancel_recording(&state));
+    }
+
+    #[test]
+    fn test_a_stuck_start_can_be_abandoned() {
+        let state = AudioState::new();
+        assert!(!abandon_start(&state));
+        state.transition(PhaseEvent::Start).unwrap();
+        assert!(abandon_start(&state));
+        assert_eq!(state.phase(), RecordingPhase::Idle);
+        // A start that finishes after all can't leave the phase recording
+        assert!(state.transition(PhaseEvent::Opened).is_err());
+
+        state.transition(PhaseEvent::Start).unwrap();
+        state.transition(PhaseEvent::Opened).unwrap();
+        assert!(!abandon_start(&state));
+        assert_eq!(state.phase(), RecordingPhase::Recording);
+    }
+
+    #[test]
+    fn test_pausing_needs_a_recording() {
+        let state = AudioState::new();
+        assert!(set_paused(&state, true).is_err());
+        state.transition(PhaseEvent::Start).unwrap();
+        assert!(set_paused(&state, true).is_err());
+        state.transition(PhaseEvent::Opened).unwrap();
+        assert!(set_paused(&state, true).unwrap());
+        assert!(!set_paused(&state, true).unwrap());
+        assert!(set_paused(&state, false).unwrap());
+    }
+
+    #[test]
+    fn test_silence_is_floored() {
+        assert_eq!(to_dbfs(0.0), SILENCE_DB);
+        assert_eq!(to_dbfs(1e-9), SILENCE_DB);
+        assert!((to_dbfs(0.5) + 6.02).abs() < 0.01);
+    }
+
+    fn device(name: &str, id: &str) -> AudioDevice {
+        AudioDevice {
+            name: name.to_string(),
+            id: id.to_string(),
+            is_default: false,
+            supported_formats: Vec::new(),
+            alias: None,
+            favorite: false,
+        }
+    }
+
+    #[test]
+    fn test_favorites_are_listed_first_with_their_aliases() {
+        let devices = vec![
+            device("hw:0,0", "alsa:0"),
+            device("USB Audio CODEC", "alsa:1"),
+            device("hw:2,0", "alsa:2"),
+        ];
+        let mut labels = HashMap::new();
+        labels.insert(
+            "USB Audio CODEC".to_string(),
+            DeviceLabel {
+                alias: Some("Name only".to_string()),
+                favorite: false,
+            },
+        );
+        labels.insert(
+            "alsa:1".to_string(),
+            DeviceLabel {
+                alias: Some(" Desk mic ".to_string()),
+                favorite: true,
+            },
+        );
+        labels.insert(
+            "alsa:2".to_string(),
+            DeviceLabel {
+                alias: Some(String::new()),
+                favorite: true,
+            },
+        );
+
+        let labelled = label_devices(devices, &labels);
+        let ids: Vec<&str> = labelled.iter().map(|d| d.id.as_str()).collect();
+        assert_eq!(ids, vec!["alsa:1", "alsa:2", "alsa:0"]);
+        assert_eq!(labelled[0].alias.as_deref(), Some("Desk mic"));
+        assert_eq!(labelled[1].alias, None);
+        assert!(!labelled[2].favorite);
+    }
+
+    #[test]
+    fn test_hosts_are_found_in_any_case() {
+        let default = cpal::default_host().id();
+        let name = default.name().to_lowercase();
+        assert_eq!(find_host(&name), Some(default));
+        assert_eq!(find_host("NoSuchHost"), None);
+
+        let hosts = list_audio_hosts(Some("NoSuchHost"));
+        let selected: Vec<_> = hosts.iter().filter(|h| h.selected).collect();
+        assert_eq!(selected.len(), 1);
+        assert!(selected[0].is_default);
+    }
+}
@@ -0,0 +1,3 @@
+fn main() {
+    std::process::exit(openclaw_voice_client::run_cli())
+}
@@ -0,0 +1,128 @@
+use std::sync::{Arc, Mutex};
+
+use crate::turns::{self, TurnAggregator};
+use crate::types::VoiceEvent;
+
+/// Asks the gateway for a short, spoken-style reply
+pub const STYLE_HEADER: &str = "X-Reply-Style";
+/// The most sentences a brief reply is let through with
+pub const MAX_SENTENCES_HEADER: &str = "X-Reply-Max-Sentences";
+
+/// Brief mode, from `brief_mode` and `brief_max_sentences`
+pub struct BriefState {
+    pub max_sentences: Arc<Mutex<Option<usize>>>,
+}
+
+impl BriefState {
+    pub fn new() -> Self {
+        Self {
+            max_sentences: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn configure(&self, enabled: bool, max_sentences: u64) {
+        if let Ok(mut current) = self.max_sentences.lock() {
+            *current = enabled.then_some(max_sentences.max(1) as usize);
+        }
+    }
+
+    /// How many sentences replies are cut after, None while brief mode is off
+    pub fn limit(&self) -> Option<usize> {
+        self.max_sentences.lock().ok().and_then(|max| *max)
+    }
+}
+
+/// Cuts a streamed reply off after its first `max_sentences` sentences, for gateways
+/// that don't keep to `X-Reply-Max-Sentences` themselves
+pub struct BriefCap {
+    max_sentences: usize,
+    turn: TurnAggregator,
+}
+
+impl BriefCap {
+    pub fn new(max_sentences: usize) -> Self {
+        Self {
+            max_sentences,
+            turn: TurnAggregator::new(),
+        }
+    }
+
+    /// Feed one streamed event. Once the reply has reached the cap, returns the events
+    /// that finish it in place of this one; the rest of the stream is to be dropped.
+    pub fn cut(&mut self, event: &VoiceEvent) -> Option<Vec<VoiceEvent>> {
+        // The final event ends the reply anyway
+        let VoiceEvent::Openclaw {
+            done: false,
+            timestamp,
+            ..
+        } = event
+        else {
+            return None;
+        };
+        let before = self.turn.reply().len();
+        self.turn.push(event);
+        let reply = self.turn.reply();
+        let end = turns::end_of_sentences(reply, self.max_sentences)?;
+        let kept = reply[before.min(end)..end].to_string();
+
+        let mut finish = Vec::new();
+        if !kept.trim().is_empty() {
+            finish.push(VoiceEvent::Openclaw {
+                text: kept,
+                done: false,
+                timestamp: timestamp.clone(),
+            });
+        }
+        finish.push(VoiceEvent::Openclaw {
+            text: String::new(),
+            done: true,
+            timestamp: timestamp.clone(),
+        });
+        finish.push(VoiceEvent::System {
+            status: "done".to_string(),
+            message: Some(format!(
+                "Brief mode: stopped after {} sentences",
+                self.max_sentences
+            )),
+            timestamp: timestamp.clone(),
+        });
+        Some(finish)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delta(text: &str) -> VoiceEvent {
+        VoiceEvent::Openclaw {
+            text: text.to_string(),
+            done: false,
+            timestamp: "t".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_reply_is_cut_after_the_last_allowed_sentence() {
+        let mut cap = BriefCap::new(2);
+        assert!(cap.cut(&delta("Sunny today. Highs")).is_none());
+        assert!(cap.cut(&delta(" of 21.")).is_none());
+        // The second sentence only ends once something follows it, which isn't kept
+        let finish = cap.cut(&delta(" Bring a jacket.")).unwrap();
+        assert_eq!(finish.len(), 2);
+        assert!(matches!(
+            &finish[0],
+            VoiceEvent::Openclaw { done: true, .. }
+        ));
+        assert!(matches!(&finish[1], VoiceEvent::System { status, .. } if status == "done"));
+    }
+
+    #[test]
+    fn test_delta_is_cut_at_the_sentence_end() {
+        let mut cap = BriefCap::new(1);
+        assert!(cap.cut(&delta("Sunny")).is_none());
+        let finish = cap.cut(&delta(" today. Highs of 21.")).unwrap();
+        assert!(matches!(&finish[0], VoiceEvent::Openclaw { text, .. } if text == " today."));
+        assert_eq!(finish.len(), 3);
+    }
+}
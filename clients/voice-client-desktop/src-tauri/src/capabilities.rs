@@ -0,0 +1,237 @@
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use reqwest::{Client, StatusCode};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::api::Gateway;
+use crate::error::AppError;
+use crate::events;
+use crate::routing::Endpoint;
+use crate::types::{GatewayCapabilities, Support};
+
+/// An optional gateway endpoint the client works without
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Capability {
+    HistorySearch,
+    HistorySession,
+    Feedback,
+}
+
+impl Capability {
+    const ALL: [Capability; 3] = [
+        Capability::HistorySearch,
+        Capability::HistorySession,
+        Capability::Feedback,
+    ];
+
+    fn endpoint(self) -> Endpoint {
+        match self {
+            Self::HistorySearch => Endpoint::HistorySearch,
+            Self::HistorySession => Endpoint::HistorySession,
+            Self::Feedback => Endpoint::Feedback,
+        }
+    }
+
+    /// What the user would miss, for `UnsupportedByGateway`
+    fn feature(self) -> &'static str {
+        match self {
+            Self::HistorySearch => "searching its transcripts",
+            Self::HistorySession => "fetching stored transcripts",
+            Self::Feedback => "rating replies",
+        }
+    }
+
+    fn of(self, matrix: &GatewayCapabilities) -> Support {
+        match self {
+            Self::HistorySearch => matrix.history_search,
+            Self::HistorySession => matrix.history_session,
+            Self::Feedback => matrix.feedback,
+        }
+    }
+
+    fn set(self, matrix: &mut GatewayCapabilities, support: Support) {
+        match self {
+            Self::HistorySearch => matrix.history_search = support,
+            Self::HistorySession => matrix.history_session = support,
+            Self::Feedback => matrix.feedback = support,
+        }
+    }
+}
+
+/// What the last probe found, for the gateway it probed
+pub struct CapabilityState {
+    pub matrix: Arc<Mutex<Option<GatewayCapabilities>>>,
+}
+
+impl CapabilityState {
+    pub fn new() -> Self {
+        Self {
+            matrix: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn get(&self) -> Option<GatewayCapabilities> {
+        self.matrix.lock().ok().and_then(|matrix| matrix.clone())
+    }
+
+    fn set(&self, capabilities: GatewayCapabilities) {
+        if let Ok(mut matrix) = self.matrix.lock() {
+            *matrix = Some(capabilities);
+        }
+    }
+
+    /// Unknown until `base_url` has been probed
+    pub fn support(&self, base_url: &str, capability: Capability) -> Support {
+        self.get()
+            .filter(|matrix| matrix.gateway_url == base_url)
+            .map(|matrix| capability.of(&matrix))
+            .unwrap_or_default()
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Read a probe's answer. The probes leave out what the endpoints need, so a gateway
+/// that serves one answers 400 or similar; 404 and 501 mean it has no such route,
+/// unless the 404 is the plugin saying it doesn't know a session. Credentials and
+/// server errors say nothing either way, since they can come before routing.
+fn classify(status: StatusCode, body: &str) -> Support {
+    match status {
+        StatusCode::NOT_FOUND => match AppError::from_gateway_response(status, body) {
+            AppError::SessionNotFound => Support::Supported,
+            _ => Support::Unsupported,
+        },
+        StatusCode::NOT_IMPLEMENTED => Support::Unsupported,
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => Support::Unknown,
+        status if status.is_server_error() => Support::Unknown,
+        _ => Support::Supported,
+    }
+}
+
+async fn probe_one(gateway: &Gateway, base_url: &str, capability: Capability) -> Support {
+    let client = Client::new();
+    let Ok(url) = gateway.routes.url(base_url, capability.endpoint(), &[]) else {
+        return Support::Unknown;
+    };
+    // Feedback is only ever posted to; an empty body is rejected without recording anything
+    let request = match capability {
+        Capability::Feedback => client.post(url).json(&serde_json::json!({})),
+        Capability::HistorySearch | Capability::HistorySession => client.get(url),
+    };
+    match gateway.execute(request).await {
+        Ok(resp) => {
+            let status = resp.status();
+            let body = resp.text().await.unwrap_or_default();
+            classify(status, &body)
+        }
+        Err(_) => Support::Unknown,
+    }
+}
+
+/// Probe every optional endpoint of `base_url`, keep the matrix and emit
+/// "gateway-capabilities"
+pub async fn probe(app: &AppHandle, base_url: &str) -> GatewayCapabilities {
+    let gateway = Gateway::from_app(app);
+    let mut capabilities = GatewayCapabilities {
+        gateway_url: base_url.to_string(),
+        probed_at_ms: now_ms(),
+        ..GatewayCapabilities::default()
+    };
+    for capability in Capability::ALL {
+        let support = probe_one(&gateway, base_url, capability).await;
+        capability.set(&mut capabilities, support);
+    }
+    events::record_state(app, "gateway-capabilities", &capabilities);
+    app.state::<CapabilityState>().set(capabilities.clone());
+    let _ = app.emit("gateway-capabilities", &capabilities);
+    capabilities
+}
+
+/// Probe in the background once a session on `base_url` is up, unless it already was
+/// and nothing came back unknown
+pub fn probe_on_connect(app: &AppHandle, base_url: &str) {
+    let known = app.state::<CapabilityState>().get().is_some_and(|matrix| {
+        matrix.gateway_url == base_url
+            && Capability::ALL
+                .iter()
+                .all(|capability| capability.of(&matrix) != Support::Unknown)
+    });
+    if known {
+        return;
+    }
+    let app = app.clone();
+    let base_url = base_url.to_string();
+    tauri::async_runtime::spawn(async move {
+        probe(&app, &base_url).await;
+    });
+}
+
+/// Fail fast when the probe found `base_url` has no endpoint for `capability`; an
+/// unprobed gateway is given the benefit of the doubt
+pub fn require(app: &AppHandle, base_url: &str, capability: Capability) -> Result<(), AppError> {
+    match app.state::<CapabilityState>().support(base_url, capability) {
+        Support::Unsupported => Err(AppError::UnsupportedByGateway(
+            capability.feature().to_string(),
+        )),
+        Support::Supported | Support::Unknown => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_routes_are_told_from_plugin_answers() {
+        assert_eq!(
+            classify(StatusCode::NOT_FOUND, "Not Found"),
+            Support::Unsupported
+        );
+        assert_eq!(
+            classify(StatusCode::NOT_FOUND, r#"{"error":"Session not found"}"#),
+            Support::Supported
+        );
+        assert_eq!(
+            classify(StatusCode::NOT_IMPLEMENTED, ""),
+            Support::Unsupported
+        );
+        assert_eq!(
+            classify(StatusCode::BAD_REQUEST, "missing q"),
+            Support::Supported
+        );
+        assert_eq!(
+            classify(StatusCode::METHOD_NOT_ALLOWED, ""),
+            Support::Supported
+        );
+        assert_eq!(classify(StatusCode::UNAUTHORIZED, ""), Support::Unknown);
+        assert_eq!(classify(StatusCode::BAD_GATEWAY, ""), Support::Unknown);
+    }
+
+    #[test]
+    fn test_support_is_unknown_for_another_gateway() {
+        let state = CapabilityState::new();
+        state.set(GatewayCapabilities {
+            gateway_url: "http://a".to_string(),
+            feedback: Support::Unsupported,
+            ..GatewayCapabilities::default()
+        });
+        assert_eq!(
+            state.support("http://a", Capability::Feedback),
+            Support::Unsupported
+        );
+        assert_eq!(
+            state.support("http://a", Capability::HistorySearch),
+            Support::Unknown
+        );
+        assert_eq!(
+            state.support("http://b", Capability::Feedback),
+            Support::Unknown
+        );
+    }
+}
@@ -0,0 +1,37 @@
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::audio::AudioState;
+use crate::events;
+use crate::types::CaptureLimitReached;
+
+const INTERVAL: Duration = Duration::from_millis(250);
+
+/// Emit "capture-limit-reached" once the recording that just started reaches the
+/// memory cap. With the stop policy capturing has already stopped there, and the popup
+/// sends what was recorded.
+pub fn watch(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(INTERVAL).await;
+            let audio = app.state::<AudioState>();
+            if !audio.is_recording.load(Ordering::SeqCst) {
+                return;
+            }
+            if !audio.limit_reached.load(Ordering::SeqCst) {
+                continue;
+            }
+            let limit = audio.capture_limit.lock().map(|l| *l).unwrap_or_default();
+            let reached = CaptureLimitReached {
+                max_mb: (limit.max_bytes / (1024 * 1024)) as u64,
+                overflow: limit.overflow,
+            };
+            let _ = app.emit("capture-limit-reached", &reached);
+            events::record_state(&app, "capture-limit-reached", reached);
+            return;
+        }
+    });
+}
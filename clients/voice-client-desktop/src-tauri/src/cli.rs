@@ -0,0 +1,541 @@
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::api::{self, Gateway, Turn};
+use crate::error::AppError;
+use crate::file_transcription;
+use crate::http_log::HttpLog;
+use crate::ipc_client;
+use crate::persist;
+use crate::playback::decode_wav_mono;
+use crate::quality;
+use crate::routing::{self, Routes};
+use crate::schema;
+use crate::stt::SttConfig;
+use crate::types::{AppSettings, FileTranscriptionEvent, QualityPreset, VoiceEvent};
+
+/// Must match `identifier` in tauri.conf.json — the app's data dir is named after it
+const APP_IDENTIFIER: &str = "com.openclaw.voice-client";
+
+const USAGE: &str = "\
+Usage: openclaw-voice send [--stdin-wav | - | FILE] [options]
+       openclaw-voice transcribe FILE [--json]
+       openclaw-voice schema
+       openclaw-voice client
+
+Send a WAV recording to the gateway and stream the reply to stdout as it arrives.
+
+  --stdin-wav, -        Read the WAV from stdin (e.g. arecord -f S16_LE | openclaw-voice send -)
+  --json                Print every gateway event as one JSON object per line
+  --gateway URL         Gateway URL (default: from the app's settings)
+  --profile NAME        Profile name (default: from the app's settings)
+  --session ID          Reuse a session instead of starting a new one
+  --session-key KEY     Session key to share context with (default: from settings)
+  --language TAG        Reply language, e.g. de or pt-BR (default: from settings)
+  --workspace NAME      Workspace on a gateway serving several teams (default: from settings)
+  --brief               Ask for a short spoken-style reply, cut after brief_max_sentences
+                        sentences (default: brief_mode from settings)
+
+`transcribe` runs local whisper.cpp (whisper_model in settings) over a WAV file of any
+length and prints the transcript chunk by chunk; --json prints the progress events.
+`schema` prints the JSON Schema of the app's events, commands and settings; `client` prints
+the typed TypeScript wrappers of the commands that the webview imports as src/ipc.ts.";
+
+/// Where the audio comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AudioSource {
+    Stdin,
+    File(PathBuf),
+}
+
+/// How streamed gateway events are written to stdout
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Reply text only, written as it streams in; the transcript goes to stderr
+    #[default]
+    Text,
+    /// Every event as a JSON line, for other programs to consume
+    Json,
+}
+
+/// A parsed `send` invocation; unset options fall back to the app's saved settings
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SendOptions {
+    pub source: Option<AudioSource>,
+    pub format: OutputFormat,
+    pub gateway_url: Option<String>,
+    pub profile_name: Option<String>,
+    pub session_id: Option<String>,
+    pub session_key: Option<String>,
+    pub response_language: Option<String>,
+    pub workspace: Option<String>,
+    /// Brief mode for this reply, whether or not it is on in settings
+    pub brief: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CliCommand {
+    Send(SendOptions),
+    Transcribe { path: PathBuf, format: OutputFormat },
+    Schema,
+    Client,
+    Help,
+}
+
+pub fn parse_args(args: &[String]) -> Result<CliCommand, String> {
+    let mut args = args.iter();
+    match args.next().map(String::as_str) {
+        Some("send") => {}
+        Some("schema") => return Ok(CliCommand::Schema),
+        Some("client") => return Ok(CliCommand::Client),
+        Some("transcribe") => return parse_transcribe(args),
+        None | Some("help") | Some("--help") | Some("-h") => return Ok(CliCommand::Help),
+        Some(other) => return Err(format!("Unknown command '{other}'")),
+    }
+
+    let mut options = SendOptions::default();
+    while let Some(arg) = args.next() {
+        let mut value = |flag: &str| {
+            args.next()
+                .cloned()
+                .ok_or_else(|| format!("{flag} needs a value"))
+        };
+        match arg.as_str() {
+            "--stdin-wav" | "-" => options.source = Some(AudioSource::Stdin),
+            "--json" => options.format = OutputFormat::Json,
+            "--gateway" => options.gateway_url = Some(value(arg)?),
+            "--profile" => options.profile_name = Some(value(arg)?),
+            "--session" => options.session_id = Some(value(arg)?),
+            "--session-key" => options.session_key = Some(value(arg)?),
+            "--language" => options.response_language = Some(value(arg)?),
+            "--workspace" => options.workspace = Some(value(arg)?),
+            "--brief" => options.brief = true,
+            "--help" | "-h" => return Ok(CliCommand::Help),
+            flag if flag.starts_with("--") => return Err(format!("Unknown option '{flag}'")),
+            path => {
+                if options.source.is_some() {
+                    return Err("Only one audio input can be given".to_string());
+                }
+                options.source = Some(AudioSource::File(PathBuf::from(path)));
+            }
+        }
+    }
+
+    Ok(CliCommand::Send(options))
+}
+
+fn parse_transcribe<'a>(args: impl Iterator<Item = &'a String>) -> Result<CliCommand, String> {
+    let mut path = None;
+    let mut format = OutputFormat::Text;
+    for arg in args {
+        match arg.as_str() {
+            "--json" => format = OutputFormat::Json,
+            flag if flag.starts_with("--") => return Err(format!("Unknown option '{flag}'")),
+            file if path.is_none() => path = Some(PathBuf::from(file)),
+            _ => return Err("Only one file can be transcribed at a time".to_string()),
+        }
+    }
+    let path = path.ok_or_else(|| "No file given".to_string())?;
+    Ok(CliCommand::Transcribe { path, format })
+}
+
+/// Run the CLI and return the process exit code
+pub fn run(args: Vec<String>) -> i32 {
+    let command = match parse_args(&args) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("{e}\n\n{USAGE}");
+            return 2;
+        }
+    };
+
+    match command {
+        CliCommand::Help => {
+            println!("{USAGE}");
+            0
+        }
+        CliCommand::Schema => {
+            print!("{}", schema::export_pretty());
+            0
+        }
+        CliCommand::Client => {
+            print!("{}", ipc_client::generate());
+            0
+        }
+        CliCommand::Transcribe { path, format } => match transcribe(&path, format) {
+            Ok(()) => 0,
+            Err(e) => {
+                eprintln!("Error: {e}");
+                1
+            }
+        },
+        CliCommand::Send(options) => match tauri::async_runtime::block_on(send(options)) {
+            Ok(()) => 0,
+            Err(e) => {
+                eprintln!("Error: {e}");
+                if let Some(suggestion) = e.suggestion() {
+                    eprintln!("{suggestion}");
+                }
+                1
+            }
+        },
+    }
+}
+
+/// Settings saved by the desktop app, if it has been configured on this machine
+fn saved_settings() -> HashMap<String, Value> {
+    dirs::data_dir()
+        .map(|dir| dir.join(APP_IDENTIFIER).join(persist::STORE_FILE))
+        .and_then(|path| std::fs::read(path).ok())
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+fn saved_string(saved: &HashMap<String, Value>, key: &str) -> Option<String> {
+    saved
+        .get(key)
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+}
+
+/// Read the audio and encode it the way the app encodes its own recordings
+fn read_audio(source: &AudioSource, preset: QualityPreset) -> Result<Vec<u8>, String> {
+    let mut bytes = match source {
+        AudioSource::Stdin => {
+            let mut bytes = Vec::new();
+            std::io::stdin()
+                .read_to_end(&mut bytes)
+                .map_err(|e| format!("Failed to read stdin: {e}"))?;
+            bytes
+        }
+        AudioSource::File(path) => {
+            std::fs::read(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?
+        }
+    };
+    fix_streamed_wav(&mut bytes);
+
+    let (samples, sample_rate) = decode_wav_mono(&bytes)?;
+    if samples.is_empty() {
+        return Err("The recording is empty".to_string());
+    }
+    quality::encode(&samples, sample_rate, preset, None)
+}
+
+/// Recorders writing to a pipe can't seek back to fill in the chunk sizes, so they leave
+/// placeholders (0 or 0xFFFFFFFF). Point the RIFF and data sizes at what actually arrived.
+pub fn fix_streamed_wav(bytes: &mut [u8]) {
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return;
+    }
+    let riff_size = (bytes.len() - 8) as u32;
+    bytes[4..8].copy_from_slice(&riff_size.to_le_bytes());
+
+    let mut offset = 12;
+    while offset + 8 <= bytes.len() {
+        let id = &bytes[offset..offset + 4];
+        let size = u32::from_le_bytes([
+            bytes[offset + 4],
+            bytes[offset + 5],
+            bytes[offset + 6],
+            bytes[offset + 7],
+        ]) as usize;
+        let body = offset + 8;
+        if id == b"data" {
+            let available = (bytes.len() - body) as u32;
+            if size == 0 || size > available as usize {
+                bytes[offset + 4..offset + 8].copy_from_slice(&available.to_le_bytes());
+            }
+            return;
+        }
+        // Chunks are word-aligned
+        offset = body + size + (size % 2);
+    }
+}
+
+/// Write one streamed event to `out`. Text is flushed per delta so a reader sees it
+/// at the same pace the popup does.
+pub fn write_event(
+    out: &mut impl Write,
+    format: OutputFormat,
+    event: &VoiceEvent,
+) -> io::Result<()> {
+    match format {
+        OutputFormat::Json => {
+            serde_json::to_writer(&mut *out, event)?;
+            writeln!(out)?;
+        }
+        OutputFormat::Text => match event {
+            VoiceEvent::User { text, .. } => eprintln!("You said: {text}"),
+            VoiceEvent::Openclaw { text, done, .. } => {
+                write!(out, "{text}")?;
+                if *done {
+                    writeln!(out)?;
+                }
+            }
+            VoiceEvent::System { .. } | VoiceEvent::Audio { .. } => {}
+        },
+    }
+    out.flush()
+}
+
+fn saved_stt(saved: &HashMap<String, Value>) -> SttConfig {
+    SttConfig {
+        providers: saved
+            .get("stt_providers")
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_default(),
+        whisper_command: saved_string(saved, "whisper_command"),
+        whisper_model: saved_string(saved, "whisper_model"),
+        prefer_local: saved
+            .get("prefer_local_stt")
+            .and_then(Value::as_bool)
+            .unwrap_or_default(),
+        preview_model: saved_string(saved, "whisper_preview_model"),
+    }
+}
+
+/// Transcribe a file locally, printing each chunk as soon as whisper.cpp finishes it
+fn transcribe(path: &Path, format: OutputFormat) -> Result<(), AppError> {
+    let whisper = saved_stt(&saved_settings()).whisper()?;
+    let (samples, rate) = file_transcription::read_file(path)?;
+    let file = path.display().to_string();
+    let never = AtomicBool::new(false);
+    let mut stdout = io::stdout().lock();
+    let mut failure = None;
+    file_transcription::transcribe(&file, &samples, rate, &whisper, &never, |event| {
+        let written = match (&event, format) {
+            (_, OutputFormat::Json) => serde_json::to_string(&event)
+                .map_err(io::Error::other)
+                .and_then(|line| writeln!(stdout, "{line}")),
+            (
+                FileTranscriptionEvent::Chunk {
+                    index,
+                    chunks,
+                    text,
+                    ..
+                },
+                OutputFormat::Text,
+            ) => {
+                eprintln!("[{}/{chunks}]", index + 1);
+                writeln!(stdout, "{text}")
+            }
+            _ => Ok(()),
+        };
+        let _ = written.and_then(|()| stdout.flush());
+        if let FileTranscriptionEvent::Failed { message, .. } = event {
+            failure = Some(message);
+        }
+    });
+    failure.map_or(Ok(()), |message| Err(AppError::Other(message)))
+}
+
+async fn send(options: SendOptions) -> Result<(), AppError> {
+    let source = options
+        .source
+        .clone()
+        .ok_or_else(|| AppError::Other("No audio given — pass a WAV file or -".to_string()))?;
+    let saved = saved_settings();
+    let preset = saved
+        .get("audio_quality")
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default();
+    let wav_bytes = read_audio(&source, preset)?;
+
+    let gateway_url = options
+        .gateway_url
+        .or_else(|| saved_string(&saved, "gateway_url"))
+        .unwrap_or_else(|| AppSettings::default().gateway_url);
+    let profile_name = options
+        .profile_name
+        .or_else(|| saved_string(&saved, "profile_name"))
+        .ok_or_else(|| AppError::Other("No profile given — pass --profile".to_string()))?;
+    let session_key = options
+        .session_key
+        .or_else(|| saved_string(&saved, "session_key"));
+    let response_language = options
+        .response_language
+        .or_else(|| saved_string(&saved, "response_language"));
+    let workspace = options
+        .workspace
+        .or_else(|| saved_string(&saved, "workspace"))
+        .filter(|w| !w.is_empty());
+    if let Some(workspace) = &workspace {
+        api::validate_workspace(workspace)?;
+    }
+    let overrides: HashMap<String, String> = saved
+        .get("endpoint_overrides")
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default();
+    let template = saved_string(&saved, "endpoint_template")
+        .unwrap_or_else(|| routing::DEFAULT_TEMPLATE.to_string());
+    let response_timeout_secs = saved
+        .get("response_timeout_secs")
+        .and_then(|v| v.as_u64())
+        .unwrap_or_else(|| AppSettings::default().response_timeout_secs);
+    let brief = options.brief
+        || saved
+            .get("brief_mode")
+            .and_then(|v| v.as_bool())
+            .unwrap_or_default();
+    let brief_max_sentences = saved
+        .get("brief_max_sentences")
+        .and_then(|v| v.as_u64())
+        .unwrap_or_else(|| AppSettings::default().brief_max_sentences);
+
+    let gateway = Gateway {
+        routes: Routes::new(&template, &overrides)?,
+        http_log: HttpLog::new(),
+        workspace,
+        response_timeout: (response_timeout_secs > 0)
+            .then(|| Duration::from_secs(response_timeout_secs)),
+        brief_sentences: brief.then_some(brief_max_sentences.max(1) as usize),
+    };
+
+    let session_id = match options.session_id {
+        Some(id) => id,
+        None => {
+            let session = api::create_session(&gateway, &gateway_url, &profile_name).await?;
+            eprintln!("Session: {}", session.session_id);
+            session.session_id
+        }
+    };
+
+    let turn = Turn {
+        base_url: &gateway_url,
+        session_id: &session_id,
+        profile_name: &profile_name,
+        session_key: session_key.as_deref(),
+        response_language: response_language.as_deref(),
+        context: None,
+    };
+    let utterance = saved_stt(&saved)
+        .provider_for(&profile_name)?
+        .prepare(wav_bytes.into())?;
+
+    let mut stdout = io::stdout().lock();
+    let mut failure = None;
+    let mut stdout_closed = false;
+    let result = api::stream_audio(&gateway, &turn, utterance, |event| {
+        if let VoiceEvent::System {
+            status, message, ..
+        } = event
+        {
+            match status.as_str() {
+                "error" => failure = Some(message.clone().unwrap_or_default()),
+                "empty_transcription" => failure = Some("No speech detected".to_string()),
+                _ => {}
+            }
+        }
+        write_event(&mut stdout, options.format, event).map_err(|e| {
+            stdout_closed = e.kind() == io::ErrorKind::BrokenPipe;
+            AppError::Other(format!("Failed to write output: {e}"))
+        })
+    })
+    .await;
+
+    // The reader went away (e.g. `| head`) — that's how pipelines end, not an error
+    if stdout_closed {
+        return Ok(());
+    }
+    result?;
+    if let Some(message) = failure {
+        return Err(AppError::classify_message(&message).unwrap_or(AppError::Other(message)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_stdin_send() {
+        let command = parse_args(&args(&["send", "-", "--profile", "alice"])).unwrap();
+        assert_eq!(
+            command,
+            CliCommand::Send(SendOptions {
+                source: Some(AudioSource::Stdin),
+                profile_name: Some("alice".to_string()),
+                ..Default::default()
+            })
+        );
+        let command = parse_args(&args(&["send", "--stdin-wav", "--brief"])).unwrap();
+        assert!(matches!(
+            command,
+            CliCommand::Send(SendOptions {
+                source: Some(AudioSource::Stdin),
+                brief: true,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse_args(&args(&["send", "--profile"])).is_err());
+        assert!(parse_args(&args(&["send", "a.wav", "b.wav"])).is_err());
+        assert!(parse_args(&args(&["record"])).is_err());
+        assert_eq!(parse_args(&[]).unwrap(), CliCommand::Help);
+        assert_eq!(parse_args(&args(&["schema"])).unwrap(), CliCommand::Schema);
+        assert_eq!(parse_args(&args(&["client"])).unwrap(), CliCommand::Client);
+        assert!(parse_args(&args(&["transcribe"])).is_err());
+        assert!(parse_args(&args(&["transcribe", "a.wav", "b.wav"])).is_err());
+    }
+
+    #[test]
+    fn test_text_output_streams_deltas() {
+        let mut out = Vec::new();
+        for (text, done) in [("Hel", false), ("lo", false), ("", true)] {
+            let event = VoiceEvent::Openclaw {
+                text: text.to_string(),
+                done,
+                timestamp: String::new(),
+            };
+            write_event(&mut out, OutputFormat::Text, &event).unwrap();
+        }
+        assert_eq!(String::from_utf8(out).unwrap(), "Hello\n");
+    }
+
+    #[test]
+    fn test_json_output_is_one_event_per_line() {
+        let mut out = Vec::new();
+        let event = VoiceEvent::System {
+            status: "typing".to_string(),
+            message: None,
+            timestamp: "t".to_string(),
+        };
+        write_event(&mut out, OutputFormat::Json, &event).unwrap();
+        write_event(&mut out, OutputFormat::Json, &event).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let value: Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(value["type"], "system");
+        assert_eq!(value["status"], "typing");
+    }
+
+    #[test]
+    fn test_streamed_wav_header_is_fixed() {
+        let mut wav = audio::encode_wav(&[0.1, -0.2, 0.3], 16_000).unwrap();
+        let data = wav.windows(4).position(|w| w == b"data").unwrap();
+        // What arecord writes when its output isn't seekable
+        wav[4..8].copy_from_slice(&u32::MAX.to_le_bytes());
+        wav[data + 4..data + 8].copy_from_slice(&u32::MAX.to_le_bytes());
+
+        fix_streamed_wav(&mut wav);
+        let (samples, rate) = decode_wav_mono(&wav).unwrap();
+        assert_eq!(rate, 16_000);
+        assert_eq!(samples.len(), 3);
+    }
+}
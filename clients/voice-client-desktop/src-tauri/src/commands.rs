@@ -1,21 +1,132 @@
-use tauri::{AppHandle, Manager, State, WebviewUrl, WebviewWindowBuilder};
+use std::sync::atomic::Ordering;
 
+use bytes::Bytes;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+use crate::api::{Gateway, Turn};
+use crate::capabilities::CapabilityState;
+use crate::countdown::CountdownState;
+use crate::cues::{self, Cue};
+use crate::duplicates::{self, DuplicateState};
+use crate::error::AppError;
+use crate::events::EventLog;
+use crate::failed_sends::{self, FailedSendState};
+use crate::file_transcription::{self, FileTranscriptionState};
+use crate::http_log::{self, HttpLog};
+use crate::idle::PresenceState;
+use crate::launch::LaunchState;
+use crate::playback::PlaybackState;
+use crate::quality::{self, QualityState};
+use crate::quick_talk;
+use crate::response_cache;
+use crate::routing::Routes;
+use crate::session::SessionState;
+use crate::stt;
+use crate::tasks::TaskState;
+use crate::trigger::TriggerGate;
 use crate::types::{
-    AppSettings, AudioDevice, ConnectionResult, SessionResponse,
+    A11yPrefs, ActiveSession, AppSettings, ArchivedRecording, AudioDevice, AudioHost,
+    CompletedTurn, ConnectionDiagnosis, ConnectionResult, DeviceCapabilities, EventKind,
+    FailedSend, FeedbackRating, GatewayCapabilities, HistorySearchResult, LearnedCorrection,
+    ListeningPaused, LoggedEvent, MicSource, MicTestReport, MicUsage, PlaybackStatus,
+    RecordingPause, RemotePairing, ScrollDirection, SelfCheckReport, SessionOverview,
+    SessionResponse, StorageUsage, TaskHealth, TranscriptPage, TriggerSource, VoiceprintStatus,
+};
+use crate::voiceprint::{self, VoiceprintState};
+use crate::watchdog::{SendingGuard, WatchdogState};
+use crate::{
+    a11y, actions, api, audio, capabilities, capture_limit, context, conversations, corrections,
+    countdown, demo, device_monitor, diagnostics, dictation, effects, events, feedback, history,
+    hotkey, journal, keymap, live_upload, macros, mic_level, mic_test, mic_usage, playback,
+    privacy, recordings, remote, resend, segments, selfcheck, session, settings, shutdown, storage,
+    trigger, unread, vad, waveform,
 };
-use crate::{audio, api, settings};
 
 #[tauri::command]
-pub async fn list_audio_devices() -> Result<Vec<AudioDevice>, String> {
-    audio::list_audio_devices()
+pub async fn list_audio_devices(app: AppHandle) -> Result<Vec<AudioDevice>, String> {
+    let settings = settings::load_settings(&app)?;
+    let devices = audio::list_audio_devices(settings.audio_host.as_deref())?;
+    Ok(audio::label_devices(devices, &settings.device_labels))
+}
+
+/// The audio backends (ALSA, JACK, WASAPI, ASIO, ...) `audio_host` can pick from
+#[tauri::command]
+pub async fn list_audio_hosts(app: AppHandle) -> Result<Vec<AudioHost>, String> {
+    let selected = settings::load_settings(&app)?.audio_host;
+    Ok(audio::list_audio_hosts(selected.as_deref()))
+}
+
+/// What `device_id` (the default microphone if None) can capture, for pinning its
+/// sample rate or buffer size in `device_formats`
+#[tauri::command]
+pub async fn get_device_capabilities(
+    device_id: Option<String>,
+    state: State<'_, crate::audio::AudioState>,
+) -> Result<DeviceCapabilities, String> {
+    let device_id = device_id.filter(|id| !id.is_empty());
+    audio::device_capabilities(&state, device_id.as_deref())
 }
 
 #[tauri::command]
 pub async fn start_recording(
+    app: AppHandle,
     device_id: Option<String>,
+    source: Option<TriggerSource>,
+    channel_index: Option<u16>,
     state: State<'_, crate::audio::AudioState>,
+    gate: State<'_, TriggerGate>,
+    player: State<'_, PlaybackState>,
 ) -> Result<(), String> {
-    audio::start_recording(&state, device_id.as_deref())
+    if channel_index == Some(0) {
+        return Err("Channels count from 1".to_string());
+    }
+    let source = source.unwrap_or(TriggerSource::Popup);
+    trigger::admit(&app, &gate, source)?;
+    countdown::run(&app, source).await?;
+    // Barge-in: speaking over a reply pauses it, like a smart speaker
+    player.update(&app, |queue| queue.barge_in())?;
+    cues::play_before_recording(&app).await;
+    // A missing microphone falls back to the last one used, then the default one
+    let device_id = match device_monitor::start_recording(&app, &state, device_id, channel_index) {
+        Ok(device_id) => device_id,
+        Err(e) => {
+            playback::end_barge_in(&app, false);
+            cues::play(&app, Cue::Error);
+            return Err(e);
+        }
+    };
+    let device = state.device_name();
+    state.mark_in_use(mic_usage::opened(
+        &app,
+        MicSource::Recording,
+        Some(source),
+        device,
+    ));
+    vad::watch(&app);
+    mic_level::watch(&app);
+    waveform::watch(&app);
+    capture_limit::watch(&app);
+    context::begin_turn(&app);
+    live_upload::begin(&app);
+    if source == TriggerSource::Tray {
+        quick_talk::recording_started(&app);
+    }
+    events::record_state(
+        &app,
+        "recording-started",
+        serde_json::json!({
+            "source": source,
+            "deviceId": device_id,
+            "channelIndex": channel_index,
+        }),
+    );
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn cancel_countdown(countdown: State<'_, CountdownState>) -> Result<(), String> {
+    countdown::cancel(&countdown);
+    Ok(())
 }
 
 #[tauri::command]
@@ -25,31 +136,550 @@ pub async fn stop_and_send(
     session_id: String,
     profile_name: String,
     session_key: Option<String>,
+    response_language: Option<String>,
+    state: State<'_, crate::audio::AudioState>,
+) -> Result<(), AppError> {
+    let _quick_talk = quick_talk::SendGuard(&app);
+    app.state::<SessionState>()
+        .activate(&session_id, &profile_name, &base_url);
+    let recording = audio::take_recording(&state);
+    // The reply spoken over gives way to the answer to what was said
+    playback::end_barge_in(&app, recording.is_ok());
+    let recording = recording?;
+    cues::play(&app, Cue::Stop);
+    // Already on its way when streaming; the encoded copy is only kept if that fails
+    let live = live_upload::take(&app, &session_id);
+    let turn = Turn {
+        base_url: &base_url,
+        session_id: &session_id,
+        profile_name: &profile_name,
+        session_key: session_key.as_deref(),
+        response_language: response_language.as_deref(),
+        context: None,
+    };
+    send_recording(&app, turn, recording, live).await
+}
+
+/// Send a finished recording: the profile's effects and the speaker filter, then
+/// encoding and the upload, journaled throughout and kept for retry if the send fails.
+/// A recording that went through is added to the archive when that is on.
+/// The preprocessing pipeline has already run in `take_recording`.
+pub async fn send_recording(
+    app: &AppHandle,
+    turn: Turn<'_>,
+    recording: audio::Recording,
+    live: Option<live_upload::LiveUpload>,
+) -> Result<(), AppError> {
+    let audio::Recording {
+        samples,
+        sample_rate,
+        trimmed_start,
+    } = recording;
+    if samples.is_empty() {
+        return Err(AppError::Other(
+            "Nothing but silence was recorded, so nothing was sent".to_string(),
+        ));
+    }
+    let (session_id, profile_name) = (turn.session_id, turn.profile_name);
+    // Cleared when this returns; a crash before then leaves it for the next launch
+    let journal = journal::begin(app, &turn, &samples, sample_rate);
+    if let Some(journal) = &journal {
+        journal.encoding();
+    }
+    let samples = effects::apply(app, profile_name, samples, sample_rate);
+    let samples = voiceprint::apply_filter(app, samples, sample_rate);
+    if samples.is_empty() {
+        return Err(AppError::Other(
+            "Only other speakers were heard, so nothing was sent".to_string(),
+        ));
+    }
+    let encoder = app.state::<QualityState>();
+    let preset = encoder.get();
+    let device = app.state::<audio::AudioState>().device_name();
+    let buffer = encoder.take_buffer();
+    let wav_bytes = quality::encode_into(buffer, &samples, sample_rate, preset, device.as_deref())?;
+    let wav_bytes = Bytes::from(wav_bytes);
+    let duplicates = app.state::<DuplicateState>();
+    let claim = duplicates::claim(app, &duplicates, session_id, &wav_bytes)?;
+    if let Some(journal) = &journal {
+        journal.uploading(&wav_bytes);
+    }
+    let turn_context = context::finish_turn(app).await;
+    events::record_state(
+        app,
+        "upload-started",
+        serde_json::json!({
+            "sessionId": session_id,
+            "bytes": wav_bytes.len(),
+            "quality": preset,
+            "live": live.is_some(),
+        }),
+    );
+    stt::preview(app, session_id, profile_name, wav_bytes.clone());
+    let turn = Turn {
+        context: turn_context.as_ref(),
+        ..turn
+    };
+    // Shares the buffer, in case the send fails and it is kept for a retry
+    let payload = wav_bytes.clone();
+    resend::keep(app, &turn, &payload);
+    let duration_ms = samples.len() as u64 * 1000 / sample_rate.max(1) as u64;
+    let sessions = app.state::<SessionState>();
+    let previous_turn = sessions.last_turn().map(|turn| turn.turn_id);
+    let watchdog = app.state::<WatchdogState>();
+    let _sending = SendingGuard::start(&watchdog);
+    let send = async {
+        if let Some(live) = live {
+            return live_upload::finish(live, &samples, trimmed_start).await;
+        }
+        let utterance = stt::prepare(app, profile_name, wav_bytes).await?;
+        response_cache::send(app, &turn, utterance).await
+    };
+    let result = tokio::select! {
+        result = send => result,
+        _ = watchdog.cancel_send.notified() => Err(AppError::Other(
+            "Sending took too long and was cancelled".to_string(),
+        )),
+    };
+    match &result {
+        Ok(()) => {
+            claim.sent();
+            events::record_state(app, "upload-finished", ());
+            // The reply has finished streaming, so a new turn for this session is this one
+            let transcript = sessions
+                .last_turn()
+                .filter(|last| Some(&last.turn_id) != previous_turn.as_ref())
+                .filter(|last| last.turn_id.starts_with(&format!("{session_id}/")))
+                .and_then(|last| last.user_text);
+            recordings::save(app, &turn, &payload, duration_ms, transcript);
+        }
+        Err(e) => {
+            events::record_state(app, "upload-failed", serde_json::json!({ "error": e }));
+            events::speak(app, profile_name, &e.to_string(), EventKind::Error);
+            cues::play(app, Cue::Error);
+            if let Err(save_error) = failed_sends::save(app, &turn, &payload, e) {
+                eprintln!("Failed recording could not be kept for retry: {save_error}");
+            }
+        }
+    }
+    encoder.recycle(payload);
+    result
+}
+
+/// Keep recording and send each utterance on its own as the speaker pauses, until
+/// `stop_dictation`; "dictation-utterance" follows each one
+#[tauri::command]
+pub async fn start_dictation(
+    app: AppHandle,
+    device_id: Option<String>,
+    base_url: String,
+    session_id: String,
+    profile_name: String,
+    session_key: Option<String>,
+    response_language: Option<String>,
+) -> Result<(), String> {
+    let target = dictation::DictationTarget {
+        base_url,
+        session_id,
+        profile_name,
+        session_key,
+        response_language,
+    };
+    dictation::start(&app, device_id, target)
+}
+
+/// End dictation; the utterance in progress is still sent
+#[tauri::command]
+pub async fn stop_dictation(app: AppHandle) -> Result<(), String> {
+    dictation::stop(&app);
+    Ok(())
+}
+
+/// Stop taking in audio without ending the recording, e.g. while answering the phone.
+/// What was captured so far is kept and sent with the rest.
+#[tauri::command]
+pub async fn pause_recording(
+    app: AppHandle,
     state: State<'_, crate::audio::AudioState>,
 ) -> Result<(), String> {
-    let wav_bytes = audio::stop_recording(&state)?;
-    api::send_audio_streaming(
+    set_recording_paused(&app, &state, true)
+}
+
+/// Carry on capturing into the paused recording
+#[tauri::command]
+pub async fn resume_recording(
+    app: AppHandle,
+    state: State<'_, crate::audio::AudioState>,
+) -> Result<(), String> {
+    set_recording_paused(&app, &state, false)
+}
+
+fn set_recording_paused(
+    app: &AppHandle,
+    state: &crate::audio::AudioState,
+    paused: bool,
+) -> Result<(), String> {
+    if audio::set_paused(state, paused)? {
+        let pause = RecordingPause { paused };
+        events::record_state(app, "recording-pause", &pause);
+        let _ = app.emit("recording-pause", &pause);
+    }
+    Ok(())
+}
+
+/// Sessions with recorded exchanges, most recently active first
+#[tauri::command]
+pub async fn get_sessions_overview(app: AppHandle) -> Result<Vec<SessionOverview>, String> {
+    history::sessions_overview(&app)
+}
+
+#[tauri::command]
+pub async fn get_session_transcript(
+    app: AppHandle,
+    session_id: String,
+    offset: Option<usize>,
+    limit: Option<usize>,
+) -> Result<TranscriptPage, String> {
+    history::session_transcript(&app, &session_id, offset.unwrap_or(0), limit)
+}
+
+/// Local history and the gateway's stored transcripts matching `query`
+#[tauri::command]
+pub async fn search_remote_history(
+    app: AppHandle,
+    query: String,
+) -> Result<HistorySearchResult, String> {
+    history::search(&app, &query).await
+}
+
+/// A whole transcript, from the gateway if it keeps it, else from local history
+#[tauri::command]
+pub async fn get_remote_session(app: AppHandle, id: String) -> Result<TranscriptPage, String> {
+    history::remote_session(&app, &id).await
+}
+
+/// Rate a reply by its `turnId` from "turn-completed", with an optional comment
+#[tauri::command]
+pub async fn submit_feedback(
+    app: AppHandle,
+    turn_id: String,
+    rating: FeedbackRating,
+    comment: Option<String>,
+) -> Result<(), AppError> {
+    feedback::submit(&app, &turn_id, rating, comment).await
+}
+
+/// Segment `index` of the latest reply, or None once it is used up or when "show more"
+/// opens the popup instead
+#[tauri::command]
+pub async fn show_more_of_reply(
+    app: AppHandle,
+    turn_id: String,
+    index: usize,
+) -> Result<Option<String>, String> {
+    segments::show_more(&app, &turn_id, index)
+}
+
+/// Learn from the user editing a transcript from `original` into `corrected`
+#[tauri::command]
+pub async fn learn_correction(
+    app: AppHandle,
+    original: String,
+    corrected: String,
+) -> Result<(), String> {
+    corrections::learn(&app, &original, &corrected)
+}
+
+#[tauri::command]
+pub async fn list_corrections(app: AppHandle) -> Result<Vec<LearnedCorrection>, String> {
+    corrections::list(&app)
+}
+
+#[tauri::command]
+pub async fn delete_correction(app: AppHandle, from: String, to: String) -> Result<(), String> {
+    corrections::delete(&app, &from, &to)
+}
+
+#[tauri::command]
+pub async fn list_failed_sends(app: AppHandle) -> Result<Vec<FailedSend>, String> {
+    failed_sends::list(&app)
+}
+
+#[tauri::command]
+pub async fn retry_failed(
+    app: AppHandle,
+    id: String,
+    state: State<'_, FailedSendState>,
+) -> Result<(), AppError> {
+    failed_sends::retry(&app, &state, &id).await
+}
+
+/// Send the last recording again, e.g. after the gateway dropped its reply mid-stream
+#[tauri::command]
+pub async fn resend_last_audio(app: AppHandle) -> Result<(), AppError> {
+    resend::resend(&app).await
+}
+
+#[tauri::command]
+pub async fn discard_failed(app: AppHandle, id: String) -> Result<(), String> {
+    failed_sends::discard(&app, &id)
+}
+
+/// Recordings kept by the archive, newest first
+#[tauri::command]
+pub async fn list_recordings(app: AppHandle) -> Result<Vec<ArchivedRecording>, String> {
+    recordings::list(&app)
+}
+
+#[tauri::command]
+pub async fn delete_recording(app: AppHandle, id: String) -> Result<(), String> {
+    recordings::delete(&app, &id)
+}
+
+#[tauri::command]
+pub async fn transcribe_file(app: AppHandle, path: String) -> Result<(), AppError> {
+    file_transcription::run(&app, path).await
+}
+
+#[tauri::command]
+pub async fn cancel_file_transcription(
+    state: State<'_, FileTranscriptionState>,
+) -> Result<(), String> {
+    state.cancel.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn start_voiceprint_enrollment(
+    app: AppHandle,
+    device_id: Option<String>,
+    state: State<'_, crate::audio::AudioState>,
+) -> Result<(), String> {
+    audio::start_recording(&state, device_id.as_deref(), None)?;
+    let device = state.device_name();
+    state.mark_in_use(mic_usage::opened(
         &app,
-        base_url.as_str(),
-        session_id.as_str(),
-        profile_name.as_str(),
-        session_key.as_deref(),
-        wav_bytes,
+        MicSource::VoiceprintEnrollment,
+        None,
+        device,
+    ));
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn finish_voiceprint_enrollment(
+    app: AppHandle,
+    state: State<'_, crate::audio::AudioState>,
+    voiceprints: State<'_, VoiceprintState>,
+) -> Result<VoiceprintStatus, String> {
+    let recording = audio::take_recording(&state)?;
+    voiceprint::enroll(
+        &app,
+        &voiceprints,
+        &recording.samples,
+        recording.sample_rate,
     )
-    .await
+}
+
+#[tauri::command]
+pub async fn clear_voiceprint(
+    app: AppHandle,
+    voiceprints: State<'_, VoiceprintState>,
+) -> Result<VoiceprintStatus, String> {
+    voiceprint::clear(&app, &voiceprints)
+}
+
+#[tauri::command]
+pub async fn get_voiceprint_status(
+    voiceprints: State<'_, VoiceprintState>,
+) -> Result<VoiceprintStatus, String> {
+    Ok(voiceprints.status())
+}
+
+#[tauri::command]
+pub async fn enqueue_audio(
+    app: AppHandle,
+    wav_bytes: Vec<u8>,
+    player: State<'_, PlaybackState>,
+) -> Result<PlaybackStatus, String> {
+    playback::enqueue_wav(&app, &player, &wav_bytes)
+}
+
+#[tauri::command]
+pub async fn skip_playback(
+    app: AppHandle,
+    player: State<'_, PlaybackState>,
+) -> Result<PlaybackStatus, String> {
+    player.update(&app, |queue| queue.skip())
+}
+
+#[tauri::command]
+pub async fn stop_all_playback(
+    app: AppHandle,
+    player: State<'_, PlaybackState>,
+) -> Result<PlaybackStatus, String> {
+    player.update(&app, |queue| queue.stop_all())
+}
+
+#[tauri::command]
+pub async fn resume_playback(
+    app: AppHandle,
+    player: State<'_, PlaybackState>,
+) -> Result<PlaybackStatus, String> {
+    player.update(&app, |queue| queue.resume())
+}
+
+/// Speak WAV audio given as base64 `data` (a `data:` URI works too) or by `url`
+#[tauri::command]
+pub async fn play_audio(
+    app: AppHandle,
+    data: Option<String>,
+    url: Option<String>,
+) -> Result<PlaybackStatus, String> {
+    playback::play_audio(&app, data.as_deref(), url.as_deref()).await
+}
+
+/// Stop speaking, drop whatever is queued and release the output device
+#[tauri::command]
+pub async fn stop_playback(
+    app: AppHandle,
+    player: State<'_, PlaybackState>,
+) -> Result<PlaybackStatus, String> {
+    let status = player.update(&app, |queue| queue.stop_all())?;
+    player.close();
+    Ok(status)
+}
+
+#[tauri::command]
+pub async fn get_playback_status(
+    player: State<'_, PlaybackState>,
+) -> Result<PlaybackStatus, String> {
+    let queue = player.queue.lock().map_err(|e| e.to_string())?;
+    Ok(queue.status())
+}
+
+/// Set and save the volume replies are spoken at, from 0.0 (muted) to 1.0
+#[tauri::command]
+pub async fn set_playback_volume(
+    app: AppHandle,
+    volume: f32,
+    player: State<'_, PlaybackState>,
+) -> Result<PlaybackStatus, String> {
+    let status = player.update(&app, |queue| queue.set_volume(volume))?;
+    let mut current = settings::load_settings(&app)?;
+    current.playback_volume = status.volume;
+    settings::save_settings(&app, &current)?;
+    let _ = app.emit("settings-changed", &current);
+    Ok(status)
 }
 
 #[tauri::command]
 pub async fn create_session(
+    app: AppHandle,
     base_url: String,
     profile_name: String,
-) -> Result<SessionResponse, String> {
-    api::create_session(base_url.as_str(), profile_name.as_str()).await
+    sessions: State<'_, SessionState>,
+) -> Result<SessionResponse, AppError> {
+    if demo::is_enabled(&app) {
+        let session = demo::session(&profile_name);
+        sessions.activate(&session.session_id, &session.profile_name, &base_url);
+        return Ok(session);
+    }
+    let gateway = Gateway::from_app(&app);
+    let session = api::create_session(&gateway, base_url.as_str(), profile_name.as_str()).await?;
+    sessions.activate(&session.session_id, &session.profile_name, &base_url);
+    capabilities::probe_on_connect(&app, &base_url);
+    Ok(session)
 }
 
 #[tauri::command]
-pub async fn test_connection(base_url: String) -> Result<ConnectionResult, String> {
-    api::test_connection(base_url.as_str()).await
+pub async fn restore_last_session(
+    app: AppHandle,
+    base_url: String,
+    sessions: State<'_, SessionState>,
+) -> Result<Option<ActiveSession>, AppError> {
+    // Demo sessions only live as long as the run
+    if demo::is_enabled(&app) {
+        return Ok(None);
+    }
+    let gateway = Gateway::from_app(&app);
+    let restored =
+        session::restore_last_session(&app, &sessions, &gateway, base_url.as_str()).await?;
+    if restored.is_some() {
+        capabilities::probe_on_connect(&app, &base_url);
+    }
+    Ok(restored)
+}
+
+#[tauri::command]
+pub async fn test_connection(
+    app: AppHandle,
+    base_url: String,
+    endpoint_template: Option<String>,
+    workspace: Option<String>,
+) -> Result<ConnectionResult, String> {
+    if demo::is_enabled(&app) {
+        return Ok(ConnectionResult {
+            success: true,
+            error: None,
+        });
+    }
+    let gateway = unsaved_gateway(&app, endpoint_template, workspace)?;
+    api::test_connection(&gateway, base_url.as_str()).await
+}
+
+/// Like test_connection, but checks DNS, TCP, TLS, credentials and the /profiles
+/// response one at a time and reports each with its timing
+#[tauri::command]
+pub async fn diagnose_connection(
+    app: AppHandle,
+    base_url: String,
+    endpoint_template: Option<String>,
+    workspace: Option<String>,
+) -> Result<ConnectionDiagnosis, String> {
+    let gateway = unsaved_gateway(&app, endpoint_template, workspace)?;
+    Ok(diagnostics::diagnose(&gateway, base_url.as_str()).await)
+}
+
+/// Which optional endpoints the connected gateway serves, None until a session is up
+#[tauri::command]
+pub async fn get_gateway_capabilities(
+    capabilities: State<'_, CapabilityState>,
+) -> Result<Option<GatewayCapabilities>, String> {
+    Ok(capabilities.get())
+}
+
+/// Probe the optional endpoints of `base_url` again, e.g. after the gateway was updated
+#[tauri::command]
+pub async fn probe_gateway_capabilities(
+    app: AppHandle,
+    base_url: String,
+) -> Result<GatewayCapabilities, String> {
+    if demo::is_enabled(&app) {
+        return Err("Demo mode has no gateway to probe".to_string());
+    }
+    Ok(capabilities::probe(&app, &base_url).await)
+}
+
+/// The gateway as configured, with the settings window's unsaved values applied
+fn unsaved_gateway(
+    app: &AppHandle,
+    endpoint_template: Option<String>,
+    workspace: Option<String>,
+) -> Result<Gateway, String> {
+    let mut gateway = Gateway::from_app(app);
+    // The settings window tests unsaved values, so an explicit template wins
+    if let Some(template) = endpoint_template {
+        gateway.routes = Routes::new(&template, &gateway.routes.overrides)?;
+    }
+    if let Some(workspace) = workspace {
+        let workspace = workspace.trim();
+        if !workspace.is_empty() {
+            api::validate_workspace(workspace)?;
+        }
+        gateway.workspace = Some(workspace.to_string()).filter(|w| !w.is_empty());
+    }
+    Ok(gateway)
 }
 
 #[tauri::command]
@@ -58,30 +688,211 @@ pub async fn load_settings(app: AppHandle) -> Result<AppSettings, String> {
 }
 
 #[tauri::command]
-pub async fn save_settings(app: AppHandle, settings: AppSettings) -> Result<(), String> {
-    settings::save_settings(&app, &settings)
+pub async fn save_settings(app: AppHandle, mut settings: AppSettings) -> Result<(), String> {
+    // Keep the hotkey on the keys it was typed on, whatever layout is active later
+    if let Some(hotkey) = settings
+        .push_to_talk_hotkey
+        .as_deref()
+        .filter(|h| !h.is_empty())
+    {
+        let layout = keymap::layout(&settings);
+        settings.push_to_talk_hotkey = Some(hotkey::normalize_hotkey(hotkey, layout)?);
+    }
+    settings::save_settings(&app, &settings)?;
+    settings::apply_settings(&app, &settings);
+    Ok(())
 }
 
+/// `hotkey`, or the saved push-to-talk hotkey, as printed on the current keyboard layout
 #[tauri::command]
-pub async fn open_settings_window(app: AppHandle) -> Result<(), String> {
-    if let Some(window) = app.get_webview_window("settings") {
-        window.show().map_err(|e| e.to_string())?;
-        window.set_focus().map_err(|e| e.to_string())?;
-        return Ok(());
+pub async fn describe_hotkey(
+    app: AppHandle,
+    hotkey: Option<String>,
+) -> Result<Option<String>, String> {
+    let current = settings::load_settings(&app)?;
+    let layout = keymap::layout(&current);
+    hotkey
+        .or(current.push_to_talk_hotkey)
+        .filter(|h| !h.is_empty())
+        .map(|h| hotkey::describe_hotkey(&h, layout))
+        .transpose()
+}
+
+/// The last finished exchange with its reply merged from the streamed deltas
+#[tauri::command]
+pub async fn get_last_turn(
+    sessions: State<'_, SessionState>,
+) -> Result<Option<CompletedTurn>, String> {
+    Ok(sessions.last_turn())
+}
+
+#[tauri::command]
+pub async fn get_event_log(
+    limit: Option<usize>,
+    log: State<'_, EventLog>,
+) -> Result<Vec<LoggedEvent>, String> {
+    Ok(log.snapshot(limit))
+}
+
+#[tauri::command]
+pub async fn tail_http_log(
+    lines: Option<usize>,
+    http_log: State<'_, HttpLog>,
+) -> Result<Vec<String>, String> {
+    match http_log.log_path() {
+        Some(path) => http_log::tail(&path, lines.unwrap_or(200)),
+        None => Ok(Vec::new()),
     }
+}
 
-    WebviewWindowBuilder::new(&app, "settings", WebviewUrl::App("settings.html".into()))
-        .title("OpenClaw Settings")
-        .inner_size(500.0, 600.0)
-        .center()
-        .build()
-        .map_err(|e| e.to_string())?;
+#[tauri::command]
+pub async fn run_self_check(app: AppHandle) -> Result<SelfCheckReport, String> {
+    Ok(selfcheck::run_self_check(&app).await)
+}
+
+/// Record a few seconds from `device_id`, play them back and report the levels, so the
+/// microphone can be checked without sending anything
+#[tauri::command]
+pub async fn test_microphone(
+    app: AppHandle,
+    device_id: Option<String>,
+) -> Result<MicTestReport, String> {
+    mic_test::test_microphone(&app, device_id).await
+}
+
+#[tauri::command]
+pub async fn get_storage_usage(app: AppHandle) -> Result<StorageUsage, String> {
+    storage::usage(&app)
+}
 
+/// The address and token a phone pairs with, while remote control is on
+#[tauri::command]
+pub async fn get_remote_pairing(app: AppHandle) -> Result<RemotePairing, String> {
+    remote::pairing(&app)
+}
+
+/// Issue a new pairing token, cutting off every device paired with the old one
+#[tauri::command]
+pub async fn reset_remote_pairing(app: AppHandle) -> Result<RemotePairing, String> {
+    remote::reset_pairing(&app)
+}
+
+/// Pin or unpin the popup and remember the choice
+#[tauri::command]
+pub async fn set_popup_pinned(app: AppHandle, pinned: bool) -> Result<(), String> {
+    let mut current = settings::load_settings(&app)?;
+    current.popup_pinned = pinned;
+    settings::save_settings(&app, &current)?;
+    actions::set_popup_pinned(&app, pinned)?;
+    let _ = app.emit("settings-changed", &current);
     Ok(())
 }
 
+#[tauri::command]
+pub async fn get_listening_pause(
+    presence: State<'_, PresenceState>,
+) -> Result<Option<ListeningPaused>, String> {
+    Ok(presence
+        .pause_reason()
+        .map(|reason| ListeningPaused { reason }))
+}
+
+/// Reduced motion and high contrast as the OS has them set; "a11y-prefs-changed"
+/// follows later changes
+#[tauri::command]
+pub async fn get_system_a11y_prefs(app: AppHandle) -> Result<A11yPrefs, String> {
+    Ok(a11y::current(&app).await)
+}
+
+/// Each background task with its status, restarts, last panic and dropped work
+#[tauri::command]
+pub async fn get_task_health(tasks: State<'_, TaskState>) -> Result<Vec<TaskHealth>, String> {
+    Ok(tasks.health())
+}
+
+/// When the microphone was open and what opened it, newest first
+#[tauri::command]
+pub async fn get_mic_usage_log(app: AppHandle) -> Result<Vec<MicUsage>, String> {
+    Ok(mic_usage::list(&app))
+}
+
+/// True once if the app was launched with `--start-recording`: the popup asks when
+/// its session is ready, and starts the recording itself
+#[tauri::command]
+pub async fn take_launch_recording(app: AppHandle) -> Result<bool, String> {
+    Ok(app.state::<LaunchState>().take_start_recording())
+}
+
+/// Whether recordings are answered here instead of by the gateway, from the
+/// `demo_mode` setting or `--demo`
+#[tauri::command]
+pub async fn is_demo_mode(app: AppHandle) -> Result<bool, String> {
+    Ok(demo::is_enabled(&app))
+}
+
+/// Answers that finished while the popup was hidden
+#[tauri::command]
+pub async fn get_unread_count(app: AppHandle) -> Result<u32, String> {
+    Ok(unread::count(&app))
+}
+
+#[tauri::command]
+pub async fn mark_all_read(app: AppHandle) -> Result<(), String> {
+    unread::mark_all_read(&app);
+    Ok(())
+}
+
+/// Stop (or resume) writing anything from the conversation to disk; lasts until quit
+#[tauri::command]
+pub async fn set_privacy_mode(app: AppHandle, enabled: bool) -> Result<(), String> {
+    privacy::set(&app, enabled);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_privacy_mode<R: Runtime>(app: AppHandle<R>) -> Result<bool, String> {
+    Ok(privacy::is_enabled(&app))
+}
+
+#[tauri::command]
+pub async fn open_settings_window(app: AppHandle) -> Result<(), String> {
+    actions::open_settings_window(&app)
+}
+
+#[tauri::command]
+pub async fn open_history_window(app: AppHandle) -> Result<(), String> {
+    actions::open_history_window(&app)
+}
+
+/// Detach a session into its own window, which then gets that session's events
+#[tauri::command]
+pub async fn open_conversation_window(app: AppHandle, session_id: String) -> Result<(), String> {
+    conversations::open_window(&app, &session_id)
+}
+
+#[tauri::command]
+pub async fn focus_popup(app: AppHandle) -> Result<(), String> {
+    actions::focus_popup(&app)
+}
+
+#[tauri::command]
+pub async fn scroll_history(app: AppHandle, direction: ScrollDirection) -> Result<(), String> {
+    actions::scroll_history(&app, direction)
+}
+
+#[tauri::command]
+pub async fn invoke_action(app: AppHandle, action_id: String) -> Result<(), String> {
+    actions::invoke_action(&app, action_id.as_str())
+}
+
+/// Run the macro called `name` from settings, step by step
+#[tauri::command]
+pub async fn run_macro(app: AppHandle, name: String) -> Result<(), String> {
+    macros::run(&app, &name).await
+}
+
 #[tauri::command]
 pub async fn quit_app(app: AppHandle) -> Result<(), String> {
-    app.exit(0);
+    shutdown::quit(&app);
     Ok(())
 }
@@ -0,0 +1,297 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::types::TurnContext;
+
+/// How long a send waits for a capture that is still running before going without it
+const CAPTURE_WAIT: Duration = Duration::from_millis(500);
+
+/// Opt-in capture of what the user was looking at when a turn started.
+/// Capture begins with the recording, while the user's own app is still in front.
+pub struct ContextState {
+    pub enabled: Arc<AtomicBool>,
+    pub pending: Arc<Mutex<Option<JoinHandle<TurnContext>>>>,
+}
+
+impl ContextState {
+    pub fn new() -> Self {
+        Self {
+            enabled: Arc::new(AtomicBool::new(false)),
+            pending: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::SeqCst)
+    }
+}
+
+/// Turn sharing on or off; the popup shows an indicator whenever it is on
+pub fn configure(app: &AppHandle, enabled: bool) {
+    let state = app.state::<ContextState>();
+    if state.enabled.swap(enabled, Ordering::SeqCst) != enabled {
+        let _ = app.emit("context-sharing-changed", enabled);
+    }
+    if !enabled {
+        if let Ok(mut pending) = state.pending.lock() {
+            *pending = None;
+        }
+    }
+}
+
+/// Start capturing context for the turn being recorded, if sharing is enabled
+pub fn begin_turn(app: &AppHandle) {
+    let state = app.state::<ContextState>();
+    if !state.is_enabled() {
+        return;
+    }
+    let handle = tauri::async_runtime::spawn_blocking(capture);
+    if let Ok(mut pending) = state.pending.lock() {
+        *pending = Some(handle);
+    };
+}
+
+/// Context captured for the current turn, if sharing is enabled and the capture finished.
+/// Emits "context-captured" so the popup can show exactly what was attached.
+pub async fn finish_turn(app: &AppHandle) -> Option<TurnContext> {
+    let state = app.state::<ContextState>();
+    let handle = state.pending.lock().ok()?.take()?;
+    if !state.is_enabled() {
+        return None;
+    }
+    let context = tokio::time::timeout(CAPTURE_WAIT, handle)
+        .await
+        .ok()?
+        .ok()?;
+    let _ = app.emit("context-captured", &context);
+    Some(context)
+}
+
+/// Snapshot the frontmost app, its window title, the locale and the time zone.
+/// Our own windows (the popup was clicked) are left out rather than reported.
+pub fn capture() -> TurnContext {
+    let (app_name, window_title) = match foreground_window() {
+        Some(window) if window.pid != std::process::id() => (window.app_name, window.title),
+        _ => (None, None),
+    };
+    TurnContext {
+        app_name,
+        window_title,
+        locale: locale(),
+        time_zone: iana_time_zone::get_timezone().ok(),
+    }
+}
+
+/// Encode the context for the `X-Turn-Context` header.
+/// Window titles are rarely ASCII, so the JSON is percent-encoded.
+pub fn encode_header(context: &TurnContext) -> Result<String, String> {
+    let json = serde_json::to_string(context)
+        .map_err(|e| format!("Failed to encode turn context: {e}"))?;
+    Ok(url::form_urlencoded::byte_serialize(json.as_bytes()).collect())
+}
+
+/// POSIX locale names (`de_DE.UTF-8@euro`) as language tags (`de-DE`)
+#[cfg(any(test, not(target_os = "windows")))]
+fn normalize_locale(raw: &str) -> Option<String> {
+    let name = raw.split(['.', '@']).next().unwrap_or_default().trim();
+    if name.is_empty() || name == "C" || name == "POSIX" {
+        return None;
+    }
+    Some(name.replace('_', "-"))
+}
+
+struct ForegroundWindow {
+    pid: u32,
+    app_name: Option<String>,
+    title: Option<String>,
+}
+
+fn non_empty(value: String) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+#[cfg(target_os = "windows")]
+fn foreground_window() -> Option<ForegroundWindow> {
+    use windows_sys::Win32::Foundation::CloseHandle;
+    use windows_sys::Win32::System::Threading::{
+        OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
+        PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        GetForegroundWindow, GetWindowTextW, GetWindowThreadProcessId,
+    };
+
+    // SAFETY: GetForegroundWindow has no preconditions.
+    let hwnd = unsafe { GetForegroundWindow() };
+    if hwnd.is_null() {
+        return None;
+    }
+
+    let mut title = [0u16; 512];
+    // SAFETY: `hwnd` came from GetForegroundWindow and `title` outlives the call.
+    let len = unsafe { GetWindowTextW(hwnd, title.as_mut_ptr(), title.len() as i32) };
+    let title = String::from_utf16_lossy(&title[..len.max(0) as usize]);
+
+    let mut pid = 0u32;
+    // SAFETY: `pid` is a valid out pointer for the duration of the call.
+    unsafe { GetWindowThreadProcessId(hwnd, &mut pid) };
+
+    // SAFETY: OpenProcess has no preconditions; a non-null handle is closed below.
+    let process = unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid) };
+    let app_name = if process.is_null() {
+        None
+    } else {
+        let mut path = [0u16; 1024];
+        let mut size = path.len() as u32;
+        // SAFETY: `process` is a valid handle and `size` holds the buffer length.
+        let ok = unsafe {
+            QueryFullProcessImageNameW(process, PROCESS_NAME_WIN32, path.as_mut_ptr(), &mut size)
+        };
+        // SAFETY: `process` is a valid handle returned by OpenProcess.
+        unsafe { CloseHandle(process) };
+        (ok != 0)
+            .then(|| String::from_utf16_lossy(&path[..size as usize]))
+            .and_then(|path| {
+                std::path::Path::new(&path)
+                    .file_stem()
+                    .map(|s| s.to_string_lossy().into_owned())
+            })
+    };
+
+    Some(ForegroundWindow {
+        pid,
+        app_name,
+        title: non_empty(title),
+    })
+}
+
+/// Needs the Accessibility permission for the window title; without it only the app is known
+#[cfg(target_os = "macos")]
+fn foreground_window() -> Option<ForegroundWindow> {
+    const SCRIPT: &str = r#"tell application "System Events"
+    set proc to first application process whose frontmost is true
+    set title to ""
+    try
+        set title to name of front window of proc
+    end try
+    return (unix id of proc as text) & linefeed & (name of proc) & linefeed & title
+end tell"#;
+    let output = std::process::Command::new("osascript")
+        .args(["-e", SCRIPT])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = String::from_utf8_lossy(&output.stdout);
+    let mut lines = text.lines();
+    let pid = lines.next()?.trim().parse().ok()?;
+    Some(ForegroundWindow {
+        pid,
+        app_name: lines.next().map(String::from).and_then(non_empty),
+        title: lines.next().map(String::from).and_then(non_empty),
+    })
+}
+
+/// X11 only, via xdotool; Wayland compositors don't expose the focused window
+#[cfg(target_os = "linux")]
+fn foreground_window() -> Option<ForegroundWindow> {
+    let xdotool = |args: &[&str]| {
+        std::process::Command::new("xdotool")
+            .args(args)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    };
+    let pid: u32 = xdotool(&["getactivewindow", "getwindowpid"])?
+        .parse()
+        .ok()?;
+    let app_name = std::fs::read_to_string(format!("/proc/{pid}/comm"))
+        .ok()
+        .and_then(non_empty);
+    Some(ForegroundWindow {
+        pid,
+        app_name,
+        title: xdotool(&["getactivewindow", "getwindowname"]).and_then(non_empty),
+    })
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+fn foreground_window() -> Option<ForegroundWindow> {
+    None
+}
+
+#[cfg(target_os = "windows")]
+fn locale() -> Option<String> {
+    use windows_sys::Win32::Globalization::GetUserDefaultLocaleName;
+
+    let mut name = [0u16; 85];
+    // SAFETY: `name` is LOCALE_NAME_MAX_LENGTH wide and outlives the call.
+    let len = unsafe { GetUserDefaultLocaleName(name.as_mut_ptr(), name.len() as i32) };
+    // The returned length includes the terminating NUL
+    (len > 1).then(|| String::from_utf16_lossy(&name[..len as usize - 1]))
+}
+
+/// Apps started from Finder get no LANG, so fall back to the user's region setting
+#[cfg(target_os = "macos")]
+fn locale() -> Option<String> {
+    env_locale().or_else(|| {
+        let output = std::process::Command::new("defaults")
+            .args(["read", "-g", "AppleLocale"])
+            .output()
+            .ok()?;
+        normalize_locale(&String::from_utf8_lossy(&output.stdout))
+    })
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn locale() -> Option<String> {
+    env_locale()
+}
+
+#[cfg(not(target_os = "windows"))]
+fn env_locale() -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|key| std::env::var(key).ok())
+        .find_map(|value| normalize_locale(&value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_posix_locale() {
+        assert_eq!(normalize_locale("de_DE.UTF-8"), Some("de-DE".to_string()));
+        assert_eq!(normalize_locale("fr_FR@euro"), Some("fr-FR".to_string()));
+        assert_eq!(normalize_locale("en_GB\n"), Some("en-GB".to_string()));
+        assert_eq!(normalize_locale("C.UTF-8"), None);
+        assert_eq!(normalize_locale(""), None);
+    }
+
+    #[test]
+    fn test_header_round_trips_unicode_title() {
+        let context = TurnContext {
+            app_name: Some("Code".to_string()),
+            window_title: Some("main.rs — Größe".to_string()),
+            locale: Some("de-DE".to_string()),
+            time_zone: None,
+        };
+        let header = encode_header(&context).unwrap();
+        assert!(header.is_ascii());
+
+        let decoded: String = url::form_urlencoded::parse(format!("c={header}").as_bytes())
+            .next()
+            .map(|(_, value)| value.into_owned())
+            .unwrap();
+        let parsed: TurnContext = serde_json::from_str(&decoded).unwrap();
+        assert_eq!(parsed, context);
+    }
+}
@@ -0,0 +1,63 @@
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, EventTarget, Manager, WebviewUrl, WebviewWindowBuilder};
+
+use crate::persist;
+
+/// Detached conversation windows are labelled with this prefix and their session id
+const WINDOW_PREFIX: &str = "conversation-";
+
+pub fn window_label(session_id: &str) -> String {
+    format!("{WINDOW_PREFIX}{session_id}")
+}
+
+/// Open a standard window for one session, or focus it if it is already detached
+pub fn open_window(app: &AppHandle, session_id: &str) -> Result<(), String> {
+    // Window labels only allow a few characters; gateway ids (`voice-<ms>-<random>`) fit
+    persist::check_id(session_id, "session")?;
+    let label = window_label(session_id);
+    if let Some(window) = app.get_webview_window(&label) {
+        window.show().map_err(|e| e.to_string())?;
+        window.set_focus().map_err(|e| e.to_string())?;
+        return Ok(());
+    }
+
+    WebviewWindowBuilder::new(app, &label, WebviewUrl::App("conversation.html".into()))
+        .title(format!("OpenClaw — {session_id}"))
+        .inner_size(480.0, 640.0)
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Deliver a session's event to whoever shows that session: its detached window as
+/// "conversation-event", or every window as `event` while it isn't detached.
+/// Detached windows listen window-scoped, so they never see each other's events.
+pub fn route<S: Serialize + Clone>(
+    app: &AppHandle,
+    session_id: &str,
+    event: &str,
+    payload: S,
+) -> Result<(), String> {
+    let label = window_label(session_id);
+    let result = if app.get_webview_window(&label).is_some() {
+        app.emit_to(
+            EventTarget::webview_window(label),
+            "conversation-event",
+            payload,
+        )
+    } else {
+        app.emit(event, payload)
+    };
+    result.map_err(|e| format!("Failed to emit event: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window_label_names_the_session() {
+        assert_eq!(window_label("voice-1-a"), "conversation-voice-1-a");
+    }
+}
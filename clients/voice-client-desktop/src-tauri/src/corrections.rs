@@ -0,0 +1,433 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::types::{LearnedCorrection, VoiceEvent};
+use crate::{events, persist, privacy};
+
+pub const CORRECTIONS_FILE: &str = "corrections.json";
+/// Longest phrase, in words, learned as one correction; longer edits are rewrites
+/// rather than misheard words
+const MAX_PHRASE_WORDS: usize = 3;
+/// Share of a rule's observations that must agree with it for it to be applied
+const MIN_CONFIDENCE: f32 = 0.8;
+const MAX_RULES: usize = 500;
+
+/// A word lowercased without its punctuation, as words are compared
+fn bare(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Lookup key of a phrase; None if a word in it is only punctuation
+fn phrase_key(words: &[&str]) -> Option<String> {
+    let bare: Vec<String> = words.iter().map(|word| bare(word)).collect();
+    (!bare.is_empty() && bare.iter().all(|word| !word.is_empty())).then(|| bare.join(" "))
+}
+
+/// Lookup key of a phrase as one string
+fn text_key(text: &str) -> Option<String> {
+    phrase_key(&text.split_whitespace().collect::<Vec<_>>())
+}
+
+/// The punctuation before and after a word's letters
+fn edges(word: &str) -> (&str, &str) {
+    let start = word.find(char::is_alphanumeric).unwrap_or(word.len());
+    let end = word.rfind(char::is_alphanumeric).map_or(start, |i| {
+        i + word[i..].chars().next().map_or(1, char::len_utf8)
+    });
+    (&word[..start], &word[end.max(start)..])
+}
+
+/// Spans of `original` the user replaced in `corrected`, as (original words, corrected
+/// words), from a word-level longest common subsequence. Changes in case or
+/// punctuation alone don't count.
+fn replacements<'a>(
+    original: &[&'a str],
+    corrected: &[&'a str],
+) -> Vec<(Vec<&'a str>, Vec<&'a str>)> {
+    let a: Vec<String> = original.iter().map(|word| bare(word)).collect();
+    let b: Vec<String> = corrected.iter().map(|word| bare(word)).collect();
+    let (n, m) = (a.len(), b.len());
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut spans = Vec::new();
+    let (mut from, mut to) = (Vec::new(), Vec::new());
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && a[i] == b[j] {
+            if !from.is_empty() || !to.is_empty() {
+                spans.push((std::mem::take(&mut from), std::mem::take(&mut to)));
+            }
+            i += 1;
+            j += 1;
+        } else if j < m && (i == n || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            to.push(corrected[j]);
+            j += 1;
+        } else {
+            from.push(original[i]);
+            i += 1;
+        }
+    }
+    if !from.is_empty() || !to.is_empty() {
+        spans.push((from, to));
+    }
+    spans
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Rule {
+    /// As returned by `phrase_key`
+    from: String,
+    /// As the user last typed it
+    to: String,
+    /// Times the user made this correction
+    count: u32,
+    /// Times the user undid it after it was applied
+    reverts: u32,
+    learned_at_ms: u64,
+}
+
+impl Rule {
+    fn confidence(&self) -> f32 {
+        self.count as f32 / (self.count + self.reverts).max(1) as f32
+    }
+
+    fn applies(&self, min_count: u32) -> bool {
+        self.count >= min_count && self.confidence() >= MIN_CONFIDENCE
+    }
+}
+
+/// Corrections learned from the user's edits to transcripts. A rule is applied once
+/// the user has made it `min_count` times and rarely undone it.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Dictionary {
+    rules: Vec<Rule>,
+}
+
+impl Dictionary {
+    /// Learn from the user changing `original` into `corrected`. An edit that changes
+    /// an applied correction back counts against that rule. Returns whether anything
+    /// was learned.
+    pub fn learn(&mut self, original: &str, corrected: &str, now_ms: u64) -> bool {
+        let original: Vec<&str> = original.split_whitespace().collect();
+        let corrected: Vec<&str> = corrected.split_whitespace().collect();
+        let mut learned = false;
+        for (from, to) in replacements(&original, &corrected) {
+            if from.len() > MAX_PHRASE_WORDS || to.len() > MAX_PHRASE_WORDS {
+                continue;
+            }
+            let (Some(from_key), Some(to_key)) = (phrase_key(&from), phrase_key(&to)) else {
+                continue;
+            };
+            let (lead, _) = edges(to[0]);
+            let (_, trail) = edges(to[to.len() - 1]);
+            let joined = to.join(" ");
+            let to = &joined[lead.len()..joined.len() - trail.len()];
+
+            if let Some(undone) = self
+                .rules
+                .iter_mut()
+                .find(|rule| rule.from == to_key && text_key(&rule.to).as_ref() == Some(&from_key))
+            {
+                undone.reverts += 1;
+            } else if let Some(rule) = self
+                .rules
+                .iter_mut()
+                .find(|rule| rule.from == from_key && text_key(&rule.to).as_ref() == Some(&to_key))
+            {
+                rule.count += 1;
+                rule.to = to.to_string();
+                rule.learned_at_ms = now_ms;
+            } else {
+                self.rules.push(Rule {
+                    from: from_key,
+                    to: to.to_string(),
+                    count: 1,
+                    reverts: 0,
+                    learned_at_ms: now_ms,
+                });
+            }
+            learned = true;
+        }
+        if self.rules.len() > MAX_RULES {
+            // The least used go first, the oldest among equals
+            self.rules.sort_by(|a, b| {
+                b.count
+                    .cmp(&a.count)
+                    .then(b.learned_at_ms.cmp(&a.learned_at_ms))
+            });
+            self.rules.truncate(MAX_RULES);
+        }
+        learned
+    }
+
+    /// `text` with the applying corrections made, or None if none matched
+    pub fn apply(&self, text: &str, min_count: u32) -> Option<String> {
+        let mut active: HashMap<&str, &Rule> = HashMap::new();
+        for rule in self.rules.iter().filter(|rule| rule.applies(min_count)) {
+            let best = active.entry(rule.from.as_str()).or_insert(rule);
+            if rule.count > best.count {
+                *best = rule;
+            }
+        }
+        if active.is_empty() {
+            return None;
+        }
+
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let mut out: Vec<String> = Vec::with_capacity(words.len());
+        let mut changed = false;
+        let mut i = 0;
+        'words: while i < words.len() {
+            for len in (1..=MAX_PHRASE_WORDS.min(words.len() - i)).rev() {
+                let phrase = &words[i..i + len];
+                let Some(rule) = phrase_key(phrase).and_then(|key| active.get(key.as_str())) else {
+                    continue;
+                };
+                let (lead, _) = edges(phrase[0]);
+                let (_, trail) = edges(phrase[len - 1]);
+                out.push(format!("{lead}{}{trail}", rule.to));
+                changed = true;
+                i += len;
+                continue 'words;
+            }
+            out.push(words[i].to_string());
+            i += 1;
+        }
+        changed.then(|| out.join(" "))
+    }
+
+    /// Every learned rule, most used first
+    pub fn list(&self, min_count: u32) -> Vec<LearnedCorrection> {
+        let mut rules: Vec<&Rule> = self.rules.iter().collect();
+        rules.sort_by(|a, b| b.count.cmp(&a.count).then(a.from.cmp(&b.from)));
+        rules
+            .into_iter()
+            .map(|rule| LearnedCorrection {
+                from: rule.from.clone(),
+                to: rule.to.clone(),
+                count: rule.count,
+                reverts: rule.reverts,
+                active: rule.applies(min_count),
+            })
+            .collect()
+    }
+
+    /// Forget the rule changing `from` into `to`. Returns whether there was one.
+    pub fn remove(&mut self, from: &str, to: &str) -> bool {
+        let before = self.rules.len();
+        self.rules
+            .retain(|rule| !(rule.from == from && rule.to == to));
+        self.rules.len() != before
+    }
+}
+
+pub struct CorrectionState {
+    pub dictionary: Arc<Mutex<Dictionary>>,
+    /// Apply learned corrections to new transcripts
+    pub enabled: Arc<AtomicBool>,
+    /// Times a correction must be made before it is applied
+    pub min_count: Arc<AtomicU32>,
+}
+
+impl CorrectionState {
+    pub fn new() -> Self {
+        Self {
+            dictionary: Arc::new(Mutex::new(Dictionary::default())),
+            enabled: Arc::new(AtomicBool::new(true)),
+            min_count: Arc::new(AtomicU32::new(3)),
+        }
+    }
+
+    pub fn configure(&self, enabled: bool, min_count: u32) {
+        self.enabled.store(enabled, Ordering::SeqCst);
+        self.min_count.store(min_count.max(1), Ordering::SeqCst);
+    }
+}
+
+fn dictionary_path(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(CORRECTIONS_FILE))
+        .map_err(|e| format!("Failed to resolve app data dir: {e}"))
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+fn save(app: &AppHandle, dictionary: &Dictionary) -> Result<(), String> {
+    let path = dictionary_path(app)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    }
+    let bytes = serde_json::to_vec_pretty(dictionary)
+        .map_err(|e| format!("Failed to serialize corrections: {e}"))?;
+    persist::write_atomic(&path, &bytes)
+}
+
+/// Load the corrections learned in earlier runs
+pub fn load(app: &AppHandle) {
+    let Some(dictionary) = dictionary_path(app)
+        .ok()
+        .and_then(|path| fs::read(path).ok())
+        .and_then(|bytes| serde_json::from_slice::<Dictionary>(&bytes).ok())
+    else {
+        return;
+    };
+    if let Ok(mut current) = app.state::<CorrectionState>().dictionary.lock() {
+        *current = dictionary;
+    }
+}
+
+/// Learn from the user correcting a transcript. Nothing is learned in privacy mode.
+pub fn learn(app: &AppHandle, original: &str, corrected: &str) -> Result<(), String> {
+    if privacy::is_enabled(app) {
+        return Ok(());
+    }
+    let state = app.state::<CorrectionState>();
+    let mut dictionary = state.dictionary.lock().map_err(|e| e.to_string())?;
+    if dictionary.learn(original, corrected, now_ms()) {
+        save(app, &dictionary)?;
+    }
+    Ok(())
+}
+
+pub fn list(app: &AppHandle) -> Result<Vec<LearnedCorrection>, String> {
+    let state = app.state::<CorrectionState>();
+    let dictionary = state.dictionary.lock().map_err(|e| e.to_string())?;
+    Ok(dictionary.list(state.min_count.load(Ordering::SeqCst)))
+}
+
+pub fn delete(app: &AppHandle, from: &str, to: &str) -> Result<(), String> {
+    let state = app.state::<CorrectionState>();
+    let mut dictionary = state.dictionary.lock().map_err(|e| e.to_string())?;
+    if !dictionary.remove(from, to) {
+        return Err(format!("No learned correction of '{from}' to '{to}'"));
+    }
+    save(app, &dictionary)
+}
+
+/// A user transcript with the learned corrections applied, before anything shows or
+/// stores it. None for other events, or when nothing changed.
+pub fn apply(app: &AppHandle, event: &VoiceEvent) -> Option<VoiceEvent> {
+    let VoiceEvent::User {
+        text,
+        confidence,
+        timestamp,
+    } = event
+    else {
+        return None;
+    };
+    let state = app.try_state::<CorrectionState>()?;
+    if !state.enabled.load(Ordering::SeqCst) {
+        return None;
+    }
+    let corrected = state
+        .dictionary
+        .lock()
+        .ok()?
+        .apply(text, state.min_count.load(Ordering::SeqCst))?;
+    events::record_state(
+        app,
+        "transcript-corrected",
+        serde_json::json!({ "chars": corrected.len() }),
+    );
+    Some(VoiceEvent::User {
+        text: corrected,
+        confidence: *confidence,
+        timestamp: timestamp.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn learned(dictionary: &mut Dictionary, original: &str, corrected: &str, times: usize) {
+        for _ in 0..times {
+            dictionary.learn(original, corrected, 1);
+        }
+    }
+
+    #[test]
+    fn test_correction_applies_once_made_often_enough() {
+        let mut dictionary = Dictionary::default();
+        learned(
+            &mut dictionary,
+            "ask clod about it",
+            "ask Claude about it",
+            2,
+        );
+        assert_eq!(dictionary.apply("Hey clod, what's up?", 3), None);
+
+        learned(&mut dictionary, "thanks clod", "thanks Claude", 1);
+        assert_eq!(
+            dictionary.apply("Hey clod, what's up?", 3).as_deref(),
+            Some("Hey Claude, what's up?")
+        );
+        let listed = dictionary.list(3);
+        assert_eq!(listed.len(), 1);
+        assert_eq!(
+            (listed[0].from.as_str(), listed[0].to.as_str()),
+            ("clod", "Claude")
+        );
+        assert!(listed[0].active);
+    }
+
+    #[test]
+    fn test_phrases_are_learned_but_case_and_rewrites_are_not() {
+        let mut dictionary = Dictionary::default();
+        learned(&mut dictionary, "open get hub now", "Open GitHub now.", 1);
+        let listed = dictionary.list(1);
+        assert_eq!(listed.len(), 1);
+        assert_eq!(
+            (listed[0].from.as_str(), listed[0].to.as_str()),
+            ("get hub", "GitHub")
+        );
+        assert_eq!(
+            dictionary.apply("is get hub down", 1).as_deref(),
+            Some("is GitHub down")
+        );
+
+        assert!(!dictionary.learn("one two three four five", "six seven eight nine ten", 1));
+    }
+
+    #[test]
+    fn test_undoing_a_correction_lowers_its_confidence() {
+        let mut dictionary = Dictionary::default();
+        learned(&mut dictionary, "clod", "Claude", 3);
+        // The user changes the applied "Claude" back
+        learned(&mut dictionary, "the Claude layer", "the clod layer", 1);
+        let listed = dictionary.list(3);
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].reverts, 1);
+        assert!(!listed[0].active);
+        assert_eq!(dictionary.apply("clod", 3), None);
+
+        assert!(dictionary.remove("clod", "Claude"));
+        assert!(dictionary.list(3).is_empty());
+    }
+}
@@ -0,0 +1,131 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::Notify;
+
+use crate::events;
+use crate::playback::{self, PlaybackState};
+use crate::types::{RecordingCountdown, TriggerSource};
+
+const TICK: Duration = Duration::from_secs(1);
+const TICK_CUE: (f32, u32) = (880.0, 80);
+/// Higher and longer, so "now" is audible without looking
+const GO_CUE: (f32, u32) = (1320.0, 200);
+
+/// Optional countdown between a trigger and the start of capture, for triggers that
+/// don't come from a hand on the keyboard (schedules, remote control)
+pub struct CountdownState {
+    pub length: Arc<Mutex<Duration>>,
+    pub sources: Arc<Mutex<Vec<TriggerSource>>>,
+    pub running: Arc<AtomicBool>,
+    pub cancel: Arc<Notify>,
+}
+
+impl CountdownState {
+    pub fn new() -> Self {
+        Self {
+            length: Arc::new(Mutex::new(Duration::ZERO)),
+            sources: Arc::new(Mutex::new(Vec::new())),
+            running: Arc::new(AtomicBool::new(false)),
+            cancel: Arc::new(Notify::new()),
+        }
+    }
+
+    pub fn configure(&self, length: Duration, sources: Vec<TriggerSource>) {
+        if let Ok(mut current) = self.length.lock() {
+            *current = length;
+        }
+        if let Ok(mut current) = self.sources.lock() {
+            *current = sources;
+        }
+    }
+
+    /// Whole seconds to count down before recording for `source`
+    pub fn seconds_for(&self, source: TriggerSource) -> u64 {
+        let applies = self
+            .sources
+            .lock()
+            .map(|sources| sources.contains(&source))
+            .unwrap_or(false);
+        if !applies {
+            return 0;
+        }
+        self.length.lock().map(|l| l.as_secs()).unwrap_or(0)
+    }
+}
+
+fn tick(app: &AppHandle, source: TriggerSource, remaining_secs: u64, cancelled: bool) {
+    let countdown = RecordingCountdown {
+        source,
+        remaining_secs,
+        cancelled,
+    };
+    events::record_state(app, "recording-countdown", &countdown);
+    let _ = app.emit("recording-countdown", &countdown);
+    if !cancelled {
+        let (frequency, ms) = if remaining_secs == 0 {
+            GO_CUE
+        } else {
+            TICK_CUE
+        };
+        // A missing output device shouldn't stop the recording
+        if let Err(e) = playback::play_cue(app, &app.state::<PlaybackState>(), frequency, ms) {
+            eprintln!("Countdown cue not played: {e}");
+        }
+    }
+}
+
+/// Count down (if configured for `source`), emitting "recording-countdown" each second
+/// with a beep. Errors if `cancel` is called before it reaches zero.
+pub async fn run(app: &AppHandle, source: TriggerSource) -> Result<(), String> {
+    let state = app.state::<CountdownState>();
+    let secs = state.seconds_for(source);
+    if secs == 0 {
+        return Ok(());
+    }
+    if state.running.swap(true, Ordering::SeqCst) {
+        return Err("A recording countdown is already running".to_string());
+    }
+
+    let mut result = Ok(());
+    for remaining in (1..=secs).rev() {
+        tick(app, source, remaining, false);
+        tokio::select! {
+            _ = tokio::time::sleep(TICK) => {}
+            _ = state.cancel.notified() => {
+                tick(app, source, remaining, true);
+                result = Err("Recording cancelled during countdown".to_string());
+                break;
+            }
+        }
+    }
+    if result.is_ok() {
+        tick(app, source, 0, false);
+    }
+    state.running.store(false, Ordering::SeqCst);
+    result
+}
+
+/// Stop a running countdown; the recording it was for doesn't start
+pub fn cancel(state: &CountdownState) {
+    if state.running.load(Ordering::SeqCst) {
+        state.cancel.notify_waiters();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_countdown_only_for_listed_sources() {
+        let state = CountdownState::new();
+        assert_eq!(state.seconds_for(TriggerSource::Schedule), 0);
+
+        state.configure(Duration::from_secs(3), vec![TriggerSource::Schedule]);
+        assert_eq!(state.seconds_for(TriggerSource::Schedule), 3);
+        assert_eq!(state.seconds_for(TriggerSource::Hotkey), 0);
+    }
+}
@@ -0,0 +1,104 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tauri::{AppHandle, Manager};
+
+use crate::playback::{self, PlaybackState};
+use crate::types::SoundCues;
+
+/// A recording transition that can have a beep
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cue {
+    Start,
+    Stop,
+    Error,
+}
+
+impl Cue {
+    /// Beeps as (frequency, ms); a frequency of 0 is a pause
+    fn tones(self) -> &'static [(f32, u32)] {
+        match self {
+            Self::Start => &[(660.0, 70), (990.0, 90)],
+            Self::Stop => &[(990.0, 70), (660.0, 90)],
+            Self::Error => &[(330.0, 120), (0.0, 60), (330.0, 120)],
+        }
+    }
+
+    fn enabled_in(self, cues: &SoundCues) -> bool {
+        match self {
+            Self::Start => cues.start,
+            Self::Stop => cues.stop,
+            Self::Error => cues.error,
+        }
+    }
+
+    fn length(self) -> Duration {
+        Duration::from_millis(self.tones().iter().map(|&(_, ms)| u64::from(ms)).sum())
+    }
+}
+
+/// Which cues are on, from `sound_cues`
+pub struct CueState {
+    pub cues: Arc<Mutex<SoundCues>>,
+}
+
+impl CueState {
+    pub fn new() -> Self {
+        Self {
+            cues: Arc::new(Mutex::new(SoundCues::default())),
+        }
+    }
+
+    pub fn configure(&self, cues: SoundCues) {
+        if let Ok(mut current) = self.cues.lock() {
+            *current = cues;
+        }
+    }
+
+    fn is_enabled(&self, cue: Cue) -> bool {
+        self.cues.lock().is_ok_and(|cues| cue.enabled_in(&cues))
+    }
+}
+
+/// Beep for `cue` if it is turned on. Returns how long it sounds, nothing if it
+/// doesn't play; a missing output device is logged, never passed on.
+pub fn play(app: &AppHandle, cue: Cue) -> Option<Duration> {
+    if !app.state::<CueState>().is_enabled(cue) {
+        return None;
+    }
+    match playback::play_tones(app, &app.state::<PlaybackState>(), cue.tones()) {
+        Ok(()) => Some(cue.length()),
+        Err(e) => {
+            eprintln!("{cue:?} cue not played: {e}");
+            None
+        }
+    }
+}
+
+/// Beep for the start of a recording and wait for it to finish, so the microphone
+/// about to open doesn't record it
+pub async fn play_before_recording(app: &AppHandle) {
+    if let Some(length) = play(app, Cue::Start) {
+        tokio::time::sleep(length).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_each_cue_is_turned_on_on_its_own() {
+        let state = CueState::new();
+        assert!(!state.is_enabled(Cue::Start));
+        state.configure(SoundCues {
+            start: true,
+            stop: false,
+            error: true,
+        });
+        assert!(state.is_enabled(Cue::Start));
+        assert!(!state.is_enabled(Cue::Stop));
+        assert!(state.is_enabled(Cue::Error));
+        assert_eq!(Cue::Error.length(), Duration::from_millis(300));
+    }
+}
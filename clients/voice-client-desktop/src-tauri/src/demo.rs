@@ -0,0 +1,197 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tauri::{AppHandle, Manager};
+
+use crate::api::Turn;
+use crate::error::AppError;
+use crate::events;
+use crate::playback;
+use crate::response_cache;
+use crate::stt::Utterance;
+use crate::types::{EventKind, SessionResponse, VoiceEvent};
+
+/// Profile a demo session uses when none is set yet
+const DEMO_PROFILE: &str = "demo";
+/// Words per streamed delta, and the wait between them
+const WORDS_PER_DELTA: usize = 3;
+const DELTA_PACE: Duration = Duration::from_millis(80);
+
+/// Answers taken in turn, about what the demo can and can't do
+const REPLIES: [&str; 3] = [
+    "This is demo mode, so no agent heard that. With a gateway set up in \
+     Settings, your question goes to your OpenClaw agent and its answer streams in \
+     here as it is written.",
+    "Replies arrive a few words at a time, like a real agent's. Try the history, \
+     copying or rating this answer, or dictation while you're here.",
+    "Nothing leaves this computer in demo mode. Turn it off in Settings, or start \
+     the app without --demo, to talk to your agent.",
+];
+
+/// Whether uploads are answered locally instead of by the gateway
+pub struct DemoState {
+    enabled: AtomicBool,
+    /// Set by `--demo` for this run, whatever the setting says
+    forced: AtomicBool,
+    next_reply: AtomicUsize,
+}
+
+impl DemoState {
+    pub fn new() -> Self {
+        Self {
+            enabled: AtomicBool::new(false),
+            forced: AtomicBool::new(false),
+            next_reply: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn configure(&self, demo_mode: bool) {
+        let forced = self.forced.load(Ordering::SeqCst);
+        self.enabled.store(demo_mode || forced, Ordering::SeqCst);
+    }
+
+    /// Demo mode for the rest of this run, from `--demo`
+    pub fn force(&self) {
+        self.forced.store(true, Ordering::SeqCst);
+        self.enabled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::SeqCst)
+    }
+}
+
+pub fn is_enabled(app: &AppHandle) -> bool {
+    app.try_state::<DemoState>()
+        .is_some_and(|state| state.is_enabled())
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// A session that only exists on this computer
+pub fn session(profile_name: &str) -> SessionResponse {
+    let now = now_ms();
+    let profile_name = match profile_name.trim() {
+        "" => DEMO_PROFILE,
+        name => name,
+    };
+    SessionResponse {
+        session_id: format!("demo-{now}"),
+        created_at: format!("{now}"),
+        profile_name: profile_name.to_string(),
+    }
+}
+
+/// What the user is shown as their question. Audio isn't transcribed in demo mode
+/// unless the profile already transcribes locally, so it is described instead.
+fn question(utterance: &Utterance) -> String {
+    match utterance {
+        Utterance::Transcript(text) => text.clone(),
+        Utterance::Audio(wav) => match playback::decode_wav_mono(wav) {
+            Ok((samples, rate)) if rate > 0 => format!(
+                "(A {:.1} s recording — demo mode doesn't transcribe)",
+                samples.len() as f64 / rate as f64
+            ),
+            _ => "(A recording — demo mode doesn't transcribe)".to_string(),
+        },
+    }
+}
+
+/// The events a gateway would stream for `question`, with `reply` cut into deltas
+fn script(question: &str, reply: &str, timestamp: &str) -> Vec<VoiceEvent> {
+    let system = |status: &str| VoiceEvent::System {
+        status: status.to_string(),
+        message: None,
+        timestamp: timestamp.to_string(),
+    };
+    let mut events = vec![
+        VoiceEvent::User {
+            text: question.to_string(),
+            confidence: 1.0,
+            timestamp: timestamp.to_string(),
+        },
+        system("typing"),
+    ];
+    let words: Vec<&str> = reply.split_inclusive(' ').collect();
+    for chunk in words.chunks(WORDS_PER_DELTA) {
+        events.push(VoiceEvent::Openclaw {
+            text: chunk.concat(),
+            done: false,
+            timestamp: timestamp.to_string(),
+        });
+    }
+    events.push(VoiceEvent::Openclaw {
+        text: String::new(),
+        done: true,
+        timestamp: timestamp.to_string(),
+    });
+    events.push(system("done"));
+    events
+}
+
+/// Answer `utterance` with a canned reply, streamed like the gateway's
+pub async fn answer(
+    app: &AppHandle,
+    turn: &Turn<'_>,
+    utterance: Utterance,
+) -> Result<(), AppError> {
+    let state = app.state::<DemoState>();
+    let reply = REPLIES[state.next_reply.fetch_add(1, Ordering::SeqCst) % REPLIES.len()];
+    let question = question(&utterance);
+    let script = script(&question, reply, &now_ms().to_string());
+    events::record_state(
+        app,
+        "demo-answered",
+        serde_json::json!({ "sessionId": turn.session_id }),
+    );
+    response_cache::replay_events(app, turn.session_id, &script, DELTA_PACE).await;
+    events::speak(app, turn.profile_name, reply, EventKind::Reply);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_script_streams_the_reply_in_deltas() {
+        let events = script("What's on today?", REPLIES[0], "1");
+        assert!(matches!(&events[0], VoiceEvent::User { text, .. } if text == "What's on today?"));
+        assert!(matches!(
+            events.last(),
+            Some(VoiceEvent::System { status, .. }) if status == "done"
+        ));
+
+        let mut streamed = String::new();
+        let mut deltas = 0;
+        for event in &events {
+            if let VoiceEvent::Openclaw {
+                text, done: false, ..
+            } = event
+            {
+                streamed.push_str(text);
+                deltas += 1;
+            }
+        }
+        assert_eq!(streamed, REPLIES[0]);
+        assert!(deltas > 1);
+    }
+
+    #[test]
+    fn test_demo_flag_outlasts_settings_changes() {
+        let state = DemoState::new();
+        state.configure(true);
+        assert!(state.is_enabled());
+        state.configure(false);
+        assert!(!state.is_enabled());
+
+        state.force();
+        state.configure(false);
+        assert!(state.is_enabled());
+    }
+}
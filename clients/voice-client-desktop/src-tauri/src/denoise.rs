@@ -0,0 +1,79 @@
+use nnnoiseless::DenoiseState;
+
+use crate::playback::resample_linear;
+
+/// RNNoise only works on 48 kHz audio
+const MODEL_RATE: u32 = 48_000;
+/// RNNoise expects samples on a 16-bit scale rather than ±1.0
+const MODEL_SCALE: f32 = i16::MAX as f32;
+
+/// Run `samples` through RNNoise, returning audio of the same rate and length
+pub fn suppress(samples: &[f32], sample_rate: u32) -> Vec<f32> {
+    if samples.is_empty() || sample_rate == 0 {
+        return samples.to_vec();
+    }
+    let input = if sample_rate == MODEL_RATE {
+        samples.to_vec()
+    } else {
+        resample_linear(samples, sample_rate, MODEL_RATE)
+    };
+
+    let mut state = DenoiseState::new();
+    let mut frame = [0.0f32; DenoiseState::FRAME_SIZE];
+    let mut denoised = [0.0f32; DenoiseState::FRAME_SIZE];
+    let mut output = Vec::with_capacity(input.len() + DenoiseState::FRAME_SIZE);
+    for chunk in input.chunks(DenoiseState::FRAME_SIZE) {
+        // The last frame is padded with silence and cut back below
+        frame.fill(0.0);
+        for (slot, sample) in frame.iter_mut().zip(chunk) {
+            *slot = sample * MODEL_SCALE;
+        }
+        state.process_frame(&mut denoised, &frame);
+        output.extend(denoised.iter().map(|s| (s / MODEL_SCALE).clamp(-1.0, 1.0)));
+    }
+    output.truncate(input.len());
+
+    if sample_rate == MODEL_RATE {
+        output
+    } else {
+        let mut output = resample_linear(&output, MODEL_RATE, sample_rate);
+        output.resize(samples.len(), 0.0);
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    /// Deterministic white noise in [-level, level]
+    fn noise(len: usize, level: f32) -> Vec<f32> {
+        let mut seed = 0x2545_f491_u32;
+        (0..len)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                (seed as f32 / u32::MAX as f32 * 2.0 - 1.0) * level
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_keeps_rate_and_length() {
+        for rate in [16_000, 44_100, 48_000] {
+            let input = noise(rate as usize / 3 + 7, 0.05);
+            assert_eq!(suppress(&input, rate).len(), input.len());
+        }
+        assert!(suppress(&[], 16_000).is_empty());
+    }
+
+    #[test]
+    fn test_steady_noise_is_attenuated() {
+        let input = noise(48_000, 0.05);
+        let output = suppress(&input, 48_000);
+        assert!(rms(&output) < rms(&input) * 0.5);
+    }
+}
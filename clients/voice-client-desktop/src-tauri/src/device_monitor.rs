@@ -0,0 +1,266 @@
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::audio::{self, AudioState};
+use crate::types::{AudioDevice, AudioDevicesChanged, DeviceFallback, MicrophoneSwitched};
+use crate::{events, settings};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Input devices as of the last poll, so plugging a headset in or out mid-session is
+/// noticed without a restart
+pub struct DeviceMonitorState {
+    /// None until the first poll
+    pub present: Arc<Mutex<Option<Vec<AudioDevice>>>>,
+    /// The configured microphone, by id or name
+    pub wanted: Arc<Mutex<Option<String>>>,
+    /// Id of the device the last recording came from, tried when the one asked for
+    /// can't be opened
+    pub last_used: Arc<Mutex<Option<String>>>,
+}
+
+impl DeviceMonitorState {
+    pub fn new() -> Self {
+        Self {
+            present: Arc::new(Mutex::new(None)),
+            wanted: Arc::new(Mutex::new(None)),
+            last_used: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn configure(&self, microphone_device_id: Option<&str>) {
+        if let Ok(mut wanted) = self.wanted.lock() {
+            *wanted = microphone_device_id
+                .filter(|id| !id.is_empty())
+                .map(String::from);
+        }
+    }
+}
+
+/// Names of the devices that appeared and disappeared between two polls
+fn diff(before: &[AudioDevice], after: &[AudioDevice]) -> (Vec<String>, Vec<String>) {
+    let missing_from = |devices: &[AudioDevice], device: &AudioDevice| {
+        !devices.iter().any(|other| other.id == device.id)
+    };
+    let added = after
+        .iter()
+        .filter(|device| missing_from(before, device))
+        .map(|device| device.name.clone())
+        .collect();
+    let removed = before
+        .iter()
+        .filter(|device| missing_from(after, device))
+        .map(|device| device.name.clone())
+        .collect();
+    (added, removed)
+}
+
+/// Whether `wanted`, an id or a name as `microphone_device_id` holds it, is plugged in
+fn is_present(devices: &[AudioDevice], wanted: &str) -> bool {
+    devices
+        .iter()
+        .any(|device| device.id == wanted || device.name == wanted)
+}
+
+/// Devices to try in turn: the one asked for, the one last recorded from, then the
+/// default (None). Those `present` shows unplugged are left out; nothing is known to
+/// be unplugged before the first poll. Asking for the default asks for nothing else.
+fn chain(
+    requested: Option<&str>,
+    last_used: Option<&str>,
+    present: Option<&[AudioDevice]>,
+) -> Vec<Option<String>> {
+    let mut chain: Vec<Option<String>> = Vec::new();
+    if let Some(requested) = requested {
+        let plugged_in = |id: &&str| present.is_none_or(|devices| is_present(devices, id));
+        for id in [Some(requested), last_used]
+            .into_iter()
+            .flatten()
+            .filter(plugged_in)
+        {
+            if !chain.iter().flatten().any(|other| other == id) {
+                chain.push(Some(id.to_string()));
+            }
+        }
+    }
+    chain.push(None);
+    chain
+}
+
+/// Start recording from `device_id`, or if it is unplugged or won't open, from the
+/// device last recorded from and then the default one. A stand-in is announced with
+/// "device-fallback". Returns the id of the device used, None for the default.
+pub fn start_recording(
+    app: &AppHandle,
+    audio: &AudioState,
+    device_id: Option<String>,
+    channel_index: Option<u16>,
+) -> Result<Option<String>, String> {
+    let requested = device_id.filter(|id| !id.is_empty());
+    let state = app.state::<DeviceMonitorState>();
+    let last_used = state.last_used.lock().ok().and_then(|id| id.clone());
+    let candidates = {
+        let present = state.present.lock().map_err(|e| e.to_string())?;
+        chain(
+            requested.as_deref(),
+            last_used.as_deref(),
+            present.as_deref(),
+        )
+    };
+
+    let mut reason = "Not connected".to_string();
+    for candidate in candidates {
+        match audio::start_recording(audio, candidate.as_deref(), channel_index) {
+            Ok(()) => {}
+            // Out of devices, or already recording, which no other device changes
+            Err(e) if candidate.is_none() || audio.is_recording.load(Ordering::SeqCst) => {
+                return Err(e);
+            }
+            Err(e) => {
+                if candidate == requested {
+                    reason = e;
+                }
+                continue;
+            }
+        }
+        if let Some(id) = &candidate {
+            if let Ok(mut last_used) = state.last_used.lock() {
+                *last_used = Some(id.clone());
+            }
+        }
+        if let Some(requested) = requested.filter(|id| Some(id) != candidate.as_ref()) {
+            let fallback = DeviceFallback {
+                requested,
+                used_device_id: candidate.clone(),
+                used_device_name: audio.device_name(),
+                reason,
+            };
+            events::record_state(app, "device-fallback", &fallback);
+            let _ = app.emit("device-fallback", &fallback);
+        }
+        return Ok(candidate);
+    }
+    unreachable!("the chain always ends with the default device")
+}
+
+/// Poll the input devices and emit "audio-devices-changed" when they change, plus
+/// "microphone-switched" when the configured microphone goes away or comes back
+pub async fn monitor(app: AppHandle) {
+    loop {
+        let host = app.state::<AudioState>().host_name();
+        let listed = tauri::async_runtime::spawn_blocking(move || {
+            audio::list_audio_devices(host.as_deref())
+        })
+        .await;
+        if let Ok(Ok(devices)) = listed {
+            update(&app, devices);
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+fn update(app: &AppHandle, devices: Vec<AudioDevice>) {
+    let state = app.state::<DeviceMonitorState>();
+    let previous = match state.present.lock() {
+        Ok(mut present) => present.replace(devices.clone()),
+        Err(_) => return,
+    };
+    let wanted = state.wanted.lock().ok().and_then(|wanted| wanted.clone());
+
+    if let Some(previous) = &previous {
+        let (added, removed) = diff(previous, &devices);
+        if added.is_empty() && removed.is_empty() {
+            return;
+        }
+        let labels = settings::load_settings(app)
+            .map(|settings| settings.device_labels)
+            .unwrap_or_default();
+        let changed = AudioDevicesChanged {
+            devices: audio::label_devices(devices.clone(), &labels),
+            added,
+            removed,
+        };
+        events::record_state(
+            app,
+            "audio-devices-changed",
+            serde_json::json!({ "added": changed.added, "removed": changed.removed }),
+        );
+        let _ = app.emit("audio-devices-changed", &changed);
+    }
+
+    let Some(wanted) = wanted else {
+        return;
+    };
+    let available = is_present(&devices, &wanted);
+    // At startup only a missing microphone is worth a warning
+    let was_available = previous
+        .as_deref()
+        .is_none_or(|previous| is_present(previous, &wanted));
+    if available != was_available {
+        let switched = MicrophoneSwitched {
+            device_id: wanted,
+            available,
+        };
+        events::record_state(app, "microphone-switched", &switched);
+        let _ = app.emit("microphone-switched", &switched);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn device(name: &str, id: &str) -> AudioDevice {
+        AudioDevice {
+            name: name.to_string(),
+            id: id.to_string(),
+            is_default: false,
+            supported_formats: Vec::new(),
+            alias: None,
+            favorite: false,
+        }
+    }
+
+    #[test]
+    fn test_plugged_and_unplugged_devices_are_told_apart() {
+        let before = vec![device("Built-in", "a"), device("Webcam", "b")];
+        let after = vec![device("Built-in", "a"), device("USB Headset", "c")];
+        assert_eq!(
+            diff(&before, &after),
+            (vec!["USB Headset".to_string()], vec!["Webcam".to_string()])
+        );
+        assert_eq!(diff(&after, &after), (vec![], vec![]));
+    }
+
+    #[test]
+    fn test_configured_microphone_matches_by_id_or_name() {
+        let devices = vec![device("USB Headset", "c")];
+        assert!(is_present(&devices, "c"));
+        assert!(is_present(&devices, "USB Headset"));
+        assert!(!is_present(&devices, "Webcam"));
+    }
+
+    #[test]
+    fn test_fallbacks_skip_unplugged_and_repeated_devices() {
+        let devices = vec![device("USB Headset", "c"), device("Webcam", "b")];
+        let some = |id: &str| Some(id.to_string());
+        assert_eq!(
+            chain(Some("c"), Some("b"), Some(&devices)),
+            vec![some("c"), some("b"), None]
+        );
+        assert_eq!(
+            chain(Some("a"), Some("b"), Some(&devices)),
+            vec![some("b"), None]
+        );
+        assert_eq!(
+            chain(Some("c"), Some("c"), Some(&devices)),
+            vec![some("c"), None]
+        );
+        // Before the first poll every device is worth a try
+        assert_eq!(chain(Some("a"), None, None), vec![some("a"), None]);
+        assert_eq!(chain(None, Some("b"), Some(&devices)), vec![None]);
+    }
+}
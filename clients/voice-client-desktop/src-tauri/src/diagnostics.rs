@@ -0,0 +1,296 @@
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+use reqwest::{Client, StatusCode};
+use tokio::net::TcpStream;
+use url::{Host, Url};
+
+use crate::api::Gateway;
+use crate::error::AppError;
+use crate::routing::Endpoint;
+use crate::types::{CheckStatus, ConnectionDiagnosis, DiagnosticStage, StageResult};
+
+/// Longest a single stage may take before it counts as failed
+const STAGE_TIMEOUT: Duration = Duration::from_secs(5);
+
+const STAGES: [DiagnosticStage; 5] = [
+    DiagnosticStage::Dns,
+    DiagnosticStage::Tcp,
+    DiagnosticStage::Tls,
+    DiagnosticStage::Auth,
+    DiagnosticStage::Profiles,
+];
+
+/// Stages in the order they ran; a failed stage ends the run
+struct Stages(Vec<StageResult>);
+
+impl Stages {
+    fn record(
+        &mut self,
+        stage: DiagnosticStage,
+        started: Instant,
+        status: CheckStatus,
+        detail: Option<String>,
+    ) -> bool {
+        self.0.push(StageResult {
+            stage,
+            status,
+            duration_ms: started.elapsed().as_millis() as u64,
+            detail,
+        });
+        status != CheckStatus::Fail
+    }
+
+    fn fail(&mut self, stage: DiagnosticStage, started: Instant, detail: String) {
+        self.record(stage, started, CheckStatus::Fail, Some(detail));
+    }
+
+    /// Mark the stages that never ran as skipped
+    fn finish(mut self) -> ConnectionDiagnosis {
+        for stage in STAGES.into_iter().skip(self.0.len()) {
+            self.0.push(StageResult {
+                stage,
+                status: CheckStatus::Skipped,
+                duration_ms: 0,
+                detail: None,
+            });
+        }
+        ConnectionDiagnosis {
+            success: self.0.iter().all(|s| s.status != CheckStatus::Fail),
+            stages: self.0,
+        }
+    }
+}
+
+/// Check each step of reaching the gateway's GET /profiles separately — DNS, TCP, TLS,
+/// credentials, the response — so a failure names the step that broke
+pub async fn diagnose(gateway: &Gateway, base_url: &str) -> ConnectionDiagnosis {
+    let mut stages = Stages(Vec::new());
+    run(gateway, base_url, &mut stages).await;
+    stages.finish()
+}
+
+async fn run(gateway: &Gateway, base_url: &str, stages: &mut Stages) {
+    let started = Instant::now();
+    let url = match gateway.routes.url(base_url, Endpoint::Profiles, &[]) {
+        Ok(url) => url,
+        Err(e) => return stages.fail(DiagnosticStage::Dns, started, e.to_string()),
+    };
+    let Some(addresses) = resolve(&url, stages).await else {
+        return;
+    };
+    let Some(stream) = connect(&addresses, stages).await else {
+        return;
+    };
+    if !handshake(&url, stream, stages).await {
+        return;
+    }
+    fetch_profiles(gateway, url, stages).await;
+}
+
+async fn resolve(url: &Url, stages: &mut Stages) -> Option<Vec<SocketAddr>> {
+    let started = Instant::now();
+    let port = url.port_or_known_default().unwrap_or(80);
+    let domain = match url.host() {
+        Some(Host::Domain(domain)) => domain,
+        Some(Host::Ipv4(ip)) => {
+            let detail = Some("IP address, nothing to resolve".to_string());
+            stages.record(DiagnosticStage::Dns, started, CheckStatus::Skipped, detail);
+            return Some(vec![SocketAddr::new(ip.into(), port)]);
+        }
+        Some(Host::Ipv6(ip)) => {
+            let detail = Some("IP address, nothing to resolve".to_string());
+            stages.record(DiagnosticStage::Dns, started, CheckStatus::Skipped, detail);
+            return Some(vec![SocketAddr::new(ip.into(), port)]);
+        }
+        None => {
+            stages.fail(
+                DiagnosticStage::Dns,
+                started,
+                format!("'{url}' has no host"),
+            );
+            return None;
+        }
+    };
+
+    let lookup = tokio::net::lookup_host((domain, port));
+    let addresses: Vec<SocketAddr> = match tokio::time::timeout(STAGE_TIMEOUT, lookup).await {
+        Ok(Ok(addresses)) => addresses.collect(),
+        Ok(Err(e)) => {
+            stages.fail(
+                DiagnosticStage::Dns,
+                started,
+                format!("Can't resolve {domain}: {e}"),
+            );
+            return None;
+        }
+        Err(_) => {
+            let detail = format!("Resolving {domain} timed out");
+            stages.fail(DiagnosticStage::Dns, started, detail);
+            return None;
+        }
+    };
+    if addresses.is_empty() {
+        stages.fail(
+            DiagnosticStage::Dns,
+            started,
+            format!("{domain} has no addresses"),
+        );
+        return None;
+    }
+    let listed: Vec<String> = addresses.iter().map(|a| a.ip().to_string()).collect();
+    stages.record(
+        DiagnosticStage::Dns,
+        started,
+        CheckStatus::Pass,
+        Some(listed.join(", ")),
+    );
+    Some(addresses)
+}
+
+/// Connect to the first address that answers
+async fn connect(addresses: &[SocketAddr], stages: &mut Stages) -> Option<TcpStream> {
+    let started = Instant::now();
+    let mut errors = Vec::new();
+    for address in addresses {
+        match tokio::time::timeout(STAGE_TIMEOUT, TcpStream::connect(address)).await {
+            Ok(Ok(stream)) => {
+                let detail = Some(format!("Connected to {address}"));
+                stages.record(DiagnosticStage::Tcp, started, CheckStatus::Pass, detail);
+                return Some(stream);
+            }
+            Ok(Err(e)) => errors.push(format!("{address}: {e}")),
+            Err(_) => errors.push(format!("{address}: timed out")),
+        }
+    }
+    stages.fail(DiagnosticStage::Tcp, started, errors.join("; "));
+    None
+}
+
+/// Whether the TLS handshake succeeded or wasn't needed
+async fn handshake(url: &Url, stream: TcpStream, stages: &mut Stages) -> bool {
+    let started = Instant::now();
+    if url.scheme() != "https" {
+        let detail = Some("Plain http, not encrypted".to_string());
+        return stages.record(DiagnosticStage::Tls, started, CheckStatus::Skipped, detail);
+    }
+    let domain = url.host_str().unwrap_or_default();
+    let connector = match tokio_native_tls::native_tls::TlsConnector::new() {
+        Ok(connector) => tokio_native_tls::TlsConnector::from(connector),
+        Err(e) => {
+            stages.fail(DiagnosticStage::Tls, started, e.to_string());
+            return false;
+        }
+    };
+    match tokio::time::timeout(STAGE_TIMEOUT, connector.connect(domain, stream)).await {
+        Ok(Ok(_)) => stages.record(DiagnosticStage::Tls, started, CheckStatus::Pass, None),
+        Ok(Err(e)) => {
+            stages.fail(
+                DiagnosticStage::Tls,
+                started,
+                format!("Handshake failed: {e}"),
+            );
+            false
+        }
+        Err(_) => {
+            let detail = "Handshake timed out".to_string();
+            stages.fail(DiagnosticStage::Tls, started, detail);
+            false
+        }
+    }
+}
+
+async fn fetch_profiles(gateway: &Gateway, url: Url, stages: &mut Stages) {
+    let started = Instant::now();
+    let client = Client::new();
+    let request = client.get(url).timeout(STAGE_TIMEOUT);
+    let response = match gateway.execute(request).await {
+        Ok(response) => response,
+        Err(e) => {
+            let detail = format!("Request failed: {e}");
+            return stages.fail(DiagnosticStage::Auth, started, detail);
+        }
+    };
+    let status = response.status();
+    if matches!(status, StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) {
+        let body = response.text().await.unwrap_or_default();
+        let error = AppError::from_gateway_response(status, &body);
+        return stages.fail(DiagnosticStage::Auth, started, error.to_string());
+    }
+    let detail = Some(format!("HTTP {}", status.as_u16()));
+    stages.record(DiagnosticStage::Auth, started, CheckStatus::Pass, detail);
+
+    let started = Instant::now();
+    let body = match response.text().await {
+        Ok(body) => body,
+        Err(e) => {
+            let detail = format!("Reading the response failed: {e}");
+            return stages.fail(DiagnosticStage::Profiles, started, detail);
+        }
+    };
+    if !status.is_success() {
+        let error = AppError::from_gateway_response(status, &body);
+        return stages.fail(DiagnosticStage::Profiles, started, error.to_string());
+    }
+    let (status, detail) = describe_profiles(&body);
+    stages.record(DiagnosticStage::Profiles, started, status, detail);
+}
+
+/// A successful /profiles body: JSON listing the profiles, or a warning that it isn't
+fn describe_profiles(body: &str) -> (CheckStatus, Option<String>) {
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(body) else {
+        let detail = "The response isn't JSON; is this an OpenClaw gateway?".to_string();
+        return (CheckStatus::Warn, Some(detail));
+    };
+    let profiles = value.as_array().or_else(|| value["profiles"].as_array());
+    let detail = profiles.map(|profiles| match profiles.len() {
+        1 => "1 profile".to_string(),
+        count => format!("{count} profiles"),
+    });
+    (CheckStatus::Pass, detail)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stages_after_a_failure_are_skipped() {
+        let mut stages = Stages(Vec::new());
+        let started = Instant::now();
+        stages.record(DiagnosticStage::Dns, started, CheckStatus::Pass, None);
+        stages.fail(DiagnosticStage::Tcp, started, "refused".to_string());
+        let diagnosis = stages.finish();
+
+        assert!(!diagnosis.success);
+        let statuses: Vec<_> = diagnosis
+            .stages
+            .iter()
+            .map(|s| (s.stage, s.status))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                (DiagnosticStage::Dns, CheckStatus::Pass),
+                (DiagnosticStage::Tcp, CheckStatus::Fail),
+                (DiagnosticStage::Tls, CheckStatus::Skipped),
+                (DiagnosticStage::Auth, CheckStatus::Skipped),
+                (DiagnosticStage::Profiles, CheckStatus::Skipped),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_profiles_body_is_described() {
+        assert_eq!(
+            describe_profiles(r#"["default", "work"]"#),
+            (CheckStatus::Pass, Some("2 profiles".to_string()))
+        );
+        assert_eq!(
+            describe_profiles(r#"{ "profiles": [{ "name": "default" }] }"#),
+            (CheckStatus::Pass, Some("1 profile".to_string()))
+        );
+        assert_eq!(describe_profiles("{}"), (CheckStatus::Pass, None));
+        assert_eq!(describe_profiles("<html>").0, CheckStatus::Warn);
+    }
+}
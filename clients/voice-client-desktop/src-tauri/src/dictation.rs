@@ -0,0 +1,374 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::mpsc;
+
+use crate::api::Turn;
+use crate::audio::{self, AudioState};
+use crate::commands;
+use crate::context;
+use crate::device_monitor;
+use crate::events;
+use crate::mic_level;
+use crate::mic_usage;
+use crate::session::SessionState;
+use crate::types::{DictationUtterance, MicSource};
+use crate::vad::{self, SilenceDetector, VadState};
+use crate::waveform;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// An utterance nobody paused in is cut here anyway, so it doesn't wait forever
+const MAX_UTTERANCE: Duration = Duration::from_secs(60);
+/// Audio kept from before speech starts, so the first syllable isn't clipped
+const PREROLL: Duration = Duration::from_millis(500);
+/// Utterances waiting to be sent; the capture carries on while they wait
+const QUEUE_UTTERANCES: usize = 8;
+
+/// Where dictated utterances go, as the popup passes it to `start_dictation`
+#[derive(Debug, Clone)]
+pub struct DictationTarget {
+    pub base_url: String,
+    pub session_id: String,
+    pub profile_name: String,
+    pub session_key: Option<String>,
+    pub response_language: Option<String>,
+}
+
+impl DictationTarget {
+    fn turn(&self) -> Turn<'_> {
+        Turn {
+            base_url: &self.base_url,
+            session_id: &self.session_id,
+            profile_name: &self.profile_name,
+            session_key: self.session_key.as_deref(),
+            response_language: self.response_language.as_deref(),
+            context: None,
+        }
+    }
+}
+
+/// The pause that ends an utterance, and the dictation running now, if any
+pub struct DictationState {
+    pub pause: Arc<Mutex<Duration>>,
+    /// Set from `start` until the capture loop has sent off its last utterance
+    running: Arc<AtomicBool>,
+    /// Bumped by `stop`; the loop ends when it no longer matches the one it started with
+    generation: Arc<AtomicU64>,
+}
+
+impl DictationState {
+    pub fn new() -> Self {
+        Self {
+            pause: Arc::new(Mutex::new(Duration::from_millis(1200))),
+            running: Arc::new(AtomicBool::new(false)),
+            generation: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    pub fn configure(&self, pause: Duration) {
+        if let Ok(mut current) = self.pause.lock() {
+            *current = pause;
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+}
+
+/// Splits a capture that keeps growing into utterances, each ending at a pause
+#[derive(Debug)]
+pub struct Segmenter {
+    threshold_db: f32,
+    pause: Duration,
+    detector: SilenceDetector,
+    frame_len: usize,
+    max_len: usize,
+    preroll: usize,
+    /// Samples at the front of the buffer already fed to the detector
+    scanned: usize,
+}
+
+impl Segmenter {
+    pub fn new(threshold_db: f32, pause: Duration, sample_rate: u32) -> Self {
+        let samples = |duration: Duration| (duration.as_secs_f64() * sample_rate as f64) as usize;
+        Self {
+            threshold_db,
+            pause,
+            detector: SilenceDetector::new(threshold_db, pause),
+            frame_len: SilenceDetector::frame_len(sample_rate),
+            max_len: samples(MAX_UTTERANCE),
+            preroll: samples(PREROLL),
+            scanned: 0,
+        }
+    }
+
+    /// Speech was heard since the last utterance was cut
+    pub fn heard_speech(&self) -> bool {
+        self.detector.heard_speech()
+    }
+
+    /// Scan what was captured since the last call and take the utterances that ended
+    /// off the front of `samples`. Quiet before speech is dropped too, all but the
+    /// preroll, so the buffer only ever holds the utterance in progress.
+    pub fn cut(&mut self, samples: &mut Vec<f32>) -> Vec<Vec<f32>> {
+        let mut utterances = Vec::new();
+        while self.scanned + self.frame_len <= samples.len() {
+            let frame = &samples[self.scanned..self.scanned + self.frame_len];
+            let paused = self.detector.push(frame);
+            self.scanned += self.frame_len;
+            if paused || (self.scanned >= self.max_len && self.heard_speech()) {
+                utterances.push(samples.drain(..self.scanned).collect());
+                self.scanned = 0;
+                self.detector = SilenceDetector::new(self.threshold_db, self.pause);
+            } else if !self.heard_speech() && self.scanned > self.preroll {
+                let excess = self.scanned - self.preroll;
+                samples.drain(..excess);
+                self.scanned -= excess;
+            }
+        }
+        utterances
+    }
+}
+
+struct Utterance {
+    index: u32,
+    samples: Vec<f32>,
+    sample_rate: u32,
+}
+
+fn emit(app: &AppHandle, utterance: &DictationUtterance) {
+    events::record_state(app, "dictation-utterance", utterance);
+    let _ = app.emit("dictation-utterance", utterance);
+}
+
+fn changed(app: &AppHandle, dictating: bool) {
+    events::record_state(
+        app,
+        "dictation-changed",
+        serde_json::json!({ "dictating": dictating }),
+    );
+    let _ = app.emit("dictation-changed", dictating);
+}
+
+/// Start capturing from `device_id` and send every utterance to `target` as it ends
+pub fn start(
+    app: &AppHandle,
+    device_id: Option<String>,
+    target: DictationTarget,
+) -> Result<(), String> {
+    let state = app.state::<DictationState>();
+    if state.running.swap(true, Ordering::SeqCst) {
+        return Err("Dictation is already running".to_string());
+    }
+    let audio = app.state::<AudioState>();
+    if let Err(e) = device_monitor::start_recording(app, &audio, device_id, None) {
+        state.running.store(false, Ordering::SeqCst);
+        return Err(e);
+    }
+    audio.mark_in_use(mic_usage::opened(
+        app,
+        MicSource::Dictation,
+        None,
+        audio.device_name(),
+    ));
+    mic_level::watch(app);
+    waveform::watch(app);
+    app.state::<SessionState>().activate(
+        &target.session_id,
+        &target.profile_name,
+        &target.base_url,
+    );
+    let generation = state.generation.load(Ordering::SeqCst);
+    changed(app, true);
+
+    let (queue, deliveries) = mpsc::channel(QUEUE_UTTERANCES);
+    tauri::async_runtime::spawn(send_all(app.clone(), target, deliveries));
+    tauri::async_runtime::spawn(capture(app.clone(), generation, queue));
+    Ok(())
+}
+
+/// End dictation; what was said since the last pause is still sent
+pub fn stop(app: &AppHandle) {
+    let state = app.state::<DictationState>();
+    if state.is_running() {
+        state.generation.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+/// Cut the capture into utterances until dictation is stopped or the recording ends
+async fn capture(app: AppHandle, generation: u64, queue: mpsc::Sender<Utterance>) {
+    let state = app.state::<DictationState>();
+    let audio = app.state::<AudioState>();
+    let pause = state
+        .pause
+        .lock()
+        .map(|p| *p)
+        .unwrap_or(Duration::from_millis(1200));
+    let vad = app.state::<VadState>();
+    let threshold_db = vad.threshold_db.lock().map(|t| *t).unwrap_or(-45.0);
+    let calibrate = vad.calibrate.load(Ordering::SeqCst);
+    let mut segmenter: Option<Segmenter> = None;
+    let mut index = 0;
+    loop {
+        tokio::time::sleep(POLL_INTERVAL).await;
+        let stopped = state.generation.load(Ordering::SeqCst) != generation;
+        if stopped || !audio.is_recording.load(Ordering::SeqCst) {
+            break;
+        }
+        let Some(rate) = audio.sample_rate.lock().ok().and_then(|rate| *rate) else {
+            continue;
+        };
+        let utterances = match audio.samples.lock() {
+            Ok(mut samples) => {
+                if segmenter.is_none() {
+                    let threshold_db = if calibrate {
+                        // Calibrating waits for the first 300 ms, like auto-stop
+                        let Some(floor_db) = vad::noise_floor_db(&samples, rate) else {
+                            continue;
+                        };
+                        vad::adapt_threshold(threshold_db, floor_db)
+                    } else {
+                        threshold_db
+                    };
+                    segmenter = Some(Segmenter::new(threshold_db, pause, rate));
+                }
+                let Some(segmenter) = segmenter.as_mut() else {
+                    continue;
+                };
+                let before = samples.len();
+                let utterances = segmenter.cut(&mut samples);
+                // Taken off the front like audio dropped at the memory cap, so
+                // readers keeping a position in the buffer stay in step
+                audio
+                    .dropped
+                    .fetch_add(before - samples.len(), Ordering::SeqCst);
+                utterances
+            }
+            Err(_) => break,
+        };
+        let pipeline = audio.preprocessing();
+        for samples in utterances {
+            // As `take_recording` does for the last one
+            let (samples, _) = pipeline.run(samples, rate);
+            if samples.is_empty() {
+                continue;
+            }
+            index += 1;
+            queue_utterance(&app, &queue, index, samples, rate).await;
+        }
+    }
+
+    // Whatever was said since the last pause goes out last
+    let heard_speech = segmenter.as_ref().is_some_and(Segmenter::heard_speech);
+    if let Ok(tail) = audio::take_recording(&audio) {
+        if heard_speech && !tail.samples.is_empty() {
+            index += 1;
+            queue_utterance(&app, &queue, index, tail.samples, tail.sample_rate).await;
+        }
+    }
+    drop(queue);
+    state.running.store(false, Ordering::SeqCst);
+    changed(&app, false);
+}
+
+async fn queue_utterance(
+    app: &AppHandle,
+    queue: &mpsc::Sender<Utterance>,
+    index: u32,
+    samples: Vec<f32>,
+    sample_rate: u32,
+) {
+    emit(
+        app,
+        &DictationUtterance::Captured {
+            index,
+            duration_ms: samples.len() as u64 * 1000 / sample_rate.max(1) as u64,
+        },
+    );
+    // The context is read when the utterance ends, which is when the user moved on
+    context::begin_turn(app);
+    let utterance = Utterance {
+        index,
+        samples,
+        sample_rate,
+    };
+    if queue.send(utterance).await.is_err() {
+        emit(
+            app,
+            &DictationUtterance::Failed {
+                index,
+                message: "Dictation stopped before this utterance was sent".to_string(),
+            },
+        );
+    }
+}
+
+/// Send utterances one at a time, in the order they were spoken
+async fn send_all(app: AppHandle, target: DictationTarget, mut queue: mpsc::Receiver<Utterance>) {
+    while let Some(utterance) = queue.recv().await {
+        let recording = audio::Recording {
+            samples: utterance.samples,
+            sample_rate: utterance.sample_rate,
+            trimmed_start: 0,
+        };
+        let index = utterance.index;
+        let event = match commands::send_recording(&app, target.turn(), recording, None).await {
+            Ok(()) => DictationUtterance::Sent { index },
+            Err(e) => DictationUtterance::Failed {
+                index,
+                message: e.to_string(),
+            },
+        };
+        emit(&app, &event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 16_000;
+
+    fn audio(level: f32, ms: u32) -> Vec<f32> {
+        vec![level; (RATE * ms / 1000) as usize]
+    }
+
+    #[test]
+    fn test_cuts_an_utterance_at_each_pause() {
+        let mut segmenter = Segmenter::new(-40.0, Duration::from_millis(400), RATE);
+        let mut samples = audio(0.0, 2000);
+        samples.extend(audio(0.2, 1000));
+        assert!(segmenter.cut(&mut samples).is_empty());
+        // Quiet before the speech is dropped down to the preroll
+        let speech = (RATE * 1000 / 1000) as usize;
+        assert!(samples.len() > speech && samples.len() <= speech + segmenter.preroll);
+        assert!(segmenter.heard_speech());
+
+        let buffered = samples.len();
+        samples.extend(audio(0.0, 600));
+        samples.extend(audio(0.2, 800));
+        samples.extend(audio(0.0, 500));
+        let utterances = segmenter.cut(&mut samples);
+        assert_eq!(utterances.len(), 2);
+        // Preroll and speech, then the pause that ended it
+        assert_eq!(utterances[0].len(), buffered + (RATE * 400 / 1000) as usize);
+        assert_eq!(
+            utterances[1].len(),
+            (RATE * (200 + 800 + 400) / 1000) as usize
+        );
+        assert!(!segmenter.heard_speech());
+    }
+
+    #[test]
+    fn test_long_speech_is_cut_at_the_limit() {
+        let mut segmenter = Segmenter::new(-40.0, Duration::from_millis(400), RATE);
+        let mut samples = audio(0.2, 61_000);
+        let utterances = segmenter.cut(&mut samples);
+        assert_eq!(utterances.len(), 1);
+        assert_eq!(utterances[0].len(), (RATE * 60) as usize);
+        assert!(segmenter.heard_speech());
+    }
+}
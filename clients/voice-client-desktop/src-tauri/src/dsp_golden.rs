@@ -0,0 +1,157 @@
+//! Golden tests of the DSP stages a recording goes through before it is uploaded:
+//! each fixture in `fixtures/audio/` is run through a stage and compared, sample by
+//! sample, with `snapshots/dsp/<fixture>.<stage>.wav`. The fixtures are 16-bit mono
+//! clips of synthesized voiced speech between pauses over a steady room noise, as a
+//! microphone would deliver them; real recordings can be added next to them. Regenerate
+//! the goldens after an intended change with `UPDATE_SNAPSHOTS=1 cargo test dsp_golden`
+//! and listen to them before checking them in.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::audio::{self, Preprocessing, SilenceTrim};
+use crate::playback::{decode_wav_mono, resample_linear};
+use crate::types::{Effect, PreprocessStage};
+
+const FIXTURE_DIR: &str = "fixtures/audio";
+const GOLDEN_DIR: &str = "snapshots/dsp";
+/// Largest difference allowed per sample, about -80 dBFS: far below anything
+/// audible, far above float rounding across platforms
+const TOLERANCE: f32 = 1e-4;
+
+#[derive(Debug, Clone, Copy)]
+enum Stage {
+    /// Silence trimming as the pipeline runs it, with the default settings turned on
+    Trim,
+    /// The effects chain's noise gate
+    Gate,
+    /// The pipeline's automatic gain control
+    Agc,
+    /// Down to the 16 kHz of the voiceSmall preset
+    Resample,
+}
+
+impl Stage {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Trim => "trim",
+            Self::Gate => "gate",
+            Self::Agc => "agc",
+            Self::Resample => "resample",
+        }
+    }
+
+    fn run(self, samples: Vec<f32>, sample_rate: u32) -> (Vec<f32>, u32) {
+        let pipeline = |stage| Preprocessing {
+            stages: vec![stage],
+            high_pass_hz: 0,
+            denoise: false,
+            trim: SilenceTrim {
+                enabled: true,
+                ..SilenceTrim::default()
+            },
+        };
+        let gate = Effect::Gate {
+            threshold_db: -40.0,
+            hold_ms: 100,
+        };
+        let output = match self {
+            Self::Trim => pipeline(PreprocessStage::Trim).run(samples, sample_rate).0,
+            Self::Gate => gate.process(samples, sample_rate),
+            Self::Agc => pipeline(PreprocessStage::Agc).run(samples, sample_rate).0,
+            Self::Resample => return (resample_linear(&samples, sample_rate, 16_000), 16_000),
+        };
+        (output, sample_rate)
+    }
+}
+
+/// Fixtures and the stages each one is run through
+const CASES: &[(&str, Stage)] = &[
+    ("speech-with-pauses", Stage::Trim),
+    ("speech-with-pauses", Stage::Gate),
+    ("speech-with-pauses", Stage::Agc),
+    // A noise floor loud enough for trimming to raise its threshold
+    ("noisy-room", Stage::Trim),
+    ("noisy-room", Stage::Gate),
+    ("noisy-room", Stage::Agc),
+    ("speech-48k", Stage::Resample),
+];
+
+fn manifest_path(path: String) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join(path)
+}
+
+fn read_wav(path: &Path) -> (Vec<f32>, u32) {
+    let bytes = std::fs::read(path).unwrap_or_else(|e| panic!("{}: {e}", path.display()));
+    decode_wav_mono(&bytes).unwrap_or_else(|e| panic!("{}: {e}", path.display()))
+}
+
+/// Where `actual` first strays from `expected` by more than the tolerance, if it does
+fn compare(expected: &[f32], actual: &[f32], sample_rate: u32) -> Result<(), String> {
+    if expected.len() != actual.len() {
+        return Err(format!(
+            "{} samples instead of {}",
+            actual.len(),
+            expected.len()
+        ));
+    }
+    let at = |index: usize| Duration::from_secs_f64(index as f64 / sample_rate as f64);
+    match expected
+        .iter()
+        .zip(actual)
+        .position(|(e, a)| (e - a).abs() > TOLERANCE)
+    {
+        Some(index) => Err(format!(
+            "sample {index} ({:?} in) is {} instead of {}",
+            at(index),
+            actual[index],
+            expected[index]
+        )),
+        None => Ok(()),
+    }
+}
+
+#[test]
+fn test_dsp_stages_match_their_goldens() {
+    let update = std::env::var_os("UPDATE_SNAPSHOTS").is_some();
+    let mut failures = Vec::new();
+    for &(fixture, stage) in CASES {
+        let input = manifest_path(format!("{FIXTURE_DIR}/{fixture}.wav"));
+        let (samples, sample_rate) = read_wav(&input);
+        let (actual, actual_rate) = stage.run(samples, sample_rate);
+        let golden = manifest_path(format!("{GOLDEN_DIR}/{fixture}.{}.wav", stage.name()));
+        if update {
+            let wav = audio::encode_wav(&actual, actual_rate).unwrap();
+            std::fs::write(&golden, wav).unwrap();
+            continue;
+        }
+        let (expected, expected_rate) = read_wav(&golden);
+        let result = if expected_rate == actual_rate {
+            compare(&expected, &actual, actual_rate)
+        } else {
+            Err(format!("{actual_rate} Hz instead of {expected_rate} Hz"))
+        };
+        if let Err(e) = result {
+            failures.push(format!("{fixture} through {}: {e}", stage.name()));
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "DSP output no longer matches {GOLDEN_DIR}:\n{}\n\
+         if the change is intended, regenerate the goldens with \
+         `UPDATE_SNAPSHOTS=1 cargo test dsp_golden`",
+        failures.join("\n")
+    );
+}
+
+#[test]
+fn test_compare_reports_where_output_strays() {
+    let expected = vec![0.0, 0.5, -0.5, 0.25];
+    assert!(compare(&expected, &[0.0, 0.50005, -0.5, 0.25], 4).is_ok());
+    let strayed = compare(&expected, &[0.0, 0.5, -0.4, 0.25], 4).unwrap_err();
+    assert!(strayed.starts_with("sample 2 (500ms in)"), "{strayed}");
+    assert_eq!(
+        compare(&expected, &[0.0], 4).unwrap_err(),
+        "1 samples instead of 4"
+    );
+}
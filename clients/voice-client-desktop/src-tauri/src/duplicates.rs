@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use sha2::{Digest, Sha256};
+use tauri::AppHandle;
+
+use crate::error::AppError;
+use crate::events;
+
+/// How long a sent recording is remembered, so sending it again is refused
+const WINDOW: Duration = Duration::from_secs(10);
+
+type Fingerprint = [u8; 32];
+
+/// Hash of a recording and the session it goes to
+fn fingerprint(session_id: &str, payload: &[u8]) -> Fingerprint {
+    let mut hasher = Sha256::new();
+    hasher.update(session_id.as_bytes());
+    hasher.update([0]);
+    hasher.update(payload);
+    hasher.finalize().into()
+}
+
+/// Why an upload was refused
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Duplicate {
+    InFlight,
+    JustSent,
+}
+
+/// Uploads in flight and those sent in the last few seconds, by fingerprint, so a
+/// hotkey or window invoking `stop_and_send` twice doesn't send one recording twice
+pub struct DuplicateState {
+    /// When each upload was sent; None while it is still in flight
+    uploads: Arc<Mutex<HashMap<Fingerprint, Option<Instant>>>>,
+}
+
+impl DuplicateState {
+    pub fn new() -> Self {
+        Self {
+            uploads: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    fn claim(&self, fingerprint: Fingerprint, now: Instant) -> Result<(), Duplicate> {
+        let Ok(mut uploads) = self.uploads.lock() else {
+            return Ok(());
+        };
+        uploads.retain(|_, sent| sent.is_none_or(|at| now.saturating_duration_since(at) < WINDOW));
+        match uploads.get(&fingerprint) {
+            Some(None) => Err(Duplicate::InFlight),
+            Some(Some(_)) => Err(Duplicate::JustSent),
+            None => {
+                uploads.insert(fingerprint, None);
+                Ok(())
+            }
+        }
+    }
+
+    fn release(&self, fingerprint: Fingerprint, sent_at: Option<Instant>) {
+        if let Ok(mut uploads) = self.uploads.lock() {
+            match sent_at {
+                Some(at) => uploads.insert(fingerprint, Some(at)),
+                None => uploads.remove(&fingerprint),
+            };
+        }
+    }
+}
+
+/// An upload's hold on its fingerprint. A failed upload lets go of it when dropped,
+/// so the recording can be retried straight away; a sent one is remembered.
+pub struct Claim<'a> {
+    state: &'a DuplicateState,
+    fingerprint: Fingerprint,
+    sent: bool,
+}
+
+impl Claim<'_> {
+    pub fn sent(mut self) {
+        self.sent = true;
+    }
+}
+
+impl Drop for Claim<'_> {
+    fn drop(&mut self) {
+        self.state
+            .release(self.fingerprint, self.sent.then(Instant::now));
+    }
+}
+
+/// Claim `payload` for sending to `session_id`, refusing it while the same recording is
+/// on its way there or was sent within the last few seconds
+pub fn claim<'a>(
+    app: &AppHandle,
+    state: &'a DuplicateState,
+    session_id: &str,
+    payload: &[u8],
+) -> Result<Claim<'a>, AppError> {
+    let fingerprint = fingerprint(session_id, payload);
+    match state.claim(fingerprint, Instant::now()) {
+        Ok(()) => Ok(Claim {
+            state,
+            fingerprint,
+            sent: false,
+        }),
+        Err(duplicate) => {
+            let in_flight = duplicate == Duplicate::InFlight;
+            events::record_state(
+                app,
+                "upload-duplicate",
+                serde_json::json!({ "sessionId": session_id, "inFlight": in_flight }),
+            );
+            Err(AppError::DuplicateUpload { in_flight })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repeats_are_refused_until_the_window_passes() {
+        let state = DuplicateState::new();
+        let t0 = Instant::now();
+        let recording = fingerprint("voice-1", b"RIFF....WAVE");
+        assert_eq!(state.claim(recording, t0), Ok(()));
+        assert_eq!(state.claim(recording, t0), Err(Duplicate::InFlight));
+        // The same audio for another session is a different upload
+        assert_eq!(
+            state.claim(fingerprint("voice-2", b"RIFF....WAVE"), t0),
+            Ok(())
+        );
+
+        state.release(recording, Some(t0));
+        assert_eq!(
+            state.claim(recording, t0 + Duration::from_secs(2)),
+            Err(Duplicate::JustSent)
+        );
+        assert_eq!(state.claim(recording, t0 + WINDOW), Ok(()));
+    }
+
+    #[test]
+    fn test_failed_upload_can_be_retried_at_once() {
+        let state = DuplicateState::new();
+        let recording = fingerprint("voice-1", b"RIFF....WAVE");
+        assert_eq!(state.claim(recording, Instant::now()), Ok(()));
+        drop(Claim {
+            state: &state,
+            fingerprint: recording,
+            sent: false,
+        });
+        assert_eq!(state.claim(recording, Instant::now()), Ok(()));
+    }
+}
@@ -0,0 +1,333 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use tauri::{AppHandle, Manager};
+
+use crate::audio::{db_to_gain, to_dbfs};
+use crate::quality::Biquad;
+use crate::types::{Effect, EffectStage};
+use crate::{denoise, events};
+
+/// Frame the gate and AGC measure levels over
+const FRAME_MS: u64 = 10;
+/// The AGC leaves frames quieter than this alone rather than amplifying noise
+const AGC_FLOOR_DB: f32 = -60.0;
+/// Share of the way to its new gain the AGC moves each frame, settling in about 200 ms
+const AGC_SMOOTHING: f32 = 0.05;
+/// Time the limiter takes to let go after a peak
+const LIMITER_RELEASE_SECS: f32 = 0.05;
+
+/// Effects chains per profile name, run over recordings before they are sent.
+/// Profiles without one send the recording as captured.
+pub struct EffectsState {
+    pub chains: Arc<Mutex<HashMap<String, Vec<EffectStage>>>>,
+}
+
+impl EffectsState {
+    pub fn new() -> Self {
+        Self {
+            chains: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn configure(&self, chains: HashMap<String, Vec<EffectStage>>) {
+        if let Ok(mut current) = self.chains.lock() {
+            *current = chains;
+        }
+    }
+
+    /// The enabled effects of `profile_name`'s chain, in order
+    pub fn chain_for(&self, profile_name: &str) -> Vec<Effect> {
+        self.chains
+            .lock()
+            .ok()
+            .and_then(|chains| chains.get(profile_name).cloned())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|stage| stage.enabled)
+            .map(|stage| stage.effect)
+            .collect()
+    }
+}
+
+fn rms(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+}
+
+fn frame_len(sample_rate: u32) -> usize {
+    (sample_rate as u64 * FRAME_MS / 1000).max(1) as usize
+}
+
+impl Effect {
+    /// Name recorded in the "effects-applied" event
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::HighPass { .. } => "highPass",
+            Self::Gate { .. } => "gate",
+            Self::Agc { .. } => "agc",
+            Self::Denoise => "denoise",
+            Self::Limiter { .. } => "limiter",
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        match *self {
+            Self::HighPass { cutoff_hz }
+                if cutoff_hz.is_nan() || cutoff_hz <= 0.0 || cutoff_hz > 1_000.0 =>
+            {
+                Err(format!(
+                    "highPass cutoffHz must be above 0 and at most 1000, not {cutoff_hz}"
+                ))
+            }
+            Self::Gate { threshold_db, .. } if threshold_db.is_nan() || threshold_db >= 0.0 => Err(
+                format!("gate thresholdDb must be below 0, not {threshold_db}"),
+            ),
+            Self::Agc { target_db, .. } if target_db.is_nan() || target_db >= 0.0 => {
+                Err(format!("agc targetDb must be below 0, not {target_db}"))
+            }
+            Self::Agc { max_gain_db, .. } if !(0.0..=40.0).contains(&max_gain_db) => Err(format!(
+                "agc maxGainDb must be between 0 and 40, not {max_gain_db}"
+            )),
+            Self::Limiter { ceiling_db } if ceiling_db.is_nan() || ceiling_db > 0.0 => Err(
+                format!("limiter ceilingDb must be at most 0, not {ceiling_db}"),
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    /// Run the effect over a whole mono recording; the length and rate don't change
+    pub fn process(&self, mut samples: Vec<f32>, sample_rate: u32) -> Vec<f32> {
+        match *self {
+            Self::HighPass { cutoff_hz } => {
+                // A cutoff past Nyquist would filter nothing sensible
+                if cutoff_hz < sample_rate as f32 / 2.0 {
+                    Biquad::new(cutoff_hz, sample_rate, true).process(&mut samples);
+                }
+                samples
+            }
+            Self::Gate {
+                threshold_db,
+                hold_ms,
+            } => gate(samples, sample_rate, threshold_db, hold_ms),
+            Self::Agc {
+                target_db,
+                max_gain_db,
+            } => agc(samples, sample_rate, target_db, max_gain_db),
+            Self::Denoise => denoise::suppress(&samples, sample_rate),
+            Self::Limiter { ceiling_db } => limit(samples, sample_rate, ceiling_db),
+        }
+    }
+}
+
+/// Zero frames below the threshold, keeping `hold_ms` after speech so word endings
+/// aren't clipped. The gate starts closed.
+fn gate(mut samples: Vec<f32>, sample_rate: u32, threshold_db: f32, hold_ms: u64) -> Vec<f32> {
+    let hold_frames = hold_ms / FRAME_MS;
+    let mut quiet_frames = hold_frames;
+    for frame in samples.chunks_mut(frame_len(sample_rate)) {
+        if to_dbfs(rms(frame)) >= threshold_db {
+            quiet_frames = 0;
+        } else {
+            quiet_frames = quiet_frames.saturating_add(1);
+        }
+        if quiet_frames > hold_frames {
+            frame.fill(0.0);
+        }
+    }
+    samples
+}
+
+/// Move each frame's level towards `target_db`, ramping the gain across frames so
+/// changes don't click
+fn agc(mut samples: Vec<f32>, sample_rate: u32, target_db: f32, max_gain_db: f32) -> Vec<f32> {
+    let mut gain_db = 0.0f32;
+    for frame in samples.chunks_mut(frame_len(sample_rate)) {
+        let from = db_to_gain(gain_db);
+        let level = to_dbfs(rms(frame));
+        if level > AGC_FLOOR_DB {
+            let wanted = (target_db - level).min(max_gain_db);
+            gain_db += (wanted - gain_db) * AGC_SMOOTHING;
+        }
+        let to = db_to_gain(gain_db);
+        let len = frame.len() as f32;
+        for (i, sample) in frame.iter_mut().enumerate() {
+            *sample *= from + (to - from) * (i + 1) as f32 / len;
+        }
+    }
+    samples
+}
+
+/// Peak limiter with instant attack, so no sample ends up above the ceiling
+fn limit(mut samples: Vec<f32>, sample_rate: u32, ceiling_db: f32) -> Vec<f32> {
+    let ceiling = db_to_gain(ceiling_db);
+    let release = 1.0 - (-1.0 / (LIMITER_RELEASE_SECS * sample_rate as f32)).exp();
+    let mut gain = 1.0f32;
+    for sample in samples.iter_mut() {
+        let allowed = if sample.abs() > ceiling {
+            ceiling / sample.abs()
+        } else {
+            1.0
+        };
+        gain = (gain + (1.0 - gain) * release).min(allowed);
+        *sample *= gain;
+    }
+    samples
+}
+
+/// Check every profile's chain for parameters the effects can't work with
+pub fn validate(chains: &HashMap<String, Vec<EffectStage>>) -> Result<(), String> {
+    for (profile_name, chain) in chains {
+        for stage in chain {
+            stage
+                .effect
+                .validate()
+                .map_err(|e| format!("Effects chain of '{profile_name}': {e}"))?;
+        }
+    }
+    Ok(())
+}
+
+/// Run `chain` over a recording, each effect on the previous one's output
+pub fn run(chain: &[Effect], samples: Vec<f32>, sample_rate: u32) -> Vec<f32> {
+    chain.iter().fold(samples, |samples, effect| {
+        effect.process(samples, sample_rate)
+    })
+}
+
+/// Whether `profile_name` has effects to run
+pub fn has_chain(app: &AppHandle, profile_name: &str) -> bool {
+    app.try_state::<EffectsState>()
+        .is_some_and(|state| !state.chain_for(profile_name).is_empty())
+}
+
+/// The recording after `profile_name`'s effects chain
+pub fn apply(app: &AppHandle, profile_name: &str, samples: Vec<f32>, sample_rate: u32) -> Vec<f32> {
+    let chain = app.state::<EffectsState>().chain_for(profile_name);
+    if chain.is_empty() || samples.is_empty() {
+        return samples;
+    }
+    let started = Instant::now();
+    let processed = run(&chain, samples, sample_rate);
+    events::record_state(
+        app,
+        "effects-applied",
+        serde_json::json!({
+            "profile": profile_name,
+            "effects": chain.iter().map(Effect::name).collect::<Vec<_>>(),
+            "elapsedMs": started.elapsed().as_millis() as u64,
+        }),
+    );
+    processed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    fn sine(freq: f32, rate: u32, secs: f32, amplitude: f32) -> Vec<f32> {
+        (0..(rate as f32 * secs) as usize)
+            .map(|i| amplitude * (2.0 * PI * freq * i as f32 / rate as f32).sin())
+            .collect()
+    }
+
+    #[test]
+    fn test_stages_parse_with_enabled_by_default() {
+        let chain: Vec<EffectStage> = serde_json::from_value(serde_json::json!([
+            { "type": "highPass", "cutoffHz": 80.0 },
+            { "type": "denoise", "enabled": false },
+        ]))
+        .unwrap();
+        assert!(chain[0].enabled);
+        assert_eq!(chain[0].effect, Effect::HighPass { cutoff_hz: 80.0 });
+        assert!(!chain[1].enabled);
+
+        let state = EffectsState::new();
+        state.configure(HashMap::from([("alice".to_string(), chain)]));
+        assert_eq!(
+            state.chain_for("alice"),
+            vec![Effect::HighPass { cutoff_hz: 80.0 }]
+        );
+        assert!(state.chain_for("bob").is_empty());
+    }
+
+    #[test]
+    fn test_gate_silences_quiet_stretches_after_hold() {
+        let rate = 16_000;
+        let mut samples = sine(440.0, rate, 0.5, 0.3);
+        samples.extend(sine(440.0, rate, 0.5, 0.001));
+        let gated = gate(samples, rate, -40.0, 100);
+        assert!(rms(&gated[..8_000]) > 0.2);
+        // Held open for 100 ms after speech, then closed
+        assert!(rms(&gated[8_000..9_000]) > 0.0);
+        assert_eq!(rms(&gated[10_000..]), 0.0);
+    }
+
+    #[test]
+    fn test_agc_reaches_target_within_max_gain() {
+        let rate = 16_000;
+        let tail_db = |samples: &[f32]| to_dbfs(rms(&samples[samples.len() / 2..]));
+
+        // -30 dBFS RMS raised to -20
+        let quiet = sine(440.0, rate, 2.0, 0.0447);
+        let level = tail_db(&agc(quiet, rate, -20.0, 20.0));
+        assert!((level + 20.0).abs() < 1.0, "level {level}");
+
+        // -50 dBFS can only be raised by the 12 dB allowed
+        let very_quiet = sine(440.0, rate, 2.0, 0.00447);
+        let level = tail_db(&agc(very_quiet, rate, -20.0, 12.0));
+        assert!((level + 38.0).abs() < 1.0, "level {level}");
+    }
+
+    #[test]
+    fn test_limiter_keeps_peaks_under_ceiling() {
+        let limited = limit(sine(440.0, 16_000, 0.2, 1.5), 16_000, -3.0);
+        let peak = limited.iter().fold(0.0f32, |p, s| p.max(s.abs()));
+        assert!(peak <= db_to_gain(-3.0) + 1e-6, "peak {peak}");
+        assert!(peak > 0.6);
+    }
+
+    #[test]
+    fn test_chain_runs_in_order() {
+        let rate = 16_000;
+        let input = sine(440.0, rate, 0.5, 0.05);
+        let chain = [
+            Effect::Agc {
+                target_db: -6.0,
+                max_gain_db: 30.0,
+            },
+            Effect::Limiter { ceiling_db: -12.0 },
+        ];
+        let output = run(&chain, input.clone(), rate);
+        assert_eq!(output.len(), input.len());
+        // Limiting last wins over the AGC's boost
+        let peak = output.iter().fold(0.0f32, |p, s| p.max(s.abs()));
+        assert!(peak <= db_to_gain(-12.0) + 1e-6);
+    }
+
+    #[test]
+    fn test_invalid_parameters_rejected() {
+        let chains = |effect| {
+            HashMap::from([(
+                "alice".to_string(),
+                vec![EffectStage {
+                    enabled: true,
+                    effect,
+                }],
+            )])
+        };
+        assert!(validate(&chains(Effect::HighPass { cutoff_hz: 100.0 })).is_ok());
+        assert!(validate(&chains(Effect::HighPass { cutoff_hz: 0.0 })).is_err());
+        assert!(validate(&chains(Effect::Limiter { ceiling_db: 3.0 })).is_err());
+        let err = validate(&chains(Effect::Agc {
+            target_db: -20.0,
+            max_gain_db: f32::NAN,
+        }))
+        .unwrap_err();
+        assert!(err.contains("alice") && err.contains("maxGainDb"), "{err}");
+    }
+}
@@ -0,0 +1,290 @@
+use std::fmt;
+
+use reqwest::StatusCode;
+use schemars::gen::SchemaGenerator;
+use schemars::schema::Schema;
+use schemars::JsonSchema;
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+/// Errors surfaced to the webview.
+/// Gateway failures are classified so the UI can show what went wrong and how to fix it,
+/// instead of a raw status line and response body.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppError {
+    QuotaExceeded(String),
+    ModelUnavailable(String),
+    ProfileNotFound(String),
+    ProfileNotAllowed(String),
+    SessionNotFound,
+    AudioTooLarge,
+    Unauthorized,
+    /// The gateway doesn't serve the workspace sent in `X-Workspace`, or not to this token
+    WorkspaceRejected(String),
+    /// Gateway error we don't have a specific mapping for
+    Gateway {
+        status: u16,
+        message: String,
+    },
+    /// Gateway could not be reached at all
+    Network(String),
+    /// The reply didn't finish within this many seconds, so its stream was abandoned
+    ResponseTimeout(u64),
+    /// The same recording is already on its way to the session, or was just sent
+    DuplicateUpload {
+        in_flight: bool,
+    },
+    /// The configured gateway URL can't be used to build requests
+    InvalidGatewayUrl(String),
+    /// The connected gateway has no endpoint for this feature, as probed on connect
+    UnsupportedByGateway(String),
+    Other(String),
+}
+
+impl AppError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::QuotaExceeded(_) => "quotaExceeded",
+            Self::ModelUnavailable(_) => "modelUnavailable",
+            Self::ProfileNotFound(_) => "profileNotFound",
+            Self::ProfileNotAllowed(_) => "profileNotAllowed",
+            Self::SessionNotFound => "sessionNotFound",
+            Self::AudioTooLarge => "audioTooLarge",
+            Self::Unauthorized => "unauthorized",
+            Self::WorkspaceRejected(_) => "workspaceRejected",
+            Self::Gateway { .. } => "gateway",
+            Self::Network(_) => "network",
+            Self::ResponseTimeout(_) => "responseTimeout",
+            Self::DuplicateUpload { .. } => "duplicateUpload",
+            Self::InvalidGatewayUrl(_) => "invalidGatewayUrl",
+            Self::UnsupportedByGateway(_) => "unsupportedByGateway",
+            Self::Other(_) => "other",
+        }
+    }
+
+    /// What the user can do about it, if there is anything
+    pub fn suggestion(&self) -> Option<&'static str> {
+        match self {
+            Self::QuotaExceeded(_) => {
+                Some("Wait for the quota to reset or raise the limit on the gateway's provider account.")
+            }
+            Self::ModelUnavailable(_) => {
+                Some("Try again shortly, or switch the profile to a different model on the gateway.")
+            }
+            Self::ProfileNotFound(_) => {
+                Some("Check the profile name in Settings matches one configured on the gateway.")
+            }
+            Self::ProfileNotAllowed(_) => {
+                Some("Add the profile to the gateway's allowed profiles list, or pick another profile in Settings.")
+            }
+            Self::SessionNotFound => Some("Start a new session."),
+            Self::AudioTooLarge => Some("Keep recordings shorter, or raise the gateway's upload limit."),
+            Self::Unauthorized => Some("Check the token in Settings."),
+            Self::WorkspaceRejected(_) => {
+                Some("Check the workspace in Settings is one the gateway serves, or leave it empty.")
+            }
+            Self::Network(_) => {
+                Some("Check the gateway URL in Settings and that the gateway is running.")
+            }
+            Self::ResponseTimeout(_) => {
+                Some("Try again, or raise response_timeout_secs in settings.json for slower agents.")
+            }
+            Self::InvalidGatewayUrl(_) => {
+                Some("Use a URL like http://127.0.0.1:18790/voice-client in Settings.")
+            }
+            Self::UnsupportedByGateway(_) => {
+                Some("Update the gateway's voice-client plugin to a version that has it.")
+            }
+            Self::Gateway { .. } | Self::DuplicateUpload { .. } | Self::Other(_) => None,
+        }
+    }
+
+    /// Classify a non-success HTTP response from the gateway.
+    /// The plugin answers errors with `{"error": "..."}`; anything else is used verbatim.
+    pub fn from_gateway_response(status: StatusCode, body: &str) -> Self {
+        let message = serde_json::from_str::<serde_json::Value>(body)
+            .ok()
+            .and_then(|v| v.get("error").and_then(|e| e.as_str()).map(String::from))
+            .unwrap_or_else(|| body.trim().to_string());
+
+        match status {
+            StatusCode::UNAUTHORIZED => Self::Unauthorized,
+            StatusCode::PAYLOAD_TOO_LARGE => Self::AudioTooLarge,
+            StatusCode::TOO_MANY_REQUESTS => Self::QuotaExceeded(message),
+            StatusCode::FORBIDDEN if message.to_lowercase().contains("profile") => {
+                Self::ProfileNotAllowed(message)
+            }
+            StatusCode::NOT_FOUND if message.to_lowercase().contains("session") => {
+                Self::SessionNotFound
+            }
+            StatusCode::NOT_FOUND if message.to_lowercase().contains("profile") => {
+                Self::ProfileNotFound(message)
+            }
+            StatusCode::BAD_REQUEST | StatusCode::FORBIDDEN | StatusCode::NOT_FOUND
+                if message.to_lowercase().contains("workspace") =>
+            {
+                Self::WorkspaceRejected(message)
+            }
+            _ => Self::classify_message(&message).unwrap_or(Self::Gateway {
+                status: status.as_u16(),
+                message,
+            }),
+        }
+    }
+
+    /// Classify an error message by content alone — used for in-stream `system` error events
+    pub fn classify_message(message: &str) -> Option<Self> {
+        let lower = message.to_lowercase();
+        if lower.contains("quota") || lower.contains("rate limit") {
+            Some(Self::QuotaExceeded(message.to_string()))
+        } else if lower.contains("model")
+            && (lower.contains("unavailable")
+                || lower.contains("not available")
+                || lower.contains("overloaded"))
+        {
+            Some(Self::ModelUnavailable(message.to_string()))
+        } else if lower.contains("profile") && lower.contains("not found") {
+            Some(Self::ProfileNotFound(message.to_string()))
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::QuotaExceeded(detail) => write!(f, "Gateway quota exceeded: {detail}"),
+            Self::ModelUnavailable(detail) => write!(f, "Model unavailable: {detail}"),
+            Self::ProfileNotFound(detail) => write!(f, "Profile not found: {detail}"),
+            Self::ProfileNotAllowed(detail) => write!(f, "Profile not allowed: {detail}"),
+            Self::SessionNotFound => write!(f, "Session no longer exists on the gateway"),
+            Self::AudioTooLarge => write!(f, "Recording is too large for the gateway"),
+            Self::Unauthorized => write!(f, "Gateway rejected the credentials"),
+            Self::WorkspaceRejected(detail) => write!(f, "Workspace rejected: {detail}"),
+            Self::Gateway { status, message } => write!(f, "Gateway error ({status}): {message}"),
+            Self::Network(detail) => write!(f, "Could not reach gateway: {detail}"),
+            Self::ResponseTimeout(secs) => write!(f, "The reply didn't finish within {secs} s"),
+            Self::DuplicateUpload { in_flight: true } => {
+                write!(f, "This recording is already being sent")
+            }
+            Self::DuplicateUpload { in_flight: false } => write!(f, "This recording was just sent"),
+            Self::InvalidGatewayUrl(detail) => write!(f, "Invalid gateway URL: {detail}"),
+            Self::UnsupportedByGateway(feature) => {
+                write!(f, "The gateway doesn't support {feature}")
+            }
+            Self::Other(detail) => write!(f, "{detail}"),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        Self::Other(message)
+    }
+}
+
+/// A classified failure, as commands return it and "gateway-error" carries it
+#[derive(JsonSchema)]
+#[allow(dead_code)]
+struct SerializedAppError {
+    /// What went wrong, e.g. `network`, `unauthorized` or `quotaExceeded`
+    kind: String,
+    message: String,
+    /// What the user can do about it, if there is anything
+    suggestion: Option<String>,
+}
+
+impl JsonSchema for AppError {
+    fn schema_name() -> String {
+        "AppError".to_string()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        SerializedAppError::json_schema(generator)
+    }
+}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AppError", 3)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("suggestion", &self.suggestion())?;
+        state.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_not_allowed_from_json_body() {
+        let err = AppError::from_gateway_response(
+            StatusCode::FORBIDDEN,
+            r#"{"error":"Profile not allowed"}"#,
+        );
+        assert_eq!(
+            err,
+            AppError::ProfileNotAllowed("Profile not allowed".to_string())
+        );
+    }
+
+    #[test]
+    fn test_session_not_found() {
+        let err = AppError::from_gateway_response(
+            StatusCode::NOT_FOUND,
+            r#"{"error":"Session not found"}"#,
+        );
+        assert_eq!(err, AppError::SessionNotFound);
+    }
+
+    #[test]
+    fn test_workspace_rejected() {
+        let err = AppError::from_gateway_response(
+            StatusCode::FORBIDDEN,
+            r#"{"error":"Unknown workspace 'desing'"}"#,
+        );
+        assert_eq!(
+            err,
+            AppError::WorkspaceRejected("Unknown workspace 'desing'".to_string())
+        );
+        // A profile missing from a workspace is still about the profile
+        let err = AppError::from_gateway_response(
+            StatusCode::NOT_FOUND,
+            r#"{"error":"Profile not found in workspace 'design'"}"#,
+        );
+        assert!(matches!(err, AppError::ProfileNotFound(_)));
+    }
+
+    #[test]
+    fn test_quota_from_message_on_generic_status() {
+        let err = AppError::from_gateway_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            r#"{"error":"Provider quota exceeded for this month"}"#,
+        );
+        assert!(matches!(err, AppError::QuotaExceeded(_)));
+    }
+
+    #[test]
+    fn test_unknown_error_keeps_status_and_body() {
+        let err = AppError::from_gateway_response(StatusCode::BAD_GATEWAY, "upstream down");
+        assert_eq!(
+            err,
+            AppError::Gateway {
+                status: 502,
+                message: "upstream down".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_serializes_kind_message_and_suggestion() {
+        let value = serde_json::to_value(AppError::Unauthorized).unwrap();
+        assert_eq!(value["kind"], "unauthorized");
+        assert_eq!(value["message"], "Gateway rejected the credentials");
+        assert_eq!(value["suggestion"], "Check the token in Settings.");
+    }
+}
@@ -0,0 +1,407 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::conversations;
+use crate::corrections;
+use crate::error::AppError;
+use crate::history;
+use crate::hotkey;
+use crate::journal;
+use crate::keywords;
+use crate::playback;
+use crate::privacy;
+use crate::remote;
+use crate::response_cache;
+use crate::response_routes;
+use crate::segments;
+use crate::session::SessionState;
+use crate::types::{
+    EventFilters, EventKind, LoggedEvent, LoggedEventKind, ResponseTimeout, VoiceEvent,
+};
+use crate::unread;
+use crate::webhooks;
+
+/// Ring buffer of recently emitted events and internal state transitions,
+/// feeding the diagnostics window.
+pub struct EventLog {
+    pub entries: Arc<Mutex<VecDeque<LoggedEvent>>>,
+    pub capacity: Arc<Mutex<usize>>,
+    pub next_seq: Arc<Mutex<u64>>,
+}
+
+impl EventLog {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity: Arc::new(Mutex::new(capacity)),
+            next_seq: Arc::new(Mutex::new(0)),
+        }
+    }
+
+    pub fn set_capacity(&self, capacity: usize) {
+        if let Ok(mut current) = self.capacity.lock() {
+            *current = capacity;
+        }
+        if let Ok(mut entries) = self.entries.lock() {
+            let overflow = entries.len().saturating_sub(capacity);
+            entries.drain(..overflow);
+        }
+    }
+
+    pub fn record(&self, kind: LoggedEventKind, name: &str, payload: Value) {
+        let capacity = self.capacity.lock().map(|c| *c).unwrap_or(0);
+        if capacity == 0 {
+            return;
+        }
+
+        let seq = match self.next_seq.lock() {
+            Ok(mut next) => {
+                *next += 1;
+                *next
+            }
+            Err(_) => return,
+        };
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+
+        if let Ok(mut entries) = self.entries.lock() {
+            while entries.len() >= capacity {
+                entries.pop_front();
+            }
+            entries.push_back(LoggedEvent {
+                seq,
+                timestamp_ms,
+                kind,
+                name: name.to_string(),
+                payload,
+            });
+        }
+    }
+
+    /// The newest `limit` entries (all if None), oldest first
+    pub fn snapshot(&self, limit: Option<usize>) -> Vec<LoggedEvent> {
+        let Ok(entries) = self.entries.lock() else {
+            return Vec::new();
+        };
+        let skip = limit.map_or(0, |limit| entries.len().saturating_sub(limit));
+        entries.iter().skip(skip).cloned().collect()
+    }
+}
+
+/// The consumers `event_filters` decide for; the popup and history aren't among them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Consumer {
+    Notifications,
+    Speech,
+    Webhooks,
+    ResponseRoutes,
+}
+
+impl Consumer {
+    const ALL: [Consumer; 4] = [
+        Consumer::Notifications,
+        Consumer::Speech,
+        Consumer::Webhooks,
+        Consumer::ResponseRoutes,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Consumer::Notifications => "notifications",
+            Consumer::Speech => "speech",
+            Consumer::Webhooks => "webhooks",
+            Consumer::ResponseRoutes => "responseRoutes",
+        }
+    }
+
+    /// The kinds this consumer has anything to do with
+    fn handles(self, kind: EventKind) -> bool {
+        match self {
+            Consumer::Notifications => true,
+            Consumer::Speech | Consumer::Webhooks => {
+                matches!(kind, EventKind::Reply | EventKind::Error)
+            }
+            Consumer::ResponseRoutes => kind == EventKind::Reply,
+        }
+    }
+
+    fn kinds(self, filters: &EventFilters) -> &[EventKind] {
+        match self {
+            Consumer::Notifications => &filters.notifications,
+            Consumer::Speech => &filters.speech,
+            Consumer::Webhooks => &filters.webhooks,
+            Consumer::ResponseRoutes => &filters.response_routes,
+        }
+    }
+}
+
+/// `event_filters`, as the router below applies them
+pub struct EventFilterState {
+    pub filters: Arc<Mutex<EventFilters>>,
+}
+
+impl EventFilterState {
+    pub fn new() -> Self {
+        Self {
+            filters: Arc::new(Mutex::new(EventFilters::default())),
+        }
+    }
+
+    pub fn configure(&self, filters: EventFilters) {
+        if let Ok(mut current) = self.filters.lock() {
+            *current = filters;
+        }
+    }
+}
+
+/// Every kind listed must be one its consumer handles
+pub fn validate_filters(filters: &EventFilters) -> Result<(), String> {
+    for consumer in Consumer::ALL {
+        if let Some(kind) = consumer
+            .kinds(filters)
+            .iter()
+            .find(|k| !consumer.handles(**k))
+        {
+            return Err(format!(
+                "'event_filters.{}' can't include {}",
+                consumer.name(),
+                serde_json::json!(kind)
+            ));
+        }
+    }
+    Ok(())
+}
+
+pub fn kind_of(event: &VoiceEvent) -> EventKind {
+    match event {
+        VoiceEvent::User { .. } => EventKind::Transcript,
+        VoiceEvent::Openclaw { done: false, .. } => EventKind::Partial,
+        VoiceEvent::Openclaw { done: true, .. } | VoiceEvent::Audio { .. } => EventKind::Reply,
+        VoiceEvent::System { status, .. } if status == "error" => EventKind::Error,
+        VoiceEvent::System { .. } => EventKind::Status,
+    }
+}
+
+fn passes(filters: &EventFilters, consumer: Consumer, kind: EventKind) -> bool {
+    consumer.kinds(filters).contains(&kind)
+}
+
+/// Whether `consumer` is set to receive events of `kind`
+pub fn delivers(app: &AppHandle, consumer: Consumer, kind: EventKind) -> bool {
+    app.try_state::<EventFilterState>()
+        .and_then(|state| {
+            state
+                .filters
+                .lock()
+                .ok()
+                .map(|f| passes(&f, consumer, kind))
+        })
+        .unwrap_or(true)
+}
+
+/// Speak `text` with the profile's local voice, if speech receives `kind`. For what
+/// doesn't arrive as a gateway event: the app's own errors, cached and demo answers.
+pub fn speak(app: &AppHandle, profile_name: &str, text: &str, kind: EventKind) {
+    if delivers(app, Consumer::Speech, kind) {
+        playback::speak_text(app, profile_name, text);
+    }
+}
+
+/// Emit a gateway event for `session_id`, recording it in the event log and session context.
+/// Goes to the session's detached window if it has one, otherwise to all windows; what
+/// else it reaches is up to `event_filters`.
+pub fn emit_voice_event(
+    app: &AppHandle,
+    session_id: &str,
+    event: &VoiceEvent,
+) -> Result<(), String> {
+    let kind = kind_of(event);
+    // Synthesized speech is played, not shown or kept with the transcript
+    if let VoiceEvent::Audio { data, url, .. } = event {
+        record_state(
+            app,
+            "reply-audio",
+            serde_json::json!({ "inline": data.is_some(), "url": url }),
+        );
+        if delivers(app, Consumer::Speech, kind) {
+            playback::speak_reply(app, data.clone(), url.clone());
+        }
+        return Ok(());
+    }
+    let corrected = corrections::apply(app, event);
+    let event = corrected.as_ref().unwrap_or(event);
+    if let Some(log) = app.try_state::<EventLog>() {
+        log.record(
+            LoggedEventKind::Emitted,
+            "voice-event",
+            privacy::loggable(app, event),
+        );
+    }
+    let completed = app
+        .try_state::<SessionState>()
+        .and_then(|sessions| sessions.record_event(session_id, event));
+    conversations::route(app, session_id, "voice-event", event)?;
+    journal::stream_started(app, session_id);
+    if delivers(app, Consumer::Notifications, kind) {
+        remote::send_event(app, session_id, event);
+    }
+    if let VoiceEvent::Openclaw { .. } = event {
+        hotkey::reply_arrived(app);
+    }
+    if let VoiceEvent::User {
+        text, timestamp, ..
+    } = event
+    {
+        keywords::check(app, text, timestamp);
+    }
+    if let VoiceEvent::System {
+        message: Some(message),
+        timestamp,
+        ..
+    } = event
+    {
+        let active = app
+            .try_state::<SessionState>()
+            .and_then(|sessions| sessions.snapshot())
+            .filter(|active| active.session_id == session_id);
+        if let Some(active) = active.filter(|_| kind == EventKind::Error) {
+            // The gateway doesn't synthesize speech for its own errors
+            speak(app, &active.profile_name, message, kind);
+            if delivers(app, Consumer::Webhooks, kind) {
+                webhooks::deliver_error(app, message, timestamp);
+            }
+        }
+    }
+    if let Some(turn) = completed {
+        record_state(
+            app,
+            "turn-completed",
+            serde_json::json!({ "chars": turn.reply.len() }),
+        );
+        response_cache::store(app, &turn);
+        history::record(app, &turn);
+        if delivers(app, Consumer::Webhooks, EventKind::Reply) {
+            webhooks::deliver(app, &turn);
+        }
+        if delivers(app, Consumer::ResponseRoutes, EventKind::Reply) {
+            response_routes::route(app, &turn);
+        }
+        let _ = app.emit("turn-completed", &turn);
+        if delivers(app, Consumer::Notifications, EventKind::Reply) {
+            remote::send_turn(app, &turn);
+            segments::publish(app, &turn);
+        }
+        unread::turn_completed(app);
+    }
+    Ok(())
+}
+
+/// Emit a classified gateway failure so the UI can show an actionable message
+pub fn emit_gateway_error(app: &AppHandle, err: &AppError) {
+    if let Some(log) = app.try_state::<EventLog>() {
+        log.record(
+            LoggedEventKind::Emitted,
+            "gateway-error",
+            serde_json::to_value(err).unwrap_or(Value::Null),
+        );
+    }
+    hotkey::reply_arrived(app);
+    let _ = app.emit("gateway-error", err);
+}
+
+/// Keep what arrived of a reply that didn't finish in time, marked as incomplete in
+/// history, and tell the windows so none of them waits for the rest
+pub fn emit_response_timeout(app: &AppHandle, session_id: &str, timeout_secs: u64) {
+    let at = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+    let partial = app
+        .try_state::<SessionState>()
+        .and_then(|sessions| sessions.abandon_turn(session_id, &at));
+    if let Some(turn) = &partial {
+        history::record_incomplete(app, turn);
+    }
+    let (user_text, partial_reply) = partial
+        .map(|turn| (turn.user_text, turn.reply))
+        .unwrap_or_default();
+    record_state(
+        app,
+        "response-timeout",
+        serde_json::json!({
+            "sessionId": session_id,
+            "chars": partial_reply.len(),
+            "timeoutSecs": timeout_secs,
+        }),
+    );
+    hotkey::reply_arrived(app);
+    let timeout = ResponseTimeout {
+        session_id: session_id.to_string(),
+        user_text,
+        partial_reply,
+        timeout_secs,
+    };
+    let _ = app.emit("response-timeout", &timeout);
+}
+
+/// Record an internal state transition (not emitted to the webview)
+pub fn record_state<T: Serialize>(app: &AppHandle, name: &str, details: T) {
+    if let Some(log) = app.try_state::<EventLog>() {
+        log.record(
+            LoggedEventKind::State,
+            name,
+            serde_json::to_value(details).unwrap_or(Value::Null),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_errors_and_replies_only() {
+        let filters = EventFilters {
+            notifications: vec![EventKind::Reply, EventKind::Error],
+            ..EventFilters::default()
+        };
+        let error = VoiceEvent::System {
+            status: "error".to_string(),
+            message: Some("Agent unavailable".to_string()),
+            timestamp: "1".to_string(),
+        };
+        let typing = VoiceEvent::System {
+            status: "typing".to_string(),
+            message: None,
+            timestamp: "1".to_string(),
+        };
+        let delta = VoiceEvent::Openclaw {
+            text: "Sure".to_string(),
+            done: false,
+            timestamp: "1".to_string(),
+        };
+        assert!(passes(&filters, Consumer::Notifications, kind_of(&error)));
+        assert!(!passes(&filters, Consumer::Notifications, kind_of(&typing)));
+        assert!(!passes(&filters, Consumer::Notifications, kind_of(&delta)));
+        // The other consumers keep their defaults
+        assert!(passes(&filters, Consumer::Speech, EventKind::Error));
+        assert!(!passes(&filters, Consumer::Webhooks, EventKind::Error));
+    }
+
+    #[test]
+    fn test_consumers_only_take_kinds_they_handle() {
+        assert!(validate_filters(&EventFilters::default()).is_ok());
+        let filters = EventFilters {
+            speech: vec![EventKind::Partial],
+            ..EventFilters::default()
+        };
+        assert_eq!(
+            validate_filters(&filters),
+            Err("'event_filters.speech' can't include \"partial\"".to_string())
+        );
+    }
+}
@@ -0,0 +1,300 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::api::Turn;
+use crate::error::AppError;
+use crate::events;
+use crate::persist;
+use crate::privacy;
+use crate::response_cache;
+use crate::storage;
+use crate::stt;
+use crate::types::{FailedSend, SendStage};
+
+pub const FAILED_DIR: &str = "failed-sends";
+/// Recordings can be tens of MB each, so only the most recent failures are kept
+const MAX_FAILED_SENDS: usize = 10;
+
+/// Recordings whose upload failed, kept on disk so they can be retried instead of lost
+pub struct FailedSendState {
+    /// Ids currently being retried, so a double click can't send the same message twice
+    pub in_flight: Arc<Mutex<HashSet<String>>>,
+}
+
+impl FailedSendState {
+    pub fn new() -> Self {
+        Self {
+            in_flight: Arc::new(Mutex::new(HashSet::new())),
+        }
+    }
+}
+
+fn failed_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(FAILED_DIR))
+        .map_err(|e| format!("Failed to resolve app data dir: {e}"))
+}
+
+fn meta_path(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{id}.json"))
+}
+
+fn wav_path(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{id}.wav"))
+}
+
+fn write_meta(dir: &Path, entry: &FailedSend) -> Result<(), String> {
+    let bytes = serde_json::to_vec_pretty(entry)
+        .map_err(|e| format!("Failed to serialize failed send: {e}"))?;
+    persist::write_atomic(&meta_path(dir, &entry.id), &bytes)
+}
+
+fn read_meta(dir: &Path, id: &str) -> Result<FailedSend, String> {
+    let bytes = fs::read(meta_path(dir, id)).map_err(|_| format!("Unknown failed send '{id}'"))?;
+    serde_json::from_slice(&bytes).map_err(|e| format!("Failed to read failed send '{id}': {e}"))
+}
+
+fn remove(dir: &Path, id: &str) {
+    let _ = fs::remove_file(wav_path(dir, id));
+    let _ = fs::remove_file(meta_path(dir, id));
+    let _ = fs::remove_file(dir.join(format!("{id}.json.bak")));
+}
+
+/// Entries in `dir`, newest first. The metadata is written after the audio,
+/// so an entry only counts once both files exist.
+fn list_dir(dir: &Path) -> Vec<FailedSend> {
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut entries: Vec<FailedSend> = read_dir
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let id = name.strip_suffix(".json")?.to_string();
+            persist::check_id(&id, "failed send").ok()?;
+            wav_path(dir, &id)
+                .exists()
+                .then(|| read_meta(dir, &id).ok())?
+        })
+        .collect();
+    entries.sort_by(|a, b| b.failed_at_ms.cmp(&a.failed_at_ms).then(b.id.cmp(&a.id)));
+    entries
+}
+
+/// Drop the oldest entries beyond the cap
+fn prune(dir: &Path, keep: usize) {
+    for entry in list_dir(dir).into_iter().skip(keep) {
+        remove(dir, &entry.id);
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+fn notify(app: &AppHandle) {
+    if let Ok(list) = list(app) {
+        let _ = app.emit("failed-sends-changed", list);
+    }
+}
+
+/// Keep a recording whose upload failed so it can be retried later
+pub fn save(
+    app: &AppHandle,
+    turn: &Turn<'_>,
+    wav_bytes: &[u8],
+    error: &AppError,
+) -> Result<FailedSend, String> {
+    keep(app, turn, wav_bytes, error.to_string(), None)
+}
+
+/// Keep a recording the app closed on before it was answered, found by `journal::recover`
+pub fn save_interrupted(
+    app: &AppHandle,
+    turn: &Turn<'_>,
+    wav_bytes: &[u8],
+    stage: SendStage,
+) -> Result<FailedSend, String> {
+    let error = match stage {
+        SendStage::Recorded | SendStage::Encoding => "The app closed before it was sent",
+        SendStage::Uploading => "The app closed while it was being sent",
+        SendStage::AwaitingStream => "The app closed before the reply came in",
+    };
+    keep(app, turn, wav_bytes, error.to_string(), Some(stage))
+}
+
+fn keep(
+    app: &AppHandle,
+    turn: &Turn<'_>,
+    wav_bytes: &[u8],
+    error: String,
+    interrupted: Option<SendStage>,
+) -> Result<FailedSend, String> {
+    if privacy::is_enabled(app) {
+        return Err("Privacy mode is on, so recordings aren't kept".to_string());
+    }
+    storage::admit(app, false)?;
+    let dir = failed_dir(app)?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+
+    let failed_at_ms = now_ms();
+    let mut id = failed_at_ms.to_string();
+    let mut suffix = 1;
+    while meta_path(&dir, &id).exists() || wav_path(&dir, &id).exists() {
+        id = format!("{failed_at_ms}-{suffix}");
+        suffix += 1;
+    }
+
+    let entry = FailedSend {
+        id,
+        base_url: turn.base_url.to_string(),
+        session_id: turn.session_id.to_string(),
+        profile_name: turn.profile_name.to_string(),
+        session_key: turn.session_key.map(String::from),
+        response_language: turn.response_language.map(String::from),
+        bytes: wav_bytes.len(),
+        failed_at_ms,
+        error,
+        attempts: 1,
+        interrupted,
+    };
+    fs::write(wav_path(&dir, &entry.id), wav_bytes)
+        .map_err(|e| format!("Failed to save recording: {e}"))?;
+    write_meta(&dir, &entry)?;
+    prune(&dir, MAX_FAILED_SENDS);
+
+    events::record_state(app, "send-saved", serde_json::json!({ "id": entry.id }));
+    notify(app);
+    Ok(entry)
+}
+
+/// Failed sends waiting for a retry or discard, newest first
+pub fn list(app: &AppHandle) -> Result<Vec<FailedSend>, String> {
+    Ok(list_dir(&failed_dir(app)?))
+}
+
+pub fn discard(app: &AppHandle, id: &str) -> Result<(), String> {
+    persist::check_id(id, "failed send")?;
+    let dir = failed_dir(app)?;
+    read_meta(&dir, id)?;
+    remove(&dir, id);
+    notify(app);
+    Ok(())
+}
+
+/// Send a saved recording again. It is removed on success; on failure the entry
+/// is kept with the new error so the user can try again or discard it.
+/// Voice events stream to the popup exactly like a fresh recording.
+pub async fn retry(app: &AppHandle, state: &FailedSendState, id: &str) -> Result<(), AppError> {
+    persist::check_id(id, "failed send")?;
+    let dir = failed_dir(app)?;
+    let mut entry = read_meta(&dir, id)?;
+    let wav_bytes = fs::read(wav_path(&dir, id))
+        .map_err(|e| AppError::Other(format!("Failed to read saved recording: {e}")))?;
+
+    if !state
+        .in_flight
+        .lock()
+        .map(|mut ids| ids.insert(id.to_string()))
+        .unwrap_or(false)
+    {
+        return Err(AppError::Other(
+            "This message is already being resent".to_string(),
+        ));
+    }
+
+    let turn = Turn {
+        base_url: &entry.base_url,
+        session_id: &entry.session_id,
+        profile_name: &entry.profile_name,
+        session_key: entry.session_key.as_deref(),
+        response_language: entry.response_language.as_deref(),
+        context: None,
+    };
+    // The profile's provider may have changed since, so transcription runs again
+    let result = match stt::prepare(app, &entry.profile_name, wav_bytes.into()).await {
+        Ok(utterance) => response_cache::send(app, &turn, utterance).await,
+        Err(e) => Err(e),
+    };
+
+    if let Ok(mut ids) = state.in_flight.lock() {
+        ids.remove(id);
+    }
+    match &result {
+        Ok(()) => remove(&dir, id),
+        Err(e) => {
+            entry.error = e.to_string();
+            entry.attempts += 1;
+            entry.interrupted = None;
+            write_meta(&dir, &entry)?;
+        }
+    }
+    events::record_state(
+        app,
+        "send-retried",
+        serde_json::json!({ "id": id, "ok": result.is_ok() }),
+    );
+    notify(app);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    fn entry(id: &str, failed_at_ms: u64) -> FailedSend {
+        FailedSend {
+            id: id.to_string(),
+            base_url: "http://127.0.0.1:18790/voice-client".to_string(),
+            session_id: "s1".to_string(),
+            profile_name: "alice".to_string(),
+            session_key: None,
+            response_language: None,
+            bytes: 4,
+            failed_at_ms,
+            error: "Could not reach gateway".to_string(),
+            attempts: 1,
+            interrupted: None,
+        }
+    }
+
+    fn store(dir: &Path, entry: &FailedSend) {
+        fs::write(wav_path(dir, &entry.id), b"RIFF").unwrap();
+        write_meta(dir, entry).unwrap();
+    }
+
+    #[test]
+    fn test_list_is_newest_first_and_needs_audio() {
+        let dir = TempDir::new("failed-list");
+        store(&dir, &entry("100", 100));
+        store(&dir, &entry("300", 300));
+        store(&dir, &entry("200", 200));
+        fs::remove_file(wav_path(&dir, "200")).unwrap();
+
+        let ids: Vec<String> = list_dir(&dir).into_iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec!["300", "100"]);
+    }
+
+    #[test]
+    fn test_prune_keeps_newest() {
+        let dir = TempDir::new("failed-prune");
+        for ms in 1..=5 {
+            store(&dir, &entry(&ms.to_string(), ms));
+        }
+        prune(&dir, 2);
+
+        let ids: Vec<String> = list_dir(&dir).into_iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec!["5", "4"]);
+        assert!(!wav_path(&dir, "1").exists());
+    }
+}
@@ -0,0 +1,113 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tauri::{AppHandle, Manager};
+
+use crate::api::{self, Gateway};
+use crate::capabilities::{self, Capability};
+use crate::error::AppError;
+use crate::session::SessionState;
+use crate::types::{FeedbackRating, FeedbackRequest, TurnFeedback};
+use crate::{demo, events, history, settings, turns};
+
+const MAX_COMMENT_CHARS: usize = 2_000;
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Trimmed comment, None when blank
+fn clean_comment(comment: Option<String>) -> Result<Option<String>, String> {
+    let Some(comment) = comment
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty())
+    else {
+        return Ok(None);
+    };
+    if comment.chars().count() > MAX_COMMENT_CHARS {
+        return Err(format!(
+            "Feedback comments are limited to {MAX_COMMENT_CHARS} characters"
+        ));
+    }
+    Ok(Some(comment))
+}
+
+/// Rate a reply: keep the rating with the exchange in history and post it to the
+/// gateway, so its operators can see which answers missed. Rating a turn again
+/// replaces the earlier rating.
+pub async fn submit(
+    app: &AppHandle,
+    turn_id: &str,
+    rating: FeedbackRating,
+    comment: Option<String>,
+) -> Result<(), AppError> {
+    let (session_id, completed_at) = turns::parse_turn_id(turn_id)?;
+    let comment = clean_comment(comment)?;
+    let feedback = TurnFeedback {
+        rating,
+        comment: comment.clone(),
+        submitted_at_ms: now_ms(),
+    };
+    let recorded = history::record_feedback(app, session_id, completed_at, &feedback)?;
+
+    // Privacy mode keeps no history, but the turn just finished is still in memory
+    let sessions = app.state::<SessionState>();
+    let last = sessions.last_turn().filter(|turn| turn.turn_id == turn_id);
+    let (user_text, reply) = match (&recorded, last) {
+        (Some(entry), _) => (entry.user_text.clone(), Some(entry.reply.clone())),
+        (None, Some(turn)) => (turn.user_text, Some(turn.reply)),
+        (None, None) => (None, None),
+    };
+    // Demo answers never came from a gateway, so the rating stays in history
+    if demo::is_enabled(app) {
+        return Ok(());
+    }
+
+    let settings = settings::load_settings(app)?;
+    let active = sessions
+        .snapshot()
+        .filter(|active| active.session_id == session_id);
+    let base_url = match &active {
+        Some(active) => active.gateway_url.clone(),
+        None => settings.gateway_url.clone(),
+    };
+    let profile_name = match (recorded, active) {
+        (Some(entry), _) => entry.profile_name,
+        (None, Some(active)) => active.profile_name,
+        (None, None) => settings.profile_name,
+    };
+    capabilities::require(app, &base_url, Capability::Feedback)?;
+
+    let request = FeedbackRequest {
+        session_id: session_id.to_string(),
+        profile_name,
+        completed_at: completed_at.to_string(),
+        rating,
+        comment,
+        user_text,
+        reply,
+    };
+    api::submit_feedback(&Gateway::from_app(app), &base_url, &request).await?;
+    events::record_state(
+        app,
+        "feedback-submitted",
+        serde_json::json!({ "turnId": turn_id, "rating": rating }),
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_comments_are_trimmed_and_capped() {
+        assert_eq!(clean_comment(None).unwrap(), None);
+        assert_eq!(clean_comment(Some("   ".to_string())).unwrap(), None);
+        let comment = clean_comment(Some(" too slow ".to_string())).unwrap();
+        assert_eq!(comment.as_deref(), Some("too slow"));
+        assert!(clean_comment(Some("x".repeat(MAX_COMMENT_CHARS + 1))).is_err());
+    }
+}
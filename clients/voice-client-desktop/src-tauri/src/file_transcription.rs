@@ -0,0 +1,197 @@
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::audio;
+use crate::error::AppError;
+use crate::events;
+use crate::playback::decode_wav_mono;
+use crate::stt::{LocalWhisper, SttProvider, SttState, Utterance};
+use crate::types::FileTranscriptionEvent;
+
+/// Target chunk length; whisper works on 30 s windows anyway
+const CHUNK_SECS: usize = 30;
+/// How far back from a chunk's end to look for a pause to cut at
+const SEARCH_SECS: usize = 3;
+/// Window used to find the quietest spot
+const WINDOW_MS: usize = 100;
+
+/// Split `len` samples into ~30 s chunks, cutting at the quietest 100 ms near each
+/// boundary so words aren't split between chunks
+pub fn chunk_bounds(samples: &[f32], rate: u32) -> Vec<Range<usize>> {
+    let rate = rate.max(1) as usize;
+    let chunk = CHUNK_SECS * rate;
+    let search = SEARCH_SECS * rate;
+    let window = (WINDOW_MS * rate / 1000).max(1);
+
+    let mut bounds = Vec::new();
+    let mut start = 0;
+    while samples.len() - start > chunk {
+        let target = start + chunk;
+        let energy = |at: &usize| -> f32 {
+            let window = &samples[*at..*at + window];
+            window.iter().map(|s| s * s).sum()
+        };
+        let cut = (target - search..target - window)
+            .step_by(window)
+            .min_by(|a, b| energy(a).total_cmp(&energy(b)))
+            .map_or(target, |quietest| quietest + window / 2);
+        bounds.push(start..cut);
+        start = cut;
+    }
+    if start < samples.len() {
+        bounds.push(start..samples.len());
+    }
+    bounds
+}
+
+/// Transcribe chunk by chunk, handing each event to `on_event` as soon as it is known.
+/// Blocks; checks `cancel` between chunks.
+pub fn transcribe(
+    file: &str,
+    samples: &[f32],
+    rate: u32,
+    whisper: &LocalWhisper,
+    cancel: &AtomicBool,
+    mut on_event: impl FnMut(FileTranscriptionEvent),
+) {
+    let bounds = chunk_bounds(samples, rate);
+    on_event(FileTranscriptionEvent::Started {
+        file: file.to_string(),
+        chunks: bounds.len(),
+        duration_secs: samples.len() as f64 / f64::from(rate),
+    });
+    for (index, range) in bounds.iter().enumerate() {
+        if cancel.load(Ordering::SeqCst) {
+            on_event(FileTranscriptionEvent::Cancelled {
+                file: file.to_string(),
+                done: index,
+            });
+            return;
+        }
+        let text = audio::encode_wav(&samples[range.clone()], rate)
+            .map_err(AppError::Other)
+            .and_then(|wav| whisper.prepare(wav.into()));
+        match text {
+            Ok(Utterance::Transcript(text)) => on_event(FileTranscriptionEvent::Chunk {
+                index,
+                chunks: bounds.len(),
+                start_secs: range.start as f64 / f64::from(rate),
+                text,
+            }),
+            Ok(Utterance::Audio(_)) => {}
+            Err(e) => {
+                on_event(FileTranscriptionEvent::Failed {
+                    file: file.to_string(),
+                    message: e.to_string(),
+                });
+                return;
+            }
+        }
+    }
+    on_event(FileTranscriptionEvent::Finished {
+        file: file.to_string(),
+    });
+}
+
+/// Read a WAV file as mono samples
+pub fn read_file(path: &Path) -> Result<(Vec<f32>, u32), AppError> {
+    let bytes = fs::read(path)
+        .map_err(|e| AppError::Other(format!("Failed to read {}: {e}", path.display())))?;
+    decode_wav_mono(&bytes).map_err(|e| {
+        AppError::Other(format!(
+            "{} can't be transcribed ({e}); only WAV files are supported",
+            path.display()
+        ))
+    })
+}
+
+pub struct FileTranscriptionState {
+    pub running: Arc<AtomicBool>,
+    pub cancel: Arc<AtomicBool>,
+}
+
+impl FileTranscriptionState {
+    pub fn new() -> Self {
+        Self {
+            running: Arc::new(AtomicBool::new(false)),
+            cancel: Arc::new(AtomicBool::new(false)),
+        }
+    }
+}
+
+/// Transcribe an imported file with local whisper.cpp, emitting "file-transcription"
+/// events as chunks finish. One file at a time.
+pub async fn run(app: &AppHandle, path: String) -> Result<(), AppError> {
+    let state = app.state::<FileTranscriptionState>();
+    if state.running.swap(true, Ordering::SeqCst) {
+        return Err(AppError::Other(
+            "Another file is already being transcribed".to_string(),
+        ));
+    }
+    state.cancel.store(false, Ordering::SeqCst);
+
+    let whisper = app
+        .state::<SttState>()
+        .config
+        .lock()
+        .map_err(|_| AppError::Other("STT config lock poisoned".to_string()))
+        .and_then(|config| config.whisper())
+        .map_err(|e| AppError::Other(format!("Transcribing files needs local transcription: {e}")));
+    let app_handle = app.clone();
+    let cancel = state.cancel.clone();
+    let result = match whisper {
+        Ok(whisper) => tauri::async_runtime::spawn_blocking(move || {
+            let path = Path::new(&path);
+            let (samples, rate) = read_file(path)?;
+            let file = path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            transcribe(&file, &samples, rate, &whisper, &cancel, |event| {
+                if !matches!(event, FileTranscriptionEvent::Chunk { .. }) {
+                    events::record_state(&app_handle, "file-transcription", &event);
+                }
+                let _ = app_handle.emit("file-transcription", &event);
+            });
+            Ok(())
+        })
+        .await
+        .map_err(|e| AppError::Other(format!("Transcription task failed: {e}")))
+        .and_then(|result| result),
+        Err(e) => Err(e),
+    };
+    state.running.store(false, Ordering::SeqCst);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_long_audio_is_cut_at_pauses() {
+        let rate = 1_000;
+        // 70 s of tone with a silent gap at 28.5-28.8 s
+        let mut samples: Vec<f32> = (0..70 * rate)
+            .map(|i| if i % 2 == 0 { 0.5 } else { -0.5 })
+            .collect();
+        samples[28_500..28_800].fill(0.0);
+
+        let bounds = chunk_bounds(&samples, rate as u32);
+        assert_eq!(bounds.len(), 3);
+        assert!((28_500..28_800).contains(&bounds[0].end), "{bounds:?}");
+        assert_eq!(bounds[1].start, bounds[0].end);
+        assert_eq!(bounds.last().unwrap().end, samples.len());
+    }
+
+    #[test]
+    fn test_short_audio_is_one_chunk() {
+        assert_eq!(chunk_bounds(&[0.1; 500], 1_000), vec![0..500]);
+        assert!(chunk_bounds(&[], 16_000).is_empty());
+    }
+}
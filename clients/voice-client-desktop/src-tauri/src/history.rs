@@ -0,0 +1,460 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tauri::{AppHandle, Manager};
+
+use crate::api::{self, Gateway};
+use crate::capabilities::{self, Capability};
+use crate::session::SessionState;
+use crate::types::{
+    CompletedTurn, HistoryEntry, HistoryMatch, HistorySearchResult, HistorySource,
+    RemoteHistoryMatch, SessionOverview, TranscriptPage, TurnFeedback,
+};
+use crate::{persist, privacy, settings, storage};
+
+pub const HISTORY_DIR: &str = "history";
+/// Page size when the history window doesn't ask for one
+const DEFAULT_PAGE: usize = 50;
+const MAX_PAGE: usize = 500;
+/// Local matches returned by one search
+const MAX_MATCHES: usize = 200;
+
+fn history_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(HISTORY_DIR))
+        .map_err(|e| format!("Failed to resolve app data dir: {e}"))
+}
+
+fn session_path(dir: &Path, session_id: &str) -> PathBuf {
+    dir.join(format!("{session_id}.jsonl"))
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Append one exchange to the session's transcript, one JSON object per line
+fn append(dir: &Path, session_id: &str, entry: &HistoryEntry) -> Result<(), String> {
+    persist::check_id(session_id, "session")?;
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    let mut line = serde_json::to_string(entry)
+        .map_err(|e| format!("Failed to serialize history entry: {e}"))?;
+    line.push('\n');
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(session_path(dir, session_id))
+        .and_then(|mut file| file.write_all(line.as_bytes()))
+        .map_err(|e| format!("Failed to write history: {e}"))
+}
+
+/// All exchanges of a session, oldest first. A line cut short by a crash is skipped.
+fn read_session(dir: &Path, session_id: &str) -> Result<Vec<HistoryEntry>, String> {
+    persist::check_id(session_id, "session")?;
+    let text = fs::read_to_string(session_path(dir, session_id))
+        .map_err(|_| format!("Unknown session '{session_id}'"))?;
+    Ok(text
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+fn summarize(session_id: &str, entries: &[HistoryEntry]) -> Option<SessionOverview> {
+    let first = entries.first()?;
+    let last = entries.last()?;
+    Some(SessionOverview {
+        session_id: session_id.to_string(),
+        profile_name: last.profile_name.clone(),
+        turns: entries.len(),
+        started_at_ms: first.recorded_at_ms,
+        last_activity_ms: last.recorded_at_ms,
+        duration_secs: last.recorded_at_ms.saturating_sub(first.recorded_at_ms) / 1000,
+    })
+}
+
+/// Every session with at least one exchange, most recently active first
+fn overview_dir(dir: &Path) -> Vec<SessionOverview> {
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut sessions: Vec<SessionOverview> = read_dir
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let session_id = name.strip_suffix(".jsonl")?.to_string();
+            let entries = read_session(dir, &session_id).ok()?;
+            summarize(&session_id, &entries)
+        })
+        .collect();
+    sessions.sort_by(|a, b| {
+        b.last_activity_ms
+            .cmp(&a.last_activity_ms)
+            .then(b.session_id.cmp(&a.session_id))
+    });
+    sessions
+}
+
+fn page(
+    dir: &Path,
+    session_id: &str,
+    offset: usize,
+    limit: Option<usize>,
+) -> Result<TranscriptPage, String> {
+    let entries = read_session(dir, session_id)?;
+    let limit = limit.unwrap_or(DEFAULT_PAGE).clamp(1, MAX_PAGE);
+    let total = entries.len();
+    let offset = offset.min(total);
+    Ok(TranscriptPage {
+        session_id: session_id.to_string(),
+        entries: entries.into_iter().skip(offset).take(limit).collect(),
+        offset,
+        total,
+    })
+}
+
+/// Whether the question or reply contains `query`, which is already lowercase
+fn mentions(entry: &HistoryEntry, query: &str) -> bool {
+    let contains = |text: &str| text.to_lowercase().contains(query);
+    contains(&entry.reply) || entry.user_text.as_deref().is_some_and(contains)
+}
+
+/// Exchanges whose question or reply contains `query` (ignoring case), newest first
+fn search_dir(dir: &Path, query: &str) -> Vec<HistoryMatch> {
+    let query = query.to_lowercase();
+    let query = query.as_str();
+    let mut matches: Vec<HistoryMatch> = overview_dir(dir)
+        .into_iter()
+        .flat_map(|session| {
+            let entries = read_session(dir, &session.session_id).unwrap_or_default();
+            entries
+                .into_iter()
+                .filter(move |entry| mentions(entry, query))
+                .map(move |entry| HistoryMatch {
+                    session_id: session.session_id.clone(),
+                    source: HistorySource::Local,
+                    entry,
+                })
+        })
+        .collect();
+    matches.sort_by_key(|m| std::cmp::Reverse(m.entry.recorded_at_ms));
+    matches.truncate(MAX_MATCHES);
+    matches
+}
+
+/// Local matches followed by the gateway's, leaving out exchanges both have
+fn merge(mut local: Vec<HistoryMatch>, remote: Vec<RemoteHistoryMatch>) -> Vec<HistoryMatch> {
+    let remote: Vec<HistoryMatch> = remote
+        .into_iter()
+        .filter(|hit| {
+            !local.iter().any(|m| {
+                m.session_id == hit.session_id && m.entry.completed_at == hit.entry.completed_at
+            })
+        })
+        .map(|hit| HistoryMatch {
+            session_id: hit.session_id,
+            source: HistorySource::Remote,
+            entry: hit.entry,
+        })
+        .collect();
+    local.extend(remote);
+    local
+}
+
+/// Attach `feedback` to the exchange completed at `completed_at` and rewrite the
+/// transcript. Returns the exchange, or None if the history doesn't have it.
+fn annotate(
+    dir: &Path,
+    session_id: &str,
+    completed_at: &str,
+    feedback: &TurnFeedback,
+) -> Result<Option<HistoryEntry>, String> {
+    persist::check_id(session_id, "session")?;
+    if !session_path(dir, session_id).exists() {
+        return Ok(None);
+    }
+    let mut entries = read_session(dir, session_id)?;
+    let Some(entry) = entries
+        .iter_mut()
+        .rev()
+        .find(|entry| entry.completed_at == completed_at)
+    else {
+        return Ok(None);
+    };
+    entry.feedback = Some(feedback.clone());
+    let annotated = entry.clone();
+
+    let mut text = String::new();
+    for entry in &entries {
+        let line = serde_json::to_string(entry)
+            .map_err(|e| format!("Failed to serialize history entry: {e}"))?;
+        text.push_str(&line);
+        text.push('\n');
+    }
+    persist::write_atomic(&session_path(dir, session_id), text.as_bytes())?;
+    Ok(Some(annotated))
+}
+
+/// Add a completed exchange to the active session's history
+pub fn record(app: &AppHandle, turn: &CompletedTurn) {
+    write_entry(app, turn, false);
+}
+
+/// Add an exchange whose reply was cut off, marked as incomplete
+pub fn record_incomplete(app: &AppHandle, turn: &CompletedTurn) {
+    write_entry(app, turn, true);
+}
+
+fn write_entry(app: &AppHandle, turn: &CompletedTurn, incomplete: bool) {
+    if privacy::is_enabled(app) {
+        return;
+    }
+    let Some(active) = app
+        .try_state::<SessionState>()
+        .and_then(|sessions| sessions.snapshot())
+    else {
+        return;
+    };
+    let entry = HistoryEntry {
+        profile_name: active.profile_name,
+        user_text: turn.user_text.clone(),
+        reply: turn.reply.clone(),
+        completed_at: turn.completed_at.clone(),
+        recorded_at_ms: now_ms(),
+        feedback: None,
+        incomplete,
+    };
+    let result = storage::admit(app, true)
+        .and_then(|()| history_dir(app))
+        .and_then(|dir| append(&dir, &active.session_id, &entry));
+    if let Err(e) = result {
+        eprintln!("Failed to record history: {e}");
+    }
+}
+
+pub fn sessions_overview(app: &AppHandle) -> Result<Vec<SessionOverview>, String> {
+    history_dir(app).map(|dir| overview_dir(&dir))
+}
+
+pub fn session_transcript(
+    app: &AppHandle,
+    session_id: &str,
+    offset: usize,
+    limit: Option<usize>,
+) -> Result<TranscriptPage, String> {
+    page(&history_dir(app)?, session_id, offset, limit)
+}
+
+/// Keep the user's rating with the exchange it rates. Nothing is written in privacy mode.
+pub fn record_feedback(
+    app: &AppHandle,
+    session_id: &str,
+    completed_at: &str,
+    feedback: &TurnFeedback,
+) -> Result<Option<HistoryEntry>, String> {
+    if privacy::is_enabled(app) {
+        return Ok(None);
+    }
+    annotate(&history_dir(app)?, session_id, completed_at, feedback)
+}
+
+/// Search this computer's history and the transcripts the gateway keeps, if it keeps any.
+/// The gateway being unreachable only leaves its matches out.
+pub async fn search(app: &AppHandle, query: &str) -> Result<HistorySearchResult, String> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Err("Search text is empty".to_string());
+    }
+    let local = search_dir(&history_dir(app)?, query);
+
+    let settings = settings::load_settings(app)?;
+    let gateway = Gateway::from_app(app);
+    let base_url = settings.gateway_url.as_str();
+    let remote = match capabilities::require(app, base_url, Capability::HistorySearch) {
+        Ok(()) => api::search_history(&gateway, base_url, query).await,
+        Err(e) => Err(e),
+    };
+    let (remote, remote_unavailable) = match remote {
+        Ok(Some(remote)) => (remote, None),
+        Ok(None) => (
+            Vec::new(),
+            Some("The gateway doesn't keep transcripts".to_string()),
+        ),
+        Err(e) => (Vec::new(), Some(e.to_string())),
+    };
+
+    Ok(HistorySearchResult {
+        query: query.to_string(),
+        matches: merge(local, remote),
+        remote_unavailable,
+    })
+}
+
+/// A whole transcript, from the gateway when it has the session, else from local history
+pub async fn remote_session(app: &AppHandle, session_id: &str) -> Result<TranscriptPage, String> {
+    let settings = settings::load_settings(app)?;
+    let gateway = Gateway::from_app(app);
+    let base_url = settings.gateway_url.as_str();
+    let remote = match capabilities::require(app, base_url, Capability::HistorySession) {
+        Ok(()) => api::get_history_session(&gateway, base_url, session_id).await,
+        Err(e) => Err(e),
+    };
+    if let Ok(Some(transcript)) = remote {
+        let total = transcript.entries.len();
+        return Ok(TranscriptPage {
+            session_id: transcript.session_id,
+            entries: transcript.entries,
+            offset: 0,
+            total,
+        });
+    }
+
+    let local = history_dir(app).and_then(|dir| read_session(&dir, session_id));
+    match (local, remote) {
+        (Ok(entries), _) => Ok(TranscriptPage {
+            session_id: session_id.to_string(),
+            total: entries.len(),
+            entries,
+            offset: 0,
+        }),
+        (Err(_), Err(e)) => Err(e.to_string()),
+        (Err(e), _) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+    use crate::types::FeedbackRating;
+
+    fn entry(reply: &str, recorded_at_ms: u64) -> HistoryEntry {
+        HistoryEntry {
+            profile_name: "alice".to_string(),
+            user_text: Some("question".to_string()),
+            reply: reply.to_string(),
+            completed_at: "2026-01-01T10:00:00Z".to_string(),
+            recorded_at_ms,
+            feedback: None,
+            incomplete: false,
+        }
+    }
+
+    #[test]
+    fn test_overview_counts_and_orders_sessions() {
+        let dir = TempDir::new("history-overview");
+        append(&dir, "voice-1-a", &entry("one", 1_000)).unwrap();
+        append(&dir, "voice-1-a", &entry("two", 91_000)).unwrap();
+        append(&dir, "voice-2-b", &entry("three", 200_000)).unwrap();
+        // A line cut short by a crash
+        fs::write(dir.join("voice-3-c.jsonl"), b"{\"profileName\":").unwrap();
+
+        let sessions = overview_dir(&dir);
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].session_id, "voice-2-b");
+        assert_eq!(sessions[1].turns, 2);
+        assert_eq!(sessions[1].duration_secs, 90);
+    }
+
+    #[test]
+    fn test_transcript_pages() {
+        let dir = TempDir::new("history-page");
+        for i in 0..5 {
+            append(&dir, "voice-1-a", &entry(&i.to_string(), i)).unwrap();
+        }
+
+        let second = page(&dir, "voice-1-a", 2, Some(2)).unwrap();
+        assert_eq!(second.total, 5);
+        assert_eq!(second.offset, 2);
+        let replies: Vec<_> = second.entries.iter().map(|e| e.reply.as_str()).collect();
+        assert_eq!(replies, ["2", "3"]);
+
+        let past_end = page(&dir, "voice-1-a", 9, None).unwrap();
+        assert!(past_end.entries.is_empty());
+        assert_eq!(past_end.offset, 5);
+    }
+
+    #[test]
+    fn test_search_matches_question_or_reply() {
+        let dir = TempDir::new("history-search");
+        append(&dir, "voice-1-a", &entry("Paris is the capital", 1_000)).unwrap();
+        append(&dir, "voice-1-a", &entry("unrelated", 2_000)).unwrap();
+        append(&dir, "voice-2-b", &entry("Also PARIS", 3_000)).unwrap();
+
+        let matches = search_dir(&dir, "paris");
+        let replies: Vec<_> = matches.iter().map(|m| m.entry.reply.as_str()).collect();
+        assert_eq!(replies, ["Also PARIS", "Paris is the capital"]);
+        assert_eq!(matches[0].session_id, "voice-2-b");
+        assert_eq!(search_dir(&dir, "question").len(), 3);
+    }
+
+    #[test]
+    fn test_merge_keeps_local_copy_of_shared_exchanges() {
+        let local = vec![HistoryMatch {
+            session_id: "voice-1-a".to_string(),
+            source: HistorySource::Local,
+            entry: entry("here", 1_000),
+        }];
+        let mut elsewhere = entry("there", 0);
+        elsewhere.completed_at = "2026-01-02T10:00:00Z".to_string();
+        let remote = vec![
+            RemoteHistoryMatch {
+                session_id: "voice-1-a".to_string(),
+                entry: entry("here", 0),
+            },
+            RemoteHistoryMatch {
+                session_id: "voice-9-z".to_string(),
+                entry: elsewhere,
+            },
+        ];
+
+        let merged = merge(local, remote);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].source, HistorySource::Local);
+        assert_eq!(merged[1].source, HistorySource::Remote);
+        assert_eq!(merged[1].session_id, "voice-9-z");
+    }
+
+    #[test]
+    fn test_feedback_is_kept_with_its_exchange() {
+        let dir = TempDir::new("history-feedback");
+        let mut first = entry("one", 1_000);
+        first.completed_at = "t1".to_string();
+        let mut second = entry("two", 2_000);
+        second.completed_at = "t2".to_string();
+        append(&dir, "voice-1-a", &first).unwrap();
+        append(&dir, "voice-1-a", &second).unwrap();
+
+        let feedback = TurnFeedback {
+            rating: FeedbackRating::Down,
+            comment: Some("wrong city".to_string()),
+            submitted_at_ms: 3_000,
+        };
+        let rated = annotate(&dir, "voice-1-a", "t1", &feedback)
+            .unwrap()
+            .unwrap();
+        assert_eq!(rated.reply, "one");
+
+        let entries = read_session(&dir, "voice-1-a").unwrap();
+        assert_eq!(entries[0].feedback.as_ref(), Some(&feedback));
+        assert_eq!(entries[1].feedback, None);
+        assert!(annotate(&dir, "voice-1-a", "t9", &feedback)
+            .unwrap()
+            .is_none());
+        assert!(annotate(&dir, "voice-2-b", "t1", &feedback)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_session_ids_cannot_escape_the_directory() {
+        let dir = TempDir::new("history-escape");
+        assert!(append(&dir, "../settings", &entry("x", 0)).is_err());
+        assert!(page(&dir, "../../etc/passwd", 0, None).is_err());
+    }
+}
@@ -0,0 +1,273 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Modifiers, Shortcut, ShortcutState};
+use tauri_plugin_positioner::{Position, WindowExt};
+
+use crate::types::{HotkeyPopup, KeyboardLayout, PopupPosition, PushToTalkState};
+use crate::{events, keymap, quick_talk};
+
+const MAC: bool = cfg!(target_os = "macos");
+
+/// Modifiers in the order they are written, with their accelerator names and key labels
+const MODIFIERS: [(Modifiers, &str, &str); 4] = [
+    (Modifiers::CONTROL, "control", "Ctrl"),
+    (Modifiers::ALT, "alt", if MAC { "Option" } else { "Alt" }),
+    (Modifiers::SHIFT, "shift", "Shift"),
+    (Modifiers::SUPER, "super", if MAC { "Cmd" } else { "Super" }),
+];
+
+/// The push-to-talk shortcut currently registered with the OS
+pub struct HotkeyState {
+    pub push_to_talk: Arc<Mutex<Option<Shortcut>>>,
+    /// What pressing the hotkey does with the popup, and where it shows it
+    pub popup: Arc<Mutex<(HotkeyPopup, PopupPosition)>>,
+    /// Set by a press in `OnReply` mode until the reply shows the popup
+    pub reveal_on_reply: Arc<AtomicBool>,
+}
+
+impl HotkeyState {
+    pub fn new() -> Self {
+        Self {
+            push_to_talk: Arc::new(Mutex::new(None)),
+            popup: Arc::new(Mutex::new(Default::default())),
+            reveal_on_reply: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn configure(&self, popup: HotkeyPopup, position: PopupPosition) {
+        if let Ok(mut current) = self.popup.lock() {
+            *current = (popup, position);
+        }
+        if popup != HotkeyPopup::OnReply {
+            self.reveal_on_reply.store(false, Ordering::SeqCst);
+        }
+    }
+}
+
+/// Parse a hotkey string such as "Control+Space"
+pub fn parse_hotkey(hotkey: &str) -> Result<Shortcut, String> {
+    hotkey
+        .parse::<Shortcut>()
+        .map_err(|e| format!("Invalid hotkey '{hotkey}': {e}"))
+}
+
+/// Rewrite a hotkey by physical key. A single character is read as typed on `layout`,
+/// so "Control+A" entered on AZERTY becomes "control+KeyQ" and keeps working after a
+/// switch to another layout; named keys (`Space`, `KeyQ`, `F5`) keep their meaning.
+pub fn normalize_hotkey(hotkey: &str, layout: KeyboardLayout) -> Result<String, String> {
+    let (mods, key) = match hotkey.trim().rsplit_once('+') {
+        Some((mods, key)) => (Some(mods), key.trim()),
+        None => (None, hotkey.trim()),
+    };
+    let mut chars = key.chars();
+    let physical = match (chars.next(), chars.next()) {
+        (Some(c), None) => keymap::code_for(layout, c).map(|code| code.to_string()),
+        _ => None,
+    }
+    .unwrap_or_else(|| key.to_string());
+    let accelerator = match mods {
+        Some(mods) => format!("{mods}+{physical}"),
+        None => physical,
+    };
+    let shortcut = accelerator
+        .parse::<Shortcut>()
+        .map_err(|e| format!("Invalid hotkey '{hotkey}': {e}"))?;
+
+    let mut parts: Vec<String> = MODIFIERS
+        .iter()
+        .filter(|(flag, ..)| shortcut.mods.contains(*flag))
+        .map(|(_, name, _)| name.to_string())
+        .collect();
+    parts.push(shortcut.key.to_string());
+    Ok(parts.join("+"))
+}
+
+/// A hotkey as printed on the keys of `layout`, e.g. "Ctrl+Shift+A"
+pub fn describe_hotkey(hotkey: &str, layout: KeyboardLayout) -> Result<String, String> {
+    let shortcut = parse_hotkey(hotkey)?;
+    let code = shortcut.key.to_string();
+    let key = keymap::label(layout, shortcut.key)
+        .map(String::from)
+        .or_else(|| code.strip_prefix("Digit").map(String::from))
+        .unwrap_or(code);
+
+    let mut parts: Vec<&str> = MODIFIERS
+        .iter()
+        .filter(|(flag, ..)| shortcut.mods.contains(*flag))
+        .map(|(.., label)| *label)
+        .collect();
+    parts.push(&key);
+    Ok(parts.join("+"))
+}
+
+/// Register the push-to-talk hotkey, replacing the previous one.
+/// Does nothing if the requested hotkey is already registered.
+pub fn apply_push_to_talk(
+    app: &AppHandle,
+    state: &HotkeyState,
+    hotkey: Option<&str>,
+) -> Result<(), String> {
+    let next = hotkey
+        .filter(|h| !h.is_empty())
+        .map(parse_hotkey)
+        .transpose()?;
+
+    let mut current = state
+        .push_to_talk
+        .lock()
+        .map_err(|e| format!("Lock error: {e}"))?;
+    if *current == next {
+        return Ok(());
+    }
+
+    let shortcuts = app.global_shortcut();
+    if let Some(previous) = current.take() {
+        shortcuts
+            .unregister(previous)
+            .map_err(|e| format!("Failed to unregister hotkey: {e}"))?;
+    }
+
+    if let Some(shortcut) = next {
+        shortcuts
+            .on_shortcut(shortcut, |app, _shortcut, event| {
+                let state = match event.state() {
+                    ShortcutState::Pressed => PushToTalkState::Pressed,
+                    ShortcutState::Released => PushToTalkState::Released,
+                };
+                on_push_to_talk(app, state);
+                let _ = app.emit_to("popup", "push-to-talk", state);
+            })
+            .map_err(|e| format!("Failed to register hotkey: {e}"))?;
+        *current = Some(shortcut);
+    }
+
+    events::record_state(
+        app,
+        "hotkey-registered",
+        serde_json::json!({ "pushToTalk": hotkey }),
+    );
+    Ok(())
+}
+
+/// What a hotkey press does with the popup in `mode`
+#[derive(Debug, PartialEq, Eq)]
+enum PopupAction {
+    Show,
+    Leave,
+    ShowOnReply,
+}
+
+fn popup_action(mode: HotkeyPopup, state: PushToTalkState) -> PopupAction {
+    match (mode, state) {
+        (_, PushToTalkState::Released) | (HotkeyPopup::TrayOnly, _) => PopupAction::Leave,
+        (HotkeyPopup::Show, PushToTalkState::Pressed) => PopupAction::Show,
+        (HotkeyPopup::OnReply, PushToTalkState::Pressed) => PopupAction::ShowOnReply,
+    }
+}
+
+/// Tint the tray while the hotkey is held and show the popup as `hotkey_popup` asks
+fn on_push_to_talk(app: &AppHandle, state: PushToTalkState) {
+    quick_talk::show_push_to_talk(app, state == PushToTalkState::Pressed);
+    let hotkey = app.state::<HotkeyState>();
+    let (mode, position) = hotkey.popup.lock().map(|p| *p).unwrap_or_default();
+    match popup_action(mode, state) {
+        PopupAction::Show => reveal_popup(app, position),
+        PopupAction::ShowOnReply => hotkey.reveal_on_reply.store(true, Ordering::SeqCst),
+        PopupAction::Leave => {}
+    }
+}
+
+/// Show the popup at `position`, leaving the focus with the app being dictated into
+fn reveal_popup(app: &AppHandle, position: PopupPosition) {
+    let Some(window) = app.get_webview_window("popup") else {
+        return;
+    };
+    let anchor = match position {
+        PopupPosition::TrayCenter => Position::TrayCenter,
+        PopupPosition::TopRight => Position::TopRight,
+        PopupPosition::BottomRight => Position::BottomRight,
+        PopupPosition::Center => Position::Center,
+    };
+    // The tray position is unknown until the tray icon has been hovered or clicked
+    if window.move_window(anchor).is_err() {
+        let _ = window.move_window(Position::TopRight);
+    }
+    let _ = window.show();
+}
+
+/// A reply (or the error ending the turn) arrived: show the popup if a press in
+/// `OnReply` mode is waiting for it
+pub fn reply_arrived(app: &AppHandle) {
+    let Some(hotkey) = app.try_state::<HotkeyState>() else {
+        return;
+    };
+    if hotkey.reveal_on_reply.swap(false, Ordering::SeqCst) {
+        let position = hotkey.popup.lock().map(|p| p.1).unwrap_or_default();
+        reveal_popup(app, position);
+        events::record_state(app, "popup-revealed", "reply");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_characters_are_stored_by_physical_key() {
+        let azerty = normalize_hotkey("Control+A", KeyboardLayout::Azerty).unwrap();
+        assert_eq!(azerty, "control+KeyQ");
+        let dvorak = normalize_hotkey("Shift+Alt+t", KeyboardLayout::Dvorak).unwrap();
+        assert_eq!(dvorak, "alt+shift+KeyK");
+        // Already physical, or a key every layout shares
+        assert_eq!(
+            normalize_hotkey(&azerty, KeyboardLayout::Dvorak).unwrap(),
+            azerty
+        );
+        assert_eq!(
+            normalize_hotkey("Control+Space", KeyboardLayout::Azerty).unwrap(),
+            "control+Space"
+        );
+        assert!(normalize_hotkey("Control+Nonsense", KeyboardLayout::Qwerty).is_err());
+    }
+
+    #[test]
+    fn test_only_a_press_acts_on_the_popup() {
+        use PushToTalkState::{Pressed, Released};
+        assert_eq!(popup_action(HotkeyPopup::Show, Pressed), PopupAction::Show);
+        assert_eq!(
+            popup_action(HotkeyPopup::OnReply, Pressed),
+            PopupAction::ShowOnReply
+        );
+        assert_eq!(
+            popup_action(HotkeyPopup::TrayOnly, Pressed),
+            PopupAction::Leave
+        );
+        assert_eq!(
+            popup_action(HotkeyPopup::Show, Released),
+            PopupAction::Leave
+        );
+        assert_eq!(
+            popup_action(HotkeyPopup::OnReply, Released),
+            PopupAction::Leave
+        );
+    }
+
+    #[test]
+    fn test_description_follows_the_layout() {
+        let hotkey = normalize_hotkey("Control+Shift+Z", KeyboardLayout::Qwertz).unwrap();
+        assert_eq!(
+            describe_hotkey(&hotkey, KeyboardLayout::Qwertz).unwrap(),
+            "Ctrl+Shift+Z"
+        );
+        assert_eq!(
+            describe_hotkey(&hotkey, KeyboardLayout::Qwerty).unwrap(),
+            "Ctrl+Shift+Y"
+        );
+        assert_eq!(
+            describe_hotkey("Control+Digit1", KeyboardLayout::Azerty).unwrap(),
+            "Ctrl+1"
+        );
+    }
+}
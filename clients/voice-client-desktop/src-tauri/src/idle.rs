@@ -0,0 +1,253 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::audio::{self, AudioState};
+use crate::types::{ListeningPauseReason, ListeningPaused};
+use crate::{events, live_upload, playback};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Tracks whether always-listening features are allowed to run.
+/// They are paused while the screen is locked or the user has been idle too long.
+pub struct PresenceState {
+    pub paused: Arc<Mutex<Option<ListeningPauseReason>>>,
+    /// Idle time after which listening pauses; zero disables idle detection
+    pub idle_threshold: Arc<Mutex<Duration>>,
+    pub pause_on_lock: Arc<Mutex<bool>>,
+}
+
+impl PresenceState {
+    pub fn new() -> Self {
+        Self {
+            paused: Arc::new(Mutex::new(None)),
+            idle_threshold: Arc::new(Mutex::new(Duration::ZERO)),
+            pause_on_lock: Arc::new(Mutex::new(true)),
+        }
+    }
+
+    pub fn configure(&self, idle_threshold: Duration, pause_on_lock: bool) {
+        if let Ok(mut threshold) = self.idle_threshold.lock() {
+            *threshold = idle_threshold;
+        }
+        if let Ok(mut lock) = self.pause_on_lock.lock() {
+            *lock = pause_on_lock;
+        }
+    }
+
+    pub fn pause_reason(&self) -> Option<ListeningPauseReason> {
+        self.paused.lock().ok().and_then(|paused| *paused)
+    }
+
+    /// Decide the pause reason from the latest platform readings
+    fn evaluate(&self, locked: bool, idle: Option<Duration>) -> Option<ListeningPauseReason> {
+        let pause_on_lock = self.pause_on_lock.lock().map(|v| *v).unwrap_or(true);
+        let threshold = self
+            .idle_threshold
+            .lock()
+            .map(|v| *v)
+            .unwrap_or(Duration::ZERO);
+
+        if pause_on_lock && locked {
+            return Some(ListeningPauseReason::Locked);
+        }
+        match idle {
+            Some(idle) if !threshold.is_zero() && idle >= threshold => {
+                Some(ListeningPauseReason::Idle)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Poll the platform idle/lock state and emit "listening-paused" / "listening-resumed" on changes
+pub async fn monitor(app: AppHandle) {
+    loop {
+        tokio::time::sleep(POLL_INTERVAL).await;
+
+        let (locked, idle) =
+            tauri::async_runtime::spawn_blocking(|| (is_screen_locked(), idle_duration()))
+                .await
+                .unwrap_or((false, None));
+
+        let presence = app.state::<PresenceState>();
+        let next = presence.evaluate(locked, idle);
+        let previous = match presence.paused.lock() {
+            Ok(mut paused) => std::mem::replace(&mut *paused, next),
+            Err(_) => continue,
+        };
+
+        match (previous, next) {
+            (None, Some(reason)) => {
+                // Never keep the mic open behind a lock screen
+                if reason == ListeningPauseReason::Locked {
+                    audio::cancel_recording(&app.state::<AudioState>());
+                    live_upload::abort(&app);
+                    playback::end_barge_in(&app, false);
+                }
+                events::record_state(&app, "listening-paused", ListeningPaused { reason });
+                let _ = app.emit("listening-paused", ListeningPaused { reason });
+            }
+            (Some(_), None) => {
+                events::record_state(&app, "listening-resumed", ());
+                let _ = app.emit("listening-resumed", ());
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Time since the last keyboard/mouse input, if the platform exposes it
+#[cfg(target_os = "windows")]
+fn idle_duration() -> Option<Duration> {
+    use windows_sys::Win32::System::SystemInformation::GetTickCount;
+    use windows_sys::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+
+    let mut info = LASTINPUTINFO {
+        cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+        dwTime: 0,
+    };
+    // SAFETY: `info` is a properly sized LASTINPUTINFO owned by this frame.
+    if unsafe { GetLastInputInfo(&mut info) } == 0 {
+        return None;
+    }
+    // SAFETY: GetTickCount has no preconditions.
+    let now = unsafe { GetTickCount() };
+    Some(Duration::from_millis(now.wrapping_sub(info.dwTime) as u64))
+}
+
+/// Time since the last keyboard/mouse input, if the platform exposes it
+#[cfg(target_os = "macos")]
+fn idle_duration() -> Option<Duration> {
+    // HIDIdleTime is reported in nanoseconds by the IOHIDSystem registry entry
+    let output = std::process::Command::new("ioreg")
+        .args(["-c", "IOHIDSystem", "-d", "4", "-k", "HIDIdleTime"])
+        .output()
+        .ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    text.lines()
+        .find(|line| line.contains("\"HIDIdleTime\""))
+        .and_then(|line| line.rsplit('=').next())
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map(Duration::from_nanos)
+}
+
+/// Time since the last keyboard/mouse input, if the platform exposes it.
+/// logind only exposes a boolean hint, so an idle session reports the threshold as reached.
+#[cfg(target_os = "linux")]
+fn idle_duration() -> Option<Duration> {
+    match loginctl_hint("IdleHint")? {
+        true => Some(Duration::MAX),
+        false => Some(Duration::ZERO),
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+fn idle_duration() -> Option<Duration> {
+    None
+}
+
+#[cfg(target_os = "windows")]
+fn is_screen_locked() -> bool {
+    use windows_sys::Win32::System::StationsAndDesktops::{CloseDesktop, OpenInputDesktop};
+
+    // The input desktop can't be opened while the secure (lock) desktop is active
+    const DESKTOP_SWITCHDESKTOP: u32 = 0x0100;
+    // SAFETY: OpenInputDesktop has no preconditions; a non-null handle is closed below.
+    let desktop = unsafe { OpenInputDesktop(0, 0, DESKTOP_SWITCHDESKTOP) };
+    if desktop.is_null() {
+        return true;
+    }
+    // SAFETY: `desktop` is a valid handle returned by OpenInputDesktop.
+    unsafe { CloseDesktop(desktop) };
+    false
+}
+
+/// The login session's lock flag, which the IORegistry root lists with the console users
+#[cfg(target_os = "macos")]
+fn is_screen_locked() -> bool {
+    let Ok(output) = std::process::Command::new("ioreg")
+        .args(["-n", "Root", "-d", "1", "-k", "IOConsoleUsers"])
+        .output()
+    else {
+        return false;
+    };
+    String::from_utf8_lossy(&output.stdout).contains("\"CGSSessionScreenIsLocked\"=Yes")
+}
+
+#[cfg(target_os = "linux")]
+fn is_screen_locked() -> bool {
+    loginctl_hint("LockedHint").unwrap_or(false)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+fn is_screen_locked() -> bool {
+    false
+}
+
+#[cfg(target_os = "linux")]
+fn loginctl_hint(property: &str) -> Option<bool> {
+    let session = std::env::var("XDG_SESSION_ID").unwrap_or_else(|_| "self".to_string());
+    let output = std::process::Command::new("loginctl")
+        .args(["show-session", session.as_str(), "-p", property, "--value"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    match String::from_utf8_lossy(&output.stdout).trim() {
+        "yes" => Some(true),
+        "no" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn test_lock_pauses_before_idle() {
+        let presence = PresenceState::new();
+        presence.configure(5 * MINUTE, true);
+        assert_eq!(
+            presence.evaluate(true, Some(10 * MINUTE)),
+            Some(ListeningPauseReason::Locked)
+        );
+        assert_eq!(
+            presence.evaluate(true, None),
+            Some(ListeningPauseReason::Locked)
+        );
+        assert_eq!(presence.evaluate(false, Some(Duration::ZERO)), None);
+    }
+
+    #[test]
+    fn test_idle_pauses_from_the_threshold_on() {
+        let presence = PresenceState::new();
+        presence.configure(5 * MINUTE, true);
+        assert_eq!(presence.evaluate(false, Some(4 * MINUTE)), None);
+        assert_eq!(
+            presence.evaluate(false, Some(5 * MINUTE)),
+            Some(ListeningPauseReason::Idle)
+        );
+        // The platform couldn't tell
+        assert_eq!(presence.evaluate(false, None), None);
+    }
+
+    #[test]
+    fn test_disabled_settings_never_pause() {
+        let presence = PresenceState::new();
+        presence.configure(Duration::ZERO, false);
+        assert_eq!(presence.evaluate(true, Some(Duration::MAX)), None);
+
+        // Pausing on lock off still leaves idle detection
+        presence.configure(MINUTE, false);
+        assert_eq!(
+            presence.evaluate(true, Some(2 * MINUTE)),
+            Some(ListeningPauseReason::Idle)
+        );
+    }
+}
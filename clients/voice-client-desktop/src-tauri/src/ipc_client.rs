@@ -0,0 +1,195 @@
+//! The webview's typed command client, `src/ipc.ts`, generated from the commands in
+//! `schema::export()`: one function per command with its arguments and result typed,
+//! so a changed Rust signature fails `npm run typecheck` instead of an `invoke` at runtime.
+
+use std::collections::BTreeSet;
+
+use serde_json::Value;
+
+use crate::schema;
+
+/// Longer signatures get one argument per line
+const MAX_LINE: usize = 100;
+
+const HEADER: &str = "\
+// Generated from the Rust commands by `openclaw-voice client`; don't edit by hand.
+// Regenerate it with `npm run gen:ipc` after changing a command's signature.
+import { invoke } from '@tauri-apps/api/core';
+";
+
+/// `load_settings` → `loadSettings`
+fn function_name(command: &str) -> String {
+    let mut parts = command.split('_');
+    let mut name = parts.next().unwrap_or_default().to_string();
+    for part in parts {
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            name.extend(first.to_uppercase());
+            name.push_str(chars.as_str());
+        }
+    }
+    name
+}
+
+/// The TypeScript type for a schema, noting the `types.ts` definitions it names
+fn ts_type(schema: &Value, refs: &mut BTreeSet<String>) -> String {
+    let reference = schema["$ref"].as_str();
+    if let Some(name) = reference.and_then(|r| r.strip_prefix("#/definitions/")) {
+        refs.insert(name.to_string());
+        return name.to_string();
+    }
+    if let Some(variants) = schema["anyOf"].as_array() {
+        let variants: Vec<String> = variants.iter().map(|v| ts_type(v, refs)).collect();
+        return variants.join(" | ");
+    }
+    match &schema["type"] {
+        Value::String(kind) => primitive(kind, schema, refs),
+        Value::Array(kinds) => {
+            let kinds: Vec<String> = kinds
+                .iter()
+                .map(|kind| primitive(kind.as_str().unwrap_or_default(), schema, refs))
+                .collect();
+            kinds.join(" | ")
+        }
+        _ => "unknown".to_string(),
+    }
+}
+
+fn primitive(kind: &str, schema: &Value, refs: &mut BTreeSet<String>) -> String {
+    match kind {
+        "string" | "boolean" | "null" => kind.to_string(),
+        "integer" | "number" => "number".to_string(),
+        "array" => match ts_type(&schema["items"], refs) {
+            item if item.contains(' ') => format!("({item})[]"),
+            item => format!("{item}[]"),
+        },
+        _ => "unknown".to_string(),
+    }
+}
+
+/// One exported function calling `command`
+fn function(command: &str, spec: &Value, refs: &mut BTreeSet<String>) -> String {
+    let name = function_name(command);
+    let returns = match ts_type(&spec["returns"], refs) {
+        returns if returns == "null" => "void".to_string(),
+        returns => returns,
+    };
+
+    let mut fields = Vec::new();
+    let mut all_optional = true;
+    for (arg, schema) in spec["args"].as_object().into_iter().flatten() {
+        let ty = ts_type(schema, refs);
+        let optional = ty.ends_with("| null");
+        all_optional &= optional;
+        fields.push(format!("{arg}{}: {ty}", if optional { "?" } else { "" }));
+    }
+    let default = if all_optional { " = {}" } else { "" };
+
+    let signature = if fields.is_empty() {
+        format!("export function {name}(): Promise<{returns}> {{")
+    } else {
+        let line = format!(
+            "export function {name}(args: {{ {} }}{default}): Promise<{returns}> {{",
+            fields.join("; ")
+        );
+        if line.len() <= MAX_LINE {
+            line
+        } else {
+            let fields: String = fields.iter().map(|f| format!("  {f};\n")).collect();
+            format!("export function {name}(args: {{\n{fields}}}{default}): Promise<{returns}> {{")
+        }
+    };
+    let call = if spec["args"]
+        .as_object()
+        .is_some_and(|args| !args.is_empty())
+    {
+        format!("invoke<{returns}>('{command}', args)")
+    } else {
+        format!("invoke<{returns}>('{command}')")
+    };
+    let doc = if spec["error"]["$ref"] == "#/definitions/AppError" {
+        "/** Rejects with an `AppError` */\n"
+    } else {
+        ""
+    };
+    format!("{doc}{signature}\n  return {call};\n}}\n")
+}
+
+/// The text checked in as `src/ipc.ts`
+pub fn generate() -> String {
+    let schema = schema::export();
+    let mut refs = BTreeSet::new();
+    let functions: Vec<String> = schema["x-commands"]
+        .as_object()
+        .into_iter()
+        .flatten()
+        .map(|(command, spec)| function(command, spec, &mut refs))
+        .collect();
+
+    let mut text = HEADER.to_string();
+    if !refs.is_empty() {
+        let names: String = refs.iter().map(|name| format!("  {name},\n")).collect();
+        text.push_str(&format!("import type {{\n{names}}} from './types';\n"));
+    }
+    for function in functions {
+        text.push('\n');
+        text.push_str(&function);
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::json;
+
+    const CLIENT_FILE: &str = "../src/ipc.ts";
+    const TYPES_FILE: &str = "../src/types.ts";
+
+    fn read(file: &str) -> String {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(file);
+        std::fs::read_to_string(path).unwrap_or_default()
+    }
+
+    #[test]
+    fn test_checked_in_client_is_current() {
+        assert!(
+            read(CLIENT_FILE) == generate(),
+            "{CLIENT_FILE} is out of date; regenerate it with `npm run gen:ipc`"
+        );
+    }
+
+    #[test]
+    fn test_client_types_exist_in_types_ts() {
+        let types = read(TYPES_FILE);
+        let mut refs = BTreeSet::new();
+        for spec in schema::export()["x-commands"].as_object().unwrap().values() {
+            function("command", spec, &mut refs);
+        }
+        for name in refs {
+            assert!(
+                types.contains(&format!("export interface {name} "))
+                    || types.contains(&format!("export type {name} ")),
+                "{name} is used by a command but not exported from {TYPES_FILE}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_optional_arguments_and_void_results() {
+        let spec = json!({
+            "args": { "deviceId": { "type": ["string", "null"] } },
+            "returns": { "type": "null" },
+            "error": { "type": "string" },
+        });
+        assert_eq!(
+            function("start_recording", &spec, &mut BTreeSet::new()),
+            "export function startRecording(args: { deviceId?: string | null } = {}): \
+             Promise<void> {\n  return invoke<void>('start_recording', args);\n}\n"
+        );
+
+        let items = json!({ "type": "array", "items": { "type": ["integer", "null"] } });
+        assert_eq!(ts_type(&items, &mut BTreeSet::new()), "(number | null)[]");
+    }
+}
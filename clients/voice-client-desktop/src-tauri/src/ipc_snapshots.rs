@@ -0,0 +1,297 @@
+//! Snapshot tests of the command layer: a headless app on Tauri's mock runtime is
+//! sent real IPC requests, and what each command returns and emits is compared with
+//! `snapshots/ipc-commands.json`. Regenerate it after an intended change with
+//! `UPDATE_SNAPSHOTS=1 cargo test ipc_snapshots`.
+
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use serde_json::{json, Map, Value};
+use tauri::ipc::{CallbackFn, InvokeBody};
+use tauri::test::{mock_builder, mock_context, noop_assets, MockRuntime, INVOKE_KEY};
+use tauri::webview::InvokeRequest;
+use tauri::{App, Listener, WebviewWindow, WebviewWindowBuilder};
+
+use crate::capabilities::CapabilityState;
+use crate::countdown::CountdownState;
+use crate::events::EventLog;
+use crate::file_transcription::FileTranscriptionState;
+use crate::http_log::HttpLog;
+use crate::idle::PresenceState;
+use crate::playback::PlaybackState;
+use crate::privacy::PrivacyState;
+use crate::session::SessionState;
+use crate::types::{LoggedEventKind, VoiceEvent};
+use crate::voiceprint::VoiceprintState;
+use crate::{commands, schema};
+
+const SNAPSHOT_FILE: &str = "snapshots/ipc-commands.json";
+
+/// Commands invoked by the snapshot test, with their arguments
+const CASES: &[(&str, &str)] = &[
+    ("cancel_countdown", "{}"),
+    ("cancel_file_transcription", "{}"),
+    ("get_event_log", r#"{ "limit": 2 }"#),
+    ("get_gateway_capabilities", "{}"),
+    ("get_last_turn", "{}"),
+    ("get_listening_pause", "{}"),
+    ("get_playback_status", "{}"),
+    ("get_privacy_mode", "{}"),
+    ("get_voiceprint_status", "{}"),
+    ("tail_http_log", r#"{ "lines": 10 }"#),
+];
+
+/// Commands the mock runtime can't run: they take the desktop `AppHandle` (windows,
+/// tray, store, audio devices), so they are covered by their modules' own tests
+const UNCOVERED: &[&str] = &[
+    "clear_voiceprint",
+    "create_session",
+    "delete_correction",
+    "delete_recording",
+    "describe_hotkey",
+    "diagnose_connection",
+    "discard_failed",
+    "enqueue_audio",
+    "finish_voiceprint_enrollment",
+    "focus_popup",
+    "get_device_capabilities",
+    "get_mic_usage_log",
+    "get_remote_pairing",
+    "get_remote_session",
+    "get_session_transcript",
+    "get_sessions_overview",
+    "get_storage_usage",
+    "get_system_a11y_prefs",
+    "get_task_health",
+    "get_unread_count",
+    "invoke_action",
+    "is_demo_mode",
+    "learn_correction",
+    "list_audio_devices",
+    "list_audio_hosts",
+    "list_corrections",
+    "list_failed_sends",
+    "list_recordings",
+    "load_settings",
+    "mark_all_read",
+    "open_conversation_window",
+    "open_history_window",
+    "open_settings_window",
+    "pause_recording",
+    "play_audio",
+    "probe_gateway_capabilities",
+    "quit_app",
+    "resend_last_audio",
+    "reset_remote_pairing",
+    "restore_last_session",
+    "resume_recording",
+    "resume_playback",
+    "retry_failed",
+    "run_macro",
+    "run_self_check",
+    "save_settings",
+    "scroll_history",
+    "search_remote_history",
+    "set_playback_volume",
+    "set_popup_pinned",
+    "set_privacy_mode",
+    "show_more_of_reply",
+    "skip_playback",
+    "start_dictation",
+    "start_recording",
+    "start_voiceprint_enrollment",
+    "stop_all_playback",
+    "stop_and_send",
+    "stop_dictation",
+    "stop_playback",
+    "submit_feedback",
+    "take_launch_recording",
+    "test_connection",
+    "test_microphone",
+    "transcribe_file",
+];
+
+/// Events emitted by the case running now, as (name, payload)
+type Captured = Arc<Mutex<Vec<(String, Value)>>>;
+
+/// The app with the states the covered commands read, seeded with a finished turn
+/// and a few logged events so the getters have something to return
+fn mock_app() -> App<MockRuntime> {
+    let sessions = SessionState::new();
+    let turn = [
+        VoiceEvent::User {
+            text: "What's the weather?".to_string(),
+            confidence: 0.93,
+            timestamp: "2025-01-01T10:00:00Z".to_string(),
+        },
+        VoiceEvent::Openclaw {
+            text: "Sunny, 21 degrees.".to_string(),
+            done: true,
+            timestamp: "2025-01-01T10:00:02Z".to_string(),
+        },
+    ];
+    for event in &turn {
+        sessions.record_event("voice-1", event);
+    }
+
+    let log = EventLog::new(50);
+    log.record(
+        LoggedEventKind::State,
+        "recording-started",
+        json!({ "source": "popup" }),
+    );
+    log.record(
+        LoggedEventKind::Emitted,
+        "auto-stop",
+        json!({ "silenceMs": 1500 }),
+    );
+    log.record(
+        LoggedEventKind::State,
+        "live-upload-skipped",
+        json!("noise suppression needs the whole recording"),
+    );
+
+    mock_builder()
+        .manage(sessions)
+        .manage(log)
+        .manage(CapabilityState::new())
+        .manage(CountdownState::new())
+        .manage(FileTranscriptionState::new())
+        .manage(HttpLog::new())
+        .manage(PlaybackState::new())
+        .manage(PresenceState::new())
+        .manage(PrivacyState::new())
+        .manage(VoiceprintState::new())
+        .invoke_handler(tauri::generate_handler![
+            commands::cancel_countdown,
+            commands::cancel_file_transcription,
+            commands::get_event_log,
+            commands::get_gateway_capabilities,
+            commands::get_last_turn,
+            commands::get_listening_pause,
+            commands::get_playback_status,
+            commands::get_privacy_mode,
+            commands::get_voiceprint_status,
+            commands::tail_http_log,
+        ])
+        .build(mock_context(noop_assets()))
+        .expect("failed to build the mock app")
+}
+
+/// Record every event the schema declares
+fn capture_events(app: &App<MockRuntime>) -> Captured {
+    let captured = Captured::default();
+    let schema = schema::export();
+    let names = schema["x-events"].as_object().cloned().unwrap_or_default();
+    for name in names.keys() {
+        let captured = captured.clone();
+        let event_name = name.clone();
+        app.listen_any(name.as_str(), move |event| {
+            let payload = serde_json::from_str(event.payload()).unwrap_or(Value::Null);
+            if let Ok(mut captured) = captured.lock() {
+                captured.push((event_name.clone(), payload));
+            }
+        });
+    }
+    captured
+}
+
+fn invoke(webview: &WebviewWindow<MockRuntime>, cmd: &str, args: Value) -> Value {
+    let request = InvokeRequest {
+        cmd: cmd.to_string(),
+        callback: CallbackFn(0),
+        error: CallbackFn(1),
+        url: "http://tauri.localhost".parse().unwrap(),
+        body: InvokeBody::Json(args),
+        headers: Default::default(),
+        invoke_key: INVOKE_KEY.to_string(),
+    };
+    match tauri::test::get_ipc_response(webview, request) {
+        Ok(body) => json!({ "ok": body.deserialize::<Value>().unwrap_or(Value::Null) }),
+        Err(error) => json!({ "error": error }),
+    }
+}
+
+/// Blank out what changes from run to run
+fn normalize(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if key == "timestampMs" {
+                    *value = json!(0);
+                } else {
+                    normalize(value);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(normalize),
+        _ => {}
+    }
+}
+
+fn run_cases() -> String {
+    let app = mock_app();
+    let captured = capture_events(&app);
+    let webview = WebviewWindowBuilder::new(&app, "main", Default::default())
+        .build()
+        .expect("failed to open the mock webview");
+
+    let mut snapshot = Map::new();
+    for (cmd, args) in CASES {
+        let args: Value = serde_json::from_str(args).unwrap();
+        let mut response = invoke(&webview, cmd, args.clone());
+        normalize(&mut response);
+        let events: Vec<Value> = captured
+            .lock()
+            .map(|mut captured| captured.drain(..).collect::<Vec<_>>())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(event, payload)| json!({ "event": event, "payload": payload }))
+            .collect();
+        snapshot.insert(
+            cmd.to_string(),
+            json!({ "args": args, "response": response, "events": events }),
+        );
+    }
+    let mut text = serde_json::to_string_pretty(&Value::Object(snapshot)).unwrap_or_default();
+    text.push('\n');
+    text
+}
+
+#[test]
+fn test_command_snapshots() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(SNAPSHOT_FILE);
+    let actual = run_cases();
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        std::fs::write(&path, &actual).unwrap();
+        return;
+    }
+    let checked_in = std::fs::read_to_string(&path).unwrap_or_default();
+    assert!(
+        checked_in == actual,
+        "{SNAPSHOT_FILE} doesn't match the commands' output:\n{actual}\n\
+         if the change is intended, regenerate it with \
+         `UPDATE_SNAPSHOTS=1 cargo test ipc_snapshots`"
+    );
+}
+
+#[test]
+fn test_every_command_is_accounted_for() {
+    let schema = schema::export();
+    let commands = schema["x-commands"].as_object().unwrap();
+    for name in commands.keys() {
+        let covered = CASES.iter().any(|(cmd, _)| cmd == name);
+        assert!(
+            covered || UNCOVERED.contains(&name.as_str()),
+            "{name} has no snapshot case; add one to CASES, or to UNCOVERED if it needs \
+             the desktop runtime"
+        );
+    }
+    for name in CASES
+        .iter()
+        .map(|(cmd, _)| *cmd)
+        .chain(UNCOVERED.iter().copied())
+    {
+        assert!(commands.contains_key(name), "{name} is not a command");
+    }
+}
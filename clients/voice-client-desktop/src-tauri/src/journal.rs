@@ -0,0 +1,310 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::api::Turn;
+use crate::types::SendStage;
+use crate::{audio, events, failed_sends, persist, privacy, storage};
+
+pub const JOURNAL_DIR: &str = "pending-sends";
+
+/// A send in progress, as written next to its audio
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingSend {
+    id: String,
+    stage: SendStage,
+    base_url: String,
+    session_id: String,
+    profile_name: String,
+    session_key: Option<String>,
+    response_language: Option<String>,
+    started_at_ms: u64,
+}
+
+/// Sends in progress, each journaled to disk at every step until it is answered or
+/// saved as failed. Whatever a crash leaves behind is offered for resending on the
+/// next launch instead of the recording silently vanishing.
+pub struct JournalState {
+    /// Journaled sends of this run, by id
+    pub active: Arc<Mutex<HashMap<String, PendingSend>>>,
+}
+
+impl JournalState {
+    pub fn new() -> Self {
+        Self {
+            active: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+fn journal_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(JOURNAL_DIR))
+        .map_err(|e| format!("Failed to resolve app data dir: {e}"))
+}
+
+fn meta_path(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{id}.json"))
+}
+
+fn wav_path(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{id}.wav"))
+}
+
+fn write_meta(dir: &Path, pending: &PendingSend) -> Result<(), String> {
+    let bytes = serde_json::to_vec_pretty(pending)
+        .map_err(|e| format!("Failed to serialize pending send: {e}"))?;
+    persist::write_atomic(&meta_path(dir, &pending.id), &bytes)
+}
+
+fn remove(dir: &Path, id: &str) {
+    let _ = fs::remove_file(wav_path(dir, id));
+    let _ = fs::remove_file(meta_path(dir, id));
+    let _ = fs::remove_file(dir.join(format!("{id}.json.bak")));
+}
+
+/// Sends left in `dir`, oldest first. The audio is written before the metadata,
+/// so a send only counts once both files exist.
+fn list_dir(dir: &Path) -> Vec<PendingSend> {
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut pending: Vec<PendingSend> = read_dir
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let id = name.strip_suffix(".json")?.to_string();
+            let bytes = fs::read(meta_path(dir, &id)).ok()?;
+            let pending: PendingSend = serde_json::from_slice(&bytes).ok()?;
+            (pending.id == id && wav_path(dir, &id).exists()).then_some(pending)
+        })
+        .collect();
+    pending.sort_by(|a, b| a.started_at_ms.cmp(&b.started_at_ms).then(a.id.cmp(&b.id)));
+    pending
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// One journaled send. Dropping it removes the journal, so every way out of a send,
+/// early returns included, clears it; only a crash leaves it behind.
+pub struct JournalEntry {
+    app: AppHandle,
+    dir: PathBuf,
+    id: String,
+}
+
+/// Journal a recording that is about to be processed and sent, with the audio as
+/// captured. None when it can't or mustn't be written: privacy mode, a full disk.
+pub fn begin(
+    app: &AppHandle,
+    turn: &Turn<'_>,
+    samples: &[f32],
+    sample_rate: u32,
+) -> Option<JournalEntry> {
+    if privacy::is_enabled(app) || storage::admit(app, false).is_err() {
+        return None;
+    }
+    let started = (|| {
+        let dir = journal_dir(app)?;
+        fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+        let started_at_ms = now_ms();
+        let mut id = started_at_ms.to_string();
+        let mut suffix = 1;
+        while meta_path(&dir, &id).exists() || wav_path(&dir, &id).exists() {
+            id = format!("{started_at_ms}-{suffix}");
+            suffix += 1;
+        }
+        let pending = PendingSend {
+            id,
+            stage: SendStage::Recorded,
+            base_url: turn.base_url.to_string(),
+            session_id: turn.session_id.to_string(),
+            profile_name: turn.profile_name.to_string(),
+            session_key: turn.session_key.map(String::from),
+            response_language: turn.response_language.map(String::from),
+            started_at_ms,
+        };
+        persist::write_atomic(
+            &wav_path(&dir, &pending.id),
+            &audio::encode_wav(samples, sample_rate)?,
+        )?;
+        write_meta(&dir, &pending)?;
+        Ok::<_, String>((dir, pending))
+    })();
+    match started {
+        Ok((dir, pending)) => {
+            let id = pending.id.clone();
+            if let Ok(mut active) = app.state::<JournalState>().active.lock() {
+                active.insert(id.clone(), pending);
+            }
+            Some(JournalEntry {
+                app: app.clone(),
+                dir,
+                id,
+            })
+        }
+        Err(e) => {
+            eprintln!("Send not journaled: {e}");
+            None
+        }
+    }
+}
+
+impl JournalEntry {
+    fn set_stage(&self, stage: SendStage) {
+        let state = self.app.state::<JournalState>();
+        let Ok(mut active) = state.active.lock() else {
+            return;
+        };
+        if let Some(pending) = active.get_mut(&self.id) {
+            pending.stage = stage;
+            if let Err(e) = write_meta(&self.dir, pending) {
+                eprintln!("Send journal not updated: {e}");
+            }
+        }
+    }
+
+    /// Processing and encoding has started
+    pub fn encoding(&self) {
+        self.set_stage(SendStage::Encoding);
+    }
+
+    /// The upload has started; from here on a resend sends exactly `wav_bytes`
+    pub fn uploading(&self, wav_bytes: &[u8]) {
+        if let Err(e) = persist::write_atomic(&wav_path(&self.dir, &self.id), wav_bytes) {
+            eprintln!("Send journal not updated: {e}");
+            return;
+        }
+        self.set_stage(SendStage::Uploading);
+    }
+}
+
+impl Drop for JournalEntry {
+    fn drop(&mut self) {
+        if let Ok(mut active) = self.app.state::<JournalState>().active.lock() {
+            active.remove(&self.id);
+        }
+        remove(&self.dir, &self.id);
+    }
+}
+
+/// The gateway has started streaming the reply to a journaled upload
+pub fn stream_started(app: &AppHandle, session_id: &str) {
+    let Some(state) = app.try_state::<JournalState>() else {
+        return;
+    };
+    let Ok(mut active) = state.active.lock() else {
+        return;
+    };
+    let Ok(dir) = journal_dir(app) else {
+        return;
+    };
+    for pending in active
+        .values_mut()
+        .filter(|pending| pending.session_id == session_id && pending.stage == SendStage::Uploading)
+    {
+        pending.stage = SendStage::AwaitingStream;
+        if let Err(e) = write_meta(&dir, pending) {
+            eprintln!("Send journal not updated: {e}");
+        }
+    }
+}
+
+/// Move sends an earlier run never finished into the failed sends, where they can be
+/// resent or discarded. Runs at startup, before anything new is journaled.
+pub fn recover(app: &AppHandle) {
+    let Ok(dir) = journal_dir(app) else {
+        return;
+    };
+    for pending in list_dir(&dir) {
+        let Ok(wav_bytes) = fs::read(wav_path(&dir, &pending.id)) else {
+            continue;
+        };
+        let turn = Turn {
+            base_url: &pending.base_url,
+            session_id: &pending.session_id,
+            profile_name: &pending.profile_name,
+            session_key: pending.session_key.as_deref(),
+            response_language: pending.response_language.as_deref(),
+            context: None,
+        };
+        // Kept in the journal if it can't be saved now, e.g. on a full disk
+        match failed_sends::save_interrupted(app, &turn, &wav_bytes, pending.stage) {
+            Ok(saved) => {
+                remove(&dir, &pending.id);
+                events::record_state(
+                    app,
+                    "send-recovered",
+                    serde_json::json!({ "id": saved.id, "stage": pending.stage }),
+                );
+            }
+            Err(e) => eprintln!("Interrupted send {} not recovered: {e}", pending.id),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    fn pending(id: &str, started_at_ms: u64, stage: SendStage) -> PendingSend {
+        PendingSend {
+            id: id.to_string(),
+            stage,
+            base_url: "http://127.0.0.1:18790/voice-client".to_string(),
+            session_id: "s1".to_string(),
+            profile_name: "alice".to_string(),
+            session_key: None,
+            response_language: Some("de".to_string()),
+            started_at_ms,
+        }
+    }
+
+    fn store(dir: &Path, pending: &PendingSend) {
+        fs::write(wav_path(dir, &pending.id), b"RIFF").unwrap();
+        write_meta(dir, pending).unwrap();
+    }
+
+    #[test]
+    fn test_left_over_sends_are_listed_oldest_first() {
+        let dir = TempDir::new("journal-list");
+        store(&dir, &pending("300", 300, SendStage::AwaitingStream));
+        store(&dir, &pending("100", 100, SendStage::Recorded));
+        store(&dir, &pending("200", 200, SendStage::Uploading));
+
+        let listed = list_dir(&dir);
+        let ids: Vec<&str> = listed.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(ids, vec!["100", "200", "300"]);
+        assert_eq!(listed[2], pending("300", 300, SendStage::AwaitingStream));
+    }
+
+    #[test]
+    fn test_half_written_sends_are_ignored() {
+        let dir = TempDir::new("journal-partial");
+        // Crashed between writing the audio and the metadata
+        fs::write(wav_path(&dir, "100"), b"RIFF").unwrap();
+        store(&dir, &pending("200", 200, SendStage::Encoding));
+        fs::write(meta_path(&dir, "300"), b"{ not json").unwrap();
+        fs::write(wav_path(&dir, "300"), b"RIFF").unwrap();
+
+        let ids: Vec<String> = list_dir(&dir).into_iter().map(|p| p.id).collect();
+        assert_eq!(ids, vec!["200"]);
+
+        remove(&dir, "200");
+        assert!(list_dir(&dir).is_empty());
+        assert!(!wav_path(&dir, "200").exists());
+    }
+}
@@ -0,0 +1,309 @@
+use tauri_plugin_global_shortcut::Code;
+
+use crate::types::{AppSettings, KeyboardLayout};
+
+/// What each physical key is labelled on a US QWERTY keyboard. Hotkeys are registered
+/// by physical key, so this is also how a bare character in an accelerator was read.
+const QWERTY: &[(Code, char)] = &[
+    (Code::KeyQ, 'Q'),
+    (Code::KeyW, 'W'),
+    (Code::KeyE, 'E'),
+    (Code::KeyR, 'R'),
+    (Code::KeyT, 'T'),
+    (Code::KeyY, 'Y'),
+    (Code::KeyU, 'U'),
+    (Code::KeyI, 'I'),
+    (Code::KeyO, 'O'),
+    (Code::KeyP, 'P'),
+    (Code::BracketLeft, '['),
+    (Code::BracketRight, ']'),
+    (Code::KeyA, 'A'),
+    (Code::KeyS, 'S'),
+    (Code::KeyD, 'D'),
+    (Code::KeyF, 'F'),
+    (Code::KeyG, 'G'),
+    (Code::KeyH, 'H'),
+    (Code::KeyJ, 'J'),
+    (Code::KeyK, 'K'),
+    (Code::KeyL, 'L'),
+    (Code::Semicolon, ';'),
+    (Code::Quote, '\''),
+    (Code::KeyZ, 'Z'),
+    (Code::KeyX, 'X'),
+    (Code::KeyC, 'C'),
+    (Code::KeyV, 'V'),
+    (Code::KeyB, 'B'),
+    (Code::KeyN, 'N'),
+    (Code::KeyM, 'M'),
+    (Code::Comma, ','),
+    (Code::Period, '.'),
+    (Code::Slash, '/'),
+    (Code::Minus, '-'),
+    (Code::Equal, '='),
+];
+
+/// French AZERTY keys that differ from QWERTY
+const AZERTY: &[(Code, char)] = &[
+    (Code::KeyQ, 'A'),
+    (Code::KeyW, 'Z'),
+    (Code::KeyA, 'Q'),
+    (Code::Semicolon, 'M'),
+    (Code::Quote, 'Ù'),
+    (Code::KeyZ, 'W'),
+    (Code::KeyM, ','),
+    (Code::Comma, ';'),
+    (Code::Period, ':'),
+    (Code::Slash, '!'),
+    (Code::BracketLeft, '^'),
+    (Code::BracketRight, '$'),
+    (Code::Minus, ')'),
+];
+
+/// German QWERTZ keys that differ from QWERTY
+const QWERTZ: &[(Code, char)] = &[
+    (Code::KeyY, 'Z'),
+    (Code::KeyZ, 'Y'),
+    (Code::BracketLeft, 'Ü'),
+    (Code::BracketRight, '+'),
+    (Code::Semicolon, 'Ö'),
+    (Code::Quote, 'Ä'),
+    (Code::Slash, '-'),
+    (Code::Minus, 'ß'),
+];
+
+/// US Dvorak keys that differ from QWERTY
+const DVORAK: &[(Code, char)] = &[
+    (Code::KeyQ, '\''),
+    (Code::KeyW, ','),
+    (Code::KeyE, '.'),
+    (Code::KeyR, 'P'),
+    (Code::KeyT, 'Y'),
+    (Code::KeyY, 'F'),
+    (Code::KeyU, 'G'),
+    (Code::KeyI, 'C'),
+    (Code::KeyO, 'R'),
+    (Code::KeyP, 'L'),
+    (Code::BracketLeft, '/'),
+    (Code::BracketRight, '='),
+    (Code::KeyS, 'O'),
+    (Code::KeyD, 'E'),
+    (Code::KeyF, 'U'),
+    (Code::KeyG, 'I'),
+    (Code::KeyH, 'D'),
+    (Code::KeyJ, 'H'),
+    (Code::KeyK, 'T'),
+    (Code::KeyL, 'N'),
+    (Code::Semicolon, 'S'),
+    (Code::Quote, '-'),
+    (Code::KeyZ, ';'),
+    (Code::KeyX, 'Q'),
+    (Code::KeyC, 'J'),
+    (Code::KeyV, 'K'),
+    (Code::KeyB, 'X'),
+    (Code::KeyN, 'B'),
+    (Code::Comma, 'W'),
+    (Code::Period, 'V'),
+    (Code::Slash, 'Z'),
+    (Code::Minus, '['),
+    (Code::Equal, ']'),
+];
+
+fn overrides(layout: KeyboardLayout) -> &'static [(Code, char)] {
+    match layout {
+        KeyboardLayout::Qwerty => &[],
+        KeyboardLayout::Azerty => AZERTY,
+        KeyboardLayout::Qwertz => QWERTZ,
+        KeyboardLayout::Dvorak => DVORAK,
+    }
+}
+
+/// The character printed on a physical key, if it is one that moves between layouts
+pub fn label(layout: KeyboardLayout, code: Code) -> Option<char> {
+    overrides(layout)
+        .iter()
+        .chain(QWERTY)
+        .find(|(key, _)| *key == code)
+        .map(|(_, label)| *label)
+}
+
+/// The physical key that types `label` on `layout`, ignoring case
+pub fn code_for(layout: KeyboardLayout, label: char) -> Option<Code> {
+    let wanted = |printed: &char| printed.to_lowercase().eq(label.to_lowercase());
+    let moved = overrides(layout);
+    moved
+        .iter()
+        .find(|(_, printed)| wanted(printed))
+        .or_else(|| {
+            // Keys the layout leaves alone, unless their QWERTY label moved elsewhere
+            QWERTY
+                .iter()
+                .filter(|(code, _)| !moved.iter().any(|(m, _)| m == code))
+                .find(|(_, printed)| wanted(printed))
+        })
+        .map(|(code, _)| *code)
+}
+
+/// Windows keyboard layout ids (KLID) such as `0000040C`
+#[cfg(any(test, target_os = "windows"))]
+fn layout_from_klid(klid: &str) -> KeyboardLayout {
+    let klid = klid.to_ascii_uppercase();
+    if klid.starts_with("0001") && klid.ends_with("0409") {
+        return KeyboardLayout::Dvorak;
+    }
+    match klid.get(4..).unwrap_or_default() {
+        // French (France, Belgium)
+        "040C" | "080C" => KeyboardLayout::Azerty,
+        // German (Germany, Switzerland, Austria), Czech, Hungarian
+        "0407" | "0807" | "0C07" | "0405" | "040E" => KeyboardLayout::Qwertz,
+        _ => KeyboardLayout::Qwerty,
+    }
+}
+
+/// The `KeyboardLayout Name` macOS reports for the selected input source
+#[cfg(any(test, target_os = "macos"))]
+fn layout_from_mac_name(name: &str) -> KeyboardLayout {
+    if name.contains("Dvorak") {
+        KeyboardLayout::Dvorak
+    } else if (name.contains("French") || name.contains("Belgian")) && !name.contains("Canadian") {
+        KeyboardLayout::Azerty
+    } else if ["German", "Austrian", "Czech", "Hungarian"]
+        .iter()
+        .any(|language| name.contains(language))
+    {
+        KeyboardLayout::Qwertz
+    } else {
+        KeyboardLayout::Qwerty
+    }
+}
+
+/// The first layout and variant from `setxkbmap -query`
+#[cfg(any(test, target_os = "linux"))]
+fn layout_from_xkb(query: &str) -> KeyboardLayout {
+    let field = |name: &str| {
+        query
+            .lines()
+            .find_map(|line| line.strip_prefix(name))
+            .and_then(|value| value.trim().split(',').next())
+            .unwrap_or_default()
+            .to_string()
+    };
+    let layout = field("layout:");
+    let variant = field("variant:");
+    if variant.contains("dvorak") {
+        return KeyboardLayout::Dvorak;
+    }
+    match layout.as_str() {
+        "fr" | "be" if variant.is_empty() || variant == "latin9" => KeyboardLayout::Azerty,
+        "de" | "at" | "ch" | "cz" | "hu" => KeyboardLayout::Qwertz,
+        _ => KeyboardLayout::Qwerty,
+    }
+}
+
+/// The layout the OS is typing with; QWERTY when it can't be told
+#[cfg(target_os = "windows")]
+pub fn detect() -> KeyboardLayout {
+    use windows_sys::Win32::UI::Input::KeyboardAndMouse::GetKeyboardLayoutNameW;
+
+    let mut name = [0u16; 9];
+    // SAFETY: the buffer holds KL_NAMELENGTH (9) characters as the call requires
+    if unsafe { GetKeyboardLayoutNameW(name.as_mut_ptr()) } == 0 {
+        return KeyboardLayout::Qwerty;
+    }
+    let len = name.iter().position(|&c| c == 0).unwrap_or(name.len());
+    layout_from_klid(&String::from_utf16_lossy(&name[..len]))
+}
+
+#[cfg(target_os = "macos")]
+pub fn detect() -> KeyboardLayout {
+    let output = std::process::Command::new("defaults")
+        .args(["read", "com.apple.HIToolbox", "AppleSelectedInputSources"])
+        .output();
+    let Ok(output) = output else {
+        return KeyboardLayout::Qwerty;
+    };
+    let text = String::from_utf8_lossy(&output.stdout);
+    text.lines()
+        .find_map(|line| line.trim().strip_prefix("\"KeyboardLayout Name\" ="))
+        .map(|name| layout_from_mac_name(name.trim().trim_end_matches(';').trim_matches('"')))
+        .unwrap_or(KeyboardLayout::Qwerty)
+}
+
+#[cfg(target_os = "linux")]
+pub fn detect() -> KeyboardLayout {
+    std::process::Command::new("setxkbmap")
+        .arg("-query")
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| layout_from_xkb(&String::from_utf8_lossy(&output.stdout)))
+        .unwrap_or(KeyboardLayout::Qwerty)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+pub fn detect() -> KeyboardLayout {
+    KeyboardLayout::Qwerty
+}
+
+/// The layout chosen in settings, or the one the OS is using
+pub fn layout(settings: &AppSettings) -> KeyboardLayout {
+    settings.keyboard_layout.unwrap_or_else(detect)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_labels_follow_the_layout() {
+        assert_eq!(label(KeyboardLayout::Qwerty, Code::KeyQ), Some('Q'));
+        assert_eq!(label(KeyboardLayout::Azerty, Code::KeyQ), Some('A'));
+        assert_eq!(label(KeyboardLayout::Qwertz, Code::KeyZ), Some('Y'));
+        assert_eq!(label(KeyboardLayout::Dvorak, Code::KeyK), Some('T'));
+        assert_eq!(label(KeyboardLayout::Azerty, Code::Space), None);
+    }
+
+    #[test]
+    fn test_code_for_is_the_inverse_of_label() {
+        for layout in [
+            KeyboardLayout::Qwerty,
+            KeyboardLayout::Azerty,
+            KeyboardLayout::Qwertz,
+            KeyboardLayout::Dvorak,
+        ] {
+            for (code, _) in QWERTY {
+                let printed = label(layout, *code).unwrap();
+                assert_eq!(
+                    code_for(layout, printed),
+                    Some(*code),
+                    "{layout:?} {printed}"
+                );
+            }
+        }
+        assert_eq!(code_for(KeyboardLayout::Azerty, 'a'), Some(Code::KeyQ));
+        // Not on the QWERTY row at all
+        assert_eq!(code_for(KeyboardLayout::Azerty, '['), None);
+    }
+
+    #[test]
+    fn test_platform_layout_names() {
+        assert_eq!(layout_from_klid("0000040C"), KeyboardLayout::Azerty);
+        assert_eq!(layout_from_klid("00010409"), KeyboardLayout::Dvorak);
+        assert_eq!(layout_from_klid("00000407"), KeyboardLayout::Qwertz);
+        assert_eq!(layout_from_klid("00000409"), KeyboardLayout::Qwerty);
+
+        assert_eq!(layout_from_mac_name("French - PC"), KeyboardLayout::Azerty);
+        assert_eq!(
+            layout_from_mac_name("Canadian French - CSA"),
+            KeyboardLayout::Qwerty
+        );
+        assert_eq!(layout_from_mac_name("Swiss German"), KeyboardLayout::Qwertz);
+        assert_eq!(layout_from_mac_name("ABC"), KeyboardLayout::Qwerty);
+
+        let query = "rules:      evdev\nmodel:      pc105\nlayout:     fr,us\n";
+        assert_eq!(layout_from_xkb(query), KeyboardLayout::Azerty);
+        let query = "layout:     us\nvariant:    dvorak\n";
+        assert_eq!(layout_from_xkb(query), KeyboardLayout::Dvorak);
+        let query = "layout:     fr\nvariant:    bepo\n";
+        assert_eq!(layout_from_xkb(query), KeyboardLayout::Qwerty);
+    }
+}
@@ -0,0 +1,165 @@
+use std::sync::{Arc, Mutex};
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::events;
+use crate::types::{KeywordHit, KeywordKind};
+
+/// Phrases that usually mean someone is handing out work
+const ACTION_ITEM_PHRASES: &[&str] = &[
+    "action item",
+    "todo",
+    "follow up",
+    "next step",
+    "deadline",
+    "assigned to",
+];
+
+struct Term {
+    label: String,
+    words: Vec<String>,
+    kind: KeywordKind,
+}
+
+/// Local keyword spotting over transcripts: whole-word, case-insensitive matching of
+/// configured names and terms, optionally plus common action-item phrases
+#[derive(Default)]
+pub struct KeywordSpotter {
+    terms: Vec<Term>,
+}
+
+/// Lowercased words, split on anything that isn't a letter or digit
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// `heard` matches `term` exactly or as its plural ("action items")
+fn word_matches(heard: &str, term: &str) -> bool {
+    heard == term || heard.strip_suffix('s') == Some(term)
+}
+
+impl KeywordSpotter {
+    pub fn new(keywords: &[String], action_items: bool) -> Self {
+        let watched = keywords
+            .iter()
+            .map(|k| (k.trim().to_string(), KeywordKind::Watched));
+        let actions = ACTION_ITEM_PHRASES
+            .iter()
+            .filter(|_| action_items)
+            .map(|p| (p.to_string(), KeywordKind::ActionItem));
+        let terms = watched
+            .chain(actions)
+            .filter_map(|(label, kind)| {
+                let words = words(&label);
+                (!words.is_empty()).then_some(Term { label, words, kind })
+            })
+            .collect();
+        Self { terms }
+    }
+
+    /// Terms heard in `text`, each reported once
+    pub fn spot(&self, text: &str) -> Vec<(&str, KeywordKind)> {
+        let heard = words(text);
+        self.terms
+            .iter()
+            .filter(|term| {
+                heard.windows(term.words.len()).any(|window| {
+                    window
+                        .iter()
+                        .zip(&term.words)
+                        .all(|(heard, term)| word_matches(heard, term))
+                })
+            })
+            .map(|term| (term.label.as_str(), term.kind))
+            .collect()
+    }
+}
+
+pub struct KeywordState {
+    pub spotter: Arc<Mutex<KeywordSpotter>>,
+}
+
+impl KeywordState {
+    pub fn new() -> Self {
+        Self {
+            spotter: Arc::new(Mutex::new(KeywordSpotter::default())),
+        }
+    }
+
+    pub fn configure(&self, spotter: KeywordSpotter) {
+        if let Ok(mut current) = self.spotter.lock() {
+            *current = spotter;
+        }
+    }
+}
+
+/// Spot keywords in a transcript and emit a "keyword-hit" for each one heard
+pub fn check(app: &AppHandle, text: &str, timestamp: &str) {
+    let Some(state) = app.try_state::<KeywordState>() else {
+        return;
+    };
+    let hits: Vec<KeywordHit> = match state.spotter.lock() {
+        Ok(spotter) => spotter
+            .spot(text)
+            .into_iter()
+            .map(|(term, kind)| KeywordHit {
+                term: term.to_string(),
+                kind,
+                text: text.to_string(),
+                timestamp: timestamp.to_string(),
+            })
+            .collect(),
+        Err(_) => return,
+    };
+    for hit in hits {
+        events::record_state(
+            app,
+            "keyword-hit",
+            serde_json::json!({ "term": hit.term, "kind": hit.kind }),
+        );
+        let _ = app.emit("keyword-hit", &hit);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(spotter: &KeywordSpotter, text: &str) -> Vec<String> {
+        spotter
+            .spot(text)
+            .into_iter()
+            .map(|(label, _)| label.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_whole_words_case_insensitive() {
+        let spotter = KeywordSpotter::new(&["Anna".to_string(), "Q3 budget".to_string()], false);
+        assert_eq!(
+            labels(&spotter, "anna, can you check the q3 Budget?"),
+            vec!["Anna", "Q3 budget"]
+        );
+        // Not inside other words
+        assert!(labels(&spotter, "Annabelle sent the budget").is_empty());
+    }
+
+    #[test]
+    fn test_action_items_are_opt_in_and_match_plurals() {
+        let text = "Two action items: follow up with legal";
+        assert!(labels(&KeywordSpotter::new(&[], false), text).is_empty());
+        assert_eq!(
+            labels(&KeywordSpotter::new(&[], true), text),
+            vec!["action item", "follow up"]
+        );
+    }
+
+    #[test]
+    fn test_blank_terms_are_ignored() {
+        let spotter = KeywordSpotter::new(&["  ".to_string(), "--".to_string()], false);
+        assert!(spotter.spot("anything at all").is_empty());
+    }
+}
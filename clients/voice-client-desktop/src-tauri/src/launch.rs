@@ -0,0 +1,138 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use tauri::{AppHandle, Manager};
+
+use crate::demo::DemoState;
+use crate::events;
+use crate::settings;
+
+/// What a launcher asked for on the command line, e.g. a Stream Deck button running
+/// `openclaw-voice-client --profile work --start-recording`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LaunchArgs {
+    /// Profile to switch to before the popup connects
+    pub profile: Option<String>,
+    /// Start recording once the popup has a session
+    pub start_recording: bool,
+    /// Demo mode for this run only; the setting is left as it is
+    pub demo: bool,
+}
+
+/// Read `--profile <name>` (or `--profile=<name>`), `--start-recording` and `--demo`.
+/// Anything else is left alone: macOS and `tauri dev` pass arguments of their own.
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<LaunchArgs, String> {
+    let mut launch = LaunchArgs::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--start-recording" {
+            launch.start_recording = true;
+            continue;
+        }
+        if arg == "--demo" {
+            launch.demo = true;
+            continue;
+        }
+        let profile = match arg.strip_prefix("--profile") {
+            Some("") => args.next(),
+            Some(value) => match value.strip_prefix('=') {
+                Some(value) => Some(value.to_string()),
+                None => continue,
+            },
+            None => continue,
+        };
+        match profile
+            .map(|p| p.trim().to_string())
+            .filter(|p| !p.is_empty())
+        {
+            Some(profile) if !profile.starts_with("--") => launch.profile = Some(profile),
+            _ => return Err("--profile needs a profile name".to_string()),
+        }
+    }
+    Ok(launch)
+}
+
+/// A `--start-recording` the popup hasn't picked up yet
+pub struct LaunchState {
+    start_recording: AtomicBool,
+}
+
+impl LaunchState {
+    pub fn new() -> Self {
+        Self {
+            start_recording: AtomicBool::new(false),
+        }
+    }
+
+    /// True once after a launch with `--start-recording`
+    pub fn take_start_recording(&self) -> bool {
+        self.start_recording.swap(false, Ordering::SeqCst)
+    }
+}
+
+/// Act on the launch arguments during setup, after settings are applied and before
+/// the popup loads them. The profile is saved, as if picked in Settings, so the
+/// popup and everything keyed by profile use it.
+pub fn apply(app: &AppHandle, launch: LaunchArgs) {
+    if launch.demo {
+        events::record_state(app, "launch-demo", ());
+        app.state::<DemoState>().force();
+    }
+    if let Some(profile) = launch.profile {
+        let switched = settings::load_settings(app).and_then(|mut current| {
+            if current.profile_name == profile {
+                return Ok(());
+            }
+            current.profile_name = profile.clone();
+            settings::save_settings(app, &current)?;
+            settings::apply_settings(app, &current);
+            Ok(())
+        });
+        match switched {
+            Ok(()) => events::record_state(app, "launch-profile", &profile),
+            Err(e) => eprintln!("Profile '{profile}' from the command line not applied: {e}"),
+        }
+    }
+    if launch.start_recording {
+        events::record_state(app, "launch-recording", ());
+        app.state::<LaunchState>()
+            .start_recording
+            .store(true, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Result<LaunchArgs, String> {
+        parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parses_profile_and_start_recording() {
+        assert_eq!(args(&[]), Ok(LaunchArgs::default()));
+        let expected = LaunchArgs {
+            profile: Some("work".to_string()),
+            start_recording: true,
+            demo: false,
+        };
+        assert_eq!(
+            args(&["--profile", "work", "--start-recording"]),
+            Ok(expected.clone())
+        );
+        assert_eq!(args(&["--start-recording", "--profile=work"]), Ok(expected));
+        assert_eq!(args(&["--demo"]).map(|l| l.demo), Ok(true));
+        // Arguments the OS or the dev server add are skipped
+        assert_eq!(
+            args(&["-psn_0_12345", "--profiles", "--profile", "home"]).map(|l| l.profile),
+            Ok(Some("home".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_profile_needs_a_name() {
+        assert!(args(&["--profile"]).is_err());
+        assert!(args(&["--profile="]).is_err());
+        assert!(args(&["--profile", "--start-recording"]).is_err());
+    }
+}
@@ -1,32 +1,236 @@
+mod a11y;
+mod actions;
 mod api;
 mod audio;
+mod brief;
+mod capabilities;
+mod capture_limit;
+mod cli;
 mod commands;
-mod sse;
+mod context;
+mod conversations;
+mod corrections;
+mod countdown;
+mod cues;
+mod demo;
+mod denoise;
+mod device_monitor;
+mod diagnostics;
+mod dictation;
+#[cfg(test)]
+mod dsp_golden;
+mod duplicates;
+mod effects;
+mod error;
+mod events;
+mod failed_sends;
+mod feedback;
+mod file_transcription;
+mod history;
+mod hotkey;
+mod http_log;
+mod idle;
+mod ipc_client;
+#[cfg(test)]
+mod ipc_snapshots;
+mod journal;
+mod keymap;
+mod keywords;
+mod launch;
+mod live_upload;
+mod macros;
+mod mic_level;
+mod mic_test;
+mod mic_usage;
+mod persist;
+mod playback;
+mod privacy;
+mod quality;
+mod quick_talk;
+mod recordings;
+mod remote;
+mod resend;
+mod response_cache;
+mod response_routes;
+mod routing;
+mod schedule;
+mod schema;
+mod segments;
+mod selfcheck;
+mod session;
 mod settings;
+mod shutdown;
+mod sse;
+mod storage;
+mod stt;
+mod tasks;
+#[cfg(test)]
+mod test_support;
+mod trigger;
+mod tts;
+mod turns;
 mod types;
+mod unread;
+mod vad;
+mod voiceprint;
+mod wake_word;
+mod watchdog;
+mod watcher;
+mod wav_info;
+mod waveform;
+mod webhooks;
+
+use std::time::Duration;
 
 use tauri::{
-    menu::{Menu, MenuItem},
-    tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
-    Manager, WebviewUrl, WebviewWindowBuilder,
     image::Image,
+    menu::{CheckMenuItem, Menu, MenuItem, Submenu},
+    tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
+    Manager, RunEvent, WebviewUrl, WebviewWindowBuilder,
 };
 
+use crate::tasks::Restart;
+
+/// Entry point of the headless `openclaw-voice` command-line tool; returns the exit code
+pub fn run_cli() -> i32 {
+    cli::run(std::env::args().skip(1).collect())
+}
+
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_positioner::init())
         .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .plugin(tauri_plugin_store::Builder::default().build())
         .manage(audio::AudioState::new())
+        .manage(session::SessionState::new())
+        .manage(context::ContextState::new())
+        .manage(failed_sends::FailedSendState::new())
+        .manage(duplicates::DuplicateState::new())
+        .manage(journal::JournalState::new())
+        .manage(file_transcription::FileTranscriptionState::new())
+        .manage(quality::QualityState::new())
+        .manage(quick_talk::QuickTalkState::new())
+        .manage(response_cache::ResponseCacheState::new())
+        .manage(watchdog::WatchdogState::new())
+        .manage(stt::SttState::new())
+        .manage(launch::LaunchState::new())
+        .manage(tts::TtsState::new())
+        .manage(demo::DemoState::new())
+        .manage(unread::UnreadState::new())
+        .manage(keywords::KeywordState::new())
+        .manage(schedule::ScheduleState::new())
+        .manage(segments::SegmentState::new())
+        .manage(corrections::CorrectionState::new())
+        .manage(device_monitor::DeviceMonitorState::new())
+        .manage(storage::StorageState::new())
+        .manage(recordings::RecordingArchiveState::new())
+        .manage(resend::LastAudioState::new())
+        .manage(vad::VadState::new())
+        .manage(dictation::DictationState::new())
+        .manage(effects::EffectsState::new())
+        .manage(webhooks::WebhookState::new())
+        .manage(response_routes::ResponseRouteState::new())
+        .manage(countdown::CountdownState::new())
+        .manage(cues::CueState::new())
+        .manage(brief::BriefState::new())
+        .manage(capabilities::CapabilityState::new())
+        .manage(playback::PlaybackState::new())
+        .manage(idle::PresenceState::new())
+        .manage(macros::MacroState::new())
+        .manage(a11y::A11yState::new())
+        .manage(wake_word::WakeWordState::new())
+        .manage(hotkey::HotkeyState::new())
+        .manage(privacy::PrivacyState::new())
+        .manage(live_upload::LiveUploadState::new())
+        .manage(routing::RoutingState::new())
+        .manage(http_log::HttpLog::new())
+        .manage(shutdown::ShutdownState::new())
+        .manage(tasks::TaskState::new())
+        .manage(mic_usage::MicUsageState::new())
+        .manage(voiceprint::VoiceprintState::new())
+        .manage(remote::RemoteControlState::new())
+        .manage(events::EventFilterState::new())
+        .manage(events::EventLog::new(
+            types::AppSettings::default().event_log_capacity,
+        ))
+        .manage(trigger::TriggerGate::new(Duration::from_millis(
+            types::AppSettings::default().trigger_cooldown_ms,
+        )))
         .invoke_handler(tauri::generate_handler![
             commands::list_audio_devices,
+            commands::list_audio_hosts,
+            commands::get_device_capabilities,
             commands::start_recording,
+            commands::cancel_countdown,
             commands::stop_and_send,
+            commands::start_dictation,
+            commands::stop_dictation,
+            commands::pause_recording,
+            commands::resume_recording,
+            commands::start_voiceprint_enrollment,
+            commands::finish_voiceprint_enrollment,
+            commands::clear_voiceprint,
+            commands::get_voiceprint_status,
+            commands::enqueue_audio,
+            commands::skip_playback,
+            commands::stop_all_playback,
+            commands::resume_playback,
+            commands::play_audio,
+            commands::stop_playback,
+            commands::get_playback_status,
+            commands::set_playback_volume,
             commands::create_session,
+            commands::restore_last_session,
             commands::test_connection,
+            commands::diagnose_connection,
+            commands::get_gateway_capabilities,
+            commands::probe_gateway_capabilities,
             commands::load_settings,
             commands::save_settings,
+            commands::describe_hotkey,
+            commands::set_popup_pinned,
+            commands::get_listening_pause,
+            commands::get_system_a11y_prefs,
+            commands::set_privacy_mode,
+            commands::get_privacy_mode,
+            commands::get_event_log,
+            commands::get_last_turn,
+            commands::get_sessions_overview,
+            commands::get_session_transcript,
+            commands::search_remote_history,
+            commands::get_remote_session,
+            commands::submit_feedback,
+            commands::show_more_of_reply,
+            commands::learn_correction,
+            commands::list_corrections,
+            commands::delete_correction,
+            commands::list_failed_sends,
+            commands::retry_failed,
+            commands::resend_last_audio,
+            commands::discard_failed,
+            commands::list_recordings,
+            commands::delete_recording,
+            commands::transcribe_file,
+            commands::cancel_file_transcription,
+            commands::tail_http_log,
+            commands::run_self_check,
+            commands::test_microphone,
+            commands::get_storage_usage,
+            commands::get_remote_pairing,
+            commands::reset_remote_pairing,
             commands::open_settings_window,
+            commands::open_history_window,
+            commands::open_conversation_window,
+            commands::focus_popup,
+            commands::scroll_history,
+            commands::invoke_action,
+            commands::run_macro,
+            commands::get_task_health,
+            commands::get_mic_usage_log,
+            commands::take_launch_recording,
+            commands::is_demo_mode,
+            commands::get_unread_count,
+            commands::mark_all_read,
             commands::quit_app,
         ])
         .setup(|app| {
@@ -37,18 +241,92 @@ pub fn run() {
             // On subsequent launches the permission is cached and returns instantly.
             audio::request_mic_permission();
 
-            let open_item = MenuItem::with_id(app, "open", "Open Voice Client", true, None::<&str>)?;
+            // Must run before anything opens the store, which caches its first load
+            match persist::recover_store(app.handle()) {
+                Ok(persist::Recovery::Intact) => {}
+                Ok(recovery) => {
+                    eprintln!("Settings file was corrupt: {recovery:?}");
+                    events::record_state(
+                        app.handle(),
+                        "settings-recovered",
+                        format!("{recovery:?}"),
+                    );
+                }
+                Err(e) => eprintln!("Failed to check settings file: {e}"),
+            }
+
+            voiceprint::load(app.handle(), &app.state::<voiceprint::VoiceprintState>());
+            if let Ok(settings) = settings::load_settings(app.handle()) {
+                settings::apply_settings(app.handle(), &settings);
+            }
+            // After settings, so entries past the configured TTL are dropped
+            response_cache::load(app.handle());
+            corrections::load(app.handle());
+            mic_usage::load(app.handle());
+            // After settings, so a profile given here isn't overwritten by the saved one
+            match launch::parse(std::env::args().skip(1)) {
+                Ok(launch) => launch::apply(app.handle(), launch),
+                Err(e) => eprintln!("Launch arguments ignored: {e}"),
+            }
+            // After settings too, so the storage limits apply
+            journal::recover(app.handle());
+            let handle = app.handle();
+            tasks::supervise(handle, "idle", Restart::OnPanic, idle::monitor);
+            tasks::supervise(handle, "a11y", Restart::OnPanic, a11y::monitor);
+            tasks::supervise(handle, "devices", Restart::OnPanic, device_monitor::monitor);
+            tasks::supervise(handle, "schedule", Restart::OnPanic, schedule::monitor);
+            tasks::supervise(handle, "watchdog", Restart::OnPanic, watchdog::monitor);
+            tasks::supervise(handle, "wake-word", Restart::OnPanic, wake_word::listen);
+            tasks::supervise(
+                handle,
+                "settings-watcher",
+                Restart::OnPanic,
+                watcher::watch_settings,
+            );
+            tasks::supervise(handle, webhooks::TASK, Restart::OnPanic, webhooks::worker);
+            tasks::supervise(
+                handle,
+                "signals",
+                Restart::Never,
+                shutdown::listen_for_signals,
+            );
+
+            let open_item =
+                MenuItem::with_id(app, "open", "Open Voice Client", true, None::<&str>)?;
+            let history_item = MenuItem::with_id(app, "history", "History", true, None::<&str>)?;
             let settings_item = MenuItem::with_id(app, "settings", "Settings", true, None::<&str>)?;
+            let privacy_item = CheckMenuItem::with_id(
+                app,
+                privacy::MENU_ID,
+                "Privacy Mode",
+                true,
+                false,
+                None::<&str>,
+            )?;
+            let macros_menu = Submenu::with_id(app, macros::MENU_ID, "Macros", false)?;
             let quit_item = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
 
-            let menu = Menu::with_items(app, &[&open_item, &settings_item, &quit_item])?;
+            let menu = Menu::with_items(
+                app,
+                &[
+                    &open_item,
+                    &history_item,
+                    &settings_item,
+                    &privacy_item,
+                    &macros_menu,
+                    &quit_item,
+                ],
+            )?;
+            if let Ok(mut item) = app.state::<privacy::PrivacyState>().menu_item.lock() {
+                *item = Some(privacy_item.clone());
+            }
+            macros::attach_menu(app.handle(), macros_menu);
 
-            let icon = Image::from_bytes(include_bytes!("../icons/tray-icon.png"))
-                .expect("failed to load tray icon");
-            let _tray = TrayIconBuilder::new()
+            let icon = Image::from_bytes(quick_talk::TRAY_ICON).expect("failed to load tray icon");
+            let _tray = TrayIconBuilder::with_id(quick_talk::TRAY_ID)
                 .icon(icon)
                 .icon_as_template(true)
-                .tooltip("OpenClaw Voice Client")
+                .tooltip(quick_talk::TRAY_TOOLTIP)
                 .menu(&menu)
                 .show_menu_on_left_click(false)
                 .on_menu_event(|app, event| match event.id().as_ref() {
@@ -59,33 +337,37 @@ pub fn run() {
                         }
                     }
                     "settings" => {
-                        if let Some(window) = app.get_webview_window("settings") {
-                            let _ = window.show();
-                            let _ = window.set_focus();
-                        } else {
-                            let _ = WebviewWindowBuilder::new(
-                                app,
-                                "settings",
-                                WebviewUrl::App("settings.html".into()),
-                            )
-                            .title("OpenClaw Settings")
-                            .inner_size(500.0, 600.0)
-                            .build();
-                        }
+                        let _ = actions::open_settings_window(app);
                     }
-                    "quit" => {
-                        app.exit(0);
+                    "history" => {
+                        let _ = actions::open_history_window(app);
                     }
-                    _ => {}
+                    privacy::MENU_ID => privacy::set(app, !privacy::is_enabled(app)),
+                    "quit" => shutdown::quit(app),
+                    id => macros::menu_clicked(app, id),
                 })
                 .on_tray_icon_event(|tray, event| {
-                    if let TrayIconEvent::Click {
-                        button: MouseButton::Left,
-                        button_state: MouseButtonState::Up,
-                        ..
-                    } = event
-                    {
-                        let app = tray.app_handle();
+                    // Lets the positioner place the popup next to the tray icon
+                    tauri_plugin_positioner::on_tray_event(tray.app_handle(), &event);
+                    let click = match event {
+                        TrayIconEvent::Click {
+                            button: MouseButton::Left,
+                            button_state: MouseButtonState::Up,
+                            ..
+                        } => quick_talk::TrayClick::Primary,
+                        TrayIconEvent::Click {
+                            button: MouseButton::Middle,
+                            button_state: MouseButtonState::Up,
+                            ..
+                        }
+                        | TrayIconEvent::DoubleClick {
+                            button: MouseButton::Left,
+                            ..
+                        } => quick_talk::TrayClick::Quick,
+                        _ => return,
+                    };
+                    let app = tray.app_handle();
+                    if !quick_talk::handle_click(app, click) {
                         if let Some(window) = app.get_webview_window("popup") {
                             if window.is_visible().unwrap_or(false) {
                                 let _ = window.hide();
@@ -98,21 +380,34 @@ pub fn run() {
                 })
                 .build(app)?;
 
-            let _popup = WebviewWindowBuilder::new(
-                app,
-                "popup",
-                WebviewUrl::App("popup.html".into()),
-            )
-            .title("OpenClaw Voice")
-            .inner_size(360.0, 600.0)
-            .decorations(false)
-            .skip_taskbar(true)
-            .visible(false)
-            .always_on_top(true)
-            .build()?;
+            let popup =
+                WebviewWindowBuilder::new(app, "popup", WebviewUrl::App("popup.html".into()))
+                    .title("OpenClaw Voice")
+                    .inner_size(360.0, 600.0)
+                    .decorations(false)
+                    .skip_taskbar(true)
+                    .visible(false)
+                    .always_on_top(true)
+                    .build()?;
+            // Answers that came in while it was hidden are read once it is focused
+            let popup_app = app.handle().clone();
+            popup.on_window_event(move |event| {
+                if let tauri::WindowEvent::Focused(true) = event {
+                    unread::mark_all_read(&popup_app);
+                }
+            });
+            if let Ok(settings) = settings::load_settings(app.handle()) {
+                let _ = actions::set_popup_pinned(app.handle(), settings.popup_pinned);
+            }
 
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            // Exits not started by quit_app (e.g. OS logout) still get the orderly shutdown
+            if let RunEvent::ExitRequested { .. } = event {
+                shutdown::run(app);
+            }
+        });
 }
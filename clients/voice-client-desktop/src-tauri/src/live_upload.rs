@@ -0,0 +1,310 @@
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures_util::stream;
+use reqwest::Body;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Manager};
+use tokio::sync::mpsc;
+
+use crate::api::{self, Gateway, Turn};
+use crate::audio::AudioState;
+use crate::error::AppError;
+use crate::session::SessionState;
+use crate::stt::SttState;
+use crate::types::{PreprocessStage, SttProviderKind};
+use crate::voiceprint::VoiceprintState;
+use crate::{context, demo, effects, events, settings};
+
+const PUMP_INTERVAL: Duration = Duration::from_millis(100);
+/// Chunks queued for a slow network before capture waits on it (about 6 s of audio)
+const QUEUE_CHUNKS: usize = 64;
+
+/// The sending end of a request body, shared by the pump and `finish`
+struct Pump {
+    /// Taken by `finish`; dropping it ends the body
+    tx: Option<mpsc::Sender<Vec<u8>>>,
+    /// Samples of the recording already queued, or skipped after being dropped at
+    /// the memory cap
+    sent: usize,
+}
+
+/// A turn posted to the gateway while it is still being recorded
+pub struct LiveUpload {
+    session_id: String,
+    pump: Arc<tokio::sync::Mutex<Pump>>,
+    request: JoinHandle<Result<(), AppError>>,
+}
+
+impl Drop for LiveUpload {
+    /// Cancelled or superseded: close the connection so the gateway drops the partial turn
+    fn drop(&mut self) {
+        self.request.abort();
+    }
+}
+
+/// Streaming mode: the recording is uploaded while it is captured, so the gateway can
+/// start transcribing before the speaker is done.
+pub struct LiveUploadState {
+    pub enabled: Arc<AtomicBool>,
+    pub current: Arc<Mutex<Option<LiveUpload>>>,
+}
+
+impl LiveUploadState {
+    pub fn new() -> Self {
+        Self {
+            enabled: Arc::new(AtomicBool::new(false)),
+            current: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn configure(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::SeqCst);
+    }
+}
+
+/// Header of a 16-bit mono WAV of unknown length. The sizes are the largest a WAV
+/// can hold, which is how streaming WAV is usually marked; readers stop at the end.
+pub fn streaming_wav_header(sample_rate: u32) -> Vec<u8> {
+    let mut header = Vec::with_capacity(44);
+    header.extend_from_slice(b"RIFF");
+    header.extend_from_slice(&u32::MAX.to_le_bytes());
+    header.extend_from_slice(b"WAVEfmt ");
+    header.extend_from_slice(&16u32.to_le_bytes());
+    header.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    header.extend_from_slice(&1u16.to_le_bytes()); // mono
+    header.extend_from_slice(&sample_rate.to_le_bytes());
+    header.extend_from_slice(&(sample_rate * 2).to_le_bytes()); // bytes per second
+    header.extend_from_slice(&2u16.to_le_bytes()); // bytes per frame
+    header.extend_from_slice(&16u16.to_le_bytes());
+    header.extend_from_slice(b"data");
+    header.extend_from_slice(&u32::MAX.to_le_bytes());
+    header
+}
+
+/// Samples as little-endian 16-bit PCM
+fn pcm16(samples: &[f32]) -> Vec<u8> {
+    samples
+        .iter()
+        .flat_map(|s| ((s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16).to_le_bytes())
+        .collect()
+}
+
+/// Why the recording has to be sent whole, if it does
+fn ineligible(app: &AppHandle, profile_name: &str) -> Option<&'static str> {
+    if demo::is_enabled(app) {
+        return Some("demo mode answers without the gateway");
+    }
+    let gateway_stt = app
+        .state::<SttState>()
+        .provider_for(profile_name)
+        .is_ok_and(|provider| provider.kind() == SttProviderKind::Gateway);
+    if !gateway_stt {
+        return Some("local transcription needs the whole recording");
+    }
+    let filtered = app
+        .state::<VoiceprintState>()
+        .enabled
+        .lock()
+        .is_ok_and(|enabled| *enabled);
+    if filtered {
+        return Some("the speaker filter needs the whole recording");
+    }
+    match app
+        .state::<AudioState>()
+        .preprocessing()
+        .needs_whole_recording()
+    {
+        Some(PreprocessStage::Denoise) => {
+            return Some("noise suppression needs the whole recording");
+        }
+        Some(PreprocessStage::Agc) => return Some("level control needs the whole recording"),
+        Some(_) => return Some("a high-pass after other stages needs the whole recording"),
+        None => {}
+    }
+    if effects::has_chain(app, profile_name) {
+        return Some("the profile's effects chain needs the whole recording");
+    }
+    None
+}
+
+/// Start posting the recording that just began to the active session, when streaming
+/// is on and nothing needs the whole recording first. `stop_and_send` finishes it.
+pub fn begin(app: &AppHandle) {
+    let state = app.state::<LiveUploadState>();
+    // Whatever was left over belongs to an earlier recording
+    if let Ok(mut current) = state.current.lock() {
+        current.take();
+    }
+    if !state.enabled.load(Ordering::SeqCst) {
+        return;
+    }
+    let Some(session) = app.state::<SessionState>().snapshot() else {
+        return;
+    };
+    if let Some(reason) = ineligible(app, &session.profile_name) {
+        events::record_state(app, "live-upload-skipped", reason);
+        return;
+    }
+    let Some(rate) = app
+        .state::<AudioState>()
+        .sample_rate
+        .lock()
+        .ok()
+        .and_then(|rate| *rate)
+    else {
+        return;
+    };
+    let settings = settings::load_settings(app).unwrap_or_default();
+
+    let (tx, rx) = mpsc::channel::<Vec<u8>>(QUEUE_CHUNKS);
+    let _ = tx.try_send(streaming_wav_header(rate));
+    let body = Body::wrap_stream(stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|chunk| (Ok::<_, io::Error>(chunk), rx))
+    }));
+    let pump = Arc::new(tokio::sync::Mutex::new(Pump {
+        tx: Some(tx),
+        sent: 0,
+    }));
+
+    let request = {
+        let app = app.clone();
+        let session = session.clone();
+        tauri::async_runtime::spawn(async move {
+            // Captured when recording started, since the headers go out now
+            let turn_context = context::finish_turn(&app).await;
+            let turn = Turn {
+                base_url: &session.gateway_url,
+                session_id: &session.session_id,
+                profile_name: &session.profile_name,
+                session_key: settings.session_key.as_deref(),
+                response_language: settings.response_language.as_deref(),
+                context: turn_context.as_ref(),
+            };
+            api::send_live_audio(&app, &Gateway::from_app(&app), &turn, body).await
+        })
+    };
+    spawn_pump(app, pump.clone());
+
+    if let Ok(mut current) = state.current.lock() {
+        *current = Some(LiveUpload {
+            session_id: session.session_id.clone(),
+            pump,
+            request,
+        });
+    }
+    events::record_state(
+        app,
+        "live-upload-started",
+        serde_json::json!({ "sessionId": session.session_id, "sampleRate": rate }),
+    );
+}
+
+/// Queue newly captured audio every few frames until the recording stops
+fn spawn_pump(app: &AppHandle, pump: Arc<tokio::sync::Mutex<Pump>>) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(PUMP_INTERVAL).await;
+            let mut pump = pump.lock().await;
+            let Some(tx) = pump.tx.clone() else {
+                return;
+            };
+            let audio = app.state::<AudioState>();
+            // Stopped: `finish` sends the rest, or the upload was dropped with the recording
+            if !audio.is_recording.load(Ordering::SeqCst) {
+                return;
+            }
+            let (end, chunk) = match audio.samples.lock() {
+                Ok(samples) => {
+                    // Audio dropped at the memory cap before it was sent is skipped
+                    let dropped = audio.dropped.load(Ordering::SeqCst);
+                    let start = pump.sent.max(dropped) - dropped;
+                    let fresh = samples.get(start..).unwrap_or_default();
+                    (dropped + samples.len(), pcm16(fresh))
+                }
+                Err(_) => return,
+            };
+            if end <= pump.sent {
+                continue;
+            }
+            // The request ended early; `finish` reports why
+            if tx.send(chunk).await.is_err() {
+                return;
+            }
+            pump.sent = end;
+        }
+    });
+}
+
+/// The upload running for `session_id`, if any. One for another session is cancelled.
+pub fn take(app: &AppHandle, session_id: &str) -> Option<LiveUpload> {
+    let upload = app.state::<LiveUploadState>().current.lock().ok()?.take()?;
+    (upload.session_id == session_id).then_some(upload)
+}
+
+/// Send what the pump hasn't yet of the finished `samples`, end the body and wait
+/// for the gateway's reply to stream in. `trimmed_start` is how much was cut from
+/// their start, leading silence or audio dropped at the memory cap, after the pump may
+/// already have sent it.
+pub async fn finish(
+    mut upload: LiveUpload,
+    samples: &[f32],
+    trimmed_start: usize,
+) -> Result<(), AppError> {
+    {
+        let mut pump = upload.pump.lock().await;
+        if let Some(tx) = pump.tx.take() {
+            let sent = pump.sent.saturating_sub(trimmed_start);
+            let tail = samples.get(sent..).unwrap_or_default();
+            if !tail.is_empty() {
+                let _ = tx.send(pcm16(tail)).await;
+            }
+        }
+    }
+    match (&mut upload.request).await {
+        Ok(result) => result,
+        Err(e) => Err(AppError::Other(format!("Upload task failed: {e}"))),
+    }
+}
+
+/// Drop the upload of a recording that was cancelled rather than sent
+pub fn abort(app: &AppHandle) {
+    if let Some(state) = app.try_state::<LiveUploadState>() {
+        if let Ok(mut current) = state.current.lock() {
+            current.take();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_streaming_header_is_16_bit_mono_pcm() {
+        let header = streaming_wav_header(16_000);
+        assert_eq!(header.len(), 44);
+        assert_eq!(&header[0..4], b"RIFF");
+        assert_eq!(&header[8..16], b"WAVEfmt ");
+        assert_eq!(u16::from_le_bytes([header[22], header[23]]), 1);
+        assert_eq!(
+            u32::from_le_bytes([header[24], header[25], header[26], header[27]]),
+            16_000
+        );
+        assert_eq!(u16::from_le_bytes([header[34], header[35]]), 16);
+        assert_eq!(&header[36..40], b"data");
+    }
+
+    #[test]
+    fn test_pcm16_clamps_and_scales() {
+        let bytes = pcm16(&[0.0, 1.0, -2.0]);
+        let values: Vec<i16> = bytes
+            .chunks_exact(2)
+            .map(|b| i16::from_le_bytes([b[0], b[1]]))
+            .collect();
+        assert_eq!(values, vec![0, i16::MAX, -i16::MAX]);
+    }
+}
@@ -0,0 +1,272 @@
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tauri::menu::{MenuItem, Submenu};
+use tauri::{AppHandle, Emitter, Manager, Wry};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+use crate::actions::{self, PopupAction};
+use crate::types::{AppSettings, Macro, MacroStep};
+use crate::{events, hotkey, privacy, settings};
+
+pub const MENU_ID: &str = "macros";
+/// Tray items running a macro are this followed by the macro's name
+pub const MENU_PREFIX: &str = "macro:";
+/// Longest pause a `wait` step may ask for
+const MAX_WAIT_MS: u64 = 60_000;
+
+/// The macros from settings, with the hotkeys and tray entries that run them
+pub struct MacroState {
+    pub macros: Arc<Mutex<Vec<Macro>>>,
+    /// Shortcuts registered for macros, replaced when the macros change
+    pub hotkeys: Arc<Mutex<Vec<Shortcut>>>,
+    /// The tray's Macros submenu, filled once the tray is built
+    pub menu: Arc<Mutex<Option<Submenu<Wry>>>>,
+}
+
+impl MacroState {
+    pub fn new() -> Self {
+        Self {
+            macros: Arc::new(Mutex::new(Vec::new())),
+            hotkeys: Arc::new(Mutex::new(Vec::new())),
+            menu: Arc::new(Mutex::new(None)),
+        }
+    }
+}
+
+/// Reject macros that couldn't run: unnamed or repeated names, no steps, unknown
+/// actions, unparseable hotkeys
+pub fn validate(macros: &[Macro]) -> Result<(), String> {
+    let mut names = HashSet::new();
+    for m in macros {
+        let name = m.name.trim();
+        if name.is_empty() {
+            return Err("Macros need a name".to_string());
+        }
+        if !names.insert(name) {
+            return Err(format!("Duplicate macro '{name}'"));
+        }
+        if m.steps.is_empty() {
+            return Err(format!("Macro '{name}' has no steps"));
+        }
+        if let Some(hotkey) = m.hotkey.as_deref().filter(|h| !h.is_empty()) {
+            hotkey::parse_hotkey(hotkey)?;
+        }
+        for step in &m.steps {
+            match step {
+                MacroStep::SwitchProfile { profile_name } if profile_name.trim().is_empty() => {
+                    return Err(format!("Macro '{name}' switches to an empty profile"));
+                }
+                MacroStep::Action { id } => {
+                    id.parse::<PopupAction>()
+                        .map_err(|e| format!("Macro '{name}': {e}"))?;
+                }
+                MacroStep::Wait { ms } if *ms > MAX_WAIT_MS => {
+                    return Err(format!("Macro '{name}' waits longer than {MAX_WAIT_MS} ms"));
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Take on the macros from settings, registering their hotkeys and listing them in
+/// the tray. Nothing changes if they are the same as before.
+pub fn configure(app: &AppHandle, macros: &[Macro]) {
+    let state = app.state::<MacroState>();
+    match state.macros.lock() {
+        Ok(mut current) if current.as_slice() != macros => *current = macros.to_vec(),
+        _ => return,
+    }
+    if let Err(e) = register_hotkeys(app, &state, macros) {
+        eprintln!("Macro hotkeys not registered: {e}");
+    }
+    refresh_menu(app, &state);
+}
+
+/// Keep the tray's Macros submenu and list the macros in it
+pub fn attach_menu(app: &AppHandle, menu: Submenu<Wry>) {
+    let state = app.state::<MacroState>();
+    if let Ok(mut current) = state.menu.lock() {
+        *current = Some(menu);
+    }
+    refresh_menu(app, &state);
+}
+
+fn refresh_menu(app: &AppHandle, state: &MacroState) {
+    let Ok(menu) = state.menu.lock() else {
+        return;
+    };
+    let Some(menu) = menu.as_ref() else {
+        return;
+    };
+    for item in menu.items().unwrap_or_default() {
+        let _ = menu.remove(&item);
+    }
+    let names: Vec<String> = state
+        .macros
+        .lock()
+        .map(|macros| macros.iter().map(|m| m.name.clone()).collect())
+        .unwrap_or_default();
+    for name in &names {
+        let id = format!("{MENU_PREFIX}{name}");
+        if let Ok(item) = MenuItem::with_id(app, id, name, true, None::<&str>) {
+            let _ = menu.append(&item);
+        }
+    }
+    let _ = menu.set_enabled(!names.is_empty());
+}
+
+fn register_hotkeys(app: &AppHandle, state: &MacroState, macros: &[Macro]) -> Result<(), String> {
+    let mut registered = state
+        .hotkeys
+        .lock()
+        .map_err(|e| format!("Lock error: {e}"))?;
+    let shortcuts = app.global_shortcut();
+    for shortcut in registered.drain(..) {
+        let _ = shortcuts.unregister(shortcut);
+    }
+    for m in macros {
+        let Some(hotkey) = m.hotkey.as_deref().filter(|h| !h.is_empty()) else {
+            continue;
+        };
+        let shortcut = hotkey::parse_hotkey(hotkey)?;
+        let name = m.name.clone();
+        shortcuts
+            .on_shortcut(shortcut, move |app, _shortcut, event| {
+                if event.state() == ShortcutState::Pressed {
+                    spawn_run(app.clone(), name.clone());
+                }
+            })
+            .map_err(|e| format!("Failed to register '{hotkey}' for macro '{}': {e}", m.name))?;
+        registered.push(shortcut);
+    }
+    Ok(())
+}
+
+/// Run a macro picked from the tray menu; other menu ids are ignored
+pub fn menu_clicked(app: &AppHandle, id: &str) {
+    if let Some(name) = id.strip_prefix(MENU_PREFIX) {
+        spawn_run(app.clone(), name.to_string());
+    }
+}
+
+/// Run a macro from a hotkey or the tray, where nobody waits for the result; a failure
+/// shows up as "macro-failed"
+fn spawn_run(app: AppHandle, name: String) {
+    tauri::async_runtime::spawn(async move {
+        let _ = run(&app, &name).await;
+    });
+}
+
+/// Run the steps of the macro called `name` in order, stopping at the first that fails
+pub async fn run(app: &AppHandle, name: &str) -> Result<(), String> {
+    let found = app
+        .state::<MacroState>()
+        .macros
+        .lock()
+        .map_err(|e| e.to_string())?
+        .iter()
+        .find(|m| m.name == name)
+        .cloned()
+        .ok_or_else(|| format!("No macro named '{name}'"))?;
+
+    events::record_state(app, "macro-started", serde_json::json!({ "name": name }));
+    for (index, step) in found.steps.iter().enumerate() {
+        if let Err(e) = run_step(app, step).await {
+            let error = format!("Macro '{name}' stopped at step {}: {e}", index + 1);
+            events::record_state(
+                app,
+                "macro-failed",
+                serde_json::json!({ "name": name, "step": index + 1, "error": e }),
+            );
+            let _ = app.emit("macro-failed", &error);
+            return Err(error);
+        }
+    }
+    events::record_state(app, "macro-finished", serde_json::json!({ "name": name }));
+    Ok(())
+}
+
+async fn run_step(app: &AppHandle, step: &MacroStep) -> Result<(), String> {
+    match step {
+        MacroStep::SwitchProfile { profile_name } => update_settings(app, |settings| {
+            settings.profile_name = profile_name.trim().to_string();
+        }),
+        MacroStep::MeetingMode { enabled } => {
+            update_settings(app, |settings| settings.meeting_mode = *enabled)
+        }
+        MacroStep::PrivacyMode { enabled } => {
+            privacy::set(app, *enabled);
+            Ok(())
+        }
+        MacroStep::Action { id } => actions::invoke_action(app, id),
+        MacroStep::Wait { ms } => {
+            tokio::time::sleep(Duration::from_millis(*ms)).await;
+            Ok(())
+        }
+    }
+}
+
+/// Change a setting as Settings would: saved, applied and announced to the windows
+fn update_settings(app: &AppHandle, change: impl FnOnce(&mut AppSettings)) -> Result<(), String> {
+    let mut current = settings::load_settings(app)?;
+    change(&mut current);
+    settings::save_settings(app, &current)?;
+    settings::apply_settings(app, &current);
+    let _ = app.emit("settings-changed", &current);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn standup() -> Macro {
+        Macro {
+            name: "standup".to_string(),
+            hotkey: Some("Control+Alt+KeyS".to_string()),
+            steps: vec![
+                MacroStep::SwitchProfile {
+                    profile_name: "team".to_string(),
+                },
+                MacroStep::Action {
+                    id: "new-session".to_string(),
+                },
+                MacroStep::MeetingMode { enabled: true },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(validate(&[standup()]).is_ok());
+        assert!(validate(&[standup(), standup()]).is_err());
+        let unknown_action = Macro {
+            steps: vec![MacroStep::Action {
+                id: "make-coffee".to_string(),
+            }],
+            ..standup()
+        };
+        assert!(validate(&[unknown_action]).is_err());
+        let bad_hotkey = Macro {
+            hotkey: Some("Control+Nonsense".to_string()),
+            ..standup()
+        };
+        assert!(validate(&[bad_hotkey]).is_err());
+        let empty = Macro {
+            steps: Vec::new(),
+            ..standup()
+        };
+        assert!(validate(&[empty]).is_err());
+        let long_wait = Macro {
+            steps: vec![MacroStep::Wait {
+                ms: MAX_WAIT_MS + 1,
+            }],
+            ..standup()
+        };
+        assert!(validate(&[long_wait]).is_err());
+    }
+}
@@ -0,0 +1,54 @@
+use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::audio::{AudioState, SILENCE_DB};
+use crate::events;
+use crate::types::{MicClipping, MicLevel};
+
+/// About 20 updates a second, enough for a meter to look live
+const INTERVAL: Duration = Duration::from_millis(50);
+
+/// Clipping is reported at most this often, so a loud passage is one warning
+const CLIPPING_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Emit "mic-level" while the recording that just started runs, for the popup's level
+/// meter, and a silent reading once it ends so the meter drops back.
+/// Too frequent for the event log, so it isn't recorded there.
+/// A second in which samples clipped is also reported, as "mic-clipping".
+pub fn watch(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut clipping_checked = Instant::now();
+        loop {
+            tokio::time::sleep(INTERVAL).await;
+            let audio = app.state::<AudioState>();
+            let silent = MicLevel {
+                rms_db: SILENCE_DB,
+                peak_db: SILENCE_DB,
+            };
+            if !audio.is_recording.load(Ordering::SeqCst) {
+                let _ = app.emit("mic-level", &silent);
+                return;
+            }
+            // Nothing is captured while paused, so the meter drops back
+            if audio.is_paused.load(Ordering::SeqCst) {
+                let _ = app.emit("mic-level", &silent);
+                continue;
+            }
+            if let Some(level) = audio.level.take() {
+                let _ = app.emit("mic-level", &level);
+            }
+            if clipping_checked.elapsed() >= CLIPPING_INTERVAL {
+                clipping_checked = Instant::now();
+                let percent = audio.level.take_clipping().unwrap_or(0.0);
+                if percent > 0.0 {
+                    let clipping = MicClipping { percent };
+                    let _ = app.emit("mic-clipping", &clipping);
+                    events::record_state(&app, "mic-clipping", clipping);
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,119 @@
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use tauri::{AppHandle, Manager};
+
+use crate::audio::{self, AudioState};
+use crate::events;
+use crate::mic_usage;
+use crate::playback::{self, PlaybackState};
+use crate::types::{MicSource, MicTestReport};
+
+const TEST_DURATION: Duration = Duration::from_secs(3);
+/// A peak below this is a muted microphone or one the OS feeds silence, not a quiet room
+const SILENCE_PEAK_DB: f32 = -60.0;
+
+/// Levels of a recording; `device` is left for the caller to fill in
+fn measure(samples: &[f32], sample_rate: u32) -> MicTestReport {
+    let peak = samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+    let sum: f64 = samples.iter().map(|&s| f64::from(s) * f64::from(s)).sum();
+    let rms = match samples.len() {
+        0 => 0.0,
+        count => (sum / count as f64).sqrt() as f32,
+    };
+    let clipped_samples = samples
+        .iter()
+        .filter(|s| s.abs() >= audio::CLIP_LEVEL)
+        .count() as u64;
+    let peak_dbfs = audio::to_dbfs(peak);
+    MicTestReport {
+        device: None,
+        duration_ms: (samples.len() as u64 * 1000)
+            .checked_div(u64::from(sample_rate))
+            .unwrap_or(0),
+        peak_dbfs,
+        rms_dbfs: audio::to_dbfs(rms),
+        clipped_samples,
+        clipping: clipped_samples > 0,
+        silence: peak_dbfs < SILENCE_PEAK_DB,
+    }
+}
+
+/// Record from `device_id`, keeping the audio host and `input_channel` as recordings
+/// do, and measure it, returning the audio too so it can be played back
+fn record(
+    app: &AppHandle,
+    host: Option<String>,
+    device_id: Option<&str>,
+    input_channel: Option<u16>,
+) -> Result<(Vec<f32>, u32, MicTestReport), String> {
+    let probe = audio::probe_input(host, device_id, input_channel, TEST_DURATION, |probe| {
+        probe.mark_in_use(mic_usage::opened(
+            app,
+            MicSource::MicTest,
+            None,
+            probe.device_name(),
+        ));
+    })?;
+    let mut report = measure(&probe.samples, probe.sample_rate);
+    report.device = probe.device;
+    Ok((probe.samples, probe.sample_rate, report))
+}
+
+/// Record a few seconds from `device_id` (the default microphone if None), play it
+/// back on the output device and report its levels. Nothing is sent to the gateway.
+pub async fn test_microphone(
+    app: &AppHandle,
+    device_id: Option<String>,
+) -> Result<MicTestReport, String> {
+    let audio = app.state::<AudioState>();
+    // The recording already has the device open
+    if audio.is_recording.load(Ordering::SeqCst) {
+        return Err("Stop recording before testing the microphone".to_string());
+    }
+    let device_id = device_id.filter(|id| !id.is_empty());
+    let input_channel = audio.input_channel.lock().ok().and_then(|c| *c);
+    let host = audio.host_name();
+    let handle = app.clone();
+    let (samples, sample_rate, report) = tauri::async_runtime::spawn_blocking(move || {
+        record(&handle, host, device_id.as_deref(), input_channel)
+    })
+    .await
+    .map_err(|e| format!("Microphone test failed: {e}"))??;
+
+    events::record_state(app, "mic-test", &report);
+    if !samples.is_empty() {
+        let state = app.state::<PlaybackState>();
+        if let Err(e) = playback::enqueue_samples(app, &state, &samples, sample_rate) {
+            eprintln!("Microphone test not played back: {e}");
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_levels_flag_clipping_and_silence() {
+        let clipped: Vec<f32> = (0..16_000)
+            .map(|i| if i % 100 == 0 { 1.0 } else { 0.1 })
+            .collect();
+        let report = measure(&clipped, 16_000);
+        assert_eq!(report.duration_ms, 1000);
+        assert_eq!(report.clipped_samples, 160);
+        assert!(report.clipping);
+        assert!(!report.silence);
+        assert_eq!(report.peak_dbfs, 0.0);
+
+        let quiet = measure(&[0.0001; 8_000], 16_000);
+        assert!(quiet.silence);
+        assert!(!quiet.clipping);
+        assert!((quiet.rms_dbfs + 80.0).abs() < 0.01);
+
+        let empty = measure(&[], 0);
+        assert_eq!(empty.duration_ms, 0);
+        assert_eq!(empty.peak_dbfs, audio::SILENCE_DB);
+    }
+}
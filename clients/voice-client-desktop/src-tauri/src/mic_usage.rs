@@ -0,0 +1,200 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::types::{MicSource, MicUsage, TriggerSource};
+use crate::{events, persist};
+
+pub const MIC_USAGE_FILE: &str = "mic-usage.json";
+/// Oldest entries are dropped past this
+const MAX_ENTRIES: usize = 1000;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct UsageLog {
+    next_id: u64,
+    entries: VecDeque<MicUsage>,
+}
+
+impl UsageLog {
+    fn open(&mut self, mut usage: MicUsage) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        usage.id = id;
+        self.entries.push_back(usage);
+        while self.entries.len() > MAX_ENTRIES {
+            self.entries.pop_front();
+        }
+        id
+    }
+
+    fn close(&mut self, id: u64, stopped_at_ms: u64) -> bool {
+        match self.entries.iter_mut().rev().find(|entry| entry.id == id) {
+            Some(entry) if entry.stopped_at_ms.is_none() => {
+                entry.stopped_at_ms = Some(stopped_at_ms);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Every time the microphone was opened, kept across restarts
+pub struct MicUsageState {
+    log: Arc<Mutex<UsageLog>>,
+}
+
+impl MicUsageState {
+    pub fn new() -> Self {
+        Self {
+            log: Arc::new(Mutex::new(UsageLog::default())),
+        }
+    }
+}
+
+/// The microphone is open for as long as this is held; dropping it notes the stop
+pub struct InUse {
+    app: AppHandle,
+    id: u64,
+}
+
+impl Drop for InUse {
+    fn drop(&mut self) {
+        let state = self.app.state::<MicUsageState>();
+        let Ok(mut log) = state.log.lock() else {
+            return;
+        };
+        if log.close(self.id, now_ms()) {
+            events::record_state(
+                &self.app,
+                "mic-closed",
+                serde_json::json!({ "id": self.id }),
+            );
+            save(&self.app, &log);
+        }
+    }
+}
+
+fn log_path(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(MIC_USAGE_FILE))
+        .map_err(|e| format!("Failed to resolve app data dir: {e}"))
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+fn save(app: &AppHandle, log: &UsageLog) {
+    let result = log_path(app).and_then(|path| {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+        }
+        let bytes = serde_json::to_vec_pretty(log)
+            .map_err(|e| format!("Failed to serialize the microphone log: {e}"))?;
+        persist::write_atomic(&path, &bytes)
+    });
+    if let Err(e) = result {
+        eprintln!("Microphone usage not saved: {e}");
+    }
+}
+
+/// Load the log kept in earlier runs
+pub fn load(app: &AppHandle) {
+    let Some(log) = log_path(app)
+        .ok()
+        .and_then(|path| fs::read(path).ok())
+        .and_then(|bytes| serde_json::from_slice::<UsageLog>(&bytes).ok())
+    else {
+        return;
+    };
+    if let Ok(mut current) = app.state::<MicUsageState>().log.lock() {
+        *current = log;
+    }
+}
+
+/// Note that `source` just opened the microphone. Logged in privacy mode too: this
+/// is the record of when the app was listening, not of what it heard.
+pub fn opened(
+    app: &AppHandle,
+    source: MicSource,
+    trigger: Option<TriggerSource>,
+    device: Option<String>,
+) -> InUse {
+    let usage = MicUsage {
+        id: 0,
+        source,
+        trigger,
+        device,
+        started_at_ms: now_ms(),
+        stopped_at_ms: None,
+    };
+    events::record_state(app, "mic-opened", &usage);
+    let state = app.state::<MicUsageState>();
+    let id = match state.log.lock() {
+        Ok(mut log) => {
+            let id = log.open(usage);
+            save(app, &log);
+            id
+        }
+        Err(_) => u64::MAX,
+    };
+    InUse {
+        app: app.clone(),
+        id,
+    }
+}
+
+/// The log, newest first
+pub fn list(app: &AppHandle) -> Vec<MicUsage> {
+    app.state::<MicUsageState>()
+        .log
+        .lock()
+        .map(|log| log.entries.iter().rev().cloned().collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(started_at_ms: u64) -> MicUsage {
+        MicUsage {
+            id: 0,
+            source: MicSource::Recording,
+            trigger: Some(TriggerSource::Hotkey),
+            device: None,
+            started_at_ms,
+            stopped_at_ms: None,
+        }
+    }
+
+    #[test]
+    fn test_open_and_close_entries() {
+        let mut log = UsageLog::default();
+        let first = log.open(usage(1));
+        let second = log.open(usage(2));
+        assert_ne!(first, second);
+        assert!(log.close(first, 10));
+        // Closing twice, or an entry that isn't there, changes nothing
+        assert!(!log.close(first, 20));
+        assert!(!log.close(99, 20));
+        assert_eq!(log.entries[0].stopped_at_ms, Some(10));
+        assert_eq!(log.entries[1].stopped_at_ms, None);
+
+        for i in 0..MAX_ENTRIES as u64 {
+            log.open(usage(i));
+        }
+        assert_eq!(log.entries.len(), MAX_ENTRIES);
+        assert!(!log.close(second, 30));
+    }
+}
@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde_json::Value;
+use tauri::Runtime;
+use tauri_plugin_store::{resolve_store_path, Store, StoreExt};
+
+pub const STORE_FILE: &str = "settings.json";
+
+/// Outcome of checking the settings file at startup
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Recovery {
+    /// File parsed (or doesn't exist yet) — nothing to do
+    Intact,
+    /// File was unreadable; the backup generation was restored
+    RestoredBackup,
+    /// File and backup were both unreadable; the app starts from defaults
+    Reset,
+}
+
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+fn parses(path: &Path) -> bool {
+    fs::read(path)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice::<HashMap<String, Value>>(&bytes).ok())
+        .is_some()
+}
+
+/// Ids that name files and windows: ASCII letters, digits, `-` and `_`. Anything else
+/// could point outside a directory. `what` names the thing the id is for in the error.
+pub fn check_id(id: &str, what: &str) -> Result<(), String> {
+    let allowed = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_';
+    if id.is_empty() || !id.chars().all(allowed) {
+        return Err(format!("Unknown {what} '{}'", id.escape_debug()));
+    }
+    Ok(())
+}
+
+/// Write `bytes` to `path` via a temp file + rename so a crash can never leave it truncated.
+/// The previous version is kept as `<path>.bak` if it was valid.
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create settings dir: {e}"))?;
+    }
+
+    let tmp = sibling(path, ".tmp");
+    {
+        let mut file =
+            fs::File::create(&tmp).map_err(|e| format!("Failed to write settings: {e}"))?;
+        file.write_all(bytes)
+            .map_err(|e| format!("Failed to write settings: {e}"))?;
+        file.sync_all()
+            .map_err(|e| format!("Failed to write settings: {e}"))?;
+    }
+
+    if parses(path) {
+        fs::copy(path, sibling(path, ".bak"))
+            .map_err(|e| format!("Failed to back up settings: {e}"))?;
+    }
+    fs::rename(&tmp, path).map_err(|e| format!("Failed to replace settings: {e}"))
+}
+
+/// Check the file at `path` parses; if not, move it aside and restore the backup
+pub fn recover_file(path: &Path) -> Recovery {
+    if !path.exists() || parses(path) {
+        return Recovery::Intact;
+    }
+
+    let _ = fs::rename(path, sibling(path, ".corrupt"));
+    let backup = sibling(path, ".bak");
+    if parses(&backup) && fs::copy(&backup, path).is_ok() {
+        Recovery::RestoredBackup
+    } else {
+        Recovery::Reset
+    }
+}
+
+/// Repair the settings file before the store first loads it.
+/// Must run before any `open_store` call, since the store caches whatever it loads first.
+pub fn recover_store<R: Runtime>(app: &tauri::AppHandle<R>) -> Result<Recovery, String> {
+    let path = resolve_store_path(app, STORE_FILE)
+        .map_err(|e| format!("Failed to resolve settings path: {e}"))?;
+    Ok(recover_file(&path))
+}
+
+/// Open the settings store. Auto-save is disabled: the plugin's writer isn't atomic,
+/// so every write goes through `save_store` instead.
+pub fn open_store<R: Runtime>(app: &tauri::AppHandle<R>) -> Result<Arc<Store<R>>, String> {
+    app.store_builder(STORE_FILE)
+        .disable_auto_save()
+        .build()
+        .map_err(|e| format!("Failed to open store: {e}"))
+}
+
+/// Atomically write the store's current contents to disk
+pub fn save_store<R: Runtime>(app: &tauri::AppHandle<R>, store: &Store<R>) -> Result<(), String> {
+    let path = resolve_store_path(app, STORE_FILE)
+        .map_err(|e| format!("Failed to resolve settings path: {e}"))?;
+    let entries: HashMap<String, Value> = store.entries().into_iter().collect();
+    let bytes = serde_json::to_vec_pretty(&entries)
+        .map_err(|e| format!("Failed to serialize settings: {e}"))?;
+    write_atomic(&path, &bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    #[test]
+    fn test_ids_cannot_escape_a_directory() {
+        assert!(check_id("voice-1700000000000-abc_123", "session").is_ok());
+        assert!(check_id("1712345678901-2", "failed send").is_ok());
+        assert_eq!(
+            check_id("../settings", "recording"),
+            Err("Unknown recording '../settings'".to_string())
+        );
+        assert!(check_id("a/b", "session").is_err());
+        assert!(check_id("a.json", "session").is_err());
+        assert!(check_id("", "session").is_err());
+    }
+
+    #[test]
+    fn test_write_atomic_keeps_previous_generation() {
+        let dir = TempDir::new("persist-backup");
+        let path = dir.join("settings.json");
+
+        write_atomic(&path, br#"{"a":1}"#).unwrap();
+        write_atomic(&path, br#"{"a":2}"#).unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), r#"{"a":2}"#);
+        assert_eq!(
+            fs::read_to_string(sibling(&path, ".bak")).unwrap(),
+            r#"{"a":1}"#
+        );
+        assert!(!sibling(&path, ".tmp").exists());
+    }
+
+    #[test]
+    fn test_recover_restores_backup() {
+        let dir = TempDir::new("persist-restore");
+        let path = dir.join("settings.json");
+        write_atomic(&path, br#"{"a":1}"#).unwrap();
+        write_atomic(&path, br#"{"a":2}"#).unwrap();
+        fs::write(&path, b"{\"a\":").unwrap();
+
+        assert_eq!(recover_file(&path), Recovery::RestoredBackup);
+        assert_eq!(fs::read_to_string(&path).unwrap(), r#"{"a":1}"#);
+        assert!(sibling(&path, ".corrupt").exists());
+    }
+
+    #[test]
+    fn test_recover_without_backup_resets() {
+        let dir = TempDir::new("persist-reset");
+        let path = dir.join("settings.json");
+        fs::write(&path, b"not json").unwrap();
+
+        assert_eq!(recover_file(&path), Recovery::Reset);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_recover_missing_file_is_intact() {
+        let dir = TempDir::new("persist-missing");
+        assert_eq!(recover_file(&dir.join("settings.json")), Recovery::Intact);
+    }
+}
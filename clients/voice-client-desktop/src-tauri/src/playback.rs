@@ -0,0 +1,778 @@
+use std::collections::VecDeque;
+use std::io::Cursor;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, SampleFormat, SizedSample, Stream, StreamConfig};
+use hound::{SampleFormat as HoundSampleFormat, WavReader};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::api::{self, Gateway};
+use crate::audio::{self, AudioState, DuplexLink};
+use crate::tts::TtsState;
+use crate::types::PlaybackStatus;
+use crate::{events, settings};
+
+/// Gain on replies ducked under an open microphone, about -14 dB
+const DUCKED_GAIN: f32 = 0.2;
+
+/// A decoded mono clip at the output device's sample rate
+#[derive(Debug, Clone)]
+pub struct Clip {
+    pub id: u64,
+    pub samples: Vec<f32>,
+}
+
+/// Ordered queue of clips waiting to be spoken.
+/// The output callback pulls from here; commands only ever mutate the queue.
+#[derive(Debug)]
+pub struct PlaybackQueue {
+    pending: VecDeque<Clip>,
+    current: Option<(Clip, usize)>,
+    paused: bool,
+    /// A recording barged in: replies are held until `end_barge_in`
+    barged_in: bool,
+    next_id: u64,
+    /// Short sound mixed over whatever plays, even while paused
+    cue: Option<(Vec<f32>, usize)>,
+    volume: f32,
+    /// Turned down while recording, with `HalfDuplex::DuckPlayback`
+    ducked: bool,
+}
+
+impl Default for PlaybackQueue {
+    fn default() -> Self {
+        Self {
+            pending: VecDeque::new(),
+            current: None,
+            paused: false,
+            barged_in: false,
+            next_id: 0,
+            cue: None,
+            volume: 1.0,
+            ducked: false,
+        }
+    }
+}
+
+impl PlaybackQueue {
+    pub fn enqueue(&mut self, samples: Vec<f32>) -> u64 {
+        self.next_id += 1;
+        let id = self.next_id;
+        self.pending.push_back(Clip { id, samples });
+        // A reply arriving mid-recording waits for it rather than playing over the mic
+        if self.barged_in {
+            self.paused = true;
+        }
+        id
+    }
+
+    /// Drop the clip that is currently playing; the next one starts on the following callback
+    pub fn skip(&mut self) {
+        self.current = None;
+    }
+
+    /// Drop everything — current clip and all queued replies
+    pub fn stop_all(&mut self) {
+        self.current = None;
+        self.pending.clear();
+        self.paused = false;
+        self.barged_in = false;
+    }
+
+    /// Pause replies being spoken or waiting to be, and hold those queued until the
+    /// recording is over. An idle queue only pauses once something is queued.
+    pub fn barge_in(&mut self) {
+        self.barged_in = true;
+        if self.current.is_some() || !self.pending.is_empty() {
+            self.paused = true;
+        }
+    }
+
+    /// Once the recording that barged in is over: a recording that was sent supersedes
+    /// the replies it spoke over, one that was dropped lets them carry on
+    pub fn end_barge_in(&mut self, sent: bool) {
+        if !self.barged_in {
+            return;
+        }
+        if sent {
+            self.stop_all();
+        } else {
+            self.resume();
+        }
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+        self.barged_in = false;
+    }
+
+    /// Clamped to 0.0 (muted) to 1.0
+    pub fn set_volume(&mut self, volume: f32) {
+        self.volume = if volume.is_finite() {
+            volume.clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+    }
+
+    /// Replies only; cues stay at full volume
+    pub fn set_ducked(&mut self, ducked: bool) {
+        self.ducked = ducked;
+    }
+
+    /// Whether a reply is coming out of the speakers
+    pub fn is_speaking(&self) -> bool {
+        !self.paused && self.current.is_some()
+    }
+
+    /// Play `samples` right away on top of the queue, replacing any cue still sounding
+    pub fn play_cue(&mut self, samples: Vec<f32>) {
+        self.cue = Some((samples, 0));
+    }
+
+    pub fn status(&self) -> PlaybackStatus {
+        PlaybackStatus {
+            playing: self.is_speaking(),
+            paused: self.paused,
+            current_clip_id: self.current.as_ref().map(|(clip, _)| clip.id),
+            queued: self.pending.len(),
+            volume: self.volume,
+        }
+    }
+
+    /// Fill an interleaved output buffer, advancing through the queue.
+    /// Silence is written while paused or when nothing is queued.
+    pub fn fill(&mut self, output: &mut [f32], channels: usize) {
+        if channels == 0 {
+            return;
+        }
+
+        let gain = if self.ducked { DUCKED_GAIN } else { 1.0 };
+        for frame in output.chunks_mut(channels) {
+            let reply = if self.paused {
+                0.0
+            } else {
+                self.next_sample() * gain
+            };
+            let sample = (reply + self.next_cue_sample()) * self.volume;
+            for out in frame.iter_mut() {
+                *out = sample;
+            }
+        }
+    }
+
+    fn next_cue_sample(&mut self) -> f32 {
+        let Some((samples, position)) = self.cue.as_mut() else {
+            return 0.0;
+        };
+        match samples.get(*position) {
+            Some(&sample) => {
+                *position += 1;
+                sample
+            }
+            None => {
+                self.cue = None;
+                0.0
+            }
+        }
+    }
+
+    fn next_sample(&mut self) -> f32 {
+        loop {
+            if let Some((clip, position)) = self.current.as_mut() {
+                if let Some(&sample) = clip.samples.get(*position) {
+                    *position += 1;
+                    return sample;
+                }
+                self.current = None;
+            }
+
+            match self.pending.pop_front() {
+                Some(clip) => self.current = Some((clip, 0)),
+                None => return 0.0,
+            }
+        }
+    }
+}
+
+pub struct PlaybackState {
+    pub queue: Arc<Mutex<PlaybackQueue>>,
+    pub stream: Arc<Mutex<Option<Stream>>>,
+    pub output_rate: Arc<Mutex<Option<u32>>>,
+    /// Speak the synthesized audio the gateway sends with replies
+    pub speak_replies: Arc<AtomicBool>,
+}
+
+// SAFETY: same reasoning as AudioState — the cpal output stream is only touched from
+// command handlers behind the mutex; the output callback only locks `queue`.
+unsafe impl Send for PlaybackState {}
+unsafe impl Sync for PlaybackState {}
+
+impl PlaybackState {
+    pub fn new() -> Self {
+        Self {
+            queue: Arc::new(Mutex::new(PlaybackQueue::default())),
+            stream: Arc::new(Mutex::new(None)),
+            output_rate: Arc::new(Mutex::new(None)),
+            speak_replies: Arc::new(AtomicBool::new(true)),
+        }
+    }
+
+    pub fn configure(&self, speak_replies: bool, volume: f32) {
+        self.speak_replies.store(speak_replies, Ordering::SeqCst);
+        if let Ok(mut queue) = self.queue.lock() {
+            queue.set_volume(volume);
+        }
+    }
+
+    /// Apply `change` to the queue and broadcast the resulting status
+    pub fn update<F>(&self, app: &AppHandle, change: F) -> Result<PlaybackStatus, String>
+    where
+        F: FnOnce(&mut PlaybackQueue),
+    {
+        let status = {
+            let mut queue = self.queue.lock().map_err(|e| e.to_string())?;
+            change(&mut queue);
+            queue.status()
+        };
+        events::record_state(app, "playback-state", &status);
+        let _ = app.emit("playback-state", &status);
+        Ok(status)
+    }
+
+    /// Drop queued clips and release the output device
+    pub fn close(&self) {
+        if let Ok(mut queue) = self.queue.lock() {
+            queue.stop_all();
+        }
+        if let Ok(mut stream) = self.stream.lock() {
+            stream.take();
+        }
+        if let Ok(mut rate) = self.output_rate.lock() {
+            *rate = None;
+        }
+    }
+}
+
+/// Open the default output device once and keep it running; the queue decides what plays.
+/// Each callback also applies the half-duplex policy: replies are ducked while recording
+/// if asked, and the microphone is told when a reply is audible.
+fn ensure_output_stream(app: &AppHandle, state: &PlaybackState) -> Result<u32, String> {
+    let mut stream_guard = state.stream.lock().map_err(|e| e.to_string())?;
+    if stream_guard.is_some() {
+        if let Some(rate) = *state.output_rate.lock().map_err(|e| e.to_string())? {
+            return Ok(rate);
+        }
+    }
+
+    let host = audio::open_host(app.state::<AudioState>().host_name().as_deref());
+    let device = host
+        .default_output_device()
+        .ok_or_else(|| "No default output device available".to_string())?;
+    let supported_config = device.default_output_config().map_err(|e| e.to_string())?;
+    let sample_rate = supported_config.sample_rate();
+    let sample_format = supported_config.sample_format();
+    let config: StreamConfig = supported_config.into();
+
+    let queue = state.queue.clone();
+    let duplex = app.state::<AudioState>().duplex_link();
+    let stream = open_output_stream(&device, &config, sample_format, queue, duplex)?;
+    stream.play().map_err(|e| e.to_string())?;
+
+    *stream_guard = Some(stream);
+    *state.output_rate.lock().map_err(|e| e.to_string())? = Some(sample_rate);
+    Ok(sample_rate)
+}
+
+/// Build a playback stream in the device's own sample format
+fn open_output_stream(
+    device: &cpal::Device,
+    config: &StreamConfig,
+    sample_format: SampleFormat,
+    queue: Arc<Mutex<PlaybackQueue>>,
+    duplex: DuplexLink,
+) -> Result<Stream, String> {
+    match sample_format {
+        SampleFormat::I8 => build_output_stream::<i8>(device, config, queue, duplex),
+        SampleFormat::I16 => build_output_stream::<i16>(device, config, queue, duplex),
+        SampleFormat::I24 => build_output_stream::<cpal::I24>(device, config, queue, duplex),
+        SampleFormat::I32 => build_output_stream::<i32>(device, config, queue, duplex),
+        SampleFormat::I64 => build_output_stream::<i64>(device, config, queue, duplex),
+        SampleFormat::U8 => build_output_stream::<u8>(device, config, queue, duplex),
+        SampleFormat::U16 => build_output_stream::<u16>(device, config, queue, duplex),
+        SampleFormat::U24 => build_output_stream::<cpal::U24>(device, config, queue, duplex),
+        SampleFormat::U32 => build_output_stream::<u32>(device, config, queue, duplex),
+        SampleFormat::U64 => build_output_stream::<u64>(device, config, queue, duplex),
+        SampleFormat::F32 => build_output_stream::<f32>(device, config, queue, duplex),
+        SampleFormat::F64 => build_output_stream::<f64>(device, config, queue, duplex),
+        SampleFormat::DsdU8 | SampleFormat::DsdU16 | SampleFormat::DsdU32 => {
+            Err("DSD sample formats are not supported".to_string())
+        }
+        _ => Err(format!("Unsupported sample format '{sample_format}'")),
+    }
+}
+
+/// The queue mixes in f32; other formats are converted from a scratch buffer that only
+/// grows until it fits the device's callback size
+fn build_output_stream<T>(
+    device: &cpal::Device,
+    config: &StreamConfig,
+    queue: Arc<Mutex<PlaybackQueue>>,
+    duplex: DuplexLink,
+) -> Result<Stream, String>
+where
+    T: SizedSample + FromSample<f32>,
+{
+    let channels = config.channels as usize;
+    let mut mixed = Vec::new();
+    device
+        .build_output_stream(
+            config,
+            move |data: &mut [T], _| {
+                mixed.resize(data.len(), 0.0);
+                match queue.lock() {
+                    Ok(mut queue) => {
+                        queue.set_ducked(duplex.duck_playback());
+                        queue.fill(&mut mixed, channels);
+                        duplex.set_reply_audible(queue.is_speaking());
+                    }
+                    Err(_) => mixed.fill(0.0),
+                }
+                for (out, &sample) in data.iter_mut().zip(&mixed) {
+                    *out = T::from_sample(sample);
+                }
+            },
+            |err| eprintln!("Playback stream error: {err}"),
+            None,
+        )
+        .map_err(|e| e.to_string())
+}
+
+/// Undo `barge_in` once the recording that interrupted a reply has ended
+pub fn end_barge_in(app: &AppHandle, sent: bool) {
+    let Some(state) = app.try_state::<PlaybackState>() else {
+        return;
+    };
+    let barged_in = state.queue.lock().map(|q| q.barged_in).unwrap_or(false);
+    if barged_in {
+        let _ = state.update(app, |queue| queue.end_barge_in(sent));
+    }
+}
+
+/// Decode WAV bytes, convert to the output rate and append to the playback queue
+pub fn enqueue_wav(
+    app: &AppHandle,
+    state: &PlaybackState,
+    wav_bytes: &[u8],
+) -> Result<PlaybackStatus, String> {
+    let (samples, source_rate) = decode_wav_mono(wav_bytes)?;
+    enqueue_samples(app, state, &samples, source_rate)
+}
+
+/// Convert mono samples to the output rate and append them to the playback queue
+pub fn enqueue_samples(
+    app: &AppHandle,
+    state: &PlaybackState,
+    samples: &[f32],
+    source_rate: u32,
+) -> Result<PlaybackStatus, String> {
+    let output_rate = ensure_output_stream(app, state)?;
+    let samples = resample_linear(samples, source_rate, output_rate);
+    state.update(app, |queue| {
+        queue.enqueue(samples);
+    })
+}
+
+/// The bytes of base64 audio, which may come as a `data:` URI
+pub fn decode_base64_audio(data: &str) -> Result<Vec<u8>, String> {
+    let data = data.trim();
+    let encoded = match data.strip_prefix("data:") {
+        Some(uri) => uri.split_once(',').map_or("", |(_, payload)| payload),
+        None => data,
+    };
+    STANDARD
+        .decode(encoded)
+        .map_err(|e| format!("Invalid base64 audio: {e}"))
+}
+
+/// Queue WAV audio given as base64 `data` or by `url`, as an `audio` event carries it
+pub async fn play_audio(
+    app: &AppHandle,
+    data: Option<&str>,
+    url: Option<&str>,
+) -> Result<PlaybackStatus, String> {
+    let wav_bytes = match (data, url) {
+        (Some(data), _) => decode_base64_audio(data)?,
+        (None, Some(url)) => {
+            let base_url = settings::load_settings(app)?.gateway_url;
+            api::fetch_audio(&Gateway::from_app(app), &base_url, url)
+                .await
+                .map_err(|e| e.to_string())?
+        }
+        (None, None) => return Err("No audio given: pass base64 data or a URL".to_string()),
+    };
+    enqueue_wav(app, &app.state::<PlaybackState>(), &wav_bytes)
+}
+
+/// Speak the audio streamed with a reply, unless spoken replies are off. Inline audio is
+/// queued at once so it keeps its place; audio behind a URL is queued once downloaded.
+pub fn speak_reply(app: &AppHandle, data: Option<String>, url: Option<String>) {
+    let Some(state) = app.try_state::<PlaybackState>() else {
+        return;
+    };
+    if !state.speak_replies.load(Ordering::SeqCst) {
+        return;
+    }
+    if let Some(data) = data {
+        let queued = decode_base64_audio(&data).and_then(|wav| enqueue_wav(app, &state, &wav));
+        if let Err(e) = queued {
+            report_speech_failure(app, &e);
+        }
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = play_audio(&app, None, url.as_deref()).await {
+            report_speech_failure(&app, &e);
+        }
+    });
+}
+
+/// Speak `text` with the profile's local voice: for what the gateway can't voice, such
+/// as the app's own error messages and answers replayed from the cache. Does nothing
+/// for profiles left to the gateway, or while spoken replies are off.
+pub fn speak_text(app: &AppHandle, profile_name: &str, text: &str) {
+    let Some(state) = app.try_state::<PlaybackState>() else {
+        return;
+    };
+    if !state.speak_replies.load(Ordering::SeqCst) || text.trim().is_empty() {
+        return;
+    }
+    let Some(piper) = app
+        .try_state::<TtsState>()
+        .and_then(|tts| tts.piper_for(profile_name))
+    else {
+        return;
+    };
+    events::record_state(
+        app,
+        "tts-started",
+        serde_json::json!({ "chars": text.chars().count() }),
+    );
+    let app = app.clone();
+    let text = text.to_string();
+    tauri::async_runtime::spawn(async move {
+        let synthesized =
+            tauri::async_runtime::spawn_blocking(move || piper.synthesize(&text)).await;
+        let queued = match synthesized {
+            Ok(Ok(wav)) => enqueue_wav(&app, &app.state::<PlaybackState>(), &wav).map(|_| ()),
+            Ok(Err(e)) => Err(e.to_string()),
+            Err(e) => Err(format!("Speech synthesis task failed: {e}")),
+        };
+        if let Err(e) = queued {
+            report_speech_failure(&app, &e);
+        }
+    });
+}
+
+fn report_speech_failure(app: &AppHandle, error: &str) {
+    eprintln!("Failed to speak reply: {error}");
+    events::record_state(app, "reply-audio-failed", error);
+}
+
+/// A sine beep with 5 ms fades so it doesn't click
+pub fn tone(frequency_hz: f32, duration_ms: u32, sample_rate: u32) -> Vec<f32> {
+    let len = (sample_rate as usize * duration_ms as usize) / 1000;
+    let fade = (sample_rate as usize / 200).clamp(1, len.max(1));
+    (0..len)
+        .map(|i| {
+            let envelope = (i.min(len - 1 - i) as f32 / fade as f32).min(1.0);
+            let phase = std::f32::consts::TAU * frequency_hz * i as f32 / sample_rate as f32;
+            0.3 * envelope * phase.sin()
+        })
+        .collect()
+}
+
+/// Beep on the output device without disturbing queued replies
+pub fn play_cue(
+    app: &AppHandle,
+    state: &PlaybackState,
+    frequency_hz: f32,
+    duration_ms: u32,
+) -> Result<(), String> {
+    play_tones(app, state, &[(frequency_hz, duration_ms)])
+}
+
+/// Play beeps one after another as a single cue; a frequency of 0 is a pause
+pub fn play_tones(
+    app: &AppHandle,
+    state: &PlaybackState,
+    tones: &[(f32, u32)],
+) -> Result<(), String> {
+    let output_rate = ensure_output_stream(app, state)?;
+    let samples = tones
+        .iter()
+        .flat_map(|&(frequency_hz, duration_ms)| tone(frequency_hz, duration_ms, output_rate))
+        .collect();
+    state
+        .queue
+        .lock()
+        .map_err(|e| e.to_string())?
+        .play_cue(samples);
+    Ok(())
+}
+
+/// Decode a WAV file to mono f32 samples, averaging all channels
+pub fn decode_wav_mono(wav_bytes: &[u8]) -> Result<(Vec<f32>, u32), String> {
+    let reader =
+        WavReader::new(Cursor::new(wav_bytes)).map_err(|e| format!("Invalid WAV data: {e}"))?;
+    let spec = reader.spec();
+    let channels = spec.channels.max(1) as usize;
+
+    let interleaved: Vec<f32> = match spec.sample_format {
+        HoundSampleFormat::Float => reader
+            .into_samples::<f32>()
+            .collect::<Result<_, _>>()
+            .map_err(|e| format!("Invalid WAV data: {e}"))?,
+        HoundSampleFormat::Int => {
+            let scale = (1_i64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .into_samples::<i32>()
+                .map(|sample| sample.map(|value| value as f32 / scale))
+                .collect::<Result<_, _>>()
+                .map_err(|e| format!("Invalid WAV data: {e}"))?
+        }
+    };
+
+    let mono = interleaved
+        .chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+        .collect();
+    Ok((mono, spec.sample_rate))
+}
+
+/// Linear-interpolation resampler — adequate for speech playback
+pub fn resample_linear(samples: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
+    if from_rate == to_rate || samples.is_empty() || from_rate == 0 {
+        return samples.to_vec();
+    }
+
+    let ratio = from_rate as f64 / to_rate as f64;
+    let out_len = (samples.len() as f64 / ratio).floor() as usize;
+    (0..out_len)
+        .map(|i| {
+            let position = i as f64 * ratio;
+            let index = position.floor() as usize;
+            let frac = (position - index as f64) as f32;
+            let a = samples[index];
+            let b = samples.get(index + 1).copied().unwrap_or(a);
+            a + (b - a) * frac
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fill_plays_queued_clips_in_order() {
+        let mut queue = PlaybackQueue::default();
+        queue.enqueue(vec![0.1, 0.2]);
+        queue.enqueue(vec![0.3]);
+
+        let mut out = [0.0; 4];
+        queue.fill(&mut out, 1);
+        assert_eq!(out, [0.1, 0.2, 0.3, 0.0]);
+        assert!(!queue.status().playing);
+    }
+
+    #[test]
+    fn test_cue_sounds_over_a_paused_reply() {
+        let mut queue = PlaybackQueue::default();
+        queue.enqueue(vec![0.5, 0.5, 0.5]);
+        let mut out = [0.0; 1];
+        queue.fill(&mut out, 1);
+        queue.barge_in();
+        queue.play_cue(vec![0.25, 0.25]);
+
+        let mut out = [0.0; 3];
+        queue.fill(&mut out, 1);
+        assert_eq!(out, [0.25, 0.25, 0.0]);
+        assert!(queue.status().paused);
+    }
+
+    #[test]
+    fn test_fill_duplicates_mono_across_channels() {
+        let mut queue = PlaybackQueue::default();
+        queue.enqueue(vec![0.5, -0.5]);
+
+        let mut out = [0.0; 4];
+        queue.fill(&mut out, 2);
+        assert_eq!(out, [0.5, 0.5, -0.5, -0.5]);
+    }
+
+    #[test]
+    fn test_skip_moves_to_next_clip() {
+        let mut queue = PlaybackQueue::default();
+        queue.enqueue(vec![0.1, 0.1, 0.1]);
+        let second = queue.enqueue(vec![0.9, 0.9]);
+
+        let mut out = [0.0; 1];
+        queue.fill(&mut out, 1);
+        queue.skip();
+        queue.fill(&mut out, 1);
+        assert_eq!(out, [0.9]);
+        assert_eq!(queue.status().current_clip_id, Some(second));
+    }
+
+    #[test]
+    fn test_barge_in_emits_silence_and_keeps_position() {
+        let mut queue = PlaybackQueue::default();
+        queue.enqueue(vec![0.1, 0.2, 0.3]);
+
+        let mut out = [0.0; 1];
+        queue.fill(&mut out, 1);
+        queue.barge_in();
+        let mut silent = [1.0; 2];
+        queue.fill(&mut silent, 1);
+        assert_eq!(silent, [0.0, 0.0]);
+
+        queue.resume();
+        let mut rest = [0.0; 2];
+        queue.fill(&mut rest, 1);
+        assert_eq!(rest, [0.2, 0.3]);
+    }
+
+    #[test]
+    fn test_barge_in_ends_with_the_recording() {
+        let mut queue = PlaybackQueue::default();
+        queue.enqueue(vec![0.1, 0.2]);
+        queue.enqueue(vec![0.3]);
+        let mut out = [0.0; 1];
+        queue.fill(&mut out, 1);
+
+        // A dropped recording lets the reply carry on where it was
+        queue.barge_in();
+        queue.end_barge_in(false);
+        queue.fill(&mut out, 1);
+        assert_eq!(out, [0.2]);
+
+        // A sent one supersedes it and everything queued behind it
+        queue.barge_in();
+        queue.end_barge_in(true);
+        let status = queue.status();
+        assert!(!status.paused);
+        assert_eq!((status.current_clip_id, status.queued), (None, 0));
+    }
+
+    #[test]
+    fn test_end_barge_in_does_nothing_once_resumed() {
+        let mut queue = PlaybackQueue::default();
+        queue.enqueue(vec![0.1, 0.2]);
+        let mut out = [0.0; 1];
+        queue.fill(&mut out, 1);
+        queue.barge_in();
+        queue.resume();
+        queue.end_barge_in(true);
+        assert_eq!(queue.status().queued, 0);
+        assert!(queue.status().current_clip_id.is_some());
+    }
+
+    #[test]
+    fn test_barge_in_holds_replies_not_yet_playing() {
+        // Queued but not yet picked up by the output callback
+        let mut queue = PlaybackQueue::default();
+        queue.enqueue(vec![0.4]);
+        queue.barge_in();
+        let mut out = [1.0; 1];
+        queue.fill(&mut out, 1);
+        assert_eq!(out, [0.0]);
+        assert!(queue.status().paused);
+    }
+
+    #[test]
+    fn test_replies_arriving_mid_recording_wait_for_it() {
+        let mut queue = PlaybackQueue::default();
+        queue.barge_in();
+        assert!(!queue.status().paused);
+        queue.enqueue(vec![0.4]);
+
+        let mut out = [1.0; 1];
+        queue.fill(&mut out, 1);
+        assert_eq!(out, [0.0]);
+        queue.end_barge_in(false);
+        queue.fill(&mut out, 1);
+        assert_eq!(out, [0.4]);
+
+        // Once the recording is over, replies play straight away
+        queue.enqueue(vec![0.5]);
+        queue.fill(&mut out, 1);
+        assert_eq!(out, [0.5]);
+    }
+
+    #[test]
+    fn test_stop_all_clears_queue() {
+        let mut queue = PlaybackQueue::default();
+        queue.enqueue(vec![0.1; 10]);
+        queue.enqueue(vec![0.2; 10]);
+        let mut out = [0.0; 1];
+        queue.fill(&mut out, 1);
+
+        queue.stop_all();
+        let status = queue.status();
+        assert!(!status.playing);
+        assert_eq!(status.queued, 0);
+    }
+
+    #[test]
+    fn test_volume_scales_replies_and_cues() {
+        let mut queue = PlaybackQueue::default();
+        queue.set_volume(0.5);
+        queue.enqueue(vec![0.5, 0.5]);
+        queue.play_cue(vec![0.25]);
+
+        let mut out = [0.0; 2];
+        queue.fill(&mut out, 1);
+        assert_eq!(out, [0.375, 0.25]);
+
+        queue.set_volume(3.0);
+        assert_eq!(queue.status().volume, 1.0);
+        queue.set_volume(f32::NAN);
+        assert_eq!(queue.status().volume, 1.0);
+    }
+
+    #[test]
+    fn test_ducking_turns_down_replies_but_not_cues() {
+        let mut queue = PlaybackQueue::default();
+        queue.enqueue(vec![0.5, 0.5]);
+        queue.set_ducked(true);
+        queue.play_cue(vec![0.25]);
+
+        let mut out = [0.0; 2];
+        queue.fill(&mut out, 1);
+        assert_eq!(out, [0.5 * DUCKED_GAIN + 0.25, 0.5 * DUCKED_GAIN]);
+        assert!(queue.is_speaking());
+    }
+
+    #[test]
+    fn test_base64_audio_decodes_with_or_without_a_data_uri() {
+        assert_eq!(decode_base64_audio("UklGRg==").unwrap(), b"RIFF");
+        assert_eq!(
+            decode_base64_audio("data:audio/wav;base64,UklGRg==").unwrap(),
+            b"RIFF"
+        );
+        assert!(decode_base64_audio("not base64!").is_err());
+    }
+
+    #[test]
+    fn test_resample_linear_halves_length() {
+        let samples = vec![0.0, 1.0, 0.0, 1.0];
+        let out = resample_linear(&samples, 32_000, 16_000);
+        assert_eq!(out, vec![0.0, 0.0]);
+    }
+}
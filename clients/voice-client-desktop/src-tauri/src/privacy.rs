@@ -0,0 +1,90 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use serde_json::Value;
+use tauri::menu::CheckMenuItem;
+use tauri::{AppHandle, Emitter, Manager, Runtime, Wry};
+
+use crate::types::{PrivacyMode, VoiceEvent};
+use crate::{events, quick_talk};
+
+pub const MENU_ID: &str = "privacy";
+
+/// Privacy mode: while it is on nothing said is written anywhere — no history, no
+/// recordings kept for retry, no cached replies and no transcript text in the event
+/// log. It isn't saved, so every launch starts with it off.
+pub struct PrivacyState {
+    pub enabled: Arc<AtomicBool>,
+    /// The tray's check item, kept in step when the mode changes elsewhere
+    pub menu_item: Arc<Mutex<Option<CheckMenuItem<Wry>>>>,
+}
+
+impl PrivacyState {
+    pub fn new() -> Self {
+        Self {
+            enabled: Arc::new(AtomicBool::new(false)),
+            menu_item: Arc::new(Mutex::new(None)),
+        }
+    }
+}
+
+pub fn is_enabled<R: Runtime>(app: &AppHandle<R>) -> bool {
+    app.try_state::<PrivacyState>()
+        .is_some_and(|state| state.enabled.load(Ordering::SeqCst))
+}
+
+/// Turn privacy mode on or off, updating the tray and telling the windows
+pub fn set(app: &AppHandle, enabled: bool) {
+    let state = app.state::<PrivacyState>();
+    if let Ok(item) = state.menu_item.lock() {
+        if let Some(item) = item.as_ref() {
+            let _ = item.set_checked(enabled);
+        }
+    }
+    if state.enabled.swap(enabled, Ordering::SeqCst) == enabled {
+        return;
+    }
+    quick_talk::refresh_tray(app);
+    let mode = PrivacyMode { enabled };
+    events::record_state(app, "privacy-mode", &mode);
+    let _ = app.emit("privacy-mode", &mode);
+}
+
+/// A conversation event as the event log keeps it: without its words in privacy mode
+pub fn loggable(app: &AppHandle, event: &VoiceEvent) -> Value {
+    let value = serde_json::to_value(event).unwrap_or(Value::Null);
+    if is_enabled(app) {
+        redact(value)
+    } else {
+        value
+    }
+}
+
+fn redact(mut value: Value) -> Value {
+    if let Some(text) = value.get_mut("text") {
+        let chars = text.as_str().map_or(0, |text| text.chars().count());
+        *text = Value::String(format!("<{chars} characters hidden>"));
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_hides_only_the_words() {
+        let event = VoiceEvent::User {
+            text: "my bank pin is 1234".to_string(),
+            confidence: 0.9,
+            timestamp: "1".to_string(),
+        };
+        let value = redact(serde_json::to_value(&event).unwrap());
+        assert_eq!(value["text"], "<19 characters hidden>");
+        assert_eq!(value["type"], "user");
+        assert_eq!(value["confidence"], 0.9);
+
+        let status = serde_json::json!({ "type": "system", "status": "done" });
+        assert_eq!(redact(status.clone()), status);
+    }
+}
@@ -0,0 +1,395 @@
+use std::f32::consts::PI;
+use std::io::Cursor;
+use std::sync::{Arc, Mutex};
+
+use bytes::Bytes;
+use hound::{SampleFormat, WavSpec, WavWriter};
+
+use crate::audio;
+use crate::playback::resample_linear;
+use crate::types::QualityPreset;
+use crate::wav_info;
+
+/// A spare upload buffer bigger than this (about three minutes of 48 kHz float audio)
+/// is freed rather than kept for the next turn
+const MAX_SPARE_BYTES: usize = 32 * 1024 * 1024;
+
+/// How a preset maps onto the upload pipeline
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EncodeProfile {
+    /// Downsample to this rate if the device records faster (never upsampled)
+    pub max_sample_rate: Option<u32>,
+    /// 16-bit PCM instead of 32-bit float
+    pub pcm16: bool,
+    pub remove_dc: bool,
+    /// Cut rumble (desk thumps, HVAC) below this frequency
+    pub high_pass_hz: Option<f32>,
+    /// Scale so the loudest sample reaches this level
+    pub normalize_peak: Option<f32>,
+}
+
+impl QualityPreset {
+    pub fn profile(self) -> EncodeProfile {
+        match self {
+            // Speech recognisers don't use anything above 8 kHz
+            Self::VoiceSmall => EncodeProfile {
+                max_sample_rate: Some(16_000),
+                pcm16: true,
+                remove_dc: true,
+                high_pass_hz: Some(100.0),
+                normalize_peak: Some(0.9),
+            },
+            Self::Balanced => EncodeProfile {
+                max_sample_rate: Some(24_000),
+                pcm16: true,
+                remove_dc: true,
+                high_pass_hz: None,
+                normalize_peak: None,
+            },
+            // What the microphone delivered, untouched
+            Self::HighFidelity => EncodeProfile {
+                max_sample_rate: None,
+                pcm16: false,
+                remove_dc: false,
+                high_pass_hz: None,
+                normalize_peak: None,
+            },
+        }
+    }
+}
+
+/// The preset used when encoding recordings for upload
+pub struct QualityState {
+    pub preset: Arc<Mutex<QualityPreset>>,
+    /// The last upload's buffer, so the next turn encodes without allocating again
+    spare: Arc<Mutex<Vec<u8>>>,
+}
+
+impl QualityState {
+    pub fn new() -> Self {
+        Self {
+            preset: Arc::new(Mutex::new(QualityPreset::default())),
+            spare: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    pub fn set(&self, preset: QualityPreset) {
+        if let Ok(mut current) = self.preset.lock() {
+            *current = preset;
+        }
+    }
+
+    pub fn get(&self) -> QualityPreset {
+        self.preset.lock().map(|p| *p).unwrap_or_default()
+    }
+
+    /// A buffer to encode the next upload into, empty but possibly already allocated
+    pub fn take_buffer(&self) -> Vec<u8> {
+        self.spare
+            .lock()
+            .map(|mut spare| std::mem::take(&mut *spare))
+            .unwrap_or_default()
+    }
+
+    /// Keep an upload's buffer for the next turn. Only taken back once nothing else
+    /// (the request body, a retry copy) still shares it; larger than
+    /// `MAX_SPARE_BYTES` it is freed instead, so one long recording isn't held forever.
+    pub fn recycle(&self, wav: Bytes) {
+        let Ok(buffer) = wav.try_into_mut() else {
+            return;
+        };
+        let buffer = Vec::from(buffer);
+        if buffer.capacity() > MAX_SPARE_BYTES {
+            return;
+        }
+        if let Ok(mut spare) = self.spare.lock() {
+            if buffer.capacity() > spare.capacity() {
+                *spare = buffer;
+            }
+        }
+    }
+}
+
+/// Second-order IIR section (RBJ cookbook coefficients)
+pub struct Biquad {
+    b: [f32; 3],
+    a: [f32; 2],
+    x: [f32; 2],
+    y: [f32; 2],
+}
+
+impl Biquad {
+    pub fn new(cutoff_hz: f32, sample_rate: u32, high_pass: bool) -> Self {
+        let w0 = 2.0 * PI * cutoff_hz / sample_rate as f32;
+        let alpha = w0.sin() / (2.0 * std::f32::consts::FRAC_1_SQRT_2);
+        let cos = w0.cos();
+        let a0 = 1.0 + alpha;
+        let b = if high_pass {
+            [(1.0 + cos) / 2.0, -(1.0 + cos), (1.0 + cos) / 2.0]
+        } else {
+            [(1.0 - cos) / 2.0, 1.0 - cos, (1.0 - cos) / 2.0]
+        };
+        Self {
+            b: b.map(|v| v / a0),
+            a: [-2.0 * cos / a0, (1.0 - alpha) / a0],
+            x: [0.0; 2],
+            y: [0.0; 2],
+        }
+    }
+
+    pub fn process(&mut self, samples: &mut [f32]) {
+        for sample in samples {
+            let x0 = *sample;
+            let y0 = self.b[0] * x0 + self.b[1] * self.x[0] + self.b[2] * self.x[1]
+                - self.a[0] * self.y[0]
+                - self.a[1] * self.y[1];
+            self.x = [x0, self.x[0]];
+            self.y = [y0, self.y[0]];
+            *sample = y0;
+        }
+    }
+}
+
+/// Output of `process`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Processed {
+    pub samples: Vec<f32>,
+    pub sample_rate: u32,
+    /// Linear gain applied by normalisation (1.0 when none was)
+    pub gain: f32,
+    /// Filters and conversions that actually ran, e.g. `dc,highpass100,resample48000>16000`
+    pub dsp: Vec<String>,
+}
+
+/// Run the preset's DSP and rate conversion
+pub fn process(samples: &[f32], sample_rate: u32, profile: &EncodeProfile) -> Processed {
+    let mut samples = samples.to_vec();
+    let mut dsp = Vec::new();
+
+    if profile.remove_dc && !samples.is_empty() {
+        let mean = samples.iter().sum::<f32>() / samples.len() as f32;
+        samples.iter_mut().for_each(|s| *s -= mean);
+        dsp.push("dc".to_string());
+    }
+    if let Some(cutoff) = profile.high_pass_hz {
+        Biquad::new(cutoff, sample_rate, true).process(&mut samples);
+        dsp.push(format!("highpass{cutoff}"));
+    }
+
+    let mut rate = sample_rate;
+    if let Some(target) = profile
+        .max_sample_rate
+        .filter(|&target| target < sample_rate)
+    {
+        // Band-limit first so content above the new Nyquist doesn't alias into speech
+        Biquad::new(target as f32 * 0.45, sample_rate, false).process(&mut samples);
+        samples = resample_linear(&samples, sample_rate, target);
+        rate = target;
+        dsp.push(format!("resample{sample_rate}>{target}"));
+    }
+
+    let mut gain = 1.0;
+    if let Some(target_peak) = profile.normalize_peak {
+        let peak = samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+        // Leave near-silence alone rather than amplifying noise
+        if peak > 0.01 {
+            gain = target_peak / peak;
+            samples.iter_mut().for_each(|s| *s *= gain);
+            dsp.push(format!("normalize{target_peak}"));
+        }
+    }
+
+    Processed {
+        samples,
+        sample_rate: rate,
+        gain,
+        dsp,
+    }
+}
+
+/// The `key=value; ...` ICMT comment describing how a recording was produced
+fn describe(preset: QualityPreset, processed: &Processed, device: Option<&str>) -> String {
+    let format = if preset.profile().pcm16 {
+        "pcm16"
+    } else {
+        "float32"
+    };
+    let preset = serde_json::to_value(preset).unwrap_or_default();
+    let mut parts = vec![
+        format!("preset={}", preset.as_str().unwrap_or_default()),
+        format!("format={format}"),
+        format!("gain={:+.1}dB", 20.0 * processed.gain.log10()),
+        format!(
+            "dsp={}",
+            if processed.dsp.is_empty() {
+                "none".to_string()
+            } else {
+                processed.dsp.join(",")
+            }
+        ),
+    ];
+    if let Some(device) = device {
+        parts.push(format!("device={device}"));
+    }
+    parts.join("; ")
+}
+
+/// Encode a mono recording for upload according to `preset`. A LIST/INFO chunk records
+/// the client version, input device, applied gain and DSP so the file explains itself.
+pub fn encode(
+    samples: &[f32],
+    sample_rate: u32,
+    preset: QualityPreset,
+    device: Option<&str>,
+) -> Result<Vec<u8>, String> {
+    encode_into(Vec::new(), samples, sample_rate, preset, device)
+}
+
+/// `encode` into `buffer` (see `QualityState::take_buffer`), cleared first
+pub fn encode_into(
+    buffer: Vec<u8>,
+    samples: &[f32],
+    sample_rate: u32,
+    preset: QualityPreset,
+    device: Option<&str>,
+) -> Result<Vec<u8>, String> {
+    let profile = preset.profile();
+    let processed = process(samples, sample_rate, &profile);
+    let mut wav = if profile.pcm16 {
+        encode_pcm16(buffer, &processed.samples, processed.sample_rate)?
+    } else {
+        audio::encode_wav_into(buffer, &processed.samples, processed.sample_rate)?
+    };
+    wav_info::append(
+        &mut wav,
+        &[
+            (wav_info::SOFTWARE, wav_info::software()),
+            (wav_info::COMMENT, describe(preset, &processed, device)),
+        ],
+    );
+    Ok(wav)
+}
+
+fn encode_pcm16(mut buffer: Vec<u8>, samples: &[f32], sample_rate: u32) -> Result<Vec<u8>, String> {
+    buffer.clear();
+    buffer.reserve(44 + samples.len() * 2);
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+    let mut cursor = Cursor::new(buffer);
+    {
+        let mut writer = WavWriter::new(&mut cursor, spec).map_err(|err| err.to_string())?;
+        for &sample in samples {
+            let value = (sample.clamp(-1.0, 1.0) * i16::MAX as f32).round() as i16;
+            writer.write_sample(value).map_err(|err| err.to_string())?;
+        }
+        writer.finalize().map_err(|err| err.to_string())?;
+    }
+    Ok(cursor.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::playback::decode_wav_mono;
+
+    fn sine(freq: f32, rate: u32, secs: f32, amplitude: f32) -> Vec<f32> {
+        (0..(rate as f32 * secs) as usize)
+            .map(|i| amplitude * (2.0 * PI * freq * i as f32 / rate as f32).sin())
+            .collect()
+    }
+
+    #[test]
+    fn test_voice_small_is_16k_pcm16_and_normalized() {
+        let samples = sine(440.0, 48_000, 0.5, 0.2);
+        let wav = encode(&samples, 48_000, QualityPreset::VoiceSmall, None).unwrap();
+
+        let reader = hound::WavReader::new(Cursor::new(&wav)).unwrap();
+        assert_eq!(reader.spec().sample_rate, 16_000);
+        assert_eq!(reader.spec().bits_per_sample, 16);
+
+        let (decoded, _) = decode_wav_mono(&wav).unwrap();
+        let peak = decoded.iter().fold(0.0f32, |p, s| p.max(s.abs()));
+        assert!((peak - 0.9).abs() < 0.05, "peak {peak}");
+        // Roughly a third of the samples at a third of the rate
+        assert!((decoded.len() as i64 - 8_000).abs() < 10);
+    }
+
+    #[test]
+    fn test_high_fidelity_is_untouched() {
+        let samples = sine(440.0, 44_100, 0.1, 0.3);
+        let wav = encode(&samples, 44_100, QualityPreset::HighFidelity, None).unwrap();
+        let plain = audio::encode_wav(&samples, 44_100).unwrap();
+        // Same audio; only the INFO chunk is added after it
+        assert_eq!(wav[12..plain.len()], plain[12..]);
+        assert_eq!(&wav[plain.len()..plain.len() + 4], b"LIST");
+    }
+
+    #[test]
+    fn test_upload_buffer_is_reused_once_released() {
+        let state = QualityState::new();
+        let samples = sine(440.0, 16_000, 0.5, 0.3);
+        let preset = QualityPreset::HighFidelity;
+        let wav = encode_into(state.take_buffer(), &samples, 16_000, preset, None).unwrap();
+        let wav = Bytes::from(wav);
+        let len = wav.len();
+
+        // Still shared with the body being uploaded, so it can't be taken back
+        let body = wav.clone();
+        state.recycle(wav);
+        assert_eq!(state.take_buffer().capacity(), 0);
+
+        state.recycle(body);
+        let buffer = state.take_buffer();
+        assert!(buffer.capacity() >= len);
+        let again = encode_into(buffer, &samples, 16_000, preset, None).unwrap();
+        assert_eq!(again, encode(&samples, 16_000, preset, None).unwrap());
+    }
+
+    #[test]
+    fn test_comment_records_gain_dsp_and_device() {
+        let samples = sine(440.0, 48_000, 0.1, 0.45);
+        let processed = process(&samples, 48_000, &QualityPreset::VoiceSmall.profile());
+        // Filter overshoot makes the exact gain a little under 0.9 / 0.45
+        assert!(
+            (1.7..=2.0).contains(&processed.gain),
+            "gain {}",
+            processed.gain
+        );
+        let comment = describe(QualityPreset::VoiceSmall, &processed, Some("USB Mic"));
+        assert_eq!(
+            comment,
+            format!(
+                "preset=voiceSmall; format=pcm16; gain=+{:.1}dB; \
+                 dsp=dc,highpass100,resample48000>16000,normalize0.9; device=USB Mic",
+                20.0 * processed.gain.log10()
+            )
+        );
+    }
+
+    #[test]
+    fn test_low_rate_devices_are_not_upsampled() {
+        let processed = process(&[0.1; 800], 8_000, &QualityPreset::Balanced.profile());
+        assert_eq!(processed.sample_rate, 8_000);
+        assert_eq!(processed.samples.len(), 800);
+        assert!(processed
+            .dsp
+            .iter()
+            .all(|step| !step.starts_with("resample")));
+    }
+
+    #[test]
+    fn test_high_pass_removes_rumble() {
+        let peak_after = |freq: f32| {
+            let mut samples = sine(freq, 16_000, 1.0, 0.5);
+            Biquad::new(100.0, 16_000, true).process(&mut samples);
+            // Skip the filter's settling time
+            samples[8_000..].iter().fold(0.0f32, |p, s| p.max(s.abs()))
+        };
+        assert!(peak_after(20.0) < 0.05);
+        assert!(peak_after(1_000.0) > 0.45);
+    }
+}
@@ -0,0 +1,284 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tauri::{image::Image, AppHandle, Emitter, Manager};
+
+use crate::types::PushToTalkState;
+use crate::{events, privacy, unread};
+
+pub const TRAY_ID: &str = "main";
+pub const TRAY_ICON: &[u8] = include_bytes!("../icons/tray-icon.png");
+pub const TRAY_TOOLTIP: &str = "OpenClaw Voice Client";
+
+/// A double-click arrives with the clicks that make it up; ignore those trailing
+/// clicks so starting doesn't immediately send
+const SETTLE: Duration = Duration::from_millis(600);
+
+/// Tray clicks that matter to quick mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrayClick {
+    /// Plain left click — normally toggles the popup
+    Primary,
+    /// Double-click or middle-click — starts a quick recording
+    Quick,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Idle,
+    /// Asked the popup to record; waiting for it to start
+    Starting,
+    Recording,
+    /// Recording handed to the popup to send
+    Sending,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Not a quick-mode click; fall through to the usual tray behaviour
+    Default,
+    Ignore,
+    Start,
+    Stop,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct QuickTalk {
+    pub phase: Phase,
+    since: Instant,
+}
+
+impl QuickTalk {
+    fn new() -> Self {
+        Self {
+            phase: Phase::Idle,
+            since: Instant::now(),
+        }
+    }
+
+    pub fn on_click(&mut self, click: TrayClick, now: Instant) -> Action {
+        let settling = now.duration_since(self.since) < SETTLE;
+        let action = match (self.phase, click) {
+            (Phase::Starting | Phase::Recording, _) if settling => Action::Ignore,
+            (Phase::Recording, _) => Action::Stop,
+            (Phase::Idle | Phase::Starting, TrayClick::Quick) => Action::Start,
+            // The popup couldn't start recording; behave like a normal click again
+            (Phase::Starting, TrayClick::Primary) => {
+                self.phase = Phase::Idle;
+                Action::Default
+            }
+            // Still waiting on the previous reply
+            (Phase::Sending, TrayClick::Quick) => Action::Ignore,
+            (Phase::Idle | Phase::Sending, TrayClick::Primary) => Action::Default,
+        };
+        match action {
+            Action::Start => {
+                self.phase = Phase::Starting;
+                self.since = now;
+            }
+            Action::Stop => self.phase = Phase::Sending,
+            Action::Default | Action::Ignore => {}
+        }
+        action
+    }
+}
+
+/// "Tap tray to talk": record without opening the popup, send on the next tray click
+pub struct QuickTalkState {
+    pub quick_talk: Arc<Mutex<QuickTalk>>,
+}
+
+impl QuickTalkState {
+    pub fn new() -> Self {
+        Self {
+            quick_talk: Arc::new(Mutex::new(QuickTalk::new())),
+        }
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.quick_talk
+            .lock()
+            .map(|q| q.phase)
+            .unwrap_or(Phase::Idle)
+    }
+
+    fn advance(&self, from: Phase, to: Phase) -> bool {
+        self.quick_talk
+            .lock()
+            .map(|mut q| {
+                let matched = q.phase == from;
+                if matched {
+                    q.phase = to;
+                }
+                matched
+            })
+            .unwrap_or(false)
+    }
+}
+
+/// Red while a quick recording is running
+const RECORDING_TINT: [u8; 3] = [0xE5, 0x39, 0x35];
+/// Purple while privacy mode is on
+const PRIVATE_TINT: [u8; 3] = [0x8E, 0x44, 0xAD];
+
+/// The tray icon in a single colour
+fn tinted_icon([r, g, b]: [u8; 3]) -> Option<Image<'static>> {
+    let icon = Image::from_bytes(TRAY_ICON).ok()?;
+    let rgba = icon
+        .rgba()
+        .chunks_exact(4)
+        .flat_map(|px| [r, g, b, px[3]])
+        .collect();
+    Some(Image::new_owned(rgba, icon.width(), icon.height()))
+}
+
+fn paint_tray(app: &AppHandle, tint: Option<[u8; 3]>, tooltip: &str) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    let icon = match tint {
+        Some(tint) => tinted_icon(tint),
+        None => Image::from_bytes(TRAY_ICON).ok(),
+    };
+    let _ = tray.set_icon(icon);
+    // A template icon is drawn monochrome on macOS, which would hide the colour
+    let _ = tray.set_icon_as_template(tint.is_none());
+    let _ = tray.set_tooltip(Some(tooltip));
+}
+
+fn set_feedback(app: &AppHandle, phase: Phase) {
+    let private = privacy::is_enabled(app);
+    let tint = match phase {
+        Phase::Recording => Some(RECORDING_TINT),
+        _ if private => Some(PRIVATE_TINT),
+        _ => None,
+    };
+    let tooltip = match phase {
+        Phase::Recording => "Recording — click the tray icon to send",
+        Phase::Sending => "Sending…",
+        Phase::Idle | Phase::Starting if private => "Privacy mode — nothing is being saved",
+        Phase::Idle | Phase::Starting => TRAY_TOOLTIP,
+    };
+    // Answers waiting to be read show whatever the tray is doing
+    paint_tray(app, tint, &unread::tooltip(tooltip, unread::count(app)));
+}
+
+/// Redraw the tray for the current quick-mode phase, e.g. after privacy mode changed
+pub fn refresh_tray(app: &AppHandle) {
+    set_feedback(app, app.state::<QuickTalkState>().phase());
+}
+
+/// Turn the tray red while the push-to-talk hotkey is held, so a recording with the
+/// popup hidden still shows
+pub fn show_push_to_talk(app: &AppHandle, held: bool) {
+    if held {
+        paint_tray(
+            app,
+            Some(RECORDING_TINT),
+            "Recording — release the hotkey to send",
+        );
+    } else {
+        refresh_tray(app);
+    }
+}
+
+/// Handle a tray click; returns false when the usual tray behaviour should run
+pub fn handle_click(app: &AppHandle, click: TrayClick) -> bool {
+    let state = app.state::<QuickTalkState>();
+    let action = state
+        .quick_talk
+        .lock()
+        .map(|mut q| q.on_click(click, Instant::now()))
+        .unwrap_or(Action::Default);
+    match action {
+        Action::Default => return false,
+        Action::Ignore => return true,
+        Action::Start => {
+            if let Some(window) = app.get_webview_window("popup") {
+                let _ = window.hide();
+            }
+            let _ = app.emit_to("popup", "quick-talk", PushToTalkState::Pressed);
+        }
+        Action::Stop => {
+            set_feedback(app, Phase::Sending);
+            let _ = app.emit_to("popup", "quick-talk", PushToTalkState::Released);
+        }
+    }
+    events::record_state(app, "quick-talk", format!("{action:?}"));
+    true
+}
+
+/// The popup started the recording quick mode asked for
+pub fn recording_started(app: &AppHandle) {
+    if app
+        .state::<QuickTalkState>()
+        .advance(Phase::Starting, Phase::Recording)
+    {
+        set_feedback(app, Phase::Recording);
+    }
+}
+
+/// Back to idle after the popup stopped the recording some other way, or never sent
+/// the one quick mode stopped
+pub fn reset(app: &AppHandle) {
+    let state = app.state::<QuickTalkState>();
+    if state.advance(Phase::Recording, Phase::Idle) || state.advance(Phase::Sending, Phase::Idle) {
+        set_feedback(app, Phase::Idle);
+    }
+}
+
+/// Restores the tray once a quick-mode turn has been sent, however sending ends
+pub struct SendGuard<'a>(pub &'a AppHandle);
+
+impl Drop for SendGuard<'_> {
+    fn drop(&mut self) {
+        if self
+            .0
+            .state::<QuickTalkState>()
+            .advance(Phase::Sending, Phase::Idle)
+        {
+            set_feedback(self.0, Phase::Idle);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quick_click_starts_and_next_click_sends() {
+        let mut quick = QuickTalk::new();
+        let t0 = Instant::now();
+        assert_eq!(quick.on_click(TrayClick::Primary, t0), Action::Default);
+        assert_eq!(quick.on_click(TrayClick::Quick, t0), Action::Start);
+        quick.phase = Phase::Recording;
+        // The trailing click of the double-click
+        assert_eq!(
+            quick.on_click(TrayClick::Primary, t0 + Duration::from_millis(50)),
+            Action::Ignore
+        );
+        assert_eq!(
+            quick.on_click(TrayClick::Primary, t0 + Duration::from_secs(3)),
+            Action::Stop
+        );
+        assert_eq!(quick.phase, Phase::Sending);
+        assert_eq!(
+            quick.on_click(TrayClick::Quick, t0 + Duration::from_secs(4)),
+            Action::Ignore
+        );
+    }
+
+    #[test]
+    fn test_failed_start_falls_back_to_normal_clicks() {
+        let mut quick = QuickTalk::new();
+        let t0 = Instant::now();
+        quick.on_click(TrayClick::Quick, t0);
+        // The popup never reported recording
+        assert_eq!(
+            quick.on_click(TrayClick::Primary, t0 + Duration::from_secs(2)),
+            Action::Default
+        );
+        assert_eq!(quick.phase, Phase::Idle);
+    }
+}
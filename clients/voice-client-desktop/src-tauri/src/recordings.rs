@@ -0,0 +1,292 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::api::Turn;
+use crate::events;
+use crate::persist;
+use crate::privacy;
+use crate::storage;
+use crate::types::ArchivedRecording;
+
+pub const RECORDINGS_DIR: &str = "recordings";
+const DAY_MS: u64 = 24 * 60 * 60 * 1000;
+
+/// The opt-in archive of sent recordings, for checking later what the gateway heard
+pub struct RecordingArchiveState {
+    pub enabled: Arc<AtomicBool>,
+    /// Oldest recordings are deleted past this many; 0 means no limit
+    pub max_count: Arc<Mutex<usize>>,
+    /// Recordings older than this many days are deleted; 0 keeps them
+    pub max_age_days: Arc<Mutex<u64>>,
+}
+
+impl RecordingArchiveState {
+    pub fn new() -> Self {
+        Self {
+            enabled: Arc::new(AtomicBool::new(false)),
+            max_count: Arc::new(Mutex::new(0)),
+            max_age_days: Arc::new(Mutex::new(0)),
+        }
+    }
+
+    pub fn configure(&self, enabled: bool, max_count: u64, max_age_days: u64) {
+        self.enabled.store(enabled, Ordering::SeqCst);
+        if let Ok(mut current) = self.max_count.lock() {
+            *current = usize::try_from(max_count).unwrap_or(usize::MAX);
+        }
+        if let Ok(mut current) = self.max_age_days.lock() {
+            *current = max_age_days;
+        }
+    }
+
+    fn retention(&self) -> (usize, u64) {
+        let max_count = self.max_count.lock().map(|n| *n).unwrap_or(0);
+        let max_age_days = self.max_age_days.lock().map(|d| *d).unwrap_or(0);
+        (max_count, max_age_days)
+    }
+}
+
+fn archive_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(RECORDINGS_DIR))
+        .map_err(|e| format!("Failed to resolve app data dir: {e}"))
+}
+
+/// `<recorded at ms>-<session id>`, so the files sort by time and name their session
+fn new_id(recorded_at_ms: u64, session_id: &str) -> String {
+    let session: String = session_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .take(64)
+        .collect();
+    format!("{recorded_at_ms}-{session}")
+}
+
+fn meta_path(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{id}.json"))
+}
+
+fn wav_path(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{id}.wav"))
+}
+
+fn read_meta(dir: &Path, id: &str) -> Result<ArchivedRecording, String> {
+    let bytes = fs::read(meta_path(dir, id)).map_err(|_| format!("Unknown recording '{id}'"))?;
+    serde_json::from_slice(&bytes).map_err(|e| format!("Failed to read recording '{id}': {e}"))
+}
+
+fn remove(dir: &Path, id: &str) {
+    let _ = fs::remove_file(wav_path(dir, id));
+    let _ = fs::remove_file(meta_path(dir, id));
+    let _ = fs::remove_file(dir.join(format!("{id}.json.bak")));
+}
+
+/// Recordings in `dir`, newest first. As with failed sends, the metadata is written
+/// after the audio, so a recording only counts once both files exist.
+fn list_dir(dir: &Path) -> Vec<ArchivedRecording> {
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut entries: Vec<ArchivedRecording> = read_dir
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let id = name.strip_suffix(".json")?.to_string();
+            persist::check_id(&id, "recording").ok()?;
+            wav_path(dir, &id)
+                .exists()
+                .then(|| read_meta(dir, &id).ok())?
+        })
+        .collect();
+    entries.sort_by(|a, b| {
+        b.recorded_at_ms
+            .cmp(&a.recorded_at_ms)
+            .then(b.id.cmp(&a.id))
+    });
+    entries
+}
+
+/// Ids of the recordings past the retention limits, from a newest-first list
+fn expired(
+    entries: &[ArchivedRecording],
+    now_ms: u64,
+    max_count: usize,
+    max_age_days: u64,
+) -> Vec<String> {
+    let max_age_ms = max_age_days.saturating_mul(DAY_MS);
+    entries
+        .iter()
+        .enumerate()
+        .filter(|(index, entry)| {
+            let too_many = max_count > 0 && *index >= max_count;
+            let too_old =
+                max_age_days > 0 && now_ms.saturating_sub(entry.recorded_at_ms) > max_age_ms;
+            too_many || too_old
+        })
+        .map(|(_, entry)| entry.id.clone())
+        .collect()
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+fn notify(app: &AppHandle) {
+    if let Ok(list) = list(app) {
+        let _ = app.emit("recordings-changed", list);
+    }
+}
+
+/// Delete what the retention settings no longer allow; run at launch, after
+/// settings change and with every recording archived
+pub fn prune(app: &AppHandle) {
+    let Ok(dir) = archive_dir(app) else {
+        return;
+    };
+    let (max_count, max_age_days) = app.state::<RecordingArchiveState>().retention();
+    let expired = expired(&list_dir(&dir), now_ms(), max_count, max_age_days);
+    if expired.is_empty() {
+        return;
+    }
+    for id in &expired {
+        remove(&dir, id);
+    }
+    events::record_state(
+        app,
+        "recordings-pruned",
+        serde_json::json!({ "count": expired.len() }),
+    );
+    notify(app);
+}
+
+/// Archive a recording that was sent, when the archive is on. `transcript` is the
+/// gateway's, if its reply finished before this was called. Failures are logged,
+/// never passed on: the send itself went through.
+pub fn save(
+    app: &AppHandle,
+    turn: &Turn<'_>,
+    wav_bytes: &[u8],
+    duration_ms: u64,
+    transcript: Option<String>,
+) {
+    let state = app.state::<RecordingArchiveState>();
+    if !state.enabled.load(Ordering::SeqCst) || privacy::is_enabled(app) {
+        return;
+    }
+    if let Err(e) = keep(app, turn, wav_bytes, duration_ms, transcript) {
+        eprintln!("Recording not archived: {e}");
+    }
+}
+
+fn keep(
+    app: &AppHandle,
+    turn: &Turn<'_>,
+    wav_bytes: &[u8],
+    duration_ms: u64,
+    transcript: Option<String>,
+) -> Result<(), String> {
+    storage::admit(app, false)?;
+    let dir = archive_dir(app)?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+
+    let recorded_at_ms = now_ms();
+    let base = new_id(recorded_at_ms, turn.session_id);
+    let mut id = base.clone();
+    let mut suffix = 1;
+    while meta_path(&dir, &id).exists() || wav_path(&dir, &id).exists() {
+        id = format!("{base}-{suffix}");
+        suffix += 1;
+    }
+
+    let entry = ArchivedRecording {
+        id,
+        session_id: turn.session_id.to_string(),
+        profile_name: turn.profile_name.to_string(),
+        recorded_at_ms,
+        duration_ms,
+        bytes: wav_bytes.len(),
+        transcript,
+    };
+    fs::write(wav_path(&dir, &entry.id), wav_bytes)
+        .map_err(|e| format!("Failed to save recording: {e}"))?;
+    let meta = serde_json::to_vec_pretty(&entry)
+        .map_err(|e| format!("Failed to serialize recording: {e}"))?;
+    persist::write_atomic(&meta_path(&dir, &entry.id), &meta)?;
+
+    events::record_state(
+        app,
+        "recording-archived",
+        serde_json::json!({ "id": entry.id }),
+    );
+    prune(app);
+    notify(app);
+    Ok(())
+}
+
+/// Archived recordings, newest first
+pub fn list(app: &AppHandle) -> Result<Vec<ArchivedRecording>, String> {
+    Ok(list_dir(&archive_dir(app)?))
+}
+
+pub fn delete(app: &AppHandle, id: &str) -> Result<(), String> {
+    persist::check_id(id, "recording")?;
+    let dir = archive_dir(app)?;
+    read_meta(&dir, id)?;
+    remove(&dir, id);
+    notify(app);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, recorded_at_ms: u64) -> ArchivedRecording {
+        ArchivedRecording {
+            id: id.to_string(),
+            session_id: "s1".to_string(),
+            profile_name: "alice".to_string(),
+            recorded_at_ms,
+            duration_ms: 1500,
+            bytes: 4,
+            transcript: None,
+        }
+    }
+
+    #[test]
+    fn test_retention_drops_the_oldest_and_the_expired() {
+        let now = 40 * DAY_MS;
+        let entries = vec![
+            entry("new", now - DAY_MS),
+            entry("week", now - 7 * DAY_MS),
+            entry("month", now - 31 * DAY_MS),
+        ];
+        assert!(expired(&entries, now, 0, 0).is_empty());
+        assert_eq!(expired(&entries, now, 2, 0), vec!["month"]);
+        assert_eq!(expired(&entries, now, 0, 5), vec!["week", "month"]);
+        assert_eq!(expired(&entries, now, 1, 30), vec!["week", "month"]);
+    }
+
+    #[test]
+    fn test_ids_name_the_session_and_stay_in_the_directory() {
+        assert_eq!(new_id(1712345678901, "demo-42"), "1712345678901-demo-42");
+        let id = new_id(1, "../a/b c");
+        assert_eq!(id, "1-___a_b_c");
+        assert!(persist::check_id(&id, "recording").is_ok());
+    }
+}
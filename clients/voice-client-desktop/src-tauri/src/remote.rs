@@ -0,0 +1,397 @@
+use std::net::{IpAddr, SocketAddr, UdpSocket};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures_util::{SinkExt, StreamExt};
+use keyring::Entry;
+use rand::distributions::{Alphanumeric, DistString};
+use serde::{Deserialize, Serialize};
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::task::JoinSet;
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::http::StatusCode;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::events;
+use crate::settings::{self, KEYRING_SERVICE};
+use crate::types::{CompletedTurn, PushToTalkState, RemotePairing, VoiceEvent};
+
+const KEYRING_USERNAME: &str = "remote-control-token";
+const TOKEN_LEN: usize = 32;
+/// Messages kept for a device that reads slower than replies stream in
+const OUTBOX_CAPACITY: usize = 256;
+/// Pause after a failed accept, which tends to repeat at once (e.g. out of file descriptors)
+const ACCEPT_RETRY_DELAY: Duration = Duration::from_millis(500);
+/// How long a device has to finish the WebSocket handshake
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// What a paired device sends: its push-to-talk button going down and up
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum RemoteCommand {
+    Pressed,
+    Released,
+}
+
+/// What paired devices are sent: the popup session's conversation as it streams in
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum RemoteMessage<'a> {
+    #[serde(rename_all = "camelCase")]
+    VoiceEvent {
+        session_id: &'a str,
+        event: &'a VoiceEvent,
+    },
+    TurnCompleted {
+        turn: &'a CompletedTurn,
+    },
+}
+
+/// The listening server, while remote control is on
+struct RunningServer {
+    port: u16,
+    task: JoinHandle<()>,
+}
+
+/// Remote control: a phone on the same network holds a WebSocket open to act as a
+/// wireless push-to-talk button and read the replies. Off unless enabled in settings.
+pub struct RemoteControlState {
+    pub outbox: broadcast::Sender<String>,
+    server: Arc<Mutex<Option<RunningServer>>>,
+}
+
+impl RemoteControlState {
+    pub fn new() -> Self {
+        let (outbox, _) = broadcast::channel(OUTBOX_CAPACITY);
+        Self {
+            outbox,
+            server: Arc::new(Mutex::new(None)),
+        }
+    }
+}
+
+/// The pairing token from the OS keyring, created the first time it is needed
+fn pairing_token() -> Result<String, String> {
+    let entry =
+        Entry::new(KEYRING_SERVICE, KEYRING_USERNAME).map_err(|e| format!("Keyring error: {e}"))?;
+    match entry.get_password() {
+        Ok(token) if !token.is_empty() => Ok(token),
+        Ok(_) | Err(keyring::Error::NoEntry) => {
+            let token = Alphanumeric.sample_string(&mut rand::thread_rng(), TOKEN_LEN);
+            entry
+                .set_password(&token)
+                .map_err(|e| format!("Failed to save pairing token: {e}"))?;
+            Ok(token)
+        }
+        Err(e) => Err(format!("Failed to load pairing token: {e}")),
+    }
+}
+
+fn forget_pairing_token() -> Result<(), String> {
+    let entry =
+        Entry::new(KEYRING_SERVICE, KEYRING_USERNAME).map_err(|e| format!("Keyring error: {e}"))?;
+    match entry.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(format!("Failed to delete pairing token: {e}")),
+    }
+}
+
+/// Compare without returning early, so response times don't give the token away
+fn same_token(presented: &str, token: &str) -> bool {
+    presented.len() == token.len()
+        && presented
+            .bytes()
+            .zip(token.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// The token from an `Authorization: Bearer` header or a `token` query parameter
+fn presented_token(request: &Request) -> Option<String> {
+    let bearer = request
+        .headers()
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::to_string);
+    bearer.or_else(|| {
+        let query = request.uri().query()?;
+        url::form_urlencoded::parse(query.as_bytes())
+            .find(|(key, _)| key == "token")
+            .map(|(_, value)| value.into_owned())
+    })
+}
+
+/// Start, restart or stop the server to match the settings
+pub fn configure(app: &AppHandle, enabled: bool, port: u16) {
+    let state = app.state::<RemoteControlState>();
+    let Ok(mut server) = state.server.lock() else {
+        return;
+    };
+    if enabled && server.as_ref().is_some_and(|running| running.port == port) {
+        return;
+    }
+    if let Some(running) = server.take() {
+        running.task.abort();
+    }
+    if !enabled {
+        return;
+    }
+    match pairing_token() {
+        Ok(token) => {
+            let task = spawn_server(app, port, token);
+            *server = Some(RunningServer { port, task });
+        }
+        Err(e) => eprintln!("Remote control not started: {e}"),
+    }
+}
+
+/// Accept devices until aborted; aborting also drops every open connection
+fn spawn_server(app: &AppHandle, port: u16, token: String) -> JoinHandle<()> {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let listener = match TcpListener::bind(("0.0.0.0", port)).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                eprintln!("Remote control can't listen on port {port}: {e}");
+                events::record_state(
+                    &app,
+                    "remote-control-failed",
+                    serde_json::json!({ "port": port, "error": e.to_string() }),
+                );
+                return;
+            }
+        };
+        events::record_state(
+            &app,
+            "remote-control-listening",
+            serde_json::json!({ "port": port }),
+        );
+        let mut connections = JoinSet::new();
+        loop {
+            let (stream, peer) = match listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    eprintln!("Remote control failed to accept a connection: {e}");
+                    tokio::time::sleep(ACCEPT_RETRY_DELAY).await;
+                    continue;
+                }
+            };
+            while connections.try_join_next().is_some() {}
+            connections.spawn(handle_device(app.clone(), stream, peer, token.clone()));
+        }
+    })
+}
+
+/// Let the handshake through only with the pairing token
+// The error type is fixed by tungstenite's handshake callback
+#[allow(clippy::result_large_err)]
+fn authorize(
+    request: &Request,
+    response: Response,
+    token: &str,
+) -> Result<Response, ErrorResponse> {
+    match presented_token(request) {
+        Some(presented) if same_token(&presented, token) => Ok(response),
+        _ => {
+            let mut rejection = ErrorResponse::new(Some("Invalid pairing token".to_string()));
+            *rejection.status_mut() = StatusCode::UNAUTHORIZED;
+            Err(rejection)
+        }
+    }
+}
+
+async fn handle_device(app: AppHandle, stream: TcpStream, peer: SocketAddr, token: String) {
+    let handshake =
+        tokio_tungstenite::accept_hdr_async(stream, |request: &Request, response: Response| {
+            authorize(request, response, &token)
+        });
+    let accepted = match tokio::time::timeout(HANDSHAKE_TIMEOUT, handshake).await {
+        Ok(accepted) => accepted.map_err(|e| e.to_string()),
+        Err(_) => Err("Handshake timed out".to_string()),
+    };
+    let socket = match accepted {
+        Ok(socket) => socket,
+        Err(error) => {
+            events::record_state(
+                &app,
+                "remote-rejected",
+                serde_json::json!({ "peer": peer.to_string(), "error": error }),
+            );
+            return;
+        }
+    };
+    events::record_state(
+        &app,
+        "remote-connected",
+        serde_json::json!({ "peer": peer.to_string() }),
+    );
+
+    let mut outbox = app.state::<RemoteControlState>().outbox.subscribe();
+    let (mut sink, mut incoming) = socket.split();
+    let mut held = false;
+    loop {
+        tokio::select! {
+            message = incoming.next() => match message {
+                Some(Ok(Message::Text(text))) => {
+                    match serde_json::from_str::<RemoteCommand>(&text) {
+                        Ok(command) => {
+                            held = command == RemoteCommand::Pressed;
+                            push_to_talk(&app, command);
+                        }
+                        Err(e) => eprintln!("Ignoring remote message from {peer}: {e}"),
+                    }
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+            outgoing = outbox.recv() => match outgoing {
+                Ok(text) => {
+                    if sink.send(Message::Text(text)).await.is_err() {
+                        break;
+                    }
+                }
+                // Missed deltas only cost the phone part of a reply it will get whole
+                Err(RecvError::Lagged(_)) => {}
+                Err(RecvError::Closed) => break,
+            },
+        }
+    }
+    // A phone that drops off mid-press mustn't leave the microphone open
+    if held {
+        push_to_talk(&app, RemoteCommand::Released);
+    }
+    events::record_state(
+        &app,
+        "remote-disconnected",
+        serde_json::json!({ "peer": peer.to_string() }),
+    );
+}
+
+/// The popup records for a paired device just like for the push-to-talk hotkey
+fn push_to_talk(app: &AppHandle, command: RemoteCommand) {
+    let state = match command {
+        RemoteCommand::Pressed => PushToTalkState::Pressed,
+        RemoteCommand::Released => PushToTalkState::Released,
+    };
+    let _ = app.emit_to("popup", "remote-push-to-talk", state);
+}
+
+fn send(app: &AppHandle, message: &RemoteMessage) {
+    let Some(state) = app.try_state::<RemoteControlState>() else {
+        return;
+    };
+    if state.outbox.receiver_count() == 0 {
+        return;
+    }
+    if let Ok(text) = serde_json::to_string(message) {
+        let _ = state.outbox.send(text);
+    }
+}
+
+/// Pass a conversation event on to paired devices
+pub fn send_event(app: &AppHandle, session_id: &str, event: &VoiceEvent) {
+    send(app, &RemoteMessage::VoiceEvent { session_id, event });
+}
+
+/// Pass a finished reply on to paired devices
+pub fn send_turn(app: &AppHandle, turn: &CompletedTurn) {
+    send(app, &RemoteMessage::TurnCompleted { turn });
+}
+
+/// The address other devices on the network reach this one at. Connecting a UDP
+/// socket sends nothing; it only picks the interface that routes outwards.
+fn lan_address() -> Option<IpAddr> {
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("192.0.2.1:9").ok()?;
+    socket.local_addr().ok().map(|address| address.ip())
+}
+
+/// What a phone needs to connect, for the settings window
+pub fn pairing(app: &AppHandle) -> Result<RemotePairing, String> {
+    let settings = settings::load_settings(app)?;
+    if !settings.remote_control {
+        return Ok(RemotePairing {
+            enabled: false,
+            url: None,
+        });
+    }
+    let host = lan_address().map_or_else(|| "localhost".to_string(), |ip| ip.to_string());
+    let token = pairing_token()?;
+    Ok(RemotePairing {
+        enabled: true,
+        url: Some(format!(
+            "ws://{host}:{}/remote?token={token}",
+            settings.remote_control_port
+        )),
+    })
+}
+
+/// Replace the pairing token, disconnecting every paired device
+pub fn reset_pairing(app: &AppHandle) -> Result<RemotePairing, String> {
+    forget_pairing_token()?;
+    let settings = settings::load_settings(app)?;
+    // Stop first so the server restarts with the new token
+    configure(app, false, settings.remote_control_port);
+    configure(app, settings.remote_control, settings.remote_control_port);
+    events::record_state(app, "remote-pairing-reset", ());
+    pairing(app)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(uri: &str, authorization: Option<&str>) -> Request {
+        let mut builder = Request::builder().uri(uri);
+        if let Some(value) = authorization {
+            builder = builder.header("Authorization", value);
+        }
+        builder.body(()).unwrap()
+    }
+
+    #[test]
+    fn test_token_from_header_or_query() {
+        let token = presented_token(&request("/remote", Some("Bearer abc123")));
+        assert_eq!(token.as_deref(), Some("abc123"));
+        let token = presented_token(&request("/remote?token=abc%20123", None));
+        assert_eq!(token.as_deref(), Some("abc 123"));
+        assert!(presented_token(&request("/remote?other=1", None)).is_none());
+    }
+
+    #[test]
+    fn test_tokens_must_match_exactly() {
+        assert!(same_token("abc123", "abc123"));
+        assert!(!same_token("abc124", "abc123"));
+        assert!(!same_token("abc12", "abc123"));
+        assert!(!same_token("", "abc123"));
+    }
+
+    #[test]
+    fn test_protocol_shapes() {
+        let command: RemoteCommand = serde_json::from_str(r#"{"type":"pressed"}"#).unwrap();
+        assert_eq!(command, RemoteCommand::Pressed);
+        assert!(serde_json::from_str::<RemoteCommand>(r#"{"type":"reboot"}"#).is_err());
+
+        let event = VoiceEvent::Openclaw {
+            text: "Hi".to_string(),
+            done: false,
+            timestamp: "t1".to_string(),
+        };
+        let message = RemoteMessage::VoiceEvent {
+            session_id: "voice-1",
+            event: &event,
+        };
+        assert_eq!(
+            serde_json::to_value(&message).unwrap(),
+            serde_json::json!({
+                "type": "voiceEvent",
+                "sessionId": "voice-1",
+                "event": { "type": "openclaw", "text": "Hi", "done": false, "timestamp": "t1" },
+            })
+        );
+    }
+}
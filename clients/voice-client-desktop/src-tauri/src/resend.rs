@@ -0,0 +1,95 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use bytes::Bytes;
+use tauri::{AppHandle, Manager};
+
+use crate::api::Turn;
+use crate::error::AppError;
+use crate::events;
+use crate::response_cache;
+use crate::stt;
+
+/// The last recording sent and where it went
+#[derive(Debug, Clone)]
+struct LastAudio {
+    base_url: String,
+    session_id: String,
+    profile_name: String,
+    session_key: Option<String>,
+    response_language: Option<String>,
+    wav: Bytes,
+}
+
+/// The most recent upload, kept in memory only so it can be sent again when the
+/// gateway drops the reply part way through, instead of saying it all again
+pub struct LastAudioState {
+    last: Arc<Mutex<Option<LastAudio>>>,
+    /// Set while a resend runs, so a double click can't send it twice
+    in_flight: Arc<AtomicBool>,
+}
+
+impl LastAudioState {
+    pub fn new() -> Self {
+        Self {
+            last: Arc::new(Mutex::new(None)),
+            in_flight: Arc::new(AtomicBool::new(false)),
+        }
+    }
+}
+
+/// Remember an encoded recording as it is sent. It shares the upload's buffer.
+pub fn keep(app: &AppHandle, turn: &Turn<'_>, wav: &Bytes) {
+    let last = LastAudio {
+        base_url: turn.base_url.to_string(),
+        session_id: turn.session_id.to_string(),
+        profile_name: turn.profile_name.to_string(),
+        session_key: turn.session_key.map(String::from),
+        response_language: turn.response_language.map(String::from),
+        wav: wav.clone(),
+    };
+    if let Ok(mut current) = app.state::<LastAudioState>().last.lock() {
+        *current = Some(last);
+    }
+}
+
+/// Post the last recording again to the session it was sent to. Transcription runs
+/// again, and the reply streams to the popup like a fresh recording's.
+pub async fn resend(app: &AppHandle) -> Result<(), AppError> {
+    let state = app.state::<LastAudioState>();
+    let last = state
+        .last
+        .lock()
+        .ok()
+        .and_then(|last| last.clone())
+        .ok_or_else(|| AppError::Other("Nothing has been sent yet".to_string()))?;
+    if state.in_flight.swap(true, Ordering::SeqCst) {
+        return Err(AppError::Other(
+            "The last recording is already being resent".to_string(),
+        ));
+    }
+    events::record_state(
+        app,
+        "resend-started",
+        serde_json::json!({ "sessionId": last.session_id, "bytes": last.wav.len() }),
+    );
+    let turn = Turn {
+        base_url: &last.base_url,
+        session_id: &last.session_id,
+        profile_name: &last.profile_name,
+        session_key: last.session_key.as_deref(),
+        response_language: last.response_language.as_deref(),
+        context: None,
+    };
+    let result = match stt::prepare(app, &last.profile_name, last.wav.clone()).await {
+        Ok(utterance) => response_cache::send(app, &turn, utterance).await,
+        Err(e) => Err(e),
+    };
+    state.in_flight.store(false, Ordering::SeqCst);
+    events::record_state(
+        app,
+        "resend-finished",
+        serde_json::json!({ "ok": result.is_ok() }),
+    );
+    result
+}
@@ -0,0 +1,360 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::api::{self, Gateway, Turn};
+use crate::demo;
+use crate::error::AppError;
+use crate::events;
+use crate::persist;
+use crate::privacy;
+use crate::session::SessionState;
+use crate::stt::{self, Utterance};
+use crate::types::{CompletedTurn, EventKind, VoiceEvent};
+
+pub const CACHE_FILE: &str = "response-cache.json";
+const MAX_ENTRIES: usize = 200;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CachedResponse {
+    pub profile_name: String,
+    /// The question as returned by `normalize`
+    pub question: String,
+    pub reply: String,
+    pub cached_at_ms: u64,
+}
+
+/// Lowercased words stripped of punctuation, so "What's the Wi-Fi password?" and
+/// "whats the wifi password" are the same question
+pub fn normalize(text: &str) -> String {
+    text.split_whitespace()
+        .map(|word| {
+            word.chars()
+                .filter(|c| c.is_alphanumeric())
+                .flat_map(char::to_lowercase)
+                .collect::<String>()
+        })
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Final replies keyed by profile and normalised question, newest last
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ResponseCache {
+    entries: Vec<CachedResponse>,
+}
+
+impl ResponseCache {
+    pub fn get(
+        &self,
+        profile_name: &str,
+        question: &str,
+        now_ms: u64,
+        ttl: Duration,
+    ) -> Option<&CachedResponse> {
+        let question = normalize(question);
+        self.entries.iter().rev().find(|entry| {
+            entry.profile_name == profile_name
+                && entry.question == question
+                && now_ms.saturating_sub(entry.cached_at_ms) < ttl.as_millis() as u64
+        })
+    }
+
+    pub fn insert(&mut self, profile_name: &str, question: &str, reply: &str, now_ms: u64) {
+        let question = normalize(question);
+        if question.is_empty() || reply.trim().is_empty() {
+            return;
+        }
+        self.entries
+            .retain(|entry| !(entry.profile_name == profile_name && entry.question == question));
+        self.entries.push(CachedResponse {
+            profile_name: profile_name.to_string(),
+            question,
+            reply: reply.to_string(),
+            cached_at_ms: now_ms,
+        });
+        let overflow = self.entries.len().saturating_sub(MAX_ENTRIES);
+        self.entries.drain(..overflow);
+    }
+
+    /// Drop expired entries so they don't linger on disk
+    pub fn prune(&mut self, now_ms: u64, ttl: Duration) {
+        self.entries
+            .retain(|entry| now_ms.saturating_sub(entry.cached_at_ms) < ttl.as_millis() as u64);
+    }
+}
+
+pub struct ResponseCacheState {
+    pub cache: Arc<Mutex<ResponseCache>>,
+    /// How long a reply stays usable; zero disables caching
+    pub ttl: Arc<Mutex<Duration>>,
+    /// Answer from the cache when the gateway can't be reached
+    pub answer_offline: Arc<AtomicBool>,
+    /// Set while an answer is replayed, so replaying doesn't re-cache it
+    replaying: Arc<AtomicBool>,
+}
+
+impl ResponseCacheState {
+    pub fn new() -> Self {
+        Self {
+            cache: Arc::new(Mutex::new(ResponseCache::default())),
+            ttl: Arc::new(Mutex::new(Duration::ZERO)),
+            answer_offline: Arc::new(AtomicBool::new(false)),
+            replaying: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn configure(&self, ttl: Duration, answer_offline: bool) {
+        if let Ok(mut current) = self.ttl.lock() {
+            *current = ttl;
+        }
+        self.answer_offline.store(answer_offline, Ordering::SeqCst);
+    }
+
+    fn ttl(&self) -> Duration {
+        self.ttl.lock().map(|ttl| *ttl).unwrap_or_default()
+    }
+}
+
+fn cache_path(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(CACHE_FILE))
+        .map_err(|e| format!("Failed to resolve app data dir: {e}"))
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+fn save(app: &AppHandle, cache: &ResponseCache) -> Result<(), String> {
+    let path = cache_path(app)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    }
+    let bytes = serde_json::to_vec(cache)
+        .map_err(|e| format!("Failed to serialize response cache: {e}"))?;
+    persist::write_atomic(&path, &bytes)
+}
+
+/// Load the cache written by a previous run, dropping what has expired since
+pub fn load(app: &AppHandle) {
+    let state = app.state::<ResponseCacheState>();
+    let Some(mut cache) = cache_path(app)
+        .ok()
+        .and_then(|path| fs::read(path).ok())
+        .and_then(|bytes| serde_json::from_slice::<ResponseCache>(&bytes).ok())
+    else {
+        return;
+    };
+    cache.prune(now_ms(), state.ttl());
+    if let Ok(mut current) = state.cache.lock() {
+        *current = cache;
+    };
+}
+
+/// Remember a completed turn's reply for the active profile
+pub fn store(app: &AppHandle, turn: &CompletedTurn) {
+    let Some(state) = app.try_state::<ResponseCacheState>() else {
+        return;
+    };
+    let ttl = state.ttl();
+    if ttl.is_zero() || state.replaying.load(Ordering::SeqCst) || privacy::is_enabled(app) {
+        return;
+    }
+    let (Some(question), Some(session)) = (
+        turn.user_text.as_deref(),
+        app.state::<SessionState>().snapshot(),
+    ) else {
+        return;
+    };
+    let Ok(mut cache) = state.cache.lock() else {
+        return;
+    };
+    let now = now_ms();
+    cache.prune(now, ttl);
+    cache.insert(&session.profile_name, question, &turn.reply, now);
+    if let Err(e) = save(app, &cache) {
+        eprintln!("Response cache not saved: {e}");
+    }
+}
+
+/// Emit `script` to `session_id` as if the gateway had streamed it, `pace` apart,
+/// without caching the reply it makes up. Cached and demo answers are sent this way.
+pub async fn replay_events(
+    app: &AppHandle,
+    session_id: &str,
+    script: &[VoiceEvent],
+    pace: Duration,
+) {
+    let state = app.state::<ResponseCacheState>();
+    state.replaying.store(true, Ordering::SeqCst);
+    for (i, event) in script.iter().enumerate() {
+        if i > 0 && !pace.is_zero() {
+            tokio::time::sleep(pace).await;
+        }
+        let _ = events::emit_voice_event(app, session_id, event);
+    }
+    state.replaying.store(false, Ordering::SeqCst);
+}
+
+/// Replay a cached answer as if the gateway had sent it, marked with a
+/// `cached` system event. Returns false when there is nothing to replay.
+async fn replay(app: &AppHandle, turn: &Turn<'_>, question: &str) -> bool {
+    let state = app.state::<ResponseCacheState>();
+    if !state.answer_offline.load(Ordering::SeqCst) || privacy::is_enabled(app) {
+        return false;
+    }
+    let hit = state.cache.lock().ok().and_then(|cache| {
+        cache
+            .get(turn.profile_name, question, now_ms(), state.ttl())
+            .cloned()
+    });
+    let Some(hit) = hit else {
+        return false;
+    };
+
+    let timestamp = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+    let age_mins = now_ms().saturating_sub(hit.cached_at_ms) / 60_000;
+    let script = [
+        VoiceEvent::System {
+            status: "cached".to_string(),
+            message: Some(format!("Offline — answer cached {age_mins} min ago")),
+            timestamp: timestamp.clone(),
+        },
+        VoiceEvent::User {
+            text: question.to_string(),
+            confidence: 1.0,
+            timestamp: timestamp.clone(),
+        },
+        VoiceEvent::Openclaw {
+            text: hit.reply.clone(),
+            done: false,
+            timestamp: timestamp.clone(),
+        },
+        VoiceEvent::Openclaw {
+            text: String::new(),
+            done: true,
+            timestamp: timestamp.clone(),
+        },
+        VoiceEvent::System {
+            status: "done".to_string(),
+            message: None,
+            timestamp: timestamp.clone(),
+        },
+    ];
+    replay_events(app, turn.session_id, &script, Duration::ZERO).await;
+    // The gateway's speech for the answer wasn't cached with it
+    events::speak(app, turn.profile_name, &hit.reply, EventKind::Reply);
+    events::record_state(
+        app,
+        "cache-answered",
+        serde_json::json!({ "ageMins": age_mins }),
+    );
+    true
+}
+
+/// Send a turn to the gateway. If it can't be reached, audio is transcribed locally
+/// when a whisper model is set, and a cached answer to the same question is used
+/// instead; without one, the local transcript is still shown before the error.
+/// In demo mode the gateway isn't tried at all.
+pub async fn send(app: &AppHandle, turn: &Turn<'_>, utterance: Utterance) -> Result<(), AppError> {
+    if demo::is_enabled(app) {
+        return demo::answer(app, turn, utterance).await;
+    }
+    let (question, fallback) = match &utterance {
+        Utterance::Transcript(text) => (Some(text.clone()), None),
+        Utterance::Audio(wav) => (
+            None,
+            stt::fallback(app).map(|whisper| (whisper, wav.clone())),
+        ),
+    };
+    let result = api::send_audio_streaming(app, &Gateway::from_app(app), turn, utterance).await;
+    if !matches!(result, Err(AppError::Network(_))) {
+        return result;
+    }
+    let (question, transcribed_here) = match (question, fallback) {
+        (Some(question), _) => (question, false),
+        (None, Some((whisper, wav))) => match stt::transcribe_offline(app, whisper, wav).await {
+            Some(transcript) => (transcript, true),
+            None => return result,
+        },
+        (None, None) => return result,
+    };
+    if replay(app, turn, &question).await {
+        return Ok(());
+    }
+    if transcribed_here {
+        let event = VoiceEvent::User {
+            text: question,
+            confidence: 1.0,
+            timestamp: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        };
+        let _ = events::emit_voice_event(app, turn.session_id, &event);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    #[test]
+    fn test_questions_match_after_normalising() {
+        assert_eq!(
+            normalize("What's the Wi-Fi password?"),
+            "whats the wifi password"
+        );
+        assert_eq!(
+            normalize("  whats THE wifi   password - "),
+            "whats the wifi password"
+        );
+    }
+
+    #[test]
+    fn test_entries_are_per_profile_and_expire() {
+        let mut cache = ResponseCache::default();
+        cache.insert("alice", "Office wifi password?", "hunter2", 1_000);
+
+        assert_eq!(
+            cache
+                .get("alice", "office Wi-Fi password", 2_000, DAY)
+                .map(|e| e.reply.as_str()),
+            Some("hunter2")
+        );
+        assert!(cache
+            .get("bob", "office wifi password", 2_000, DAY)
+            .is_none());
+        let a_day_later = 1_000 + DAY.as_millis() as u64;
+        assert!(cache
+            .get("alice", "office wifi password", a_day_later, DAY)
+            .is_none());
+
+        cache.prune(a_day_later, DAY);
+        assert!(cache.entries.is_empty());
+    }
+
+    #[test]
+    fn test_newer_reply_replaces_older() {
+        let mut cache = ResponseCache::default();
+        cache.insert("alice", "standup time", "10:00", 1);
+        cache.insert("alice", "Standup time?", "10:30", 2);
+        assert_eq!(cache.entries.len(), 1);
+        assert_eq!(
+            cache.get("alice", "standup time", 3, DAY).unwrap().reply,
+            "10:30"
+        );
+    }
+}
@@ -0,0 +1,300 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use chrono::Local;
+use fs2::FileExt;
+use regex::Regex;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::session::SessionState;
+use crate::types::{CompletedTurn, ResponseRoute, ResponseRouteFailed};
+use crate::{events, privacy};
+
+/// A route with its pattern compiled
+struct Compiled {
+    route: ResponseRoute,
+    pattern: Option<Regex>,
+}
+
+impl Compiled {
+    fn matches(&self, profile: &str, reply: &str) -> bool {
+        let profile_matches = self
+            .route
+            .profile
+            .as_deref()
+            .is_none_or(|wanted| wanted.eq_ignore_ascii_case(profile));
+        profile_matches && self.pattern.as_ref().is_none_or(|p| p.is_match(reply))
+    }
+}
+
+/// Rules writing completed replies to files
+pub struct ResponseRouteState {
+    routes: Arc<Mutex<Vec<Compiled>>>,
+}
+
+impl ResponseRouteState {
+    pub fn new() -> Self {
+        Self {
+            routes: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    pub fn configure(&self, routes: Vec<ResponseRoute>) {
+        let compiled = routes
+            .into_iter()
+            .filter(|route| route.enabled)
+            .filter_map(|route| {
+                let pattern = match route.pattern.as_deref().filter(|p| !p.is_empty()) {
+                    Some(pattern) => match Regex::new(pattern) {
+                        Ok(regex) => Some(regex),
+                        Err(e) => {
+                            eprintln!("Response route to {} skipped: {e}", route.path);
+                            return None;
+                        }
+                    },
+                    None => None,
+                };
+                Some(Compiled { route, pattern })
+            })
+            .collect();
+        if let Ok(mut current) = self.routes.lock() {
+            *current = compiled;
+        }
+    }
+}
+
+/// Every route needs a path, and its pattern must be a valid regular expression
+pub fn validate(routes: &[ResponseRoute]) -> Result<(), String> {
+    for route in routes {
+        if route.path.trim().is_empty() {
+            return Err("Every response route needs a path".to_string());
+        }
+        if let Some(pattern) = route.pattern.as_deref().filter(|p| !p.is_empty()) {
+            Regex::new(pattern)
+                .map_err(|e| format!("Response route pattern '{pattern}' is invalid: {e}"))?;
+        }
+    }
+    Ok(())
+}
+
+/// What the placeholders of a route's path and template stand for
+struct Fields<'a> {
+    reply: &'a str,
+    user: &'a str,
+    profile: &'a str,
+    session: &'a str,
+    timestamp: &'a str,
+    date: String,
+    time: String,
+}
+
+impl Fields<'_> {
+    fn get(&self, name: &str) -> Option<&str> {
+        Some(match name {
+            "reply" => self.reply,
+            "user" => self.user,
+            "profile" => self.profile,
+            "session" => self.session,
+            "timestamp" => self.timestamp,
+            "date" => &self.date,
+            "time" => &self.time,
+            _ => return None,
+        })
+    }
+}
+
+/// Fill in `{name}` placeholders in one pass, so braces in the reply itself are left
+/// alone; unknown names are kept as written
+fn render(template: &str, fields: &Fields) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let value = after
+            .find('}')
+            .and_then(|end| fields.get(&after[..end]).map(|value| (end, value)));
+        match value {
+            Some((end, value)) => {
+                out.push_str(value);
+                rest = &after[end + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// `~/notes/journal.md` and relative paths are under the home directory
+fn expand_path(path: &str) -> Option<PathBuf> {
+    let path = path.trim();
+    let relative = path.strip_prefix("~/").unwrap_or(path);
+    if relative == "~" {
+        return None;
+    }
+    if Path::new(relative).is_absolute() {
+        return Some(PathBuf::from(relative));
+    }
+    dirs::home_dir().map(|home| home.join(relative))
+}
+
+/// Append `text` to `path`, or replace its contents, holding an exclusive lock so two
+/// replies (or another program using the same lock) don't interleave
+fn write(path: &Path, text: &str, append: bool) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    // Emptied once locked rather than on open, which would pull it from under a reader
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(append)
+        .write(true)
+        .truncate(false)
+        .open(path)
+        .map_err(|e| e.to_string())?;
+    file.lock_exclusive().map_err(|e| e.to_string())?;
+    let written = if append { Ok(()) } else { file.set_len(0) }
+        .and_then(|_| file.write_all(text.as_bytes()))
+        .and_then(|_| file.flush());
+    let _ = FileExt::unlock(&file);
+    written.map_err(|e| e.to_string())
+}
+
+/// Write a completed turn of the active session to every file whose route matches, in
+/// the background. Nothing is written in privacy mode.
+pub fn route(app: &AppHandle, turn: &CompletedTurn) {
+    if privacy::is_enabled(app) {
+        return;
+    }
+    let Some(active) = app
+        .try_state::<SessionState>()
+        .and_then(|sessions| sessions.snapshot())
+    else {
+        return;
+    };
+    let now = Local::now();
+    let fields = Fields {
+        reply: &turn.reply,
+        user: turn.user_text.as_deref().unwrap_or_default(),
+        profile: &active.profile_name,
+        session: &active.session_id,
+        timestamp: &turn.completed_at,
+        date: now.format("%Y-%m-%d").to_string(),
+        time: now.format("%H:%M").to_string(),
+    };
+    let state = app.state::<ResponseRouteState>();
+    let writes: Vec<(String, String, bool)> = match state.routes.lock() {
+        Ok(routes) => routes
+            .iter()
+            .filter(|compiled| compiled.matches(&active.profile_name, &turn.reply))
+            .map(|compiled| {
+                let route = &compiled.route;
+                (
+                    render(&route.path, &fields),
+                    render(&route.template, &fields),
+                    route.append,
+                )
+            })
+            .collect(),
+        Err(_) => return,
+    };
+    if writes.is_empty() {
+        return;
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        for (path, text, append) in writes {
+            let written = expand_path(&path)
+                .ok_or_else(|| "No home directory to write under".to_string())
+                .and_then(|file| write(&file, &text, append));
+            match written {
+                Ok(()) => events::record_state(&app, "response-routed", &path),
+                Err(error) => {
+                    let failed = ResponseRouteFailed { path, error };
+                    events::record_state(&app, "response-route-failed", &failed);
+                    let _ = app.emit("response-route-failed", &failed);
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    fn fields() -> Fields<'static> {
+        Fields {
+            reply: "Logged {user}: walked 5 km",
+            user: "Log my walk",
+            profile: "journal",
+            session: "voice-1",
+            timestamp: "2026-03-01T08:00:00Z",
+            date: "2026-03-01".to_string(),
+            time: "08:00".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_template_is_filled_in_once() {
+        assert_eq!(
+            render("## {date} {time} ({profile})\n{reply}\n", &fields()),
+            "## 2026-03-01 08:00 (journal)\nLogged {user}: walked 5 km\n"
+        );
+        assert_eq!(render("{unknown} {reply", &fields()), "{unknown} {reply");
+        assert_eq!(render("notes/{date}.md", &fields()), "notes/2026-03-01.md");
+    }
+
+    #[test]
+    fn test_routes_match_profile_and_pattern() {
+        let compiled = |profile: Option<&str>, pattern: Option<&str>| Compiled {
+            route: ResponseRoute {
+                profile: profile.map(String::from),
+                pattern: pattern.map(String::from),
+                ..ResponseRoute::default()
+            },
+            pattern: pattern.map(|p| Regex::new(p).unwrap()),
+        };
+        assert!(compiled(Some("Journal"), None).matches("journal", "anything"));
+        assert!(!compiled(Some("journal"), None).matches("work", "anything"));
+        assert!(compiled(None, Some(r"(?i)\btodo\b")).matches("work", "TODO: call Sam"));
+        assert!(!compiled(Some("work"), Some("todo")).matches("work", "nothing to do"));
+    }
+
+    #[test]
+    fn test_appended_writes_keep_earlier_text() {
+        let dir = TempDir::new("response-routes");
+        let file = dir.join("notes/journal.md");
+        write(&file, "first\n", true).unwrap();
+        write(&file, "second\n", true).unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), "first\nsecond\n");
+        write(&file, "only\n", false).unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), "only\n");
+    }
+
+    #[test]
+    fn test_paths_are_under_the_home_directory() {
+        let home = dirs::home_dir().unwrap();
+        assert_eq!(
+            expand_path("~/notes/journal.md"),
+            Some(home.join("notes/journal.md"))
+        );
+        assert_eq!(
+            expand_path("notes/journal.md"),
+            Some(home.join("notes/journal.md"))
+        );
+        assert_eq!(
+            expand_path("/tmp/out.md"),
+            Some(PathBuf::from("/tmp/out.md"))
+        );
+        assert!(validate(&[ResponseRoute::default()]).is_err());
+    }
+}
@@ -0,0 +1,241 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use url::Url;
+
+use crate::api;
+use crate::error::AppError;
+
+/// Plugin layout: every endpoint directly under the configured gateway URL
+pub const DEFAULT_TEMPLATE: &str = "{base}/{endpoint}";
+
+/// Gateway endpoints the client calls
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endpoint {
+    Audio,
+    Session,
+    SessionNew,
+    Profiles,
+    HistorySearch,
+    HistorySession,
+    Feedback,
+}
+
+impl Endpoint {
+    pub const ALL: [Endpoint; 7] = [
+        Endpoint::Audio,
+        Endpoint::Session,
+        Endpoint::SessionNew,
+        Endpoint::Profiles,
+        Endpoint::HistorySearch,
+        Endpoint::HistorySession,
+        Endpoint::Feedback,
+    ];
+
+    /// Path relative to the gateway base; also the key for per-endpoint overrides
+    pub fn path(self) -> &'static str {
+        match self {
+            Endpoint::Audio => "audio",
+            Endpoint::Session => "session",
+            Endpoint::SessionNew => "session/new",
+            Endpoint::Profiles => "profiles",
+            Endpoint::HistorySearch => "history/search",
+            Endpoint::HistorySession => "history/session",
+            Endpoint::Feedback => "feedback",
+        }
+    }
+}
+
+/// How endpoint URLs are built from the gateway URL.
+/// Templates substitute `{base}` (the gateway URL, no trailing slash) and `{endpoint}`
+/// (the endpoint path), so a gateway behind a path-rewriting proxy can be reached with
+/// e.g. `{base}/v1/{endpoint}`, or an override like `"audio": "{base}/v1/upload"`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Routes {
+    pub template: String,
+    pub overrides: HashMap<String, String>,
+}
+
+impl Default for Routes {
+    fn default() -> Self {
+        Self {
+            template: DEFAULT_TEMPLATE.to_string(),
+            overrides: HashMap::new(),
+        }
+    }
+}
+
+impl Routes {
+    /// Validate templates up front so a typo surfaces when saving, not on the next request
+    pub fn new(template: &str, overrides: &HashMap<String, String>) -> Result<Self, String> {
+        let template = if template.trim().is_empty() {
+            DEFAULT_TEMPLATE.to_string()
+        } else {
+            template.trim().to_string()
+        };
+        if !template.contains("{endpoint}") {
+            return Err(format!(
+                "Endpoint template '{template}' must contain {{endpoint}}"
+            ));
+        }
+        check_template(&template)?;
+
+        for (key, value) in overrides {
+            if !Endpoint::ALL.iter().any(|e| e.path() == key) {
+                return Err(format!("Unknown endpoint '{key}' in endpoint overrides"));
+            }
+            check_template(value)?;
+        }
+
+        Ok(Self {
+            template,
+            overrides: overrides.clone(),
+        })
+    }
+
+    /// Build the URL for `endpoint` with encoded query parameters
+    pub fn url(
+        &self,
+        base_url: &str,
+        endpoint: Endpoint,
+        query: &[(&str, &str)],
+    ) -> Result<Url, AppError> {
+        let base = api::parse_base_url(base_url)?;
+        let template = self
+            .overrides
+            .get(endpoint.path())
+            .unwrap_or(&self.template);
+        let expanded = expand(template, base.as_str(), endpoint.path());
+
+        let mut url = Url::parse(&expanded).map_err(|e| {
+            AppError::InvalidGatewayUrl(format!("endpoint template produced '{expanded}': {e}"))
+        })?;
+        if !query.is_empty() {
+            url.query_pairs_mut().extend_pairs(query);
+        }
+        Ok(url)
+    }
+}
+
+fn expand(template: &str, base: &str, endpoint: &str) -> String {
+    template
+        .replace("{base}", base.trim_end_matches('/'))
+        .replace("{endpoint}", endpoint)
+}
+
+fn check_template(template: &str) -> Result<(), String> {
+    let sample = expand(template, "http://gateway/voice-client", "audio");
+    match Url::parse(&sample) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") && url.query().is_none() => Ok(()),
+        _ => Err(format!(
+            "Endpoint template '{template}' does not produce a valid URL"
+        )),
+    }
+}
+
+/// Routing applied to all gateway calls, updated from settings
+pub struct RoutingState {
+    pub routes: Arc<Mutex<Routes>>,
+    /// Workspace named on every call, for gateways serving several teams
+    pub workspace: Arc<Mutex<Option<String>>>,
+}
+
+impl RoutingState {
+    pub fn new() -> Self {
+        Self {
+            routes: Arc::new(Mutex::new(Routes::default())),
+            workspace: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn set_workspace(&self, workspace: Option<String>) {
+        if let Ok(mut current) = self.workspace.lock() {
+            *current = workspace
+                .map(|w| w.trim().to_string())
+                .filter(|w| !w.is_empty());
+        }
+    }
+
+    pub fn workspace(&self) -> Option<String> {
+        self.workspace.lock().ok().and_then(|w| w.clone())
+    }
+
+    pub fn set(&self, routes: Routes) {
+        if let Ok(mut current) = self.routes.lock() {
+            *current = routes;
+        }
+    }
+
+    pub fn get(&self) -> Routes {
+        self.routes
+            .lock()
+            .map(|routes| routes.clone())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_layout() {
+        let url = Routes::default()
+            .url(
+                "http://127.0.0.1:18790/voice-client//",
+                Endpoint::SessionNew,
+                &[],
+            )
+            .unwrap();
+        assert_eq!(
+            url.as_str(),
+            "http://127.0.0.1:18790/voice-client/session/new"
+        );
+    }
+
+    #[test]
+    fn test_host_only_base() {
+        let url = Routes::default()
+            .url("https://gw.example.com/", Endpoint::Profiles, &[])
+            .unwrap();
+        assert_eq!(url.as_str(), "https://gw.example.com/profiles");
+    }
+
+    #[test]
+    fn test_session_id_is_percent_encoded() {
+        let url = Routes::default()
+            .url(
+                "http://gw/voice-client",
+                Endpoint::Audio,
+                &[("sessionId", "a&b=c#d")],
+            )
+            .unwrap();
+        assert_eq!(
+            url.as_str(),
+            "http://gw/voice-client/audio?sessionId=a%26b%3Dc%23d"
+        );
+    }
+
+    #[test]
+    fn test_prefixed_template_and_override() {
+        let overrides = HashMap::from([("audio".to_string(), "{base}/v1/upload".to_string())]);
+        let routes = Routes::new("{base}/v1/{endpoint}", &overrides).unwrap();
+
+        let session = routes
+            .url("https://proxy/oc", Endpoint::Session, &[])
+            .unwrap();
+        assert_eq!(session.as_str(), "https://proxy/oc/v1/session");
+        let audio = routes
+            .url("https://proxy/oc", Endpoint::Audio, &[])
+            .unwrap();
+        assert_eq!(audio.as_str(), "https://proxy/oc/v1/upload");
+    }
+
+    #[test]
+    fn test_invalid_templates_rejected() {
+        assert!(Routes::new("{base}/v1", &HashMap::new()).is_err());
+        assert!(Routes::new("ftp://{endpoint}", &HashMap::new()).is_err());
+        let unknown = HashMap::from([("upload".to_string(), "{base}/up".to_string())]);
+        assert!(Routes::new(DEFAULT_TEMPLATE, &unknown).is_err());
+    }
+}
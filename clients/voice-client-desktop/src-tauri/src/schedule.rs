@@ -0,0 +1,313 @@
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{Datelike, Local, NaiveDateTime, NaiveTime, TimeDelta, Weekday};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::events;
+use crate::idle::PresenceState;
+use crate::types::{ListeningPauseReason, ScheduleEvent, ScheduledRecording};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// Longest capture a schedule may ask for
+pub const MAX_DURATION_MINS: u32 = 240;
+
+pub fn parse_time(time: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(time, "%H:%M")
+        .map_err(|_| format!("Invalid schedule time '{time}', expected HH:MM"))
+}
+
+fn parse_day(day: &str) -> Result<Weekday, String> {
+    day.parse::<Weekday>()
+        .map_err(|_| format!("Invalid weekday '{day}', expected mon, tue, ..."))
+}
+
+/// Reject schedules the monitor couldn't run
+pub fn validate(schedules: &[ScheduledRecording]) -> Result<(), String> {
+    let mut names = HashSet::new();
+    for schedule in schedules {
+        if schedule.name.trim().is_empty() {
+            return Err("Scheduled recordings need a name".to_string());
+        }
+        if !names.insert(schedule.name.as_str()) {
+            return Err(format!("Duplicate scheduled recording '{}'", schedule.name));
+        }
+        parse_time(&schedule.time)?;
+        for day in &schedule.days {
+            parse_day(day)?;
+        }
+        if schedule.duration_mins == 0 || schedule.duration_mins > MAX_DURATION_MINS {
+            return Err(format!(
+                "'{}' must last 1-{MAX_DURATION_MINS} minutes",
+                schedule.name
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Starts of `schedule` from yesterday to tomorrow, so runs crossing midnight
+/// and notices due before midnight are both seen
+fn occurrences(schedule: &ScheduledRecording, now: NaiveDateTime) -> Vec<NaiveDateTime> {
+    let Ok(time) = parse_time(&schedule.time) else {
+        return Vec::new();
+    };
+    let days: Vec<Weekday> = schedule
+        .days
+        .iter()
+        .filter_map(|day| parse_day(day).ok())
+        .collect();
+    (-1..=1)
+        .filter_map(|offset| now.date().checked_add_signed(TimeDelta::days(offset)))
+        .filter(|date| days.is_empty() || days.contains(&date.weekday()))
+        .map(|date| date.and_time(time))
+        .collect()
+}
+
+/// One run of a schedule: its name and start
+type Occurrence = (String, NaiveDateTime);
+
+/// Decides, tick by tick, when schedules are announced, started and stopped.
+/// Only one scheduled capture runs at a time, and none starts while `locked`; a run
+/// held back that way starts once the screen is unlocked, if it's still on.
+#[derive(Debug, Default)]
+pub struct Scheduler {
+    notified: HashSet<Occurrence>,
+    started: HashSet<Occurrence>,
+    /// The running capture and when it stops
+    active: Option<(String, NaiveDateTime)>,
+}
+
+impl Scheduler {
+    pub fn tick(
+        &mut self,
+        schedules: &[ScheduledRecording],
+        now: NaiveDateTime,
+        notice: Duration,
+        locked: bool,
+    ) -> Vec<ScheduleEvent> {
+        let mut events = Vec::new();
+        if let Some((name, end)) = &self.active {
+            let still_scheduled = schedules.iter().any(|s| s.enabled && &s.name == name);
+            if now >= *end || !still_scheduled {
+                events.push(ScheduleEvent::Stop { name: name.clone() });
+                self.active = None;
+            }
+        }
+
+        let notice = TimeDelta::from_std(notice).unwrap_or_default();
+        for schedule in schedules.iter().filter(|s| s.enabled) {
+            let duration = TimeDelta::minutes(schedule.duration_mins.into());
+            for start in occurrences(schedule, now) {
+                let name = schedule.name.clone();
+                let key = (name.clone(), start);
+                if now < start && now >= start - notice && self.notified.insert(key.clone()) {
+                    events.push(ScheduleEvent::Upcoming {
+                        name: name.clone(),
+                        in_secs: (start - now).num_seconds() as u64,
+                    });
+                }
+                // After a restart mid-run this starts late and still stops on time
+                if now >= start
+                    && now < start + duration
+                    && self.active.is_none()
+                    && !locked
+                    && self.started.insert(key)
+                {
+                    self.active = Some((name.clone(), start + duration));
+                    events.push(ScheduleEvent::Start { name });
+                }
+            }
+        }
+
+        // Runs older than this can no longer fire
+        let horizon = now - TimeDelta::days(2);
+        self.notified.retain(|(_, start)| *start > horizon);
+        self.started.retain(|(_, start)| *start > horizon);
+        events
+    }
+}
+
+pub struct ScheduleState {
+    pub schedules: Arc<Mutex<Vec<ScheduledRecording>>>,
+    pub notice: Arc<Mutex<Duration>>,
+    pub scheduler: Arc<Mutex<Scheduler>>,
+}
+
+impl ScheduleState {
+    pub fn new() -> Self {
+        Self {
+            schedules: Arc::new(Mutex::new(Vec::new())),
+            notice: Arc::new(Mutex::new(Duration::ZERO)),
+            scheduler: Arc::new(Mutex::new(Scheduler::default())),
+        }
+    }
+
+    pub fn configure(&self, schedules: Vec<ScheduledRecording>, notice: Duration) {
+        if let Ok(mut current) = self.schedules.lock() {
+            *current = schedules;
+        }
+        if let Ok(mut current) = self.notice.lock() {
+            *current = notice;
+        }
+    }
+}
+
+fn dispatch(app: &AppHandle, event: ScheduleEvent) {
+    // Bring the popup up so the warning is actually seen
+    if let ScheduleEvent::Upcoming { .. } = event {
+        if let Some(window) = app.get_webview_window("popup") {
+            let _ = window.show();
+        }
+    }
+    events::record_state(app, "scheduled-recording", &event);
+    let _ = app.emit("scheduled-recording", &event);
+}
+
+/// Check the schedules every few seconds and emit "scheduled-recording" events;
+/// the popup starts and stops the capture in response
+pub async fn monitor(app: AppHandle) {
+    loop {
+        tokio::time::sleep(POLL_INTERVAL).await;
+
+        let state = app.state::<ScheduleState>();
+        let schedules = state
+            .schedules
+            .lock()
+            .map(|s| s.clone())
+            .unwrap_or_default();
+        let notice = state.notice.lock().map(|n| *n).unwrap_or_default();
+        // Never open the mic behind a lock screen
+        let locked =
+            app.state::<PresenceState>().pause_reason() == Some(ListeningPauseReason::Locked);
+        let due = match state.scheduler.lock() {
+            Ok(mut scheduler) => {
+                scheduler.tick(&schedules, Local::now().naive_local(), notice, locked)
+            }
+            Err(_) => continue,
+        };
+        for event in due {
+            dispatch(&app, event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn standup() -> ScheduledRecording {
+        ScheduledRecording {
+            name: "standup".to_string(),
+            time: "10:00".to_string(),
+            days: vec!["mon".to_string(), "fri".to_string()],
+            duration_mins: 15,
+            enabled: true,
+        }
+    }
+
+    /// 2026-10-16 is a Friday
+    fn at(time: &str) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 10, 16)
+            .unwrap()
+            .and_time(parse_time(time).unwrap())
+    }
+
+    #[test]
+    fn test_notice_start_and_stop() {
+        let schedules = vec![standup()];
+        let notice = Duration::from_secs(60);
+        let mut scheduler = Scheduler::default();
+
+        assert!(scheduler
+            .tick(&schedules, at("09:58"), notice, false)
+            .is_empty());
+        assert_eq!(
+            scheduler.tick(&schedules, at("09:59"), notice, false),
+            vec![ScheduleEvent::Upcoming {
+                name: "standup".to_string(),
+                in_secs: 60
+            }]
+        );
+        // Announced once
+        assert!(scheduler
+            .tick(&schedules, at("09:59"), notice, false)
+            .is_empty());
+        let start = vec![ScheduleEvent::Start {
+            name: "standup".to_string(),
+        }];
+        assert_eq!(
+            scheduler.tick(&schedules, at("10:00"), notice, false),
+            start
+        );
+        assert!(scheduler
+            .tick(&schedules, at("10:10"), notice, false)
+            .is_empty());
+        assert_eq!(
+            scheduler.tick(&schedules, at("10:15"), notice, false),
+            vec![ScheduleEvent::Stop {
+                name: "standup".to_string()
+            }]
+        );
+        // Doesn't start again in the same window
+        assert!(scheduler
+            .tick(&schedules, at("10:15"), notice, false)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_restart_mid_run_starts_late_and_other_days_are_skipped() {
+        let mut scheduler = Scheduler::default();
+        let events = scheduler.tick(&[standup()], at("10:07"), Duration::ZERO, false);
+        assert_eq!(events.len(), 1);
+        assert_eq!(
+            scheduler.active.as_ref().map(|(_, end)| *end),
+            Some(at("10:15"))
+        );
+
+        let thursday = ScheduledRecording {
+            days: vec!["thu".to_string()],
+            ..standup()
+        };
+        let mut scheduler = Scheduler::default();
+        assert!(scheduler
+            .tick(&[thursday], at("10:01"), Duration::ZERO, false)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_locked_start_waits_for_unlock() {
+        let mut scheduler = Scheduler::default();
+        assert!(scheduler
+            .tick(&[standup()], at("10:00"), Duration::ZERO, true)
+            .is_empty());
+        assert_eq!(
+            scheduler.tick(&[standup()], at("10:05"), Duration::ZERO, false),
+            vec![ScheduleEvent::Start {
+                name: "standup".to_string()
+            }]
+        );
+        assert_eq!(
+            scheduler.active.as_ref().map(|(_, end)| *end),
+            Some(at("10:15"))
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(validate(&[standup()]).is_ok());
+        let bad_time = ScheduledRecording {
+            time: "25:00".to_string(),
+            ..standup()
+        };
+        assert!(validate(&[bad_time]).is_err());
+        assert!(validate(&[standup(), standup()]).is_err());
+        let empty = ScheduledRecording {
+            duration_mins: 0,
+            ..standup()
+        };
+        assert!(validate(&[empty]).is_err());
+    }
+}
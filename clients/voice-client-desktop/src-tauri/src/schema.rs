@@ -0,0 +1,658 @@
+use schemars::gen::{SchemaGenerator, SchemaSettings};
+use schemars::schema::Schema;
+use serde_json::{json, Map, Value};
+
+use crate::error::AppError;
+use crate::types::{
+    A11yPrefs, ActiveSession, AppSettings, ArchivedRecording, AudioDevice, AudioDevicesChanged,
+    AudioHost, AutoStop, CaptureLimitReached, CompletedTurn, ConnectionDiagnosis, ConnectionResult,
+    DeviceCapabilities, DeviceFallback, DictationUtterance, FailedSend, FeedbackRating,
+    FileTranscriptionEvent, GatewayCapabilities, HistorySearchResult, KeywordHit,
+    LearnedCorrection, ListeningPaused, LoggedEvent, MicClipping, MicLevel, MicTestReport,
+    MicUsage, MicrophoneSwitched, PlaybackStatus, PrivacyMode, PushToTalkState, RecordingCountdown,
+    RecordingPause, RemotePairing, ReplySegments, ResponseRouteFailed, ResponseTimeout,
+    ScheduleEvent, ScrollDirection, SelfCheckReport, SessionOverview, SessionResponse,
+    SettingsRejected, StateRecovered, StorageUsage, TaskHealth, TranscriptPage, TranscriptPreview,
+    TriggerSource, TriggerSuppressed, TurnContext, UnreadCount, VoiceEvent, VoiceprintStatus,
+    WakeWordDetected, WaveformChunk, WebhookFailed,
+};
+
+fn value(schema: Schema) -> Value {
+    serde_json::to_value(schema).unwrap_or(Value::Null)
+}
+
+/// A command's arguments (as named in `invoke`), success value and error
+fn command(args: Vec<(&str, Schema)>, returns: Schema, error: Schema) -> Value {
+    let args: Map<String, Value> = args
+        .into_iter()
+        .map(|(name, schema)| (name.to_string(), value(schema)))
+        .collect();
+    json!({ "args": args, "returns": value(returns), "error": value(error) })
+}
+
+/// JSON Schema (draft-07) for everything crossing the webview boundary: event payloads
+/// under `x-events`, commands under `x-commands`, and the types both use in `definitions`
+pub fn export() -> Value {
+    let mut g: SchemaGenerator = SchemaSettings::draft07().into_generator();
+
+    let events = [
+        ("voice-event", g.subschema_for::<VoiceEvent>()),
+        ("conversation-event", g.subschema_for::<VoiceEvent>()),
+        ("turn-completed", g.subschema_for::<CompletedTurn>()),
+        ("reply-segments", g.subschema_for::<ReplySegments>()),
+        ("gateway-error", g.subschema_for::<AppError>()),
+        ("keyword-hit", g.subschema_for::<KeywordHit>()),
+        ("scheduled-recording", g.subschema_for::<ScheduleEvent>()),
+        (
+            "recording-countdown",
+            g.subschema_for::<RecordingCountdown>(),
+        ),
+        ("state-recovered", g.subschema_for::<StateRecovered>()),
+        ("recording-pause", g.subschema_for::<RecordingPause>()),
+        ("auto-stop", g.subschema_for::<AutoStop>()),
+        ("mic-level", g.subschema_for::<MicLevel>()),
+        ("mic-clipping", g.subschema_for::<MicClipping>()),
+        ("waveform-chunk", g.subschema_for::<WaveformChunk>()),
+        (
+            "audio-devices-changed",
+            g.subschema_for::<AudioDevicesChanged>(),
+        ),
+        (
+            "microphone-switched",
+            g.subschema_for::<MicrophoneSwitched>(),
+        ),
+        ("device-fallback", g.subschema_for::<DeviceFallback>()),
+        (
+            "capture-limit-reached",
+            g.subschema_for::<CaptureLimitReached>(),
+        ),
+        ("webhook-failed", g.subschema_for::<WebhookFailed>()),
+        (
+            "response-route-failed",
+            g.subschema_for::<ResponseRouteFailed>(),
+        ),
+        ("response-timeout", g.subschema_for::<ResponseTimeout>()),
+        ("storage-warning", g.subschema_for::<StorageUsage>()),
+        ("privacy-mode", g.subschema_for::<PrivacyMode>()),
+        ("unread-changed", g.subschema_for::<UnreadCount>()),
+        (
+            "file-transcription",
+            g.subschema_for::<FileTranscriptionEvent>(),
+        ),
+        ("push-to-talk", g.subschema_for::<PushToTalkState>()),
+        ("quick-talk", g.subschema_for::<PushToTalkState>()),
+        ("remote-push-to-talk", g.subschema_for::<PushToTalkState>()),
+        ("trigger-suppressed", g.subschema_for::<TriggerSuppressed>()),
+        ("playback-state", g.subschema_for::<PlaybackStatus>()),
+        ("listening-paused", g.subschema_for::<ListeningPaused>()),
+        ("listening-resumed", g.subschema_for::<()>()),
+        ("a11y-prefs-changed", g.subschema_for::<A11yPrefs>()),
+        ("settings-changed", g.subschema_for::<AppSettings>()),
+        ("settings-rejected", g.subschema_for::<SettingsRejected>()),
+        ("failed-sends-changed", g.subschema_for::<Vec<FailedSend>>()),
+        (
+            "recordings-changed",
+            g.subschema_for::<Vec<ArchivedRecording>>(),
+        ),
+        ("context-captured", g.subschema_for::<TurnContext>()),
+        ("context-sharing-changed", g.subschema_for::<bool>()),
+        ("popup-action", g.subschema_for::<String>()),
+        ("popup-scroll", g.subschema_for::<ScrollDirection>()),
+        ("macro-failed", g.subschema_for::<String>()),
+        ("wake-word", g.subschema_for::<WakeWordDetected>()),
+        ("wake-word-error", g.subschema_for::<String>()),
+        (
+            "dictation-utterance",
+            g.subschema_for::<DictationUtterance>(),
+        ),
+        ("dictation-changed", g.subschema_for::<bool>()),
+        ("transcript-preview", g.subschema_for::<TranscriptPreview>()),
+        (
+            "gateway-capabilities",
+            g.subschema_for::<GatewayCapabilities>(),
+        ),
+    ];
+    let events: Map<String, Value> = events
+        .into_iter()
+        .map(|(name, schema)| (name.to_string(), value(schema)))
+        .collect();
+
+    let text = g.subschema_for::<String>();
+    let app_error = g.subschema_for::<AppError>();
+    let none = g.subschema_for::<()>();
+    let playback = g.subschema_for::<PlaybackStatus>();
+    let voiceprint = g.subschema_for::<VoiceprintStatus>();
+    let optional_text = g.subschema_for::<Option<String>>();
+    let commands = [
+        (
+            "list_audio_devices",
+            command(vec![], g.subschema_for::<Vec<AudioDevice>>(), text.clone()),
+        ),
+        (
+            "list_audio_hosts",
+            command(vec![], g.subschema_for::<Vec<AudioHost>>(), text.clone()),
+        ),
+        (
+            "get_device_capabilities",
+            command(
+                vec![("deviceId", optional_text.clone())],
+                g.subschema_for::<DeviceCapabilities>(),
+                text.clone(),
+            ),
+        ),
+        (
+            "start_recording",
+            command(
+                vec![
+                    ("deviceId", optional_text.clone()),
+                    ("source", g.subschema_for::<Option<TriggerSource>>()),
+                    ("channelIndex", g.subschema_for::<Option<u16>>()),
+                ],
+                none.clone(),
+                text.clone(),
+            ),
+        ),
+        (
+            "cancel_countdown",
+            command(vec![], none.clone(), text.clone()),
+        ),
+        (
+            "stop_and_send",
+            command(
+                vec![
+                    ("baseUrl", text.clone()),
+                    ("sessionId", text.clone()),
+                    ("profileName", text.clone()),
+                    ("sessionKey", optional_text.clone()),
+                    ("responseLanguage", optional_text.clone()),
+                ],
+                none.clone(),
+                app_error.clone(),
+            ),
+        ),
+        (
+            "start_dictation",
+            command(
+                vec![
+                    ("deviceId", optional_text.clone()),
+                    ("baseUrl", text.clone()),
+                    ("sessionId", text.clone()),
+                    ("profileName", text.clone()),
+                    ("sessionKey", optional_text.clone()),
+                    ("responseLanguage", optional_text.clone()),
+                ],
+                none.clone(),
+                text.clone(),
+            ),
+        ),
+        (
+            "stop_dictation",
+            command(vec![], none.clone(), text.clone()),
+        ),
+        (
+            "pause_recording",
+            command(vec![], none.clone(), text.clone()),
+        ),
+        (
+            "resume_recording",
+            command(vec![], none.clone(), text.clone()),
+        ),
+        (
+            "start_voiceprint_enrollment",
+            command(
+                vec![("deviceId", optional_text.clone())],
+                none.clone(),
+                text.clone(),
+            ),
+        ),
+        (
+            "finish_voiceprint_enrollment",
+            command(vec![], voiceprint.clone(), text.clone()),
+        ),
+        (
+            "clear_voiceprint",
+            command(vec![], voiceprint.clone(), text.clone()),
+        ),
+        (
+            "get_voiceprint_status",
+            command(vec![], voiceprint, text.clone()),
+        ),
+        (
+            "enqueue_audio",
+            command(
+                vec![("wavBytes", g.subschema_for::<Vec<u8>>())],
+                playback.clone(),
+                text.clone(),
+            ),
+        ),
+        (
+            "skip_playback",
+            command(vec![], playback.clone(), text.clone()),
+        ),
+        (
+            "stop_all_playback",
+            command(vec![], playback.clone(), text.clone()),
+        ),
+        (
+            "resume_playback",
+            command(vec![], playback.clone(), text.clone()),
+        ),
+        (
+            "play_audio",
+            command(
+                vec![
+                    ("data", optional_text.clone()),
+                    ("url", optional_text.clone()),
+                ],
+                playback.clone(),
+                text.clone(),
+            ),
+        ),
+        (
+            "stop_playback",
+            command(vec![], playback.clone(), text.clone()),
+        ),
+        (
+            "get_playback_status",
+            command(vec![], playback.clone(), text.clone()),
+        ),
+        (
+            "set_playback_volume",
+            command(
+                vec![("volume", g.subschema_for::<f32>())],
+                playback,
+                text.clone(),
+            ),
+        ),
+        (
+            "create_session",
+            command(
+                vec![("baseUrl", text.clone()), ("profileName", text.clone())],
+                g.subschema_for::<SessionResponse>(),
+                app_error.clone(),
+            ),
+        ),
+        (
+            "restore_last_session",
+            command(
+                vec![("baseUrl", text.clone())],
+                g.subschema_for::<Option<ActiveSession>>(),
+                app_error.clone(),
+            ),
+        ),
+        (
+            "test_connection",
+            command(
+                vec![
+                    ("baseUrl", text.clone()),
+                    ("endpointTemplate", optional_text.clone()),
+                    ("workspace", optional_text.clone()),
+                ],
+                g.subschema_for::<ConnectionResult>(),
+                text.clone(),
+            ),
+        ),
+        (
+            "diagnose_connection",
+            command(
+                vec![
+                    ("baseUrl", text.clone()),
+                    ("endpointTemplate", optional_text.clone()),
+                    ("workspace", optional_text.clone()),
+                ],
+                g.subschema_for::<ConnectionDiagnosis>(),
+                text.clone(),
+            ),
+        ),
+        (
+            "get_gateway_capabilities",
+            command(
+                vec![],
+                g.subschema_for::<Option<GatewayCapabilities>>(),
+                text.clone(),
+            ),
+        ),
+        (
+            "probe_gateway_capabilities",
+            command(
+                vec![("baseUrl", text.clone())],
+                g.subschema_for::<GatewayCapabilities>(),
+                text.clone(),
+            ),
+        ),
+        (
+            "load_settings",
+            command(vec![], g.subschema_for::<AppSettings>(), text.clone()),
+        ),
+        (
+            "save_settings",
+            command(
+                vec![("settings", g.subschema_for::<AppSettings>())],
+                none.clone(),
+                text.clone(),
+            ),
+        ),
+        (
+            "describe_hotkey",
+            command(
+                vec![("hotkey", optional_text.clone())],
+                optional_text.clone(),
+                text.clone(),
+            ),
+        ),
+        (
+            "set_popup_pinned",
+            command(
+                vec![("pinned", g.subschema_for::<bool>())],
+                none.clone(),
+                text.clone(),
+            ),
+        ),
+        (
+            "get_listening_pause",
+            command(
+                vec![],
+                g.subschema_for::<Option<ListeningPaused>>(),
+                text.clone(),
+            ),
+        ),
+        (
+            "get_system_a11y_prefs",
+            command(vec![], g.subschema_for::<A11yPrefs>(), text.clone()),
+        ),
+        (
+            "get_task_health",
+            command(vec![], g.subschema_for::<Vec<TaskHealth>>(), text.clone()),
+        ),
+        (
+            "get_mic_usage_log",
+            command(vec![], g.subschema_for::<Vec<MicUsage>>(), text.clone()),
+        ),
+        (
+            "take_launch_recording",
+            command(vec![], g.subschema_for::<bool>(), text.clone()),
+        ),
+        (
+            "is_demo_mode",
+            command(vec![], g.subschema_for::<bool>(), text.clone()),
+        ),
+        (
+            "get_unread_count",
+            command(vec![], g.subschema_for::<u32>(), text.clone()),
+        ),
+        ("mark_all_read", command(vec![], none.clone(), text.clone())),
+        (
+            "set_privacy_mode",
+            command(
+                vec![("enabled", g.subschema_for::<bool>())],
+                none.clone(),
+                text.clone(),
+            ),
+        ),
+        (
+            "get_privacy_mode",
+            command(vec![], g.subschema_for::<bool>(), text.clone()),
+        ),
+        (
+            "get_event_log",
+            command(
+                vec![("limit", g.subschema_for::<Option<usize>>())],
+                g.subschema_for::<Vec<LoggedEvent>>(),
+                text.clone(),
+            ),
+        ),
+        (
+            "get_last_turn",
+            command(
+                vec![],
+                g.subschema_for::<Option<CompletedTurn>>(),
+                text.clone(),
+            ),
+        ),
+        (
+            "get_sessions_overview",
+            command(
+                vec![],
+                g.subschema_for::<Vec<SessionOverview>>(),
+                text.clone(),
+            ),
+        ),
+        (
+            "get_session_transcript",
+            command(
+                vec![
+                    ("sessionId", text.clone()),
+                    ("offset", g.subschema_for::<Option<usize>>()),
+                    ("limit", g.subschema_for::<Option<usize>>()),
+                ],
+                g.subschema_for::<TranscriptPage>(),
+                text.clone(),
+            ),
+        ),
+        (
+            "search_remote_history",
+            command(
+                vec![("query", text.clone())],
+                g.subschema_for::<HistorySearchResult>(),
+                text.clone(),
+            ),
+        ),
+        (
+            "get_remote_session",
+            command(
+                vec![("id", text.clone())],
+                g.subschema_for::<TranscriptPage>(),
+                text.clone(),
+            ),
+        ),
+        (
+            "submit_feedback",
+            command(
+                vec![
+                    ("turnId", text.clone()),
+                    ("rating", g.subschema_for::<FeedbackRating>()),
+                    ("comment", optional_text.clone()),
+                ],
+                none.clone(),
+                app_error.clone(),
+            ),
+        ),
+        (
+            "show_more_of_reply",
+            command(
+                vec![
+                    ("turnId", text.clone()),
+                    ("index", g.subschema_for::<usize>()),
+                ],
+                optional_text.clone(),
+                text.clone(),
+            ),
+        ),
+        (
+            "learn_correction",
+            command(
+                vec![("original", text.clone()), ("corrected", text.clone())],
+                none.clone(),
+                text.clone(),
+            ),
+        ),
+        (
+            "list_corrections",
+            command(
+                vec![],
+                g.subschema_for::<Vec<LearnedCorrection>>(),
+                text.clone(),
+            ),
+        ),
+        (
+            "delete_correction",
+            command(
+                vec![("from", text.clone()), ("to", text.clone())],
+                none.clone(),
+                text.clone(),
+            ),
+        ),
+        (
+            "list_failed_sends",
+            command(vec![], g.subschema_for::<Vec<FailedSend>>(), text.clone()),
+        ),
+        (
+            "retry_failed",
+            command(vec![("id", text.clone())], none.clone(), app_error.clone()),
+        ),
+        (
+            "resend_last_audio",
+            command(vec![], none.clone(), app_error.clone()),
+        ),
+        (
+            "discard_failed",
+            command(vec![("id", text.clone())], none.clone(), text.clone()),
+        ),
+        (
+            "list_recordings",
+            command(
+                vec![],
+                g.subschema_for::<Vec<ArchivedRecording>>(),
+                text.clone(),
+            ),
+        ),
+        (
+            "delete_recording",
+            command(vec![("id", text.clone())], none.clone(), text.clone()),
+        ),
+        (
+            "transcribe_file",
+            command(vec![("path", text.clone())], none.clone(), app_error),
+        ),
+        (
+            "cancel_file_transcription",
+            command(vec![], none.clone(), text.clone()),
+        ),
+        (
+            "tail_http_log",
+            command(
+                vec![("lines", g.subschema_for::<Option<usize>>())],
+                g.subschema_for::<Vec<String>>(),
+                text.clone(),
+            ),
+        ),
+        (
+            "run_self_check",
+            command(vec![], g.subschema_for::<SelfCheckReport>(), text.clone()),
+        ),
+        (
+            "test_microphone",
+            command(
+                vec![("deviceId", optional_text.clone())],
+                g.subschema_for::<MicTestReport>(),
+                text.clone(),
+            ),
+        ),
+        (
+            "get_storage_usage",
+            command(vec![], g.subschema_for::<StorageUsage>(), text.clone()),
+        ),
+        (
+            "get_remote_pairing",
+            command(vec![], g.subschema_for::<RemotePairing>(), text.clone()),
+        ),
+        (
+            "reset_remote_pairing",
+            command(vec![], g.subschema_for::<RemotePairing>(), text.clone()),
+        ),
+        (
+            "open_settings_window",
+            command(vec![], none.clone(), text.clone()),
+        ),
+        (
+            "open_history_window",
+            command(vec![], none.clone(), text.clone()),
+        ),
+        (
+            "open_conversation_window",
+            command(
+                vec![("sessionId", text.clone())],
+                none.clone(),
+                text.clone(),
+            ),
+        ),
+        ("focus_popup", command(vec![], none.clone(), text.clone())),
+        (
+            "scroll_history",
+            command(
+                vec![("direction", g.subschema_for::<ScrollDirection>())],
+                none.clone(),
+                text.clone(),
+            ),
+        ),
+        (
+            "invoke_action",
+            command(vec![("actionId", text.clone())], none.clone(), text.clone()),
+        ),
+        (
+            "run_macro",
+            command(vec![("name", text.clone())], none.clone(), text.clone()),
+        ),
+        ("quit_app", command(vec![], none, text)),
+    ];
+    let commands: Map<String, Value> = commands
+        .into_iter()
+        .map(|(name, command)| (name.to_string(), command))
+        .collect();
+
+    let definitions: Map<String, Value> = g
+        .take_definitions()
+        .into_iter()
+        .map(|(name, schema)| (name, value(schema)))
+        .collect();
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "OpenClaw Voice Client IPC",
+        "x-events": events,
+        "x-commands": commands,
+        "definitions": definitions,
+    })
+}
+
+/// `export()` as the pretty-printed text checked in as `schema/ipc.schema.json`
+pub fn export_pretty() -> String {
+    let mut text = serde_json::to_string_pretty(&export()).unwrap_or_default();
+    text.push('\n');
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA_FILE: &str = "schema/ipc.schema.json";
+
+    #[test]
+    fn test_checked_in_schema_is_current() {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(SCHEMA_FILE);
+        let checked_in = std::fs::read_to_string(&path).unwrap_or_default();
+        assert!(
+            checked_in == export_pretty(),
+            "{SCHEMA_FILE} is out of date; regenerate it with \
+             `cargo run --bin openclaw-voice -- schema > {SCHEMA_FILE}`"
+        );
+    }
+
+    #[test]
+    fn test_refs_resolve() {
+        let schema = export();
+        let text = schema.to_string();
+        let definitions = schema["definitions"].as_object().unwrap();
+        for name in text
+            .split("\"#/definitions/")
+            .skip(1)
+            .filter_map(|rest| rest.split('"').next())
+        {
+            assert!(definitions.contains_key(name), "missing definition {name}");
+        }
+        assert_eq!(
+            schema["x-events"]["voice-event"]["$ref"],
+            "#/definitions/VoiceEvent"
+        );
+    }
+}
@@ -0,0 +1,70 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::session::SessionState;
+use crate::types::{CompletedTurn, ReplySegments};
+use crate::{actions, events, turns};
+
+/// Finished replies cut into notification-sized segments, for notification and speech
+/// consumers that can't show a long reply at once
+pub struct SegmentState {
+    /// 0 keeps replies whole
+    pub max_chars: Arc<AtomicUsize>,
+    /// "Show more" opens the popup rather than handing out the next segment
+    pub show_more_opens_popup: Arc<AtomicBool>,
+}
+
+impl SegmentState {
+    pub fn new() -> Self {
+        Self {
+            max_chars: Arc::new(AtomicUsize::new(0)),
+            show_more_opens_popup: Arc::new(AtomicBool::new(true)),
+        }
+    }
+
+    pub fn configure(&self, max_chars: usize, show_more_opens_popup: bool) {
+        self.max_chars.store(max_chars, Ordering::SeqCst);
+        self.show_more_opens_popup
+            .store(show_more_opens_popup, Ordering::SeqCst);
+    }
+
+    fn segments(&self, turn: &CompletedTurn) -> ReplySegments {
+        ReplySegments {
+            turn_id: turn.turn_id.clone(),
+            segments: turns::segment(&turn.reply, self.max_chars.load(Ordering::SeqCst)),
+            show_more_opens_popup: self.show_more_opens_popup.load(Ordering::SeqCst),
+        }
+    }
+}
+
+/// Emit "reply-segments" for a completed turn
+pub fn publish(app: &AppHandle, turn: &CompletedTurn) {
+    let Some(state) = app.try_state::<SegmentState>() else {
+        return;
+    };
+    let segments = state.segments(turn);
+    events::record_state(
+        app,
+        "reply-segments",
+        serde_json::json!({ "turnId": segments.turn_id, "count": segments.segments.len() }),
+    );
+    let _ = app.emit("reply-segments", &segments);
+}
+
+/// Segment `index` of the latest reply, for a consumer's "show more". With
+/// `show_more_opens_popup` the popup is opened on the whole reply instead and None returned.
+pub fn show_more(app: &AppHandle, turn_id: &str, index: usize) -> Result<Option<String>, String> {
+    let state = app.state::<SegmentState>();
+    if state.show_more_opens_popup.load(Ordering::SeqCst) {
+        actions::focus_popup(app)?;
+        return Ok(None);
+    }
+    let turn = app
+        .state::<SessionState>()
+        .last_turn()
+        .filter(|turn| turn.turn_id == turn_id)
+        .ok_or_else(|| "Only the latest reply can be continued".to_string())?;
+    Ok(state.segments(&turn).segments.into_iter().nth(index))
+}
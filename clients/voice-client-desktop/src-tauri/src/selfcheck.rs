@@ -0,0 +1,221 @@
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use tauri::{AppHandle, Manager};
+use tauri_plugin_global_shortcut::GlobalShortcutExt;
+
+use crate::api::{self, Gateway};
+use crate::audio::{self, AudioState};
+use crate::hotkey::{self, HotkeyState};
+use crate::types::{AppSettings, CheckItem, CheckStatus, MicSource, SelfCheckReport};
+use crate::{mic_usage, persist, settings};
+
+const MIC_PROBE_DURATION: Duration = Duration::from_millis(400);
+
+fn item(id: &str, label: &str, status: CheckStatus, detail: Option<String>) -> CheckItem {
+    CheckItem {
+        id: id.to_string(),
+        label: label.to_string(),
+        status,
+        detail,
+    }
+}
+
+/// Run every check and collect the results in checklist order
+pub async fn run_self_check(app: &AppHandle) -> SelfCheckReport {
+    let settings = settings::load_settings(app).unwrap_or_default();
+
+    let mut items = vec![check_input_device(&settings)];
+    items.push(check_microphone(app, &settings).await);
+    items.push(check_keyring());
+    items.push(check_store(app));
+    items.push(check_hotkey(app, &settings));
+    items.push(check_gateway(app, &settings).await);
+
+    SelfCheckReport {
+        ok: items.iter().all(|i| i.status != CheckStatus::Fail),
+        items,
+    }
+}
+
+fn check_input_device(settings: &AppSettings) -> CheckItem {
+    const ID: &str = "input-device";
+    const LABEL: &str = "Input device available";
+
+    let devices = match audio::list_audio_devices(settings.audio_host.as_deref()) {
+        Ok(devices) => devices,
+        Err(e) => return item(ID, LABEL, CheckStatus::Fail, Some(e)),
+    };
+
+    if let Some(wanted) = &settings.microphone_device_id {
+        return match devices
+            .iter()
+            .find(|d| &d.id == wanted || &d.name == wanted)
+        {
+            Some(device) => item(ID, LABEL, CheckStatus::Pass, Some(device.name.clone())),
+            None => item(
+                ID,
+                LABEL,
+                CheckStatus::Fail,
+                Some(format!("Selected microphone '{wanted}' is not connected")),
+            ),
+        };
+    }
+
+    match devices.iter().find(|d| d.is_default) {
+        Some(device) => item(ID, LABEL, CheckStatus::Pass, Some(device.name.clone())),
+        None => item(
+            ID,
+            LABEL,
+            CheckStatus::Fail,
+            Some("No default input device".to_string()),
+        ),
+    }
+}
+
+async fn check_microphone(app: &AppHandle, settings: &AppSettings) -> CheckItem {
+    const ID: &str = "microphone";
+    const LABEL: &str = "Microphone access";
+
+    // Opening a second stream while recording would fight over the device
+    if app
+        .state::<AudioState>()
+        .is_recording
+        .load(Ordering::SeqCst)
+    {
+        return item(
+            ID,
+            LABEL,
+            CheckStatus::Skipped,
+            Some("Recording in progress".to_string()),
+        );
+    }
+
+    let host = settings.audio_host.clone();
+    let device_id = settings.microphone_device_id.clone();
+    let handle = app.clone();
+    let probe = tauri::async_runtime::spawn_blocking(move || {
+        audio::probe_input(
+            host,
+            device_id.as_deref(),
+            None,
+            MIC_PROBE_DURATION,
+            |probe| {
+                let device = probe.device_name();
+                probe.mark_in_use(mic_usage::opened(
+                    &handle,
+                    MicSource::SelfCheck,
+                    None,
+                    device,
+                ));
+            },
+        )
+        .map(|probe| (probe.samples.len(), probe.peak()))
+    })
+    .await
+    .unwrap_or_else(|e| Err(format!("Probe task failed: {e}")));
+
+    match probe {
+        Ok((0, _)) => item(
+            ID,
+            LABEL,
+            CheckStatus::Fail,
+            Some("The microphone delivered no audio — access may be blocked by the OS".to_string()),
+        ),
+        // macOS feeds digital silence to apps without microphone permission
+        Ok((_, 0.0)) => item(
+            ID,
+            LABEL,
+            CheckStatus::Warn,
+            Some("Only silence was captured — check microphone permission and mute".to_string()),
+        ),
+        Ok(_) => item(ID, LABEL, CheckStatus::Pass, None),
+        Err(e) => item(ID, LABEL, CheckStatus::Fail, Some(e)),
+    }
+}
+
+fn check_keyring() -> CheckItem {
+    const ID: &str = "keyring";
+    const LABEL: &str = "Credential storage";
+
+    match settings::load_token() {
+        Ok(token) if token.is_empty() => item(
+            ID,
+            LABEL,
+            CheckStatus::Pass,
+            Some("No token stored".to_string()),
+        ),
+        Ok(_) => item(ID, LABEL, CheckStatus::Pass, None),
+        Err(e) => item(ID, LABEL, CheckStatus::Fail, Some(e)),
+    }
+}
+
+fn check_store(app: &AppHandle) -> CheckItem {
+    const ID: &str = "settings-store";
+    const LABEL: &str = "Settings file writable";
+
+    // Rewriting the current contents proves the directory and file are writable
+    match persist::open_store(app).and_then(|store| persist::save_store(app, &store)) {
+        Ok(()) => item(ID, LABEL, CheckStatus::Pass, None),
+        Err(e) => item(ID, LABEL, CheckStatus::Fail, Some(e)),
+    }
+}
+
+fn check_hotkey(app: &AppHandle, settings: &AppSettings) -> CheckItem {
+    const ID: &str = "hotkey";
+    const LABEL: &str = "Push-to-talk hotkey";
+
+    let Some(wanted) = settings
+        .push_to_talk_hotkey
+        .as_deref()
+        .filter(|h| !h.is_empty())
+    else {
+        return item(
+            ID,
+            LABEL,
+            CheckStatus::Skipped,
+            Some("No hotkey configured".to_string()),
+        );
+    };
+
+    let shortcut = match hotkey::parse_hotkey(wanted) {
+        Ok(shortcut) => shortcut,
+        Err(e) => return item(ID, LABEL, CheckStatus::Fail, Some(e)),
+    };
+    let current = app
+        .state::<HotkeyState>()
+        .push_to_talk
+        .lock()
+        .ok()
+        .and_then(|current| *current);
+
+    if current == Some(shortcut) && app.global_shortcut().is_registered(shortcut) {
+        item(ID, LABEL, CheckStatus::Pass, Some(wanted.to_string()))
+    } else {
+        item(
+            ID,
+            LABEL,
+            CheckStatus::Fail,
+            Some(format!(
+                "'{wanted}' is not registered — another application may be using it"
+            )),
+        )
+    }
+}
+
+async fn check_gateway(app: &AppHandle, settings: &AppSettings) -> CheckItem {
+    const ID: &str = "gateway";
+    const LABEL: &str = "Gateway reachable";
+
+    let gateway = Gateway::from_app(app);
+    match api::test_connection(&gateway, &settings.gateway_url).await {
+        Ok(result) if result.success => item(
+            ID,
+            LABEL,
+            CheckStatus::Pass,
+            Some(settings.gateway_url.clone()),
+        ),
+        Ok(result) => item(ID, LABEL, CheckStatus::Fail, result.error),
+        Err(e) => item(ID, LABEL, CheckStatus::Fail, Some(e)),
+    }
+}
@@ -0,0 +1,156 @@
+use std::sync::{Arc, Mutex};
+
+use crate::api::Gateway;
+use crate::error::AppError;
+use crate::turns::TurnAggregator;
+use crate::types::{ActiveSession, CompletedTurn, VoiceEvent};
+use crate::{api, persist, turns};
+
+const LAST_SESSION_KEY: &str = "last_session";
+/// Number of user/agent events kept as recent context for a restored session
+const MAX_RECENT_EVENTS: usize = 50;
+
+/// Tracks the session the popup is currently talking to
+pub struct SessionState {
+    pub current: Arc<Mutex<Option<ActiveSession>>>,
+    pub aggregator: Arc<Mutex<TurnAggregator>>,
+    /// The most recently completed exchange, merged from its streamed deltas
+    pub last_turn: Arc<Mutex<Option<CompletedTurn>>>,
+}
+
+impl SessionState {
+    pub fn new() -> Self {
+        Self {
+            current: Arc::new(Mutex::new(None)),
+            aggregator: Arc::new(Mutex::new(TurnAggregator::new())),
+            last_turn: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Make `session_id` the active session, keeping recent context if it is unchanged
+    pub fn activate(&self, session_id: &str, profile_name: &str, gateway_url: &str) {
+        if let Ok(mut current) = self.current.lock() {
+            match current.as_mut() {
+                Some(active) if active.session_id == session_id => {
+                    active.profile_name = profile_name.to_string();
+                    active.gateway_url = gateway_url.to_string();
+                }
+                _ => {
+                    *current = Some(ActiveSession {
+                        session_id: session_id.to_string(),
+                        profile_name: profile_name.to_string(),
+                        gateway_url: gateway_url.to_string(),
+                        recent: Vec::new(),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Feed a streamed event through the turn aggregator. The active session's recent
+    /// context keeps user events and one merged reply per turn, never individual deltas.
+    /// Returns the turn when this event completed it.
+    pub fn record_event(&self, session_id: &str, event: &VoiceEvent) -> Option<CompletedTurn> {
+        let completed = self
+            .aggregator
+            .lock()
+            .ok()?
+            .push(event)
+            .map(|turn| CompletedTurn {
+                turn_id: turns::turn_id(session_id, &turn.completed_at),
+                ..turn
+            });
+        let recorded = match (event, &completed) {
+            (VoiceEvent::User { .. }, _) => Some(event.clone()),
+            (VoiceEvent::Openclaw { .. }, Some(turn)) => Some(VoiceEvent::Openclaw {
+                text: turn.reply.clone(),
+                done: true,
+                timestamp: turn.completed_at.clone(),
+            }),
+            _ => None,
+        };
+        if let (Some(recorded), Ok(mut current)) = (recorded, self.current.lock()) {
+            if let Some(active) = current.as_mut() {
+                active.recent.push(recorded);
+                let overflow = active.recent.len().saturating_sub(MAX_RECENT_EVENTS);
+                active.recent.drain(..overflow);
+            }
+        }
+        if let (Some(turn), Ok(mut last)) = (&completed, self.last_turn.lock()) {
+            *last = Some(turn.clone());
+        }
+        completed
+    }
+
+    /// Give up on the turn streaming into `session_id`, returning what arrived of it
+    pub fn abandon_turn(&self, session_id: &str, at: &str) -> Option<CompletedTurn> {
+        let turn = self.aggregator.lock().ok()?.abandon(at)?;
+        Some(CompletedTurn {
+            turn_id: turns::turn_id(session_id, &turn.completed_at),
+            ..turn
+        })
+    }
+
+    pub fn last_turn(&self) -> Option<CompletedTurn> {
+        self.last_turn.lock().ok().and_then(|last| last.clone())
+    }
+
+    pub fn snapshot(&self) -> Option<ActiveSession> {
+        self.current.lock().ok().and_then(|current| current.clone())
+    }
+}
+
+/// Write the active session to the store so it can be offered again after a restart
+pub fn persist_session<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    state: &SessionState,
+) -> Result<(), String> {
+    let store = persist::open_store(app)?;
+
+    match state.snapshot() {
+        Some(active) => {
+            let value = serde_json::to_value(&active)
+                .map_err(|e| format!("Failed to serialize session: {e}"))?;
+            store.set(LAST_SESSION_KEY, value);
+        }
+        None => {
+            store.delete(LAST_SESSION_KEY);
+        }
+    }
+
+    persist::save_store(app, &store)
+}
+
+/// Load the session saved at last shutdown and check it still exists on the gateway.
+/// Returns None (and forgets the saved session) if the gateway no longer knows it.
+pub async fn restore_last_session<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    state: &SessionState,
+    gateway: &Gateway,
+    base_url: &str,
+) -> Result<Option<ActiveSession>, AppError> {
+    let store = persist::open_store(app)?;
+
+    let saved = store
+        .get(LAST_SESSION_KEY)
+        .and_then(|v| serde_json::from_value::<ActiveSession>(v).ok());
+    let Some(mut saved) = saved else {
+        return Ok(None);
+    };
+
+    match api::get_session(gateway, base_url, &saved.session_id).await? {
+        Some(info) => {
+            saved.profile_name = info.profile_name;
+            saved.gateway_url = base_url.to_string();
+            if let Ok(mut current) = state.current.lock() {
+                *current = Some(saved.clone());
+            }
+            Ok(Some(saved))
+        }
+        None => {
+            store.delete(LAST_SESSION_KEY);
+            let _ = persist::save_store(app, &store);
+            Ok(None)
+        }
+    }
+}
@@ -1,10 +1,50 @@
+use std::time::Duration;
+
 use keyring::Entry;
 use serde_json::Value;
-use tauri_plugin_store::StoreExt;
+use tauri::{AppHandle, Manager};
 
-use crate::types::AppSettings;
+use crate::actions;
+use crate::api;
+use crate::audio::{AudioState, CaptureLimit, MeetingCapture, SilenceTrim};
+use crate::brief::BriefState;
+use crate::context;
+use crate::corrections::CorrectionState;
+use crate::countdown::CountdownState;
+use crate::cues::CueState;
+use crate::demo::DemoState;
+use crate::device_monitor::DeviceMonitorState;
+use crate::dictation::DictationState;
+use crate::effects::{self, EffectsState};
+use crate::events::{self, EventFilterState, EventLog};
+use crate::hotkey::{self, HotkeyState};
+use crate::http_log::{self, HttpLog};
+use crate::idle::PresenceState;
+use crate::keywords::{KeywordSpotter, KeywordState};
+use crate::live_upload::LiveUploadState;
+use crate::macros;
+use crate::persist;
+use crate::playback::PlaybackState;
+use crate::quality::QualityState;
+use crate::recordings::{self, RecordingArchiveState};
+use crate::remote;
+use crate::response_cache::ResponseCacheState;
+use crate::response_routes::{self, ResponseRouteState};
+use crate::routing::{Routes, RoutingState};
+use crate::schedule::ScheduleState;
+use crate::segments::SegmentState;
+use crate::storage::StorageState;
+use crate::stt::{SttConfig, SttState};
+use crate::trigger::TriggerGate;
+use crate::tts::{TtsConfig, TtsState};
+use crate::types::{AppSettings, TtsProviderKind};
+use crate::vad::VadState;
+use crate::voiceprint::VoiceprintState;
+use crate::wake_word::{self, WakeWordState};
+use crate::watchdog::WatchdogState;
+use crate::webhooks::WebhookState;
 
-const KEYRING_SERVICE: &str = "openclaw-voice-client";
+pub const KEYRING_SERVICE: &str = "openclaw-voice-client";
 const KEYRING_USERNAME: &str = "token";
 
 /// Save token to OS keyring (macOS Keychain, Windows Credential Manager, Linux libsecret)
@@ -40,9 +80,8 @@ pub fn delete_token() -> Result<(), String> {
 
 /// Load settings from tauri-plugin-store + token from keyring
 pub fn load_settings<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Result<AppSettings, String> {
-    let store = app
-        .store("settings.json")
-        .map_err(|e| format!("Failed to open store: {e}"))?;
+    let store = persist::open_store(app)?;
+    let defaults = AppSettings::default();
 
     let gateway_url = store
         .get("gateway_url")
@@ -59,16 +98,425 @@ pub fn load_settings<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Result<App
         .and_then(|v| v.as_str().map(String::from))
         .filter(|s| !s.is_empty());
 
+    let response_language = store
+        .get("response_language")
+        .and_then(|v| v.as_str().map(String::from))
+        .filter(|s| !s.is_empty());
+
+    let brief_mode = store
+        .get("brief_mode")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(defaults.brief_mode);
+
+    let brief_max_sentences = store
+        .get("brief_max_sentences")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(defaults.brief_max_sentences);
+
+    let workspace = store
+        .get("workspace")
+        .and_then(|v| v.as_str().map(String::from))
+        .filter(|s| !s.is_empty());
+
     let microphone_device_id = store
         .get("microphone_device_id")
         .and_then(|v| v.as_str().map(String::from))
         .filter(|s| !s.is_empty());
 
+    let audio_host = store
+        .get("audio_host")
+        .and_then(|v| v.as_str().map(String::from))
+        .filter(|s| !s.is_empty());
+
+    let input_channel = store
+        .get("input_channel")
+        .and_then(|v| v.as_u64())
+        .and_then(|c| u16::try_from(c).ok())
+        .filter(|c| *c > 0);
+
+    let capture_high_pass_hz = store
+        .get("capture_high_pass_hz")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(defaults.capture_high_pass_hz);
+
     let push_to_talk_hotkey = store
         .get("push_to_talk_hotkey")
         .and_then(|v| v.as_str().map(String::from))
         .filter(|s| !s.is_empty());
 
+    let keyboard_layout = store
+        .get("keyboard_layout")
+        .and_then(|v| serde_json::from_value(v).ok());
+
+    let trigger_cooldown_ms = store
+        .get("trigger_cooldown_ms")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(defaults.trigger_cooldown_ms);
+
+    let idle_pause_secs = store
+        .get("idle_pause_secs")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(defaults.idle_pause_secs);
+
+    let pause_on_lock = store
+        .get("pause_on_lock")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(defaults.pause_on_lock);
+
+    let event_log_capacity = store
+        .get("event_log_capacity")
+        .and_then(|v| v.as_u64())
+        .map(|v| v as usize)
+        .unwrap_or(defaults.event_log_capacity);
+
+    let endpoint_template = store
+        .get("endpoint_template")
+        .and_then(|v| v.as_str().map(String::from))
+        .filter(|s| !s.is_empty())
+        .unwrap_or(defaults.endpoint_template);
+
+    let endpoint_overrides = store
+        .get("endpoint_overrides")
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or(defaults.endpoint_overrides);
+
+    let http_trace = store
+        .get("http_trace")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(defaults.http_trace);
+
+    let speaker_filter = store
+        .get("speaker_filter")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(defaults.speaker_filter);
+
+    let speaker_filter_threshold = store
+        .get("speaker_filter_threshold")
+        .and_then(|v| v.as_f64())
+        .map(|v| v as f32)
+        .unwrap_or(defaults.speaker_filter_threshold);
+
+    let share_context = store
+        .get("share_context")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(defaults.share_context);
+
+    let audio_quality = store
+        .get("audio_quality")
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or(defaults.audio_quality);
+
+    let stt_providers = store
+        .get("stt_providers")
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or(defaults.stt_providers);
+
+    let whisper_command = store
+        .get("whisper_command")
+        .and_then(|v| v.as_str().map(String::from))
+        .filter(|s| !s.is_empty());
+
+    let whisper_model = store
+        .get("whisper_model")
+        .and_then(|v| v.as_str().map(String::from))
+        .filter(|s| !s.is_empty());
+
+    let whisper_preview_model = store
+        .get("whisper_preview_model")
+        .and_then(|v| v.as_str().map(String::from))
+        .filter(|s| !s.is_empty());
+
+    let prefer_local_stt = store
+        .get("prefer_local_stt")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(defaults.prefer_local_stt);
+
+    let tts_providers = store
+        .get("tts_providers")
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or(defaults.tts_providers);
+
+    let piper_command = store
+        .get("piper_command")
+        .and_then(|v| v.as_str().map(String::from))
+        .filter(|s| !s.is_empty());
+
+    let piper_model = store
+        .get("piper_model")
+        .and_then(|v| v.as_str().map(String::from))
+        .filter(|s| !s.is_empty());
+
+    let alert_keywords = store
+        .get("alert_keywords")
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or(defaults.alert_keywords);
+
+    let alert_action_items = store
+        .get("alert_action_items")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(defaults.alert_action_items);
+
+    let scheduled_recordings = store
+        .get("scheduled_recordings")
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or(defaults.scheduled_recordings);
+
+    let webhooks = store
+        .get("webhooks")
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or(defaults.webhooks);
+
+    let response_routes = store
+        .get("response_routes")
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or(defaults.response_routes);
+
+    let event_filters = store
+        .get("event_filters")
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or(defaults.event_filters);
+
+    let schedule_notice_secs = store
+        .get("schedule_notice_secs")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(defaults.schedule_notice_secs);
+
+    let countdown_secs = store
+        .get("countdown_secs")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(defaults.countdown_secs);
+
+    let countdown_sources = store
+        .get("countdown_sources")
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or(defaults.countdown_sources);
+
+    let macros = store
+        .get("macros")
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or(defaults.macros);
+
+    let wake_word = store
+        .get("wake_word")
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or(defaults.wake_word);
+
+    let response_cache_ttl_secs = store
+        .get("response_cache_ttl_secs")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(defaults.response_cache_ttl_secs);
+
+    let answer_offline_from_cache = store
+        .get("answer_offline_from_cache")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(defaults.answer_offline_from_cache);
+
+    let demo_mode = store
+        .get("demo_mode")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(defaults.demo_mode);
+
+    let send_timeout_mins = store
+        .get("send_timeout_mins")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(defaults.send_timeout_mins);
+
+    let response_timeout_secs = store
+        .get("response_timeout_secs")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(defaults.response_timeout_secs);
+
+    let device_formats = store
+        .get("device_formats")
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or(defaults.device_formats);
+
+    let device_labels = store
+        .get("device_labels")
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or(defaults.device_labels);
+
+    let popup_pinned = store
+        .get("popup_pinned")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(defaults.popup_pinned);
+
+    let storage_min_free_mb = store
+        .get("storage_min_free_mb")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(defaults.storage_min_free_mb);
+
+    let history_max_mb = store
+        .get("history_max_mb")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(defaults.history_max_mb);
+
+    let archive_recordings = store
+        .get("archive_recordings")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(defaults.archive_recordings);
+
+    let archive_max_count = store
+        .get("archive_max_count")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(defaults.archive_max_count);
+
+    let archive_max_age_days = store
+        .get("archive_max_age_days")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(defaults.archive_max_age_days);
+
+    let auto_stop_silence_ms = store
+        .get("auto_stop_silence_ms")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(defaults.auto_stop_silence_ms);
+
+    let vad_threshold_db = store
+        .get("vad_threshold_db")
+        .and_then(|v| v.as_f64())
+        .map(|v| v as f32)
+        .unwrap_or(defaults.vad_threshold_db);
+
+    let dictation_pause_ms = store
+        .get("dictation_pause_ms")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(defaults.dictation_pause_ms);
+
+    let calibrate_noise_floor = store
+        .get("calibrate_noise_floor")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(defaults.calibrate_noise_floor);
+
+    let streaming_upload = store
+        .get("streaming_upload")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(defaults.streaming_upload);
+
+    let preprocessing = store
+        .get("preprocessing")
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or(defaults.preprocessing);
+
+    let noise_suppression = store
+        .get("noise_suppression")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(defaults.noise_suppression);
+
+    let trim_silence = store
+        .get("trim_silence")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(defaults.trim_silence);
+
+    let trim_threshold_db = store
+        .get("trim_threshold_db")
+        .and_then(|v| v.as_f64())
+        .map(|v| v as f32)
+        .unwrap_or(defaults.trim_threshold_db);
+
+    let trim_padding_ms = store
+        .get("trim_padding_ms")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(defaults.trim_padding_ms);
+
+    let effect_chains = store
+        .get("effect_chains")
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or(defaults.effect_chains);
+
+    let remote_control = store
+        .get("remote_control")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(defaults.remote_control);
+
+    let remote_control_port = store
+        .get("remote_control_port")
+        .and_then(|v| v.as_u64())
+        .and_then(|v| u16::try_from(v).ok())
+        .filter(|port| *port != 0)
+        .unwrap_or(defaults.remote_control_port);
+
+    let notification_max_chars = store
+        .get("notification_max_chars")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(defaults.notification_max_chars);
+
+    let show_more_opens_popup = store
+        .get("show_more_opens_popup")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(defaults.show_more_opens_popup);
+
+    let capture_max_mb = store
+        .get("capture_max_mb")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(defaults.capture_max_mb);
+
+    let capture_overflow = store
+        .get("capture_overflow")
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or(defaults.capture_overflow);
+
+    let autocorrect = store
+        .get("autocorrect")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(defaults.autocorrect);
+
+    let autocorrect_min_count = store
+        .get("autocorrect_min_count")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(defaults.autocorrect_min_count);
+
+    let meeting_mode = store
+        .get("meeting_mode")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(defaults.meeting_mode);
+
+    let system_audio_device_id = store
+        .get("system_audio_device_id")
+        .and_then(|v| v.as_str().map(String::from))
+        .filter(|s| !s.is_empty());
+
+    let meeting_mic_gain_db = store
+        .get("meeting_mic_gain_db")
+        .and_then(|v| v.as_f64())
+        .map(|v| v as f32)
+        .unwrap_or(defaults.meeting_mic_gain_db);
+
+    let meeting_system_gain_db = store
+        .get("meeting_system_gain_db")
+        .and_then(|v| v.as_f64())
+        .map(|v| v as f32)
+        .unwrap_or(defaults.meeting_system_gain_db);
+
+    let speak_replies = store
+        .get("speak_replies")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(defaults.speak_replies);
+
+    let playback_volume = store
+        .get("playback_volume")
+        .and_then(|v| v.as_f64())
+        .map(|v| v as f32)
+        .unwrap_or(defaults.playback_volume);
+
+    let sound_cues = store
+        .get("sound_cues")
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or(defaults.sound_cues);
+
+    let half_duplex = store
+        .get("half_duplex")
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or(defaults.half_duplex);
+
+    let hotkey_popup = store
+        .get("hotkey_popup")
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or(defaults.hotkey_popup);
+
+    let hotkey_popup_position = store
+        .get("hotkey_popup_position")
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or(defaults.hotkey_popup_position);
 
     let token = load_token().unwrap_or_default();
 
@@ -77,8 +525,88 @@ pub fn load_settings<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Result<App
         token,
         profile_name,
         session_key,
+        response_language,
+        brief_mode,
+        brief_max_sentences,
+        workspace,
         microphone_device_id,
+        audio_host,
+        input_channel,
+        capture_high_pass_hz,
         push_to_talk_hotkey,
+        keyboard_layout,
+        trigger_cooldown_ms,
+        idle_pause_secs,
+        pause_on_lock,
+        event_log_capacity,
+        endpoint_template,
+        endpoint_overrides,
+        http_trace,
+        speaker_filter,
+        speaker_filter_threshold,
+        share_context,
+        audio_quality,
+        stt_providers,
+        whisper_command,
+        whisper_model,
+        whisper_preview_model,
+        prefer_local_stt,
+        tts_providers,
+        piper_command,
+        piper_model,
+        alert_keywords,
+        alert_action_items,
+        scheduled_recordings,
+        schedule_notice_secs,
+        countdown_secs,
+        countdown_sources,
+        macros,
+        wake_word,
+        response_cache_ttl_secs,
+        answer_offline_from_cache,
+        demo_mode,
+        send_timeout_mins,
+        response_timeout_secs,
+        device_formats,
+        device_labels,
+        popup_pinned,
+        storage_min_free_mb,
+        history_max_mb,
+        archive_recordings,
+        archive_max_count,
+        archive_max_age_days,
+        auto_stop_silence_ms,
+        vad_threshold_db,
+        dictation_pause_ms,
+        calibrate_noise_floor,
+        streaming_upload,
+        preprocessing,
+        noise_suppression,
+        trim_silence,
+        trim_threshold_db,
+        trim_padding_ms,
+        effect_chains,
+        webhooks,
+        response_routes,
+        event_filters,
+        remote_control,
+        remote_control_port,
+        notification_max_chars,
+        show_more_opens_popup,
+        capture_max_mb,
+        capture_overflow,
+        autocorrect,
+        autocorrect_min_count,
+        meeting_mode,
+        system_audio_device_id,
+        meeting_mic_gain_db,
+        meeting_system_gain_db,
+        speak_replies,
+        playback_volume,
+        sound_cues,
+        half_duplex,
+        hotkey_popup,
+        hotkey_popup_position,
     })
 }
 
@@ -87,16 +615,68 @@ pub fn save_settings<R: tauri::Runtime>(
     app: &tauri::AppHandle<R>,
     settings: &AppSettings,
 ) -> Result<(), String> {
-    let store = app
-        .store("settings.json")
-        .map_err(|e| format!("Failed to open store: {e}"))?;
-
+    Routes::new(&settings.endpoint_template, &settings.endpoint_overrides)?;
+    let response_language = settings
+        .response_language
+        .as_deref()
+        .filter(|l| !l.is_empty());
+    if let Some(language) = response_language {
+        api::validate_language_tag(language)?;
+    }
+    let workspace = settings
+        .workspace
+        .as_deref()
+        .map(str::trim)
+        .filter(|w| !w.is_empty());
+    if let Some(workspace) = workspace {
+        api::validate_workspace(workspace)?;
+    }
+    effects::validate(&settings.effect_chains)?;
+    response_routes::validate(&settings.response_routes)?;
+    events::validate_filters(&settings.event_filters)?;
+    macros::validate(&settings.macros)?;
+    if let Some(ref config) = settings.wake_word {
+        wake_word::validate(config)?;
+    }
+    if settings.prefer_local_stt && settings.whisper_model.is_none() {
+        return Err("'prefer_local_stt' needs a whisper_model".to_string());
+    }
+    let speaks_locally = settings
+        .tts_providers
+        .values()
+        .any(|&kind| kind == TtsProviderKind::Piper);
+    if speaks_locally && settings.piper_model.is_none() {
+        return Err("Local speech needs a piper_model".to_string());
+    }
+    if settings.capture_high_pass_hz != 0 && !(20..=500).contains(&settings.capture_high_pass_hz) {
+        return Err("'capture_high_pass_hz' must be 0 (off) or between 20 and 500".to_string());
+    }
+    for (i, stage) in settings.preprocessing.iter().enumerate() {
+        if settings.preprocessing[..i].contains(stage) {
+            let name = format!("{stage:?}").to_lowercase();
+            return Err(format!("'preprocessing' lists {name} more than once"));
+        }
+    }
+    for (key, gain_db) in [
+        ("meeting_mic_gain_db", settings.meeting_mic_gain_db),
+        ("meeting_system_gain_db", settings.meeting_system_gain_db),
+    ] {
+        if !(-24.0..=12.0).contains(&gain_db) {
+            return Err(format!(
+                "'{key}' must be between -24 and 12 dB, not {gain_db}"
+            ));
+        }
+    }
+    if !(1..=10).contains(&settings.brief_max_sentences) {
+        return Err("'brief_max_sentences' must be between 1 and 10".to_string());
+    }
+    if !(200..=10_000).contains(&settings.dictation_pause_ms) {
+        return Err("'dictation_pause_ms' must be between 200 and 10000".to_string());
+    }
+    let store = persist::open_store(app)?;
 
     store.set("gateway_url", Value::String(settings.gateway_url.clone()));
-    store.set(
-        "profile_name",
-        Value::String(settings.profile_name.clone()),
-    );
+    store.set("profile_name", Value::String(settings.profile_name.clone()));
 
     if let Some(ref key) = settings.session_key {
         store.set("session_key", Value::String(key.clone()));
@@ -104,30 +684,441 @@ pub fn save_settings<R: tauri::Runtime>(
         store.delete("session_key");
     }
 
+    if let Some(language) = response_language {
+        store.set("response_language", Value::String(language.to_string()));
+    } else {
+        store.delete("response_language");
+    }
+    store.set("brief_mode", Value::Bool(settings.brief_mode));
+    store.set(
+        "brief_max_sentences",
+        Value::from(settings.brief_max_sentences),
+    );
+
+    if let Some(workspace) = workspace {
+        store.set("workspace", Value::String(workspace.to_string()));
+    } else {
+        store.delete("workspace");
+    }
+
     if let Some(ref device_id) = settings.microphone_device_id {
         store.set("microphone_device_id", Value::String(device_id.clone()));
     } else {
         store.delete("microphone_device_id");
     }
 
+    if let Some(ref host) = settings.audio_host {
+        store.set("audio_host", Value::String(host.clone()));
+    } else {
+        store.delete("audio_host");
+    }
+
+    if let Some(channel) = settings.input_channel.filter(|c| *c > 0) {
+        store.set("input_channel", Value::from(channel));
+    } else {
+        store.delete("input_channel");
+    }
+    store.set(
+        "capture_high_pass_hz",
+        Value::from(settings.capture_high_pass_hz),
+    );
+
     if let Some(ref hotkey) = settings.push_to_talk_hotkey {
         store.set("push_to_talk_hotkey", Value::String(hotkey.clone()));
     } else {
         store.delete("push_to_talk_hotkey");
     }
 
+    if let Some(layout) = settings.keyboard_layout {
+        store.set(
+            "keyboard_layout",
+            serde_json::to_value(layout).unwrap_or_default(),
+        );
+    } else {
+        store.delete("keyboard_layout");
+    }
+
+    store.set(
+        "trigger_cooldown_ms",
+        Value::from(settings.trigger_cooldown_ms),
+    );
+    store.set("idle_pause_secs", Value::from(settings.idle_pause_secs));
+    store.set("pause_on_lock", Value::Bool(settings.pause_on_lock));
+    store.set(
+        "event_log_capacity",
+        Value::from(settings.event_log_capacity),
+    );
+    store.set(
+        "endpoint_template",
+        Value::String(settings.endpoint_template.clone()),
+    );
+    store.set(
+        "endpoint_overrides",
+        serde_json::to_value(&settings.endpoint_overrides).unwrap_or_default(),
+    );
+    store.set("http_trace", Value::Bool(settings.http_trace));
+    store.set("speaker_filter", Value::Bool(settings.speaker_filter));
+    store.set(
+        "speaker_filter_threshold",
+        Value::from(settings.speaker_filter_threshold),
+    );
+    store.set("share_context", Value::Bool(settings.share_context));
+    store.set(
+        "audio_quality",
+        serde_json::to_value(settings.audio_quality).unwrap_or_default(),
+    );
+    store.set(
+        "stt_providers",
+        serde_json::to_value(&settings.stt_providers).unwrap_or_default(),
+    );
+
+    if let Some(ref command) = settings.whisper_command {
+        store.set("whisper_command", Value::String(command.clone()));
+    } else {
+        store.delete("whisper_command");
+    }
+
+    if let Some(ref model) = settings.whisper_model {
+        store.set("whisper_model", Value::String(model.clone()));
+    } else {
+        store.delete("whisper_model");
+    }
+    if let Some(ref model) = settings.whisper_preview_model {
+        store.set("whisper_preview_model", Value::String(model.clone()));
+    } else {
+        store.delete("whisper_preview_model");
+    }
+    store.set("prefer_local_stt", Value::Bool(settings.prefer_local_stt));
+    store.set(
+        "tts_providers",
+        serde_json::to_value(&settings.tts_providers).unwrap_or_default(),
+    );
 
-    store
-        .save()
-        .map_err(|e| format!("Failed to save store: {e}"))?;
+    if let Some(ref command) = settings.piper_command {
+        store.set("piper_command", Value::String(command.clone()));
+    } else {
+        store.delete("piper_command");
+    }
 
+    if let Some(ref model) = settings.piper_model {
+        store.set("piper_model", Value::String(model.clone()));
+    } else {
+        store.delete("piper_model");
+    }
+
+    store.set(
+        "alert_keywords",
+        serde_json::to_value(&settings.alert_keywords).unwrap_or_default(),
+    );
+    store.set(
+        "alert_action_items",
+        Value::Bool(settings.alert_action_items),
+    );
+    store.set(
+        "scheduled_recordings",
+        serde_json::to_value(&settings.scheduled_recordings).unwrap_or_default(),
+    );
+    store.set(
+        "schedule_notice_secs",
+        Value::from(settings.schedule_notice_secs),
+    );
+    store.set("countdown_secs", Value::from(settings.countdown_secs));
+    store.set(
+        "countdown_sources",
+        serde_json::to_value(&settings.countdown_sources).unwrap_or_default(),
+    );
+    store.set(
+        "macros",
+        serde_json::to_value(&settings.macros).unwrap_or_default(),
+    );
+    if let Some(ref config) = settings.wake_word {
+        store.set(
+            "wake_word",
+            serde_json::to_value(config).unwrap_or_default(),
+        );
+    } else {
+        store.delete("wake_word");
+    }
+    store.set(
+        "response_cache_ttl_secs",
+        Value::from(settings.response_cache_ttl_secs),
+    );
+    store.set(
+        "answer_offline_from_cache",
+        Value::Bool(settings.answer_offline_from_cache),
+    );
+    store.set("demo_mode", Value::Bool(settings.demo_mode));
+    store.set("send_timeout_mins", Value::from(settings.send_timeout_mins));
+    store.set(
+        "response_timeout_secs",
+        Value::from(settings.response_timeout_secs),
+    );
+    store.set(
+        "device_formats",
+        serde_json::to_value(&settings.device_formats).unwrap_or_default(),
+    );
+    store.set(
+        "device_labels",
+        serde_json::to_value(&settings.device_labels).unwrap_or_default(),
+    );
+    store.set("popup_pinned", Value::Bool(settings.popup_pinned));
+    store.set(
+        "storage_min_free_mb",
+        Value::from(settings.storage_min_free_mb),
+    );
+    store.set("history_max_mb", Value::from(settings.history_max_mb));
+    store.set(
+        "archive_recordings",
+        Value::Bool(settings.archive_recordings),
+    );
+    store.set("archive_max_count", Value::from(settings.archive_max_count));
+    store.set(
+        "archive_max_age_days",
+        Value::from(settings.archive_max_age_days),
+    );
+    store.set(
+        "auto_stop_silence_ms",
+        Value::from(settings.auto_stop_silence_ms),
+    );
+    store.set("vad_threshold_db", Value::from(settings.vad_threshold_db));
+    store.set(
+        "dictation_pause_ms",
+        Value::from(settings.dictation_pause_ms),
+    );
+    store.set(
+        "calibrate_noise_floor",
+        Value::Bool(settings.calibrate_noise_floor),
+    );
+    store.set("streaming_upload", Value::Bool(settings.streaming_upload));
+    store.set(
+        "preprocessing",
+        serde_json::to_value(&settings.preprocessing).unwrap_or_default(),
+    );
+    store.set("noise_suppression", Value::Bool(settings.noise_suppression));
+    store.set("trim_silence", Value::Bool(settings.trim_silence));
+    store.set("trim_threshold_db", Value::from(settings.trim_threshold_db));
+    store.set("trim_padding_ms", Value::from(settings.trim_padding_ms));
+    store.set(
+        "effect_chains",
+        serde_json::to_value(&settings.effect_chains).unwrap_or_default(),
+    );
+    store.set(
+        "webhooks",
+        serde_json::to_value(&settings.webhooks).unwrap_or_default(),
+    );
+    store.set(
+        "response_routes",
+        serde_json::to_value(&settings.response_routes).unwrap_or_default(),
+    );
+    store.set(
+        "event_filters",
+        serde_json::to_value(&settings.event_filters).unwrap_or_default(),
+    );
+    store.set("remote_control", Value::Bool(settings.remote_control));
+    store.set(
+        "remote_control_port",
+        Value::from(settings.remote_control_port),
+    );
+    store.set(
+        "notification_max_chars",
+        Value::from(settings.notification_max_chars),
+    );
+    store.set(
+        "show_more_opens_popup",
+        Value::Bool(settings.show_more_opens_popup),
+    );
+    store.set("capture_max_mb", Value::from(settings.capture_max_mb));
+    store.set(
+        "capture_overflow",
+        serde_json::to_value(settings.capture_overflow).unwrap_or_default(),
+    );
+    store.set("autocorrect", Value::Bool(settings.autocorrect));
+    store.set(
+        "autocorrect_min_count",
+        Value::from(settings.autocorrect_min_count),
+    );
+    store.set("meeting_mode", Value::Bool(settings.meeting_mode));
+    if let Some(ref device_id) = settings.system_audio_device_id {
+        store.set("system_audio_device_id", Value::String(device_id.clone()));
+    } else {
+        store.delete("system_audio_device_id");
+    }
+    store.set(
+        "meeting_mic_gain_db",
+        Value::from(settings.meeting_mic_gain_db),
+    );
+    store.set(
+        "meeting_system_gain_db",
+        Value::from(settings.meeting_system_gain_db),
+    );
+    store.set("speak_replies", Value::Bool(settings.speak_replies));
+    store.set("playback_volume", Value::from(settings.playback_volume));
+    store.set(
+        "sound_cues",
+        serde_json::to_value(settings.sound_cues).unwrap_or_default(),
+    );
+    store.set(
+        "half_duplex",
+        serde_json::to_value(settings.half_duplex).unwrap_or_default(),
+    );
+    store.set(
+        "hotkey_popup",
+        serde_json::to_value(settings.hotkey_popup).unwrap_or_default(),
+    );
+    store.set(
+        "hotkey_popup_position",
+        serde_json::to_value(settings.hotkey_popup_position).unwrap_or_default(),
+    );
+
+    persist::save_store(app, &store)?;
 
     if !settings.token.is_empty() {
         save_token(&settings.token)?;
     } else {
-
         let _ = delete_token();
     }
 
     Ok(())
 }
+
+/// Push settings that affect running subsystems into their managed state.
+/// Called once at startup and after every save.
+pub fn apply_settings(app: &AppHandle, settings: &AppSettings) {
+    app.state::<TriggerGate>()
+        .set_min_gap(Duration::from_millis(settings.trigger_cooldown_ms));
+    app.state::<PresenceState>().configure(
+        Duration::from_secs(settings.idle_pause_secs),
+        settings.pause_on_lock,
+    );
+    app.state::<EventLog>()
+        .set_capacity(settings.event_log_capacity);
+    match Routes::new(&settings.endpoint_template, &settings.endpoint_overrides) {
+        Ok(routes) => app.state::<RoutingState>().set(routes),
+        Err(e) => eprintln!("Keeping previous endpoint routing: {e}"),
+    }
+    app.state::<RoutingState>()
+        .set_workspace(settings.workspace.clone());
+    let log_path = app
+        .path()
+        .app_log_dir()
+        .ok()
+        .map(|dir| dir.join(http_log::LOG_FILE));
+    app.state::<HttpLog>()
+        .configure(settings.http_trace, log_path);
+    app.state::<VoiceprintState>()
+        .configure(settings.speaker_filter, settings.speaker_filter_threshold);
+    context::configure(app, settings.share_context);
+    app.state::<QualityState>().set(settings.audio_quality);
+    app.state::<SttState>()
+        .configure(SttConfig::from_settings(settings));
+    app.state::<TtsState>()
+        .configure(TtsConfig::from_settings(settings));
+    app.state::<KeywordState>().configure(KeywordSpotter::new(
+        &settings.alert_keywords,
+        settings.alert_action_items,
+    ));
+    app.state::<ScheduleState>().configure(
+        settings.scheduled_recordings.clone(),
+        Duration::from_secs(settings.schedule_notice_secs),
+    );
+    app.state::<CountdownState>().configure(
+        Duration::from_secs(settings.countdown_secs),
+        settings.countdown_sources.clone(),
+    );
+    app.state::<ResponseCacheState>().configure(
+        Duration::from_secs(settings.response_cache_ttl_secs),
+        settings.answer_offline_from_cache,
+    );
+    app.state::<DemoState>().configure(settings.demo_mode);
+    let send_limit = match settings.send_timeout_mins {
+        0 => Duration::MAX,
+        mins => Duration::from_secs(mins.saturating_mul(60)),
+    };
+    app.state::<WatchdogState>().set_send_limit(send_limit);
+    let response_limit = (settings.response_timeout_secs > 0)
+        .then(|| Duration::from_secs(settings.response_timeout_secs));
+    app.state::<WatchdogState>()
+        .set_response_limit(response_limit);
+    let audio = app.state::<AudioState>();
+    audio.set_format_overrides(settings.device_formats.clone());
+    audio.set_silence_trim(SilenceTrim {
+        enabled: settings.trim_silence,
+        threshold_db: settings.trim_threshold_db,
+        padding: Duration::from_millis(settings.trim_padding_ms),
+        calibrate: settings.calibrate_noise_floor,
+    });
+    audio.set_preprocessing(settings.preprocessing.clone(), settings.noise_suppression);
+    audio.set_capture_limit(CaptureLimit {
+        max_bytes: (settings.capture_max_mb as usize).saturating_mul(1024 * 1024),
+        overflow: settings.capture_overflow,
+    });
+    audio.set_meeting_capture(MeetingCapture {
+        enabled: settings.meeting_mode,
+        system_device_id: settings.system_audio_device_id.clone(),
+        mic_gain_db: settings.meeting_mic_gain_db,
+        system_gain_db: settings.meeting_system_gain_db,
+    });
+    audio.set_half_duplex(settings.half_duplex);
+    audio.set_input_channel(settings.input_channel);
+    audio.set_high_pass(settings.capture_high_pass_hz.min(u64::from(u32::MAX)) as u32);
+    audio.set_host(settings.audio_host.clone());
+    app.state::<PlaybackState>()
+        .configure(settings.speak_replies, settings.playback_volume);
+    app.state::<CueState>().configure(settings.sound_cues);
+    app.state::<BriefState>()
+        .configure(settings.brief_mode, settings.brief_max_sentences);
+    app.state::<StorageState>()
+        .configure(settings.storage_min_free_mb, settings.history_max_mb);
+    app.state::<RecordingArchiveState>().configure(
+        settings.archive_recordings,
+        settings.archive_max_count,
+        settings.archive_max_age_days,
+    );
+    recordings::prune(app);
+    app.state::<VadState>().configure(
+        Duration::from_millis(settings.auto_stop_silence_ms),
+        settings.vad_threshold_db,
+        settings.calibrate_noise_floor,
+    );
+    app.state::<DictationState>()
+        .configure(Duration::from_millis(settings.dictation_pause_ms));
+    app.state::<LiveUploadState>()
+        .configure(settings.streaming_upload);
+    app.state::<EffectsState>()
+        .configure(settings.effect_chains.clone());
+    app.state::<WebhookState>()
+        .configure(settings.webhooks.clone());
+    app.state::<ResponseRouteState>()
+        .configure(settings.response_routes.clone());
+    app.state::<EventFilterState>()
+        .configure(settings.event_filters.clone());
+    remote::configure(app, settings.remote_control, settings.remote_control_port);
+    app.state::<SegmentState>().configure(
+        settings.notification_max_chars as usize,
+        settings.show_more_opens_popup,
+    );
+    app.state::<DeviceMonitorState>()
+        .configure(settings.microphone_device_id.as_deref());
+    app.state::<WakeWordState>().configure(
+        settings.wake_word.clone(),
+        settings.microphone_device_id.clone(),
+    );
+    app.state::<CorrectionState>().configure(
+        settings.autocorrect,
+        settings.autocorrect_min_count.min(u64::from(u32::MAX)) as u32,
+    );
+    // The popup doesn't exist yet at startup; setup pins it once built
+    if let Err(e) = actions::set_popup_pinned(app, settings.popup_pinned) {
+        eprintln!("Popup window not updated: {e}");
+    }
+    app.state::<HotkeyState>()
+        .configure(settings.hotkey_popup, settings.hotkey_popup_position);
+    if let Err(e) = hotkey::apply_push_to_talk(
+        app,
+        &app.state::<HotkeyState>(),
+        settings.push_to_talk_hotkey.as_deref(),
+    ) {
+        eprintln!("Push-to-talk hotkey not registered: {e}");
+    }
+    macros::configure(app, &settings.macros);
+}
@@ -0,0 +1,116 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use tauri::{AppHandle, Manager};
+use tauri_plugin_global_shortcut::GlobalShortcutExt;
+
+use crate::audio::{self, AudioState};
+use crate::live_upload;
+use crate::persist;
+use crate::playback::PlaybackState;
+use crate::session::{self, SessionState};
+
+/// Guards the shutdown sequence so it runs exactly once,
+/// whether it was started by the UI, a signal or the OS asking the app to exit.
+pub struct ShutdownState {
+    pub started: Arc<AtomicBool>,
+}
+
+impl ShutdownState {
+    pub fn new() -> Self {
+        Self {
+            started: Arc::new(AtomicBool::new(false)),
+        }
+    }
+}
+
+type Step = fn(&AppHandle) -> Result<(), String>;
+
+/// Ordered shutdown: stop audio first so nothing new is produced,
+/// then persist state, release OS resources and finally close the store.
+const STEPS: &[(&str, Step)] = &[
+    ("stop recording", stop_recording),
+    ("stop playback", stop_playback),
+    ("persist session", persist_session),
+    ("unregister hotkeys", unregister_hotkeys),
+    ("close settings store", close_store),
+];
+
+fn stop_recording(app: &AppHandle) -> Result<(), String> {
+    audio::cancel_recording(&app.state::<AudioState>());
+    live_upload::abort(app);
+    Ok(())
+}
+
+fn stop_playback(app: &AppHandle) -> Result<(), String> {
+    app.state::<PlaybackState>().close();
+    Ok(())
+}
+
+fn persist_session(app: &AppHandle) -> Result<(), String> {
+    session::persist_session(app, &app.state::<SessionState>())
+}
+
+fn unregister_hotkeys(app: &AppHandle) -> Result<(), String> {
+    app.global_shortcut()
+        .unregister_all()
+        .map_err(|e| e.to_string())
+}
+
+fn close_store(app: &AppHandle) -> Result<(), String> {
+    // Everything is saved atomically by now; closing the store keeps the
+    // store plugin's own (non-atomic) exit-time save from rewriting the file.
+    let store = persist::open_store(app)?;
+    persist::save_store(app, &store)?;
+    store.close_resource();
+    Ok(())
+}
+
+/// Run the shutdown sequence if it hasn't run yet. Failing steps are logged, not fatal.
+pub fn run(app: &AppHandle) {
+    let state = app.state::<ShutdownState>();
+    if state.started.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    for (name, step) in STEPS {
+        if let Err(e) = step(app) {
+            eprintln!("Shutdown step '{name}' failed: {e}");
+        }
+    }
+}
+
+/// Shut down in order, then exit — use instead of `app.exit(0)`
+pub fn quit(app: &AppHandle) {
+    run(app);
+    app.exit(0);
+}
+
+/// Quit gracefully on SIGINT/SIGTERM (Ctrl+C on Windows)
+pub async fn listen_for_signals(app: AppHandle) {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let (Ok(mut terminate), Ok(mut interrupt)) = (
+            signal(SignalKind::terminate()),
+            signal(SignalKind::interrupt()),
+        ) else {
+            eprintln!("Failed to install signal handlers");
+            return;
+        };
+        tokio::select! {
+            _ = terminate.recv() => {}
+            _ = interrupt.recv() => {}
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        if tokio::signal::ctrl_c().await.is_err() {
+            eprintln!("Failed to install Ctrl+C handler");
+            return;
+        }
+    }
+
+    quit(&app);
+}
@@ -0,0 +1,200 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::events;
+use crate::types::{StorageLimit, StorageUsage};
+use crate::{failed_sends, history, journal, recordings, response_cache};
+
+const MB: u64 = 1024 * 1024;
+
+/// Limits on what the app writes to disk. Once one is hit, history and saved
+/// recordings stop being written until space is freed.
+pub struct StorageState {
+    pub min_free_mb: Arc<Mutex<u64>>,
+    pub max_history_mb: Arc<Mutex<u64>>,
+    /// The limit last reported in "storage-warning", so each change is emitted once
+    pub limit: Arc<Mutex<Option<StorageLimit>>>,
+}
+
+impl StorageState {
+    pub fn new() -> Self {
+        Self {
+            min_free_mb: Arc::new(Mutex::new(0)),
+            max_history_mb: Arc::new(Mutex::new(0)),
+            limit: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn configure(&self, min_free_mb: u64, max_history_mb: u64) {
+        if let Ok(mut current) = self.min_free_mb.lock() {
+            *current = min_free_mb;
+        }
+        if let Ok(mut current) = self.max_history_mb.lock() {
+            *current = max_history_mb;
+        }
+    }
+
+    fn limits(&self) -> (u64, u64) {
+        let min_free = self.min_free_mb.lock().map(|mb| *mb).unwrap_or(0);
+        let max_history = self.max_history_mb.lock().map(|mb| *mb).unwrap_or(0);
+        (min_free * MB, max_history * MB)
+    }
+}
+
+fn data_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data dir: {e}"))
+}
+
+/// Total size of the files directly in `dir`; the app's stores don't nest
+fn dir_size(dir: &Path) -> u64 {
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return 0;
+    };
+    read_dir
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.metadata().ok())
+        .filter(|meta| meta.is_file())
+        .map(|meta| meta.len())
+        .sum()
+}
+
+/// The first limit `usage` is over. 0 disables a limit, and an unknown free space passes.
+fn exceeded(usage: &StorageUsage) -> Option<StorageLimit> {
+    let low_disk = usage
+        .free_bytes
+        .is_some_and(|free| usage.min_free_bytes > 0 && free < usage.min_free_bytes);
+    if low_disk {
+        return Some(StorageLimit::LowDiskSpace);
+    }
+    if usage.max_history_bytes > 0 && usage.history_bytes >= usage.max_history_bytes {
+        return Some(StorageLimit::HistoryFull);
+    }
+    None
+}
+
+fn measure(dir: &Path, min_free_bytes: u64, max_history_bytes: u64) -> StorageUsage {
+    let mut usage = StorageUsage {
+        data_dir: dir.display().to_string(),
+        history_bytes: dir_size(&dir.join(history::HISTORY_DIR)),
+        failed_sends_bytes: dir_size(&dir.join(failed_sends::FAILED_DIR)),
+        recordings_bytes: dir_size(&dir.join(recordings::RECORDINGS_DIR)),
+        pending_sends_bytes: dir_size(&dir.join(journal::JOURNAL_DIR)),
+        response_cache_bytes: fs::metadata(dir.join(response_cache::CACHE_FILE))
+            .map(|meta| meta.len())
+            .unwrap_or(0),
+        // The data dir may not exist before the first write; its parent is on the same disk
+        free_bytes: dir
+            .ancestors()
+            .find_map(|path| fs2::available_space(path).ok()),
+        min_free_bytes,
+        max_history_bytes,
+        limit: None,
+    };
+    usage.limit = exceeded(&usage);
+    usage
+}
+
+/// What the app stores on disk and how much room is left, for the settings window
+pub fn usage(app: &AppHandle) -> Result<StorageUsage, String> {
+    let (min_free_bytes, max_history_bytes) = app.state::<StorageState>().limits();
+    Ok(measure(&data_dir(app)?, min_free_bytes, max_history_bytes))
+}
+
+/// Whether there is room to write history, or when `for_history` is false a recording:
+/// a failed send, one for the archive or one journaled while it is sent.
+/// Emits "storage-warning" whenever the limit reached changes, including back to none.
+pub fn admit(app: &AppHandle, for_history: bool) -> Result<(), String> {
+    let usage = usage(app)?;
+    let state = app.state::<StorageState>();
+    if let Ok(mut last) = state.limit.lock() {
+        if *last != usage.limit {
+            *last = usage.limit;
+            events::record_state(app, "storage-warning", &usage);
+            let _ = app.emit("storage-warning", &usage);
+        }
+    }
+    match usage.limit {
+        Some(StorageLimit::LowDiskSpace) => Err(format!(
+            "Less than {} MB free in {}",
+            usage.min_free_bytes / MB,
+            usage.data_dir
+        )),
+        Some(StorageLimit::HistoryFull) if for_history => Err(format!(
+            "History has reached its {} MB limit",
+            usage.max_history_bytes / MB
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    fn usage(free_bytes: Option<u64>, history_bytes: u64) -> StorageUsage {
+        StorageUsage {
+            data_dir: String::new(),
+            history_bytes,
+            failed_sends_bytes: 0,
+            recordings_bytes: 0,
+            pending_sends_bytes: 0,
+            response_cache_bytes: 0,
+            free_bytes,
+            min_free_bytes: 500 * MB,
+            max_history_bytes: 100 * MB,
+            limit: None,
+        }
+    }
+
+    #[test]
+    fn test_limits() {
+        assert_eq!(exceeded(&usage(Some(600 * MB), 10 * MB)), None);
+        assert_eq!(
+            exceeded(&usage(Some(400 * MB), 200 * MB)),
+            Some(StorageLimit::LowDiskSpace)
+        );
+        assert_eq!(
+            exceeded(&usage(Some(600 * MB), 100 * MB)),
+            Some(StorageLimit::HistoryFull)
+        );
+        // Free space couldn't be read
+        assert_eq!(exceeded(&usage(None, 10 * MB)), None);
+
+        let mut unlimited = usage(Some(0), 200 * MB);
+        unlimited.min_free_bytes = 0;
+        unlimited.max_history_bytes = 0;
+        assert_eq!(exceeded(&unlimited), None);
+    }
+
+    #[test]
+    fn test_measure_sums_each_store() {
+        let dir = TempDir::new("storage");
+        fs::create_dir_all(dir.join(history::HISTORY_DIR)).unwrap();
+        fs::write(dir.join(history::HISTORY_DIR).join("a.jsonl"), [0u8; 10]).unwrap();
+        fs::write(dir.join(history::HISTORY_DIR).join("b.jsonl"), [0u8; 5]).unwrap();
+        fs::write(dir.join(response_cache::CACHE_FILE), [0u8; 7]).unwrap();
+        fs::create_dir_all(dir.join(recordings::RECORDINGS_DIR)).unwrap();
+        fs::write(
+            dir.join(recordings::RECORDINGS_DIR).join("a.wav"),
+            [0u8; 20],
+        )
+        .unwrap();
+        fs::create_dir_all(dir.join(journal::JOURNAL_DIR)).unwrap();
+        fs::write(dir.join(journal::JOURNAL_DIR).join("1.wav"), [0u8; 3]).unwrap();
+
+        let usage = measure(&dir, 0, 15);
+        assert_eq!(usage.history_bytes, 15);
+        assert_eq!(usage.failed_sends_bytes, 0);
+        assert_eq!(usage.recordings_bytes, 20);
+        assert_eq!(usage.pending_sends_bytes, 3);
+        assert_eq!(usage.response_cache_bytes, 7);
+        assert!(usage.free_bytes.is_some());
+        assert_eq!(usage.limit, Some(StorageLimit::HistoryFull));
+    }
+}
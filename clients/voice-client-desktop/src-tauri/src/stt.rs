@@ -0,0 +1,430 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use bytes::Bytes;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::error::AppError;
+use crate::events;
+use crate::playback::decode_wav_mono;
+use crate::quality;
+use crate::types::{AppSettings, QualityPreset, SttProviderKind, TranscriptPreview};
+
+/// whisper.cpp's CLI binary name since it stopped being called `main`
+const DEFAULT_WHISPER_COMMAND: &str = "whisper-cli";
+
+/// What is uploaded for a turn
+#[derive(Debug, Clone, PartialEq)]
+pub enum Utterance {
+    /// Encoded recording for the gateway to transcribe; shared, not copied, with the
+    /// request body and the copy kept for a retry
+    Audio(Bytes),
+    /// Text already transcribed on this machine; the audio never leaves it
+    Transcript(String),
+}
+
+/// Turns a finished recording into what the gateway receives.
+/// Cloud APIs can be added as further implementations.
+pub trait SttProvider: Send + Sync {
+    fn kind(&self) -> SttProviderKind;
+
+    /// May block for seconds, so call it off the async runtime
+    fn prepare(&self, wav_bytes: Bytes) -> Result<Utterance, AppError>;
+}
+
+/// Leaves transcription to the gateway, as it always has
+pub struct GatewayStt;
+
+impl SttProvider for GatewayStt {
+    fn kind(&self) -> SttProviderKind {
+        SttProviderKind::Gateway
+    }
+
+    fn prepare(&self, wav_bytes: Bytes) -> Result<Utterance, AppError> {
+        Ok(Utterance::Audio(wav_bytes))
+    }
+}
+
+/// Transcribes with whisper.cpp on this machine: linked in through whisper-rs when
+/// built with the `local-whisper` feature, otherwise by running its CLI
+pub struct LocalWhisper {
+    /// The CLI to run; builds with whisper.cpp linked in only run one that is set
+    pub command: Option<String>,
+    pub model: PathBuf,
+}
+
+/// Loaded models, kept between turns since loading takes seconds. The preview model
+/// and the full one are both kept, so neither reloads after the other ran.
+#[cfg(feature = "local-whisper")]
+static CONTEXTS: Mutex<Vec<(PathBuf, whisper_rs::WhisperContext)>> = Mutex::new(Vec::new());
+#[cfg(feature = "local-whisper")]
+const MAX_LOADED_MODELS: usize = 2;
+
+impl LocalWhisper {
+    fn transcribe(&self, samples: &[f32], rate: u32) -> Result<String, AppError> {
+        #[cfg(feature = "local-whisper")]
+        if self.command.is_none() {
+            return self.transcribe_linked(samples, rate);
+        }
+        self.run_cli(samples, rate)
+    }
+
+    #[cfg(feature = "local-whisper")]
+    fn transcribe_linked(&self, samples: &[f32], rate: u32) -> Result<String, AppError> {
+        use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
+
+        let failed = |e: whisper_rs::WhisperError| {
+            AppError::Other(format!("Local transcription failed: {e}"))
+        };
+        let mut contexts = CONTEXTS
+            .lock()
+            .map_err(|_| AppError::Other("Whisper model lock poisoned".to_string()))?;
+        let index = match contexts.iter().position(|(model, _)| *model == self.model) {
+            Some(index) => index,
+            None => {
+                let path = self.model.to_string_lossy();
+                let loaded =
+                    WhisperContext::new_with_params(&path, WhisperContextParameters::default())
+                        .map_err(|e| {
+                            AppError::Other(format!("Failed to load whisper model '{path}': {e}"))
+                        })?;
+                if contexts.len() >= MAX_LOADED_MODELS {
+                    contexts.remove(0);
+                }
+                contexts.push((self.model.clone(), loaded));
+                contexts.len() - 1
+            }
+        };
+        let (_, loaded) = &contexts[index];
+
+        // whisper.cpp works on 16 kHz mono
+        let samples = crate::playback::resample_linear(samples, rate, 16_000);
+        let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+        params.set_language(Some("auto"));
+        params.set_print_progress(false);
+        params.set_print_realtime(false);
+        params.set_print_timestamps(false);
+        params.set_print_special(false);
+        let mut state = loaded.create_state().map_err(failed)?;
+        state.full(params, &samples).map_err(failed)?;
+        let mut lines = Vec::new();
+        for segment in 0..state.full_n_segments().map_err(failed)? {
+            lines.push(state.full_get_segment_text(segment).map_err(failed)?);
+        }
+        Ok(parse_transcript(&lines.join("\n")))
+    }
+
+    fn run_cli(&self, samples: &[f32], rate: u32) -> Result<String, AppError> {
+        // whisper.cpp only reads 16 kHz 16-bit WAV
+        let wav_16k = quality::encode(samples, rate, QualityPreset::VoiceSmall, None)?;
+
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "openclaw-stt-{}-{}.wav",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::write(&path, wav_16k)
+            .map_err(|e| AppError::Other(format!("Failed to write audio for whisper: {e}")))?;
+
+        let command = self.command.as_deref().unwrap_or(DEFAULT_WHISPER_COMMAND);
+        let output = Command::new(command)
+            .arg("-m")
+            .arg(&self.model)
+            .arg("-f")
+            .arg(&path)
+            .args(["-l", "auto", "-nt", "-np"])
+            .output();
+        let _ = fs::remove_file(&path);
+
+        let output = output.map_err(|e| {
+            AppError::Other(if e.kind() == ErrorKind::NotFound {
+                format!("whisper.cpp not found ('{command}'); install it or set whisper_command")
+            } else {
+                format!("Failed to run whisper.cpp: {e}")
+            })
+        })?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let reason = stderr.lines().rev().find(|l| !l.trim().is_empty());
+            return Err(AppError::Other(format!(
+                "Local transcription failed: {}",
+                reason.unwrap_or("whisper.cpp exited with an error")
+            )));
+        }
+        Ok(parse_transcript(&String::from_utf8_lossy(&output.stdout)))
+    }
+}
+
+impl SttProvider for LocalWhisper {
+    fn kind(&self) -> SttProviderKind {
+        SttProviderKind::LocalWhisper
+    }
+
+    fn prepare(&self, wav_bytes: Bytes) -> Result<Utterance, AppError> {
+        let (samples, rate) = decode_wav_mono(&wav_bytes)?;
+        Ok(Utterance::Transcript(self.transcribe(&samples, rate)?))
+    }
+}
+
+/// Join whisper.cpp's per-segment lines, dropping markers like `[BLANK_AUDIO]`
+fn parse_transcript(stdout: &str) -> String {
+    stdout
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .filter(|line| !(line.starts_with('[') && line.ends_with(']')))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Which provider each profile uses, from settings
+#[derive(Debug, Clone, Default)]
+pub struct SttConfig {
+    pub providers: HashMap<String, SttProviderKind>,
+    pub whisper_command: Option<String>,
+    pub whisper_model: Option<String>,
+    /// Transcribe locally for every profile, whatever `providers` says
+    pub prefer_local: bool,
+    pub preview_model: Option<String>,
+}
+
+impl SttConfig {
+    pub fn from_settings(settings: &AppSettings) -> Self {
+        Self {
+            providers: settings.stt_providers.clone(),
+            whisper_command: settings.whisper_command.clone(),
+            whisper_model: settings.whisper_model.clone(),
+            prefer_local: settings.prefer_local_stt,
+            preview_model: settings.whisper_preview_model.clone(),
+        }
+    }
+
+    pub fn provider_for(&self, profile_name: &str) -> Result<Box<dyn SttProvider>, AppError> {
+        let kind = if self.prefer_local {
+            SttProviderKind::LocalWhisper
+        } else {
+            self.providers
+                .get(profile_name)
+                .copied()
+                .unwrap_or_default()
+        };
+        match kind {
+            SttProviderKind::Gateway => Ok(Box::new(GatewayStt)),
+            SttProviderKind::LocalWhisper => Ok(Box::new(self.whisper().map_err(|_| {
+                AppError::Other(format!(
+                    "Profile '{profile_name}' uses local transcription \
+                     but no whisper model is set"
+                ))
+            })?)),
+        }
+    }
+
+    /// The local whisper.cpp install, whichever provider profiles use
+    pub fn whisper(&self) -> Result<LocalWhisper, AppError> {
+        let model = self
+            .whisper_model
+            .as_deref()
+            .ok_or_else(|| AppError::Other("No whisper model is set".to_string()))?;
+        Ok(LocalWhisper {
+            command: self.whisper_command.clone(),
+            model: PathBuf::from(model),
+        })
+    }
+
+    /// whisper.cpp with the preview model, for profiles the gateway transcribes for;
+    /// a local transcript is already final, so it needs no preview
+    pub fn previewer(&self, profile_name: &str) -> Option<LocalWhisper> {
+        let gateway = self
+            .provider_for(profile_name)
+            .is_ok_and(|provider| provider.kind() == SttProviderKind::Gateway);
+        Some(LocalWhisper {
+            command: self.whisper_command.clone(),
+            model: PathBuf::from(self.preview_model.as_deref().filter(|_| gateway)?),
+        })
+    }
+}
+
+pub struct SttState {
+    pub config: Arc<Mutex<SttConfig>>,
+}
+
+impl SttState {
+    pub fn new() -> Self {
+        Self {
+            config: Arc::new(Mutex::new(SttConfig::default())),
+        }
+    }
+
+    pub fn configure(&self, config: SttConfig) {
+        if let Ok(mut current) = self.config.lock() {
+            *current = config;
+        }
+    }
+
+    pub fn provider_for(&self, profile_name: &str) -> Result<Box<dyn SttProvider>, AppError> {
+        self.config
+            .lock()
+            .map_err(|_| AppError::Other("STT config lock poisoned".to_string()))?
+            .provider_for(profile_name)
+    }
+}
+
+/// Run the profile's provider on a recording without blocking the runtime
+pub async fn prepare(
+    app: &AppHandle,
+    profile_name: &str,
+    wav_bytes: Bytes,
+) -> Result<Utterance, AppError> {
+    let provider = app.state::<SttState>().provider_for(profile_name)?;
+    events::record_state(
+        app,
+        "stt-started",
+        serde_json::json!({ "provider": provider.kind() }),
+    );
+    tauri::async_runtime::spawn_blocking(move || provider.prepare(wav_bytes))
+        .await
+        .map_err(|e| AppError::Other(format!("Transcription task failed: {e}")))?
+}
+
+/// Transcribe a recording with the preview model while it is uploaded, and show the
+/// result as a provisional transcript until the gateway's own arrives
+pub fn preview(app: &AppHandle, session_id: &str, profile_name: &str, wav_bytes: Bytes) {
+    let whisper = app
+        .state::<SttState>()
+        .config
+        .lock()
+        .ok()
+        .and_then(|config| config.previewer(profile_name));
+    let Some(whisper) = whisper else {
+        return;
+    };
+    let app = app.clone();
+    let session_id = session_id.to_string();
+    tauri::async_runtime::spawn(async move {
+        let started = Instant::now();
+        let result = tauri::async_runtime::spawn_blocking(move || whisper.prepare(wav_bytes)).await;
+        let text = match result {
+            Ok(Ok(Utterance::Transcript(text))) if !text.is_empty() => text,
+            Ok(Ok(_)) => return,
+            Ok(Err(e)) => {
+                eprintln!("Transcript preview failed: {e}");
+                return;
+            }
+            Err(e) => {
+                eprintln!("Transcript preview task failed: {e}");
+                return;
+            }
+        };
+        let preview = TranscriptPreview {
+            session_id,
+            text,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+        };
+        events::record_state(
+            &app,
+            "transcript-preview",
+            serde_json::json!({ "sessionId": preview.session_id, "elapsedMs": preview.elapsed_ms }),
+        );
+        let _ = app.emit("transcript-preview", &preview);
+    });
+}
+
+/// Local transcription to fall back on when the gateway can't be reached, if a
+/// whisper model is set
+pub fn fallback(app: &AppHandle) -> Option<LocalWhisper> {
+    app.state::<SttState>().config.lock().ok()?.whisper().ok()
+}
+
+/// Transcribe a recording the gateway was to transcribe but couldn't be reached for
+pub async fn transcribe_offline(
+    app: &AppHandle,
+    whisper: LocalWhisper,
+    wav_bytes: Bytes,
+) -> Option<String> {
+    events::record_state(app, "stt-fallback", ());
+    let result = tauri::async_runtime::spawn_blocking(move || whisper.prepare(wav_bytes)).await;
+    match result {
+        Ok(Ok(Utterance::Transcript(text))) => Some(text).filter(|text| !text.is_empty()),
+        Ok(Ok(Utterance::Audio(_))) => None,
+        Ok(Err(e)) => {
+            eprintln!("Local fallback transcription failed: {e}");
+            None
+        }
+        Err(e) => {
+            eprintln!("Local fallback transcription task failed: {e}");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_transcript_joins_segments_and_drops_markers() {
+        let stdout = "\n [BLANK_AUDIO]\n Turn on the\n lights please.\n\n";
+        assert_eq!(parse_transcript(stdout), "Turn on the lights please.");
+        assert_eq!(parse_transcript("[BLANK_AUDIO]\n"), "");
+    }
+
+    #[test]
+    fn test_preview_only_for_profiles_the_gateway_transcribes() {
+        let mut config = SttConfig {
+            providers: HashMap::from([("alice".to_string(), SttProviderKind::LocalWhisper)]),
+            whisper_model: Some("/models/ggml-base.bin".to_string()),
+            ..SttConfig::default()
+        };
+        assert!(config.previewer("bob").is_none());
+
+        config.preview_model = Some("/models/ggml-tiny-q8_0.bin".to_string());
+        assert_eq!(
+            config.previewer("bob").map(|whisper| whisper.model),
+            Some(PathBuf::from("/models/ggml-tiny-q8_0.bin"))
+        );
+        assert!(config.previewer("alice").is_none());
+    }
+
+    #[test]
+    fn test_providers_are_chosen_per_profile() {
+        let mut config = SttConfig {
+            providers: HashMap::from([("alice".to_string(), SttProviderKind::LocalWhisper)]),
+            ..SttConfig::default()
+        };
+        assert_eq!(
+            config.provider_for("bob").unwrap().kind(),
+            SttProviderKind::Gateway
+        );
+        // Local transcription without a model can't work
+        assert!(config.provider_for("alice").is_err());
+
+        config.whisper_model = Some("/models/ggml-base.bin".to_string());
+        assert_eq!(
+            config.provider_for("alice").unwrap().kind(),
+            SttProviderKind::LocalWhisper
+        );
+
+        // Preferring local transcription overrides the per-profile choice
+        config.prefer_local = true;
+        assert_eq!(
+            config.provider_for("bob").unwrap().kind(),
+            SttProviderKind::LocalWhisper
+        );
+    }
+
+    #[test]
+    fn test_gateway_passes_audio_through() {
+        let wav = Bytes::from_static(&[1, 2, 3]);
+        assert_eq!(
+            GatewayStt.prepare(wav.clone()).unwrap(),
+            Utterance::Audio(wav)
+        );
+    }
+}
@@ -0,0 +1,169 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tauri::{AppHandle, Manager};
+
+use crate::events;
+use crate::types::{TaskHealth, TaskStatus};
+
+/// Wait before the first restart; doubles with each quick failure after it
+const FIRST_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+/// A run that lasted this long wasn't a quick failure, so the backoff starts over
+const HEALTHY_RUN: Duration = Duration::from_secs(60);
+/// Quick failures in a row after which a task is given up on
+const MAX_QUICK_FAILURES: u32 = 5;
+
+/// When a supervised task is started again after it ends
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Restart {
+    /// Only after a panic; a task that returns is done (monitors that loop until quit)
+    OnPanic,
+    /// Never; a panic leaves it failed
+    Never,
+}
+
+/// How a run of a task ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Exit {
+    Returned,
+    Panicked,
+}
+
+/// How long to wait before running the task again, or None to leave it stopped.
+/// `quick_failures` counts this run and the runs that ended early just before it.
+fn next_run(policy: Restart, exit: Exit, quick_failures: u32) -> Option<Duration> {
+    let restart = match policy {
+        Restart::OnPanic => exit == Exit::Panicked,
+        Restart::Never => false,
+    };
+    if !restart || quick_failures > MAX_QUICK_FAILURES {
+        return None;
+    }
+    Some((FIRST_BACKOFF * 2u32.pow(quick_failures.saturating_sub(1))).min(MAX_BACKOFF))
+}
+
+/// The background tasks started with `supervise` and how each is doing
+pub struct TaskState {
+    tasks: Arc<Mutex<BTreeMap<&'static str, TaskHealth>>>,
+}
+
+impl TaskState {
+    pub fn new() -> Self {
+        Self {
+            tasks: Arc::new(Mutex::new(BTreeMap::new())),
+        }
+    }
+
+    fn update(&self, name: &'static str, change: impl FnOnce(&mut TaskHealth)) {
+        if let Ok(mut tasks) = self.tasks.lock() {
+            change(tasks.entry(name).or_insert_with(|| TaskHealth {
+                name: name.to_string(),
+                status: TaskStatus::Running,
+                restarts: 0,
+                last_error: None,
+                dropped: 0,
+            }));
+        }
+    }
+
+    /// Every supervised task, by name
+    pub fn health(&self) -> Vec<TaskHealth> {
+        self.tasks
+            .lock()
+            .map(|tasks| tasks.values().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Count work `name` had to turn away, e.g. because its queue was full
+pub fn dropped(app: &AppHandle, name: &'static str) {
+    app.state::<TaskState>()
+        .update(name, |task| task.dropped += 1);
+}
+
+/// Run `task` in the background as `name`. A panic stays inside the task: the
+/// supervisor notes it and, as `policy` says, starts the task again after a backoff.
+pub fn supervise<F, Fut>(app: &AppHandle, name: &'static str, policy: Restart, task: F)
+where
+    F: Fn(AppHandle) -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<TaskState>();
+        let mut quick_failures = 0;
+        loop {
+            state.update(name, |task| task.status = TaskStatus::Running);
+            let started = Instant::now();
+            let exit = match tauri::async_runtime::spawn(task(app.clone())).await {
+                Ok(()) => Exit::Returned,
+                Err(e) => {
+                    let error = e.to_string();
+                    eprintln!("Background task '{name}' panicked: {error}");
+                    events::record_state(
+                        &app,
+                        "task-panicked",
+                        serde_json::json!({ "task": name, "error": error }),
+                    );
+                    state.update(name, |task| task.last_error = Some(error));
+                    Exit::Panicked
+                }
+            };
+            quick_failures = if started.elapsed() < HEALTHY_RUN {
+                quick_failures + 1
+            } else {
+                1
+            };
+
+            let Some(wait) = next_run(policy, exit, quick_failures) else {
+                let status = match exit {
+                    Exit::Returned => TaskStatus::Stopped,
+                    Exit::Panicked => TaskStatus::Failed,
+                };
+                state.update(name, |task| task.status = status);
+                events::record_state(
+                    &app,
+                    "task-stopped",
+                    serde_json::json!({ "task": name, "status": status }),
+                );
+                return;
+            };
+            state.update(name, |task| {
+                task.status = TaskStatus::Restarting;
+                task.restarts += 1;
+            });
+            tokio::time::sleep(wait).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restart_policy_and_backoff() {
+        assert_eq!(next_run(Restart::OnPanic, Exit::Returned, 1), None);
+        assert_eq!(next_run(Restart::Never, Exit::Panicked, 1), None);
+        assert_eq!(
+            next_run(Restart::OnPanic, Exit::Panicked, 1),
+            Some(FIRST_BACKOFF)
+        );
+        assert_eq!(
+            next_run(Restart::OnPanic, Exit::Panicked, 3),
+            Some(FIRST_BACKOFF * 4)
+        );
+        assert_eq!(
+            next_run(Restart::OnPanic, Exit::Panicked, MAX_QUICK_FAILURES),
+            Some(FIRST_BACKOFF * 16)
+        );
+        // Given up on after too many quick failures
+        assert_eq!(
+            next_run(Restart::OnPanic, Exit::Panicked, MAX_QUICK_FAILURES + 1),
+            None
+        );
+    }
+}
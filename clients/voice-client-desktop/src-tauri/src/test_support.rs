@@ -0,0 +1,30 @@
+use std::fs;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+/// An empty directory under the system temp dir for one test, removed when dropped
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    /// `name` must be unique across tests, which run in parallel
+    pub fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("openclaw-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        Self(dir)
+    }
+}
+
+impl Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
@@ -0,0 +1,107 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tauri::{AppHandle, Emitter};
+
+use crate::events;
+use crate::types::{TriggerSource, TriggerSuppressed};
+
+/// Debounce layer in front of recording triggers.
+/// Key bounce and wake-word false positives can fire several triggers within a few
+/// milliseconds; anything arriving within `min_gap` of the last accepted trigger is dropped.
+pub struct TriggerGate {
+    pub last_accepted: Arc<Mutex<Option<Instant>>>,
+    pub min_gap: Arc<Mutex<Duration>>,
+}
+
+impl TriggerGate {
+    pub fn new(min_gap: Duration) -> Self {
+        Self {
+            last_accepted: Arc::new(Mutex::new(None)),
+            min_gap: Arc::new(Mutex::new(min_gap)),
+        }
+    }
+
+    pub fn set_min_gap(&self, min_gap: Duration) {
+        if let Ok(mut gap) = self.min_gap.lock() {
+            *gap = min_gap;
+        }
+    }
+
+    /// Accept the trigger (recording `now`) or return how long ago the last one was accepted
+    pub fn check(&self, now: Instant) -> Result<(), Duration> {
+        let min_gap = self.min_gap.lock().map(|gap| *gap).unwrap_or_default();
+        let mut last = match self.last_accepted.lock() {
+            Ok(last) => last,
+            Err(_) => return Ok(()),
+        };
+
+        if let Some(previous) = *last {
+            let elapsed = now.saturating_duration_since(previous);
+            if elapsed < min_gap {
+                return Err(elapsed);
+            }
+        }
+
+        *last = Some(now);
+        Ok(())
+    }
+}
+
+/// Pass a trigger through the gate, emitting "trigger-suppressed" if it is dropped
+pub fn admit(app: &AppHandle, gate: &TriggerGate, source: TriggerSource) -> Result<(), String> {
+    match gate.check(Instant::now()) {
+        Ok(()) => Ok(()),
+        Err(elapsed) => {
+            let min_gap = gate.min_gap.lock().map(|gap| *gap).unwrap_or_default();
+            let payload = TriggerSuppressed {
+                source,
+                since_last_ms: elapsed.as_millis() as u64,
+                min_gap_ms: min_gap.as_millis() as u64,
+            };
+            events::record_state(app, "trigger-suppressed", &payload);
+            let _ = app.emit("trigger-suppressed", &payload);
+            Err(format!(
+                "Trigger ignored: {}ms since last trigger (cooldown {}ms)",
+                payload.since_last_ms, payload.min_gap_ms
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_trigger_accepted() {
+        let gate = TriggerGate::new(Duration::from_millis(300));
+        assert!(gate.check(Instant::now()).is_ok());
+    }
+
+    #[test]
+    fn test_trigger_within_gap_suppressed() {
+        let gate = TriggerGate::new(Duration::from_millis(300));
+        let start = Instant::now();
+        assert!(gate.check(start).is_ok());
+        let result = gate.check(start + Duration::from_millis(50));
+        assert_eq!(result, Err(Duration::from_millis(50)));
+    }
+
+    #[test]
+    fn test_suppressed_trigger_does_not_extend_cooldown() {
+        let gate = TriggerGate::new(Duration::from_millis(300));
+        let start = Instant::now();
+        assert!(gate.check(start).is_ok());
+        assert!(gate.check(start + Duration::from_millis(200)).is_err());
+        assert!(gate.check(start + Duration::from_millis(310)).is_ok());
+    }
+
+    #[test]
+    fn test_zero_gap_disables_gate() {
+        let gate = TriggerGate::new(Duration::ZERO);
+        let start = Instant::now();
+        assert!(gate.check(start).is_ok());
+        assert!(gate.check(start).is_ok());
+    }
+}
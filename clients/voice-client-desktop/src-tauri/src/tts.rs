@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::error::AppError;
+use crate::types::{AppSettings, TtsProviderKind};
+
+const DEFAULT_PIPER_COMMAND: &str = "piper";
+
+/// Piper on this machine, speaking with one voice model
+#[derive(Debug, Clone, PartialEq)]
+pub struct Piper {
+    /// Executable to run; `piper` on PATH if unset
+    pub command: Option<String>,
+    /// The voice's `.onnx` file; its `.onnx.json` config must sit next to it
+    pub model: PathBuf,
+}
+
+impl Piper {
+    /// Synthesize `text` to WAV at the voice's own rate. Blocks while Piper runs,
+    /// so call it off the async runtime.
+    pub fn synthesize(&self, text: &str) -> Result<Vec<u8>, AppError> {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "openclaw-tts-{}-{}.wav",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        let command = self.command.as_deref().unwrap_or(DEFAULT_PIPER_COMMAND);
+        let mut child = Command::new(command)
+            .arg("--model")
+            .arg(&self.model)
+            .arg("--output_file")
+            .arg(&path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| {
+                AppError::Other(if e.kind() == ErrorKind::NotFound {
+                    format!("Piper not found ('{command}'); install it or set piper_command")
+                } else {
+                    format!("Failed to run Piper: {e}")
+                })
+            })?;
+        // Piper reads the text from stdin and starts once it is closed
+        if let Some(mut stdin) = child.stdin.take() {
+            let written = stdin.write_all(speakable(text).as_bytes());
+            drop(stdin);
+            if let Err(e) = written {
+                let _ = child.kill();
+                let _ = child.wait();
+                let _ = fs::remove_file(&path);
+                return Err(AppError::Other(format!(
+                    "Failed to pass text to Piper: {e}"
+                )));
+            }
+        }
+        let output = child
+            .wait_with_output()
+            .map_err(|e| AppError::Other(format!("Failed to run Piper: {e}")));
+        let wav = match output {
+            Ok(output) if output.status.success() => {
+                fs::read(&path).map_err(|e| AppError::Other(format!("Piper wrote no audio: {e}")))
+            }
+            Ok(output) => {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                let reason = stderr.lines().rev().find(|l| !l.trim().is_empty());
+                Err(AppError::Other(format!(
+                    "Local speech failed: {}",
+                    reason.unwrap_or("Piper exited with an error")
+                )))
+            }
+            Err(e) => Err(e),
+        };
+        let _ = fs::remove_file(&path);
+        wav
+    }
+}
+
+/// Piper speaks each line on its own, so a reply wrapped over several lines would be
+/// read with a pause at every break; paragraphs keep theirs.
+fn speakable(text: &str) -> String {
+    let mut lines = Vec::new();
+    for paragraph in text.split("\n\n") {
+        let line = paragraph.split_whitespace().collect::<Vec<_>>().join(" ");
+        if !line.is_empty() {
+            lines.push(line);
+        }
+    }
+    lines.join("\n") + "\n"
+}
+
+/// Which profiles speak with Piper, from settings
+#[derive(Debug, Clone, Default)]
+pub struct TtsConfig {
+    pub providers: HashMap<String, TtsProviderKind>,
+    pub piper_command: Option<String>,
+    pub piper_model: Option<String>,
+}
+
+impl TtsConfig {
+    pub fn from_settings(settings: &AppSettings) -> Self {
+        Self {
+            providers: settings.tts_providers.clone(),
+            piper_command: settings.piper_command.clone(),
+            piper_model: settings.piper_model.clone(),
+        }
+    }
+
+    /// Piper, if `profile_name` speaks with it; profiles left to the gateway get `None`
+    pub fn piper_for(&self, profile_name: &str) -> Option<Piper> {
+        match self
+            .providers
+            .get(profile_name)
+            .copied()
+            .unwrap_or_default()
+        {
+            TtsProviderKind::Gateway => None,
+            TtsProviderKind::Piper => Some(Piper {
+                command: self.piper_command.clone(),
+                model: PathBuf::from(self.piper_model.as_deref()?),
+            }),
+        }
+    }
+}
+
+pub struct TtsState {
+    pub config: Arc<Mutex<TtsConfig>>,
+}
+
+impl TtsState {
+    pub fn new() -> Self {
+        Self {
+            config: Arc::new(Mutex::new(TtsConfig::default())),
+        }
+    }
+
+    pub fn configure(&self, config: TtsConfig) {
+        if let Ok(mut current) = self.config.lock() {
+            *current = config;
+        }
+    }
+
+    pub fn piper_for(&self, profile_name: &str) -> Option<Piper> {
+        self.config.lock().ok()?.piper_for(profile_name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrapped_lines_are_joined_but_paragraphs_kept() {
+        let text = "The lights in the\nkitchen are on.\n\n  Anything else?  \n\n\n";
+        assert_eq!(
+            speakable(text),
+            "The lights in the kitchen are on.\nAnything else?\n"
+        );
+    }
+
+    #[test]
+    fn test_piper_is_chosen_per_profile() {
+        let mut config = TtsConfig {
+            providers: HashMap::from([("alice".to_string(), TtsProviderKind::Piper)]),
+            ..TtsConfig::default()
+        };
+        // Without a voice there is nothing to speak with
+        assert_eq!(config.piper_for("alice"), None);
+
+        config.piper_model = Some("/voices/en_US-lessac-medium.onnx".to_string());
+        assert_eq!(
+            config.piper_for("alice"),
+            Some(Piper {
+                command: None,
+                model: PathBuf::from("/voices/en_US-lessac-medium.onnx"),
+            })
+        );
+        assert_eq!(config.piper_for("bob"), None);
+    }
+}
@@ -0,0 +1,310 @@
+use crate::types::{CompletedTurn, VoiceEvent};
+
+/// Id of a completed turn, unique across sessions; see `CompletedTurn::turn_id`
+pub fn turn_id(session_id: &str, completed_at: &str) -> String {
+    format!("{session_id}/{completed_at}")
+}
+
+/// The session id and completion timestamp of a turn id
+pub fn parse_turn_id(turn_id: &str) -> Result<(&str, &str), String> {
+    turn_id
+        .rsplit_once('/')
+        .filter(|(session_id, completed_at)| !session_id.is_empty() && !completed_at.is_empty())
+        .ok_or_else(|| format!("Invalid turn id '{}'", turn_id.escape_debug()))
+}
+
+/// Assembles the streamed `openclaw` deltas of one turn into its final reply.
+/// History, clipboard, notifications and exports consume the merged result
+/// instead of each re-implementing delta handling.
+#[derive(Debug, Default)]
+pub struct TurnAggregator {
+    user_text: Option<String>,
+    reply: String,
+}
+
+impl TurnAggregator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed one streamed event; returns the finished turn when the reply completes
+    pub fn push(&mut self, event: &VoiceEvent) -> Option<CompletedTurn> {
+        match event {
+            VoiceEvent::User { text, .. } => {
+                *self = Self {
+                    user_text: Some(text.clone()),
+                    ..Self::default()
+                };
+                None
+            }
+            VoiceEvent::Openclaw {
+                text,
+                done,
+                timestamp,
+            } => {
+                self.reply.push_str(text);
+                if !*done {
+                    return None;
+                }
+                let reply = std::mem::take(&mut self.reply);
+                let user_text = self.user_text.take();
+                (!reply.trim().is_empty()).then(|| CompletedTurn {
+                    turn_id: String::new(),
+                    user_text,
+                    reply,
+                    completed_at: timestamp.clone(),
+                })
+            }
+            VoiceEvent::System { status, .. } => {
+                // A failed or aborted turn must not leak into the next one
+                if matches!(status.as_str(), "error" | "timeout" | "aborted") {
+                    *self = Self::default();
+                }
+                None
+            }
+            VoiceEvent::Audio { .. } => None,
+        }
+    }
+
+    /// The reply of the turn in progress as far as it has arrived
+    pub fn reply(&self) -> &str {
+        &self.reply
+    }
+
+    /// Give up on the turn in progress, returning what arrived of it (if anything) as
+    /// of `at`, and start afresh
+    pub fn abandon(&mut self, at: &str) -> Option<CompletedTurn> {
+        let Self { user_text, reply } = std::mem::take(self);
+        (user_text.is_some() || !reply.trim().is_empty()).then(|| CompletedTurn {
+            turn_id: String::new(),
+            user_text,
+            reply,
+            completed_at: at.to_string(),
+        })
+    }
+}
+
+/// Split a reply into chunks of at most `max_chars` characters for notifications and
+/// speech. Chunks break between sentences where possible, then between words; only a
+/// single word longer than `max_chars` is cut. 0 keeps the reply whole.
+pub fn segment(text: &str, max_chars: usize) -> Vec<String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Vec::new();
+    }
+    if max_chars == 0 || text.chars().count() <= max_chars {
+        return vec![text.to_string()];
+    }
+    let mut segments = Vec::new();
+    for sentence in sentences(text) {
+        if sentence.chars().count() > max_chars {
+            let words = sentence
+                .split_whitespace()
+                .flat_map(|word| split_word(word, max_chars));
+            pack(&mut segments, words, max_chars);
+        } else {
+            pack(
+                &mut segments,
+                std::iter::once(sentence.to_string()),
+                max_chars,
+            );
+        }
+    }
+    segments
+}
+
+/// Where sentences end: at `.`, `!`, `?` or `…` followed by whitespace, and at line breaks
+fn sentence_ends(text: &str) -> impl Iterator<Item = usize> + '_ {
+    let mut chars = text.char_indices().peekable();
+    std::iter::from_fn(move || {
+        while let Some((i, c)) = chars.next() {
+            let end = match c {
+                '\n' => Some(i),
+                '.' | '!' | '?' | '…' => chars
+                    .peek()
+                    .filter(|(_, next)| next.is_whitespace())
+                    .map(|_| i + c.len_utf8()),
+                _ => None,
+            };
+            if end.is_some() {
+                return end;
+            }
+        }
+        None
+    })
+}
+
+fn sentences(text: &str) -> Vec<&str> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    for end in sentence_ends(text) {
+        sentences.push(text[start..end].trim());
+        start = end;
+    }
+    sentences.push(text[start..].trim());
+    sentences.retain(|sentence| !sentence.is_empty());
+    sentences
+}
+
+/// Where the first `count` sentences of a reply that may still be streaming in end.
+/// None while there are fewer, or the last of them isn't followed by anything yet.
+pub fn end_of_sentences(text: &str, count: usize) -> Option<usize> {
+    let mut start = 0;
+    let mut seen = 0;
+    for end in sentence_ends(text) {
+        if !text[start..end].trim().is_empty() {
+            seen += 1;
+        }
+        start = end;
+        if seen >= count {
+            return Some(end);
+        }
+    }
+    None
+}
+
+fn split_word(word: &str, max_chars: usize) -> Vec<String> {
+    let chars: Vec<char> = word.chars().collect();
+    chars
+        .chunks(max_chars)
+        .map(|chunk| chunk.iter().collect())
+        .collect()
+}
+
+/// Add each piece to the last segment while it has room, else start a new one
+fn pack(segments: &mut Vec<String>, pieces: impl Iterator<Item = String>, max_chars: usize) {
+    for piece in pieces {
+        match segments.last_mut() {
+            Some(last) if last.chars().count() + 1 + piece.chars().count() <= max_chars => {
+                last.push(' ');
+                last.push_str(&piece);
+            }
+            _ => segments.push(piece),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(text: &str) -> VoiceEvent {
+        VoiceEvent::User {
+            text: text.to_string(),
+            confidence: 1.0,
+            timestamp: "t0".to_string(),
+        }
+    }
+
+    fn reply(text: &str, done: bool, timestamp: &str) -> VoiceEvent {
+        VoiceEvent::Openclaw {
+            text: text.to_string(),
+            done,
+            timestamp: timestamp.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_deltas_merge_on_done() {
+        let mut aggregator = TurnAggregator::new();
+        assert!(aggregator.push(&user("what time is it")).is_none());
+        assert!(aggregator.push(&reply("It is ", false, "t1")).is_none());
+        assert!(aggregator.push(&reply("noon.", false, "t2")).is_none());
+        let turn = aggregator.push(&reply("", true, "t3")).unwrap();
+        assert_eq!(turn.user_text.as_deref(), Some("what time is it"));
+        assert_eq!(turn.reply, "It is noon.");
+        assert_eq!(turn.completed_at, "t3");
+    }
+
+    #[test]
+    fn test_abandoned_turn_keeps_what_arrived() {
+        let mut aggregator = TurnAggregator::new();
+        aggregator.push(&user("summarise my inbox"));
+        aggregator.push(&reply("You have three", false, "t1"));
+        let partial = aggregator.abandon("t9").unwrap();
+        assert_eq!(partial.user_text.as_deref(), Some("summarise my inbox"));
+        assert_eq!(partial.reply, "You have three");
+        assert_eq!(partial.completed_at, "t9");
+
+        // Nothing is left over for the next turn
+        assert!(aggregator.abandon("t10").is_none());
+        assert_eq!(
+            aggregator.push(&reply("Hi", true, "t11")).unwrap().reply,
+            "Hi"
+        );
+    }
+
+    #[test]
+    fn test_repeated_deltas_are_kept() {
+        let mut aggregator = TurnAggregator::new();
+        aggregator.push(&reply("ha", false, "t1"));
+        aggregator.push(&reply("ha", false, "t1"));
+        // The done event may carry the last delta
+        let turn = aggregator.push(&reply("!", true, "t2")).unwrap();
+        assert_eq!(turn.reply, "haha!");
+    }
+
+    #[test]
+    fn test_turn_ids_round_trip() {
+        let id = turn_id("voice-1-a", "2026-01-01T10:00:00.000Z");
+        assert_eq!(
+            parse_turn_id(&id).unwrap(),
+            ("voice-1-a", "2026-01-01T10:00:00.000Z")
+        );
+        assert!(parse_turn_id("no-slash").is_err());
+        assert!(parse_turn_id("voice-1-a/").is_err());
+    }
+
+    #[test]
+    fn test_error_discards_partial_reply() {
+        let mut aggregator = TurnAggregator::new();
+        aggregator.push(&reply("Half a", false, "t1"));
+        aggregator.push(&VoiceEvent::System {
+            status: "error".to_string(),
+            message: None,
+            timestamp: "t2".to_string(),
+        });
+        assert!(aggregator.push(&reply("", true, "t3")).is_none());
+    }
+
+    #[test]
+    fn test_segments_break_between_sentences() {
+        let reply = "It is sunny. Highs of 21 degrees! Bring a jacket tonight?";
+        assert_eq!(segment(reply, 0), vec![reply]);
+        assert_eq!(segment(reply, 100), vec![reply]);
+        assert_eq!(
+            segment(reply, 33),
+            vec![
+                "It is sunny. Highs of 21 degrees!",
+                "Bring a jacket tonight?"
+            ]
+        );
+        // Not at decimal points, but at line breaks
+        assert_eq!(
+            segment("Version 2.5 is out.\nUpdate now", 20),
+            vec!["Version 2.5 is out.", "Update now"]
+        );
+        assert!(segment("   ", 10).is_empty());
+    }
+
+    #[test]
+    fn test_end_of_sentences_waits_for_what_follows() {
+        let reply = "It is sunny. Highs of 21!\n\nBring a jacket";
+        assert_eq!(end_of_sentences(reply, 1), Some(12));
+        assert_eq!(end_of_sentences(reply, 2), Some(25));
+        // The blank line isn't a sentence, and the last one may still go on
+        assert_eq!(end_of_sentences(reply, 3), None);
+        assert_eq!(end_of_sentences("It is sunny.", 1), None);
+    }
+
+    #[test]
+    fn test_long_sentences_break_between_words() {
+        let segments = segment("one two three four five. Six.", 9);
+        assert_eq!(segments, vec!["one two", "three", "four", "five.", "Six."]);
+        // A word longer than the limit is the only thing cut
+        assert_eq!(
+            segment("Supercalifragilistic ok. Yes.", 8),
+            vec!["Supercal", "ifragili", "stic ok.", "Yes."]
+        );
+    }
+}
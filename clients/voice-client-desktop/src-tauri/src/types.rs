@@ -1,24 +1,267 @@
+use std::collections::HashMap;
+
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// Audio input device for microphone selection
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct AudioDevice {
     pub name: String,
     pub id: String,
     pub is_default: bool,
+    /// What the driver says it can capture, e.g. `i16 44100-48000 Hz 2 ch`
+    pub supported_formats: Vec<String>,
+    /// Name the user gave the device, shown instead of the driver's
+    pub alias: Option<String>,
+    /// Marked as a favorite; favorites are listed first
+    pub favorite: bool,
 }
 
-/// Application settings — persisted to store + keyring
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// An audio backend cpal can record through, for the `audio_host` setting
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioHost {
+    /// cpal's name for it: `ALSA`, `JACK`, `WASAPI`, `ASIO`, `CoreAudio`, ...
+    pub name: String,
+    /// The platform's default backend, used when `audio_host` is unset
+    pub is_default: bool,
+    /// The backend recordings use now
+    pub selected: bool,
+}
+
+/// Capture format forced for a device whose driver reports an unusable default
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(default, rename_all = "camelCase")]
+pub struct DeviceFormat {
+    /// Sample format as cpal names it: `i16`, `i32`, `f32`, `u8`, ...
+    pub sample_format: Option<String>,
+    /// Sample rate in Hz; the device's default rate when unset (if supported)
+    pub sample_rate: Option<u32>,
+    /// Frames the driver delivers per callback; the driver's choice when unset
+    pub buffer_size: Option<u32>,
+}
+
+/// A range of capture configurations an input device supports
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureFormatRange {
+    /// Sample format as cpal names it: `i16`, `i32`, `f32`, `u8`, ...
+    pub sample_format: String,
+    pub channels: u16,
+    pub min_sample_rate: u32,
+    pub max_sample_rate: u32,
+    /// Buffer sizes the driver accepts, in frames; None when it doesn't say
+    pub min_buffer_size: Option<u32>,
+    pub max_buffer_size: Option<u32>,
+}
+
+/// What an input device can capture, as `get_device_capabilities` returns it
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
+pub struct DeviceCapabilities {
+    /// Key for a `device_formats` entry pinning this device's format
+    pub id: String,
+    pub name: String,
+    /// The driver's default format, used unless one is pinned
+    pub default_sample_format: Option<String>,
+    pub default_sample_rate: Option<u32>,
+    pub formats: Vec<CaptureFormatRange>,
+    /// The `device_formats` entry recordings from this device use, if any
+    pub pinned: Option<DeviceFormat>,
+}
+
+/// How the user labelled an input device
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(default, rename_all = "camelCase")]
+pub struct DeviceLabel {
+    pub alias: Option<String>,
+    pub favorite: bool,
+}
+
+/// Application settings — persisted to store + keyring
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default, rename_all = "camelCase")]
 pub struct AppSettings {
     pub gateway_url: String,
     pub token: String,
     pub profile_name: String,
     pub session_key: Option<String>,
+    /// Language tag (`de`, `pt-BR`) replies are requested in, whatever language is spoken
+    pub response_language: Option<String>,
+    /// Ask for short spoken-style replies and cut them off after `brief_max_sentences`
+    pub brief_mode: bool,
+    /// Sentences a reply is cut after in brief mode, from 1 to 10
+    pub brief_max_sentences: u64,
+    /// Workspace sent as `X-Workspace` on every request, for gateways serving several teams
+    pub workspace: Option<String>,
     pub microphone_device_id: Option<String>,
+    /// Audio backend microphones are opened through, by cpal's name for it (`JACK` on
+    /// Linux, `ASIO` on Windows); the platform default when unset
+    pub audio_host: Option<String>,
+    /// The one input channel recorded, counting from 1, for an interface with the
+    /// microphone on a later input; all channels are mixed to mono when unset
+    pub input_channel: Option<u16>,
+    /// Rumble and DC offset below this frequency (Hz) are filtered out of the microphone
+    /// as it is captured; 0 turns the filter off
+    pub capture_high_pass_hz: u64,
+    /// Stored by physical key (`control+KeyQ`), so it survives a keyboard layout switch
     pub push_to_talk_hotkey: Option<String>,
+    /// Layout hotkeys are typed and shown in; detected from the OS when unset
+    pub keyboard_layout: Option<KeyboardLayout>,
+    /// Minimum gap between accepted recording triggers (0 disables the cooldown)
+    pub trigger_cooldown_ms: u64,
+    /// Pause always-listening features after this much user inactivity (0 disables)
+    pub idle_pause_secs: u64,
+    /// Pause always-listening features while the screen is locked
+    pub pause_on_lock: bool,
+    /// Number of entries kept in the diagnostics event log (0 disables it)
+    pub event_log_capacity: usize,
+    /// How endpoint URLs are built, e.g. `{base}/v1/{endpoint}` behind a path-rewriting proxy
+    pub endpoint_template: String,
+    /// Per-endpoint templates keyed by endpoint path (`audio`, `session/new`, ...)
+    pub endpoint_overrides: HashMap<String, String>,
+    /// Log gateway request/response metadata (never bodies or tokens) to http.log
+    pub http_trace: bool,
+    /// Drop recorded segments that don't match the enrolled voiceprint
+    pub speaker_filter: bool,
+    /// Minimum voiceprint similarity (-1..1) for a segment to be kept
+    pub speaker_filter_threshold: f32,
+    /// Attach the frontmost app, window title, locale and time zone to each turn
+    pub share_context: bool,
+    /// How recordings are encoded for upload
+    pub audio_quality: QualityPreset,
+    /// Speech-to-text provider per profile name; profiles not listed use the gateway
+    pub stt_providers: HashMap<String, SttProviderKind>,
+    /// whisper.cpp executable for local transcription (`whisper-cli` on PATH if unset)
+    pub whisper_command: Option<String>,
+    /// ggml model file passed to whisper.cpp
+    pub whisper_model: Option<String>,
+    /// Transcribe locally for every profile; needs `whisper_model`
+    pub prefer_local_stt: bool,
+    /// Small, fast ggml model (whisper tiny, int8) for a provisional transcript shown
+    /// while the gateway transcribes; no preview when unset
+    pub whisper_preview_model: Option<String>,
+    /// Text-to-speech per profile name, for messages and cached answers the gateway
+    /// can't voice; profiles not listed only play the gateway's speech
+    pub tts_providers: HashMap<String, TtsProviderKind>,
+    /// Piper executable for local speech (`piper` on PATH if unset)
+    pub piper_command: Option<String>,
+    /// Piper voice (`.onnx`, with its `.onnx.json` beside it)
+    pub piper_model: Option<String>,
+    /// Names and terms that raise a "keyword-hit" alert when heard in a transcript
+    pub alert_keywords: Vec<String>,
+    /// Also alert on action-item phrases ("action item", "follow up", "deadline", ...)
+    pub alert_action_items: bool,
+    /// Recordings started automatically at set times
+    pub scheduled_recordings: Vec<ScheduledRecording>,
+    /// How long before a scheduled recording the popup warns about it
+    pub schedule_notice_secs: u64,
+    /// Seconds counted down, with beeps, before a recording actually starts; 0 starts at once
+    pub countdown_secs: u64,
+    /// Triggers that get the countdown; a hand on the hotkey or mic button doesn't need one
+    pub countdown_sources: Vec<TriggerSource>,
+    /// Named sequences of steps run together from `run_macro`, the tray or a hotkey
+    pub macros: Vec<Macro>,
+    /// Listen for a wake word, hands-free; off when unset
+    pub wake_word: Option<WakeWord>,
+    /// How long a reply is kept for answering the same question again; 0 disables caching
+    pub response_cache_ttl_secs: u64,
+    /// Answer from the cache when the gateway is unreachable (needs local transcription)
+    pub answer_offline_from_cache: bool,
+    /// Answer recordings here with canned replies instead of sending them to the
+    /// gateway, to try the app without one; `--demo` turns it on for a single run
+    pub demo_mode: bool,
+    /// Cancel a send still running after this many minutes; 0 waits forever
+    pub send_timeout_mins: u64,
+    /// Give up on a reply that hasn't finished this many seconds after the gateway
+    /// answered, keeping what arrived; 0 waits as long as the gateway keeps streaming
+    pub response_timeout_secs: u64,
+    /// Capture format overrides keyed by device name or id, for misbehaving drivers
+    pub device_formats: HashMap<String, DeviceFormat>,
+    /// Aliases and favorites keyed by device id or name
+    pub device_labels: HashMap<String, DeviceLabel>,
+    /// Borderless always-on-top popup hidden from the taskbar; off makes it a normal window
+    pub popup_pinned: bool,
+    /// Stop writing history and saved recordings when the disk has less free; 0 disables
+    pub storage_min_free_mb: u64,
+    /// Stop writing history once it takes this much space; 0 means no limit
+    pub history_max_mb: u64,
+    /// Keep a copy of every recording sent, with its transcript, in the app data dir
+    pub archive_recordings: bool,
+    /// Delete the oldest archived recordings past this many; 0 means no limit
+    pub archive_max_count: u64,
+    /// Delete archived recordings older than this many days; 0 keeps them
+    pub archive_max_age_days: u64,
+    /// Stop recording after this much silence following speech; 0 waits for a manual stop
+    pub auto_stop_silence_ms: u64,
+    /// Level (dBFS) below which a 20 ms frame counts as silence for auto-stop
+    pub vad_threshold_db: f32,
+    /// Pause that ends one utterance and sends it while dictating
+    pub dictation_pause_ms: u64,
+    /// Raise the auto-stop and trimming thresholds for each recording above the noise
+    /// floor of its first 300 ms, so a noisy room isn't taken for speech
+    pub calibrate_noise_floor: bool,
+    /// Upload while still recording instead of after the recording stops
+    pub streaming_upload: bool,
+    /// Processing run over each finished recording, in this order. A leading
+    /// `highpass` runs as the microphone is captured instead; `denoise` and `trim`
+    /// only run while noise suppression and silence trimming are on.
+    pub preprocessing: Vec<PreprocessStage>,
+    /// Remove steady background noise (RNNoise) before a recording is sent
+    pub noise_suppression: bool,
+    /// Cut silence from the start and end of each recording before it is sent
+    pub trim_silence: bool,
+    /// Level (dBFS) below which a 10 ms frame counts as silence for trimming
+    pub trim_threshold_db: f32,
+    /// Audio kept before the first and after the last sound when trimming
+    pub trim_padding_ms: u64,
+    /// Effects each recording of a profile goes through before it is sent, in order
+    pub effect_chains: HashMap<String, Vec<EffectStage>>,
+    /// URLs every completed turn is posted to
+    pub webhooks: Vec<Webhook>,
+    /// Files replies from a profile or matching a pattern are written to
+    pub response_routes: Vec<ResponseRoute>,
+    /// Which kinds of gateway event reach notifications, speech, webhooks and routes
+    pub event_filters: EventFilters,
+    /// Let a paired phone on the local network act as a push-to-talk button
+    pub remote_control: bool,
+    /// Port the remote control WebSocket listens on, on every interface
+    pub remote_control_port: u16,
+    /// Longest reply segment handed to notifications and speech; 0 keeps replies whole
+    pub notification_max_chars: u64,
+    /// "Show more" on a segmented reply opens the popup instead of the next segment
+    pub show_more_opens_popup: bool,
+    /// Most memory a recording's audio may take; 0 means no limit
+    pub capture_max_mb: u64,
+    /// What happens to a recording that reaches `capture_max_mb`
+    pub capture_overflow: CaptureOverflow,
+    /// Apply corrections learned from edited transcripts to new ones
+    pub autocorrect: bool,
+    /// Times the same correction must be made before it is applied
+    pub autocorrect_min_count: u64,
+    /// Record what the computer plays along with the microphone, so both sides of a
+    /// call are transcribed
+    pub meeting_mode: bool,
+    /// Device carrying the system audio in meeting mode, by id or name; the default
+    /// output device when unset
+    pub system_audio_device_id: Option<String>,
+    /// Gain (dB) on the microphone in meeting mode's mix, from -24 to 12
+    pub meeting_mic_gain_db: f32,
+    /// Gain (dB) on the system audio in meeting mode's mix, from -24 to 12
+    pub meeting_system_gain_db: f32,
+    /// Speak the synthesized audio the gateway sends with replies
+    pub speak_replies: bool,
+    /// Volume replies are spoken at, from 0.0 (muted) to 1.0
+    pub playback_volume: f32,
+    /// Beeps when a recording starts, stops or fails, each on or off
+    pub sound_cues: SoundCues,
+    /// What keeps a reply spoken while recording out of the recording
+    pub half_duplex: HalfDuplex,
+    /// Whether the push-to-talk hotkey shows the popup, and when
+    pub hotkey_popup: HotkeyPopup,
+    /// Where the popup appears when the hotkey shows it
+    pub hotkey_popup_position: PopupPosition,
 }
 
 impl Default for AppSettings {
@@ -28,14 +271,98 @@ impl Default for AppSettings {
             token: String::new(),
             profile_name: String::new(),
             session_key: None,
+            response_language: None,
+            brief_mode: false,
+            brief_max_sentences: 2,
+            workspace: None,
             microphone_device_id: None,
+            audio_host: None,
+            input_channel: None,
+            capture_high_pass_hz: 80,
             push_to_talk_hotkey: None,
+            keyboard_layout: None,
+            trigger_cooldown_ms: 300,
+            idle_pause_secs: 600,
+            pause_on_lock: true,
+            event_log_capacity: 500,
+            endpoint_template: crate::routing::DEFAULT_TEMPLATE.to_string(),
+            endpoint_overrides: HashMap::new(),
+            http_trace: false,
+            speaker_filter: false,
+            speaker_filter_threshold: 0.8,
+            share_context: false,
+            audio_quality: QualityPreset::default(),
+            stt_providers: HashMap::new(),
+            whisper_command: None,
+            whisper_model: None,
+            whisper_preview_model: None,
+            prefer_local_stt: false,
+            tts_providers: HashMap::new(),
+            piper_command: None,
+            piper_model: None,
+            alert_keywords: Vec::new(),
+            alert_action_items: false,
+            scheduled_recordings: Vec::new(),
+            schedule_notice_secs: 60,
+            countdown_secs: 0,
+            countdown_sources: vec![TriggerSource::Schedule],
+            macros: Vec::new(),
+            wake_word: None,
+            response_cache_ttl_secs: 0,
+            answer_offline_from_cache: false,
+            demo_mode: false,
+            send_timeout_mins: 5,
+            response_timeout_secs: 120,
+            device_formats: HashMap::new(),
+            device_labels: HashMap::new(),
+            popup_pinned: true,
+            storage_min_free_mb: 500,
+            history_max_mb: 0,
+            archive_recordings: false,
+            archive_max_count: 500,
+            archive_max_age_days: 30,
+            auto_stop_silence_ms: 0,
+            vad_threshold_db: -45.0,
+            dictation_pause_ms: 1200,
+            calibrate_noise_floor: true,
+            streaming_upload: false,
+            preprocessing: vec![
+                PreprocessStage::Highpass,
+                PreprocessStage::Trim,
+                PreprocessStage::Denoise,
+            ],
+            noise_suppression: false,
+            trim_silence: true,
+            trim_threshold_db: -50.0,
+            trim_padding_ms: 250,
+            effect_chains: HashMap::new(),
+            webhooks: Vec::new(),
+            response_routes: Vec::new(),
+            event_filters: EventFilters::default(),
+            remote_control: false,
+            remote_control_port: 18791,
+            notification_max_chars: 240,
+            show_more_opens_popup: true,
+            capture_max_mb: 512,
+            capture_overflow: CaptureOverflow::Stop,
+            autocorrect: true,
+            autocorrect_min_count: 3,
+            meeting_mode: false,
+            system_audio_device_id: None,
+            meeting_mic_gain_db: 0.0,
+            meeting_system_gain_db: 0.0,
+            speak_replies: true,
+            playback_volume: 1.0,
+            sound_cues: SoundCues::default(),
+            half_duplex: HalfDuplex::MuteMicrophone,
+            hotkey_popup: HotkeyPopup::TrayOnly,
+            hotkey_popup_position: PopupPosition::TrayCenter,
         }
     }
 }
 
 /// Response from POST /session/new
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct SessionResponse {
     pub session_id: String,
@@ -43,28 +370,1272 @@ pub struct SessionResponse {
     pub profile_name: String,
 }
 
+/// Response from GET /session?id=
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionInfo {
+    pub session_id: String,
+    pub profile_name: String,
+    pub created_at: String,
+    pub last_activity: String,
+    pub message_count: u32,
+    pub paused: bool,
+}
+
+/// Session the client is currently using — persisted across restarts
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiveSession {
+    pub session_id: String,
+    pub profile_name: String,
+    pub gateway_url: String,
+    /// Most recent user/agent events, oldest first
+    pub recent: Vec<VoiceEvent>,
+}
+
 // Old JSON response types removed — POST /audio now returns SSE stream
 
 /// Result of test_connection
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ConnectionResult {
     pub success: bool,
     pub error: Option<String>,
 }
 
+/// Whether the gateway serves an optional endpoint
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum Support {
+    /// Not probed yet, or the probe couldn't reach the gateway
+    #[default]
+    Unknown,
+    Supported,
+    Unsupported,
+}
+
+/// Which optional endpoints the connected gateway serves, probed on connect.
+/// The UI hides what is `unsupported`; commands for it fail with `unsupportedByGateway`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct GatewayCapabilities {
+    /// The gateway URL probed
+    pub gateway_url: String,
+    pub probed_at_ms: u64,
+    /// GET /history/search
+    pub history_search: Support,
+    /// GET /history/session
+    pub history_session: Support,
+    /// POST /feedback
+    pub feedback: Support,
+}
+
+/// A step of diagnose_connection, in the order they run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum DiagnosticStage {
+    /// Resolving the gateway's host name
+    Dns,
+    /// Opening a TCP connection to one of its addresses
+    Tcp,
+    /// The TLS handshake, skipped for http:// gateways
+    Tls,
+    /// Whether the gateway accepted the request's credentials
+    Auth,
+    /// Whether GET /profiles answered with JSON
+    Profiles,
+}
+
+/// How one stage of diagnose_connection went; the stages after a failed one are skipped
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StageResult {
+    pub stage: DiagnosticStage,
+    pub status: CheckStatus,
+    pub duration_ms: u64,
+    pub detail: Option<String>,
+}
+
+/// Result of diagnose_connection — `success` is false if any stage failed
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionDiagnosis {
+    pub success: bool,
+    pub stages: Vec<StageResult>,
+}
+
+/// Result of test_microphone: levels of a short recording, which is then played back
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MicTestReport {
+    /// The device recorded from, as the driver names it
+    pub device: Option<String>,
+    pub duration_ms: u64,
+    pub peak_dbfs: f32,
+    pub rms_dbfs: f32,
+    /// Samples at or past full scale
+    pub clipped_samples: u64,
+    pub clipping: bool,
+    /// Nothing above the noise floor was recorded: a muted or blocked microphone
+    pub silence: bool,
+}
+
 /// Request body for POST /session/new
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateSessionRequest {
     pub profile_name: String,
 }
 
 /// SSE event types from voice-client plugin
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", rename_all = "camelCase")]
 pub enum VoiceEvent {
-    User { text: String, confidence: f64, timestamp: String },
-    Openclaw { text: String, done: bool, timestamp: String },
-    System { status: String, message: Option<String>, timestamp: String },
+    User {
+        text: String,
+        confidence: f64,
+        timestamp: String,
+    },
+    Openclaw {
+        text: String,
+        done: bool,
+        timestamp: String,
+    },
+    System {
+        status: String,
+        message: Option<String>,
+        timestamp: String,
+    },
+    /// Synthesized speech for the reply, as base64 WAV `data` or a `url` to fetch it
+    /// from (relative to the gateway URL). Spoken by the app, not forwarded as "voice-event".
+    Audio {
+        data: Option<String>,
+        url: Option<String>,
+        timestamp: String,
+    },
+}
+
+/// Scroll target for keyboard navigation of the popup history
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum ScrollDirection {
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Top,
+    Bottom,
+}
+
+/// A stage of the `preprocessing` pipeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PreprocessStage {
+    /// Rumble filter at `capture_high_pass_hz`
+    Highpass,
+    /// RNNoise, while noise suppression is on
+    Denoise,
+    /// Automatic gain control, evening the level out towards a steady speaking volume
+    Agc,
+    /// Silence cut from both ends, while silence trimming is on
+    Trim,
+}
+
+/// Recording quality preset; see `quality::EncodeProfile` for what each one sets
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum QualityPreset {
+    /// 16 kHz 16-bit with rumble filter and level normalisation — smallest uploads
+    VoiceSmall,
+    /// 24 kHz 16-bit
+    #[default]
+    Balanced,
+    /// The microphone's native rate as 32-bit float, no processing
+    HighFidelity,
+}
+
+/// What a recording does once its audio reaches the capture memory cap
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum CaptureOverflow {
+    /// Keep recording, dropping the oldest audio so only the most recent fits
+    DropOldest,
+    /// Stop capturing and send what fits, with an error saying why
+    #[default]
+    Stop,
+}
+
+/// What pressing the push-to-talk hotkey does with the popup
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum HotkeyPopup {
+    /// Show the popup at `hotkey_popup_position` as soon as the hotkey is pressed
+    Show,
+    /// Leave the popup as it is; the tray icon turns red while the hotkey is held
+    #[default]
+    TrayOnly,
+    /// Keep the popup hidden while recording and show it when the reply arrives
+    OnReply,
+}
+
+/// Where the popup appears when the hotkey shows it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum PopupPosition {
+    /// Next to the tray icon
+    #[default]
+    TrayCenter,
+    /// The top-right corner of the screen
+    TopRight,
+    /// The bottom-right corner of the screen
+    BottomRight,
+    /// The middle of the screen
+    Center,
+}
+
+/// What happens when a reply is spoken while recording. There is no echo cancellation,
+/// so without a guard the microphone records the reply from the speakers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum HalfDuplex {
+    /// Record everything, for headsets that can't pick up the speakers
+    Off,
+    /// Drop the microphone's audio while a reply is audible
+    #[default]
+    MuteMicrophone,
+    /// Keep recording and turn the reply down while the microphone is open
+    DuckPlayback,
+}
+
+/// Where a profile's speech is turned into text; see `stt::SttProvider`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum SttProviderKind {
+    /// The gateway transcribes the uploaded audio
+    #[default]
+    Gateway,
+    /// whisper.cpp on this machine; only the transcript is uploaded
+    LocalWhisper,
+}
+
+/// Who speaks a profile's messages and cached answers; see `tts::TtsConfig`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum TtsProviderKind {
+    /// Only speech the gateway synthesizes is played
+    #[default]
+    Gateway,
+    /// Piper on this machine, which works without the gateway
+    Piper,
+}
+
+/// Why a transcript raised a keyword alert
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum KeywordKind {
+    /// One of the user's configured names or terms
+    Watched,
+    ActionItem,
+}
+
+/// Payload of the "keyword-hit" event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct KeywordHit {
+    /// The term as configured (or the action-item phrase)
+    pub term: String,
+    pub kind: KeywordKind,
+    /// Transcript the term was heard in
+    pub text: String,
+    pub timestamp: String,
+}
+
+/// A state the app should never stay in, found by the watchdog
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum StuckState {
+    /// A recording still opening its microphone long after it was started
+    RecordingNeverStarted,
+    /// Tray shows a quick recording that isn't running
+    QuickTalkWithoutRecording,
+    /// Tray shows a quick-mode send that never started
+    QuickTalkWithoutSend,
+    /// A send has run longer than `send_timeout_mins`
+    SendingTooLong,
+}
+
+/// Payload of the "state-recovered" event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StateRecovered {
+    pub stuck: StuckState,
+    /// What was done about it
+    pub action: String,
+}
+
+/// Which storage limit stopped history and recordings from being written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum StorageLimit {
+    /// Free disk space fell below `storage_min_free_mb`; nothing more is written
+    LowDiskSpace,
+    /// History reached `history_max_mb`; failed recordings are still kept
+    HistoryFull,
+}
+
+/// Disk use of the app's stores, returned by `get_storage_usage` and emitted as
+/// "storage-warning" when a limit is reached or cleared
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageUsage {
+    pub data_dir: String,
+    pub history_bytes: u64,
+    pub failed_sends_bytes: u64,
+    pub recordings_bytes: u64,
+    /// Recordings journaled while they are being sent
+    pub pending_sends_bytes: u64,
+    pub response_cache_bytes: u64,
+    /// None when the platform can't tell
+    pub free_bytes: Option<u64>,
+    /// 0 when disabled
+    pub min_free_bytes: u64,
+    /// 0 when disabled
+    pub max_history_bytes: u64,
+    /// Set while writing is stopped
+    pub limit: Option<StorageLimit>,
+}
+
+/// A recording started automatically, e.g. the daily standup
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ScheduledRecording {
+    pub name: String,
+    /// Local start time as `HH:MM`
+    pub time: String,
+    /// Weekdays it runs on (`mon`, `tue`, ...); empty means every day
+    pub days: Vec<String>,
+    /// Recording stops and is sent after this many minutes
+    pub duration_mins: u32,
+    pub enabled: bool,
+}
+
+impl Default for ScheduledRecording {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            time: String::new(),
+            days: Vec::new(),
+            duration_mins: 15,
+            enabled: true,
+        }
+    }
+}
+
+/// A named sequence of steps run together, e.g. "standup": switch to the team's
+/// profile, start a new session and turn meeting mode on
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Macro {
+    /// Shown in the tray's Macros menu and passed to `run_macro`
+    pub name: String,
+    /// Global shortcut that runs it, e.g. `Control+Alt+KeyS`
+    pub hotkey: Option<String>,
+    /// Run in order; the first that fails stops the rest
+    pub steps: Vec<MacroStep>,
+}
+
+/// One step of a macro
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum MacroStep {
+    /// Talk to another agent profile from now on, as if picked in Settings
+    #[serde(rename_all = "camelCase")]
+    SwitchProfile { profile_name: String },
+    /// Turn meeting mode on or off
+    MeetingMode { enabled: bool },
+    /// Turn privacy mode on or off
+    PrivacyMode { enabled: bool },
+    /// A keyboard action by id, as `invoke_action` takes it: `new-session`,
+    /// `toggle-recording`, `open-history`, ...
+    Action { id: String },
+    /// Pause before the next step, up to a minute
+    Wait { ms: u64 },
+}
+
+/// An audio effect and its parameters; see `effects` for what each one does
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Effect {
+    /// Cut rumble below `cutoffHz`
+    #[serde(rename_all = "camelCase")]
+    HighPass { cutoff_hz: f32 },
+    /// Silence stretches quieter than `thresholdDb` once `holdMs` has passed since speech
+    #[serde(rename_all = "camelCase")]
+    Gate { threshold_db: f32, hold_ms: u64 },
+    /// Even out the level towards `targetDb`, amplifying by at most `maxGainDb`
+    #[serde(rename_all = "camelCase")]
+    Agc { target_db: f32, max_gain_db: f32 },
+    /// RNNoise noise suppression
+    Denoise,
+    /// Keep peaks at or below `ceilingDb`
+    #[serde(rename_all = "camelCase")]
+    Limiter { ceiling_db: f32 },
+}
+
+/// One stage of a profile's effects chain
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EffectStage {
+    /// Off keeps the stage and its parameters in the chain without running it
+    #[serde(default = "stage_enabled")]
+    pub enabled: bool,
+    #[serde(flatten)]
+    pub effect: Effect,
+}
+
+fn stage_enabled() -> bool {
+    true
+}
+
+/// A URL completed turns are posted to, e.g. an automation or chat integration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Webhook {
+    pub url: String,
+    /// Signs each body with HMAC-SHA256 in `X-OpenClaw-Signature` when set
+    pub secret: Option<String>,
+    pub enabled: bool,
+}
+
+impl Default for Webhook {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            secret: None,
+            enabled: true,
+        }
+    }
+}
+
+/// Body posted to webhooks when a turn completes, or fails if `event_filters` say so
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookPayload {
+    /// `turn.completed`, or `turn.failed` for an error the gateway reported
+    pub event: String,
+    /// Also sent as `X-OpenClaw-Delivery`; unchanged across retries
+    pub delivery_id: String,
+    pub session_id: String,
+    pub profile_name: String,
+    pub user_text: Option<String>,
+    pub reply: String,
+    /// Gateway timestamp of the final `done` event, or of the error
+    pub completed_at: String,
+    /// What went wrong, for `turn.failed`; empty `reply` then
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub sent_at_ms: u64,
+}
+
+/// What a gateway event is, as far as `event_filters` are concerned
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum EventKind {
+    /// What the gateway heard the user say
+    Transcript,
+    /// A piece of a reply that is still streaming
+    Partial,
+    /// A finished reply, and the speech that comes with it
+    Reply,
+    /// A `system` event with status "error"
+    Error,
+    /// Any other `system` event, e.g. "typing"
+    Status,
+}
+
+/// Which kinds of gateway event each consumer outside the popup receives. The popup
+/// and history always get everything.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default, rename_all = "camelCase")]
+pub struct EventFilters {
+    /// Paired remote devices and "reply-segments"; any kind
+    pub notifications: Vec<EventKind>,
+    /// Spoken replies and spoken error messages; reply and error only
+    pub speech: Vec<EventKind>,
+    /// `turn.completed` for a reply, `turn.failed` for an error; reply and error only
+    pub webhooks: Vec<EventKind>,
+    /// Files written by `response_routes`; reply only
+    pub response_routes: Vec<EventKind>,
+}
+
+impl Default for EventFilters {
+    fn default() -> Self {
+        Self {
+            notifications: vec![
+                EventKind::Transcript,
+                EventKind::Partial,
+                EventKind::Reply,
+                EventKind::Error,
+                EventKind::Status,
+            ],
+            speech: vec![EventKind::Reply, EventKind::Error],
+            webhooks: vec![EventKind::Reply],
+            response_routes: vec![EventKind::Reply],
+        }
+    }
+}
+
+/// Short beeps on recording transitions, for when the popup isn't in view; each is
+/// off unless turned on
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SoundCues {
+    /// Rising beep just before the microphone opens
+    pub start: bool,
+    /// Falling beep when the recording stops to be sent
+    pub stop: bool,
+    /// Low double beep when a recording can't start or its send fails
+    pub error: bool,
+}
+
+/// Writes replies from a profile, or matching a pattern, to a file, e.g. every
+/// "journal" reply appended to `~/notes/journal.md`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ResponseRoute {
+    /// Only replies from this profile; any profile when unset
+    pub profile: Option<String>,
+    /// Only replies matching this regular expression; any reply when unset
+    pub pattern: Option<String>,
+    /// The file, under the home directory unless absolute; may use the template's
+    /// placeholders, e.g. `notes/{date}.md`
+    pub path: String,
+    /// What is written: `{reply}`, `{user}`, `{profile}`, `{session}`, `{date}`,
+    /// `{time}` and `{timestamp}` are filled in
+    pub template: String,
+    /// Add to the end of the file rather than replacing it
+    pub append: bool,
+    pub enabled: bool,
+}
+
+impl Default for ResponseRoute {
+    fn default() -> Self {
+        Self {
+            profile: None,
+            pattern: None,
+            path: String::new(),
+            template: "## {date} {time}\n\n{reply}\n\n".to_string(),
+            append: true,
+            enabled: true,
+        }
+    }
+}
+
+/// Payload of the "response-route-failed" event: a reply couldn't be written to its file
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseRouteFailed {
+    pub path: String,
+    pub error: String,
+}
+
+/// Payload of the "response-timeout" event: the reply didn't finish in time and its
+/// stream was abandoned
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseTimeout {
+    pub session_id: String,
+    pub user_text: Option<String>,
+    /// What arrived of the reply before it was abandoned; empty if nothing did
+    pub partial_reply: String,
+    pub timeout_secs: u64,
+}
+
+/// Payload of the "webhook-failed" event, once every attempt has failed
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookFailed {
+    pub url: String,
+    /// 0 when the turn was dropped before any attempt, the delivery queue being full
+    pub attempts: u32,
+    pub error: String,
+}
+
+/// Payload of the "scheduled-recording" event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "phase", rename_all = "camelCase")]
+pub enum ScheduleEvent {
+    /// Starting in `inSecs`
+    #[serde(rename_all = "camelCase")]
+    Upcoming {
+        name: String,
+        in_secs: u64,
+    },
+    Start {
+        name: String,
+    },
+    Stop {
+        name: String,
+    },
+}
+
+/// Payload of the "file-transcription" event, streamed while an audio file is transcribed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "phase", rename_all = "camelCase")]
+pub enum FileTranscriptionEvent {
+    #[serde(rename_all = "camelCase")]
+    Started {
+        file: String,
+        chunks: usize,
+        duration_secs: f64,
+    },
+    /// One chunk's text, in order; `index` counts from 0
+    #[serde(rename_all = "camelCase")]
+    Chunk {
+        index: usize,
+        chunks: usize,
+        start_secs: f64,
+        text: String,
+    },
+    Finished {
+        file: String,
+    },
+    /// Stopped on request after `done` chunks
+    Cancelled {
+        file: String,
+        done: usize,
+    },
+    Failed {
+        file: String,
+        message: String,
+    },
+}
+
+/// Payload of the "transcript-preview" event: a quick local transcript of a recording
+/// the gateway is still transcribing, shown until the gateway's own arrives
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptPreview {
+    pub session_id: String,
+    pub text: String,
+    /// How long the preview model took
+    pub elapsed_ms: u64,
+}
+
+/// Payload of the "dictation-utterance" event; `index` counts a dictation's
+/// utterances from 1
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "phase", rename_all = "camelCase")]
+pub enum DictationUtterance {
+    /// The speaker paused; the utterance is queued behind any still being sent
+    #[serde(rename_all = "camelCase")]
+    Captured {
+        index: u32,
+        duration_ms: u64,
+    },
+    /// Sent, and its reply streamed as "voice-event" like any other turn
+    Sent {
+        index: u32,
+    },
+    Failed {
+        index: u32,
+        message: String,
+    },
+}
+
+/// What asked for a recording to start
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum TriggerSource {
+    Popup,
+    Hotkey,
+    WakeWord,
+    /// Quick mode: tray double-click or middle-click
+    Tray,
+    Schedule,
+    /// A phone paired for remote control
+    Remote,
+    /// `--start-recording` on the command line, e.g. from a launcher shortcut
+    Launch,
+}
+
+/// What opened the microphone, as the usage log records it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum MicSource {
+    Recording,
+    Dictation,
+    WakeWord,
+    MicTest,
+    SelfCheck,
+    VoiceprintEnrollment,
+}
+
+/// One stretch of the microphone being open; an entry of `get_mic_usage_log`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MicUsage {
+    pub id: u64,
+    pub source: MicSource,
+    /// What started a recording; None for the other sources
+    pub trigger: Option<TriggerSource>,
+    /// Name of the device opened
+    pub device: Option<String>,
+    pub started_at_ms: u64,
+    /// None while the microphone is still open, or if the app quit before closing it
+    pub stopped_at_ms: Option<u64>,
+}
+
+/// Keyboard layouts whose letter and punctuation keys differ from US QWERTY
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum KeyboardLayout {
+    Qwerty,
+    /// French and Belgian
+    Azerty,
+    /// German, Swiss, Austrian, Czech and Hungarian
+    Qwertz,
+    Dvorak,
+}
+
+/// Payload of the "trigger-suppressed" diagnostic event
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TriggerSuppressed {
+    pub source: TriggerSource,
+    pub since_last_ms: u64,
+    pub min_gap_ms: u64,
+}
+
+/// Payload of the "recording-countdown" event, sent once a second before capture starts
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingCountdown {
+    pub source: TriggerSource,
+    /// Seconds left; 0 when capture starts
+    pub remaining_secs: u64,
+    pub cancelled: bool,
+}
+
+/// Payload of the "auto-stop" event: the speaker went quiet, so the recording should be sent
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoStop {
+    pub silence_ms: u64,
+}
+
+/// Payload of the "capture-limit-reached" event: the recording's audio hit `capture_max_mb`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureLimitReached {
+    pub max_mb: u64,
+    pub overflow: CaptureOverflow,
+}
+
+/// Payload of the "mic-level" event: the input level over the last ~50 ms, in dBFS
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MicLevel {
+    pub rms_db: f32,
+    pub peak_db: f32,
+}
+
+/// Payload of the "waveform-chunk" event: the audio recorded since the last chunk,
+/// decimated for a scrolling waveform
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WaveformChunk {
+    /// `[lowest, highest]` sample of each `bucketMs` of audio, oldest first
+    pub peaks: Vec<[f32; 2]>,
+    pub bucket_ms: u32,
+}
+
+/// Payload of the "mic-clipping" event: part of the last second's input hit full
+/// scale, so the input gain should come down
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MicClipping {
+    /// Share of the samples at or past `audio::CLIP_LEVEL`, 0–100
+    pub percent: f32,
+}
+
+/// Payload of the "recording-pause" event
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingPause {
+    /// The recording is still open but audio isn't being captured
+    pub paused: bool,
+}
+
+/// Payload of the "reply-segments" event: a finished reply cut to notification size
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplySegments {
+    pub turn_id: String,
+    /// Whole sentences where they fit, of at most `notification_max_chars` characters
+    pub segments: Vec<String>,
+    /// "Show more" should open the popup rather than ask `show_more_of_reply` for the next
+    pub show_more_opens_popup: bool,
+}
+
+/// A correction learned from the user's edits to transcripts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LearnedCorrection {
+    /// The misheard words, lowercased without punctuation
+    pub from: String,
+    pub to: String,
+    /// Times the user made this correction
+    pub count: u32,
+    /// Times the user undid it after it was applied
+    pub reverts: u32,
+    /// Applied to new transcripts: made often enough and rarely undone
+    pub active: bool,
+}
+
+/// How a phone connects for remote control
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RemotePairing {
+    pub enabled: bool,
+    /// WebSocket URL with the pairing token, to open on the phone; None while disabled
+    pub url: Option<String>,
+}
+
+/// Payload of the "unread-changed" event
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UnreadCount {
+    /// Answers that finished while the popup was hidden and haven't been seen yet
+    pub count: u32,
+}
+
+/// Payload of the "privacy-mode" event
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PrivacyMode {
+    /// Nothing from the conversation is being written to disk or the event log
+    pub enabled: bool,
+}
+
+/// Snapshot of the response playback queue, emitted as "playback-state"
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaybackStatus {
+    pub playing: bool,
+    pub paused: bool,
+    pub current_clip_id: Option<u64>,
+    pub queued: usize,
+    /// 0.0 (muted) to 1.0
+    pub volume: f32,
+}
+
+/// Why always-listening features are currently paused
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum ListeningPauseReason {
+    Locked,
+    Idle,
+}
+
+/// Payload of the "audio-devices-changed" event: input devices were plugged in or removed
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioDevicesChanged {
+    /// Every input device now present, as `list_audio_devices` returns them
+    pub devices: Vec<AudioDevice>,
+    /// Names of the devices that appeared
+    pub added: Vec<String>,
+    /// Names of the devices that went away
+    pub removed: Vec<String>,
+}
+
+/// Payload of the "microphone-switched" event: the configured microphone was unplugged,
+/// so recordings use the default one, or it is back and used again
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MicrophoneSwitched {
+    /// The configured `microphone_device_id`
+    pub device_id: String,
+    pub available: bool,
+}
+
+/// OS accessibility preferences windows adapt to, from `get_system_a11y_prefs` and the
+/// "a11y-prefs-changed" event
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct A11yPrefs {
+    /// Animations are turned down or off
+    pub reduced_motion: bool,
+    /// A high-contrast theme or "increase contrast" is on
+    pub high_contrast: bool,
+}
+
+/// Payload of the "device-fallback" event: the microphone asked for couldn't be opened,
+/// so the recording came from the next one in line
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceFallback {
+    /// The microphone asked for, by id or name
+    pub requested: String,
+    /// The device recorded from instead, by id; None for the default microphone
+    pub used_device_id: Option<String>,
+    /// Its name as the driver reports it
+    pub used_device_name: Option<String>,
+    /// Why the one asked for wasn't used
+    pub reason: String,
+}
+
+/// Payload of the "listening-paused" event
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ListeningPaused {
+    pub reason: ListeningPauseReason,
+}
+
+/// Whether a logged entry was emitted to the webview or is an internal transition
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum LoggedEventKind {
+    Emitted,
+    State,
+}
+
+/// One entry of the diagnostics event log
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LoggedEvent {
+    pub seq: u64,
+    pub timestamp_ms: u64,
+    pub kind: LoggedEventKind,
+    pub name: String,
+    pub payload: serde_json::Value,
+}
+
+/// Push-to-talk hotkey transition, emitted to the popup as "push-to-talk"
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum PushToTalkState {
+    Pressed,
+    Released,
+}
+
+/// Payload of the "settings-rejected" event — an external edit that failed validation
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsRejected {
+    pub error: String,
+}
+
+/// Outcome of a single self-check item
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+    Skipped,
+}
+
+/// One row of the self-check checklist
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckItem {
+    pub id: String,
+    pub label: String,
+    pub status: CheckStatus,
+    pub detail: Option<String>,
+}
+
+/// Result of run_self_check — `ok` is false if any item failed
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SelfCheckReport {
+    pub ok: bool,
+    pub items: Vec<CheckItem>,
+}
+
+/// Voiceprint enrollment state for the settings UI
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct VoiceprintStatus {
+    pub enrolled: bool,
+    /// Seconds of speech the voiceprint was learned from
+    pub voiced_secs: f32,
+    pub filter_enabled: bool,
+}
+
+/// Context attached to a turn when sharing is enabled; payload of "context-captured"
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TurnContext {
+    /// Frontmost application when the recording started
+    pub app_name: Option<String>,
+    pub window_title: Option<String>,
+    /// Language tag of the user's locale, e.g. `de-DE`
+    pub locale: Option<String>,
+    /// IANA time zone, e.g. `Europe/Berlin`
+    pub time_zone: Option<String>,
+}
+
+/// One finished exchange with the reply merged from its streamed deltas;
+/// payload of the "turn-completed" event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletedTurn {
+    /// Names the turn for `submit_feedback`: `<session id>/<completedAt>`
+    #[serde(default)]
+    pub turn_id: String,
+    /// Transcript of what was said, if the gateway sent one before the reply
+    pub user_text: Option<String>,
+    pub reply: String,
+    /// Gateway timestamp of the final `done` event
+    pub completed_at: String,
+}
+
+/// A completed exchange as kept in the local history
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+    pub profile_name: String,
+    pub user_text: Option<String>,
+    pub reply: String,
+    /// Gateway timestamp of the final `done` event
+    pub completed_at: String,
+    /// When the app recorded it; 0 for exchanges only the gateway kept
+    #[serde(default)]
+    pub recorded_at_ms: u64,
+    /// The user's rating of the reply, once they gave one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub feedback: Option<TurnFeedback>,
+    /// The reply was cut off: it didn't finish within `response_timeout_secs`
+    #[serde(default)]
+    pub incomplete: bool,
+}
+
+/// Thumbs up or down on a reply
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum FeedbackRating {
+    Up,
+    Down,
+}
+
+/// A user's rating of a reply, as kept in history
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TurnFeedback {
+    pub rating: FeedbackRating,
+    pub comment: Option<String>,
+    pub submitted_at_ms: u64,
+}
+
+/// Request body for POST /feedback
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedbackRequest {
+    pub session_id: String,
+    pub profile_name: String,
+    /// Gateway timestamp of the rated reply's `done` event
+    pub completed_at: String,
+    pub rating: FeedbackRating,
+    pub comment: Option<String>,
+    /// The exchange, when the app still has it
+    pub user_text: Option<String>,
+    pub reply: Option<String>,
+}
+
+/// A session in the history window's list
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionOverview {
+    pub session_id: String,
+    /// Profile of the most recent exchange
+    pub profile_name: String,
+    pub turns: usize,
+    pub started_at_ms: u64,
+    pub last_activity_ms: u64,
+    /// Time between the first and the last exchange
+    pub duration_secs: u64,
+}
+
+/// One page of a session's transcript, oldest exchange first
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptPage {
+    pub session_id: String,
+    pub entries: Vec<HistoryEntry>,
+    /// Index of the first entry in the whole transcript
+    pub offset: usize,
+    pub total: usize,
+}
+
+/// Where a history search found an exchange
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum HistorySource {
+    /// This computer's history
+    Local,
+    /// Transcripts the gateway keeps
+    Remote,
+}
+
+/// An exchange matching a history search
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryMatch {
+    pub session_id: String,
+    pub source: HistorySource,
+    pub entry: HistoryEntry,
+}
+
+/// Local and gateway history matching a search, local matches first
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct HistorySearchResult {
+    pub query: String,
+    pub matches: Vec<HistoryMatch>,
+    /// Why the gateway's transcripts weren't searched, when they weren't
+    pub remote_unavailable: Option<String>,
+}
+
+/// Response from GET /history/search?q=
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteHistorySearch {
+    pub matches: Vec<RemoteHistoryMatch>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteHistoryMatch {
+    pub session_id: String,
+    pub entry: HistoryEntry,
+}
+
+/// Response from GET /history/session?id=
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteTranscript {
+    pub session_id: String,
+    pub entries: Vec<HistoryEntry>,
+}
+
+/// How far a recording got on its way to the gateway
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum SendStage {
+    Recorded,
+    /// Noise suppression, effects, speaker filter and WAV encoding
+    Encoding,
+    Uploading,
+    /// Uploaded; the reply was still streaming in
+    AwaitingStream,
+}
+
+/// A recording whose upload failed, kept on disk for retry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FailedSend {
+    pub id: String,
+    pub base_url: String,
+    pub session_id: String,
+    pub profile_name: String,
+    pub session_key: Option<String>,
+    pub response_language: Option<String>,
+    /// Size of the saved WAV
+    pub bytes: usize,
+    pub failed_at_ms: u64,
+    /// Why the most recent attempt failed
+    pub error: String,
+    pub attempts: u32,
+    /// Set when the app closed (or crashed) mid-send; how far the send had got
+    #[serde(default)]
+    pub interrupted: Option<SendStage>,
+}
+
+/// A sent recording kept in the archive
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchivedRecording {
+    /// Names the WAV file too: `<recordedAtMs>-<sessionId>`
+    pub id: String,
+    pub session_id: String,
+    pub profile_name: String,
+    pub recorded_at_ms: u64,
+    /// Length of the audio as it was sent
+    pub duration_ms: u64,
+    /// Size of the saved WAV
+    pub bytes: usize,
+    /// What the gateway heard, if its reply finished
+    pub transcript: Option<String>,
+}
+
+/// Where a supervised background task stands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum TaskStatus {
+    Running,
+    /// Ended or panicked; waiting to be started again
+    Restarting,
+    /// Returned and isn't restarted
+    Stopped,
+    /// Panicked and was given up on
+    Failed,
+}
+
+/// One background task from `get_task_health`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskHealth {
+    pub name: String,
+    pub status: TaskStatus,
+    /// Times it has been started again since launch
+    pub restarts: u32,
+    /// The most recent panic
+    pub last_error: Option<String>,
+    /// Work turned away, because its queue was full or it wasn't running
+    pub dropped: u64,
+}
+
+/// Wake-word detection with Porcupine, from the user's own Picovoice install
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default, rename_all = "camelCase")]
+pub struct WakeWord {
+    pub enabled: bool,
+    /// Porcupine's shared library (`libpv_porcupine.so`, `.dylib` or `.dll`)
+    pub library_path: String,
+    /// Porcupine's model file (`porcupine_params.pv`)
+    pub model_path: String,
+    /// Keyword files (`.ppn`) listened for; each is reported by the start of its file name
+    pub keyword_paths: Vec<String>,
+    /// Picovoice Console access key
+    pub access_key: String,
+    /// From 0.0 to 1.0; higher misses fewer wake words but fires by mistake more often
+    pub sensitivity: f32,
+    /// Start recording when a wake word is heard
+    pub auto_record: bool,
+}
+
+impl Default for WakeWord {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            library_path: String::new(),
+            model_path: String::new(),
+            keyword_paths: Vec::new(),
+            access_key: String::new(),
+            sensitivity: 0.5,
+            auto_record: true,
+        }
+    }
+}
+
+/// Payload of the "wake-word" event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WakeWordDetected {
+    pub keyword: String,
+    /// The popup starts recording in response
+    pub auto_record: bool,
 }
@@ -0,0 +1,95 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::types::UnreadCount;
+use crate::{events, quick_talk};
+
+/// Answers that finished while the popup was hidden, counted until it is next focused
+pub struct UnreadState {
+    count: AtomicU32,
+}
+
+impl UnreadState {
+    pub fn new() -> Self {
+        Self {
+            count: AtomicU32::new(0),
+        }
+    }
+}
+
+pub fn count(app: &AppHandle) -> u32 {
+    app.try_state::<UnreadState>()
+        .map_or(0, |state| state.count.load(Ordering::SeqCst))
+}
+
+/// The tray tooltip with the unread answers added, if there are any
+pub fn tooltip(base: &str, count: u32) -> String {
+    match count {
+        0 => base.to_string(),
+        1 => format!("{base} — 1 new answer"),
+        n => format!("{base} — {n} new answers"),
+    }
+}
+
+fn popup_visible(app: &AppHandle) -> bool {
+    app.get_webview_window("popup")
+        .and_then(|window| window.is_visible().ok())
+        .unwrap_or(false)
+}
+
+/// Count a finished answer, unless the popup is open to show it
+pub fn turn_completed(app: &AppHandle) {
+    if popup_visible(app) {
+        return;
+    }
+    let count = app
+        .state::<UnreadState>()
+        .count
+        .fetch_add(1, Ordering::SeqCst)
+        + 1;
+    changed(app, count);
+}
+
+/// Everything has been seen, e.g. the popup was focused
+pub fn mark_all_read(app: &AppHandle) {
+    if app.state::<UnreadState>().count.swap(0, Ordering::SeqCst) != 0 {
+        changed(app, 0);
+    }
+}
+
+fn changed(app: &AppHandle, count: u32) {
+    let unread = UnreadCount { count };
+    events::record_state(app, "unread-changed", &unread);
+    let _ = app.emit("unread-changed", &unread);
+    quick_talk::refresh_tray(app);
+    show_badge(app, count);
+}
+
+/// The count on the Dock icon and next to the menu bar icon
+#[cfg(target_os = "macos")]
+fn show_badge(app: &AppHandle, count: u32) {
+    let badge = (count > 0).then_some(count);
+    if let Some(window) = app.get_webview_window("popup") {
+        let _ = window.set_badge_count(badge.map(i64::from));
+    }
+    if let Some(tray) = app.tray_by_id(quick_talk::TRAY_ID) {
+        let _ = tray.set_title(badge.map(|count| count.to_string()));
+    }
+}
+
+/// Other platforms have only the tooltip
+#[cfg(not(target_os = "macos"))]
+fn show_badge(_app: &AppHandle, _count: u32) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tooltip_counts_new_answers() {
+        assert_eq!(tooltip("OpenClaw", 0), "OpenClaw");
+        assert_eq!(tooltip("OpenClaw", 1), "OpenClaw — 1 new answer");
+        assert_eq!(tooltip("OpenClaw", 3), "OpenClaw — 3 new answers");
+    }
+}
@@ -0,0 +1,259 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::audio::{to_dbfs, AudioState};
+use crate::events;
+use crate::types::AutoStop;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+const FRAME_MS: u32 = 20;
+/// Frames above the threshold needed before the recording counts as having speech,
+/// so a click or a cough at the start doesn't arm the auto-stop
+const SPEECH_FRAMES: u32 = 5;
+
+/// Ambient noise is measured over the start of each recording
+const CALIBRATION: Duration = Duration::from_millis(300);
+/// How far above the noise floor a frame has to be to count as sound
+const NOISE_MARGIN_DB: f32 = 10.0;
+/// Highest a threshold is raised to, so a recording that starts mid-word still has
+/// its speech heard
+const MAX_ADAPTED_DB: f32 = -30.0;
+
+fn rms(frame: &[f32]) -> f32 {
+    (frame.iter().map(|s| s * s).sum::<f32>() / frame.len().max(1) as f32).sqrt()
+}
+
+/// Ambient level (dBFS) over the first 300 ms of a recording: its quietest fifth of
+/// 10 ms frames, so speech starting straight away isn't taken for noise.
+/// None until that much has been recorded.
+pub fn noise_floor_db(samples: &[f32], sample_rate: u32) -> Option<f32> {
+    let needed = (CALIBRATION.as_secs_f64() * sample_rate as f64) as usize;
+    if needed == 0 || samples.len() < needed {
+        return None;
+    }
+    let frame = (sample_rate as usize / 100).max(1);
+    let mut levels: Vec<f32> = samples[..needed].chunks(frame).map(rms).collect();
+    levels.sort_by(f32::total_cmp);
+    Some(to_dbfs(levels[levels.len() / 5]))
+}
+
+/// The configured threshold, raised to clear a noisy room's floor; never lowered,
+/// so a quiet room behaves as configured
+pub fn adapt_threshold(configured_db: f32, floor_db: f32) -> f32 {
+    configured_db.max((floor_db + NOISE_MARGIN_DB).min(MAX_ADAPTED_DB))
+}
+
+/// Energy-based end-of-speech detection: once speech was heard, a run of quiet
+/// frames as long as the configured silence ends the recording.
+#[derive(Debug)]
+pub struct SilenceDetector {
+    threshold: f32,
+    frames_needed: u32,
+    voiced: u32,
+    silent: u32,
+}
+
+impl SilenceDetector {
+    pub fn new(threshold_db: f32, silence: Duration) -> Self {
+        Self {
+            threshold: 10f32.powf(threshold_db / 20.0),
+            frames_needed: (silence.as_millis() as u32).div_ceil(FRAME_MS).max(1),
+            voiced: 0,
+            silent: 0,
+        }
+    }
+
+    pub fn frame_len(sample_rate: u32) -> usize {
+        (sample_rate * FRAME_MS / 1000).max(1) as usize
+    }
+
+    /// Enough loud frames in a row were heard to count as speech
+    pub fn heard_speech(&self) -> bool {
+        self.voiced >= SPEECH_FRAMES
+    }
+
+    /// Feed one frame; true once the speaker has gone quiet for long enough
+    pub fn push(&mut self, frame: &[f32]) -> bool {
+        if frame.is_empty() {
+            return false;
+        }
+        if rms(frame) >= self.threshold {
+            self.voiced += 1;
+            self.silent = 0;
+            return false;
+        }
+        if self.voiced < SPEECH_FRAMES {
+            // Quiet before anything was said doesn't count, and neither do lone blips
+            self.voiced = 0;
+            return false;
+        }
+        self.silent += 1;
+        self.silent >= self.frames_needed
+    }
+}
+
+/// Auto-stop settings, and which recording the running watcher belongs to
+pub struct VadState {
+    pub silence: Arc<Mutex<Duration>>,
+    pub threshold_db: Arc<Mutex<f32>>,
+    /// Raise the threshold above the noise floor measured as each recording starts
+    pub calibrate: Arc<AtomicBool>,
+    pub recording: Arc<AtomicU64>,
+}
+
+impl VadState {
+    pub fn new() -> Self {
+        Self {
+            silence: Arc::new(Mutex::new(Duration::ZERO)),
+            threshold_db: Arc::new(Mutex::new(-45.0)),
+            calibrate: Arc::new(AtomicBool::new(true)),
+            recording: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    pub fn configure(&self, silence: Duration, threshold_db: f32, calibrate: bool) {
+        if let Ok(mut current) = self.silence.lock() {
+            *current = silence;
+        }
+        if let Ok(mut current) = self.threshold_db.lock() {
+            *current = threshold_db;
+        }
+        self.calibrate.store(calibrate, Ordering::SeqCst);
+    }
+}
+
+/// Watch the recording that just started and emit "auto-stop" once the speaker goes
+/// quiet; the popup stops and sends it like a release of the mic button.
+/// Does nothing when auto-stop is off.
+pub fn watch(app: &AppHandle) {
+    let state = app.state::<VadState>();
+    let silence = state.silence.lock().map(|s| *s).unwrap_or_default();
+    if silence.is_zero() {
+        return;
+    }
+    let threshold_db = state.threshold_db.lock().map(|t| *t).unwrap_or(-45.0);
+    let calibrate = state.calibrate.load(Ordering::SeqCst);
+    let recording = state.recording.fetch_add(1, Ordering::SeqCst) + 1;
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        // Calibrating waits for the first 300 ms before the detector is made
+        let mut detector = (!calibrate).then(|| SilenceDetector::new(threshold_db, silence));
+        let mut position = 0;
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            let audio = app.state::<AudioState>();
+            let current = app.state::<VadState>().recording.load(Ordering::SeqCst);
+            if current != recording || !audio.is_recording.load(Ordering::SeqCst) {
+                return;
+            }
+            let Some(rate) = audio.sample_rate.lock().ok().and_then(|rate| *rate) else {
+                continue;
+            };
+            let frame_len = SilenceDetector::frame_len(rate);
+            let quiet = match audio.samples.lock() {
+                Ok(samples) => {
+                    if detector.is_none() {
+                        let Some(floor_db) = noise_floor_db(&samples, rate) else {
+                            continue;
+                        };
+                        let adapted_db = adapt_threshold(threshold_db, floor_db);
+                        events::record_state(
+                            &app,
+                            "vad-calibrated",
+                            serde_json::json!({
+                                "noiseFloorDb": floor_db,
+                                "thresholdDb": adapted_db,
+                            }),
+                        );
+                        detector = Some(SilenceDetector::new(adapted_db, silence));
+                    }
+                    let Some(detector) = detector.as_mut() else {
+                        continue;
+                    };
+                    // `position` counts from the start of the recording, including
+                    // audio since dropped at the memory cap
+                    let dropped = audio.dropped.load(Ordering::SeqCst);
+                    let start = position.max(dropped) - dropped;
+                    let whole = samples.len().saturating_sub(start) / frame_len * frame_len;
+                    let fresh = samples.get(start..start + whole).unwrap_or_default();
+                    position = dropped + start + whole;
+                    fresh.chunks(frame_len).any(|frame| detector.push(frame))
+                }
+                Err(_) => return,
+            };
+            if quiet {
+                let stop = AutoStop {
+                    silence_ms: silence.as_millis() as u64,
+                };
+                events::record_state(&app, "auto-stop", &stop);
+                let _ = app.emit("auto-stop", &stop);
+                return;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 16_000;
+
+    fn frames(level: f32, ms: u32) -> Vec<Vec<f32>> {
+        let len = SilenceDetector::frame_len(RATE);
+        (0..ms / FRAME_MS).map(|_| vec![level; len]).collect()
+    }
+
+    fn stops_after(detector: &mut SilenceDetector, frames: &[Vec<f32>]) -> Option<usize> {
+        frames.iter().position(|frame| detector.push(frame))
+    }
+
+    #[test]
+    fn test_stops_after_silence_following_speech() {
+        let mut detector = SilenceDetector::new(-40.0, Duration::from_millis(1000));
+        assert_eq!(stops_after(&mut detector, &frames(0.2, 500)), None);
+        // 1 s of quiet is 50 frames
+        assert_eq!(stops_after(&mut detector, &frames(0.001, 2000)), Some(49));
+    }
+
+    #[test]
+    fn test_silence_before_speech_does_not_stop() {
+        let mut detector = SilenceDetector::new(-40.0, Duration::from_millis(200));
+        assert_eq!(stops_after(&mut detector, &frames(0.0, 3000)), None);
+        // A single loud frame is a blip, not speech
+        assert_eq!(stops_after(&mut detector, &frames(0.2, FRAME_MS)), None);
+        assert_eq!(stops_after(&mut detector, &frames(0.0, 3000)), None);
+    }
+
+    #[test]
+    fn test_threshold_is_raised_above_a_noisy_floor() {
+        let mut cafe: Vec<f32> = (0..RATE / 4)
+            .map(|i| if i % 2 == 0 { 0.03 } else { -0.03 })
+            .collect();
+        // Speech starting within the calibration window isn't taken for noise
+        cafe.extend(vec![0.3; RATE as usize / 10]);
+        let floor_db = noise_floor_db(&cafe, RATE).unwrap();
+        assert!((floor_db - to_dbfs(0.03)).abs() < 0.1);
+        assert!((adapt_threshold(-45.0, floor_db) - (floor_db + 10.0)).abs() < 0.01);
+
+        // A quiet office keeps the configured threshold, and a loud start is capped
+        assert_eq!(adapt_threshold(-45.0, -80.0), -45.0);
+        assert_eq!(adapt_threshold(-45.0, -10.0), -30.0);
+        assert_eq!(noise_floor_db(&cafe[..100], RATE), None);
+    }
+
+    #[test]
+    fn test_speech_resets_the_silence_run() {
+        let mut detector = SilenceDetector::new(-40.0, Duration::from_millis(400));
+        let mut audio = frames(0.2, 200);
+        audio.extend(frames(0.0, 300));
+        audio.extend(frames(0.2, 100));
+        audio.extend(frames(0.0, 1000));
+        // Stops 400 ms into the second pause, not the first
+        assert_eq!(stops_after(&mut detector, &audio), Some(10 + 15 + 5 + 19));
+    }
+}
@@ -0,0 +1,424 @@
+use std::f32::consts::PI;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::events;
+use crate::persist;
+use crate::playback::resample_linear;
+use crate::types::VoiceprintStatus;
+
+pub const VOICEPRINT_FILE: &str = "voiceprint.json";
+const FORMAT_VERSION: u32 = 1;
+
+const ANALYSIS_RATE: u32 = 16_000;
+/// 25ms frames with a 10ms hop, zero-padded to the FFT size
+const FRAME_LEN: usize = 400;
+const HOP: usize = 160;
+const FFT_LEN: usize = 512;
+const MEL_BANDS: usize = 26;
+const CEPSTRA: usize = 13;
+/// Frames more than this far below the loudest frame count as silence
+const VOICED_RANGE_DB: f32 = 30.0;
+/// Absolute floor so a silent recording doesn't "voice" its own noise
+const MIN_FRAME_RMS: f32 = 1e-3;
+
+/// Recording is judged speaker by speaker in windows of this length
+const SEGMENT_SECS: f32 = 1.5;
+/// A segment needs this much speech before it's worth judging
+const MIN_SEGMENT_VOICED_SECS: f32 = 0.3;
+pub const MIN_ENROLL_VOICED_SECS: f32 = 4.0;
+
+/// A locally learned description of the user's voice.
+/// This is a lightweight spectral fingerprint (mean and spread of MFCCs), not a neural
+/// speaker embedding: it separates clearly different voices, not similar ones.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Voiceprint {
+    pub version: u32,
+    pub features: Vec<f32>,
+    pub voiced_secs: f32,
+}
+
+/// Enrolled voiceprint and speaker filter configuration
+pub struct VoiceprintState {
+    pub voiceprint: Arc<Mutex<Option<Voiceprint>>>,
+    pub enabled: Arc<Mutex<bool>>,
+    /// Minimum similarity (-1..1) for a segment to count as the enrolled speaker
+    pub threshold: Arc<Mutex<f32>>,
+}
+
+impl VoiceprintState {
+    pub fn new() -> Self {
+        Self {
+            voiceprint: Arc::new(Mutex::new(None)),
+            enabled: Arc::new(Mutex::new(false)),
+            threshold: Arc::new(Mutex::new(0.8)),
+        }
+    }
+
+    pub fn configure(&self, enabled: bool, threshold: f32) {
+        if let Ok(mut current) = self.enabled.lock() {
+            *current = enabled;
+        }
+        if let Ok(mut current) = self.threshold.lock() {
+            *current = threshold;
+        }
+    }
+
+    pub fn status(&self) -> VoiceprintStatus {
+        let voiceprint = self.voiceprint.lock().ok().and_then(|v| v.clone());
+        VoiceprintStatus {
+            enrolled: voiceprint.is_some(),
+            voiced_secs: voiceprint.map_or(0.0, |v| v.voiced_secs),
+            filter_enabled: self.enabled.lock().map(|e| *e).unwrap_or(false),
+        }
+    }
+}
+
+fn voiceprint_path(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(VOICEPRINT_FILE))
+        .map_err(|e| format!("Failed to resolve app data dir: {e}"))
+}
+
+/// Load a previously enrolled voiceprint into state (missing or outdated files are ignored)
+pub fn load(app: &AppHandle, state: &VoiceprintState) {
+    let Ok(path) = voiceprint_path(app) else {
+        return;
+    };
+    let voiceprint = fs::read(&path)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice::<Voiceprint>(&bytes).ok())
+        .filter(|v| v.version == FORMAT_VERSION);
+    if let Ok(mut current) = state.voiceprint.lock() {
+        *current = voiceprint;
+    }
+}
+
+/// Learn the voiceprint from an enrollment recording and store it in the app data dir
+pub fn enroll(
+    app: &AppHandle,
+    state: &VoiceprintState,
+    samples: &[f32],
+    sample_rate: u32,
+) -> Result<VoiceprintStatus, String> {
+    let samples = resample_linear(samples, sample_rate, ANALYSIS_RATE);
+    let frames = voiced_frames(&samples);
+    let voiced_secs = frames.len() as f32 * HOP as f32 / ANALYSIS_RATE as f32;
+    if voiced_secs < MIN_ENROLL_VOICED_SECS {
+        return Err(format!(
+            "Only {voiced_secs:.1}s of speech was heard; keep talking for at least \
+             {MIN_ENROLL_VOICED_SECS:.0}s to enroll"
+        ));
+    }
+
+    let voiceprint = Voiceprint {
+        version: FORMAT_VERSION,
+        features: summarize(&frames),
+        voiced_secs,
+    };
+    let bytes = serde_json::to_vec(&voiceprint)
+        .map_err(|e| format!("Failed to serialize voiceprint: {e}"))?;
+    persist::write_atomic(&voiceprint_path(app)?, &bytes)?;
+
+    if let Ok(mut current) = state.voiceprint.lock() {
+        *current = Some(voiceprint);
+    }
+    events::record_state(app, "voiceprint-enrolled", voiced_secs);
+    Ok(state.status())
+}
+
+/// Forget the enrolled voiceprint
+pub fn clear(app: &AppHandle, state: &VoiceprintState) -> Result<VoiceprintStatus, String> {
+    let path = voiceprint_path(app)?;
+    match fs::remove_file(&path) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(format!("Failed to delete voiceprint: {e}")),
+    }
+    if let Ok(mut current) = state.voiceprint.lock() {
+        *current = None;
+    }
+    Ok(state.status())
+}
+
+/// Drop segments spoken by someone other than the enrolled user.
+/// Passes the recording through untouched when the filter is off or nobody is enrolled.
+pub fn apply_filter(app: &AppHandle, samples: Vec<f32>, sample_rate: u32) -> Vec<f32> {
+    let state = app.state::<VoiceprintState>();
+    if !state.enabled.lock().map(|e| *e).unwrap_or(false) {
+        return samples;
+    }
+    let Some(voiceprint) = state.voiceprint.lock().ok().and_then(|v| v.clone()) else {
+        return samples;
+    };
+    let threshold = state.threshold.lock().map(|t| *t).unwrap_or(0.8);
+
+    let (kept, dropped) = filter_speaker(&samples, sample_rate, &voiceprint.features, threshold);
+    events::record_state(
+        app,
+        "speaker-filter",
+        serde_json::json!({ "droppedSegments": dropped }),
+    );
+    kept
+}
+
+/// Split the recording into segments and keep only those matching `features`.
+/// Segments with too little speech to judge are kept. Returns the kept audio and
+/// how many segments were dropped.
+pub fn filter_speaker(
+    samples: &[f32],
+    sample_rate: u32,
+    features: &[f32],
+    threshold: f32,
+) -> (Vec<f32>, usize) {
+    let segment_len = ((sample_rate as f32 * SEGMENT_SECS) as usize).max(1);
+    let min_voiced_frames = (MIN_SEGMENT_VOICED_SECS * ANALYSIS_RATE as f32 / HOP as f32) as usize;
+
+    let mut kept = Vec::with_capacity(samples.len());
+    let mut dropped = 0;
+    for segment in samples.chunks(segment_len) {
+        let frames = voiced_frames(&resample_linear(segment, sample_rate, ANALYSIS_RATE));
+        if frames.len() < min_voiced_frames
+            || similarity(&summarize(&frames), features) >= threshold
+        {
+            kept.extend_from_slice(segment);
+        } else {
+            dropped += 1;
+        }
+    }
+    (kept, dropped)
+}
+
+/// Cosine similarity of two feature vectors
+pub fn similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+/// Mean and standard deviation of each cepstral coefficient except c0 (loudness)
+fn summarize(frames: &[[f32; CEPSTRA]]) -> Vec<f32> {
+    let n = frames.len().max(1) as f32;
+    let mut means = vec![0.0f32; CEPSTRA - 1];
+    for frame in frames {
+        for (mean, c) in means.iter_mut().zip(&frame[1..]) {
+            *mean += c / n;
+        }
+    }
+    let mut stds = vec![0.0f32; CEPSTRA - 1];
+    for frame in frames {
+        for ((std, mean), c) in stds.iter_mut().zip(&means).zip(&frame[1..]) {
+            *std += (c - mean).powi(2) / n;
+        }
+    }
+    means
+        .into_iter()
+        .chain(stds.into_iter().map(f32::sqrt))
+        .collect()
+}
+
+/// MFCCs of every voiced frame of a 16kHz signal
+fn voiced_frames(samples: &[f32]) -> Vec<[f32; CEPSTRA]> {
+    if samples.len() < FRAME_LEN {
+        return Vec::new();
+    }
+    let window: Vec<f32> = (0..FRAME_LEN)
+        .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / (FRAME_LEN - 1) as f32).cos())
+        .collect();
+    let filters = mel_filterbank();
+
+    let frames: Vec<(f32, &[f32])> = samples
+        .windows(FRAME_LEN)
+        .step_by(HOP)
+        .map(|frame| {
+            let rms = (frame.iter().map(|s| s * s).sum::<f32>() / FRAME_LEN as f32).sqrt();
+            (rms, frame)
+        })
+        .collect();
+    let loudest = frames.iter().fold(0.0f32, |max, (rms, _)| max.max(*rms));
+    let floor = (loudest * 10f32.powf(-VOICED_RANGE_DB / 20.0)).max(MIN_FRAME_RMS);
+
+    frames
+        .into_iter()
+        .filter(|(rms, _)| *rms >= floor)
+        .map(|(_, frame)| {
+            let mut re = vec![0.0f32; FFT_LEN];
+            let mut im = vec![0.0f32; FFT_LEN];
+            for (i, (s, w)) in frame.iter().zip(&window).enumerate() {
+                re[i] = s * w;
+            }
+            fft(&mut re, &mut im);
+
+            let power: Vec<f32> = (0..=FFT_LEN / 2)
+                .map(|k| re[k] * re[k] + im[k] * im[k])
+                .collect();
+            let log_mel: Vec<f32> = filters
+                .iter()
+                .map(|filter| {
+                    let energy: f32 = filter.iter().zip(&power).map(|(w, p)| w * p).sum();
+                    (energy + 1e-10).ln()
+                })
+                .collect();
+            dct(&log_mel)
+        })
+        .collect()
+}
+
+fn hz_to_mel(hz: f32) -> f32 {
+    2595.0 * (1.0 + hz / 700.0).log10()
+}
+
+fn mel_to_hz(mel: f32) -> f32 {
+    700.0 * (10f32.powf(mel / 2595.0) - 1.0)
+}
+
+/// Triangular filters evenly spaced on the mel scale between 0Hz and Nyquist
+fn mel_filterbank() -> Vec<Vec<f32>> {
+    let bins = FFT_LEN / 2 + 1;
+    let max_mel = hz_to_mel(ANALYSIS_RATE as f32 / 2.0);
+    let centers: Vec<f32> = (0..MEL_BANDS + 2)
+        .map(|i| {
+            let hz = mel_to_hz(max_mel * i as f32 / (MEL_BANDS + 1) as f32);
+            hz * FFT_LEN as f32 / ANALYSIS_RATE as f32
+        })
+        .collect();
+
+    (1..=MEL_BANDS)
+        .map(|m| {
+            let (left, center, right) = (centers[m - 1], centers[m], centers[m + 1]);
+            (0..bins)
+                .map(|k| {
+                    let k = k as f32;
+                    if k > left && k <= center {
+                        (k - left) / (center - left)
+                    } else if k > center && k < right {
+                        (right - k) / (right - center)
+                    } else {
+                        0.0
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// DCT-II of the log mel energies, keeping the first CEPSTRA coefficients
+fn dct(input: &[f32]) -> [f32; CEPSTRA] {
+    let n = input.len() as f32;
+    let mut out = [0.0f32; CEPSTRA];
+    for (k, c) in out.iter_mut().enumerate() {
+        *c = input
+            .iter()
+            .enumerate()
+            .map(|(i, x)| x * (PI * k as f32 * (i as f32 + 0.5) / n).cos())
+            .sum();
+    }
+    out
+}
+
+/// In-place iterative radix-2 FFT; `re.len()` must be a power of two
+fn fft(re: &mut [f32], im: &mut [f32]) {
+    let n = re.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let angle = -2.0 * PI / len as f32;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (w_re, w_im) = ((angle * k as f32).cos(), (angle * k as f32).sin());
+                let (a, b) = (start + k, start + k + len / 2);
+                let t_re = re[b] * w_re - im[b] * w_im;
+                let t_im = re[b] * w_im + im[b] * w_re;
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Harmonic-rich "voice" with the given pitch and formant-like spectral tilt
+    fn voice(pitch: f32, brightness: f32, secs: f32) -> Vec<f32> {
+        let n = (ANALYSIS_RATE as f32 * secs) as usize;
+        (0..n)
+            .map(|i| {
+                let t = i as f32 / ANALYSIS_RATE as f32;
+                (1..20)
+                    .map(|h| {
+                        let h = h as f32;
+                        (2.0 * PI * pitch * h * t).sin() * brightness.powf(h) * 0.2
+                    })
+                    .sum()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fft_of_impulse_is_flat() {
+        let mut re = vec![0.0f32; 8];
+        let mut im = vec![0.0f32; 8];
+        re[0] = 1.0;
+        fft(&mut re, &mut im);
+        assert!(re.iter().all(|v| (v - 1.0).abs() < 1e-6));
+        assert!(im.iter().all(|v| v.abs() < 1e-6));
+    }
+
+    #[test]
+    fn test_silence_has_no_voiced_frames() {
+        assert!(voiced_frames(&vec![0.0; ANALYSIS_RATE as usize]).is_empty());
+    }
+
+    #[test]
+    fn test_same_voice_more_similar_than_different_voice() {
+        let enrolled = summarize(&voiced_frames(&voice(120.0, 0.8, 2.0)));
+        let same = summarize(&voiced_frames(&voice(120.0, 0.8, 1.0)));
+        let other = summarize(&voiced_frames(&voice(260.0, 0.4, 1.0)));
+        assert!(similarity(&enrolled, &same) > similarity(&enrolled, &other));
+        assert!(similarity(&enrolled, &same) > 0.99);
+    }
+
+    #[test]
+    fn test_filter_drops_other_speaker_segments() {
+        let enrolled = summarize(&voiced_frames(&voice(120.0, 0.8, 2.0)));
+        let mut recording = voice(120.0, 0.8, 1.5);
+        recording.extend(voice(260.0, 0.4, 1.5));
+
+        let threshold = similarity(
+            &enrolled,
+            &summarize(&voiced_frames(&voice(260.0, 0.4, 1.5))),
+        ) + 0.01;
+        let (kept, dropped) = filter_speaker(&recording, ANALYSIS_RATE, &enrolled, threshold);
+        assert_eq!(dropped, 1);
+        assert_eq!(kept.len(), recording.len() / 2);
+    }
+}
@@ -0,0 +1,565 @@
+use std::ffi::{c_char, c_float, CStr, CString};
+use std::path::Path;
+use std::ptr;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use libloading::Library;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::audio::{self, AudioState};
+use crate::events;
+use crate::idle::PresenceState;
+use crate::mic_usage;
+use crate::types::{MicSource, WakeWord, WakeWordDetected};
+
+/// How often captured audio is handed to the engine
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// Wait before trying again to open an engine or microphone that failed
+const RETRY_INTERVAL: Duration = Duration::from_secs(10);
+/// The microphone is left to the recording for this long after a wake word starts one
+const RECORDING_HOLD: Duration = Duration::from_secs(3);
+
+/// The wake-word settings and the microphone they listen on
+pub struct WakeWordState {
+    pub config: Arc<Mutex<Option<WakeWord>>>,
+    /// Microphone listened on, by id; the default one if None
+    pub device_id: Arc<Mutex<Option<String>>>,
+}
+
+impl WakeWordState {
+    pub fn new() -> Self {
+        Self {
+            config: Arc::new(Mutex::new(None)),
+            device_id: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn configure(&self, config: Option<WakeWord>, device_id: Option<String>) {
+        if let Ok(mut current) = self.config.lock() {
+            *current = config;
+        }
+        if let Ok(mut current) = self.device_id.lock() {
+            *current = device_id;
+        }
+    }
+}
+
+/// An enabled wake word needs Porcupine's library, model, keywords and access key
+pub fn validate(config: &WakeWord) -> Result<(), String> {
+    if !config.enabled {
+        return Ok(());
+    }
+    for (value, name) in [
+        (&config.library_path, "libraryPath"),
+        (&config.model_path, "modelPath"),
+        (&config.access_key, "accessKey"),
+    ] {
+        if value.trim().is_empty() {
+            return Err(format!("'wake_word' needs a {name}"));
+        }
+    }
+    if config
+        .keyword_paths
+        .iter()
+        .all(|path| path.trim().is_empty())
+    {
+        return Err("'wake_word' needs at least one keyword file".to_string());
+    }
+    if !(0.0..=1.0).contains(&config.sensitivity) {
+        return Err("'wake_word' sensitivity must be between 0.0 and 1.0".to_string());
+    }
+    Ok(())
+}
+
+/// `hey-claw_en_linux_v3_0_0.ppn` is heard as "hey-claw"
+fn keyword_name(path: &str) -> String {
+    let stem = Path::new(path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string());
+    match stem.split_once('_') {
+        Some((name, _)) if !name.is_empty() => name.to_string(),
+        _ => stem,
+    }
+}
+
+type PvStatus = i32;
+const PV_STATUS_SUCCESS: PvStatus = 0;
+
+#[repr(C)]
+struct PvPorcupine {
+    _private: [u8; 0],
+}
+
+type InitFn = unsafe extern "C" fn(
+    *const c_char,
+    *const c_char,
+    i32,
+    *const *const c_char,
+    *const c_float,
+    *mut *mut PvPorcupine,
+) -> PvStatus;
+type ProcessFn = unsafe extern "C" fn(*mut PvPorcupine, *const i16, *mut i32) -> PvStatus;
+type DeleteFn = unsafe extern "C" fn(*mut PvPorcupine);
+type LengthFn = unsafe extern "C" fn() -> i32;
+type StatusToStringFn = unsafe extern "C" fn(PvStatus) -> *const c_char;
+
+/// A Porcupine instance from the shared library the settings point at, used through
+/// its C API (pv_porcupine.h) so nothing of Picovoice's is needed to build the app
+struct Porcupine {
+    handle: *mut PvPorcupine,
+    process: ProcessFn,
+    delete: DeleteFn,
+    status_to_string: StatusToStringFn,
+    sample_rate: u32,
+    frame_length: usize,
+    keywords: Vec<String>,
+    /// Keeps the functions above loaded
+    _library: Library,
+}
+
+// SAFETY: a Porcupine handle may be used from any thread as long as calls don't
+// overlap, which `&mut self` on `process` ensures.
+unsafe impl Send for Porcupine {}
+
+impl Porcupine {
+    fn open(config: &WakeWord) -> Result<Self, String> {
+        // SAFETY: loading runs the library's initializers; it is the Porcupine build the
+        // user pointed the settings at.
+        let library = unsafe { Library::new(&config.library_path) }.map_err(|e| {
+            format!(
+                "Failed to load Porcupine from '{}': {e}",
+                config.library_path
+            )
+        })?;
+        let missing = |e: libloading::Error| format!("Not a Porcupine library: {e}");
+        // SAFETY: the signatures are those of pv_porcupine.h and pv_picovoice.h.
+        let (init, process, delete, frame_length, sample_rate, status_to_string) = unsafe {
+            (
+                *library
+                    .get::<InitFn>(b"pv_porcupine_init\0")
+                    .map_err(missing)?,
+                *library
+                    .get::<ProcessFn>(b"pv_porcupine_process\0")
+                    .map_err(missing)?,
+                *library
+                    .get::<DeleteFn>(b"pv_porcupine_delete\0")
+                    .map_err(missing)?,
+                *library
+                    .get::<LengthFn>(b"pv_porcupine_frame_length\0")
+                    .map_err(missing)?,
+                *library
+                    .get::<LengthFn>(b"pv_sample_rate\0")
+                    .map_err(missing)?,
+                *library
+                    .get::<StatusToStringFn>(b"pv_status_to_string\0")
+                    .map_err(missing)?,
+            )
+        };
+
+        let paths: Vec<&String> = config
+            .keyword_paths
+            .iter()
+            .filter(|path| !path.trim().is_empty())
+            .collect();
+        let access_key = c_string(&config.access_key)?;
+        let model_path = c_string(&config.model_path)?;
+        let keyword_paths = paths
+            .iter()
+            .map(|path| c_string(path))
+            .collect::<Result<Vec<_>, _>>()?;
+        let keyword_ptrs: Vec<*const c_char> = keyword_paths.iter().map(|p| p.as_ptr()).collect();
+        let sensitivities = vec![config.sensitivity; keyword_ptrs.len()];
+
+        let mut handle = ptr::null_mut();
+        // SAFETY: every pointer refers to a live local of the length passed, and `handle`
+        // is only read once init reports success.
+        let status = unsafe {
+            init(
+                access_key.as_ptr(),
+                model_path.as_ptr(),
+                keyword_ptrs.len() as i32,
+                keyword_ptrs.as_ptr(),
+                sensitivities.as_ptr(),
+                &mut handle,
+            )
+        };
+        if status != PV_STATUS_SUCCESS || handle.is_null() {
+            return Err(format!(
+                "Porcupine didn't start: {}",
+                describe(status_to_string, status)
+            ));
+        }
+        // SAFETY: both take no arguments and only return constants.
+        let (sample_rate, frame_length) = unsafe { (sample_rate(), frame_length()) };
+
+        Ok(Self {
+            handle,
+            process,
+            delete,
+            status_to_string,
+            sample_rate: sample_rate.max(1) as u32,
+            frame_length: frame_length.max(1) as usize,
+            keywords: paths.iter().map(|path| keyword_name(path)).collect(),
+            _library: library,
+        })
+    }
+
+    /// Feed one frame of `frame_length` samples; returns the keyword heard in it
+    fn process(&mut self, frame: &[i16]) -> Result<Option<&str>, String> {
+        debug_assert_eq!(frame.len(), self.frame_length);
+        let mut index = -1;
+        // SAFETY: `handle` is live until drop and `frame` holds `frame_length` samples.
+        let status = unsafe { (self.process)(self.handle, frame.as_ptr(), &mut index) };
+        if status != PV_STATUS_SUCCESS {
+            return Err(format!(
+                "Porcupine stopped: {}",
+                describe(self.status_to_string, status)
+            ));
+        }
+        Ok(usize::try_from(index)
+            .ok()
+            .and_then(|i| self.keywords.get(i))
+            .map(String::as_str))
+    }
+}
+
+impl Drop for Porcupine {
+    fn drop(&mut self) {
+        // SAFETY: the handle came from a successful init and is deleted once.
+        unsafe { (self.delete)(self.handle) };
+    }
+}
+
+fn c_string(value: &str) -> Result<CString, String> {
+    CString::new(value).map_err(|_| format!("'{value}' contains a NUL character"))
+}
+
+fn describe(status_to_string: StatusToStringFn, status: PvStatus) -> String {
+    // SAFETY: Porcupine returns a static string for any status, or null.
+    let text = unsafe { status_to_string(status) };
+    if text.is_null() {
+        return format!("status {status}");
+    }
+    // SAFETY: non-null results are NUL-terminated static strings.
+    unsafe { CStr::from_ptr(text) }
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Turns captured audio at the microphone's rate into the 16-bit frames the engine
+/// takes, resampling linearly across the boundaries between captures
+#[derive(Debug)]
+struct Framer {
+    /// Input samples per output sample
+    step: f64,
+    frame_length: usize,
+    /// Where the next output sample falls, relative to the start of the next input
+    position: f64,
+    /// Last sample of the previous input, at position -1
+    previous: f32,
+    pending: Vec<i16>,
+}
+
+impl Framer {
+    fn new(from_rate: u32, to_rate: u32, frame_length: usize) -> Self {
+        Self {
+            step: f64::from(from_rate) / f64::from(to_rate),
+            frame_length,
+            position: 0.0,
+            previous: 0.0,
+            pending: Vec::with_capacity(frame_length),
+        }
+    }
+
+    /// Take in the next captured samples, returning every frame completed by them
+    fn push(&mut self, input: &[f32]) -> Vec<Vec<i16>> {
+        let Some(&last) = input.last() else {
+            return Vec::new();
+        };
+        let at = |i: isize| {
+            if i < 0 {
+                self.previous
+            } else {
+                input[i as usize]
+            }
+        };
+        let end = (input.len() - 1) as f64;
+        let mut frames = Vec::new();
+        while self.position <= end {
+            let i = self.position.floor() as isize;
+            let fraction = (self.position - i as f64) as f32;
+            let next = at((i + 1).min(input.len() as isize - 1));
+            let sample = at(i) + (next - at(i)) * fraction;
+            self.pending
+                .push((sample.clamp(-1.0, 1.0) * f32::from(i16::MAX)) as i16);
+            if self.pending.len() == self.frame_length {
+                frames.push(std::mem::replace(
+                    &mut self.pending,
+                    Vec::with_capacity(self.frame_length),
+                ));
+            }
+            self.position += self.step;
+        }
+        self.position -= input.len() as f64;
+        self.previous = last;
+        frames
+    }
+}
+
+/// An open engine and the microphone stream feeding it
+struct Listener {
+    engine: Porcupine,
+    capture: AudioState,
+    framer: Framer,
+    /// What it was opened with, so a settings change opens a new one
+    config: WakeWord,
+    device_id: Option<String>,
+}
+
+impl Listener {
+    fn open(
+        config: WakeWord,
+        device_id: Option<String>,
+        host: Option<String>,
+        channel: Option<u16>,
+    ) -> Result<Self, String> {
+        let engine = Porcupine::open(&config)?;
+        // A stream of its own, apart from recordings, that is drained as it fills
+        let capture = AudioState::new();
+        capture.set_host(host);
+        capture.set_input_channel(channel);
+        audio::start_recording(&capture, device_id.as_deref(), None)?;
+        let rate = capture
+            .sample_rate
+            .lock()
+            .ok()
+            .and_then(|rate| *rate)
+            .unwrap_or(engine.sample_rate);
+        let framer = Framer::new(rate, engine.sample_rate, engine.frame_length);
+        Ok(Self {
+            engine,
+            capture,
+            framer,
+            config,
+            device_id,
+        })
+    }
+
+    /// Run the audio captured since the last call through the engine
+    fn poll(&mut self) -> Result<Option<String>, String> {
+        let fresh = match self.capture.samples.lock() {
+            Ok(mut samples) => std::mem::take(&mut *samples),
+            Err(_) => return Ok(None),
+        };
+        let mut heard = None;
+        for frame in self.framer.push(&fresh) {
+            if let Some(keyword) = self.engine.process(&frame)? {
+                heard = Some(keyword.to_string());
+            }
+        }
+        Ok(heard)
+    }
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        audio::cancel_recording(&self.capture);
+    }
+}
+
+/// The settings to listen with right now: none while wake words are off, listening is
+/// paused for idleness or the lock screen, or the microphone is recording
+fn wanted(app: &AppHandle) -> Option<(WakeWord, Option<String>)> {
+    let state = app.state::<WakeWordState>();
+    let config = state.config.lock().ok()?.clone().filter(|c| c.enabled)?;
+    if app.state::<PresenceState>().pause_reason().is_some()
+        || app
+            .state::<AudioState>()
+            .is_recording
+            .load(Ordering::SeqCst)
+    {
+        return None;
+    }
+    let device_id = state.device_id.lock().ok()?.clone();
+    Some((config, device_id))
+}
+
+fn report(app: &AppHandle, error: &str) {
+    eprintln!("Wake word: {error}");
+    events::record_state(app, "wake-word-error", error);
+    let _ = app.emit("wake-word-error", error);
+}
+
+fn detected(app: &AppHandle, keyword: String, auto_record: bool) {
+    let detected = WakeWordDetected {
+        keyword,
+        auto_record,
+    };
+    events::record_state(app, "wake-word", &detected);
+    if auto_record {
+        if let Some(window) = app.get_webview_window("popup") {
+            let _ = window.show();
+        }
+    }
+    let _ = app.emit("wake-word", &detected);
+}
+
+/// Listen for the wake words while they are enabled, emitting "wake-word" when one is
+/// heard and "wake-word-error" when the engine or microphone can't be opened
+pub async fn listen(app: AppHandle) {
+    let mut listener: Option<Listener> = None;
+    let mut last_error: Option<String> = None;
+    let mut idle_until: Option<Instant> = None;
+    loop {
+        tokio::time::sleep(POLL_INTERVAL).await;
+
+        let Some((config, device_id)) = wanted(&app) else {
+            listener = None;
+            continue;
+        };
+        if listener
+            .as_ref()
+            .is_some_and(|l| l.config != config || l.device_id != device_id)
+        {
+            listener = None;
+        }
+        if idle_until.is_some_and(|until| Instant::now() < until) {
+            continue;
+        }
+
+        if listener.is_none() {
+            let audio = app.state::<AudioState>();
+            let host = audio.host_name();
+            let channel = audio.input_channel.lock().ok().and_then(|c| *c);
+            let opened = tauri::async_runtime::spawn_blocking(move || {
+                Listener::open(config, device_id, host, channel)
+            })
+            .await
+            .unwrap_or_else(|e| Err(e.to_string()));
+            match opened {
+                Ok(opened) => {
+                    let device = opened.capture.device_name();
+                    let in_use = mic_usage::opened(&app, MicSource::WakeWord, None, device);
+                    opened.capture.mark_in_use(in_use);
+                    listener = Some(opened);
+                    last_error = None;
+                }
+                Err(error) => {
+                    idle_until = Some(Instant::now() + RETRY_INTERVAL);
+                    // Reported once, not on every retry
+                    if last_error.as_deref() != Some(error.as_str()) {
+                        report(&app, &error);
+                        last_error = Some(error);
+                    }
+                    continue;
+                }
+            }
+        }
+        let Some(active) = listener.as_mut() else {
+            continue;
+        };
+
+        match active.poll() {
+            Ok(None) => {}
+            Ok(Some(keyword)) => {
+                let auto_record = active.config.auto_record;
+                if auto_record {
+                    // Free the microphone for the recording the popup is about to start
+                    listener = None;
+                    idle_until = Some(Instant::now() + RECORDING_HOLD);
+                }
+                detected(&app, keyword, auto_record);
+            }
+            Err(error) => {
+                listener = None;
+                idle_until = Some(Instant::now() + RETRY_INTERVAL);
+                report(&app, &error);
+                last_error = Some(error);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keyword_names() {
+        assert_eq!(
+            keyword_name("/models/hey-claw_en_linux_v3_0_0.ppn"),
+            "hey-claw"
+        );
+        assert_eq!(keyword_name("computer.ppn"), "computer");
+        assert_eq!(keyword_name("_odd.ppn"), "_odd");
+    }
+
+    #[test]
+    fn test_framer_resamples_across_pushes() {
+        // 48 kHz down to 16 kHz keeps every third sample of a ramp
+        let ramp: Vec<f32> = (0..12).map(|i| i as f32 / 100.0).collect();
+        let mut whole = Framer::new(48_000, 16_000, 4);
+        let frames = whole.push(&ramp);
+        assert_eq!(frames.len(), 1);
+        let expected: Vec<i16> = [0.0, 0.03, 0.06, 0.09]
+            .iter()
+            .map(|s: &f32| (s * f32::from(i16::MAX)) as i16)
+            .collect();
+        assert_eq!(frames[0], expected);
+
+        // Split unevenly, the same audio gives the same frame
+        let mut split = Framer::new(48_000, 16_000, 4);
+        assert!(split.push(&ramp[..5]).is_empty());
+        assert_eq!(split.push(&ramp[5..]), frames);
+    }
+
+    #[test]
+    fn test_framer_interpolates_between_pushes() {
+        // 16 kHz up to 32 kHz puts a sample halfway between each pair, including the
+        // pair that straddles two pushes
+        let mut framer = Framer::new(16_000, 32_000, 4);
+        assert!(framer.push(&[0.0, 0.5]).is_empty());
+        let frames = framer.push(&[1.0]);
+        let full = f32::from(i16::MAX);
+        assert_eq!(
+            frames,
+            vec![vec![
+                0,
+                (0.25 * full) as i16,
+                (0.5 * full) as i16,
+                (0.75 * full) as i16
+            ]]
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        let config = WakeWord {
+            library_path: "/opt/porcupine/libpv_porcupine.so".to_string(),
+            model_path: "/opt/porcupine/porcupine_params.pv".to_string(),
+            keyword_paths: vec!["/opt/porcupine/hey-claw.ppn".to_string()],
+            access_key: "key".to_string(),
+            ..WakeWord::default()
+        };
+        assert!(validate(&config).is_ok());
+        assert!(validate(&WakeWord::default()).is_err());
+        assert!(validate(&WakeWord {
+            enabled: false,
+            ..WakeWord::default()
+        })
+        .is_ok());
+        assert!(validate(&WakeWord {
+            sensitivity: 1.5,
+            ..config.clone()
+        })
+        .is_err());
+        assert!(validate(&WakeWord {
+            keyword_paths: Vec::new(),
+            ..config
+        })
+        .is_err());
+    }
+}
@@ -0,0 +1,280 @@
+use std::collections::HashMap;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::Notify;
+
+use crate::audio::{self, AudioState, RecordingPhase};
+use crate::quick_talk::{self, Phase, QuickTalkState};
+use crate::types::{StateRecovered, StuckState};
+use crate::{events, playback};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// How long an inconsistent state must last before it counts as stuck, so
+/// the moment between setting a flag and acting on it isn't mistaken for one
+const GRACE: Duration = Duration::from_secs(5);
+/// Opening a device can take a few seconds, a Bluetooth headset switching profiles longer
+const START_LIMIT: Duration = Duration::from_secs(15);
+
+/// What the watchdog sees on each tick
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Observation {
+    pub recording: bool,
+    /// The recording is still opening its device
+    pub starting: bool,
+    pub quick_talk_recording: bool,
+    pub quick_talk_sending: bool,
+    /// How long the current send has been running
+    pub sending_for: Option<Duration>,
+}
+
+/// Turns observations into stuck states, once each has lasted past its limit
+#[derive(Debug, Default)]
+pub struct Watchdog {
+    suspect_since: HashMap<StuckState, Instant>,
+}
+
+impl Watchdog {
+    pub fn check(
+        &mut self,
+        seen: Observation,
+        now: Instant,
+        send_limit: Duration,
+    ) -> Vec<StuckState> {
+        let mut stuck = Vec::new();
+        for (state, present, limit) in [
+            (
+                StuckState::RecordingNeverStarted,
+                seen.starting,
+                START_LIMIT,
+            ),
+            (
+                StuckState::QuickTalkWithoutRecording,
+                seen.quick_talk_recording && !seen.recording,
+                GRACE,
+            ),
+            (
+                StuckState::QuickTalkWithoutSend,
+                seen.quick_talk_sending && seen.sending_for.is_none(),
+                GRACE,
+            ),
+        ] {
+            if !present {
+                self.suspect_since.remove(&state);
+                continue;
+            }
+            let since = *self.suspect_since.entry(state).or_insert(now);
+            if now.duration_since(since) >= limit {
+                self.suspect_since.remove(&state);
+                stuck.push(state);
+            }
+        }
+        if seen
+            .sending_for
+            .is_some_and(|elapsed| elapsed >= send_limit)
+        {
+            stuck.push(StuckState::SendingTooLong);
+        }
+        stuck
+    }
+}
+
+pub struct WatchdogState {
+    pub watchdog: Arc<Mutex<Watchdog>>,
+    /// When the send in progress started
+    pub sending_since: Arc<Mutex<Option<Instant>>>,
+    pub send_limit: Arc<Mutex<Duration>>,
+    /// Longest a reply may take to finish once the gateway answers; see `api::Gateway`
+    pub response_limit: Arc<Mutex<Option<Duration>>>,
+    /// Wakes a send the watchdog gave up on
+    pub cancel_send: Arc<Notify>,
+}
+
+impl WatchdogState {
+    pub fn new() -> Self {
+        Self {
+            watchdog: Arc::new(Mutex::new(Watchdog::default())),
+            sending_since: Arc::new(Mutex::new(None)),
+            send_limit: Arc::new(Mutex::new(Duration::from_secs(5 * 60))),
+            response_limit: Arc::new(Mutex::new(Some(Duration::from_secs(120)))),
+            cancel_send: Arc::new(Notify::new()),
+        }
+    }
+
+    pub fn set_send_limit(&self, limit: Duration) {
+        if let Ok(mut current) = self.send_limit.lock() {
+            *current = limit;
+        }
+    }
+
+    pub fn set_response_limit(&self, limit: Option<Duration>) {
+        if let Ok(mut current) = self.response_limit.lock() {
+            *current = limit;
+        }
+    }
+
+    pub fn response_limit(&self) -> Option<Duration> {
+        self.response_limit.lock().ok().and_then(|limit| *limit)
+    }
+
+    fn set_sending(&self, since: Option<Instant>) {
+        if let Ok(mut current) = self.sending_since.lock() {
+            *current = since;
+        }
+    }
+}
+
+/// Marks a send as running for the watchdog until dropped
+pub struct SendingGuard<'a>(&'a WatchdogState);
+
+impl<'a> SendingGuard<'a> {
+    pub fn start(state: &'a WatchdogState) -> Self {
+        state.set_sending(Some(Instant::now()));
+        Self(state)
+    }
+}
+
+impl Drop for SendingGuard<'_> {
+    fn drop(&mut self) {
+        self.0.set_sending(None);
+    }
+}
+
+fn observe(app: &AppHandle) -> Observation {
+    let audio = app.state::<AudioState>();
+    let sending_since = app
+        .state::<WatchdogState>()
+        .sending_since
+        .lock()
+        .ok()
+        .and_then(|since| *since);
+    let quick_talk = app.state::<QuickTalkState>().phase();
+    Observation {
+        recording: audio.is_recording.load(Ordering::SeqCst),
+        starting: audio.phase() == RecordingPhase::Starting,
+        quick_talk_recording: quick_talk == Phase::Recording,
+        quick_talk_sending: quick_talk == Phase::Sending,
+        sending_for: sending_since.map(|since| since.elapsed()),
+    }
+}
+
+/// Put things back to idle and tell the diagnostics log and the popup what happened
+fn recover(app: &AppHandle, stuck: StuckState) {
+    let action = match stuck {
+        StuckState::RecordingNeverStarted => {
+            audio::abandon_start(&app.state::<AudioState>());
+            playback::end_barge_in(app, false);
+            "recording reset"
+        }
+        StuckState::QuickTalkWithoutRecording | StuckState::QuickTalkWithoutSend => {
+            quick_talk::reset(app);
+            "tray reset"
+        }
+        StuckState::SendingTooLong => {
+            let state = app.state::<WatchdogState>();
+            // Reported once; the cancelled send clears it again when it returns
+            state.set_sending(None);
+            state.cancel_send.notify_waiters();
+            "send cancelled"
+        }
+    };
+    let recovered = StateRecovered {
+        stuck,
+        action: action.to_string(),
+    };
+    events::record_state(app, "state-recovered", &recovered);
+    let _ = app.emit("state-recovered", &recovered);
+}
+
+/// Look for stuck states every couple of seconds and recover from them
+pub async fn monitor(app: AppHandle) {
+    loop {
+        tokio::time::sleep(POLL_INTERVAL).await;
+
+        let state = app.state::<WatchdogState>();
+        let send_limit = state.send_limit.lock().map(|l| *l).unwrap_or(Duration::MAX);
+        let seen = observe(&app);
+        let stuck = match state.watchdog.lock() {
+            Ok(mut watchdog) => watchdog.check(seen, Instant::now(), send_limit),
+            Err(_) => continue,
+        };
+        for stuck in stuck {
+            recover(&app, stuck);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIMIT: Duration = Duration::from_secs(300);
+
+    #[test]
+    fn test_inconsistent_state_must_outlast_grace() {
+        let mut watchdog = Watchdog::default();
+        let t0 = Instant::now();
+        let opening = Observation {
+            starting: true,
+            ..Observation::default()
+        };
+        assert!(watchdog.check(opening, t0, LIMIT).is_empty());
+        // The device opened in time
+        let open = Observation {
+            recording: true,
+            ..Observation::default()
+        };
+        assert!(watchdog
+            .check(open, t0 + Duration::from_secs(2), LIMIT)
+            .is_empty());
+        assert!(watchdog
+            .check(opening, t0 + Duration::from_secs(6), LIMIT)
+            .is_empty());
+
+        assert!(watchdog
+            .check(opening, t0 + Duration::from_secs(20), LIMIT)
+            .is_empty());
+        assert_eq!(
+            watchdog.check(opening, t0 + Duration::from_secs(21), LIMIT),
+            vec![StuckState::RecordingNeverStarted]
+        );
+    }
+
+    #[test]
+    fn test_tray_recording_without_audio_and_slow_sends() {
+        let mut watchdog = Watchdog::default();
+        let t0 = Instant::now();
+        let tray = Observation {
+            quick_talk_recording: true,
+            sending_for: Some(Duration::from_secs(30)),
+            ..Observation::default()
+        };
+        assert!(watchdog.check(tray, t0, LIMIT).is_empty());
+        assert_eq!(
+            watchdog.check(tray, t0 + GRACE, LIMIT),
+            vec![StuckState::QuickTalkWithoutRecording]
+        );
+
+        // The popup gave up on the recording before sending it
+        let unsent = Observation {
+            quick_talk_sending: true,
+            ..Observation::default()
+        };
+        assert!(watchdog.check(unsent, t0, LIMIT).is_empty());
+        assert_eq!(
+            watchdog.check(unsent, t0 + GRACE, LIMIT),
+            vec![StuckState::QuickTalkWithoutSend]
+        );
+
+        let slow = Observation {
+            sending_for: Some(LIMIT),
+            ..Observation::default()
+        };
+        assert_eq!(
+            watchdog.check(slow, t0, LIMIT),
+            vec![StuckState::SendingTooLong]
+        );
+    }
+}
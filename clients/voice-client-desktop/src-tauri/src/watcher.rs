@@ -0,0 +1,467 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use serde_json::Value;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_store::resolve_store_path;
+
+use crate::routing::{self, Routes};
+use crate::types::{
+    CaptureOverflow, DeviceFormat, DeviceLabel, EffectStage, EventFilters, HalfDuplex, HotkeyPopup,
+    KeyboardLayout, Macro, PopupPosition, PreprocessStage, QualityPreset, ResponseRoute,
+    ScheduledRecording, SettingsRejected, SoundCues, SttProviderKind, TriggerSource,
+    TtsProviderKind, WakeWord, Webhook,
+};
+use crate::{
+    api, audio, effects, events, hotkey, macros, persist, response_routes, schedule, settings,
+    wake_word, webhooks,
+};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+const STRING_KEYS: &[&str] = &[
+    "gateway_url",
+    "profile_name",
+    "session_key",
+    "response_language",
+    "workspace",
+    "microphone_device_id",
+    "audio_host",
+    "system_audio_device_id",
+    "push_to_talk_hotkey",
+    "endpoint_template",
+    "audio_quality",
+    "capture_overflow",
+    "half_duplex",
+    "hotkey_popup",
+    "hotkey_popup_position",
+    "whisper_command",
+    "whisper_model",
+    "whisper_preview_model",
+    "piper_command",
+    "piper_model",
+];
+const UINT_KEYS: &[&str] = &[
+    "trigger_cooldown_ms",
+    "idle_pause_secs",
+    "event_log_capacity",
+    "schedule_notice_secs",
+    "countdown_secs",
+    "response_cache_ttl_secs",
+    "send_timeout_mins",
+    "response_timeout_secs",
+    "storage_min_free_mb",
+    "history_max_mb",
+    "auto_stop_silence_ms",
+    "dictation_pause_ms",
+    "trim_padding_ms",
+    "remote_control_port",
+    "notification_max_chars",
+    "capture_max_mb",
+    "autocorrect_min_count",
+    "input_channel",
+    "capture_high_pass_hz",
+    "archive_max_count",
+    "archive_max_age_days",
+    "brief_max_sentences",
+];
+const FLOAT_KEYS: &[&str] = &[
+    "speaker_filter_threshold",
+    "vad_threshold_db",
+    "trim_threshold_db",
+    "playback_volume",
+    "meeting_mic_gain_db",
+    "meeting_system_gain_db",
+];
+const BOOL_KEYS: &[&str] = &[
+    "pause_on_lock",
+    "http_trace",
+    "speaker_filter",
+    "share_context",
+    "alert_action_items",
+    "answer_offline_from_cache",
+    "demo_mode",
+    "archive_recordings",
+    "popup_pinned",
+    "streaming_upload",
+    "noise_suppression",
+    "trim_silence",
+    "calibrate_noise_floor",
+    "remote_control",
+    "show_more_opens_popup",
+    "autocorrect",
+    "meeting_mode",
+    "speak_replies",
+    "prefer_local_stt",
+    "brief_mode",
+];
+const STRING_MAP_KEYS: &[&str] = &["endpoint_overrides", "stt_providers", "tts_providers"];
+const STRING_LIST_KEYS: &[&str] = &["alert_keywords"];
+
+/// Check hand-edited settings before they replace the running configuration.
+/// Unknown keys are left alone; known keys must have the type `save_settings` writes.
+pub fn validate(entries: &HashMap<String, Value>) -> Result<(), String> {
+    for (key, value) in entries {
+        let key = key.as_str();
+        let valid = if STRING_KEYS.contains(&key) {
+            value.is_string()
+        } else if UINT_KEYS.contains(&key) {
+            value.is_u64()
+        } else if FLOAT_KEYS.contains(&key) {
+            value.is_number()
+        } else if BOOL_KEYS.contains(&key) {
+            value.is_boolean()
+        } else if STRING_MAP_KEYS.contains(&key) {
+            value
+                .as_object()
+                .is_some_and(|map| map.values().all(Value::is_string))
+        } else if STRING_LIST_KEYS.contains(&key) {
+            value
+                .as_array()
+                .is_some_and(|list| list.iter().all(Value::is_string))
+        } else {
+            true
+        };
+        if !valid {
+            return Err(format!("'{key}' has the wrong type: {value}"));
+        }
+    }
+
+    if let Some(url) = entries.get("gateway_url").and_then(|v| v.as_str()) {
+        api::parse_base_url(url).map_err(|e| e.to_string())?;
+    }
+
+    if let Some(language) = entries.get("response_language").and_then(|v| v.as_str()) {
+        if !language.is_empty() {
+            api::validate_language_tag(language)?;
+        }
+    }
+
+    if let Some(workspace) = entries.get("workspace").and_then(|v| v.as_str()) {
+        if !workspace.is_empty() {
+            api::validate_workspace(workspace)?;
+        }
+    }
+
+    if let Some(quality) = entries.get("audio_quality") {
+        serde_json::from_value::<QualityPreset>(quality.clone()).map_err(|_| {
+            format!(
+                "'audio_quality' must be one of voiceSmall, balanced, highFidelity, not {quality}"
+            )
+        })?;
+    }
+
+    if let Some(overflow) = entries.get("capture_overflow") {
+        serde_json::from_value::<CaptureOverflow>(overflow.clone()).map_err(|_| {
+            format!("'capture_overflow' must be dropOldest or stop, not {overflow}")
+        })?;
+    }
+
+    if let Some(mode) = entries.get("half_duplex") {
+        serde_json::from_value::<HalfDuplex>(mode.clone()).map_err(|_| {
+            format!("'half_duplex' must be off, muteMicrophone or duckPlayback, not {mode}")
+        })?;
+    }
+
+    if let Some(mode) = entries.get("hotkey_popup") {
+        serde_json::from_value::<HotkeyPopup>(mode.clone())
+            .map_err(|_| format!("'hotkey_popup' must be show, trayOnly or onReply, not {mode}"))?;
+    }
+
+    if let Some(position) = entries.get("hotkey_popup_position") {
+        serde_json::from_value::<PopupPosition>(position.clone()).map_err(|_| {
+            format!(
+                "'hotkey_popup_position' must be trayCenter, topRight, bottomRight or center, \
+                 not {position}"
+            )
+        })?;
+    }
+
+    if let Some(volume) = entries.get("playback_volume").and_then(Value::as_f64) {
+        if !(0.0..=1.0).contains(&volume) {
+            return Err(format!(
+                "'playback_volume' must be between 0.0 and 1.0, not {volume}"
+            ));
+        }
+    }
+
+    for key in ["meeting_mic_gain_db", "meeting_system_gain_db"] {
+        if let Some(gain_db) = entries.get(key).and_then(Value::as_f64) {
+            if !(-24.0..=12.0).contains(&gain_db) {
+                return Err(format!(
+                    "'{key}' must be between -24 and 12 dB, not {gain_db}"
+                ));
+            }
+        }
+    }
+
+    if entries.get("autocorrect_min_count").and_then(Value::as_u64) == Some(0) {
+        return Err("'autocorrect_min_count' must be at least 1".to_string());
+    }
+
+    if let Some(max) = entries.get("brief_max_sentences").and_then(Value::as_u64) {
+        if !(1..=10).contains(&max) {
+            return Err(format!(
+                "'brief_max_sentences' must be between 1 and 10, not {max}"
+            ));
+        }
+    }
+
+    if let Some(providers) = entries.get("stt_providers") {
+        serde_json::from_value::<HashMap<String, SttProviderKind>>(providers.clone()).map_err(
+            |_| format!("'stt_providers' values must be gateway or localWhisper, not {providers}"),
+        )?;
+    }
+
+    if let Some(providers) = entries.get("tts_providers") {
+        serde_json::from_value::<HashMap<String, TtsProviderKind>>(providers.clone()).map_err(
+            |_| format!("'tts_providers' values must be gateway or piper, not {providers}"),
+        )?;
+    }
+
+    if let Some(formats) = entries.get("device_formats") {
+        let formats: HashMap<String, DeviceFormat> = serde_json::from_value(formats.clone())
+            .map_err(|e| format!("'device_formats' is invalid: {e}"))?;
+        for format in formats.values() {
+            if let Some(sample_format) = &format.sample_format {
+                audio::parse_sample_format(sample_format)?;
+            }
+            if format.buffer_size == Some(0) {
+                return Err("'device_formats' buffer sizes must be at least 1 frame".to_string());
+            }
+        }
+    }
+
+    if let Some(labels) = entries.get("device_labels") {
+        serde_json::from_value::<HashMap<String, DeviceLabel>>(labels.clone())
+            .map_err(|e| format!("'device_labels' is invalid: {e}"))?;
+    }
+
+    if let Some(chains) = entries.get("effect_chains") {
+        let chains: HashMap<String, Vec<EffectStage>> = serde_json::from_value(chains.clone())
+            .map_err(|e| format!("'effect_chains' is invalid: {e}"))?;
+        effects::validate(&chains)?;
+    }
+
+    if let Some(port) = entries.get("remote_control_port").and_then(Value::as_u64) {
+        if port == 0 || port > u64::from(u16::MAX) {
+            return Err(format!(
+                "'remote_control_port' must be between 1 and 65535, not {port}"
+            ));
+        }
+    }
+
+    if let Some(sources) = entries.get("countdown_sources") {
+        serde_json::from_value::<Vec<TriggerSource>>(sources.clone()).map_err(|_| {
+            format!(
+                "'countdown_sources' entries must be popup, hotkey, wakeWord, tray, schedule, \
+                 remote or launch, not {sources}"
+            )
+        })?;
+    }
+
+    if let Some(stages) = entries.get("preprocessing") {
+        serde_json::from_value::<Vec<PreprocessStage>>(stages.clone()).map_err(|_| {
+            format!("'preprocessing' stages must be highpass, denoise, agc or trim, not {stages}")
+        })?;
+    }
+
+    if let Some(layout) = entries.get("keyboard_layout").filter(|v| !v.is_null()) {
+        serde_json::from_value::<KeyboardLayout>(layout.clone()).map_err(|_| {
+            format!("'keyboard_layout' must be qwerty, azerty, qwertz or dvorak, not {layout}")
+        })?;
+    }
+
+    if let Some(schedules) = entries.get("scheduled_recordings") {
+        let schedules: Vec<ScheduledRecording> = serde_json::from_value(schedules.clone())
+            .map_err(|e| format!("'scheduled_recordings' is invalid: {e}"))?;
+        schedule::validate(&schedules)?;
+    }
+
+    if let Some(list) = entries.get("macros") {
+        let list: Vec<Macro> = serde_json::from_value(list.clone())
+            .map_err(|e| format!("'macros' is invalid: {e}"))?;
+        macros::validate(&list)?;
+    }
+
+    if let Some(hooks) = entries.get("webhooks") {
+        let hooks: Vec<Webhook> = serde_json::from_value(hooks.clone())
+            .map_err(|e| format!("'webhooks' is invalid: {e}"))?;
+        webhooks::validate(&hooks)?;
+    }
+
+    if let Some(config) = entries.get("wake_word").filter(|v| !v.is_null()) {
+        let config: WakeWord = serde_json::from_value(config.clone())
+            .map_err(|e| format!("'wake_word' is invalid: {e}"))?;
+        wake_word::validate(&config)?;
+    }
+
+    if let Some(routes) = entries.get("response_routes") {
+        let routes: Vec<ResponseRoute> = serde_json::from_value(routes.clone())
+            .map_err(|e| format!("'response_routes' is invalid: {e}"))?;
+        response_routes::validate(&routes)?;
+    }
+
+    if let Some(cues) = entries.get("sound_cues") {
+        serde_json::from_value::<SoundCues>(cues.clone())
+            .map_err(|e| format!("'sound_cues' is invalid: {e}"))?;
+    }
+
+    if let Some(filters) = entries.get("event_filters") {
+        let filters: EventFilters = serde_json::from_value(filters.clone())
+            .map_err(|e| format!("'event_filters' is invalid: {e}"))?;
+        events::validate_filters(&filters)?;
+    }
+
+    if let Some(host) = entries.get("audio_host").and_then(|v| v.as_str()) {
+        if !host.is_empty() && audio::find_host(host).is_none() {
+            let hosts: Vec<_> = audio::list_audio_hosts(None)
+                .into_iter()
+                .map(|h| h.name)
+                .collect();
+            return Err(format!("'audio_host' must be one of {}", hosts.join(", ")));
+        }
+    }
+
+    if entries.get("input_channel").and_then(|v| v.as_u64()) == Some(0) {
+        return Err("'input_channel' counts from 1".to_string());
+    }
+
+    if let Some(hotkey) = entries.get("push_to_talk_hotkey").and_then(|v| v.as_str()) {
+        if !hotkey.is_empty() {
+            hotkey::parse_hotkey(hotkey)?;
+        }
+    }
+
+    let template = entries
+        .get("endpoint_template")
+        .and_then(|v| v.as_str())
+        .unwrap_or(routing::DEFAULT_TEMPLATE);
+    let overrides: HashMap<String, String> = entries
+        .get("endpoint_overrides")
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default();
+    Routes::new(template, &overrides)?;
+
+    Ok(())
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Poll the settings file and pick up edits made outside the app.
+/// A change is only read once its mtime has been stable for a full poll,
+/// so editors that write in several steps aren't caught half-way.
+pub async fn watch_settings(app: AppHandle) {
+    let path = match resolve_store_path(&app, persist::STORE_FILE) {
+        Ok(path) => path,
+        Err(e) => {
+            eprintln!("Settings watcher disabled: {e}");
+            return;
+        }
+    };
+
+    let mut seen = modified(&path);
+    let mut pending = None;
+    loop {
+        tokio::time::sleep(POLL_INTERVAL).await;
+
+        let current = modified(&path);
+        if current == seen || current.is_none() {
+            pending = None;
+            continue;
+        }
+        if pending != current {
+            pending = current;
+            continue;
+        }
+        seen = current;
+        pending = None;
+
+        if let Err(error) = reload_external(&app, &path) {
+            eprintln!("Ignoring external settings edit: {error}");
+            let payload = SettingsRejected { error };
+            events::record_state(&app, "settings-rejected", &payload);
+            let _ = app.emit("settings-rejected", &payload);
+        }
+    }
+}
+
+/// Load the file into the store and apply it, unless it matches what the store already holds
+/// (which is the case for the app's own saves)
+fn reload_external(app: &AppHandle, path: &Path) -> Result<(), String> {
+    let bytes = fs::read(path).map_err(|e| format!("Failed to read settings: {e}"))?;
+    let entries: HashMap<String, Value> = serde_json::from_slice(&bytes)
+        .map_err(|e| format!("Settings file is not valid JSON: {e}"))?;
+    validate(&entries)?;
+
+    let store = persist::open_store(app)?;
+    let cached: HashMap<String, Value> = store.entries().into_iter().collect();
+    if cached == entries {
+        return Ok(());
+    }
+
+    let changed: BTreeSet<&String> = entries
+        .keys()
+        .chain(cached.keys())
+        .filter(|key| entries.get(*key) != cached.get(*key))
+        .collect();
+
+    store
+        .reload_ignore_defaults()
+        .map_err(|e| format!("Failed to reload settings: {e}"))?;
+    let settings = settings::load_settings(app)?;
+    settings::apply_settings(app, &settings);
+
+    events::record_state(
+        app,
+        "settings-changed",
+        serde_json::json!({ "keys": changed }),
+    );
+    app.emit("settings-changed", &settings)
+        .map_err(|e| format!("Failed to emit event: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn entries(value: Value) -> HashMap<String, Value> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_valid_settings_pass() {
+        let settings = entries(json!({
+            "gateway_url": "https://gateway.local/voice-client",
+            "push_to_talk_hotkey": "Control+Space",
+            "trigger_cooldown_ms": 250,
+            "pause_on_lock": false,
+            "last_session": { "sessionId": "abc" }
+        }));
+        assert!(validate(&settings).is_ok());
+    }
+
+    #[test]
+    fn test_wrong_type_rejected() {
+        let settings = entries(json!({ "idle_pause_secs": "ten minutes" }));
+        let err = validate(&settings).unwrap_err();
+        assert!(err.contains("idle_pause_secs"));
+    }
+
+    #[test]
+    fn test_non_http_gateway_rejected() {
+        let settings = entries(json!({ "gateway_url": "ftp://gateway.local" }));
+        assert!(validate(&settings).is_err());
+    }
+
+    #[test]
+    fn test_bad_hotkey_rejected() {
+        let settings = entries(json!({ "push_to_talk_hotkey": "Control+Nonsense" }));
+        assert!(validate(&settings).is_err());
+    }
+}
@@ -0,0 +1,88 @@
+/// Software that produced the recording (standard INFO id)
+pub const SOFTWARE: [u8; 4] = *b"ISFT";
+/// Free-form comment (standard INFO id); holds the `key=value; ...` recording details
+pub const COMMENT: [u8; 4] = *b"ICMT";
+
+/// `OpenClaw Voice Client <version>`, for the ISFT field
+pub fn software() -> String {
+    format!("OpenClaw Voice Client {}", env!("CARGO_PKG_VERSION"))
+}
+
+/// Append a LIST/INFO chunk to a finished WAV and fix up the RIFF size.
+/// Players and decoders skip chunks they don't know, so the audio is unaffected.
+pub fn append(wav: &mut Vec<u8>, fields: &[([u8; 4], String)]) {
+    if wav.len() < 12 || &wav[0..4] != b"RIFF" || &wav[8..12] != b"WAVE" || fields.is_empty() {
+        return;
+    }
+    // A chunk must start on an even offset
+    if wav.len() % 2 == 1 {
+        wav.push(0);
+    }
+
+    let mut info = b"INFO".to_vec();
+    for (id, value) in fields {
+        // Zero-terminated; a NUL inside the value would cut it short
+        let mut text: Vec<u8> = value.bytes().filter(|&b| b != 0).collect();
+        text.push(0);
+        info.extend_from_slice(id);
+        info.extend_from_slice(&(text.len() as u32).to_le_bytes());
+        info.extend_from_slice(&text);
+        if text.len() % 2 == 1 {
+            info.push(0);
+        }
+    }
+
+    wav.extend_from_slice(b"LIST");
+    wav.extend_from_slice(&(info.len() as u32).to_le_bytes());
+    wav.extend_from_slice(&info);
+    let riff_size = (wav.len() - 8) as u32;
+    wav[4..8].copy_from_slice(&riff_size.to_le_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio;
+
+    /// The INFO fields of `wav`, walking the chunk list the way a reader would
+    fn read(wav: &[u8]) -> Vec<([u8; 4], String)> {
+        let mut fields = Vec::new();
+        let mut offset = 12;
+        while offset + 8 <= wav.len() {
+            let size = u32::from_le_bytes(wav[offset + 4..offset + 8].try_into().unwrap()) as usize;
+            let body = &wav[offset + 8..offset + 8 + size];
+            if &wav[offset..offset + 4] == b"LIST" && &body[..4] == b"INFO" {
+                let mut pos = 4;
+                while pos + 8 <= body.len() {
+                    let id: [u8; 4] = body[pos..pos + 4].try_into().unwrap();
+                    let len =
+                        u32::from_le_bytes(body[pos + 4..pos + 8].try_into().unwrap()) as usize;
+                    let text = &body[pos + 8..pos + 8 + len];
+                    let text = String::from_utf8_lossy(text.strip_suffix(&[0]).unwrap_or(text));
+                    fields.push((id, text.into_owned()));
+                    pos += 8 + len + len % 2;
+                }
+            }
+            offset += 8 + size + size % 2;
+        }
+        fields
+    }
+
+    #[test]
+    fn test_info_chunk_round_trips_and_audio_still_decodes() {
+        let samples = [0.25, -0.5, 0.75];
+        let mut wav = audio::encode_wav(&samples, 16_000).unwrap();
+        let fields = vec![
+            (SOFTWARE, software()),
+            (COMMENT, "gain=+0.0dB; device=USB Mic".to_string()),
+        ];
+        append(&mut wav, &fields);
+
+        assert_eq!(read(&wav), fields);
+        let riff_size = u32::from_le_bytes(wav[4..8].try_into().unwrap()) as usize;
+        assert_eq!(riff_size, wav.len() - 8);
+        let (decoded, rate) = crate::playback::decode_wav_mono(&wav).unwrap();
+        assert_eq!(rate, 16_000);
+        assert_eq!(decoded, samples);
+    }
+}
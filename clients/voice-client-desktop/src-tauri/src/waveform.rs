@@ -0,0 +1,54 @@
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::audio::{self, AudioState};
+use crate::types::WaveformChunk;
+
+/// Audio each peak pair covers; 100 pairs a second scroll smoothly at popup width
+pub const BUCKET_MS: u32 = 10;
+
+/// Ten chunks a second, each about ten pairs
+const INTERVAL: Duration = Duration::from_millis(100);
+
+/// Emit "waveform-chunk" while the recording that just started runs, with the audio
+/// captured since the previous chunk as min/max pairs for the popup's waveform.
+/// A chunk means nothing without the ones before it, so the event log leaves them out.
+pub fn watch(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut position = 0;
+        loop {
+            tokio::time::sleep(INTERVAL).await;
+            let audio = app.state::<AudioState>();
+            if !audio.is_recording.load(Ordering::SeqCst) {
+                return;
+            }
+            let Some(rate) = audio.sample_rate.lock().ok().and_then(|rate| *rate) else {
+                continue;
+            };
+            let bucket_len = (rate * BUCKET_MS / 1000).max(1) as usize;
+            let peaks = match audio.samples.lock() {
+                Ok(samples) => {
+                    // `position` counts from the start of the recording, including
+                    // audio since dropped at the memory cap or sent by dictation
+                    let dropped = audio.dropped.load(Ordering::SeqCst);
+                    let start = position.max(dropped) - dropped;
+                    let fresh = samples.get(start..).unwrap_or_default();
+                    let peaks = audio::peak_pairs(fresh, bucket_len);
+                    position = dropped + start + peaks.len() * bucket_len;
+                    peaks
+                }
+                Err(_) => return,
+            };
+            if !peaks.is_empty() {
+                let chunk = WaveformChunk {
+                    peaks,
+                    bucket_ms: BUCKET_MS,
+                };
+                let _ = app.emit("waveform-chunk", &chunk);
+            }
+        }
+    });
+}
@@ -0,0 +1,286 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use hmac::{Hmac, Mac};
+use reqwest::header::CONTENT_TYPE;
+use reqwest::{Client, StatusCode};
+use sha2::Sha256;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::mpsc::{self, error::TrySendError};
+use url::Url;
+
+use crate::session::SessionState;
+use crate::types::{CompletedTurn, Webhook, WebhookFailed, WebhookPayload};
+use crate::{events, tasks};
+
+const SIGNATURE_HEADER: &str = "X-OpenClaw-Signature";
+/// Same value on every attempt, so a receiver can drop retried duplicates
+const DELIVERY_HEADER: &str = "X-OpenClaw-Delivery";
+const MAX_ATTEMPTS: u32 = 4;
+const FIRST_RETRY: Duration = Duration::from_secs(2);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+/// Turns waiting to be posted; further ones are dropped while the receivers are slow
+const QUEUE_DELIVERIES: usize = 32;
+/// Name of the delivery worker in `get_task_health`
+pub const TASK: &str = "webhooks";
+
+/// A completed turn on its way to the webhooks
+struct Delivery {
+    hooks: Vec<Webhook>,
+    delivery_id: String,
+    body: Vec<u8>,
+}
+
+/// Where completed turns are posted
+pub struct WebhookState {
+    pub hooks: Arc<Mutex<Vec<Webhook>>>,
+    /// Feeds the delivery worker; set once it runs
+    queue: Arc<Mutex<Option<mpsc::Sender<Delivery>>>>,
+}
+
+impl WebhookState {
+    pub fn new() -> Self {
+        Self {
+            hooks: Arc::new(Mutex::new(Vec::new())),
+            queue: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn configure(&self, hooks: Vec<Webhook>) {
+        if let Ok(mut current) = self.hooks.lock() {
+            *current = hooks;
+        }
+    }
+
+    fn enabled(&self) -> Vec<Webhook> {
+        self.hooks
+            .lock()
+            .map(|hooks| hooks.iter().filter(|h| h.enabled).cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Webhook URLs must be absolute http(s) URLs
+pub fn validate(hooks: &[Webhook]) -> Result<(), String> {
+    for hook in hooks {
+        let url = Url::parse(&hook.url)
+            .map_err(|e| format!("Webhook URL '{}' is invalid: {e}", hook.url))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(format!("Webhook URL '{}' must use http or https", hook.url));
+        }
+    }
+    Ok(())
+}
+
+/// `sha256=<hex>` HMAC of the body, as sent in `X-OpenClaw-Signature`
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any length");
+    mac.update(body);
+    let hex: String = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    format!("sha256={hex}")
+}
+
+/// Server errors, rate limiting and timeouts may pass; other client errors won't
+fn retryable(status: StatusCode) -> bool {
+    status.is_server_error()
+        || status == StatusCode::TOO_MANY_REQUESTS
+        || status == StatusCode::REQUEST_TIMEOUT
+}
+
+/// Wait before attempt `attempt` (2, 3, ...): 2 s, 4 s, 8 s
+fn retry_delay(attempt: u32) -> Duration {
+    FIRST_RETRY * 2u32.pow(attempt.saturating_sub(2))
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// POST `body` until it is accepted or the attempts run out; returns the attempts made
+async fn post(
+    client: &Client,
+    hook: &Webhook,
+    delivery: &str,
+    body: &[u8],
+) -> Result<u32, (u32, String)> {
+    let mut attempt = 1;
+    loop {
+        let mut request = client
+            .post(&hook.url)
+            .timeout(REQUEST_TIMEOUT)
+            .header(CONTENT_TYPE, "application/json")
+            .header(DELIVERY_HEADER, delivery)
+            .body(body.to_vec());
+        if let Some(secret) = hook.secret.as_deref().filter(|s| !s.is_empty()) {
+            request = request.header(SIGNATURE_HEADER, sign(secret, body));
+        }
+        let (error, retry) = match request.send().await {
+            Ok(resp) if resp.status().is_success() => return Ok(attempt),
+            Ok(resp) => (format!("HTTP {}", resp.status()), retryable(resp.status())),
+            Err(e) => (e.to_string(), true),
+        };
+        if !retry || attempt >= MAX_ATTEMPTS {
+            return Err((attempt, error));
+        }
+        attempt += 1;
+        tokio::time::sleep(retry_delay(attempt)).await;
+    }
+}
+
+fn failed(app: &AppHandle, failed: WebhookFailed) {
+    events::record_state(app, "webhook-failed", &failed);
+    let _ = app.emit("webhook-failed", &failed);
+}
+
+/// Post queued turns one after another, so slow receivers hold up only this worker
+pub async fn worker(app: AppHandle) {
+    let (tx, mut rx) = mpsc::channel(QUEUE_DELIVERIES);
+    if let Ok(mut queue) = app.state::<WebhookState>().queue.lock() {
+        *queue = Some(tx);
+    }
+    let client = Client::new();
+    while let Some(delivery) = rx.recv().await {
+        for hook in delivery.hooks {
+            match post(&client, &hook, &delivery.delivery_id, &delivery.body).await {
+                Ok(attempts) => events::record_state(
+                    &app,
+                    "webhook-delivered",
+                    serde_json::json!({ "url": hook.url, "attempts": attempts }),
+                ),
+                Err((attempts, error)) => failed(
+                    &app,
+                    WebhookFailed {
+                        url: hook.url,
+                        attempts,
+                        error,
+                    },
+                ),
+            }
+        }
+    }
+}
+
+/// Queue a completed turn of the active session for every enabled webhook
+pub fn deliver(app: &AppHandle, turn: &CompletedTurn) {
+    queue(app, "turn.completed", |payload| WebhookPayload {
+        user_text: turn.user_text.clone(),
+        reply: turn.reply.clone(),
+        completed_at: turn.completed_at.clone(),
+        ..payload
+    });
+}
+
+/// Queue an error the gateway reported for the active session, as `turn.failed`
+pub fn deliver_error(app: &AppHandle, message: &str, timestamp: &str) {
+    queue(app, "turn.failed", |payload| WebhookPayload {
+        completed_at: timestamp.to_string(),
+        error: Some(message.to_string()),
+        ..payload
+    });
+}
+
+/// Queue `event` for every enabled webhook; `fill` adds what the event is about
+fn queue(app: &AppHandle, event: &str, fill: impl FnOnce(WebhookPayload) -> WebhookPayload) {
+    let hooks = app.state::<WebhookState>().enabled();
+    if hooks.is_empty() {
+        return;
+    }
+    let Some(active) = app
+        .try_state::<SessionState>()
+        .and_then(|sessions| sessions.snapshot())
+    else {
+        return;
+    };
+    let sent_at_ms = now_ms();
+    let payload = fill(WebhookPayload {
+        event: event.to_string(),
+        delivery_id: format!("{}-{sent_at_ms}", active.session_id),
+        session_id: active.session_id,
+        profile_name: active.profile_name,
+        user_text: None,
+        reply: String::new(),
+        completed_at: String::new(),
+        error: None,
+        sent_at_ms,
+    });
+    let body = match serde_json::to_vec(&payload) {
+        Ok(body) => body,
+        Err(e) => {
+            eprintln!("Webhook payload not serialized: {e}");
+            return;
+        }
+    };
+
+    let urls: Vec<String> = hooks.iter().map(|hook| hook.url.clone()).collect();
+    let delivery = Delivery {
+        hooks,
+        delivery_id: payload.delivery_id,
+        body,
+    };
+    let sender = app
+        .state::<WebhookState>()
+        .queue
+        .lock()
+        .ok()
+        .and_then(|queue| queue.clone());
+    let error = match sender.map(|tx| tx.try_send(delivery)) {
+        Some(Ok(())) => return,
+        Some(Err(TrySendError::Full(_))) => "Too many deliveries waiting; this one was dropped",
+        Some(Err(TrySendError::Closed(_))) | None => "Webhook delivery isn't running",
+    };
+    tasks::dropped(app, TASK);
+    for url in urls {
+        failed(
+            app,
+            WebhookFailed {
+                url,
+                attempts: 0,
+                error: error.to_string(),
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature_is_hmac_sha256_hex() {
+        assert_eq!(
+            sign("key", b"The quick brown fox jumps over the lazy dog"),
+            "sha256=f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+        );
+    }
+
+    #[test]
+    fn test_retry_policy() {
+        assert!(retryable(StatusCode::BAD_GATEWAY));
+        assert!(retryable(StatusCode::TOO_MANY_REQUESTS));
+        assert!(!retryable(StatusCode::NOT_FOUND));
+        assert!(!retryable(StatusCode::UNAUTHORIZED));
+
+        assert_eq!(retry_delay(2), Duration::from_secs(2));
+        assert_eq!(retry_delay(4), Duration::from_secs(8));
+    }
+
+    #[test]
+    fn test_only_http_urls_are_accepted() {
+        let hook = |url: &str| Webhook {
+            url: url.to_string(),
+            ..Webhook::default()
+        };
+        assert!(validate(&[hook("https://hooks.example.com/voice")]).is_ok());
+        assert!(validate(&[hook("file:///etc/passwd")]).is_err());
+        assert!(validate(&[hook("not a url")]).is_err());
+    }
+}
@@ -0,0 +1,142 @@
+import { getCurrentWebviewWindow } from '@tauri-apps/api/webviewWindow';
+import { marked } from 'marked';
+import * as ipc from './ipc';
+import { AppError, AppSettings, VoiceEvent } from './types';
+marked.setOptions({ breaks: true, gfm: true });
+
+/** Earlier exchanges loaded from the local history when the window opens */
+const BACKLOG = 20;
+
+const appWindow = getCurrentWebviewWindow();
+// Detached windows are labelled "conversation-<sessionId>"
+const sessionId = appWindow.label.replace(/^conversation-/, '');
+
+const exchangeEl = document.getElementById('exchange') as HTMLElement;
+const errorEl = document.getElementById('error') as HTMLDivElement;
+const recordBtn = document.getElementById('record-btn') as HTMLButtonElement;
+
+let settings: AppSettings | null = null;
+let recording = false;
+let processing = false;
+/** Reply being streamed; created by the first delta of each turn */
+let agentDiv: HTMLDivElement | null = null;
+let agentText = '';
+
+function describeError(e: unknown): string {
+  if (e && typeof e === 'object' && 'message' in e) {
+    const err = e as AppError;
+    return err.suggestion ? `${err.message}. ${err.suggestion}` : err.message;
+  }
+  return String(e);
+}
+
+function showError(msg: string | null) {
+  errorEl.hidden = !msg;
+  errorEl.textContent = msg ?? '';
+}
+
+function append(className: string, text: string): HTMLDivElement {
+  const div = document.createElement('div');
+  div.className = className;
+  div.textContent = text;
+  exchangeEl.appendChild(div);
+  exchangeEl.scrollTop = exchangeEl.scrollHeight;
+  return div;
+}
+
+function setProcessing(value: boolean) {
+  processing = value;
+  recordBtn.disabled = value;
+  recordBtn.textContent = value ? '… Waiting' : '● Record';
+}
+
+async function loadBacklog() {
+  try {
+    const first = await ipc.getSessionTranscript({ sessionId, offset: 0, limit: 1 });
+    const page = await ipc.getSessionTranscript({
+      sessionId,
+      offset: Math.max(first.total - BACKLOG, 0),
+      limit: BACKLOG,
+    });
+    for (const entry of page.entries) {
+      if (entry.userText) append('exchange-user', entry.userText);
+      append('exchange-assistant', '').innerHTML = marked.parse(entry.reply) as string;
+    }
+  } catch {
+    // Nothing recorded for this session yet
+  }
+}
+
+function handleEvent(payload: VoiceEvent) {
+  switch (payload.type) {
+    case 'user':
+      append('exchange-user', payload.text);
+      break;
+    case 'openclaw':
+      agentText += payload.text;
+      if (!payload.done && agentText) {
+        agentDiv ??= append('exchange-assistant', '');
+        agentDiv.innerHTML = marked.parse(agentText) as string;
+        exchangeEl.scrollTop = exchangeEl.scrollHeight;
+      }
+      break;
+    case 'system':
+      if (payload.status === 'error') showError(payload.message || 'Processing failed');
+      if (payload.status === 'done' || payload.status === 'error' || payload.status === 'empty_transcription') {
+        agentDiv = null;
+        agentText = '';
+        setProcessing(false);
+      }
+      break;
+  }
+}
+
+async function toggleRecording() {
+  if (!settings || processing) return;
+  if (!recording) {
+    try {
+      await ipc.startRecording({ deviceId: settings.microphoneDeviceId || null, source: 'popup' });
+      recording = true;
+      recordBtn.classList.add('recording');
+      recordBtn.textContent = '■ Send';
+      showError(null);
+    } catch (e) {
+      showError('Recording failed: ' + e);
+    }
+    return;
+  }
+
+  recording = false;
+  recordBtn.classList.remove('recording');
+  setProcessing(true);
+  try {
+    await ipc.stopAndSend({
+      baseUrl: settings.gatewayUrl,
+      sessionId,
+      profileName: settings.profileName,
+      sessionKey: settings.sessionKey || null,
+      responseLanguage: settings.responseLanguage || null,
+    });
+  } catch (e) {
+    showError('Processing failed: ' + describeError(e));
+    setProcessing(false);
+  }
+}
+
+document.addEventListener('DOMContentLoaded', async () => {
+  (document.getElementById('session-id') as HTMLDivElement).textContent = sessionId;
+
+  // Window-scoped, so only this session's events arrive here
+  await appWindow.listen<VoiceEvent>('conversation-event', (event) => handleEvent(event.payload));
+  appWindow.listen<AppSettings>('settings-changed', (event) => {
+    settings = event.payload;
+  });
+
+  try {
+    settings = await ipc.loadSettings();
+  } catch (e) {
+    showError('Failed to load settings: ' + e);
+  }
+  await loadBacklog();
+  recordBtn.addEventListener('click', toggleRecording);
+});
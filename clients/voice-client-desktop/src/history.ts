@@ -0,0 +1,112 @@
+import { listen } from '@tauri-apps/api/event';
+import * as ipc from './ipc';
+import { SessionOverview, TranscriptPage } from './types';
+
+const PAGE_SIZE = 50;
+
+document.addEventListener('DOMContentLoaded', () => {
+  const sessionList = document.getElementById('session-list') as HTMLUListElement;
+  const emptyEl = document.getElementById('transcript-empty') as HTMLDivElement;
+  const entriesEl = document.getElementById('transcript-entries') as HTMLDivElement;
+  const loadMoreButton = document.getElementById('load-more') as HTMLButtonElement;
+
+  let selected: string | null = null;
+  // Entries of the selected session shown so far
+  let loaded = 0;
+
+  function formatDuration(secs: number): string {
+    if (secs < 60) return `${secs}s`;
+    const mins = Math.round(secs / 60);
+    return mins < 60 ? `${mins} min` : `${Math.floor(mins / 60)} h ${mins % 60} min`;
+  }
+
+  function renderSessions(sessions: SessionOverview[]) {
+    sessionList.replaceChildren();
+    if (sessions.length === 0) {
+      emptyEl.textContent = 'No conversations yet';
+      return;
+    }
+    for (const session of sessions) {
+      const item = document.createElement('li');
+      item.className = 'session-item';
+      item.classList.toggle('selected', session.sessionId === selected);
+
+      const title = document.createElement('div');
+      title.className = 'session-title';
+      title.textContent = new Date(session.startedAtMs).toLocaleString();
+      const meta = document.createElement('div');
+      meta.className = 'session-meta';
+      const turns = session.turns === 1 ? '1 exchange' : `${session.turns} exchanges`;
+      meta.textContent = `${session.profileName} · ${turns} · ${formatDuration(session.durationSecs)}`;
+
+      item.append(title, meta);
+      item.addEventListener('click', () => selectSession(session.sessionId));
+      sessionList.appendChild(item);
+    }
+  }
+
+  async function loadSessions() {
+    try {
+      renderSessions(await ipc.getSessionsOverview());
+    } catch (e) {
+      emptyEl.textContent = 'Failed to load history: ' + e;
+    }
+  }
+
+  function renderPage(page: TranscriptPage) {
+    for (const entry of page.entries) {
+      const exchange = document.createElement('div');
+      exchange.className = 'history-exchange';
+      if (entry.userText) {
+        const user = document.createElement('div');
+        user.className = 'history-user';
+        user.textContent = entry.userText;
+        exchange.appendChild(user);
+      }
+      const reply = document.createElement('div');
+      reply.className = entry.incomplete ? 'history-reply incomplete' : 'history-reply';
+      reply.textContent = entry.reply;
+      if (entry.incomplete) reply.title = 'The reply was cut off before it finished';
+      const time = document.createElement('div');
+      time.className = 'history-time';
+      time.textContent = new Date(entry.recordedAtMs).toLocaleTimeString();
+      exchange.append(reply, time);
+      entriesEl.appendChild(exchange);
+    }
+    loaded = page.offset + page.entries.length;
+    loadMoreButton.hidden = loaded >= page.total;
+  }
+
+  async function loadPage(sessionId: string) {
+    try {
+      const page = await ipc.getSessionTranscript({
+        sessionId,
+        offset: loaded,
+        limit: PAGE_SIZE,
+      });
+      // Ignore a page that arrives after another session was picked
+      if (sessionId === selected) renderPage(page);
+    } catch (e) {
+      emptyEl.hidden = false;
+      emptyEl.textContent = 'Failed to load transcript: ' + e;
+    }
+  }
+
+  function selectSession(sessionId: string) {
+    selected = sessionId;
+    loaded = 0;
+    entriesEl.replaceChildren();
+    emptyEl.hidden = true;
+    loadSessions();
+    loadPage(sessionId);
+  }
+
+  loadMoreButton.addEventListener('click', () => {
+    if (selected) loadPage(selected);
+  });
+
+  // New exchanges show up in the list while the window is open
+  listen('turn-completed', () => loadSessions());
+
+  loadSessions();
+});
@@ -0,0 +1,383 @@
+// Generated from the Rust commands by `openclaw-voice client`; don't edit by hand.
+// Regenerate it with `npm run gen:ipc` after changing a command's signature.
+import { invoke } from '@tauri-apps/api/core';
+import type {
+  A11yPrefs,
+  ActiveSession,
+  AppSettings,
+  ArchivedRecording,
+  AudioDevice,
+  AudioHost,
+  CompletedTurn,
+  ConnectionDiagnosis,
+  ConnectionResult,
+  DeviceCapabilities,
+  FailedSend,
+  FeedbackRating,
+  GatewayCapabilities,
+  HistorySearchResult,
+  LearnedCorrection,
+  ListeningPaused,
+  LoggedEvent,
+  MicTestReport,
+  MicUsage,
+  PlaybackStatus,
+  RemotePairing,
+  ScrollDirection,
+  SelfCheckReport,
+  SessionOverview,
+  SessionResponse,
+  StorageUsage,
+  TaskHealth,
+  TranscriptPage,
+  TriggerSource,
+  VoiceprintStatus,
+} from './types';
+
+export function cancelCountdown(): Promise<void> {
+  return invoke<void>('cancel_countdown');
+}
+
+export function cancelFileTranscription(): Promise<void> {
+  return invoke<void>('cancel_file_transcription');
+}
+
+export function clearVoiceprint(): Promise<VoiceprintStatus> {
+  return invoke<VoiceprintStatus>('clear_voiceprint');
+}
+
+/** Rejects with an `AppError` */
+export function createSession(args: {
+  baseUrl: string;
+  profileName: string;
+}): Promise<SessionResponse> {
+  return invoke<SessionResponse>('create_session', args);
+}
+
+export function deleteCorrection(args: { from: string; to: string }): Promise<void> {
+  return invoke<void>('delete_correction', args);
+}
+
+export function deleteRecording(args: { id: string }): Promise<void> {
+  return invoke<void>('delete_recording', args);
+}
+
+export function describeHotkey(args: { hotkey?: string | null } = {}): Promise<string | null> {
+  return invoke<string | null>('describe_hotkey', args);
+}
+
+export function diagnoseConnection(args: {
+  baseUrl: string;
+  endpointTemplate?: string | null;
+  workspace?: string | null;
+}): Promise<ConnectionDiagnosis> {
+  return invoke<ConnectionDiagnosis>('diagnose_connection', args);
+}
+
+export function discardFailed(args: { id: string }): Promise<void> {
+  return invoke<void>('discard_failed', args);
+}
+
+export function enqueueAudio(args: { wavBytes: number[] }): Promise<PlaybackStatus> {
+  return invoke<PlaybackStatus>('enqueue_audio', args);
+}
+
+export function finishVoiceprintEnrollment(): Promise<VoiceprintStatus> {
+  return invoke<VoiceprintStatus>('finish_voiceprint_enrollment');
+}
+
+export function focusPopup(): Promise<void> {
+  return invoke<void>('focus_popup');
+}
+
+export function getDeviceCapabilities(args: {
+  deviceId?: string | null;
+} = {}): Promise<DeviceCapabilities> {
+  return invoke<DeviceCapabilities>('get_device_capabilities', args);
+}
+
+export function getEventLog(args: { limit?: number | null } = {}): Promise<LoggedEvent[]> {
+  return invoke<LoggedEvent[]>('get_event_log', args);
+}
+
+export function getGatewayCapabilities(): Promise<GatewayCapabilities | null> {
+  return invoke<GatewayCapabilities | null>('get_gateway_capabilities');
+}
+
+export function getLastTurn(): Promise<CompletedTurn | null> {
+  return invoke<CompletedTurn | null>('get_last_turn');
+}
+
+export function getListeningPause(): Promise<ListeningPaused | null> {
+  return invoke<ListeningPaused | null>('get_listening_pause');
+}
+
+export function getMicUsageLog(): Promise<MicUsage[]> {
+  return invoke<MicUsage[]>('get_mic_usage_log');
+}
+
+export function getPlaybackStatus(): Promise<PlaybackStatus> {
+  return invoke<PlaybackStatus>('get_playback_status');
+}
+
+export function getPrivacyMode(): Promise<boolean> {
+  return invoke<boolean>('get_privacy_mode');
+}
+
+export function getRemotePairing(): Promise<RemotePairing> {
+  return invoke<RemotePairing>('get_remote_pairing');
+}
+
+export function getRemoteSession(args: { id: string }): Promise<TranscriptPage> {
+  return invoke<TranscriptPage>('get_remote_session', args);
+}
+
+export function getSessionTranscript(args: {
+  limit?: number | null;
+  offset?: number | null;
+  sessionId: string;
+}): Promise<TranscriptPage> {
+  return invoke<TranscriptPage>('get_session_transcript', args);
+}
+
+export function getSessionsOverview(): Promise<SessionOverview[]> {
+  return invoke<SessionOverview[]>('get_sessions_overview');
+}
+
+export function getStorageUsage(): Promise<StorageUsage> {
+  return invoke<StorageUsage>('get_storage_usage');
+}
+
+export function getSystemA11yPrefs(): Promise<A11yPrefs> {
+  return invoke<A11yPrefs>('get_system_a11y_prefs');
+}
+
+export function getTaskHealth(): Promise<TaskHealth[]> {
+  return invoke<TaskHealth[]>('get_task_health');
+}
+
+export function getUnreadCount(): Promise<number> {
+  return invoke<number>('get_unread_count');
+}
+
+export function getVoiceprintStatus(): Promise<VoiceprintStatus> {
+  return invoke<VoiceprintStatus>('get_voiceprint_status');
+}
+
+export function invokeAction(args: { actionId: string }): Promise<void> {
+  return invoke<void>('invoke_action', args);
+}
+
+export function isDemoMode(): Promise<boolean> {
+  return invoke<boolean>('is_demo_mode');
+}
+
+export function learnCorrection(args: { corrected: string; original: string }): Promise<void> {
+  return invoke<void>('learn_correction', args);
+}
+
+export function listAudioDevices(): Promise<AudioDevice[]> {
+  return invoke<AudioDevice[]>('list_audio_devices');
+}
+
+export function listAudioHosts(): Promise<AudioHost[]> {
+  return invoke<AudioHost[]>('list_audio_hosts');
+}
+
+export function listCorrections(): Promise<LearnedCorrection[]> {
+  return invoke<LearnedCorrection[]>('list_corrections');
+}
+
+export function listFailedSends(): Promise<FailedSend[]> {
+  return invoke<FailedSend[]>('list_failed_sends');
+}
+
+export function listRecordings(): Promise<ArchivedRecording[]> {
+  return invoke<ArchivedRecording[]>('list_recordings');
+}
+
+export function loadSettings(): Promise<AppSettings> {
+  return invoke<AppSettings>('load_settings');
+}
+
+export function markAllRead(): Promise<void> {
+  return invoke<void>('mark_all_read');
+}
+
+export function openConversationWindow(args: { sessionId: string }): Promise<void> {
+  return invoke<void>('open_conversation_window', args);
+}
+
+export function openHistoryWindow(): Promise<void> {
+  return invoke<void>('open_history_window');
+}
+
+export function openSettingsWindow(): Promise<void> {
+  return invoke<void>('open_settings_window');
+}
+
+export function pauseRecording(): Promise<void> {
+  return invoke<void>('pause_recording');
+}
+
+export function playAudio(args: {
+  data?: string | null;
+  url?: string | null;
+} = {}): Promise<PlaybackStatus> {
+  return invoke<PlaybackStatus>('play_audio', args);
+}
+
+export function probeGatewayCapabilities(args: { baseUrl: string }): Promise<GatewayCapabilities> {
+  return invoke<GatewayCapabilities>('probe_gateway_capabilities', args);
+}
+
+export function quitApp(): Promise<void> {
+  return invoke<void>('quit_app');
+}
+
+/** Rejects with an `AppError` */
+export function resendLastAudio(): Promise<void> {
+  return invoke<void>('resend_last_audio');
+}
+
+export function resetRemotePairing(): Promise<RemotePairing> {
+  return invoke<RemotePairing>('reset_remote_pairing');
+}
+
+/** Rejects with an `AppError` */
+export function restoreLastSession(args: { baseUrl: string }): Promise<ActiveSession | null> {
+  return invoke<ActiveSession | null>('restore_last_session', args);
+}
+
+export function resumePlayback(): Promise<PlaybackStatus> {
+  return invoke<PlaybackStatus>('resume_playback');
+}
+
+export function resumeRecording(): Promise<void> {
+  return invoke<void>('resume_recording');
+}
+
+/** Rejects with an `AppError` */
+export function retryFailed(args: { id: string }): Promise<void> {
+  return invoke<void>('retry_failed', args);
+}
+
+export function runMacro(args: { name: string }): Promise<void> {
+  return invoke<void>('run_macro', args);
+}
+
+export function runSelfCheck(): Promise<SelfCheckReport> {
+  return invoke<SelfCheckReport>('run_self_check');
+}
+
+export function saveSettings(args: { settings: AppSettings }): Promise<void> {
+  return invoke<void>('save_settings', args);
+}
+
+export function scrollHistory(args: { direction: ScrollDirection }): Promise<void> {
+  return invoke<void>('scroll_history', args);
+}
+
+export function searchRemoteHistory(args: { query: string }): Promise<HistorySearchResult> {
+  return invoke<HistorySearchResult>('search_remote_history', args);
+}
+
+export function setPlaybackVolume(args: { volume: number }): Promise<PlaybackStatus> {
+  return invoke<PlaybackStatus>('set_playback_volume', args);
+}
+
+export function setPopupPinned(args: { pinned: boolean }): Promise<void> {
+  return invoke<void>('set_popup_pinned', args);
+}
+
+export function setPrivacyMode(args: { enabled: boolean }): Promise<void> {
+  return invoke<void>('set_privacy_mode', args);
+}
+
+export function showMoreOfReply(args: { index: number; turnId: string }): Promise<string | null> {
+  return invoke<string | null>('show_more_of_reply', args);
+}
+
+export function skipPlayback(): Promise<PlaybackStatus> {
+  return invoke<PlaybackStatus>('skip_playback');
+}
+
+export function startDictation(args: {
+  baseUrl: string;
+  deviceId?: string | null;
+  profileName: string;
+  responseLanguage?: string | null;
+  sessionId: string;
+  sessionKey?: string | null;
+}): Promise<void> {
+  return invoke<void>('start_dictation', args);
+}
+
+export function startRecording(args: {
+  channelIndex?: number | null;
+  deviceId?: string | null;
+  source?: TriggerSource | null;
+} = {}): Promise<void> {
+  return invoke<void>('start_recording', args);
+}
+
+export function startVoiceprintEnrollment(args: { deviceId?: string | null } = {}): Promise<void> {
+  return invoke<void>('start_voiceprint_enrollment', args);
+}
+
+export function stopAllPlayback(): Promise<PlaybackStatus> {
+  return invoke<PlaybackStatus>('stop_all_playback');
+}
+
+/** Rejects with an `AppError` */
+export function stopAndSend(args: {
+  baseUrl: string;
+  profileName: string;
+  responseLanguage?: string | null;
+  sessionId: string;
+  sessionKey?: string | null;
+}): Promise<void> {
+  return invoke<void>('stop_and_send', args);
+}
+
+export function stopDictation(): Promise<void> {
+  return invoke<void>('stop_dictation');
+}
+
+export function stopPlayback(): Promise<PlaybackStatus> {
+  return invoke<PlaybackStatus>('stop_playback');
+}
+
+/** Rejects with an `AppError` */
+export function submitFeedback(args: {
+  comment?: string | null;
+  rating: FeedbackRating;
+  turnId: string;
+}): Promise<void> {
+  return invoke<void>('submit_feedback', args);
+}
+
+export function tailHttpLog(args: { lines?: number | null } = {}): Promise<string[]> {
+  return invoke<string[]>('tail_http_log', args);
+}
+
+export function takeLaunchRecording(): Promise<boolean> {
+  return invoke<boolean>('take_launch_recording');
+}
+
+export function testConnection(args: {
+  baseUrl: string;
+  endpointTemplate?: string | null;
+  workspace?: string | null;
+}): Promise<ConnectionResult> {
+  return invoke<ConnectionResult>('test_connection', args);
+}
+
+export function testMicrophone(args: { deviceId?: string | null } = {}): Promise<MicTestReport> {
+  return invoke<MicTestReport>('test_microphone', args);
+}
+
+/** Rejects with an `AppError` */
+export function transcribeFile(args: { path: string }): Promise<void> {
+  return invoke<void>('transcribe_file', args);
+}
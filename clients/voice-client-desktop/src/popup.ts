@@ -1,8 +1,16 @@
-import { invoke } from '@tauri-apps/api/core';
 import { listen, type UnlistenFn } from '@tauri-apps/api/event';
 import { getCurrentWindow } from '@tauri-apps/api/window';
+import { getCurrentWebview } from '@tauri-apps/api/webview';
 import { marked } from 'marked';
-import { AppSettings, SessionResponse, ConnectionResult, VoiceEvent } from './types';
+import * as ipc from './ipc';
+import {
+  AppError, AppSettings, VoiceEvent, PushToTalkState, TriggerSource,
+  TurnContext, KeywordHit, ScheduleEvent, StateRecovered, FileTranscriptionEvent,
+  RecordingCountdown, StorageUsage, AutoStop, WebhookFailed, PrivacyMode, MicLevel,
+  CompletedTurn, FeedbackRating, RecordingPause, FailedSend, CaptureLimitReached, MicrophoneSwitched,
+  MicClipping, ResponseRouteFailed, A11yPrefs, DeviceFallback, WakeWordDetected,
+  DictationUtterance, TranscriptPreview, GatewayCapabilities, WaveformChunk
+} from './types';
 marked.setOptions({ breaks: true, gfm: true });
 
 let settings: AppSettings | null = null;
@@ -13,6 +21,16 @@ let isProcessing = false;
 let error: string | null = null;
 let recordingReady: Promise<void> | null = null;
 let unlisten: UnlistenFn | null = null;
+/** Set by "turn-completed", which arrives just before the reply's final "done" */
+let completedTurnId: string | null = null;
+/** Dictating: the backend records and sends each utterance itself */
+let dictating = false;
+/** Dictated utterances captured but not yet answered; their replies still get shown */
+let dictationPending = 0;
+/** Recordings are answered locally, so no gateway needs to be configured */
+let demoMode = false;
+/** False once the connected gateway turned out to have no feedback endpoint */
+let feedbackSupported = true;
 
 const statusEl = document.getElementById('status') as HTMLElement;
 const micButton = document.getElementById('mic-button') as HTMLButtonElement;
@@ -23,11 +41,53 @@ const newSessionBtn = document.getElementById('new-session-btn') as HTMLButtonEl
 const settingsBtn = document.getElementById('settings-btn') as HTMLButtonElement;
 const quitBtn = document.getElementById('quit-btn') as HTMLButtonElement;
 const minimizeBtn = document.getElementById('minimize-btn') as HTMLButtonElement;
+const pinBtn = document.getElementById('pin-btn') as HTMLButtonElement;
+const detachBtn = document.getElementById('detach-btn') as HTMLButtonElement;
+const contextIndicator = document.getElementById('context-indicator') as HTMLElement;
+const privacyIndicator = document.getElementById('privacy-indicator') as HTMLElement;
+const levelMeter = document.getElementById('level-meter') as HTMLElement;
+const levelFill = document.getElementById('level-fill') as HTMLElement;
+const levelPeak = document.getElementById('level-peak') as HTMLElement;
+const waveformCanvas = document.getElementById('waveform') as HTMLCanvasElement;
+const pauseBtn = document.getElementById('pause-btn') as HTMLButtonElement;
+const noticeEl = document.getElementById('notice') as HTMLElement;
+const interruptedEl = document.getElementById('interrupted') as HTMLElement;
+const interruptedText = document.getElementById('interrupted-text') as HTMLElement;
+const interruptedResend = document.getElementById('interrupted-resend') as HTMLButtonElement;
+const interruptedDiscard = document.getElementById('interrupted-discard') as HTMLButtonElement;
+
+const CONTEXT_HINT = 'The frontmost app, window title, locale and time zone are sent with each recording';
+
+function setContextSharing(enabled: boolean) {
+  contextIndicator.style.display = enabled ? '' : 'none';
+  contextIndicator.title = CONTEXT_HINT;
+}
+
+function showPaused(paused: boolean) {
+  pauseBtn.classList.toggle('paused', paused);
+  pauseBtn.textContent = paused ? '▶' : '⏸';
+  pauseBtn.title = paused ? 'Resume recording' : 'Pause recording';
+  micButton.classList.toggle('paused', paused);
+}
+
+/** The pause button is only there while a recording is open */
+function showPauseButton(visible: boolean) {
+  pauseBtn.style.display = visible ? '' : 'none';
+  if (!visible) showPaused(false);
+}
+
+function showPinned(pinned: boolean) {
+  pinBtn.classList.toggle('pinned', pinned);
+  pinBtn.title = pinned ? 'Unpin into a normal window' : 'Pin on top';
+}
 
 async function loadSettings() {
   try {
-    settings = await invoke<AppSettings>('load_settings');
-    if (settings && settings.gatewayUrl) {
+    settings = await ipc.loadSettings();
+    demoMode = await ipc.isDemoMode();
+    setContextSharing(settings.shareContext ?? false);
+    showPinned(settings.popupPinned ?? true);
+    if (settings && (settings.gatewayUrl || demoMode)) {
       testConnection(settings.gatewayUrl);
     } else {
       showError('Settings not configured');
@@ -46,7 +106,7 @@ function delay(ms: number): Promise<void> {
 
 async function testConnection(baseUrl: string, attempt = 1): Promise<void> {
   try {
-    const result = await invoke<ConnectionResult>('test_connection', { baseUrl });
+    const result = await ipc.testConnection({ baseUrl });
     updateStatus(result.success);
     if (!result.success) {
       if (attempt < MAX_RETRIES) {
@@ -61,6 +121,10 @@ async function testConnection(baseUrl: string, attempt = 1): Promise<void> {
       if (!sessionId) {
         await handleNewSession();
       }
+      // Launched with --start-recording, e.g. from a Stream Deck button
+      if (await ipc.takeLaunchRecording()) {
+        await startRecording('launch');
+      }
     }
   } catch (e) {
     if (attempt < MAX_RETRIES) {
@@ -77,7 +141,7 @@ async function testConnection(baseUrl: string, attempt = 1): Promise<void> {
 function updateStatus(isConnected: boolean) {
   connected = isConnected;
   if (isConnected) {
-    statusEl.textContent = '● Connected';
+    statusEl.textContent = demoMode ? '● Demo' : '● Connected';
     statusEl.classList.remove('disconnected');
     statusEl.classList.add('connected');
     micButton.disabled = false;
@@ -90,13 +154,13 @@ function updateStatus(isConnected: boolean) {
 }
 
 async function handleNewSession() {
-  if (!settings || !settings.gatewayUrl || !settings.profileName) {
+  if (!settings || (!demoMode && (!settings.gatewayUrl || !settings.profileName))) {
     showError('Missing settings (URL or Profile)');
     return;
   }
 
   try {
-    const response = await invoke<SessionResponse>('create_session', {
+    const response = await ipc.createSession({
       baseUrl: settings.gatewayUrl,
       profileName: settings.profileName
     });
@@ -105,16 +169,18 @@ async function handleNewSession() {
     exchangeEl.innerHTML = ''; // Clear history
     clearError();
   } catch (e) {
-    showError('Failed to create session: ' + e);
+    showError('Failed to create session: ' + describeError(e));
   }
 }
 
-async function startRecording() {
-  if (!connected || isProcessing || !settings || !sessionId) return;
+async function startRecording(source: TriggerSource = 'popup') {
+  if (!connected || dictating || isRecording || isProcessing || !settings || !sessionId) return;
   isRecording = true;
   micButton.classList.add('recording');
-  recordingReady = invoke('start_recording', {
-    deviceId: settings.microphoneDeviceId || null
+  showPauseButton(true);
+  recordingReady = ipc.startRecording({
+    deviceId: settings.microphoneDeviceId || null,
+    source
   });
 
   try {
@@ -123,6 +189,7 @@ async function startRecording() {
     isRecording = false;
     recordingReady = null;
     micButton.classList.remove('recording');
+    showPauseButton(false);
     showError('Recording failed: ' + e);
   }
 }
@@ -153,6 +220,7 @@ async function stopAndSend() {
 
   isRecording = false;
   micButton.classList.remove('recording');
+  showPauseButton(false);
   micButton.classList.add('processing');
   isProcessing = true;
 
@@ -199,7 +267,9 @@ async function stopAndSend() {
             if (agentText) {
               agentDiv.innerHTML = marked.parse(agentText) as string;
               if (!agentDiv.parentElement) exchangeEl.appendChild(agentDiv);
+              if (completedTurnId && feedbackSupported) agentDiv.after(feedbackBar(completedTurnId));
             }
+            completedTurnId = null;
             exchangeEl.scrollTop = exchangeEl.scrollHeight;
             resetAfterProcessing();
             break;
@@ -210,6 +280,11 @@ async function stopAndSend() {
               resetAfterProcessing();
             }, 1500);
             break;
+          case 'cached':
+            // Gateway unreachable; the reply that follows is a cached answer
+            agentDiv.classList.add('cached');
+            showNotice('📦 Answered from cache', payload.message || '');
+            break;
           case 'error':
             showError(payload.message || 'Processing failed');
             resetAfterProcessing();
@@ -218,7 +293,10 @@ async function stopAndSend() {
         break;
       }
       case 'user': {
+        // Replaces the local preview, if one came first
+        userDiv.classList.remove('provisional');
         userDiv.textContent = payload.text;
+        makeCorrectable(userDiv);
         exchangeEl.appendChild(userDiv);
         exchangeEl.scrollTop = exchangeEl.scrollHeight;
         break;
@@ -238,41 +316,341 @@ async function stopAndSend() {
       }
     }
   });
+  // A quick local transcript, shown greyed out until the gateway's own arrives
+  const unlistenVoice = unlisten;
+  const unlistenPreview = await listen<TranscriptPreview>('transcript-preview', (event) => {
+    if (event.payload.sessionId !== sessionId || userDiv.textContent) return;
+    userDiv.textContent = event.payload.text;
+    userDiv.classList.add('provisional');
+    exchangeEl.appendChild(userDiv);
+    exchangeEl.scrollTop = exchangeEl.scrollHeight;
+  });
+  unlisten = () => {
+    unlistenVoice();
+    unlistenPreview();
+  };
 
-  invoke('stop_and_send', {
+  ipc.stopAndSend({
     baseUrl: settings.gatewayUrl,
     sessionId: sessionId,
     profileName: settings.profileName,
-    sessionKey: settings.sessionKey || null
+    sessionKey: settings.sessionKey || null,
+    responseLanguage: settings.responseLanguage || null
   }).catch((e) => {
-    showError('Processing failed: ' + e);
+    typingIndicator.remove();
+    // A second press for the same recording; the first one is still handling it
+    if ((e as AppError).kind === 'duplicateUpload') return;
+    // The part of the reply that arrived stays, marked as cut off
+    if ((e as AppError).kind === 'responseTimeout' && agentDiv.parentElement) {
+      agentDiv.classList.add('incomplete');
+    }
+    showError('Processing failed: ' + describeError(e));
     resetAfterProcessing();
   });
 }
 
+/** Start dictation, or stop it; while on, every pause sends what was said before it */
+async function toggleDictation() {
+  if (dictating) {
+    await ipc.stopDictation();
+    return;
+  }
+  if (!connected || isRecording || isProcessing || !settings || !sessionId) return;
+  try {
+    await ipc.startDictation({
+      deviceId: settings.microphoneDeviceId || null,
+      baseUrl: settings.gatewayUrl,
+      sessionId: sessionId,
+      profileName: settings.profileName,
+      sessionKey: settings.sessionKey || null,
+      responseLanguage: settings.responseLanguage || null
+    });
+  } catch (e) {
+    showError('Dictation failed: ' + e);
+  }
+}
+
+/** A dictated exchange, shown whole once its reply is complete */
+function renderDictatedTurn(turn: CompletedTurn) {
+  if (turn.userText) {
+    const userDiv = document.createElement('div');
+    userDiv.className = 'exchange-user';
+    userDiv.textContent = turn.userText;
+    makeCorrectable(userDiv);
+    exchangeEl.appendChild(userDiv);
+  }
+  if (turn.reply) {
+    const agentDiv = document.createElement('div');
+    agentDiv.className = 'exchange-assistant';
+    agentDiv.innerHTML = marked.parse(turn.reply) as string;
+    exchangeEl.appendChild(agentDiv);
+    if (feedbackSupported) agentDiv.after(feedbackBar(turn.turnId));
+  }
+  exchangeEl.scrollTop = exchangeEl.scrollHeight;
+}
+
+/** Double-clicking a transcript edits it in place; the fix is learned for later transcripts */
+function makeCorrectable(el: HTMLElement) {
+  el.title = 'Double-click to correct';
+  el.ondblclick = () => {
+    if (el.isContentEditable) return;
+    const original = el.textContent ?? '';
+    let cancelled = false;
+    el.contentEditable = 'true';
+    el.focus();
+    el.onkeydown = (e) => {
+      if (e.key === 'Enter') {
+        e.preventDefault();
+        el.blur();
+      } else if (e.key === 'Escape') {
+        cancelled = true;
+        el.blur();
+      }
+    };
+    el.onblur = async () => {
+      el.contentEditable = 'false';
+      el.onkeydown = null;
+      el.onblur = null;
+      const corrected = (el.textContent ?? '').trim();
+      if (cancelled || !corrected) {
+        el.textContent = original;
+        return;
+      }
+      if (corrected === original) return;
+      try {
+        await ipc.learnCorrection({ original, corrected });
+      } catch (e) {
+        showError('Correction not learned: ' + describeError(e));
+      }
+    };
+  };
+}
+
+/** Peak pairs on show in the waveform, newest last, one pixel column each */
+let waveformPeaks: [number, number][] = [];
+
+/** Scroll the newest peak pairs in from the right of the waveform */
+function drawWaveform(peaks: [number, number][]) {
+  waveformCanvas.style.display = '';
+  const width = waveformCanvas.clientWidth || 240;
+  if (waveformCanvas.width !== width) waveformCanvas.width = width;
+  waveformPeaks = waveformPeaks.concat(peaks).slice(-width);
+  const ctx = waveformCanvas.getContext('2d');
+  if (!ctx) return;
+  const mid = waveformCanvas.height / 2;
+  ctx.clearRect(0, 0, width, waveformCanvas.height);
+  ctx.fillStyle = getComputedStyle(waveformCanvas).color;
+  const offset = width - waveformPeaks.length;
+  waveformPeaks.forEach(([low, high], i) => {
+    const top = mid - Math.min(1, high) * mid;
+    const bottom = mid - Math.max(-1, low) * mid;
+    ctx.fillRect(offset + i, top, 1, Math.max(1, bottom - top));
+  });
+}
+
+function clearWaveform() {
+  waveformPeaks = [];
+  waveformCanvas.style.display = 'none';
+}
+
+function applyCapabilities(capabilities: GatewayCapabilities | null) {
+  feedbackSupported = capabilities?.feedback !== 'unsupported';
+  document.querySelectorAll<HTMLElement>('.exchange-feedback').forEach((bar) => {
+    bar.style.display = feedbackSupported ? '' : 'none';
+  });
+}
+
+/** Thumbs up/down under a reply; after rating, an optional comment is sent with Enter */
+function feedbackBar(turnId: string): HTMLElement {
+  const bar = document.createElement('div');
+  bar.className = 'exchange-feedback';
+  const comment = document.createElement('input');
+  comment.className = 'feedback-comment';
+  comment.style.display = 'none';
+  let rating: FeedbackRating | null = null;
+
+  const send = async (text: string | null) => {
+    if (!rating) return;
+    try {
+      await ipc.submitFeedback({ turnId, rating, comment: text });
+    } catch (e) {
+      showError('Feedback failed: ' + describeError(e));
+    }
+  };
+  const button = (value: FeedbackRating, label: string, title: string) => {
+    const el = document.createElement('button');
+    el.className = 'feedback-button';
+    el.textContent = label;
+    el.title = title;
+    el.addEventListener('click', () => {
+      rating = value;
+      bar.querySelectorAll('.feedback-button').forEach((b) => b.classList.remove('chosen'));
+      el.classList.add('chosen');
+      comment.style.display = '';
+      comment.placeholder = value === 'up'
+        ? 'Anything to add? (Enter to send)'
+        : 'What was wrong? (Enter to send)';
+      send(null);
+    });
+    return el;
+  };
+  comment.addEventListener('keydown', (e) => {
+    if (e.key === 'Enter' && comment.value.trim()) {
+      send(comment.value);
+      comment.style.display = 'none';
+    }
+  });
+  bar.append(button('up', '👍', 'Good answer'), button('down', '👎', 'Bad answer'), comment);
+  return bar;
+}
+
+/** Commands return either a plain string or a classified AppError */
+function describeError(e: unknown): string {
+  if (e && typeof e === 'object' && 'message' in e) {
+    const err = e as AppError;
+    return err.suggestion ? `${err.message}. ${err.suggestion}` : err.message;
+  }
+  return String(e);
+}
+
 function showError(msg: string) {
   error = msg;
   errorEl.textContent = msg;
   errorEl.style.display = 'block';
 }
 
+let noticeTimer: ReturnType<typeof setTimeout> | null = null;
+
+/** Transient banner for keyword alerts and schedule notices; `detail` becomes its tooltip */
+function showNotice(text: string, detail = '') {
+  noticeEl.textContent = text;
+  noticeEl.title = detail;
+  noticeEl.style.display = 'block';
+  if (noticeTimer) clearTimeout(noticeTimer);
+  noticeTimer = setTimeout(() => {
+    noticeEl.style.display = 'none';
+  }, 8000);
+}
+
+/** Recordings the app closed on mid-send last time, offered for resending one at a time */
+let interruptedSend: FailedSend | null = null;
+
+function showInterruptedSends(sends: FailedSend[]) {
+  interruptedSend = sends.find((send) => send.interrupted) ?? null;
+  if (!interruptedSend) {
+    interruptedEl.style.display = 'none';
+    return;
+  }
+  const foundAt = new Date(interruptedSend.failedAtMs).toLocaleString();
+  interruptedText.textContent = `⚠ ${interruptedSend.error}`;
+  interruptedText.title = `Recording by ${interruptedSend.profileName}, found ${foundAt}`;
+  interruptedEl.style.display = 'block';
+}
+
+/** Transcript of the file being transcribed, shown in the history as chunks arrive */
+let fileEntry: { el: HTMLElement; title: HTMLElement; body: HTMLElement; name: string } | null = null;
+
+function renderFileTranscription(event: FileTranscriptionEvent) {
+  switch (event.phase) {
+    case 'started': {
+      const el = document.createElement('div');
+      el.className = 'exchange-file';
+      const title = document.createElement('div');
+      title.className = 'exchange-file-title';
+      const cancel = document.createElement('button');
+      cancel.className = 'exchange-file-cancel';
+      cancel.textContent = 'Cancel';
+      cancel.addEventListener('click', () => ipc.cancelFileTranscription());
+      const body = document.createElement('div');
+      el.append(title, cancel, body);
+      exchangeEl.appendChild(el);
+      fileEntry = { el, title, body, name: event.file };
+      title.textContent = `📄 ${event.file} — 0/${event.chunks}`;
+      break;
+    }
+    case 'chunk':
+      if (!fileEntry) return;
+      fileEntry.body.textContent = `${fileEntry.body.textContent ?? ''} ${event.text}`.trim();
+      fileEntry.title.textContent = `📄 ${fileEntry.name} — ${event.index + 1}/${event.chunks}`;
+      break;
+    case 'finished':
+    case 'cancelled':
+    case 'failed':
+      if (!fileEntry) return;
+      fileEntry.title.textContent = event.phase === 'finished'
+        ? `📄 ${fileEntry.name}`
+        : `📄 ${fileEntry.name} (${event.phase})`;
+      fileEntry.el.querySelector('.exchange-file-cancel')?.remove();
+      if (event.phase === 'failed') showError(event.message);
+      fileEntry = null;
+      break;
+  }
+  exchangeEl.scrollTop = exchangeEl.scrollHeight;
+}
+
+function showKeywordAlert(hit: KeywordHit) {
+  const text = hit.kind === 'actionItem'
+    ? `🔔 Action item: “${hit.term}”`
+    : `🔔 Heard “${hit.term}”`;
+  showNotice(text, hit.text);
+}
+
 function clearError() {
   error = null;
   errorEl.style.display = 'none';
 }
 
 // Event Listeners
+/** Follow the OS reduced-motion and high-contrast settings */
+function applyA11yPrefs(prefs: A11yPrefs) {
+  document.body.classList.toggle('reduced-motion', prefs.reducedMotion);
+  document.body.classList.toggle('high-contrast', prefs.highContrast);
+}
+
 document.addEventListener('DOMContentLoaded', () => {
   loadSettings();
 
+  ipc.getSystemA11yPrefs().then(applyA11yPrefs).catch(() => {});
+  listen<A11yPrefs>('a11y-prefs-changed', (event) => applyA11yPrefs(event.payload));
+
+  ipc.listFailedSends().then(showInterruptedSends).catch(() => {});
+  listen<FailedSend[]>('failed-sends-changed', (event) => showInterruptedSends(event.payload));
+  interruptedResend.addEventListener('click', () => {
+    if (!interruptedSend) return;
+    interruptedEl.style.display = 'none';
+    ipc.retryFailed({ id: interruptedSend.id })
+      .catch((e) => showError('Resend failed: ' + describeError(e)));
+  });
+  interruptedDiscard.addEventListener('click', () => {
+    if (!interruptedSend) return;
+    ipc.discardFailed({ id: interruptedSend.id })
+      .catch((e) => showError('Failed to discard recording: ' + e));
+  });
+
   newSessionBtn.addEventListener('click', handleNewSession);
   
   settingsBtn.addEventListener('click', () => {
-    invoke('open_settings_window');
+    ipc.openSettingsWindow();
   });
 
   quitBtn.addEventListener('click', () => {
-    invoke('quit_app');
+    ipc.quitApp();
+  });
+
+  pinBtn.addEventListener('click', () => {
+    const pinned = !(settings?.popupPinned ?? true);
+    ipc.setPopupPinned({ pinned }).catch((e) => showError('Failed to change window mode: ' + e));
+  });
+
+  // The detached window takes over the session's events; the popup moves on to a new one
+  detachBtn.addEventListener('click', async () => {
+    if (!sessionId || isRecording || isProcessing) return;
+    try {
+      await ipc.openConversationWindow({ sessionId });
+      await handleNewSession();
+    } catch (e) {
+      showError('Failed to detach conversation: ' + e);
+    }
   });
 
   minimizeBtn.addEventListener('click', () => {
@@ -280,11 +658,290 @@ document.addEventListener('DOMContentLoaded', () => {
   });
 
   // Mic button interactions
-  micButton.addEventListener('mousedown', startRecording);
+  // While dictating, the mic button ends it
+  micButton.addEventListener('mousedown', () => (dictating ? toggleDictation() : startRecording()));
   micButton.addEventListener('mouseup', stopAndSend);
   micButton.addEventListener('mouseleave', () => {
     if (isRecording) {
       stopAndSend();
     }
   });
+
+  // Global push-to-talk hotkey (registered by the backend)
+  listen<PushToTalkState>('push-to-talk', (event) => {
+    if (event.payload === 'pressed') {
+      startRecording('hotkey');
+    } else {
+      stopAndSend();
+    }
+  });
+
+  // A paired phone's push-to-talk button (remote control)
+  listen<PushToTalkState>('remote-push-to-talk', (event) => {
+    if (event.payload === 'pressed') {
+      startRecording('remote');
+    } else if (isRecording) {
+      stopAndSend();
+    }
+  });
+
+  // Pausing keeps what was said so far; the recording carries on after resuming
+  pauseBtn.addEventListener('click', () => {
+    const toggle = pauseBtn.classList.contains('paused') ? ipc.resumeRecording : ipc.pauseRecording;
+    toggle().catch((e) => showError('Failed to pause recording: ' + e));
+  });
+  listen<RecordingPause>('recording-pause', (event) => showPaused(event.payload.paused));
+
+  // Hands-free: the backend heard the speaker go quiet
+  listen<AutoStop>('auto-stop', () => {
+    if (isRecording) stopAndSend();
+  });
+
+  // The configured microphone was unplugged or plugged back in
+  listen<MicrophoneSwitched>('microphone-switched', (event) => {
+    const { deviceId, available } = event.payload;
+    showNotice(
+      available ? '🎙 Microphone is back' : '🎙 Microphone unplugged — recording from another one',
+      deviceId
+    );
+  });
+
+  // Keyboard actions and macro steps that need the popup's session or recording,
+  // queued so a new session exists before a recording that follows it starts
+  let popupActions = Promise.resolve();
+  listen<string>('popup-action', (event) => {
+    popupActions = popupActions.then(async () => {
+      if (event.payload === 'new-session') {
+        await handleNewSession();
+      } else if (event.payload === 'toggle-recording') {
+        await (isRecording ? stopAndSend() : startRecording());
+      } else if (event.payload === 'toggle-dictation') {
+        await toggleDictation();
+      } else if (event.payload === 'resend-last') {
+        await ipc.resendLastAudio().catch((e) => showError('Resend failed: ' + describeError(e)));
+      }
+    });
+  });
+
+  listen<string>('macro-failed', (event) => showNotice('⚠ Macro failed', event.payload));
+
+  // Hands-free: the wake word starts a recording, which auto-stop can end
+  listen<WakeWordDetected>('wake-word', async (event) => {
+    const { keyword, autoRecord } = event.payload;
+    if (!autoRecord) {
+      showNotice(`👂 Heard “${keyword}”`);
+      return;
+    }
+    await startRecording('wakeWord');
+    if (isRecording) showNotice(`● Listening after “${keyword}”`);
+  });
+  listen<string>('wake-word-error', (event) => showNotice('⚠ Wake word unavailable', event.payload));
+
+  // This recording came from a stand-in for the microphone asked for
+  listen<DeviceFallback>('device-fallback', (event) => {
+    const { usedDeviceName, usedDeviceId, requested, reason } = event.payload;
+    const used = usedDeviceName ?? usedDeviceId ?? 'the default microphone';
+    showNotice(`🎙 Recording from ${used}`, `${requested}: ${reason}`);
+  });
+
+  // A recording that ran into the memory cap
+  listen<CaptureLimitReached>('capture-limit-reached', (event) => {
+    const { maxMb, overflow } = event.payload;
+    if (overflow === 'dropOldest') {
+      showNotice(`⚠ Recording is over ${maxMb} MB — only the latest audio is kept`);
+      return;
+    }
+    if (!isRecording) return;
+    showError(`Recording stopped at the ${maxMb} MB memory limit; what fit is being sent`);
+    stopAndSend();
+  });
+
+  // Live input level while recording; the meter spans -60 dBFS to full scale
+  const meterPercent = (db: number) => Math.min(100, Math.max(0, (db + 60) / 60 * 100));
+  listen<MicLevel>('mic-level', (event) => {
+    const { rmsDb, peakDb } = event.payload;
+    if (peakDb <= -100) {
+      levelMeter.style.display = 'none';
+      // The closing silent reading also ends the recording's waveform
+      if (!isRecording) clearWaveform();
+      return;
+    }
+    levelMeter.style.display = '';
+    levelFill.style.width = `${meterPercent(rmsDb)}%`;
+    levelPeak.style.left = `${meterPercent(peakDb)}%`;
+    levelMeter.classList.toggle('clipping', peakDb > -1);
+  });
+
+  listen<WaveformChunk>('waveform-chunk', (event) => drawWaveform(event.payload.peaks));
+
+  // Clipped audio transcribes badly, so say so before it is sent
+  listen<MicClipping>('mic-clipping', (event) => {
+    const percent = event.payload.percent;
+    showNotice(
+      `⚠ Input is clipping (${percent < 1 ? percent.toFixed(1) : Math.round(percent)}%) — lower the microphone gain`,
+      'Samples hit full scale, which garbles speech for transcription'
+    );
+  });
+
+  // Only this popup's session gets rating buttons; detached windows have their own
+  listen<CompletedTurn>('turn-completed', (event) => {
+    if (sessionId && event.payload.turnId.startsWith(`${sessionId}/`)) {
+      completedTurnId = event.payload.turnId;
+      if (dictating || dictationPending > 0) renderDictatedTurn(event.payload);
+    }
+  });
+
+  // Dictation: the backend cuts utterances at pauses and sends each one
+  listen<boolean>('dictation-changed', (event) => {
+    dictating = event.payload;
+    micButton.classList.toggle('recording', dictating);
+    micButton.classList.toggle('dictating', dictating);
+    showNotice(dictating ? '● Dictating — pause to send what you said' : 'Dictation stopped');
+  });
+  listen<DictationUtterance>('dictation-utterance', (event) => {
+    const utterance = event.payload;
+    if (utterance.phase === 'captured') {
+      dictationPending++;
+      return;
+    }
+    dictationPending = Math.max(0, dictationPending - 1);
+    if (utterance.phase === 'failed') {
+      showError(`Dictated part ${utterance.index} not sent: ${utterance.message}`);
+    }
+  });
+
+  // Privacy mode is switched from the tray; the indicator turns it back off
+  const showPrivacy = (enabled: boolean) => {
+    privacyIndicator.style.display = enabled ? '' : 'none';
+  };
+  ipc.getPrivacyMode().then(showPrivacy).catch(() => {});
+  listen<PrivacyMode>('privacy-mode', (event) => showPrivacy(event.payload.enabled));
+  privacyIndicator.addEventListener('click', () => {
+    ipc.setPrivacyMode({ enabled: false });
+  });
+
+  // Hide what the connected gateway has no endpoint for
+  ipc.getGatewayCapabilities().then(applyCapabilities).catch(() => {});
+  listen<GatewayCapabilities>('gateway-capabilities', (event) => applyCapabilities(event.payload));
+
+  // Tray quick mode: record with the popup hidden, send on the next tray click
+  listen<PushToTalkState>('quick-talk', async (event) => {
+    if (event.payload === 'pressed') {
+      await startRecording('tray');
+      // Couldn't record (e.g. not connected) — show the popup so the reason is visible
+      if (!isRecording) getCurrentWindow().show();
+    } else {
+      stopAndSend();
+    }
+  });
+
+  listen<KeywordHit>('keyword-hit', (event) => showKeywordAlert(event.payload));
+
+  // Dropped audio files are transcribed locally, chunk by chunk
+  listen<FileTranscriptionEvent>('file-transcription', (event) => renderFileTranscription(event.payload));
+  getCurrentWebview().onDragDropEvent((event) => {
+    if (event.payload.type !== 'drop') return;
+    const path = event.payload.paths.find((p) => p.toLowerCase().endsWith('.wav'));
+    if (!path) {
+      showError('Only WAV files can be transcribed');
+      return;
+    }
+    ipc.transcribeFile({ path }).catch((e) => showError('Transcription failed: ' + describeError(e)));
+  });
+
+  // Scheduled recordings: the backend keeps time, the popup records and sends.
+  // Only a recording the schedule started is stopped by it.
+  let scheduledName: string | null = null;
+  listen<ScheduleEvent>('scheduled-recording', async (event) => {
+    const schedule = event.payload;
+    switch (schedule.phase) {
+      case 'upcoming':
+        showNotice(`⏱ “${schedule.name}” recording starts in ${schedule.inSecs} s`);
+        break;
+      case 'start':
+        await startRecording('schedule');
+        if (isRecording) {
+          scheduledName = schedule.name;
+          showNotice(`● Recording “${schedule.name}”`);
+        }
+        break;
+      case 'stop':
+        if (scheduledName === schedule.name && isRecording) stopAndSend();
+        scheduledName = null;
+        break;
+    }
+  });
+
+  // Countdown before a scheduled (or otherwise hands-off) recording; clicking the banner cancels it
+  listen<RecordingCountdown>('recording-countdown', (event) => {
+    const countdown = event.payload;
+    if (countdown.cancelled || countdown.remainingSecs === 0) {
+      noticeEl.onclick = null;
+      showNotice(countdown.cancelled ? 'Recording cancelled' : '● Recording');
+      return;
+    }
+    showNotice(`⏱ Recording in ${countdown.remainingSecs} s`, 'Click to cancel');
+    noticeEl.onclick = () => ipc.cancelCountdown();
+  });
+
+  // The backend watchdog found a stuck state and reset it
+  listen<StateRecovered>('state-recovered', (event) => {
+    if (event.payload.stuck === 'recordingNeverStarted' && isRecording) {
+      isRecording = false;
+      recordingReady = null;
+      micButton.classList.remove('recording');
+      showPauseButton(false);
+      showError('Recording stopped: the microphone never opened');
+    }
+    // A cancelled send rejects stop_and_send, which resets the popup itself
+  });
+
+  // History and failed recordings stop being written when the disk is (nearly) full
+  listen<StorageUsage>('storage-warning', (event) => {
+    switch (event.payload.limit) {
+      case 'lowDiskSpace':
+        showNotice('💾 Disk almost full — history and recordings are no longer saved', event.payload.dataDir);
+        break;
+      case 'historyFull':
+        showNotice('💾 History is full — new exchanges are no longer saved', event.payload.dataDir);
+        break;
+      default:
+        showNotice('💾 Saving history again');
+    }
+  });
+
+  listen<WebhookFailed>('webhook-failed', (event) => {
+    const failed = event.payload;
+    showNotice(`⚠ Webhook not delivered after ${failed.attempts} attempts`, `${failed.url}: ${failed.error}`);
+  });
+
+  listen<ResponseRouteFailed>('response-route-failed', (event) => {
+    const failed = event.payload;
+    showNotice(`⚠ Reply not written to ${failed.path}`, failed.error);
+  });
+
+  listen<boolean>('context-sharing-changed', (event) => setContextSharing(event.payload));
+
+  // Show exactly what went out with the last turn
+  listen<TurnContext>('context-captured', (event) => {
+    const { appName, windowTitle, locale, timeZone } = event.payload;
+    const shared = [appName, windowTitle, locale, timeZone].filter(Boolean).join(' · ');
+    contextIndicator.title = shared ? `Last sent: ${shared}` : CONTEXT_HINT;
+  });
+
+  // settings.json was edited outside the app
+  listen<AppSettings>('settings-changed', async (event) => {
+    const previousUrl = settings?.gatewayUrl;
+    const wasDemo = demoMode;
+    settings = event.payload;
+    demoMode = await ipc.isDemoMode();
+    showPinned(settings.popupPinned ?? true);
+    if (demoMode !== wasDemo) {
+      // A demo session doesn't exist on the gateway, so start over either way
+      sessionId = null;
+      testConnection(settings.gatewayUrl);
+    } else if (settings.gatewayUrl && settings.gatewayUrl !== previousUrl) {
+      testConnection(settings.gatewayUrl);
+    }
+  });
 });
@@ -1,69 +1,322 @@
-import { invoke } from '@tauri-apps/api/core';
-import { AppSettings, AudioDevice, ConnectionResult } from './types';
+import { listen } from '@tauri-apps/api/event';
+import * as ipc from './ipc';
+import {
+  AppSettings, AudioDevice, AudioDevicesChanged, CheckStatus, DeviceCapabilities, HalfDuplex, HotkeyPopup, LearnedCorrection,
+  PopupPosition, PreprocessStage, QualityPreset, RemotePairing, StorageUsage, SttProviderKind, TtsProviderKind, VoiceprintStatus
+} from './types';
 
 document.addEventListener('DOMContentLoaded', () => {
   const gatewayUrlInput = document.getElementById('gateway-url') as HTMLInputElement;
   const tokenInput = document.getElementById('token') as HTMLInputElement;
+  const endpointTemplateInput = document.getElementById('endpoint-template') as HTMLInputElement;
+  const workspaceInput = document.getElementById('workspace') as HTMLInputElement;
   const profileNameInput = document.getElementById('profile-name') as HTMLInputElement;
   const sessionKeyInput = document.getElementById('session-key') as HTMLInputElement;
+  const responseLanguageSelect = document.getElementById('response-language') as HTMLSelectElement;
+  const briefModeInput = document.getElementById('brief-mode') as HTMLInputElement;
+  const briefMaxSentencesInput = document.getElementById('brief-max-sentences') as HTMLInputElement;
+  const audioHostSelect = document.getElementById('audio-host') as HTMLSelectElement;
   const microphoneSelect = document.getElementById('microphone-device') as HTMLSelectElement;
+  const microphoneAliasInput = document.getElementById('microphone-alias') as HTMLInputElement;
+  const microphoneFavoriteInput = document.getElementById('microphone-favorite') as HTMLInputElement;
+  const audioQualitySelect = document.getElementById('audio-quality') as HTMLSelectElement;
+  const autoStopSelect = document.getElementById('auto-stop-silence') as HTMLSelectElement;
+  const sttProviderSelect = document.getElementById('stt-provider') as HTMLSelectElement;
+  const whisperModelInput = document.getElementById('whisper-model') as HTMLInputElement;
+  const whisperPreviewModelInput = document.getElementById('whisper-preview-model') as HTMLInputElement;
+  const preferLocalSttInput = document.getElementById('prefer-local-stt') as HTMLInputElement;
+  const ttsProviderSelect = document.getElementById('tts-provider') as HTMLSelectElement;
+  const piperModelInput = document.getElementById('piper-model') as HTMLInputElement;
   const hotkeyInput = document.getElementById('push-to-talk-hotkey') as HTMLInputElement;
+  const hotkeyPopupSelect = document.getElementById('hotkey-popup') as HTMLSelectElement;
+  const hotkeyPopupPositionSelect = document.getElementById('hotkey-popup-position') as HTMLSelectElement;
+  const httpTraceInput = document.getElementById('http-trace') as HTMLInputElement;
+  const speakerFilterInput = document.getElementById('speaker-filter') as HTMLInputElement;
+  const shareContextInput = document.getElementById('share-context') as HTMLInputElement;
+  const alertKeywordsInput = document.getElementById('alert-keywords') as HTMLInputElement;
+  const alertActionItemsInput = document.getElementById('alert-action-items') as HTMLInputElement;
+  const responseCacheTtlSelect = document.getElementById('response-cache-ttl') as HTMLSelectElement;
+  const answerOfflineInput = document.getElementById('answer-offline-from-cache') as HTMLInputElement;
+  const demoModeInput = document.getElementById('demo-mode') as HTMLInputElement;
+  const archiveRecordingsInput = document.getElementById('archive-recordings') as HTMLInputElement;
+  const streamingUploadInput = document.getElementById('streaming-upload') as HTMLInputElement;
+  const noiseSuppressionInput = document.getElementById('noise-suppression') as HTMLInputElement;
+  const preprocessingInput = document.getElementById('preprocessing') as HTMLInputElement;
+  const trimSilenceInput = document.getElementById('trim-silence') as HTMLInputElement;
+  const speakRepliesInput = document.getElementById('speak-replies') as HTMLInputElement;
+  const cueStartInput = document.getElementById('cue-start') as HTMLInputElement;
+  const cueStopInput = document.getElementById('cue-stop') as HTMLInputElement;
+  const cueErrorInput = document.getElementById('cue-error') as HTMLInputElement;
+  const playbackVolumeInput = document.getElementById('playback-volume') as HTMLInputElement;
+  const halfDuplexSelect = document.getElementById('half-duplex') as HTMLSelectElement;
+  const meetingModeInput = document.getElementById('meeting-mode') as HTMLInputElement;
+  const systemAudioSelect = document.getElementById('system-audio-device') as HTMLSelectElement;
+  const meetingMicGainInput = document.getElementById('meeting-mic-gain') as HTMLInputElement;
+  const meetingSystemGainInput = document.getElementById('meeting-system-gain') as HTMLInputElement;
+  const enrollButton = document.getElementById('enroll-voiceprint') as HTMLButtonElement;
+  const clearVoiceprintButton = document.getElementById('clear-voiceprint') as HTMLButtonElement;
+  const voiceprintStatus = document.getElementById('voiceprint-status') as HTMLDivElement;
+  const testMicrophoneButton = document.getElementById('test-microphone') as HTMLButtonElement;
+  const microphoneTest = document.getElementById('microphone-test') as HTMLDivElement;
+  const inputChannelInput = document.getElementById('input-channel') as HTMLInputElement;
+  const sampleRateSelect = document.getElementById('capture-sample-rate') as HTMLSelectElement;
+  const bufferSizeInput = document.getElementById('capture-buffer-size') as HTMLInputElement;
+  const autocorrectInput = document.getElementById('autocorrect') as HTMLInputElement;
+  const learnedCorrections = document.getElementById('learned-corrections') as HTMLUListElement;
+  const remoteControlInput = document.getElementById('remote-control') as HTMLInputElement;
+  const resetPairingButton = document.getElementById('reset-remote-pairing') as HTMLButtonElement;
+  const remotePairing = document.getElementById('remote-pairing') as HTMLDivElement;
   
   const testButton = document.getElementById('test-connection') as HTMLButtonElement;
   const testResult = document.getElementById('test-result') as HTMLDivElement;
+  const connectionStages = document.getElementById('connection-stages') as HTMLUListElement;
   const saveButton = document.getElementById('save-settings') as HTMLButtonElement;
+  const selfCheckButton = document.getElementById('run-self-check') as HTMLButtonElement;
+  const selfCheckResult = document.getElementById('self-check-result') as HTMLUListElement;
+  const storageUsage = document.getElementById('storage-usage') as HTMLDivElement;
+  const hotkeyDescription = document.getElementById('hotkey-description') as HTMLDivElement;
+
+  // Settings not edited in this window are carried through unchanged on save
+  let loadedSettings: AppSettings | null = null;
 
   function clearStatus() {
     testResult.style.display = 'none';
+    connectionStages.style.display = 'none';
     if (saveButton.textContent === '✓ Saved!') {
       saveButton.textContent = 'Save Settings';
       saveButton.disabled = false;
     }
   }
 
+  // The saved hotkey as printed on this keyboard, since it's stored by physical key
+  async function showHotkey() {
+    const description = await ipc.describeHotkey().catch(() => null);
+    hotkeyDescription.textContent = description ? `On this keyboard: ${description}` : '';
+  }
+  showHotkey();
+
   // Load settings
-  invoke<AppSettings>('load_settings').then((settings) => {
+  ipc.loadSettings().then((settings) => {
+    loadedSettings = settings;
     if (settings.gatewayUrl) gatewayUrlInput.value = settings.gatewayUrl;
     if (settings.token) tokenInput.value = settings.token;
+    if (settings.endpointTemplate) endpointTemplateInput.value = settings.endpointTemplate;
+    if (settings.workspace) workspaceInput.value = settings.workspace;
     if (settings.profileName) profileNameInput.value = settings.profileName;
     if (settings.sessionKey) sessionKeyInput.value = settings.sessionKey;
+    if (settings.responseLanguage) {
+      // Keep hand-edited tags that aren't in the list selectable
+      if (![...responseLanguageSelect.options].some((o) => o.value === settings.responseLanguage)) {
+        const option = document.createElement('option');
+        option.value = settings.responseLanguage;
+        option.text = settings.responseLanguage;
+        responseLanguageSelect.add(option);
+      }
+      responseLanguageSelect.value = settings.responseLanguage;
+    }
+    briefModeInput.checked = settings.briefMode ?? false;
+    briefMaxSentencesInput.value = String(settings.briefMaxSentences ?? 2);
+    briefMaxSentencesInput.disabled = !briefModeInput.checked;
     if (settings.pushToTalkHotkey) hotkeyInput.value = settings.pushToTalkHotkey;
+    hotkeyPopupSelect.value = settings.hotkeyPopup ?? 'trayOnly';
+    hotkeyPopupPositionSelect.value = settings.hotkeyPopupPosition ?? 'trayCenter';
+    hotkeyPopupPositionSelect.disabled = hotkeyPopupSelect.value === 'trayOnly';
+    httpTraceInput.checked = settings.httpTrace ?? false;
+    speakerFilterInput.checked = settings.speakerFilter ?? false;
+    shareContextInput.checked = settings.shareContext ?? false;
+    alertKeywordsInput.value = (settings.alertKeywords ?? []).join(', ');
+    alertActionItemsInput.checked = settings.alertActionItems ?? false;
+    responseCacheTtlSelect.value = String(settings.responseCacheTtlSecs ?? 0);
+    // A TTL set by hand in settings.json gets its own entry so saving keeps it
+    if (responseCacheTtlSelect.selectedIndex === -1) {
+      const ttl = String(settings.responseCacheTtlSecs);
+      responseCacheTtlSelect.add(new Option(`${ttl} s`, ttl));
+      responseCacheTtlSelect.value = ttl;
+    }
+    answerOfflineInput.checked = settings.answerOfflineFromCache ?? false;
+    demoModeInput.checked = settings.demoMode ?? false;
+    archiveRecordingsInput.checked = settings.archiveRecordings ?? false;
+    streamingUploadInput.checked = settings.streamingUpload ?? false;
+    noiseSuppressionInput.checked = settings.noiseSuppression ?? false;
+    trimSilenceInput.checked = settings.trimSilence ?? true;
+    preprocessingInput.value = (settings.preprocessing ?? ['highpass', 'trim', 'denoise']).join(', ');
+    meetingModeInput.checked = settings.meetingMode ?? false;
+    meetingMicGainInput.value = String(settings.meetingMicGainDb ?? 0);
+    meetingSystemGainInput.value = String(settings.meetingSystemGainDb ?? 0);
+    speakRepliesInput.checked = settings.speakReplies ?? true;
+    cueStartInput.checked = settings.soundCues?.start ?? false;
+    cueStopInput.checked = settings.soundCues?.stop ?? false;
+    cueErrorInput.checked = settings.soundCues?.error ?? false;
+    playbackVolumeInput.value = String(Math.round((settings.playbackVolume ?? 1) * 100));
+    halfDuplexSelect.value = settings.halfDuplex ?? 'muteMicrophone';
+    selectedSystemAudio = settings.systemAudioDeviceId ?? '';
+    autocorrectInput.checked = settings.autocorrect ?? true;
+    remoteControlInput.checked = settings.remoteControl ?? false;
+    audioQualitySelect.value = settings.audioQuality ?? 'balanced';
+    autoStopSelect.value = String(settings.autoStopSilenceMs ?? 0);
+    // A silence set by hand in settings.json gets its own entry so saving keeps it
+    if (autoStopSelect.selectedIndex === -1) {
+      const ms = String(settings.autoStopSilenceMs);
+      autoStopSelect.add(new Option(`${ms} ms`, ms));
+      autoStopSelect.value = ms;
+    }
+    sttProviderSelect.value = settings.sttProviders?.[settings.profileName] ?? 'gateway';
+    if (settings.whisperModel) whisperModelInput.value = settings.whisperModel;
+    if (settings.whisperPreviewModel) whisperPreviewModelInput.value = settings.whisperPreviewModel;
+    preferLocalSttInput.checked = settings.preferLocalStt ?? false;
+    ttsProviderSelect.value = settings.ttsProviders?.[settings.profileName] ?? 'gateway';
+    if (settings.piperModel) piperModelInput.value = settings.piperModel;
     
-    const savedMicId = settings.microphoneDeviceId || '';
-    
-    // Load audio devices
-    invoke<AudioDevice[]>('list_audio_devices').then((devices) => {
-      // Clear existing options except the first one
-      while (microphoneSelect.options.length > 1) {
-        microphoneSelect.remove(1);
-      }
+    loadHosts(settings.audioHost ?? '');
+    loadDevices(settings.microphoneDeviceId || '');
+    inputChannelInput.value = settings.inputChannel ? String(settings.inputChannel) : '';
+  });
 
-      devices.forEach((device) => {
-        const option = document.createElement('option');
-        option.value = device.id;
-        option.text = device.name;
-        microphoneSelect.add(option);
+  let audioDevices: AudioDevice[] = [];
+  let selectedSystemAudio = '';
+
+  // Only backends this build supports here are offered; a name set by hand is kept
+  function loadHosts(selected: string) {
+    ipc.listAudioHosts().then((hosts) => {
+      hosts.forEach((host) => {
+        const name = host.isDefault ? `${host.name} (default)` : host.name;
+        audioHostSelect.add(new Option(name, host.name));
       });
+      const match = hosts.find((host) => host.name.toLowerCase() === selected.toLowerCase());
+      if (selected && !match) {
+        audioHostSelect.add(new Option(`${selected} (unavailable)`, selected));
+      }
+      audioHostSelect.value = match?.name ?? selected;
+    });
+  }
+
+  function loadDevices(selectedId: string) {
+    ipc.listAudioDevices().then((devices) => showDevices(devices, selectedId));
+  }
+
+  function showDevices(devices: AudioDevice[], selectedId: string) {
+    audioDevices = devices;
+    // Clear existing options except the first one
+    while (microphoneSelect.options.length > 1) {
+      microphoneSelect.remove(1);
+    }
+    while (systemAudioSelect.options.length > 1) {
+      systemAudioSelect.remove(1);
+    }
 
-      // Set selected value
-      microphoneSelect.value = savedMicId;
+    devices.forEach((device) => {
+      const option = document.createElement('option');
+      option.value = device.id;
+      const name = device.alias ? `${device.alias} (${device.name})` : device.name;
+      option.text = device.favorite ? `★ ${name}` : name;
+      // Handy when writing a device_formats override
+      option.title = device.supportedFormats.join('\n');
+      microphoneSelect.add(option);
+      systemAudioSelect.add(new Option(option.text, device.id));
     });
+
+    // An unplugged microphone stays selected, so saving doesn't switch away from it
+    if (selectedId && !devices.some((device) => device.id === selectedId)) {
+      microphoneSelect.add(new Option(`${selectedId} (unplugged)`, selectedId));
+    }
+    microphoneSelect.value = selectedId;
+    // An output device named by hand in settings.json isn't listed as an input
+    if (selectedSystemAudio && !devices.some((device) => device.id === selectedSystemAudio)) {
+      systemAudioSelect.add(new Option(selectedSystemAudio, selectedSystemAudio));
+    }
+    systemAudioSelect.value = selectedSystemAudio;
+    showDeviceLabel();
+    showCaptureFormat();
+  }
+
+  // Headsets plugged in or out while the window is open
+  listen<AudioDevicesChanged>('audio-devices-changed', (event) => {
+    showDevices(event.payload.devices, microphoneSelect.value);
   });
 
-  // Test Connection
+  // The alias and favorite fields edit the selected microphone
+  function showDeviceLabel() {
+    const device = audioDevices.find((d) => d.id === microphoneSelect.value);
+    microphoneAliasInput.disabled = !device;
+    microphoneFavoriteInput.disabled = !device;
+    microphoneAliasInput.value = device?.alias ?? '';
+    microphoneFavoriteInput.checked = device?.favorite ?? false;
+  }
+
+  // Sample rate and buffer size are pinned per device, in device_formats
+  const COMMON_RATES = [8000, 16000, 22050, 24000, 32000, 44100, 48000, 88200, 96000];
+  let capabilities: DeviceCapabilities | null = null;
+  async function showCaptureFormat() {
+    const deviceId = microphoneSelect.value;
+    const found = await ipc.getDeviceCapabilities({ deviceId: deviceId || null }).catch(() => null);
+    // Another microphone was picked while this one was asked about
+    if (microphoneSelect.value !== deviceId) return;
+    capabilities = found;
+    const formats = found?.formats ?? [];
+    const pinned = found?.pinned;
+    while (sampleRateSelect.options.length > 1) {
+      sampleRateSelect.remove(1);
+    }
+    sampleRateSelect.options[0].text = found?.defaultSampleRate
+      ? `Device default (${found.defaultSampleRate} Hz)`
+      : 'Device default';
+    const rates = COMMON_RATES.filter((rate) =>
+      formats.some((f) => f.minSampleRate <= rate && rate <= f.maxSampleRate));
+    if (pinned?.sampleRate && !rates.includes(pinned.sampleRate)) rates.push(pinned.sampleRate);
+    for (const rate of rates) {
+      sampleRateSelect.add(new Option(`${rate} Hz`, String(rate)));
+    }
+    sampleRateSelect.value = pinned?.sampleRate ? String(pinned.sampleRate) : '';
+    const buffers = formats.find((f) => f.minBufferSize != null && f.maxBufferSize != null);
+    bufferSizeInput.placeholder = buffers
+      ? `Driver default (${buffers.minBufferSize}-${buffers.maxBufferSize})`
+      : 'Driver default';
+    bufferSizeInput.value = pinned?.bufferSize ? String(pinned.bufferSize) : '';
+    sampleRateSelect.disabled = !found;
+    bufferSizeInput.disabled = !found;
+  }
+
+  // A checklist row, shared by the connection test and the self-check
+  const CHECK_ICONS = { pass: '✓', warn: '!', fail: '✗', skipped: '–' };
+  function checkRow(status: CheckStatus, label: string, detail?: string | null): HTMLLIElement {
+    const li = document.createElement('li');
+    li.className = status;
+    li.textContent = `${CHECK_ICONS[status]} ${label}`;
+    if (detail) {
+      const span = document.createElement('span');
+      span.className = 'detail';
+      span.textContent = detail;
+      li.appendChild(span);
+    }
+    return li;
+  }
+
+  // Test Connection: each stage is checked on its own so a failure says where it broke
+  const STAGE_LABELS = { dns: 'DNS lookup', tcp: 'TCP connect', tls: 'TLS handshake', auth: 'Credentials', profiles: 'Profiles' };
   testButton.addEventListener('click', async () => {
     testButton.disabled = true;
-    testResult.style.display = 'none';
+    connectionStages.style.display = 'none';
     testResult.className = 'test-result';
     testResult.textContent = 'Testing...';
     testResult.style.display = 'block';
 
     try {
       const baseUrl = gatewayUrlInput.value;
-      const result = await invoke<ConnectionResult>('test_connection', { baseUrl });
-      
-      testResult.className = result.success ? 'test-result success' : 'test-result error';
-      testResult.textContent = result.success ? 'Connection successful!' : (result.error || 'Connection failed');
+      const endpointTemplate = endpointTemplateInput.value || null;
+      const workspace = workspaceInput.value;
+      const diagnosis = await ipc.diagnoseConnection({ baseUrl, endpointTemplate, workspace });
+
+      const failed = diagnosis.stages.find((stage) => stage.status === 'fail');
+      testResult.className = diagnosis.success ? 'test-result success' : 'test-result error';
+      testResult.textContent = failed
+        ? `${STAGE_LABELS[failed.stage]} failed`
+        : 'Connection successful!';
+      connectionStages.innerHTML = '';
+      for (const stage of diagnosis.stages) {
+        const timing = stage.status === 'skipped' ? '' : ` (${stage.durationMs} ms)`;
+        connectionStages.appendChild(checkRow(stage.status, STAGE_LABELS[stage.stage] + timing, stage.detail));
+      }
+      connectionStages.style.display = 'block';
     } catch (error) {
       testResult.className = 'test-result error';
       testResult.textContent = `Error: ${error}`;
@@ -72,23 +325,310 @@ document.addEventListener('DOMContentLoaded', () => {
     }
   });
 
+  // Test Microphone: records from the selected (possibly unsaved) device and plays it back
+  testMicrophoneButton.addEventListener('click', async () => {
+    testMicrophoneButton.disabled = true;
+    microphoneTest.className = 'test-result';
+    microphoneTest.textContent = 'Recording... say something.';
+    microphoneTest.style.display = 'block';
+
+    try {
+      const report = await ipc.testMicrophone({ deviceId: microphoneSelect.value || null });
+      const levels = `peak ${report.peakDbfs.toFixed(1)} dBFS, average ${report.rmsDbfs.toFixed(1)} dBFS`;
+      const device = report.device ? `${report.device}: ` : '';
+      if (report.silence) {
+        microphoneTest.className = 'test-result error';
+        microphoneTest.textContent = `${device}only silence was recorded (${levels}). Check the mute switch and microphone permission.`;
+      } else if (report.clipping) {
+        microphoneTest.className = 'test-result error';
+        microphoneTest.textContent = `${device}too loud, ${report.clippedSamples} samples clipped (${levels}). Turn the input gain down.`;
+      } else {
+        microphoneTest.className = 'test-result success';
+        microphoneTest.textContent = `${device}sounds good (${levels}). Playing it back...`;
+      }
+    } catch (error) {
+      microphoneTest.className = 'test-result error';
+      microphoneTest.textContent = `Error: ${error}`;
+    } finally {
+      testMicrophoneButton.disabled = false;
+    }
+  });
+
+  // Voiceprint enrollment: first click starts recording, second click learns from it
+  let enrolling = false;
+
+  function showVoiceprintStatus(status: VoiceprintStatus) {
+    voiceprintStatus.className = 'test-result success';
+    voiceprintStatus.textContent = status.enrolled
+      ? `Voice enrolled from ${status.voicedSecs.toFixed(1)}s of speech`
+      : 'No voice enrolled';
+    voiceprintStatus.style.display = 'block';
+    clearVoiceprintButton.disabled = !status.enrolled;
+  }
+
+  function showVoiceprintError(error: unknown) {
+    voiceprintStatus.className = 'test-result error';
+    voiceprintStatus.textContent = `Error: ${error}`;
+    voiceprintStatus.style.display = 'block';
+  }
+
+  ipc.getVoiceprintStatus().then(showVoiceprintStatus);
+
+  enrollButton.addEventListener('click', async () => {
+    try {
+      if (!enrolling) {
+        await ipc.startVoiceprintEnrollment({
+          deviceId: microphoneSelect.value || null
+        });
+        enrolling = true;
+        enrollButton.textContent = 'Finish Enrollment';
+        voiceprintStatus.className = 'test-result';
+        voiceprintStatus.textContent = 'Recording... read a few sentences aloud, then click Finish.';
+        voiceprintStatus.style.display = 'block';
+      } else {
+        enrolling = false;
+        enrollButton.textContent = 'Enroll Voice';
+        showVoiceprintStatus(await ipc.finishVoiceprintEnrollment());
+      }
+    } catch (error) {
+      enrolling = false;
+      enrollButton.textContent = 'Enroll Voice';
+      showVoiceprintError(error);
+    }
+  });
+
+  clearVoiceprintButton.addEventListener('click', async () => {
+    try {
+      showVoiceprintStatus(await ipc.clearVoiceprint());
+    } catch (error) {
+      showVoiceprintError(error);
+    }
+  });
+
+  // Learned corrections, each with a button to forget it
+  async function refreshCorrections() {
+    let corrections: LearnedCorrection[];
+    try {
+      corrections = await ipc.listCorrections();
+    } catch (error) {
+      console.error(error);
+      return;
+    }
+    learnedCorrections.innerHTML = '';
+    corrections.forEach((correction) => {
+      const li = document.createElement('li');
+      li.className = correction.active ? 'active' : '';
+      li.textContent = `${correction.from} → ${correction.to} ×${correction.count}`;
+      li.title = correction.active
+        ? 'Applied to new transcripts'
+        : `Not applied yet${correction.reverts ? `; undone ${correction.reverts} times` : ''}`;
+      const remove = document.createElement('button');
+      remove.textContent = '✕';
+      remove.title = 'Forget this correction';
+      remove.addEventListener('click', async () => {
+        try {
+          await ipc.deleteCorrection({ from: correction.from, to: correction.to });
+        } catch (error) {
+          console.error(error);
+        }
+        refreshCorrections();
+      });
+      li.appendChild(remove);
+      learnedCorrections.appendChild(li);
+    });
+    learnedCorrections.style.display = corrections.length ? 'block' : 'none';
+  }
+  refreshCorrections();
+
+  // Remote control: the address holds the pairing token, so it is only shown once enabled
+  function showRemotePairing(pairing: RemotePairing) {
+    remotePairing.className = 'test-result success';
+    remotePairing.textContent = pairing.url
+      ? `Pair by opening ${pairing.url} on the phone`
+      : 'Remote control is off';
+    remotePairing.style.display = 'block';
+    resetPairingButton.disabled = !pairing.enabled;
+  }
+
+  function showRemotePairingError(error: unknown) {
+    remotePairing.className = 'test-result error';
+    remotePairing.textContent = `Error: ${error}`;
+    remotePairing.style.display = 'block';
+  }
+
+  function refreshRemotePairing() {
+    ipc.getRemotePairing()
+      .then(showRemotePairing)
+      .catch(showRemotePairingError);
+  }
+  refreshRemotePairing();
+
+  resetPairingButton.addEventListener('click', async () => {
+    try {
+      showRemotePairing(await ipc.resetRemotePairing());
+    } catch (error) {
+      showRemotePairingError(error);
+    }
+  });
+
+  // Storage usage
+  const megabytes = (bytes: number) => `${(bytes / (1024 * 1024)).toFixed(1)} MB`;
+  function showStorageUsage(usage: StorageUsage) {
+    const parts = [
+      `History ${megabytes(usage.historyBytes)}`,
+      `failed sends ${megabytes(usage.failedSendsBytes)}`,
+      `recordings ${megabytes(usage.recordingsBytes)}`,
+      `sends in progress ${megabytes(usage.pendingSendsBytes)}`,
+      `reply cache ${megabytes(usage.responseCacheBytes)}`
+    ];
+    if (usage.freeBytes != null) parts.push(`${megabytes(usage.freeBytes)} free`);
+    let text = parts.join(' · ');
+    if (usage.limit === 'lowDiskSpace') text += ' — disk almost full, nothing is being saved';
+    if (usage.limit === 'historyFull') text += ' — history limit reached, new exchanges aren\'t saved';
+    storageUsage.textContent = text;
+    storageUsage.title = usage.dataDir;
+  }
+  ipc.getStorageUsage()
+    .then(showStorageUsage)
+    .catch((error) => { storageUsage.textContent = `Unavailable: ${error}`; });
+  listen<StorageUsage>('storage-warning', (event) => showStorageUsage(event.payload));
+
+  // Self-check
+  selfCheckButton.addEventListener('click', async () => {
+    selfCheckButton.disabled = true;
+    selfCheckResult.innerHTML = '<li>Checking...</li>';
+    selfCheckResult.style.display = 'block';
+
+    try {
+      const report = await ipc.runSelfCheck();
+      selfCheckResult.innerHTML = '';
+      report.items.forEach((item) => {
+        selfCheckResult.appendChild(checkRow(item.status, item.label, item.detail));
+      });
+    } catch (error) {
+      selfCheckResult.innerHTML = '';
+      const li = document.createElement('li');
+      li.className = 'fail';
+      li.textContent = `Self-check failed: ${error}`;
+      selfCheckResult.appendChild(li);
+    } finally {
+      selfCheckButton.disabled = false;
+    }
+  });
+
   // Save Settings
   saveButton.addEventListener('click', async () => {
     const originalText = 'Save Settings';
     saveButton.disabled = true;
     saveButton.textContent = 'Saving...';
 
+    // The transcription and voice choices belong to the profile being saved
+    const sttProviders = { ...loadedSettings?.sttProviders };
+    const sttProvider = sttProviderSelect.value as SttProviderKind;
+    if (sttProvider === 'gateway') {
+      delete sttProviders[profileNameInput.value];
+    } else {
+      sttProviders[profileNameInput.value] = sttProvider;
+    }
+    const ttsProviders = { ...loadedSettings?.ttsProviders };
+    const ttsProvider = ttsProviderSelect.value as TtsProviderKind;
+    if (ttsProvider === 'gateway') {
+      delete ttsProviders[profileNameInput.value];
+    } else {
+      ttsProviders[profileNameInput.value] = ttsProvider;
+    }
+
+    // Labels are saved under the selected device's id, which overrides one keyed by name
+    const deviceLabels = { ...loadedSettings?.deviceLabels };
+    const deviceId = microphoneSelect.value;
+    const deviceName = audioDevices.find((d) => d.id === deviceId)?.name ?? '';
+    if (deviceId) {
+      const alias = microphoneAliasInput.value.trim();
+      if (alias || microphoneFavoriteInput.checked || deviceName in deviceLabels) {
+        deviceLabels[deviceId] = { alias: alias || null, favorite: microphoneFavoriteInput.checked };
+      } else {
+        delete deviceLabels[deviceId];
+      }
+    }
+
+    // Kept under the key already pinning the device, its name or else its id
+    const deviceFormats = { ...loadedSettings?.deviceFormats };
+    if (capabilities) {
+      const key = capabilities.name in deviceFormats ? capabilities.name : capabilities.id;
+      const sampleRate = Number(sampleRateSelect.value) || null;
+      const bufferSize = Number(bufferSizeInput.value) > 0 ? Math.floor(Number(bufferSizeInput.value)) : null;
+      const format = { ...deviceFormats[key], sampleRate, bufferSize };
+      if (format.sampleFormat || sampleRate || bufferSize) {
+        deviceFormats[key] = format;
+      } else {
+        delete deviceFormats[key];
+      }
+    }
+
     const settings: AppSettings = {
+      ...loadedSettings,
       gatewayUrl: gatewayUrlInput.value,
       token: tokenInput.value,
+      endpointTemplate: endpointTemplateInput.value,
+      workspace: workspaceInput.value,
       profileName: profileNameInput.value,
       sessionKey: sessionKeyInput.value,
+      responseLanguage: responseLanguageSelect.value,
+      briefMode: briefModeInput.checked,
+      briefMaxSentences: Math.floor(Number(briefMaxSentencesInput.value)) || 2,
       microphoneDeviceId: microphoneSelect.value,
-      pushToTalkHotkey: hotkeyInput.value
+      audioHost: audioHostSelect.value || null,
+      inputChannel: Number(inputChannelInput.value) > 0 ? Math.floor(Number(inputChannelInput.value)) : null,
+      audioQuality: audioQualitySelect.value as QualityPreset,
+      autoStopSilenceMs: Number(autoStopSelect.value),
+      streamingUpload: streamingUploadInput.checked,
+      noiseSuppression: noiseSuppressionInput.checked,
+      trimSilence: trimSilenceInput.checked,
+      preprocessing: preprocessingInput.value
+        .split(',')
+        .map((stage) => stage.trim().toLowerCase())
+        .filter(Boolean) as PreprocessStage[],
+      meetingMode: meetingModeInput.checked,
+      meetingMicGainDb: Number(meetingMicGainInput.value) || 0,
+      meetingSystemGainDb: Number(meetingSystemGainInput.value) || 0,
+      speakReplies: speakRepliesInput.checked,
+      soundCues: {
+        start: cueStartInput.checked,
+        stop: cueStopInput.checked,
+        error: cueErrorInput.checked,
+      },
+      playbackVolume: Number(playbackVolumeInput.value) / 100,
+      halfDuplex: halfDuplexSelect.value as HalfDuplex,
+      systemAudioDeviceId: systemAudioSelect.value || null,
+      autocorrect: autocorrectInput.checked,
+      remoteControl: remoteControlInput.checked,
+      sttProviders,
+      deviceLabels,
+      deviceFormats,
+      whisperModel: whisperModelInput.value || null,
+      whisperPreviewModel: whisperPreviewModelInput.value || null,
+      preferLocalStt: preferLocalSttInput.checked,
+      ttsProviders,
+      piperModel: piperModelInput.value || null,
+      pushToTalkHotkey: hotkeyInput.value,
+      hotkeyPopup: hotkeyPopupSelect.value as HotkeyPopup,
+      hotkeyPopupPosition: hotkeyPopupPositionSelect.value as PopupPosition,
+      httpTrace: httpTraceInput.checked,
+      speakerFilter: speakerFilterInput.checked,
+      shareContext: shareContextInput.checked,
+      alertKeywords: alertKeywordsInput.value.split(',').map((k) => k.trim()).filter(Boolean),
+      alertActionItems: alertActionItemsInput.checked,
+      responseCacheTtlSecs: Number(responseCacheTtlSelect.value),
+      answerOfflineFromCache: answerOfflineInput.checked,
+      demoMode: demoModeInput.checked,
+      archiveRecordings: archiveRecordingsInput.checked
     };
 
     try {
-      await invoke('save_settings', { settings });
+      await ipc.saveSettings({ settings });
+      showHotkey();
+      refreshRemotePairing();
+      loadDevices(deviceId);
       saveButton.textContent = '✓ Saved!';
       setTimeout(() => {
         if (saveButton.textContent === '✓ Saved!') {
@@ -106,10 +646,69 @@ document.addEventListener('DOMContentLoaded', () => {
     }
   });
 
+  // Keep external edits to settings.json from being overwritten by the next save
+  listen<AppSettings>('settings-changed', (event) => {
+    loadedSettings = event.payload;
+  });
+
   // Clear status on change
-  const inputs = [gatewayUrlInput, tokenInput, profileNameInput, sessionKeyInput, hotkeyInput];
+  const inputs = [
+    gatewayUrlInput, tokenInput, endpointTemplateInput, workspaceInput, profileNameInput, sessionKeyInput, hotkeyInput,
+    whisperModelInput, whisperPreviewModelInput, piperModelInput, alertKeywordsInput, microphoneAliasInput, preprocessingInput
+  ];
   inputs.forEach(input => {
     input.addEventListener('input', clearStatus);
   });
   microphoneSelect.addEventListener('change', clearStatus);
+  microphoneSelect.addEventListener('change', showDeviceLabel);
+  microphoneSelect.addEventListener('change', showCaptureFormat);
+  sampleRateSelect.addEventListener('change', clearStatus);
+  bufferSizeInput.addEventListener('input', clearStatus);
+  microphoneFavoriteInput.addEventListener('change', clearStatus);
+  audioQualitySelect.addEventListener('change', clearStatus);
+  autoStopSelect.addEventListener('change', clearStatus);
+  sttProviderSelect.addEventListener('change', clearStatus);
+  ttsProviderSelect.addEventListener('change', clearStatus);
+  responseLanguageSelect.addEventListener('change', clearStatus);
+  briefModeInput.addEventListener('change', () => {
+    briefMaxSentencesInput.disabled = !briefModeInput.checked;
+    clearStatus();
+  });
+  briefMaxSentencesInput.addEventListener('change', clearStatus);
+  httpTraceInput.addEventListener('change', clearStatus);
+  speakerFilterInput.addEventListener('change', clearStatus);
+  shareContextInput.addEventListener('change', clearStatus);
+  alertActionItemsInput.addEventListener('change', clearStatus);
+  responseCacheTtlSelect.addEventListener('change', clearStatus);
+  answerOfflineInput.addEventListener('change', clearStatus);
+  demoModeInput.addEventListener('change', clearStatus);
+  archiveRecordingsInput.addEventListener('change', clearStatus);
+  streamingUploadInput.addEventListener('change', clearStatus);
+  noiseSuppressionInput.addEventListener('change', clearStatus);
+  preferLocalSttInput.addEventListener('change', clearStatus);
+  trimSilenceInput.addEventListener('change', clearStatus);
+  meetingModeInput.addEventListener('change', clearStatus);
+  meetingMicGainInput.addEventListener('change', clearStatus);
+  meetingSystemGainInput.addEventListener('change', clearStatus);
+  speakRepliesInput.addEventListener('change', clearStatus);
+  for (const input of [cueStartInput, cueStopInput, cueErrorInput]) {
+    input.addEventListener('change', clearStatus);
+  }
+  halfDuplexSelect.addEventListener('change', clearStatus);
+  inputChannelInput.addEventListener('change', clearStatus);
+  hotkeyPopupSelect.addEventListener('change', () => {
+    hotkeyPopupPositionSelect.disabled = hotkeyPopupSelect.value === 'trayOnly';
+    clearStatus();
+  });
+  hotkeyPopupPositionSelect.addEventListener('change', clearStatus);
+  // Heard straight away, and saved, so the level can be set while a reply is spoken
+  playbackVolumeInput.addEventListener('change', () => {
+    ipc.setPlaybackVolume({ volume: Number(playbackVolumeInput.value) / 100 }).catch(() => {});
+  });
+  systemAudioSelect.addEventListener('change', () => {
+    selectedSystemAudio = systemAudioSelect.value;
+    clearStatus();
+  });
+  autocorrectInput.addEventListener('change', clearStatus);
+  remoteControlInput.addEventListener('change', clearStatus);
 });
@@ -2,6 +2,82 @@ export interface AudioDevice {
   name: string;
   id: string;
   isDefault: boolean;
+  /** What the driver says it can capture, e.g. "i16 44100-48000 Hz 2 ch" */
+  supportedFormats: string[];
+  /** Name the user gave the device, shown instead of the driver's */
+  alias?: string | null;
+  /** Favorites are listed first */
+  favorite: boolean;
+}
+
+/** An audio backend (ALSA, JACK, WASAPI, ASIO, ...) for the `audioHost` setting */
+export interface AudioHost {
+  name: string;
+  isDefault: boolean;
+  /** The backend recordings use now */
+  selected: boolean;
+}
+
+/** Payload of the "audio-devices-changed" event: input devices were plugged in or removed */
+export interface AudioDevicesChanged {
+  devices: AudioDevice[];
+  /** Names of the devices that appeared */
+  added: string[];
+  /** Names of the devices that went away */
+  removed: string[];
+}
+
+/** Payload of the "microphone-switched" event: the configured microphone is unplugged or back */
+export interface MicrophoneSwitched {
+  deviceId: string;
+  available: boolean;
+}
+
+/** Payload of the "device-fallback" event: the microphone asked for couldn't be opened */
+export interface DeviceFallback {
+  requested: string;
+  /** Recorded from instead; null for the default microphone */
+  usedDeviceId?: string | null;
+  usedDeviceName?: string | null;
+  reason: string;
+}
+
+/** Capture format forced for a device whose driver reports an unusable default */
+export interface DeviceFormat {
+  sampleFormat?: string | null;
+  sampleRate?: number | null;
+  /** Frames the driver delivers per callback; the driver's choice when unset */
+  bufferSize?: number | null;
+}
+
+/** A range of capture configurations an input device supports */
+export interface CaptureFormatRange {
+  sampleFormat: string;
+  channels: number;
+  minSampleRate: number;
+  maxSampleRate: number;
+  /** Buffer sizes the driver accepts, in frames; null when it doesn't say */
+  minBufferSize?: number | null;
+  maxBufferSize?: number | null;
+}
+
+/** What an input device can capture, as get_device_capabilities returns it */
+export interface DeviceCapabilities {
+  /** Key for a device_formats entry pinning this device's format */
+  id: string;
+  name: string;
+  /** The driver's default format, used unless one is pinned */
+  defaultSampleFormat?: string | null;
+  defaultSampleRate?: number | null;
+  formats: CaptureFormatRange[];
+  /** The device_formats entry recordings from this device use, if any */
+  pinned?: DeviceFormat | null;
+}
+
+/** How the user labelled an input device */
+export interface DeviceLabel {
+  alias?: string | null;
+  favorite?: boolean;
 }
 
 export interface AppSettings {
@@ -9,8 +85,598 @@ export interface AppSettings {
   token: string;
   profileName: string;
   sessionKey?: string;
+  responseLanguage?: string;
+  /** Ask for short spoken-style replies, cut off after `briefMaxSentences` sentences */
+  briefMode?: boolean;
+  briefMaxSentences?: number;
+  /** Sent as `X-Workspace` on every request */
+  workspace?: string;
   microphoneDeviceId?: string;
+  /** Audio backend by cpal's name (`JACK`, `ASIO`); the platform default when unset */
+  audioHost?: string | null;
+  /** The one input channel recorded, counting from 1; all channels are mixed when unset */
+  inputChannel?: number | null;
+  /** High-pass cutoff (Hz) applied to the microphone while capturing; 0 is off */
+  captureHighPassHz?: number;
+  /** Stored by physical key (`control+KeyQ`); `describe_hotkey` gives the printed keys */
   pushToTalkHotkey?: string;
+  /** Detected from the OS when unset */
+  keyboardLayout?: KeyboardLayout | null;
+  triggerCooldownMs?: number;
+  idlePauseSecs?: number;
+  pauseOnLock?: boolean;
+  eventLogCapacity?: number;
+  endpointTemplate?: string;
+  endpointOverrides?: Record<string, string>;
+  httpTrace?: boolean;
+  speakerFilter?: boolean;
+  speakerFilterThreshold?: number;
+  shareContext?: boolean;
+  audioQuality?: QualityPreset;
+  /** Speech-to-text provider keyed by profile name; missing profiles use the gateway */
+  sttProviders?: Record<string, SttProviderKind>;
+  whisperCommand?: string | null;
+  whisperModel?: string | null;
+  /** Transcribe locally for every profile; needs whisperModel */
+  preferLocalStt?: boolean;
+  /** Small ggml model for a provisional transcript while the gateway transcribes */
+  whisperPreviewModel?: string | null;
+  /** Local speech keyed by profile name, for messages and cached answers; missing profiles use the gateway */
+  ttsProviders?: Record<string, TtsProviderKind>;
+  piperCommand?: string | null;
+  piperModel?: string | null;
+  alertKeywords?: string[];
+  alertActionItems?: boolean;
+  scheduledRecordings?: ScheduledRecording[];
+  scheduleNoticeSecs?: number;
+  /** Seconds counted down, with beeps, before recording starts; 0 starts at once */
+  countdownSecs?: number;
+  /** Triggers that get the countdown */
+  countdownSources?: TriggerSource[];
+  /** Named sequences of steps, run with `run_macro`, from the tray or by hotkey */
+  macros?: Macro[];
+  /** Porcupine wake-word detection; off when unset */
+  wakeWord?: WakeWord | null;
+  /** How long replies are kept for repeated questions; 0 disables the cache */
+  responseCacheTtlSecs?: number;
+  answerOfflineFromCache?: boolean;
+  /** Canned local replies instead of the gateway; `--demo` turns it on for one run */
+  demoMode?: boolean;
+  /** Minutes before a stuck send is cancelled; 0 waits forever */
+  sendTimeoutMins?: number;
+  responseTimeoutSecs?: number;
+  /** Capture format overrides keyed by device name or id */
+  deviceFormats?: Record<string, DeviceFormat>;
+  /** Aliases and favorites keyed by device id or name */
+  deviceLabels?: Record<string, DeviceLabel>;
+  /** Borderless always-on-top popup; false makes it a normal window */
+  popupPinned?: boolean;
+  /** Stop writing history and saved recordings below this much free disk; 0 disables */
+  storageMinFreeMb?: number;
+  /** Stop writing history once it takes this much space; 0 means no limit */
+  historyMaxMb?: number;
+  /** Keep a copy of every recording sent, with its transcript, in the app data dir */
+  archiveRecordings?: boolean;
+  /** Delete the oldest archived recordings past this many; 0 means no limit */
+  archiveMaxCount?: number;
+  /** Delete archived recordings older than this many days; 0 keeps them */
+  archiveMaxAgeDays?: number;
+  /** Stop recording after this much silence following speech; 0 waits for a manual stop */
+  autoStopSilenceMs?: number;
+  /** Level (dBFS) below which audio counts as silence for auto-stop */
+  vadThresholdDb?: number;
+  /** Pause that ends one utterance and sends it while dictating */
+  dictationPauseMs?: number;
+  calibrateNoiseFloor?: boolean;
+  /** Upload while still recording instead of after the recording stops */
+  streamingUpload?: boolean;
+  /** Stages run over each finished recording, in order */
+  preprocessing?: PreprocessStage[];
+  /** Remove steady background noise (RNNoise) before a recording is sent */
+  noiseSuppression?: boolean;
+  /** Cut silence from the start and end of each recording before it is sent */
+  trimSilence?: boolean;
+  /** Level (dBFS) below which audio counts as silence for trimming */
+  trimThresholdDb?: number;
+  /** Audio kept around the first and last sound when trimming */
+  trimPaddingMs?: number;
+  /** Effects run over each recording of a profile before it is sent, in order */
+  effectChains?: Record<string, EffectStage[]>;
+  webhooks?: Webhook[];
+  /** Files replies from a profile or matching a pattern are written to */
+  responseRoutes?: ResponseRoute[];
+  /** Which kinds of gateway event reach notifications, speech, webhooks and routes */
+  eventFilters?: EventFilters;
+  /** Let a paired phone on the local network act as a push-to-talk button */
+  remoteControl?: boolean;
+  /** Port the remote control WebSocket listens on */
+  remoteControlPort?: number;
+  /** Longest reply segment handed to notifications and speech; 0 keeps replies whole */
+  notificationMaxChars?: number;
+  /** "Show more" on a segmented reply opens the popup instead of the next segment */
+  showMoreOpensPopup?: boolean;
+  /** Most memory a recording's audio may take; 0 means no limit */
+  captureMaxMb?: number;
+  /** What happens to a recording that reaches `captureMaxMb` */
+  captureOverflow?: CaptureOverflow;
+  /** Apply corrections learned from edited transcripts to new ones */
+  autocorrect?: boolean;
+  /** Times the same correction must be made before it is applied */
+  autocorrectMinCount?: number;
+  /** Record what the computer plays along with the microphone */
+  meetingMode?: boolean;
+  /** Default output device when unset */
+  systemAudioDeviceId?: string | null;
+  /** Gain (dB) on the microphone in meeting mode's mix, from -24 to 12 */
+  meetingMicGainDb?: number;
+  /** Gain (dB) on the system audio in meeting mode's mix, from -24 to 12 */
+  meetingSystemGainDb?: number;
+  /** Speak the synthesized audio the gateway sends with replies */
+  speakReplies?: boolean;
+  /** 0.0 (muted) to 1.0 */
+  playbackVolume?: number;
+  /** Beeps when a recording starts, stops or fails, each on or off */
+  soundCues?: SoundCues;
+  /** What keeps a reply spoken while recording out of the recording */
+  halfDuplex?: HalfDuplex;
+  /** Whether the push-to-talk hotkey shows the popup, and when */
+  hotkeyPopup?: HotkeyPopup;
+  /** Where the popup appears when the hotkey shows it */
+  hotkeyPopupPosition?: PopupPosition;
+}
+
+/** `trayOnly` leaves the popup alone and turns the tray icon red while the hotkey is held */
+export type HotkeyPopup = "show" | "trayOnly" | "onReply";
+
+export type PopupPosition = "trayCenter" | "topRight" | "bottomRight" | "center";
+
+/** `muteMicrophone` drops the microphone while a reply is audible; `duckPlayback` turns the reply down */
+export type HalfDuplex = "off" | "muteMicrophone" | "duckPlayback";
+
+/** `dropOldest` keeps only the most recent audio; `stop` sends what fits, with an error */
+export type CaptureOverflow = "dropOldest" | "stop";
+
+/** An audio effect and its parameters */
+export type Effect =
+  | { type: 'highPass'; cutoffHz: number }
+  | { type: 'gate'; thresholdDb: number; holdMs: number }
+  | { type: 'agc'; targetDb: number; maxGainDb: number }
+  | { type: 'denoise' }
+  | { type: 'limiter'; ceilingDb: number };
+
+/** One stage of a profile's effects chain; `enabled` defaults to true */
+export type EffectStage = Effect & { enabled?: boolean };
+
+/** A URL completed turns are posted to */
+export interface Webhook {
+  url: string;
+  /** HMAC-SHA256 signing key for `X-OpenClaw-Signature` */
+  secret?: string | null;
+  enabled: boolean;
+}
+
+/** Writes replies from a profile, or matching a pattern, to a file */
+export interface ResponseRoute {
+  /** Any profile when unset */
+  profile?: string | null;
+  /** Regular expression the reply must match; any reply when unset */
+  pattern?: string | null;
+  /** Under the home directory unless absolute; may use the template's placeholders */
+  path: string;
+  /** `{reply}`, `{user}`, `{profile}`, `{session}`, `{date}`, `{time}` and `{timestamp}` are filled in */
+  template?: string;
+  append?: boolean;
+  enabled?: boolean;
+}
+
+/** What a gateway event is, as far as `eventFilters` are concerned */
+export type EventKind = "transcript" | "partial" | "reply" | "error" | "status";
+
+/** Which kinds of gateway event each consumer outside the popup receives */
+export interface EventFilters {
+  /** Paired remote devices and "reply-segments"; any kind */
+  notifications?: EventKind[];
+  /** Spoken replies and spoken error messages; reply and error only */
+  speech?: EventKind[];
+  /** `turn.completed` for a reply, `turn.failed` for an error; reply and error only */
+  webhooks?: EventKind[];
+  /** Files written by `responseRoutes`; reply only */
+  responseRoutes?: EventKind[];
+}
+
+/** Short beeps on recording transitions; each is off unless turned on */
+export interface SoundCues {
+  /** Rising beep just before the microphone opens */
+  start?: boolean;
+  /** Falling beep when the recording stops to be sent */
+  stop?: boolean;
+  /** Low double beep when a recording can't start or its send fails */
+  error?: boolean;
+}
+
+/** Payload of the "response-route-failed" event: a reply couldn't be written to its file */
+export interface ResponseRouteFailed {
+  path: string;
+  error: string;
+}
+
+/** Payload of the "response-timeout" event: the reply didn't finish in time and its stream was abandoned */
+export interface ResponseTimeout {
+  sessionId: string;
+  userText: string | null;
+  /** What arrived of the reply before it was abandoned; empty if nothing did */
+  partialReply: string;
+  timeoutSecs: number;
+}
+
+/** Payload of the "webhook-failed" event, once every attempt has failed */
+export interface WebhookFailed {
+  url: string;
+  /** 0 when the turn was dropped before any attempt, the delivery queue being full */
+  attempts: number;
+  error: string;
+}
+
+/** A recording started automatically at a set time, e.g. the daily standup */
+export interface ScheduledRecording {
+  name: string;
+  /** Local start time as HH:MM */
+  time: string;
+  /** Weekdays ("mon", "tue", ...); empty means every day */
+  days: string[];
+  durationMins: number;
+  enabled: boolean;
+}
+
+/** A named sequence of steps run together, e.g. "standup" */
+export interface Macro {
+  name: string;
+  /** Global shortcut that runs it, e.g. "Control+Alt+KeyS" */
+  hotkey?: string | null;
+  steps: MacroStep[];
+}
+
+/** One step of a macro; `action` takes an `invoke_action` id such as "new-session" */
+export type MacroStep =
+  | { type: 'switchProfile'; profileName: string }
+  | { type: 'meetingMode'; enabled: boolean }
+  | { type: 'privacyMode'; enabled: boolean }
+  | { type: 'action'; id: string }
+  | { type: 'wait'; ms: number };
+
+/** Wake-word detection with Porcupine, from the user's own Picovoice install */
+export interface WakeWord {
+  enabled: boolean;
+  /** Porcupine's shared library (libpv_porcupine.so, .dylib or .dll) */
+  libraryPath: string;
+  /** Porcupine's model file (porcupine_params.pv) */
+  modelPath: string;
+  /** Keyword files (.ppn); each is reported by the start of its file name */
+  keywordPaths: string[];
+  accessKey: string;
+  /** 0.0 to 1.0; higher misses fewer wake words but fires by mistake more often */
+  sensitivity: number;
+  /** Start recording when a wake word is heard */
+  autoRecord: boolean;
+}
+
+/** Payload of the "wake-word" event */
+export interface WakeWordDetected {
+  keyword: string;
+  /** The popup starts recording in response */
+  autoRecord: boolean;
+}
+
+/** Payload of the "transcript-preview" event: shown until the gateway's transcript arrives */
+export interface TranscriptPreview {
+  sessionId: string;
+  text: string;
+  elapsedMs: number;
+}
+
+/** Payload of the "dictation-utterance" event; `index` counts from 1 */
+export type DictationUtterance =
+  | { phase: "captured"; index: number; durationMs: number }
+  | { phase: "sent"; index: number }
+  | { phase: "failed"; index: number; message: string };
+
+/** Payload of the "scheduled-recording" event */
+export type ScheduleEvent =
+  | { phase: "upcoming"; name: string; inSecs: number }
+  | { phase: "start"; name: string }
+  | { phase: "stop"; name: string };
+
+/** Payload of the "file-transcription" event, streamed while a dropped file is transcribed */
+export type FileTranscriptionEvent =
+  | { phase: "started"; file: string; chunks: number; durationSecs: number }
+  | { phase: "chunk"; index: number; chunks: number; startSecs: number; text: string }
+  | { phase: "finished"; file: string }
+  | { phase: "cancelled"; file: string; done: number }
+  | { phase: "failed"; file: string; message: string };
+
+/** Where a profile's speech is transcribed */
+export type SttProviderKind = "gateway" | "localWhisper";
+
+/** Who speaks a profile's messages and cached answers */
+export type TtsProviderKind = "gateway" | "piper";
+
+/** Recording quality preset */
+export type QualityPreset = "voiceSmall" | "balanced" | "highFidelity";
+
+/** A stage of the `preprocessing` pipeline */
+export type PreprocessStage = "highpass" | "denoise" | "agc" | "trim";
+
+/** Payload of the "turn-completed" event — one exchange with the reply merged from its deltas */
+export interface CompletedTurn {
+  /** Names the turn for submit_feedback: `<session id>/<completedAt>` */
+  turnId: string;
+  userText: string | null;
+  reply: string;
+  completedAt: string;
+}
+
+/** A completed exchange as kept in the local history */
+export interface HistoryEntry {
+  profileName: string;
+  userText: string | null;
+  reply: string;
+  completedAt: string;
+  /** When the app recorded it; 0 for exchanges only the gateway kept */
+  recordedAtMs: number;
+  /** The user's rating of the reply, once they gave one */
+  feedback?: TurnFeedback | null;
+  /** The reply was cut off: it didn't finish within `response_timeout_secs` */
+  incomplete?: boolean;
+}
+
+/** Thumbs up or down on a reply */
+export type FeedbackRating = "up" | "down";
+
+/** A user's rating of a reply, as kept in history */
+export interface TurnFeedback {
+  rating: FeedbackRating;
+  comment: string | null;
+  submittedAtMs: number;
+}
+
+/** A session in the history window's list */
+export interface SessionOverview {
+  sessionId: string;
+  profileName: string;
+  turns: number;
+  startedAtMs: number;
+  lastActivityMs: number;
+  durationSecs: number;
+}
+
+/** One page of a session's transcript, oldest exchange first */
+export interface TranscriptPage {
+  sessionId: string;
+  entries: HistoryEntry[];
+  offset: number;
+  total: number;
+}
+
+/** An exchange matching a history search */
+export interface HistoryMatch {
+  sessionId: string;
+  /** This computer's history, or transcripts the gateway keeps */
+  source: "local" | "remote";
+  entry: HistoryEntry;
+}
+
+/** Local and gateway history matching a search, local matches first */
+export interface HistorySearchResult {
+  query: string;
+  matches: HistoryMatch[];
+  /** Why the gateway's transcripts weren't searched, when they weren't */
+  remoteUnavailable?: string | null;
+}
+
+/** A recording whose upload failed; payload of "failed-sends-changed" is a list of these */
+export interface FailedSend {
+  id: string;
+  baseUrl: string;
+  sessionId: string;
+  profileName: string;
+  sessionKey: string | null;
+  responseLanguage: string | null;
+  bytes: number;
+  failedAtMs: number;
+  error: string;
+  attempts: number;
+  /** Set when the app closed mid-send; how far the send had got */
+  interrupted?: SendStage | null;
+}
+
+/** A sent recording kept in the archive; `id` also names its WAV file */
+export interface ArchivedRecording {
+  id: string;
+  sessionId: string;
+  profileName: string;
+  recordedAtMs: number;
+  durationMs: number;
+  bytes: number;
+  /** What the gateway heard, if its reply finished */
+  transcript?: string | null;
+}
+
+/** How far a recording got on its way to the gateway */
+export type SendStage = "recorded" | "encoding" | "uploading" | "awaitingStream";
+
+/** Payload of the "context-captured" event — what was attached to the last turn */
+export interface TurnContext {
+  appName: string | null;
+  windowTitle: string | null;
+  locale: string | null;
+  timeZone: string | null;
+}
+
+/** Payload of the "keyword-hit" event — a watched term came up in a transcript */
+export interface KeywordHit {
+  term: string;
+  kind: "watched" | "actionItem";
+  text: string;
+  timestamp: string;
+}
+
+/** Payload of the "state-recovered" event — the watchdog reset a stuck state */
+export interface StateRecovered {
+  stuck: "recordingNeverStarted" | "quickTalkWithoutRecording" | "quickTalkWithoutSend" | "sendingTooLong";
+  action: string;
+}
+
+/** Disk use of the app's stores; also the payload of the "storage-warning" event */
+export interface StorageUsage {
+  dataDir: string;
+  historyBytes: number;
+  failedSendsBytes: number;
+  recordingsBytes: number;
+  /** Recordings journaled while they are being sent */
+  pendingSendsBytes: number;
+  responseCacheBytes: number;
+  /** Missing when the platform can't tell */
+  freeBytes?: number | null;
+  minFreeBytes: number;
+  maxHistoryBytes: number;
+  /** Set while history and recordings aren't being written */
+  limit?: "lowDiskSpace" | "historyFull" | null;
+}
+
+export type KeyboardLayout = "qwerty" | "azerty" | "qwertz" | "dvorak";
+
+/** Scroll target for keyboard navigation of the popup history */
+export type ScrollDirection = "up" | "down" | "page-up" | "page-down" | "top" | "bottom";
+
+/** What asked for a recording to start */
+export type TriggerSource = "popup" | "hotkey" | "wakeWord" | "tray" | "schedule" | "remote" | "launch";
+
+/** Payload of the "auto-stop" event: the speaker went quiet */
+export interface AutoStop {
+  silenceMs: number;
+}
+
+/** Payload of the "capture-limit-reached" event: the recording's audio hit `captureMaxMb` */
+export interface CaptureLimitReached {
+  maxMb: number;
+  overflow: CaptureOverflow;
+}
+
+/** Payload of the "mic-level" event: the input level over the last ~50 ms, in dBFS */
+export interface MicLevel {
+  rmsDb: number;
+  peakDb: number;
+}
+
+/** Payload of the "mic-clipping" event: part of the last second's input hit full scale, so the input gain should come down */
+export interface MicClipping {
+  /** Share of the samples at or past full scale, 0–100 */
+  percent: number;
+}
+
+/** Payload of the "waveform-chunk" event: the audio recorded since the last chunk, decimated for a scrolling waveform */
+export interface WaveformChunk {
+  /** `[lowest, highest]` sample of each `bucketMs` of audio, oldest first */
+  peaks: [number, number][];
+  bucketMs: number;
+}
+
+/** Payload of the "recording-pause" event */
+export interface RecordingPause {
+  paused: boolean;
+}
+
+/** Payload of the "reply-segments" event: a finished reply cut to notification size */
+export interface ReplySegments {
+  turnId: string;
+  /** Whole sentences where they fit, of at most `notificationMaxChars` characters */
+  segments: string[];
+  /** "Show more" should open the popup rather than ask `show_more_of_reply` for the next */
+  showMoreOpensPopup: boolean;
+}
+
+/** A correction learned from the user's edits to transcripts */
+export interface LearnedCorrection {
+  /** The misheard words, lowercased without punctuation */
+  from: string;
+  to: string;
+  count: number;
+  /** Times the user undid it after it was applied */
+  reverts: number;
+  /** Applied to new transcripts: made often enough and rarely undone */
+  active: boolean;
+}
+
+/** How a phone connects for remote control */
+export interface RemotePairing {
+  enabled: boolean;
+  /** WebSocket URL with the pairing token; null while disabled */
+  url: string | null;
+}
+
+/** Payload of the "unread-changed" event */
+export interface UnreadCount {
+  /** Answers that finished while the popup was hidden and haven't been seen yet */
+  count: number;
+}
+
+/** Payload of the "privacy-mode" event */
+export interface PrivacyMode {
+  /** Nothing from the conversation is being written to disk or the event log */
+  enabled: boolean;
+}
+
+/** Payload of the "recording-countdown" event, sent once a second before capture starts */
+export interface RecordingCountdown {
+  source: TriggerSource;
+  /** 0 when capture starts */
+  remainingSecs: number;
+  cancelled: boolean;
+}
+
+/** Payload of the "push-to-talk" event */
+export type PushToTalkState = "pressed" | "released";
+
+/** OS accessibility preferences, from `get_system_a11y_prefs` and "a11y-prefs-changed" */
+export interface A11yPrefs {
+  reducedMotion: boolean;
+  highContrast: boolean;
+}
+
+/** Payload of the "listening-paused" event */
+export interface ListeningPaused {
+  reason: "locked" | "idle";
+}
+
+/** One entry of the diagnostics event log */
+export interface LoggedEvent {
+  seq: number;
+  timestampMs: number;
+  kind: "emitted" | "state";
+  name: string;
+  payload: unknown;
+}
+
+/** Voiceprint enrollment state */
+export interface VoiceprintStatus {
+  enrolled: boolean;
+  voicedSecs: number;
+  filterEnabled: boolean;
+}
+
+export type CheckStatus = "pass" | "warn" | "fail" | "skipped";
+
+export interface CheckItem {
+  id: string;
+  label: string;
+  status: CheckStatus;
+  detail?: string;
+}
+
+/** Result of run_self_check */
+export interface SelfCheckReport {
+  ok: boolean;
+  items: CheckItem[];
 }
 
 export interface SessionResponse {
@@ -19,6 +685,15 @@ export interface SessionResponse {
   profileName: string;
 }
 
+/** Session the client is currently using — persisted across restarts */
+export interface ActiveSession {
+  sessionId: string;
+  profileName: string;
+  gatewayUrl: string;
+  /** Most recent user/agent events, oldest first */
+  recent: VoiceEvent[];
+}
+
 export interface TranscriptionResult {
   text: string;
   confidence: number;
@@ -33,13 +708,63 @@ export interface TranscriptionResponse {
   response: AgentResponse;
 }
 
+/** Error returned by gateway-facing commands */
+export interface AppError {
+  kind: string;
+  message: string;
+  suggestion?: string;
+}
+
 export interface ConnectionResult {
   success: boolean;
   error?: string;
 }
 
+/** Whether the gateway serves an optional endpoint */
+export type Support = "unknown" | "supported" | "unsupported";
+
+/** Which optional endpoints the connected gateway serves, probed on connect ("gateway-capabilities") */
+export interface GatewayCapabilities {
+  gatewayUrl: string;
+  probedAtMs: number;
+  historySearch: Support;
+  historySession: Support;
+  feedback: Support;
+}
+
+/** A step of diagnose_connection, in the order they run */
+export type DiagnosticStage = "dns" | "tcp" | "tls" | "auth" | "profiles";
+
+/** How one stage of diagnose_connection went; the stages after a failed one are skipped */
+export interface StageResult {
+  stage: DiagnosticStage;
+  status: CheckStatus;
+  durationMs: number;
+  detail?: string | null;
+}
+
+/** Result of diagnose_connection — `success` is false if any stage failed */
+export interface ConnectionDiagnosis {
+  success: boolean;
+  stages: StageResult[];
+}
+
+/** Result of test_microphone: levels of a short recording, which is then played back */
+export interface MicTestReport {
+  /** The device recorded from, as the driver names it */
+  device: string | null;
+  durationMs: number;
+  peakDbfs: number;
+  rmsDbfs: number;
+  /** Samples at or past full scale */
+  clippedSamples: number;
+  clipping: boolean;
+  /** Nothing above the noise floor was recorded: a muted or blocked microphone */
+  silence: boolean;
+}
+
 /** SSE event types streamed from POST /audio */
-export type VoiceEventType = "user" | "openclaw" | "system";
+export type VoiceEventType = "user" | "openclaw" | "system" | "audio";
 
 export interface VoiceEventBase {
   type: VoiceEventType;
@@ -66,4 +791,57 @@ export interface SystemEvent extends VoiceEventBase {
   message?: string;
 }
 
-export type VoiceEvent = UserEvent | OpenClawEvent | SystemEvent;
+/** Synthesized speech for the reply; the app plays it rather than forwarding it */
+export interface AudioEvent extends VoiceEventBase {
+  type: "audio";
+  /** Base64 WAV */
+  data?: string | null;
+  /** Relative to the gateway URL */
+  url?: string | null;
+}
+
+export type VoiceEvent = UserEvent | OpenClawEvent | SystemEvent | AudioEvent;
+
+/** Payload of "playback-state" and what the playback commands return */
+export interface PlaybackStatus {
+  playing: boolean;
+  paused: boolean;
+  currentClipId: number | null;
+  queued: number;
+  /** 0.0 (muted) to 1.0 */
+  volume: number;
+}
+
+/** What opened the microphone, as the usage log records it */
+export type MicSource =
+  | "recording"
+  | "dictation"
+  | "wakeWord"
+  | "micTest"
+  | "selfCheck"
+  | "voiceprintEnrollment";
+
+/** One stretch of the microphone being open; an entry of `get_mic_usage_log` */
+export interface MicUsage {
+  id: number;
+  source: MicSource;
+  /** What started a recording; null for the other sources */
+  trigger: TriggerSource | null;
+  /** Name of the device opened */
+  device: string | null;
+  startedAtMs: number;
+  /** Null while the microphone is still open, or if the app quit before closing it */
+  stoppedAtMs: number | null;
+}
+
+/** One background task from `get_task_health` */
+export interface TaskHealth {
+  name: string;
+  status: "running" | "restarting" | "stopped" | "failed";
+  /** Times it has been started again since launch */
+  restarts: number;
+  /** The most recent panic */
+  lastError: string | null;
+  /** Work turned away, because its queue was full or it wasn't running */
+  dropped: number;
+}
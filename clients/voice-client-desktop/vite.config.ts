@@ -14,6 +14,8 @@ export default defineConfig({
       input: {
         popup: resolve(__dirname, "src/popup.html"),
         settings: resolve(__dirname, "src/settings.html"),
+        history: resolve(__dirname, "src/history.html"),
+        conversation: resolve(__dirname, "src/conversation.html"),
       },
     },
     outDir: "../dist",
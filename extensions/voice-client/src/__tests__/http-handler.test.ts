@@ -22,7 +22,7 @@ vi.mock("../session-manager.js", () => ({
 
 // We do NOT mock sse.ts — we want the real SSE formatting to verify wire output.
 
-import { VoiceClientHttpServer } from "../http-handler.js";
+import { VoiceClientHttpServer, parseTurnContext, wavInfoComment } from "../http-handler.js";
 import { transcribeAudio } from "../stt-service.js";
 import { generateAgentResponseStreaming } from "../agent-service.js";
 import { createSession, getSession, addMessage, getSessionMessages } from "../session-manager.js";
@@ -216,6 +216,32 @@ describe("VoiceClientHttpServer", () => {
       expect(lastEvent.data.status).toBe("done");
     });
 
+    it("should use a client-side transcript sent as text/plain without calling STT", async () => {
+      mockedGetSession.mockReturnValue({
+        id: "s1",
+        profileName: "Alice",
+        createdAt: new Date(),
+        lastActivity: new Date(),
+        messages: [],
+      });
+      mockedGetSessionMessages.mockReturnValue([]);
+      mockedStreaming.mockImplementation(async (_params, onToken) => {
+        onToken("", true);
+        return { text: "ok" };
+      });
+
+      const res = await request(`${baseUrl}/audio?sessionId=s1`, {
+        method: "POST",
+        headers: { "X-Profile": "Alice", "Content-Type": "text/plain; charset=utf-8" },
+      }, " Turn on the lights \n");
+
+      expect(res.status).toBe(200);
+      expect(mockedTranscribe).not.toHaveBeenCalled();
+      const user = parseSSEEvents(res.body).find((e) => e.event === "user");
+      expect(user?.data.text).toBe("Turn on the lights");
+      expect(user?.data.confidence).toBe(1);
+    });
+
     it("should emit system:empty_transcription and close when text is empty", async () => {
       mockedGetSession.mockReturnValue({
         id: "s1",
@@ -438,6 +464,26 @@ describe("VoiceClientHttpServer", () => {
   });
 });
 
+describe("parseTurnContext", () => {
+  it("should decode form-encoded JSON from the desktop client", () => {
+    const header = encodeURIComponent(
+      JSON.stringify({ appName: "Code", windowTitle: "main.rs — Größe", locale: "de-DE" })
+    ).replace(/%20/g, "+");
+    expect(parseTurnContext(header)).toEqual({
+      appName: "Code",
+      windowTitle: "main.rs — Größe",
+      locale: "de-DE",
+      timeZone: null,
+    });
+  });
+
+  it("should ignore missing or malformed headers", () => {
+    expect(parseTurnContext(undefined)).toBeUndefined();
+    expect(parseTurnContext("%7Bnot-json")).toBeUndefined();
+    expect(parseTurnContext("42")).toBeUndefined();
+  });
+});
+
 // --- SSE parser helper ---
 
 function parseSSEEvents(body: string): Array<{ event: string; data: any }> {
@@ -467,3 +513,26 @@ function parseSSEEvents(body: string): Array<{ event: string; data: any }> {
 
   return events;
 }
+
+describe("wavInfoComment", () => {
+  function chunk(id: string, body: Buffer): Buffer {
+    const header = Buffer.alloc(8);
+    header.write(id, 0, "latin1");
+    header.writeUInt32LE(body.length, 4);
+    return Buffer.concat([header, body, Buffer.alloc(body.length % 2)]);
+  }
+
+  it("should read the ICMT field the desktop client appends", () => {
+    const info = chunk("LIST", Buffer.concat([
+      Buffer.from("INFO"),
+      chunk("ISFT", Buffer.from("OpenClaw Voice Client 1.1.1\0")),
+      chunk("ICMT", Buffer.from("preset=balanced; gain=+0.0dB\0")),
+    ]));
+    const wav = Buffer.concat([Buffer.from("RIFF\0\0\0\0WAVE"), chunk("data", Buffer.alloc(4)), info]);
+    expect(wavInfoComment(wav)).toBe("preset=balanced; gain=+0.0dB");
+  });
+
+  it("should return null for audio without INFO", () => {
+    expect(wavInfoComment(Buffer.alloc(44))).toBeNull();
+  });
+});
@@ -19,6 +19,17 @@ export type AgentResponseParams = {
   transcript: SessionMessage[];
   userMessage: string;
   sessionKey?: string;
+  /** BCP 47 tag the reply should be written in, regardless of the spoken language */
+  responseLanguage?: string;
+  /** What the user was looking at when they spoke (client opt-in) */
+  turnContext?: TurnContext;
+};
+
+export type TurnContext = {
+  appName?: string | null;
+  windowTitle?: string | null;
+  locale?: string | null;
+  timeZone?: string | null;
 };
 
 export type AgentResponseResult = {
@@ -227,11 +238,26 @@ async function prepareAgentCallParams(params: AgentResponseParams) {
   const basePrompt = `You are ${agentName}, a helpful voice assistant. Keep responses brief and conversational (1-2 sentences max). Be natural and friendly. The user is ${profileName}.`;
 
   let extraSystemPrompt = basePrompt;
+  if (params.responseLanguage) {
+    extraSystemPrompt += ` Always reply in the language with BCP 47 tag "${params.responseLanguage}", even if the user speaks another language.`;
+  }
+  const turnContext = params.turnContext;
+  if (turnContext) {
+    const lines = [
+      turnContext.appName && `Frontmost app: ${turnContext.appName}`,
+      turnContext.windowTitle && `Window title: ${turnContext.windowTitle}`,
+      turnContext.locale && `Locale: ${turnContext.locale}`,
+      turnContext.timeZone && `Time zone: ${turnContext.timeZone}`,
+    ].filter(Boolean);
+    if (lines.length > 0) {
+      extraSystemPrompt += `\n\nThe user's current context:\n${lines.join("\n")}`;
+    }
+  }
   if (transcript.length > 0) {
     const history = transcript
       .map((entry) => `${entry.role === "assistant" ? "You" : "User"}: ${entry.content}`)
       .join("\n");
-    extraSystemPrompt = `${basePrompt}\n\nConversation so far:\n${history}`;
+    extraSystemPrompt = `${extraSystemPrompt}\n\nConversation so far:\n${history}`;
   }
 
   const timeoutMs = deps.resolveAgentTimeoutMs({ cfg: coreConfig });
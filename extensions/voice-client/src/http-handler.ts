@@ -13,7 +13,7 @@ import { URL } from "node:url";
 import type { VoiceClientConfig, SessionResponse, VoiceEvent } from "./types.js";
 import { transcribeAudio } from "./stt-service.js";
 import { createSession, getSession, getOrResumeSession, addMessage, getSessionMessages, isSessionPaused } from "./session-manager.js";
-import { generateAgentResponseStreaming } from "./agent-service.js";
+import { generateAgentResponseStreaming, type TurnContext } from "./agent-service.js";
 import { writeSSEHeaders, sendSSE, endSSE } from "./sse.js";
 import type { OpenClawConfig } from "openclaw/plugin-sdk";
 
@@ -23,6 +23,57 @@ function makeTimestamp(): string {
   return new Date().toISOString();
 }
 
+const MAX_CONTEXT_FIELD_LENGTH = 300;
+
+/**
+ * Decode the optional X-Turn-Context header (form-encoded JSON).
+ * Malformed context is ignored rather than failing the turn.
+ */
+export function parseTurnContext(header: string | undefined): TurnContext | undefined {
+  if (!header) return undefined;
+  try {
+    const parsed = JSON.parse(decodeURIComponent(header.replace(/\+/g, " ")));
+    if (typeof parsed !== "object" || parsed === null) return undefined;
+    const field = (value: unknown) =>
+      typeof value === "string" && value ? value.slice(0, MAX_CONTEXT_FIELD_LENGTH) : null;
+    return {
+      appName: field(parsed.appName),
+      windowTitle: field(parsed.windowTitle),
+      locale: field(parsed.locale),
+      timeZone: field(parsed.timeZone),
+    };
+  } catch {
+    return undefined;
+  }
+}
+
+/**
+ * The ICMT comment of a WAV's LIST/INFO chunk, where the desktop client records
+ * its preset, gain, DSP steps and input device. Null when there is none.
+ */
+export function wavInfoComment(wav: Buffer): string | null {
+  if (wav.length < 12 || wav.toString("latin1", 0, 4) !== "RIFF") return null;
+  let offset = 12;
+  while (offset + 8 <= wav.length) {
+    const size = wav.readUInt32LE(offset + 4);
+    const body = offset + 8;
+    if (wav.toString("latin1", offset, offset + 4) === "LIST" &&
+        wav.toString("latin1", body, body + 4) === "INFO") {
+      const end = Math.min(body + size, wav.length);
+      let pos = body + 4;
+      while (pos + 8 <= end) {
+        const length = wav.readUInt32LE(pos + 4);
+        if (wav.toString("latin1", pos, pos + 4) === "ICMT") {
+          return wav.toString("utf-8", pos + 8, Math.min(pos + 8 + length, end)).replace(/\0+$/, "");
+        }
+        pos += 8 + length + (length % 2);
+      }
+    }
+    offset = body + size + (size % 2);
+  }
+  return null;
+}
+
 /**
  * Voice Client HTTP Server
  */
@@ -91,7 +142,7 @@ export class VoiceClientHttpServer {
     // Set CORS headers for development
     res.setHeader("Access-Control-Allow-Origin", "*");
     res.setHeader("Access-Control-Allow-Methods", "GET, POST, OPTIONS");
-    res.setHeader("Access-Control-Allow-Headers", "Content-Type, Authorization, X-Profile, X-Session-Key");
+    res.setHeader("Access-Control-Allow-Headers", "Content-Type, Authorization, X-Profile, X-Session-Key, X-Response-Language, X-Turn-Context");
 
     // Handle OPTIONS preflight
     if (req.method === "OPTIONS") {
@@ -176,18 +227,20 @@ export class VoiceClientHttpServer {
 
       const audioBuffer = Buffer.concat(chunks);
       console.log(`[voice-client] Audio received: ${audioBuffer.length} bytes from ${profileName}`);
+      const recordingInfo = wavInfoComment(audioBuffer);
+      if (recordingInfo) console.log(`[voice-client] Recording: ${recordingInfo}`);
 
       // --- Switch to SSE mode ---
       writeSSEHeaders(res);
       sseStarted = true;
 
-      // Step 1: Transcribe
+      // Step 1: Transcribe — unless the client already did (local STT sends text/plain)
       sendSSE(res, { type: "system", status: "transcribing", timestamp: makeTimestamp() });
 
-      const transcription = await transcribeAudio(
-        { audioBuffer, profileName },
-        this.config.sonioxApiKey
-      );
+      const contentType = String(req.headers["content-type"] || "").toLowerCase();
+      const transcription = contentType.startsWith("text/plain")
+        ? { text: audioBuffer.toString("utf-8").trim(), confidence: 1 }
+        : await transcribeAudio({ audioBuffer, profileName }, this.config.sonioxApiKey);
 
       console.log(`[voice-client] Transcription: "${transcription.text}"`);
 
@@ -223,6 +276,15 @@ export class VoiceClientHttpServer {
         sessionKey = this.config.profiles.sessionKeys[profileName];
       }
 
+      // Only well-formed language tags reach the prompt
+      const headerLanguage = req.headers["x-response-language"] as string | undefined;
+      const responseLanguage =
+        headerLanguage && /^[A-Za-z]{2,3}(-[A-Za-z0-9]{1,8})*$/.test(headerLanguage)
+          ? headerLanguage
+          : undefined;
+
+      const turnContext = parseTurnContext(req.headers["x-turn-context"] as string | undefined);
+
       // Step 4: Typing indicator + agent response
       sendSSE(res, { type: "system", status: "typing", timestamp: makeTimestamp() });
 
@@ -236,6 +298,8 @@ export class VoiceClientHttpServer {
           transcript: getSessionMessages(sessionId),
           userMessage: transcription.text,
           sessionKey,
+          responseLanguage,
+          turnContext,
         },
         (delta, done) => {
           sendSSE(res, {